@@ -0,0 +1,62 @@
+//! Differential execution tests: every snippet is executed both as written and
+//! after a parse + codegen round trip (minified and pretty), and the output of
+//! the JavaScript engine must be identical. This catches cases where codegen
+//! drops parenthesis or reorders things in a way that changes semantics.
+
+use std::process::Command;
+
+use ratel::parse;
+use codegen;
+
+const SNIPPETS: &[&str] = &[
+    "console.log(2 + 2 * 2, (2 + 2) * 2);",
+    "console.log(2 / 2 * 2, 2 / (2 * 2));",
+    "console.log(2 - (3 - 4), 2 - 3 - 4);",
+    "console.log(2 ** 3 ** 2, (2 ** 3) ** 2, (-2) ** 2, 2 ** -1);",
+    "console.log((1, 2, 3) + 4, [1, (2, 3)]);",
+    "console.log((1, 2), { a: (1, 2) }.a);",
+    "var a = (1, 2), b = true ? (1, 2) : 3; console.log(a, b);",
+    "var a = 1; console.log(a++ + ++a, a-- - --a);",
+    "console.log(-(-1), +(+1), - -1, !(!0));",
+    "console.log(typeof (1 + 1), typeof 1 + 1);",
+    "console.log((function () { return 10; })());",
+    "console.log(((a, b) => a * b)(6, 7));",
+    "var o = { foo: 1, bar() { return this.foo; } }; console.log(o.bar());",
+    "console.log([,,1,,].length, [1,,].length, [,].length, 1 in [,,1,,]);",
+    "console.log((1 + 1).toString(), 10..toString());",
+    "var x = true ? 1 : 2, y = (false ? 1 : 2) + 1; console.log(x, y);",
+    "console.log(1 < 2 === true, 1 < (2 === true));",
+    "console.log('foo' in { foo: 1 }, [] instanceof Array);",
+    "for (var i = 0, s = ''; i < 3; i++) { s += i; } console.log(s);",
+    "label: for (;;) { break label; } console.log('done');",
+    "class Foo { constructor(a) { this.a = a; } get b() { return this.a * 2; } } console.log(new Foo(21).b);",
+    "console.log(`foo${ 1 + 1 }bar${ [1, 2] }baz`);",
+    "switch (2) { case 1: console.log(1); case 2: console.log(2); default: console.log(3); }",
+    "var o = { a: { 'b-c': 1, d: 2 } }; console.log(o['a']['d'], o['a']['b-c'], 10['toString'](2));",
+];
+
+fn execute(source: &str) -> String {
+    let output = Command::new("node")
+        .arg("-e")
+        .arg(source)
+        .output()
+        .expect("Failed to run `node`, the `differential` feature requires it to be on PATH");
+
+    assert!(output.status.success(), "Snippet failed to execute: {}\n{}", source, String::from_utf8_lossy(&output.stderr));
+
+    String::from_utf8(output.stdout).expect("Engine output isn't valid UTF-8")
+}
+
+#[test]
+fn round_trip_preserves_semantics() {
+    for snippet in SNIPPETS {
+        let module = parse(snippet).unwrap();
+        let expected = execute(snippet);
+
+        for &minify in &[true, false] {
+            let generated = codegen(&module, minify);
+
+            assert_eq!(execute(&generated), expected, "Generated code: {}", generated);
+        }
+    }
+}
@@ -1,8 +1,63 @@
-use ratel::ast::{Expression, Literal, OperatorKind, OperatorCategory, Property, PropertyKey, Pattern};
+use ratel::ast::{Expression, ExpressionNode, Literal, NumberLiteral, StringLiteral, OperatorKind, OperatorCategory, Property, PropertyKey, Pattern};
 use ratel::ast::expression::*;
+use std::borrow::Cow;
 
 use {ToCode, Generator};
 
+/// Shortest notation of the numeric literal `raw` with the same value,
+/// `None` if `raw` is already as short as it gets.
+pub fn shortest_number(raw: &str) -> Option<String> {
+    let bytes = raw.as_bytes();
+
+    let value = match (bytes.first(), bytes.get(1)) {
+        (Some(&b'0'), Some(&b'x')) | (Some(&b'0'), Some(&b'X')) => u64::from_str_radix(&raw[2..], 16).ok()? as f64,
+        (Some(&b'0'), Some(&b'o')) | (Some(&b'0'), Some(&b'O')) => u64::from_str_radix(&raw[2..], 8).ok()? as f64,
+        (Some(&b'0'), Some(&b'b')) | (Some(&b'0'), Some(&b'B')) => u64::from_str_radix(&raw[2..], 2).ok()? as f64,
+
+        // Legacy octal literals
+        (Some(&b'0'), Some(byte)) if byte.is_ascii_digit() => return None,
+
+        _ => raw.parse::<f64>().ok()?,
+    };
+
+    if !value.is_finite() {
+        return None;
+    }
+
+    let mut best: Option<String> = None;
+    let mut candidates = vec![format!("{}", value), format!("{:e}", value)];
+
+    if value.fract() == 0.0 && value < 9007199254740992.0 {
+        candidates.push(format!("0x{:x}", value as u64));
+    }
+
+    for mut candidate in candidates {
+        if candidate.starts_with("0.") {
+            candidate.remove(0);
+        }
+
+        let shortest = best.as_ref().map_or(raw.len(), String::len);
+
+        if candidate.len() < shortest {
+            best = Some(candidate);
+        }
+    }
+
+    best
+}
+
+/// Numeric literal as it should be printed
+#[inline]
+fn number<'a, G: Generator>(raw: &'a str, gen: &G) -> Cow<'a, str> {
+    if gen.shortest_numbers() {
+        if let Some(shortest) = shortest_number(raw) {
+            return Cow::Owned(shortest);
+        }
+    }
+
+    Cow::Borrowed(raw)
+}
+
 
 impl<'ast, G: Generator> ToCode<G> for Expression<'ast> {
     #[inline]
@@ -12,12 +67,15 @@ impl<'ast, G: Generator> ToCode<G> for Expression<'ast> {
         match *self {
             Void                         => {},
             This(_)                      => gen.write_bytes(b"this"),
+            Super(_)                     => gen.write_bytes(b"super"),
             Identifier(ref ident)        => gen.write(ident),
             Literal(ref value)           => gen.write(value),
             Sequence(ref sequence)       => gen.write(sequence),
             Array(ref array)             => gen.write(array),
             Member(ref member)           => gen.write(member),
             ComputedMember(ref computed) => gen.write(computed),
+            PrivateMember(ref member)    => gen.write(member),
+            Chain(ref chain)             => gen.write(chain),
             MetaProperty(ref property)   => gen.write(property),
             Call(ref call)               => gen.write(call),
             Binary(ref binary)           => gen.write(binary),
@@ -27,6 +85,8 @@ impl<'ast, G: Generator> ToCode<G> for Expression<'ast> {
             Template(ref template)       => gen.write(template),
             TaggedTemplate(ref tagged)   => gen.write(tagged),
             Spread(ref spread)           => gen.write(spread),
+            Yield(ref yield_expression)  => gen.write(yield_expression),
+            Await(ref await_expression)  => gen.write(await_expression),
             Arrow(ref arrow)             => gen.write(arrow),
             Object(ref object)           => gen.write(object),
             Function(ref function)       => gen.write(function),
@@ -41,18 +101,59 @@ impl<'ast, G: Generator> ToCode<G> for Literal<'ast> {
         use ratel::ast::Literal::*;
 
         match *self {
-            Undefined         => gen.write_bytes(b"undefined"),
             Null              => gen.write_bytes(b"null"),
-            True              => gen.write_bytes(b"true"),
-            False             => gen.write_bytes(b"false"),
-            Binary(n)         => gen.write(&format!("{}", n).as_str()),
-            Number(ref val)   |
-            String(ref val)   |
+            Boolean(true)     => gen.write_bytes(b"true"),
+            Boolean(false)    => gen.write_bytes(b"false"),
+            Number(n)         => {
+                let number = number(n.raw, gen);
+                gen.write(&&*number)
+            },
+            String(ref val)   => gen.write(&val.raw),
             RegEx(ref val)    => gen.write(val),
         }
     }
 }
 
+/// Whether the string `value` can follow a dot as a property name, only
+/// ASCII identifier names are accepted.
+#[inline]
+fn is_identifier_name(value: &str) -> bool {
+    let mut bytes = value.bytes();
+
+    match bytes.next() {
+        Some(b'a'..=b'z') | Some(b'A'..=b'Z') | Some(b'$') | Some(b'_') => {},
+        _ => return false,
+    }
+
+    bytes.all(|byte| byte.is_ascii_alphanumeric() || byte == b'$' || byte == b'_')
+}
+
+/// Write the `object` of a member expression followed by a dot, or by `?.`
+/// if `optional`.
+#[inline]
+fn write_dot<'ast, G: Generator>(gen: &mut G, object: &ExpressionNode<'ast>, optional: bool) {
+    gen.write_expression(object, 17);
+
+    if optional {
+        gen.write_bytes(b"?.");
+        return;
+    }
+
+    // `1.foo` would be read as a malformed fraction, legacy octal
+    // literals such as `017` have no fraction and need a space instead
+    if let Expression::Literal(Literal::Number(NumberLiteral { raw, .. })) = object.item {
+        let number = number(raw, gen);
+
+        if number.bytes().all(|byte| byte.is_ascii_digit()) {
+            let octal = number.len() > 1 && number.bytes().all(|byte| byte < b'8') && number.starts_with('0');
+
+            gen.write_byte(if octal { b' ' } else { b'.' });
+        }
+    }
+
+    gen.write_byte(b'.');
+}
+
 impl<G: Generator> ToCode<G> for OperatorKind {
     #[inline]
     fn to_code(&self, gen: &mut G) {
@@ -73,6 +174,10 @@ impl<'ast, G: Generator> ToCode<G> for PropertyKey<'ast> {
             },
             Literal(ref val) => gen.write(val),
             Binary(ref val) => gen.write(val),
+            Private(ref val) => {
+                gen.write_byte(b'#');
+                gen.write(val);
+            },
         }
     }
 }
@@ -84,6 +189,16 @@ impl<'ast, G: Generator> ToCode<G> for Property<'ast> {
 
         match *self {
             Shorthand(ref label) => gen.write(label),
+            ShorthandDefault {
+                ref name,
+                ref value,
+            } => {
+                gen.write(name);
+                gen.write_pretty(b' ');
+                gen.write_byte(b'=');
+                gen.write_pretty(b' ');
+                gen.write_expression(value, 1);
+            },
             Literal {
                 ref key,
                 ref value,
@@ -91,12 +206,15 @@ impl<'ast, G: Generator> ToCode<G> for Property<'ast> {
                 gen.write(key);
                 gen.write_byte(b':');
                 gen.write_pretty(b' ');
-                gen.write(value);
+                gen.write_expression(value, 1);
             },
             Method {
                 ref key,
                 ref value,
             } => {
+                if value.is_async {
+                    gen.write_bytes(b"async ");
+                }
                 gen.write(key);
                 gen.write(value);
             },
@@ -121,7 +239,7 @@ impl<'ast, G: Generator> ToCode<G> for ArrayExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         gen.write_byte(b'[');
-        gen.write_list(&self.body);
+        gen.write_expression_list(self.body);
         gen.write_byte(b']');
     }
 }
@@ -129,8 +247,18 @@ impl<'ast, G: Generator> ToCode<G> for ArrayExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for MemberExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write_expression(&self.object, 19);
-        gen.write_byte(b'.');
+        if !gen.preserve_members() && gen.bracket_words().contains(&self.property.item) {
+            gen.write_expression(&self.object, 17);
+            if self.optional {
+                gen.write_bytes(b"?.");
+            }
+            gen.write_bytes(b"[\"");
+            gen.write(&self.property);
+            gen.write_bytes(b"\"]");
+            return;
+        }
+
+        write_dot(gen, &self.object, self.optional);
         gen.write(&self.property);
     }
 }
@@ -138,13 +266,42 @@ impl<'ast, G: Generator> ToCode<G> for MemberExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ComputedMemberExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write_expression(&self.object, 19);
+        if let Expression::Literal(Literal::String(StringLiteral { value, .. })) = self.property.item {
+            if !gen.preserve_members() && is_identifier_name(value) && !gen.bracket_words().contains(&value) {
+                write_dot(gen, &self.object, self.optional);
+                gen.write(&value);
+                return;
+            }
+        }
+
+        gen.write_expression(&self.object, 17);
+        if self.optional {
+            gen.write_bytes(b"?.");
+        }
         gen.write_byte(b'[');
         gen.write(&self.property);
         gen.write_byte(b']');
     }
 }
 
+impl<'ast, G: Generator> ToCode<G> for PrivateMemberExpression<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        write_dot(gen, &self.object, self.optional);
+        gen.write_byte(b'#');
+        gen.write(&self.property);
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ChainExpression<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write_byte(b'(');
+        gen.write(&self.expression);
+        gen.write_byte(b')');
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for MetaPropertyExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
@@ -157,9 +314,12 @@ impl<'ast, G: Generator> ToCode<G> for MetaPropertyExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for CallExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write(&self.callee);
+        gen.write_expression(&self.callee, 17);
+        if self.optional {
+            gen.write_bytes(b"?.");
+        }
         gen.write_byte(b'(');
-        gen.write_list(&self.arguments);
+        gen.write_expression_list(self.arguments);
         gen.write_byte(b')');
     }
 }
@@ -192,7 +352,14 @@ impl<'ast, G: Generator> ToCode<G> for BinaryExpression<'ast> {
             }
         };
 
-        gen.write_expression(&self.left, bp);
+        // `**` is right-associative instead, `2 ** 3 ** 2` being `2 ** (3 ** 2)`,
+        // and a unary left operand has to be parenthesized, `(-2) ** 2`
+        let (bp_left, bp_right) = match self.operator {
+            OperatorKind::Exponent => (bp + 1, bp),
+            _                      => (bp, bp + 1),
+        };
+
+        gen.write_expression(&self.left, bp_left);
 
         if spacing_left {
             gen.write_byte(b' ');
@@ -211,18 +378,42 @@ impl<'ast, G: Generator> ToCode<G> for BinaryExpression<'ast> {
         // `2 / 2 * 2` and `2 / (2 * 2)` are different expressions,
         // hence the need for parenthesis in a right-balanced tree
         // even if binding power of operators is exactly the same.
-        gen.write_expression(&self.right, bp + 1);
+        gen.write_expression(&self.right, bp_right);
     }
 }
 
 impl<'ast, G: Generator> ToCode<G> for PrefixExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
+        let category = self.operator.category();
+
+        // `- -foo` and `-(-foo)` are fine, `--foo` is a decrement
+        let spacing = match category {
+            OperatorCategory::Word => true,
+            OperatorCategory::Safe => false,
+            category => match self.operand.item {
+                Expression::Prefix(PrefixExpression { operator, .. }) => {
+                    category == operator.category()
+                },
+                _ => false
+            }
+        };
+
         gen.write(&self.operator);
-        if self.operator.category() == OperatorCategory::Word {
+        if spacing {
             gen.write_byte(b' ');
         }
-        gen.write(&self.operand);
+        gen.write_expression(&self.operand, unary_operand_bp(&self.operand));
+    }
+}
+
+/// Binding power of the operand of a unary operator, which binds as
+/// tightly as `**` does, but `-a ** b` is an early error, not `-(a ** b)`
+#[inline]
+fn unary_operand_bp(operand: &ExpressionNode) -> u8 {
+    match operand.item {
+        Expression::Binary(BinaryExpression { operator: OperatorKind::Exponent, .. }) => 16,
+        _ => 15,
     }
 }
 
@@ -237,15 +428,15 @@ impl<'ast, G: Generator> ToCode<G> for PostfixExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ConditionalExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write(&self.test);
+        gen.write_expression(&self.test, 5);
         gen.write_pretty(b' ');
         gen.write_byte(b'?');
         gen.write_pretty(b' ');
-        gen.write(&self.consequent);
+        gen.write_expression(&self.consequent, 1);
         gen.write_pretty(b' ');
         gen.write_byte(b':');
         gen.write_pretty(b' ');
-        gen.write(&self.alternate);
+        gen.write_expression(&self.alternate, 1);
     }
 }
 
@@ -294,11 +485,45 @@ impl<'ast, G: Generator> ToCode<G> for SpreadExpression<'ast> {
     }
 }
 
+impl<'ast, G: Generator> ToCode<G> for AwaitExpression<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write_bytes(b"await ");
+        gen.write_expression(&self.argument, unary_operand_bp(&self.argument));
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for YieldExpression<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        match self.argument {
+            Some(ref argument) => {
+                if self.delegate {
+                    gen.write_bytes(b"yield*");
+                    gen.write_pretty(b' ');
+                } else {
+                    gen.write_bytes(b"yield ");
+                }
+                gen.write_expression(argument, 1);
+            },
+            None => gen.write_bytes(b"yield"),
+        }
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for ArrowBody<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         match *self {
-            ArrowBody::Expression(ref expression) => gen.write(expression),
+            ArrowBody::Expression(ref expression) => {
+                if expression.is_allowed_as_arrow_body() {
+                    gen.write(expression);
+                } else {
+                    gen.write_byte(b'(');
+                    gen.write(expression);
+                    gen.write_byte(b')');
+                }
+            },
             ArrowBody::Block(ref block)           => gen.write(block),
         }
     }
@@ -307,6 +532,9 @@ impl<'ast, G: Generator> ToCode<G> for ArrowBody<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ArrowExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
+        if self.is_async {
+            gen.write_bytes(b"async ");
+        }
         match self.params.only_element().map(|el| &el.item) {
             Some(&Pattern::Identifier(ref ident)) => gen.write(ident),
             _ => {
@@ -465,15 +693,84 @@ mod test {
     fn call_expression() {
         assert_min("foobar();", "foobar();");
         assert_min("foobar(1, 2, 3);", "foobar(1,2,3);");
+        assert_min("class A extends B { constructor() { super(a, ...b); } }", "class A extends B{constructor(){super(a,...b);}}");
     }
 
     #[test]
     fn member_expression() {
         assert_min("foo.bar", "foo.bar;");
         assert_min("this.bar", "this.bar;");
+        assert_min("({ a() { super.bar(); } })", "({a(){super.bar();}});");
+        assert_min("({ a() { super[10]; } })", "({a(){super[10];}});");
         assert_min("10..fooz", "10..fooz;");
         assert_min("foo[10]", "foo[10];");
-        assert_min(r#"foo["bar"]"#, r#"foo["bar"];"#);
+        assert_min(r#"foo["bar"]"#, "foo.bar;");
+        assert_min("foo.class.in.var", "foo.class.in.var;");
+    }
+
+    #[test]
+    fn computed_member_dot_access() {
+        assert_min(r#"foo["bar"]['baz']["$_0"]"#, "foo.bar.baz.$_0;");
+        assert_min(r#"foo["class"]"#, "foo.class;");
+        assert_min(r#"foo["b\x61r"]"#, "foo.bar;");
+        assert_min(r#"foo?.["bar"]"#, "foo?.bar;");
+        assert_min(r#"({ a() { super["bar"](); } })"#, "({a(){super.bar();}});");
+        assert_min(r#"10["fooz"]"#, "10..fooz;");
+        assert_min(r#"foo["0"]"#, r#"foo["0"];"#);
+        assert_min(r#"foo["a-b"]"#, r#"foo["a-b"];"#);
+        assert_min(r#"foo[""]"#, r#"foo[""];"#);
+        assert_min(r#"foo["é"]"#, r#"foo["é"];"#);
+        assert_min(r#"foo["bar" + baz]"#, r#"foo["bar"+baz];"#);
+    }
+
+    #[test]
+    fn preserve_members() {
+        use ratel::parse;
+        use {codegen_with_options, CodegenOptions, ES3_RESERVED_WORDS};
+
+        let options = CodegenOptions {
+            minify: true,
+            preserve_members: true,
+            bracket_words: ES3_RESERVED_WORDS,
+            ..CodegenOptions::default()
+        };
+        let module = parse(r#"foo["bar"].default['baz']"#).unwrap();
+
+        assert_eq!(codegen_with_options(&module, options), r#"foo["bar"].default['baz'];"#);
+    }
+
+    #[test]
+    fn bracket_words() {
+        use ratel::parse;
+        use {codegen_with_options, CodegenOptions, ES3_RESERVED_WORDS};
+
+        let options = CodegenOptions {
+            minify: true,
+            bracket_words: ES3_RESERVED_WORDS,
+            ..CodegenOptions::default()
+        };
+        let module = parse(r#"foo.default["class"].bar?.in; foo['catch']"#).unwrap();
+
+        assert_eq!(codegen_with_options(&module, options), r#"foo["default"]["class"].bar?.["in"];foo['catch'];"#);
+    }
+
+    #[test]
+    fn optional_chain() {
+        assert_min("foo?.bar.baz", "foo?.bar.baz;");
+        assert_min("foo?.[10]", "foo?.[10];");
+        assert_min("foo?.(1, 2)?.bar", "foo?.(1,2)?.bar;");
+        assert_min("10?.fooz", "10?.fooz;");
+    }
+
+    #[test]
+    fn parenthesized_optional_chain() {
+        assert_min("(a?.b).c", "(a?.b).c;");
+        assert_min("(a?.b)()", "(a?.b)();");
+        assert_min("(a?.[b])[c]", "(a?.[b])[c];");
+        assert_min("new (a?.b)()", "new (a?.b)();");
+        assert_min("(a?.b)`c`", "(a?.b)`c`;");
+        assert_min("(a?.b)?.c", "a?.b?.c;");
+        assert_min("(a.b).c", "a.b.c;");
     }
 
     #[test]
@@ -520,10 +817,19 @@ mod test {
         assert_min("({ foo: 10, bar: 20 });", "({foo:10,bar:20});");
         assert_min("({ foo: 10, bar() {} });", "({foo:10,bar(){}});");
         assert_min("({ foo(bar, baz) {} });", "({foo(bar,baz){}});");
+        assert_min("({ default: 1, in() {} });", "({default:1,in(){}});");
         let expected = "({\n    foo: true,\n    bar: false\n});";
         assert_pretty("({ foo: true, bar: false })", expected);
     }
 
+    #[test]
+    fn shorthand_default() {
+        assert_min("({ a = 1 } = b);", "({a=1}=b);");
+        assert_min("[{ a = 1, b: { c = 2 } }] = d;", "[{a=1,b:{c=2}}]=d;");
+        assert_min("var { a = (1, 2) } = b;", "var {a=(1,2)}=b;");
+        assert_pretty("({ a = 1 } = b);", "({\n    a = 1\n} = b);");
+    }
+
     #[test]
     fn binding_power() {
         assert_min("1 + 2 * 3;", "1+2*3;");
@@ -536,8 +842,137 @@ mod test {
         assert_min("(2 * 2) / 2;", "2*2/2;");
     }
 
+    #[test]
+    fn exponentiation() {
+        assert_min("2 ** 3 ** 2;", "2**3**2;");
+        assert_min("2 ** (3 ** 2);", "2**3**2;");
+        assert_min("(2 ** 3) ** 2;", "(2**3)**2;");
+        assert_min("a * b ** c;", "a*b**c;");
+        assert_min("(a * b) ** c;", "(a*b)**c;");
+        assert_min("(-2) ** 2;", "(-2)**2;");
+        assert_min("-(2 ** 2);", "-(2**2);");
+        assert_min("2 ** -2;", "2**-2;");
+        assert_min("a++ ** 2;", "a++**2;");
+        assert_min("(typeof a) ** 2;", "(typeof a)**2;");
+        assert_min("async () => (await a) ** 2;", "async ()=>(await a)**2;");
+        assert_min("async () => await (a ** 2);", "async ()=>await (a**2);");
+        assert_min("x **= 2;", "x**=2;");
+    }
+
     #[test]
     fn regression_increments() {
         assert_min("x++ + ++y", "x++ + ++y;");
     }
+
+    #[test]
+    fn regression_sequence_in_lists() {
+        assert_min("[1, (2, 3)]", "[1,(2,3)];");
+        assert_min("foo(1, (2, 3))", "foo(1,(2,3));");
+        assert_min("({ foo: (1, 2) })", "({foo:(1,2)});");
+        assert_min("var foo = (1, 2);", "var foo=(1,2);");
+    }
+
+    #[test]
+    fn regression_prefix_operands() {
+        assert_min("-(-foo)", "- -foo;");
+        assert_min("+(+foo)", "+ +foo;");
+        assert_min("-(+foo)", "-+foo;");
+        assert_min("!(!foo)", "!!foo;");
+        assert_min("-(a + b)", "-(a+b);");
+        assert_min("typeof (a + b)", "typeof (a+b);");
+    }
+
+    #[test]
+    fn regression_arrow_callee() {
+        assert_min("((a) => a)(1)", "(a=>a)(1);");
+        assert_min("foo().bar", "foo().bar;");
+    }
+
+    #[test]
+    fn async_arrow_function() {
+        assert_min("async (a, b) => a + b", "async (a,b)=>a+b;");
+        assert_min("async x => await x", "async x=>await x;");
+        assert_min("async () => {}", "async ()=>{};");
+        assert_min("async(a, b)", "async(a,b);");
+    }
+
+    #[test]
+    fn arrow_body_leading_token() {
+        assert_min("() => ({})", "()=>({});");
+        assert_min("() => ({}).foo", "()=>({}.foo);");
+        assert_min("() => (a, b)", "()=>(a,b);");
+        assert_min("() => function () {}", "()=>function(){};");
+    }
+
+    #[test]
+    fn let_bracket_statement() {
+        use ratel::parse;
+        use ratel::ast::{Loc, Statement, Expression};
+        use ratel::ast::expression::BinaryExpression;
+        use codegen;
+
+        // `let` can't be parsed as an identifier, but can be the result of a transform
+        let module = parse("foo[0] + 1;").unwrap();
+
+        if let Statement::Expression(expression) = module.body().only_element().unwrap().item {
+            if let Expression::Binary(BinaryExpression { left, .. }) = expression.item {
+                if let Expression::ComputedMember(ref member) = left.item {
+                    member.object.set(module.arena().alloc(Loc::new(0, 0, Expression::Identifier("let"))));
+                }
+            }
+        }
+
+        assert_eq!(codegen(&module, true).as_str(), "(let[0]+1);");
+    }
+
+    #[test]
+    fn numbers_as_written() {
+        assert_min("0xFF + 1e3 + 1000000 + 0.50 + 0b11", "0xFF+1e3+1000000+0.50+0b11;");
+    }
+
+    #[test]
+    fn shortest_numbers() {
+        use ratel::parse;
+        use {codegen_with_options, CodegenOptions};
+
+        let options = CodegenOptions {
+            minify: true,
+            shortest_numbers: true,
+            ..CodegenOptions::default()
+        };
+        let module = parse("0xFF + 1e3 + 1000000 + 0.50 + 0b11 + 1.0 + 0777 + 1e400 + 5e-7").unwrap();
+
+        assert_eq!(codegen_with_options(&module, options), "255+1e3+1e6+.5+3+1+0777+1e400+5e-7;");
+    }
+
+    #[test]
+    fn number_members() {
+        use ratel::parse;
+        use {codegen_with_options, CodegenOptions};
+
+        assert_min("1..toString()", "1..toString();");
+        assert_min("(1).toString()", "1..toString();");
+        assert_min("1.5.toString()", "1.5.toString();");
+        assert_min("017 .toString()", "017 .toString();");
+        assert_min("019 .toString()", "019..toString();");
+
+        let options = CodegenOptions {
+            minify: true,
+            shortest_numbers: true,
+            ..CodegenOptions::default()
+        };
+        let module = parse("1.0.toString()").unwrap();
+
+        assert_eq!(codegen_with_options(&module, options), "1..toString();");
+    }
+
+    #[test]
+    fn sparse_arrays() {
+        assert_min("[,,1,,]", "[,,1,,];");
+        assert_min("[1,,]", "[1,,];");
+        assert_min("[1,]", "[1,];");
+        assert_min("[,]", "[,];");
+        assert_min("[]", "[];");
+        assert_pretty("[,,1,,]", "[, , 1, , ];");
+    }
 }
@@ -36,8 +36,16 @@ pub trait ClassFunctionDeclaration<G: Generator> {
     }
 
     #[inline]
-    fn write_function(gen: &mut G) {
+    fn write_function(gen: &mut G, generator: bool, is_async: bool) {
+        if is_async {
+            gen.write_bytes(b"async ");
+        }
+
         gen.write_bytes(b"function");
+
+        if generator {
+            gen.write_byte(b'*');
+        }
     }
 }
 
@@ -45,8 +53,9 @@ impl<G: Generator> ClassFunctionDeclaration<G> for EmptyName {
     #[inline]
     fn write_class(_: &mut G) {}
 
+    /// `async` of methods is written before their key
     #[inline]
-    fn write_function(_: &mut G) {}
+    fn write_function(_: &mut G, _: bool, _: bool) {}
 }
 
 impl<'ast, G: Generator> ClassFunctionDeclaration<G> for OptionalName<'ast> {}
@@ -58,7 +67,7 @@ impl<'ast, G, N> ToCode<G> for Function<'ast, N> where
 {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        N::write_function(gen);
+        N::write_function(gen, self.generator, self.is_async);
         gen.write(&self.name);
         gen.write_byte(b'(');
         gen.write_list(&self.params);
@@ -84,6 +93,9 @@ impl<'ast, G: Generator> ToCode<G> for ClassMember<'ast> {
                 if is_static {
                     gen.write_bytes(b"static ");
                 }
+                if value.is_async {
+                    gen.write_bytes(b"async ");
+                }
                 match kind {
                     MethodKind::Get => gen.write_bytes(b"get "),
                     MethodKind::Set => gen.write_bytes(b"set "),
@@ -92,7 +104,7 @@ impl<'ast, G: Generator> ToCode<G> for ClassMember<'ast> {
                 gen.write(key);
                 gen.write(value);
             },
-            Literal {
+            Property {
                 is_static,
                 ref key,
                 ref value,
@@ -101,11 +113,20 @@ impl<'ast, G: Generator> ToCode<G> for ClassMember<'ast> {
                     gen.write_bytes(b"static ");
                 }
                 gen.write(key);
-                gen.write_pretty(b' ');
-                gen.write_byte(b'=');
-                gen.write_pretty(b' ');
-                gen.write(value);
+                if let Some(ref value) = *value {
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'=');
+                    gen.write_pretty(b' ');
+                    gen.write(value);
+                }
                 gen.write_byte(b';');
+            },
+            StaticBlock { ref body } => {
+                gen.write_bytes(b"static");
+                gen.write_pretty(b' ');
+                gen.write_byte(b'{');
+                gen.write_block(body);
+                gen.write_byte(b'}');
             }
         }
     }
@@ -121,7 +142,7 @@ impl<'ast, G, N> ToCode<G> for Class<'ast, N> where
         gen.write(&self.name);
         if let Some(ref super_class) = self.extends {
             gen.write_bytes(b" extends ");
-            gen.write(super_class);
+            gen.write_expression(super_class, 17);
         }
         gen.write_pretty(b' ');
         gen.write(&self.body);
@@ -140,6 +161,32 @@ mod test {
         assert_min("function foo(bar) { return 10; }", "function foo(bar){return 10;}");
     }
 
+    #[test]
+    fn generator() {
+        assert_min("function* foo() {}", "function* foo(){}");
+        assert_min("(function* () {})", "(function*(){});");
+        assert_min("(function *foo(a) {})", "(function* foo(a){});");
+    }
+
+    #[test]
+    fn async_function() {
+        assert_min("async function foo() {}", "async function foo(){}");
+        assert_min("(async function () {})", "(async function(){});");
+        assert_min("({ async foo() {} })", "({async foo(){}});");
+        assert_min("class Foo { static async foo() {} }", "class Foo{static async foo(){}}");
+    }
+
+    #[test]
+    fn await_expression() {
+        assert_min("async function foo() { await a; await (a + b); (await a).b; }", "async function foo(){await a;await (a+b);(await a).b;}");
+    }
+
+    #[test]
+    fn yield_expression() {
+        assert_min("function* foo() { yield; yield a; yield* b; yield (a, b); }", "function* foo(){yield;yield a;yield*b;yield (a,b);}");
+        assert_min("function* foo() { (yield a) + (yield); }", "function* foo(){(yield a)+(yield);}");
+    }
+
     #[test]
     fn rest_and_spread() {
         assert_min("function foo(...things) { bar(...things); }", "function foo(...things){bar(...things);}");
@@ -149,11 +196,18 @@ mod test {
     fn class() {
         assert_min("class Foo {}", "class Foo{}");
         assert_min("class Foo extends Bar {}", "class Foo extends Bar{}");
+        assert_min("class Foo extends bar.baz(qux) {}", "class Foo extends bar.baz(qux){}");
+        assert_min("class Foo extends (a ? B : C) {}", "class Foo extends (a?B:C){}");
+        assert_min("class Foo extends (a, B) {}", "class Foo extends (a,B){}");
         assert_min("class Foo { constructor(a, b) { debug; } }", "class Foo{constructor(a,b){debug;}}");
         assert_min("class Foo { static constructor(a, b) { debug; } }", "class Foo{static constructor(a,b){debug;}}");
         assert_min("class Foo { method(a, b) { debug; } }", "class Foo{method(a,b){debug;}}");
         assert_min("class Foo { static method(a, b) { debug; } }", "class Foo{static method(a,b){debug;}}");
         assert_min("class Foo { a = 10; b = 20; }", "class Foo{a=10;b=20;}");
         assert_min("class Foo { static a = 10; b = 20; }", "class Foo{static a=10;b=20;}");
+        assert_min("class Foo { a; static b\n c = 1 }", "class Foo{a;static b;c=1;}");
+        assert_min("class Foo { static { init(); } static {} }", "class Foo{static{init();}static{}}");
+        assert_min("class Foo { #a = 1; static #b() { return this.#a + c?.#a; } }", "class Foo{#a=1;static #b(){return this.#a+c?.#a;}}");
+        assert_min("class Foo { #a; m() { (function () {}).#a; ({}).#a = 1; } }", "class Foo{#a;m(){(function(){}.#a);({}.#a=1);}}");
     }
 }
@@ -0,0 +1,183 @@
+//! Gallery of examples generated from syntax fixtures, the `.js` files of
+//! a directory laid out as `ratel/tests/fixtures`, for authors of tools
+//! exploring which AST ratel produces for each construct. Every example is
+//! the source, its ESTree AST as JSON and the code generated back from it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ratel::error::Error;
+use ratel::{parse, write_json};
+
+use codegen;
+
+/// A fixture turned into an example, see `Example::new`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Example {
+    /// Path of the fixture relative to the directory of the gallery,
+    /// without the extension, such as `expression/await`
+    pub name: String,
+
+    pub source: String,
+
+    /// AST of the source, pretty printed ESTree JSON
+    pub ast: String,
+
+    /// Pretty printed code generated from the AST
+    pub generated: String,
+}
+
+impl Example {
+    /// Parse `source` and generate the code back from its AST.
+    pub fn new(name: &str, source: &str) -> Result<Example, Vec<Error>> {
+        let module = parse(source)?;
+        let mut ast = Vec::new();
+
+        write_json(&mut ast, &module, true).expect("Writing into a Vec can't fail");
+
+        Ok(Example {
+            name: name.to_owned(),
+            source: source.trim_end().to_owned(),
+            ast: String::from_utf8(ast).expect("JSON is valid UTF-8"),
+            generated: codegen(&module, false).trim_end().to_owned(),
+        })
+    }
+}
+
+/// Make an example of every `.js` fixture under `dir`, sorted by name.
+/// Fixtures of sources that must fail to parse, those with a
+/// `name.failure.json` next to them, are left out, any other source
+/// failing to parse is an `InvalidData` error.
+pub fn examples(dir: &Path) -> io::Result<Vec<Example>> {
+    let mut sources = Vec::new();
+
+    collect_sources(dir, &mut sources)?;
+
+    let mut examples = Vec::with_capacity(sources.len());
+
+    for path in sources {
+        if path.with_extension("failure.json").exists() {
+            continue;
+        }
+
+        let relative = path.strip_prefix(dir).unwrap_or(&path).with_extension("");
+        let name = relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        let source = fs::read_to_string(&path)?;
+
+        let example = Example::new(&name, &source).map_err(|errors| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: failed to parse: {:?}", name, errors))
+        })?;
+
+        examples.push(example);
+    }
+
+    // Sorting paths would put `class-fields` before `class`, as `-` sorts
+    // before the `.` of the extension
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(examples)
+}
+
+/// Render the `examples` as a Markdown document, a section per example.
+pub fn markdown(examples: &[Example]) -> String {
+    let mut document = String::from("# Examples\n");
+
+    for example in examples {
+        document.push_str("\n## `");
+        document.push_str(&example.name);
+        document.push_str("`\n");
+
+        for &(title, language, code) in [
+            ("Source", "js", &example.source),
+            ("AST", "json", &example.ast),
+            ("Generated", "js", &example.generated),
+        ].iter() {
+            document.push('\n');
+            document.push_str(title);
+            document.push_str(":\n\n```");
+            document.push_str(language);
+            document.push('\n');
+            document.push_str(code);
+            document.push_str("\n```\n");
+        }
+    }
+
+    document
+}
+
+/// Collect the `.js` files under `dir`.
+fn collect_sources(dir: &Path, sources: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_sources(&path, sources)?;
+        } else if path.extension().is_some_and(|extension| extension == "js") {
+            sources.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("ratel").join("tests").join("fixtures")
+    }
+
+    #[test]
+    fn example() {
+        let example = Example::new("binary", "a+b*c;\n").unwrap();
+
+        assert_eq!(example.source, "a+b*c;");
+        assert_eq!(example.generated, "a + b * c;");
+        assert!(example.ast.starts_with("{\n  \"type\": \"Program\""));
+        assert!(Example::new("error", "a +").is_err());
+    }
+
+    #[test]
+    fn fixture_examples() {
+        let examples = examples(&fixtures_dir()).unwrap();
+        let names: Vec<&str> = examples.iter().map(|example| example.name.as_str()).collect();
+
+        assert!(names.contains(&"expression/await"));
+        assert!(names.contains(&"statement/class"));
+        assert!(!names.contains(&"statement/missing-initializer"));
+
+        let mut sorted = names.clone();
+
+        sorted.sort();
+
+        assert_eq!(names, sorted);
+
+        // The generated code has the same AST as the source
+        for example in &examples {
+            let source = parse(&example.source).unwrap();
+            let generated = parse(&example.generated).unwrap_or_else(|errors| panic!("{}: {:?}", example.name, errors));
+
+            assert_eq!(source.body(), generated.body(), "{}", example.name);
+        }
+    }
+
+    #[test]
+    fn missing_directory() {
+        assert_eq!(examples(&fixtures_dir().join("missing")).err().map(|err| err.kind()), Some(io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn markdown_sections() {
+        let examples = [
+            Example::new("a", "a;").unwrap(),
+            Example::new("b", "b;").unwrap(),
+        ];
+        let document = markdown(&examples);
+
+        assert!(document.starts_with("# Examples\n\n## `a`\n\nSource:\n\n```js\na;\n```\n\nAST:\n\n```json\n{"));
+        assert!(document.contains("\n## `b`\n"));
+        assert!(document.ends_with("Generated:\n\n```js\nb;\n```\n"));
+    }
+}
@@ -4,12 +4,21 @@ extern crate pretty_assertions;
 extern crate ratel;
 
 use ratel::ast::{Node, ExpressionNode, Loc, Block, Pattern};
-use ratel::Module;
+use ratel::{Module, Output};
+use ratel::cancel::{CancellationToken, Cancelled};
+use std::collections::HashMap;
+use std::time::Instant;
 
 mod expression;
 mod statement;
 mod function;
 
+#[cfg(feature = "gallery")]
+pub mod gallery;
+
+#[cfg(all(test, feature = "differential"))]
+mod differential;
+
 pub trait Generator: Sized {
     type Output;
 
@@ -37,6 +46,25 @@ pub trait Generator: Sized {
         }
     }
 
+    /// Like `write_list`, but wraps sequence expressions in parenthesis
+    /// so that they aren't confused with the list separators
+    #[inline]
+    fn write_expression_list<'a, I>(&mut self, items: I) where
+        I: IntoIterator<Item = &'a ExpressionNode<'a>>,
+    {
+        let mut items = items.into_iter();
+
+        if let Some(item) = items.next() {
+            self.write_expression(item, 1);
+        }
+
+        for item in items {
+            self.write_byte(b',');
+            self.write_pretty(b' ');
+            self.write_expression(item, 1);
+        }
+    }
+
     #[inline]
     fn write_list<'a, T, I>(&mut self, items: I) where
         T: ToCode<Self> + 'a,
@@ -44,7 +72,7 @@ pub trait Generator: Sized {
     {
         let mut items = items.into_iter();
 
-        for item in items.next() {
+        if let Some(item) = items.next() {
             self.write(item);
         }
 
@@ -87,21 +115,116 @@ pub trait Generator: Sized {
 
     #[inline]
     fn dedent(&mut self) {}
+
+    /// See `CodegenOptions::shortest_numbers`
+    #[inline]
+    fn shortest_numbers(&self) -> bool {
+        false
+    }
+
+    /// See `CodegenOptions::preserve_members`
+    #[inline]
+    fn preserve_members(&self) -> bool {
+        false
+    }
+
+    /// See `CodegenOptions::bracket_words`
+    #[inline]
+    fn bracket_words(&self) -> &[&str] {
+        &[]
+    }
 }
 
-pub struct MinifyingGenerator {
-    code: Vec<u8>
+/// Patterns of `CodegenOptions::preserve_comments` matching the comments
+/// commonly used for licenses, such as `/*! lib v1.0 | MIT */`.
+pub const LICENSE_COMMENTS: &[&str] = &["/*!", "//!", "@license", "@preserve"];
+
+/// Reserved words of ES3, which ES3 engines don't accept as property names
+/// after a dot, for `CodegenOptions::bracket_words`.
+pub const ES3_RESERVED_WORDS: &[&str] = &[
+    "abstract", "boolean", "break", "byte", "case", "catch", "char", "class",
+    "const", "continue", "debugger", "default", "delete", "do", "double",
+    "else", "enum", "export", "extends", "false", "final", "finally", "float",
+    "for", "function", "goto", "if", "implements", "import", "in",
+    "instanceof", "int", "interface", "long", "native", "new", "null",
+    "package", "private", "protected", "public", "return", "short", "static",
+    "super", "switch", "synchronized", "this", "throw", "throws", "transient",
+    "true", "try", "typeof", "var", "void", "volatile", "while", "with",
+];
+
+/// Line break used throughout the output, see `CodegenOptions::line_ending`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+
+    /// `\r\n`
+    CrLf,
 }
 
-impl MinifyingGenerator {
-    fn new() -> Self {
+/// Settings for `codegen_with_options`, `Default` matches plain non-minified `codegen`.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct CodegenOptions<'a> {
+    pub minify: bool,
+
+    /// Print numeric literals in their shortest notation (`0xFF` as `255`,
+    /// `1000000` as `1e6`). By default literals are printed as written.
+    pub shortest_numbers: bool,
+
+    /// Comment written on its own line at the start of the output, with
+    /// its delimiters, such as `/* Generated by build.js */`.
+    pub banner: Option<&'a str>,
+
+    /// Keep the comments of the module containing any of the patterns,
+    /// with their delimiters, such as `LICENSE_COMMENTS`. All other comments
+    /// are dropped. Kept comments are written before the top-level statement
+    /// they precede or are nested in, followed by a line break.
+    pub preserve_comments: &'a [&'a str],
+
+    /// Print member expressions as written. By default a string key that
+    /// is an ASCII identifier name uses dot access, `a["b"]["c"]` is printed
+    /// as `a.b.c`.
+    pub preserve_members: bool,
+
+    /// Property names to access with brackets rather than with a dot, such
+    /// as `ES3_RESERVED_WORDS`, `a.default` is then printed as `a["default"]`.
+    /// Ignored with `preserve_members`.
+    pub bracket_words: &'a [&'a str],
+
+    /// Line break of the output. Line breaks copied from the source, in
+    /// comments and template strings, are normalized to it too, whether
+    /// they were `\n`, `\r\n` or `\r`.
+    pub line_ending: LineEnding,
+
+    /// End the output with a line break, unless it's empty or already
+    /// ends with one.
+    pub final_newline: bool,
+
+    /// Start the output with a byte order mark. The parser skips the one
+    /// of the source, so by default it's stripped.
+    pub bom: bool,
+}
+
+pub struct MinifyingGenerator<'a> {
+    code: Vec<u8>,
+    shortest_numbers: bool,
+    preserve_members: bool,
+    bracket_words: &'a [&'a str],
+}
+
+impl<'a> MinifyingGenerator<'a> {
+    fn new(options: CodegenOptions<'a>) -> Self {
         MinifyingGenerator {
-            code: Vec::with_capacity(128)
+            code: Vec::with_capacity(128),
+            shortest_numbers: options.shortest_numbers,
+            preserve_members: options.preserve_members,
+            bracket_words: options.bracket_words,
         }
     }
 }
 
-impl Generator for MinifyingGenerator {
+impl<'a> Generator for MinifyingGenerator<'a> {
     type Output = String;
 
     fn consume(self) -> String {
@@ -120,23 +243,231 @@ impl Generator for MinifyingGenerator {
     fn write_bytes(&mut self, slice: &[u8]) {
         extend_from_slice(&mut self.code, slice);
     }
+
+    #[inline]
+    fn shortest_numbers(&self) -> bool {
+        self.shortest_numbers
+    }
+
+    #[inline]
+    fn preserve_members(&self) -> bool {
+        self.preserve_members
+    }
+
+    #[inline]
+    fn bracket_words(&self) -> &[&str] {
+        self.bracket_words
+    }
+}
+
+/// Size model of the minifying printer, counting the bytes the code would
+/// take without writing it, see `minified_size`.
+pub struct SizeGenerator<'a> {
+    size: usize,
+    shortest_numbers: bool,
+    preserve_members: bool,
+    bracket_words: &'a [&'a str],
+}
+
+impl<'a> SizeGenerator<'a> {
+    #[inline]
+    pub fn new(options: CodegenOptions<'a>) -> Self {
+        SizeGenerator {
+            size: 0,
+            shortest_numbers: options.shortest_numbers,
+            preserve_members: options.preserve_members,
+            bracket_words: options.bracket_words,
+        }
+    }
+}
+
+impl<'a> Generator for SizeGenerator<'a> {
+    type Output = usize;
+
+    fn consume(self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn write_byte(&mut self, _: u8) {
+        self.size += 1;
+    }
+
+    #[inline]
+    fn write_pretty(&mut self, _: u8) {}
+
+    #[inline]
+    fn write_bytes(&mut self, slice: &[u8]) {
+        self.size += slice.len();
+    }
+
+    #[inline]
+    fn shortest_numbers(&self) -> bool {
+        self.shortest_numbers
+    }
+
+    #[inline]
+    fn preserve_members(&self) -> bool {
+        self.preserve_members
+    }
+
+    #[inline]
+    fn bracket_words(&self) -> &[&str] {
+        self.bracket_words
+    }
+}
+
+/// Length in bytes of the minified code of `item`, such as a statement or
+/// an expression, for comparing the size of rewrites.
+#[inline]
+pub fn minified_size<T>(item: &T) -> usize where
+    T: ToCode<SizeGenerator<'static>>,
+{
+    let mut gen = SizeGenerator::new(CodegenOptions::default());
+
+    gen.write(item);
+    gen.consume()
+}
+
+/// Memoized `minified_size` for the nodes of a single AST, for passes
+/// measuring the same subtrees over and over while comparing rewrites.
+///
+/// The sizes of the expressions met along the way are kept by the address
+/// of their node, and reused when the same node turns up again. Replacing
+/// a node in place, as `Transformer::swap` does, changes the size of its
+/// parents, so the cache has to be cleared whenever the AST is changed.
+/// Candidate rewrites may be measured without clearing as long as their
+/// nodes are allocated in the arena of the AST.
+pub struct SizeCache<'a> {
+    options: CodegenOptions<'a>,
+    sizes: HashMap<usize, usize>,
+}
+
+impl<'a> SizeCache<'a> {
+    #[inline]
+    pub fn new(options: CodegenOptions<'a>) -> Self {
+        SizeCache {
+            options,
+            sizes: HashMap::new(),
+        }
+    }
+
+    /// Same as `minified_size`, reusing the sizes measured before.
+    #[inline]
+    pub fn size<T>(&mut self, item: &T) -> usize where
+        T: for<'c> ToCode<CachedSizeGenerator<'c, 'a>>,
+    {
+        let mut gen = CachedSizeGenerator {
+            inner: SizeGenerator::new(self.options),
+            sizes: &mut self.sizes,
+        };
+
+        gen.write(item);
+        gen.consume()
+    }
+
+    /// Forget the sizes measured so far, after the AST was changed.
+    #[inline]
+    pub fn clear(&mut self) {
+        if !self.sizes.is_empty() {
+            self.sizes.clear();
+        }
+    }
+}
+
+impl<'a> Default for SizeCache<'a> {
+    #[inline]
+    fn default() -> Self {
+        SizeCache::new(CodegenOptions::default())
+    }
+}
+
+/// `SizeGenerator` looking up the expressions in a `SizeCache`.
+pub struct CachedSizeGenerator<'c, 'a: 'c> {
+    inner: SizeGenerator<'a>,
+    sizes: &'c mut HashMap<usize, usize>,
+}
+
+impl<'c, 'a> Generator for CachedSizeGenerator<'c, 'a> {
+    type Output = usize;
+
+    fn consume(self) -> usize {
+        self.inner.consume()
+    }
+
+    #[inline]
+    fn write_byte(&mut self, byte: u8) {
+        self.inner.write_byte(byte);
+    }
+
+    #[inline]
+    fn write_pretty(&mut self, _: u8) {}
+
+    #[inline]
+    fn write_bytes(&mut self, slice: &[u8]) {
+        self.inner.write_bytes(slice);
+    }
+
+    #[inline]
+    fn write_expression<'ast>(&mut self, item: &ExpressionNode<'ast>, bp: u8) {
+        if item.binding_power() < bp {
+            // Parenthesis
+            self.inner.size += 2;
+        }
+
+        let key = item.get() as *const Loc<_> as usize;
+
+        if let Some(&size) = self.sizes.get(&key) {
+            self.inner.size += size;
+            return;
+        }
+
+        let start = self.inner.size;
+
+        item.to_code(self);
+
+        let size = self.inner.size - start;
+
+        self.sizes.insert(key, size);
+    }
+
+    #[inline]
+    fn shortest_numbers(&self) -> bool {
+        self.inner.shortest_numbers
+    }
+
+    #[inline]
+    fn preserve_members(&self) -> bool {
+        self.inner.preserve_members
+    }
+
+    #[inline]
+    fn bracket_words(&self) -> &[&str] {
+        self.inner.bracket_words
+    }
 }
 
-struct PrettyGenerator {
+struct PrettyGenerator<'a> {
     code: Vec<u8>,
     dent: usize,
+    shortest_numbers: bool,
+    preserve_members: bool,
+    bracket_words: &'a [&'a str],
 }
 
-impl PrettyGenerator {
-    fn new() -> Self {
+impl<'a> PrettyGenerator<'a> {
+    fn new(options: CodegenOptions<'a>) -> Self {
         PrettyGenerator {
             code: Vec::with_capacity(128),
             dent: 0,
+            shortest_numbers: options.shortest_numbers,
+            preserve_members: options.preserve_members,
+            bracket_words: options.bracket_words,
         }
     }
 }
 
-impl Generator for PrettyGenerator {
+impl<'a> Generator for PrettyGenerator<'a> {
     type Output = String;
 
     fn consume(self) -> String {
@@ -175,29 +506,175 @@ impl Generator for PrettyGenerator {
     fn dedent(&mut self) {
         self.dent -= 1;
     }
+
+    #[inline]
+    fn shortest_numbers(&self) -> bool {
+        self.shortest_numbers
+    }
+
+    #[inline]
+    fn preserve_members(&self) -> bool {
+        self.preserve_members
+    }
+
+    #[inline]
+    fn bracket_words(&self) -> &[&str] {
+        self.bracket_words
+    }
 }
 
 pub fn codegen<'ast>(module: &Module, minify: bool) -> String {
-    if minify {
-        let mut gen = MinifyingGenerator::new();
+    codegen_with_options(module, CodegenOptions {
+        minify,
+        ..CodegenOptions::default()
+    })
+}
+
+/// Same as `codegen`, with custom `CodegenOptions`.
+pub fn codegen_with_options(module: &Module, options: CodegenOptions) -> String {
+    match generate(module, options, None) {
+        Ok(code) => code,
+        Err(Cancelled) => unreachable!("Code generation without a token can't be cancelled"),
+    }
+}
+
+/// Same as `codegen_with_options`, but stops with `Err(Cancelled)` once
+/// `token` is cancelled, checked before every statement of the module.
+pub fn try_codegen_with_options(module: &Module, options: CodegenOptions, token: &CancellationToken) -> Result<String, Cancelled> {
+    generate(module, options, Some(token))
+}
+
+/// Same as `codegen_with_options`, but gives the code as an `Output`, timed
+/// as `"codegen"`, to be chained after parsing and transforms.
+pub fn codegen_output<'ast>(module: &Module, options: CodegenOptions) -> Output<'ast, String> {
+    let start = Instant::now();
+    let code = codegen_with_options(module, options);
+
+    Output::new(code, module.features()).with_timing("codegen", start.elapsed())
+}
+
+fn generate(module: &Module, options: CodegenOptions, token: Option<&CancellationToken>) -> Result<String, Cancelled> {
+    let check = || token.map_or(Ok(()), CancellationToken::check);
+
+    let comments = module.comments();
+    let preserved: Vec<Node<&str>> = comments.iter().copied().filter(|comment| {
+        options.preserve_comments.iter().any(|pattern| comment.item.contains(pattern))
+    }).collect();
+    let mut preserved = &preserved[..];
+
+    if options.minify {
+        let mut gen = MinifyingGenerator::new(options);
+
+        write_comment(&mut gen, options.banner);
 
         for statement in module.body() {
+            check()?;
+            write_comments(&mut gen, &mut preserved, statement.end);
             gen.write(statement);
         }
 
-        gen.consume()
+        write_comments(&mut gen, &mut preserved, u32::MAX);
+
+        Ok(finish(gen.consume(), options))
     } else {
-        let mut gen = PrettyGenerator::new();
+        let mut gen = PrettyGenerator::new(options);
         let mut body = module.body().iter();
 
-        gen.write(&body.next().map(|s| *s));
+        write_comment(&mut gen, options.banner);
+
+        check()?;
+
+        if let Some(statement) = body.next() {
+            write_comments(&mut gen, &mut preserved, statement.end);
+            gen.write(statement);
+        }
 
         for statement in body {
+            check()?;
             gen.new_line();
+            write_comments(&mut gen, &mut preserved, statement.end);
             gen.write(statement);
         }
 
-        gen.consume()
+        if !preserved.is_empty() && !module.body().is_empty() {
+            gen.new_line();
+        }
+
+        write_comments(&mut gen, &mut preserved, u32::MAX);
+
+        Ok(finish(gen.consume(), options))
+    }
+}
+
+/// Apply the `line_ending`, `final_newline` and `bom` options to the code.
+fn finish(code: String, options: CodegenOptions) -> String {
+    let mut code = match (options.line_ending, code.contains('\r')) {
+        (LineEnding::Lf, false) => code,
+        (ending, _) => normalize_line_endings(&code, ending),
+    };
+
+    if options.final_newline && !code.is_empty() && !code.ends_with('\n') {
+        code.push_str(line_break(options.line_ending));
+    }
+
+    if options.bom {
+        code.insert(0, '\u{feff}');
+    }
+
+    code
+}
+
+/// Replace every `\n`, `\r\n` and `\r` of the code with the line ending.
+fn normalize_line_endings(code: &str, ending: LineEnding) -> String {
+    let mut normalized = String::with_capacity(code.len());
+    let mut lines = code.split('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        for (index, part) in line.split('\r').enumerate() {
+            if index > 0 {
+                normalized.push_str(line_break(ending));
+            }
+            normalized.push_str(part);
+        }
+
+        if lines.peek().is_some() {
+            normalized.push_str(line_break(ending));
+        }
+    }
+
+    normalized
+}
+
+#[inline]
+fn line_break(ending: LineEnding) -> &'static str {
+    match ending {
+        LineEnding::Lf => "\n",
+        LineEnding::CrLf => "\r\n",
+    }
+}
+
+/// Write the comment on its own line.
+#[inline]
+fn write_comment<G: Generator>(gen: &mut G, comment: Option<&str>) {
+    if let Some(comment) = comment {
+        gen.write_bytes(comment.as_bytes());
+        gen.write_byte(b'\n');
+    }
+}
+
+/// Write the preserved comments starting before `end`, taking them off
+/// the list.
+#[inline]
+fn write_comments<G: Generator>(gen: &mut G, comments: &mut &[Node<&str>], end: u32) {
+    while let Some((comment, rest)) = comments.split_first() {
+        if comment.start >= end {
+            break;
+        }
+
+        write_comment(gen, Some(comment.item));
+        *comments = rest;
     }
 }
 
@@ -331,13 +808,14 @@ fn extend_from_slice(dst: &mut Vec<u8>, src: &[u8]) {
     dst.reserve(src_len);
 
     unsafe {
-        // We would have failed if `reserve` overflowed
-        dst.set_len(dst_len + src_len);
-
         ::std::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dst.as_mut_ptr().offset(dst_len as isize),
             src_len);
+
+        // We would have failed if `reserve` overflowed, the bytes up to
+        // the new length are initialized by the copy
+        dst.set_len(dst_len + src_len);
     }
 }
 
@@ -348,6 +826,16 @@ fn assert_min(source: &str, expected: &str) {
     let module = parse(source).unwrap();
 
     assert_eq!(codegen(&module, true).as_str(), expected);
+
+    // The size model agrees with the minified output
+    assert_eq!(module.body().iter().map(minified_size).sum::<usize>(), expected.len());
+
+    // And so does the cached one, when measuring the same nodes again
+    let mut cache = SizeCache::default();
+
+    for _ in 0..2 {
+        assert_eq!(module.body().iter().map(|statement| cache.size(statement)).sum::<usize>(), expected.len());
+    }
 }
 
 #[cfg(test)]
@@ -358,3 +846,100 @@ fn assert_pretty(source: &str, expected: &str) {
 
     assert_eq!(codegen(&module, false).as_str(), expected);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    #[test]
+    fn cancelled_codegen() {
+        let module = parse("a; b;").unwrap();
+        let token = CancellationToken::new();
+        let options = CodegenOptions { minify: true, ..CodegenOptions::default() };
+
+        assert_eq!(try_codegen_with_options(&module, options, &token), Ok("a;b;".to_owned()));
+
+        token.cancel();
+
+        assert_eq!(try_codegen_with_options(&module, options, &token), Err(Cancelled));
+    }
+
+    #[test]
+    fn banner() {
+        let module = parse("a; b;").unwrap();
+        let options = CodegenOptions {
+            minify: true,
+            banner: Some("/* Generated */"),
+            ..CodegenOptions::default()
+        };
+
+        assert_eq!(codegen_with_options(&module, options), "/* Generated */\na;b;");
+        assert_eq!(codegen_with_options(&module, CodegenOptions { minify: false, ..options }), "/* Generated */\na;\nb;");
+    }
+
+    #[test]
+    fn preserved_comments() {
+        let source = "/*! lib v1 */\n// plain\na;\nfunction f() {\n    /** @license MIT */\n    /* dropped */\n}\nb; //! end";
+        let module = parse(source).unwrap();
+        let options = CodegenOptions {
+            minify: true,
+            preserve_comments: LICENSE_COMMENTS,
+            ..CodegenOptions::default()
+        };
+
+        assert_eq!(codegen_with_options(&module, options), "/*! lib v1 */\na;/** @license MIT */\nfunction f(){}b;//! end\n");
+        assert_eq!(codegen(&module, true), "a;function f(){}b;");
+
+        let options = CodegenOptions { minify: false, ..options };
+
+        assert_eq!(codegen_with_options(&module, options), "/*! lib v1 */\na;\n/** @license MIT */\nfunction f() {}\nb;\n//! end\n");
+    }
+
+    #[test]
+    fn line_endings() {
+        let module = parse("/*! a\r\nb\rc */\nfunction f() {\r\n    return `x\r\ny\nz`;\r\n}").unwrap();
+        let options = CodegenOptions {
+            preserve_comments: LICENSE_COMMENTS,
+            line_ending: LineEnding::CrLf,
+            ..CodegenOptions::default()
+        };
+
+        assert_eq!(codegen_with_options(&module, options), "/*! a\r\nb\r\nc */\r\nfunction f() {\r\n    return `x\r\ny\r\nz`;\r\n}");
+
+        let options = CodegenOptions { line_ending: LineEnding::Lf, ..options };
+
+        assert_eq!(codegen_with_options(&module, options), "/*! a\nb\nc */\nfunction f() {\n    return `x\ny\nz`;\n}");
+    }
+
+    #[test]
+    fn final_newline_and_bom() {
+        let module = parse("\u{feff}a; b;").unwrap();
+        let options = CodegenOptions {
+            minify: true,
+            final_newline: true,
+            ..CodegenOptions::default()
+        };
+
+        assert_eq!(codegen(&module, true), "a;b;");
+        assert_eq!(codegen_with_options(&module, options), "a;b;\n");
+        assert_eq!(codegen_with_options(&module, CodegenOptions { line_ending: LineEnding::CrLf, bom: true, ..options }), "\u{feff}a;b;\r\n");
+        assert_eq!(codegen_with_options(&parse("").unwrap(), options), "");
+
+        // Output ending with a kept comment already ends with a line break
+        let module = parse("a; //! end").unwrap();
+
+        assert_eq!(codegen_with_options(&module, CodegenOptions { preserve_comments: LICENSE_COMMENTS, ..options }), "a;//! end\n");
+    }
+
+    #[test]
+    fn codegen_to_an_output() {
+        let parsed = ratel::parse_output("017; () => {};", ratel::ParserOptions::default());
+        let output = parsed.as_borrowed().and_then(|module| codegen_output(module, CodegenOptions { minify: true, ..CodegenOptions::default() }));
+
+        assert_eq!(output.value, "017;()=>{};");
+        assert_eq!(output.diagnostics.len(), 1);
+        assert_eq!(output.timings.last().map(|timing| timing.name.as_str()), Some("codegen"));
+        assert_eq!(output.into_result(), Ok("017;()=>{};".to_owned()));
+    }
+}
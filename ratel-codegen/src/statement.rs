@@ -1,4 +1,4 @@
-use ratel::ast::{Statement, Declarator, DeclarationKind};
+use ratel::ast::{Statement, Expression, Declarator, DeclarationKind};
 use ratel::ast::statement::*;
 
 use {ToCode, Generator};
@@ -40,7 +40,9 @@ impl<'ast, G: Generator> ToCode<G> for Statement<'ast> {
             Function(ref function)       => gen.write(function),
             Class(ref class)             => gen.write(class),
             Continue(ref cont)           => gen.write(cont),
-            Switch(ref switch)           => gen.write(switch)
+            Switch(ref switch)           => gen.write(switch),
+            Import(ref import)           => gen.write(import),
+            Export(ref export)           => gen.write(export),
         }
     }
 }
@@ -67,7 +69,7 @@ impl<'ast, G: Generator> ToCode<G> for Declarator<'ast> {
             gen.write_pretty(b' ');
             gen.write_byte(b'=');
             gen.write_pretty(b' ');
-            gen.write(init);
+            gen.write_expression(init, 1);
         }
     }
 }
@@ -186,13 +188,29 @@ impl<'ast, G: Generator> ToCode<G> for ForInit<'ast> {
     }
 }
 
+/// Write the head of a `for` loop, in parentheses if the `expression`
+/// isn't `allowed` there.
+#[inline]
+fn write_for_init<'ast, G: Generator>(gen: &mut G, init: &ForInit<'ast>, allowed: fn(&Expression<'ast>) -> bool) {
+    match *init {
+        ForInit::Expression(ref expression) if !allowed(expression) => {
+            gen.write_byte(b'(');
+            gen.write(expression);
+            gen.write_byte(b')');
+        },
+        _ => gen.write(init),
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for ForStatement<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         gen.write_bytes(b"for");
         gen.write_pretty(b' ');
         gen.write_byte(b'(');
-        gen.write(&self.init);
+        if let Some(ref init) = self.init {
+            write_for_init(gen, init, Expression::is_allowed_as_for_init);
+        }
         gen.write_byte(b';');
         gen.write_pretty(b' ');
         gen.write(&self.test);
@@ -211,7 +229,7 @@ impl<'ast, G: Generator> ToCode<G> for ForInStatement<'ast> {
         gen.write_bytes(b"for");
         gen.write_pretty(b' ');
         gen.write_byte(b'(');
-        gen.write(&self.left);
+        write_for_init(gen, &self.left, Expression::is_allowed_as_for_init);
         gen.write_bytes(b" in ");
         gen.write(&self.right);
         gen.write_byte(b')');
@@ -226,7 +244,7 @@ impl<'ast, G: Generator> ToCode<G> for ForOfStatement<'ast> {
         gen.write_bytes(b"for");
         gen.write_pretty(b' ');
         gen.write_byte(b'(');
-        gen.write(&self.left);
+        write_for_init(gen, &self.left, Expression::is_allowed_as_for_of_left);
         gen.write_bytes(b" of ");
         gen.write(&self.right);
         gen.write_byte(b')');
@@ -309,9 +327,173 @@ impl<'ast, G: Generator> ToCode<G> for SwitchCase<'ast> {
     }
 }
 
+impl<'ast, G: Generator> ToCode<G> for ImportStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        let mut braced = false;
+
+        gen.write_bytes(b"import");
+
+        for (index, specifier) in self.specifiers.iter().enumerate() {
+            if index > 0 {
+                gen.write_byte(b',');
+            }
+
+            match specifier.item {
+                ImportSpecifier::Default(local) => {
+                    gen.write_byte(b' ');
+                    gen.write(&local);
+                },
+                ImportSpecifier::Namespace(local) => {
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'*');
+                    gen.write_pretty(b' ');
+                    gen.write_bytes(b"as ");
+                    gen.write(&local);
+                },
+                ImportSpecifier::Named { imported, local } => {
+                    gen.write_pretty(b' ');
+
+                    if !braced {
+                        braced = true;
+                        gen.write_byte(b'{');
+                        gen.write_pretty(b' ');
+                    }
+
+                    gen.write(&imported);
+
+                    if imported.item != local.item {
+                        gen.write_bytes(b" as ");
+                        gen.write(&local);
+                    }
+                },
+            }
+        }
+
+        if braced {
+            gen.write_pretty(b' ');
+            gen.write_byte(b'}');
+            gen.write_pretty(b' ');
+            gen.write_bytes(b"from");
+        } else if !self.specifiers.is_empty() {
+            gen.write_bytes(b" from");
+        }
+
+        gen.write_pretty(b' ');
+        gen.write(&self.source.raw);
+        gen.write_byte(b';');
+    }
+}
+
+/// Check whether the expression of `export default` starts with `function`
+/// or `class`, and would thus be read as a declaration without parentheses.
+#[inline]
+fn starts_with_declaration(expression: &Expression) -> bool {
+    let mut expression = expression;
+
+    loop {
+        match *expression {
+            Expression::Function(_) | Expression::Class(_) => return true,
+            _ => {},
+        }
+
+        match expression.left() {
+            Some(left) => expression = left,
+            None       => return false,
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ExportStatement<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        use ratel::ast::statement::ExportStatement::*;
+
+        gen.write_bytes(b"export");
+
+        match *self {
+            Declaration(ref declaration) => {
+                gen.write_byte(b' ');
+                gen.write(declaration);
+            },
+            DefaultDeclaration(ref declaration) if matches!(declaration.item, Expression::Function(_) | Expression::Class(_)) => {
+                gen.write_bytes(b" default ");
+                gen.write(declaration);
+            },
+            DefaultDeclaration(ref expression) | Default(ref expression) => {
+                gen.write_bytes(b" default");
+
+                if starts_with_declaration(expression) {
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'(');
+                    gen.write(expression);
+                    gen.write_byte(b')');
+                } else {
+                    gen.write_byte(b' ');
+                    gen.write_expression(expression, 1);
+                }
+
+                gen.write_byte(b';');
+            },
+            Named { ref specifiers, ref source } => {
+                gen.write_pretty(b' ');
+
+                if specifiers.is_empty() {
+                    gen.write_bytes(b"{}");
+                } else {
+                    gen.write_byte(b'{');
+                    gen.write_pretty(b' ');
+                    gen.write_list(specifiers);
+                    gen.write_pretty(b' ');
+                    gen.write_byte(b'}');
+                }
+
+                if let Some(ref source) = *source {
+                    gen.write_pretty(b' ');
+                    gen.write_bytes(b"from");
+                    gen.write_pretty(b' ');
+                    gen.write(&source.raw);
+                }
+
+                gen.write_byte(b';');
+            },
+            All { ref exported, ref source } => {
+                gen.write_pretty(b' ');
+                gen.write_byte(b'*');
+
+                if let Some(ref exported) = *exported {
+                    gen.write_pretty(b' ');
+                    gen.write_bytes(b"as ");
+                    gen.write(exported);
+                    gen.write_byte(b' ');
+                } else {
+                    gen.write_pretty(b' ');
+                }
+
+                gen.write_bytes(b"from");
+                gen.write_pretty(b' ');
+                gen.write(&source.raw);
+                gen.write_byte(b';');
+            },
+        }
+    }
+}
+
+impl<'ast, G: Generator> ToCode<G> for ExportSpecifier<'ast> {
+    #[inline]
+    fn to_code(&self, gen: &mut G) {
+        gen.write(&self.local);
+
+        if self.local.item != self.exported.item {
+            gen.write_bytes(b" as ");
+            gen.write(&self.exported);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use assert_min;
+    use {assert_min, assert_pretty};
 
     #[test]
     fn block_statement() {
@@ -325,6 +507,21 @@ mod test {
         assert_min("foo: bar;", "foo:bar;");
     }
 
+    #[test]
+    fn expression_statement_leading_token() {
+        assert_min("({});", "({});");
+        assert_min("({}).foo;", "({}.foo);");
+        assert_min("({}).foo = bar;", "({}.foo=bar);");
+        assert_min("(function () {})();", "(function(){}());");
+        assert_min("(function () {}).call(this);", "(function(){}.call(this));");
+        assert_min("(class {}).foo, bar;", "(class{}.foo,bar);");
+        assert_min("({} ? a : b);", "({}?a:b);");
+        assert_min("({})`foo`;", "({}`foo`);");
+        assert_min("(function () {}) + 1;", "(function(){}+1);");
+        assert_min("foo({});", "foo({});");
+        assert_min("1 + function () {};", "1+function(){};");
+    }
+
     #[test]
     fn function_statement() {
         assert_min("function foo() {}", "function foo(){}");
@@ -379,4 +576,122 @@ mod test {
         assert_min("for (foo of bar){}", "for(foo of bar){}");
         assert_min("for (let foo of bar){}", "for(let foo of bar){}");
     }
+
+    #[test]
+    fn for_head_leading_token() {
+        assert_min("for ((async) of a) {}", "for((async) of a){}");
+        assert_min("for ((async)() of a) {}", "for(async() of a){}");
+        assert_min("for (async.foo of a) {}", "for(async.foo of a){}");
+        assert_min("for (async in a) {}", "for(async in a){}");
+        assert_min("for ([a] of b) {}", "for([a] of b){}");
+    }
+
+    #[test]
+    fn for_head_let() {
+        use ratel::parse;
+        use ratel::ast::{Loc, Statement, Expression};
+        use ratel::ast::expression::{MemberExpression, ComputedMemberExpression};
+        use ratel::ast::statement::*;
+        use codegen;
+
+        // `let` can't be parsed as an identifier there, but can be the result of a transform
+        let module = parse("for (foo of a) {} for (foo.bar of a) {} for (foo[0] in a) {} for (foo[0] = 1;;) {}").unwrap();
+        for statement in module.body() {
+            let left = match statement.item {
+                Statement::ForOf(ForOfStatement { left, .. }) | Statement::ForIn(ForInStatement { left, .. }) => left,
+                Statement::For(ForStatement { init: Some(init), .. }) => init,
+                _ => continue,
+            };
+
+            let expression = match left.item {
+                ForInit::Expression(ref expression) => expression,
+                _ => continue,
+            };
+
+            let target = match expression.item {
+                Expression::Member(MemberExpression { ref object, .. }) => object,
+                Expression::ComputedMember(ComputedMemberExpression { ref object, .. }) => object,
+                Expression::Binary(ref binary) => match binary.left.item {
+                    Expression::ComputedMember(ComputedMemberExpression { ref object, .. }) => object,
+                    _ => continue,
+                },
+                _ => expression,
+            };
+
+            target.set(module.arena().alloc(Loc::new(0, 0, Expression::Identifier("let"))));
+        }
+
+        assert_eq!(codegen(&module, true).as_str(), "for((let) of a){}for((let.bar) of a){}for((let[0]) in a){}for((let[0]=1);;){}");
+    }
+
+    #[test]
+    fn import_statement() {
+        assert_min("import 'a';", "import'a';");
+        assert_min("import a from 'b';", "import a from'b';");
+        assert_min("import * as a from 'b';", "import*as a from'b';");
+        assert_min("import a, * as b from 'c';", "import a,*as b from'c';");
+        assert_min("import { a, b as c } from 'd';", "import{a,b as c}from'd';");
+        assert_min("import a, { default as b } from 'c';", "import a,{default as b}from'c';");
+        assert_min("import {} from 'a';", "import'a';");
+    }
+
+    #[test]
+    fn import_statement_pretty() {
+        assert_pretty("import a, { b, c as d } from 'e';", "import a, { b, c as d } from 'e';");
+        assert_pretty("import * as a from 'b';", "import * as a from 'b';");
+        assert_pretty("import 'a';", "import 'a';");
+    }
+
+    #[test]
+    fn export_statement() {
+        assert_min("export var a = 1;", "export var a=1;");
+        assert_min("export const a = 1, b = 2;", "export const a=1,b=2;");
+        assert_min("export function a() {}", "export function a(){}");
+        assert_min("export async function a() {}", "export async function a(){}");
+        assert_min("export class A {}", "export class A{}");
+        assert_min("export { a, b as c };", "export{a,b as c};");
+        assert_min("export { a as default } from 'b';", "export{a as default}from'b';");
+        assert_min("export {};", "export{};");
+        assert_min("export * from 'a';", "export*from'a';");
+        assert_min("export * as a from 'b';", "export*as a from'b';");
+    }
+
+    #[test]
+    fn export_default_statement() {
+        assert_min("export default function () {}", "export default function(){}");
+        assert_min("export default function a() {}", "export default function a(){}");
+        assert_min("export default async function () {}", "export default async function(){}");
+        assert_min("export default class extends A {}", "export default class extends A{}");
+        assert_min("export default a;", "export default a;");
+        assert_min("export default { a: 1 };", "export default {a:1};");
+        assert_min("export default (a, b);", "export default (a,b);");
+        assert_min("export default (function () {});", "export default(function(){});");
+        assert_min("export default (function () {}).call();", "export default(function(){}.call());");
+        assert_min("export default (class {}).a;", "export default(class{}.a);");
+    }
+
+    #[test]
+    fn export_statement_pretty() {
+        assert_pretty("export { a, b as c } from 'd';", "export { a, b as c } from 'd';");
+        assert_pretty("export * from 'a';", "export * from 'a';");
+        assert_pretty("export default a;", "export default a;");
+    }
+
+    #[test]
+    fn reexports_round_trip() {
+        use ratel::parse;
+        use codegen;
+
+        let source = "export { default } from 'a'; export { b as c, if as d, e as default } from './b'; export * as f from '../c'; export * from 'g';";
+        let module = parse(source).unwrap();
+
+        for &minify in &[true, false] {
+            let output = codegen(&module, minify);
+
+            assert_eq!(parse(&output).unwrap().body(), module.body(), "{}", output);
+        }
+
+        assert_min("export { default } from 'a';", "export{default}from'a';");
+        assert_min("export { if as a } from 'b';", "export{if as a}from'b';");
+    }
 }
@@ -0,0 +1,492 @@
+//! Parser server, answering JSON-RPC 2.0 requests so that editors and build
+//! tools can keep a single process around instead of spawning one per file.
+//!
+//! Messages are newline delimited: every request and every response is a
+//! single line of JSON. Supported methods:
+//!
+//! - `parse`, params `{ "source": string, "dialect"?: "estree" | "shift" | "babel",
+//!   "ids"?: bool }`, returns the AST. With `ids`, every node gets a `nodeId`
+//!   derived from its content, see `ratel::StableIds`.
+//! - `transform`, params `{ "source": string, "minify"?: bool, "passes"?: [string] }`,
+//!   runs the built-in passes of `ratel_transformer::pass::builtin` named
+//!   in `passes`, once each and in order, and returns the generated code.
+//! - `shutdown`, returns `null` and stops the server.
+//!
+//! Both `parse` and `transform` take an optional `sourceMap`, the source map
+//! of `source` if it's compiled output, as an object or a JSON string. Errors
+//! are then given the `original` position they map to, as
+//! `{ "source": string, "line": number, "column": number, "name"?: string }`
+//! with lines starting at 1 and columns at 0.
+//!
+//! Source errors are also given as a `diagnostic`, see
+//! `ratel::diagnostic::Diagnostic`, as `{ "severity": string, "code": string,
+//! "message": string, "labels": [{ "start": number, "end": number,
+//! "primary": bool, "message": string }] }`, and `display` renders it with
+//! the lines of `source` it points at.
+//!
+//! A request that panics fails with an `INTERNAL_ERROR`, the panic message
+//! as `data`, and the server goes on with the next one.
+
+extern crate ratel;
+extern crate ratel_codegen;
+extern crate ratel_transformer;
+#[macro_use]
+extern crate serde_json;
+
+use std::io::{self, BufRead, Write};
+use std::panic;
+use serde_json::Value;
+use ratel::error::Error;
+use ratel::{Dialect, InDialect, Estree, Babel, Shift, StableIds};
+use ratel::sourcemap::SourceMap;
+use ratel_transformer::pass::{PassManager, Mode};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Error code for sources that failed to parse, `data` holds the errors.
+pub const SOURCE_ERROR: i64 = -32000;
+
+/// Outcome of a single request.
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    /// Line to write back to the client
+    Reply(String),
+
+    /// Notifications don't get a reply
+    Silent,
+
+    /// Write the reply and stop serving
+    Shutdown(String),
+}
+
+struct Failure {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl Failure {
+    fn new(code: i64, message: &str) -> Self {
+        Failure {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+type Result<T> = ::std::result::Result<T, Failure>;
+
+fn reply(id: &Value, result: Result<Value>) -> String {
+    let response = match result {
+        Ok(result) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result,
+        }),
+        Err(failure) => {
+            let mut error = json!({
+                "code": failure.code,
+                "message": failure.message,
+            });
+
+            if let Some(data) = failure.data {
+                error["data"] = data;
+            }
+
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": error,
+            })
+        }
+    };
+
+    response.to_string()
+}
+
+fn source_errors(errors: Vec<Error>, source: &str, map: Option<&SourceMap>) -> Failure {
+    let errors = errors.into_iter().map(|err| {
+        let diagnostic = err.diagnostic();
+
+        let mut error = json!({
+            "start": err.start,
+            "end": err.end,
+            "message": format!("{:?}", err),
+            "display": diagnostic.display(source).to_string(),
+            "diagnostic": diagnostic,
+        });
+
+        if let Some(original) = map.and_then(|map| map.lookup_offset(source, err.start)) {
+            let mut position = json!({
+                "source": original.source,
+                "line": original.line + 1,
+                "column": original.column,
+            });
+
+            if let Some(name) = original.name {
+                position["name"] = name.into();
+            }
+
+            error["original"] = position;
+        }
+
+        error
+    }).collect::<Vec<_>>();
+
+    Failure {
+        code: SOURCE_ERROR,
+        message: "Source failed to parse".into(),
+        data: Some(Value::Array(errors)),
+    }
+}
+
+fn source(params: &Value) -> Result<&str> {
+    params["source"].as_str().ok_or_else(|| Failure::new(INVALID_PARAMS, "Missing `source` string"))
+}
+
+fn source_map(params: &Value) -> Result<Option<SourceMap>> {
+    let map = match params["sourceMap"] {
+        Value::Null => return Ok(None),
+        Value::String(ref map) => serde_json::from_str(map),
+        ref map => serde_json::from_value(map.clone()),
+    };
+
+    map.map(Some).map_err(|err| Failure::new(INVALID_PARAMS, &format!("Invalid `sourceMap`: {}", err)))
+}
+
+fn parse_source(params: &Value) -> Result<(&str, ratel::Module<'_>)> {
+    let source = source(params)?;
+    let map = source_map(params)?;
+    let module = ratel::parse(source).map_err(|errors| source_errors(errors, source, map.as_ref()))?;
+
+    Ok((source, module))
+}
+
+fn in_dialect<D: Dialect>(module: &ratel::Module, source: &str, dialect: D, ids: bool) -> serde_json::Result<Value> {
+    match ids {
+        true  => serde_json::to_value(InDialect(module, &StableIds::new(source, dialect))),
+        false => serde_json::to_value(InDialect(module, &dialect)),
+    }
+}
+
+fn parse(params: &Value) -> Result<Value> {
+    let (source, module) = parse_source(params)?;
+    let ids = params["ids"].as_bool().unwrap_or(false);
+
+    let value = match params["dialect"].as_str().unwrap_or("estree") {
+        "estree" => in_dialect(&module, source, Estree, ids),
+        "shift"  => in_dialect(&module, source, Shift, ids),
+        "babel"  => in_dialect(&module, source, Babel::new(source), ids),
+        _        => return Err(Failure::new(INVALID_PARAMS, "Unknown `dialect`")),
+    };
+
+    value.map_err(|err| Failure::new(SOURCE_ERROR, &err.to_string()))
+}
+
+fn transform(params: &Value) -> Result<Value> {
+    let minify = params["minify"].as_bool().unwrap_or(false);
+    let passes = match params["passes"] {
+        Value::Null => Vec::new(),
+        Value::Array(ref passes) => passes.iter().map(|pass| {
+            pass.as_str().ok_or_else(|| Failure::new(INVALID_PARAMS, "Invalid `passes`, expected strings"))
+        }).collect::<Result<Vec<_>>>()?,
+        _ => return Err(Failure::new(INVALID_PARAMS, "Invalid `passes`, expected an array")),
+    };
+    let (_, module) = parse_source(params)?;
+    let mut manager = PassManager::new(Mode::Once);
+
+    for pass in passes {
+        if !manager.add_builtin(pass) {
+            return Err(Failure::new(INVALID_PARAMS, &format!("Unknown pass `{}`", pass)));
+        }
+    }
+
+    manager.run(&module);
+
+    Ok(Value::String(ratel_codegen::codegen(&module, minify)))
+}
+
+/// Handle a single line of input.
+pub fn handle(line: &str) -> Response {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(_) => return Response::Reply(reply(&Value::Null, Err(Failure::new(PARSE_ERROR, "Invalid JSON")))),
+    };
+
+    let id = request.get("id").cloned();
+    let method = match request["method"].as_str() {
+        Some(method) if request["jsonrpc"] == "2.0" => method,
+        _ => {
+            let id = id.unwrap_or(Value::Null);
+            return Response::Reply(reply(&id, Err(Failure::new(INVALID_REQUEST, "Invalid request"))));
+        }
+    };
+
+    let params = &request["params"];
+    let result = panic::catch_unwind(|| match method {
+        "parse"     => parse(params),
+        "transform" => transform(params),
+        "shutdown"  => Ok(Value::Null),
+        _           => Err(Failure::new(METHOD_NOT_FOUND, "Method not found")),
+    });
+
+    // A panicking request fails on its own, the server keeps serving
+    let result = result.unwrap_or_else(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned());
+
+        Err(Failure {
+            code: INTERNAL_ERROR,
+            message: "Internal error".into(),
+            data: message.map(Value::String),
+        })
+    });
+
+    match id {
+        None if method == "shutdown" => Response::Shutdown(String::new()),
+        None => Response::Silent,
+        Some(ref id) if method == "shutdown" => Response::Shutdown(reply(id, result)),
+        Some(ref id) => Response::Reply(reply(id, result)),
+    }
+}
+
+/// Answer requests read from `input` until it's closed or a `shutdown`
+/// request comes in.
+pub fn serve<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match handle(&line) {
+            Response::Reply(reply) => writeln!(output, "{}", reply)?,
+            Response::Silent => continue,
+            Response::Shutdown(reply) => {
+                if !reply.is_empty() {
+                    writeln!(output, "{}", reply)?;
+                }
+                return output.flush();
+            }
+        }
+
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Listen on a unix socket at `path`, serving every connection on its own
+/// thread. A `shutdown` request only closes the connection it came from.
+#[cfg(unix)]
+pub fn serve_unix<P: AsRef<::std::path::Path>>(path: P) -> io::Result<()> {
+    use std::io::BufReader;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    let listener = UnixListener::bind(path)?;
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+
+        thread::spawn(move || {
+            let input = match stream.try_clone() {
+                Ok(input) => BufReader::new(input),
+                Err(_) => return,
+            };
+
+            let _ = serve(input, stream);
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn request(line: &str) -> Value {
+        match handle(line) {
+            Response::Reply(reply) | Response::Shutdown(reply) => serde_json::from_str(&reply).unwrap(),
+            Response::Silent => panic!("Expected a reply to {}", line),
+        }
+    }
+
+    #[test]
+    fn parses() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"foo;"}}"#);
+
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["type"], "Program");
+        assert_eq!(response["result"]["body"][0]["expression"]["name"], "foo");
+    }
+
+    #[test]
+    fn parses_dialects() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"foo;","dialect":"shift"}}"#);
+
+        assert_eq!(response["result"]["type"], "Script");
+
+        let response = request(r#"{"jsonrpc":"2.0","id":2,"method":"parse","params":{"source":"foo;","dialect":"babel"}}"#);
+
+        assert_eq!(response["result"]["type"], "File");
+
+        let response = request(r#"{"jsonrpc":"2.0","id":3,"method":"parse","params":{"source":"foo;","dialect":"nope"}}"#);
+
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn parses_with_ids() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"foo; foo;","ids":true}}"#);
+        let body = &response["result"]["body"];
+        let first = body[0]["nodeId"].as_str().unwrap();
+
+        assert_eq!(body[1]["nodeId"], format!("{}-1", first));
+
+        let response = request(r#"{"jsonrpc":"2.0","id":2,"method":"parse","params":{"source":"foo;"}}"#);
+
+        assert!(response["result"]["body"][0].get("nodeId").is_none());
+    }
+
+    #[test]
+    fn transforms() {
+        let response = request(r#"{"jsonrpc":"2.0","id":"a","method":"transform","params":{"source":"let  foo = 1;","minify":true}}"#);
+
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["result"], "let foo=1;");
+    }
+
+    #[test]
+    fn transforms_with_passes() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"transform","params":{"source":"let f = () => 1;","minify":true,"passes":["es2015"]}}"#);
+
+        assert_eq!(response["result"], "let f=function(){return 1;};");
+
+        let response = request(r#"{"jsonrpc":"2.0","id":2,"method":"transform","params":{"source":"a;","passes":["es2015","fold"]}}"#);
+
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(response["error"]["message"], "Unknown pass `fold`");
+
+        let response = request(r#"{"jsonrpc":"2.0","id":3,"method":"transform","params":{"source":"a;","passes":"es2015"}}"#);
+
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn reports_source_errors() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"foo +;"}}"#);
+
+        assert_eq!(response["error"]["code"], SOURCE_ERROR);
+        assert_eq!(response["error"]["data"][0]["start"], 5);
+        assert_eq!(response["error"]["data"][0]["end"], 6);
+        assert_eq!(response["error"]["data"][0]["diagnostic"]["code"], "E0001");
+    }
+
+    #[test]
+    fn reports_related_spans() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"'use strict'; function f(a, a) {}"}}"#);
+        let error = &response["error"]["data"][0];
+
+        assert_eq!(error["diagnostic"]["severity"], "error");
+        assert_eq!(error["diagnostic"]["labels"], json!([
+            { "start": 28, "end": 29, "primary": true, "message": "bound again here" },
+            { "start": 25, "end": 26, "primary": false, "message": "first bound here" },
+        ]));
+        assert!(error["display"].as_str().unwrap().contains("- first bound here"));
+    }
+
+    #[test]
+    fn reports_original_positions() {
+        let map = r#"{"version":3,"sources":["a.ts"],"names":["foo"],"mappings":";AAEIA,GAAG"}"#;
+        let line = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "transform",
+            "params": { "source": "a;\nfoo +;", "sourceMap": map },
+        });
+        let response = request(&line.to_string());
+
+        assert_eq!(response["error"]["data"][0]["start"], 8);
+        assert_eq!(response["error"]["data"][0]["original"], json!({
+            "source": "a.ts",
+            "line": 3,
+            "column": 7,
+        }));
+
+        let line = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "parse",
+            "params": { "source": "foo +;", "sourceMap": { "version": 3, "sources": ["a.ts"], "mappings": "AAAA" } },
+        });
+        let response = request(&line.to_string());
+
+        assert_eq!(response["error"]["data"][0]["original"]["line"], 1);
+        assert_eq!(response["error"]["data"][0]["original"]["column"], 0);
+    }
+
+    #[test]
+    fn reports_invalid_source_maps() {
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"foo;","sourceMap":{"version":3,"sources":[],"mappings":"AAAA"}}}"#);
+
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn reports_invalid_requests() {
+        assert_eq!(request("{")["error"]["code"], PARSE_ERROR);
+        assert_eq!(request(r#"{"id":1,"method":"parse"}"#)["error"]["code"], INVALID_REQUEST);
+        assert_eq!(request(r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#)["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{}}"#)["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn reports_panics_as_internal_errors() {
+        // The ESTree serializer doesn't handle `new` with a sequence operand
+        let response = request(r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"source":"new (a, b);"}}"#);
+
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+        assert_eq!(response["error"]["message"], "Internal error");
+
+        let response = request(r#"{"jsonrpc":"2.0","id":2,"method":"parse","params":{"source":"a.\u00e9;"}}"#);
+
+        assert_eq!(response["result"]["body"][0]["expression"]["property"]["name"], "\u{e9}");
+    }
+
+    #[test]
+    fn ignores_notifications() {
+        assert_eq!(handle(r#"{"jsonrpc":"2.0","method":"parse","params":{"source":"foo;"}}"#), Response::Silent);
+    }
+
+    #[test]
+    fn serves_until_shutdown() {
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"transform","params":{"source":"foo"}}"#, "\n",
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":3,"method":"transform","params":{"source":"bar"}}"#, "\n",
+        );
+        let mut output = Vec::new();
+
+        serve(input.as_bytes(), &mut output).unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(output).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["result"], "foo;");
+        assert_eq!(lines[1]["id"], 2);
+        assert_eq!(lines[1]["result"], Value::Null);
+    }
+}
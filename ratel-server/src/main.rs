@@ -0,0 +1,32 @@
+extern crate ratel_server;
+
+use std::env;
+use std::io;
+use std::process;
+
+const USAGE: &str = "Usage: ratel-server [stdio | socket <path>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args[..] {
+        [] | ["stdio"] => {
+            let stdin = io::stdin();
+            let stdout = io::stdout();
+
+            ratel_server::serve(stdin.lock(), stdout.lock())
+        },
+        #[cfg(unix)]
+        ["socket", path] => ratel_server::serve_unix(path),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("ratel-server: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Files a `Session` reads its modules from. Implement it to read from
+/// anywhere else, such as a zip or tar archive.
+pub trait FileSystem {
+    /// Read the file at `path`, which has to be UTF-8.
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Check whether there is a file at `path`.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Check whether there is a directory at `path`.
+    fn is_dir(&self, path: &Path) -> bool;
+}
+
+impl<F: FileSystem + ?Sized> FileSystem for &F {
+    #[inline]
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        (**self).read_to_string(path)
+    }
+
+    #[inline]
+    fn is_file(&self, path: &Path) -> bool {
+        (**self).is_file(path)
+    }
+
+    #[inline]
+    fn is_dir(&self, path: &Path) -> bool {
+        (**self).is_dir(path)
+    }
+}
+
+/// The real file system, through `std::fs`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    #[inline]
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    #[inline]
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    #[inline]
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+}
+
+/// Files kept in memory on top of a `base` file system, shadowing the
+/// files of the same path, such as the unsaved buffers of an editor.
+/// Directories holding any of the files exist as well.
+#[derive(Debug, Clone, Default)]
+pub struct Overlay<F> {
+    base: F,
+    files: HashMap<PathBuf, String>,
+}
+
+impl<F: FileSystem> Overlay<F> {
+    pub fn new(base: F) -> Self {
+        Overlay {
+            base,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Set the contents of the file at `path`, returning the previous
+    /// contents it had in the overlay.
+    pub fn insert<P: Into<PathBuf>>(&mut self, path: P, contents: String) -> Option<String> {
+        self.files.insert(path.into(), contents)
+    }
+
+    /// Remove the file at `path` from the overlay, uncovering the file of
+    /// the `base` file system, if any.
+    pub fn remove(&mut self, path: &Path) -> Option<String> {
+        self.files.remove(path)
+    }
+
+    #[inline]
+    pub fn base(&self) -> &F {
+        &self.base
+    }
+}
+
+impl<F: FileSystem> FileSystem for Overlay<F> {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.files.get(path) {
+            Some(contents) => Ok(contents.clone()),
+            None => self.base.read_to_string(path),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || self.base.is_file(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.files.keys().any(|file| file != path && file.starts_with(path)) || self.base.is_dir(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn os_file_system() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert!(OsFileSystem.is_dir(manifest));
+        assert!(OsFileSystem.is_file(&manifest.join("Cargo.toml")));
+        assert!(OsFileSystem.read_to_string(&manifest.join("Cargo.toml")).unwrap().contains("ratel-session"));
+        assert!(OsFileSystem.read_to_string(&manifest.join("missing.js")).is_err());
+    }
+
+    #[test]
+    fn overlay_shadows_base() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut fs = Overlay::new(OsFileSystem);
+
+        assert_eq!(fs.insert(manifest.join("Cargo.toml"), "unsaved".into()), None);
+        assert_eq!(fs.insert(manifest.join("virtual/a.js"), "a;".into()), None);
+
+        assert_eq!(fs.read_to_string(&manifest.join("Cargo.toml")).unwrap(), "unsaved");
+        assert_eq!(fs.read_to_string(&manifest.join("virtual/a.js")).unwrap(), "a;");
+        assert!(fs.is_file(&manifest.join("virtual/a.js")));
+        assert!(fs.is_dir(&manifest.join("virtual")));
+        assert!(!fs.is_dir(&manifest.join("virtual/a.js")));
+        assert!(fs.is_dir(&manifest.join("src")));
+
+        assert_eq!(fs.remove(&manifest.join("Cargo.toml")), Some("unsaved".into()));
+        assert!(fs.read_to_string(&manifest.join("Cargo.toml")).unwrap().contains("ratel-session"));
+    }
+}
@@ -0,0 +1,287 @@
+//! Multi-file project session, reading modules through a `FileSystem` and
+//! building the dependency graph between them from their `require` calls,
+//! `import` and `export` declarations and `import()` expressions, as found
+//! by `ratel::scan_dependencies`.
+//!
+//! The file system is pluggable, so that the graph can be built from the
+//! real file system, from an `Overlay` holding the unsaved buffers of an
+//! editor on top of it, or from an archive. Specifiers are resolved to files
+//! by a `Resolver`, by default the `RelativeResolver`, or the `NodeResolver`
+//! following the resolution algorithm of Node.
+//!
+//! Specifiers the resolver finds no file for can be streamed to a
+//! `DiagnosticsSink` as warnings, as the modules are read.
+
+extern crate ratel;
+extern crate serde_json;
+
+pub mod fs;
+pub mod resolve;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use ratel::{scan_dependencies, DependencyKind};
+use ratel::diagnostic::{Diagnostic, DiagnosticsSink, Severity};
+
+pub use fs::{FileSystem, OsFileSystem, Overlay};
+pub use resolve::{Resolver, RelativeResolver, NodeResolver};
+
+/// Dependency of a module of a `Session`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Edge {
+    pub kind: DependencyKind,
+
+    /// Specifier as written in the source
+    pub specifier: String,
+
+    /// File the specifier refers to, `None` if the resolver didn't find any
+    pub resolved: Option<PathBuf>,
+}
+
+/// Module read by a `Session`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SessionModule {
+    /// Dependencies of the module, in source order
+    pub dependencies: Vec<Edge>,
+}
+
+/// Dependency graph of the modules read from a `FileSystem`, keyed by their
+/// paths with the `.` and `..` components removed.
+pub struct Session<F, R = RelativeResolver> {
+    fs: F,
+    resolver: R,
+    modules: BTreeMap<PathBuf, SessionModule>,
+
+    /// Receives the unresolved specifiers of every module read, see
+    /// `with_diagnostics_sink`
+    sink: Option<Box<dyn DiagnosticsSink>>,
+}
+
+impl<F: FileSystem> Session<F> {
+    pub fn new(fs: F) -> Self {
+        Session {
+            fs,
+            resolver: RelativeResolver,
+            modules: BTreeMap::new(),
+            sink: None,
+        }
+    }
+}
+
+impl<F: FileSystem, R: Resolver> Session<F, R> {
+    /// Resolve the specifiers of the modules read from now on with the
+    /// `resolver`.
+    pub fn with_resolver<S: Resolver>(self, resolver: S) -> Session<F, S> {
+        Session {
+            fs: self.fs,
+            resolver,
+            modules: self.modules,
+            sink: self.sink,
+        }
+    }
+
+    /// Report the specifiers the resolver finds no file for to the `sink`,
+    /// such as an `Arc` shared with the host, as a batch of warnings for
+    /// each module read from now on. Their spans are offsets in the source
+    /// of the module, which is named by the messages.
+    pub fn with_diagnostics_sink<S: DiagnosticsSink + 'static>(mut self, sink: S) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    #[inline]
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
+    /// Get the file system to change its files, such as an `Overlay`
+    /// when an editor buffer is edited, then `reload` the changed modules.
+    #[inline]
+    pub fn fs_mut(&mut self) -> &mut F {
+        &mut self.fs
+    }
+
+    /// Read the module at `path` and, transitively, every module it depends
+    /// on that isn't in the session yet. Fails on the first file that can't
+    /// be read, keeping the modules read before it.
+    pub fn add(&mut self, path: &Path) -> io::Result<()> {
+        let mut pending = vec![resolve::normalize(path)];
+
+        while let Some(path) = pending.pop() {
+            if self.modules.contains_key(&path) {
+                continue;
+            }
+
+            let module = self.read(&path)?;
+
+            pending.extend(module.dependencies.iter().rev().filter_map(|edge| edge.resolved.clone()));
+            self.modules.insert(path, module);
+        }
+
+        Ok(())
+    }
+
+    /// Read the module at `path` again after it changed, adding the new
+    /// modules it depends on. Modules only the old version depended on are
+    /// kept. If it can't be read anymore it's removed from the session.
+    pub fn reload(&mut self, path: &Path) -> io::Result<()> {
+        let path = resolve::normalize(path);
+
+        self.modules.remove(&path);
+        self.add(&path)
+    }
+
+    #[inline]
+    pub fn module(&self, path: &Path) -> Option<&SessionModule> {
+        self.modules.get(&resolve::normalize(path))
+    }
+
+    /// Iterate over all modules of the session, ordered by path.
+    pub fn modules(&self) -> impl Iterator<Item = (&Path, &SessionModule)> {
+        self.modules.iter().map(|(path, module)| (path.as_path(), module))
+    }
+
+    /// Get the paths of the modules depending on the module at `path`,
+    /// ordered by path.
+    pub fn dependents(&self, path: &Path) -> Vec<&Path> {
+        let path = resolve::normalize(path);
+
+        self.modules()
+            .filter(|(_, module)| module.dependencies.iter().any(|edge| edge.resolved.as_ref() == Some(&path)))
+            .map(|(dependent, _)| dependent)
+            .collect()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<SessionModule> {
+        let source = self.fs.read_to_string(path)?;
+        let found = scan_dependencies(&source);
+        let dependencies: Vec<Edge> = found.iter().map(|dependency| Edge {
+            kind: dependency.kind,
+            specifier: dependency.specifier.to_owned(),
+            resolved: self.resolver.resolve(&self.fs, dependency.specifier, path, dependency.kind),
+        }).collect();
+
+        if let Some(ref sink) = self.sink {
+            let batch: Vec<_> = found.iter().zip(&dependencies)
+                .filter(|(_, edge)| edge.resolved.is_none())
+                .map(|(dependency, _)| {
+                    let message = format!("`{}` can't be resolved from `{}`", dependency.specifier, path.display());
+
+                    Diagnostic::new(Severity::Warning, "unresolved-dependency", message)
+                        .with_primary(dependency.start as usize, dependency.end as usize, "no file found by the resolver")
+                })
+                .collect();
+
+            if !batch.is_empty() {
+                sink.report(&batch);
+            }
+        }
+
+        Ok(SessionModule { dependencies })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn project() -> Overlay<OsFileSystem> {
+        let mut fs = Overlay::new(OsFileSystem);
+
+        fs.insert("/project/index.js", "import a from './a.js'; const b = require('./lib/b.js'); import('lodash');".into());
+        fs.insert("/project/a.js", "export * from './lib/b.js';".into());
+        fs.insert("/project/lib/b.js", "require('../a.js'); require('./missing.js');".into());
+        fs.insert("/project/unused.js", "".into());
+        fs
+    }
+
+    fn paths(session: &Session<Overlay<OsFileSystem>>) -> Vec<&Path> {
+        session.modules().map(|(path, _)| path).collect()
+    }
+
+    #[test]
+    fn builds_dependency_graph() {
+        let mut session = Session::new(project());
+
+        session.add(Path::new("/project/./index.js")).unwrap();
+
+        assert_eq!(paths(&session), vec![
+            Path::new("/project/a.js"),
+            Path::new("/project/index.js"),
+            Path::new("/project/lib/b.js"),
+        ]);
+        assert_eq!(session.module(Path::new("/project/index.js")).unwrap().dependencies, vec![
+            Edge { kind: DependencyKind::Import, specifier: "./a.js".into(), resolved: Some("/project/a.js".into()) },
+            Edge { kind: DependencyKind::Require, specifier: "./lib/b.js".into(), resolved: Some("/project/lib/b.js".into()) },
+            Edge { kind: DependencyKind::DynamicImport, specifier: "lodash".into(), resolved: None },
+        ]);
+        assert_eq!(session.dependents(Path::new("/project/lib/b.js")), vec![
+            Path::new("/project/a.js"),
+            Path::new("/project/index.js"),
+        ]);
+    }
+
+    #[test]
+    fn fails_on_missing_entry() {
+        let mut session = Session::new(project());
+
+        assert!(session.add(Path::new("/project/missing.js")).is_err());
+        assert!(paths(&session).is_empty());
+    }
+
+    #[test]
+    fn reloads_changed_modules() {
+        let mut session = Session::new(project());
+
+        session.add(Path::new("/project/a.js")).unwrap();
+        session.fs_mut().insert("/project/a.js", "import './unused.js';".into());
+        session.reload(Path::new("/project/a.js")).unwrap();
+
+        assert_eq!(session.dependents(Path::new("/project/lib/b.js")), Vec::<&Path>::new());
+        assert_eq!(session.dependents(Path::new("/project/unused.js")), vec![Path::new("/project/a.js")]);
+
+        session.fs_mut().remove(Path::new("/project/unused.js"));
+
+        assert!(session.reload(Path::new("/project/unused.js")).is_err());
+        assert_eq!(session.module(Path::new("/project/unused.js")), None);
+    }
+
+    #[test]
+    fn reports_unresolved_dependencies() {
+        use std::sync::{Arc, Mutex};
+
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let mut session = Session::new(project()).with_diagnostics_sink(sink.clone());
+
+        session.add(Path::new("/project/index.js")).unwrap();
+
+        let diagnostics = sink.lock().unwrap();
+        let messages: Vec<_> = diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+
+        assert_eq!(messages, vec![
+            "`lodash` can't be resolved from `/project/index.js`",
+            "`./missing.js` can't be resolved from `/project/lib/b.js`",
+        ]);
+        assert_eq!(diagnostics[0].primary().map(|label| (label.start, label.end)), Some((64, 72)));
+    }
+
+    #[test]
+    fn resolves_with_node_resolver() {
+        let mut fs = project();
+
+        fs.insert("/project/node_modules/lodash/index.js", "module.exports = require('./lodash');".into());
+        fs.insert("/project/node_modules/lodash/lodash.js", "".into());
+
+        let mut session = Session::new(fs).with_resolver(NodeResolver::new());
+
+        session.add(Path::new("/project/index.js")).unwrap();
+
+        assert_eq!(session.module(Path::new("/project/node_modules/lodash/index.js")).unwrap().dependencies, vec![
+            Edge { kind: DependencyKind::Require, specifier: "./lodash".into(), resolved: Some("/project/node_modules/lodash/lodash.js".into()) },
+        ]);
+        assert_eq!(session.dependents(Path::new("/project/node_modules/lodash/index.js")), vec![
+            Path::new("/project/index.js"),
+        ]);
+    }
+}
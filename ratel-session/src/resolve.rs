@@ -0,0 +1,401 @@
+use std::path::{Component, Path, PathBuf};
+use ratel::DependencyKind;
+use serde_json::Value;
+
+use fs::FileSystem;
+
+/// Finds which file a module specifier refers to.
+pub trait Resolver {
+    /// Resolve the `specifier` of a dependency of the `kind` found in the
+    /// module at `from`, with the files of the `fs` file system.
+    fn resolve<F: FileSystem + ?Sized>(&self, fs: &F, specifier: &str, from: &Path, kind: DependencyKind) -> Option<PathBuf>;
+}
+
+/// Resolver of relative and absolute specifiers, `./a.js` or `/a.js`, to
+/// the exact file they name. Bare specifiers such as `lodash` aren't
+/// resolved.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RelativeResolver;
+
+impl Resolver for RelativeResolver {
+    fn resolve<F: FileSystem + ?Sized>(&self, fs: &F, specifier: &str, from: &Path, _: DependencyKind) -> Option<PathBuf> {
+        relative(specifier, from).filter(|path| fs.is_file(path))
+    }
+}
+
+/// Resolver following the algorithm of Node for CommonJS modules, also
+/// for `import` declarations, as bundlers do:
+///
+/// - Relative and absolute specifiers name a file, then the same with any of
+///   the `extensions` appended, then a directory: the `main` of its
+///   `package.json`, or its `index` file with any of the `extensions`.
+/// - Bare specifiers, `lodash` or `@scope/name/sub`, name a package in the
+///   `node_modules` directory of the module or of any directory above it.
+///   Its `exports` in `package.json` are used if it has them, with the
+///   `conditions` and either `import` or `require` for the kind of the
+///   dependency, else the rest of the specifier is resolved as a relative
+///   specifier within the package.
+///
+/// Built-in modules of Node, such as `fs`, aren't resolved.
+#[derive(Debug, Clone)]
+pub struct NodeResolver {
+    extensions: Vec<String>,
+    conditions: Vec<String>,
+}
+
+impl Default for NodeResolver {
+    fn default() -> Self {
+        NodeResolver {
+            extensions: vec![".js".into(), ".mjs".into(), ".cjs".into(), ".json".into()],
+            conditions: vec!["node".into()],
+        }
+    }
+}
+
+impl NodeResolver {
+    #[inline]
+    pub fn new() -> Self {
+        NodeResolver::default()
+    }
+
+    /// Set the extensions tried in order, with the dot, `.js`, `.mjs`,
+    /// `.cjs` and `.json` by default.
+    pub fn with_extensions(mut self, extensions: &[&str]) -> Self {
+        self.extensions = extensions.iter().map(|&extension| extension.into()).collect();
+        self
+    }
+
+    /// Set the conditions of `exports` matched on top of `default`, and of
+    /// `import` or `require`, `node` by default.
+    pub fn with_conditions(mut self, conditions: &[&str]) -> Self {
+        self.conditions = conditions.iter().map(|&condition| condition.into()).collect();
+        self
+    }
+
+    fn file<F: FileSystem + ?Sized>(&self, fs: &F, path: &Path) -> Option<PathBuf> {
+        if fs.is_file(path) {
+            return Some(path.to_owned());
+        }
+
+        self.extensions.iter().map(|extension| {
+            let mut file = path.as_os_str().to_owned();
+
+            file.push(extension);
+
+            PathBuf::from(file)
+        }).find(|file| fs.is_file(file))
+    }
+
+    fn directory<F: FileSystem + ?Sized>(&self, fs: &F, path: &Path) -> Option<PathBuf> {
+        if !fs.is_dir(path) {
+            return None;
+        }
+
+        let main = package_json(fs, path).and_then(|package| match package.get("main") {
+            Some(Value::String(main)) => Some(normalize(&path.join(main))),
+            _ => None,
+        });
+
+        if let Some(main) = main {
+            if let Some(file) = self.file(fs, &main).or_else(|| self.index(fs, &main)) {
+                return Some(file);
+            }
+        }
+
+        self.index(fs, path)
+    }
+
+    fn index<F: FileSystem + ?Sized>(&self, fs: &F, path: &Path) -> Option<PathBuf> {
+        self.extensions.iter()
+            .map(|extension| path.join(format!("index{}", extension)))
+            .find(|file| fs.is_file(file))
+    }
+
+    fn package<F: FileSystem + ?Sized>(&self, fs: &F, specifier: &str, from: &Path, kind: DependencyKind) -> Option<PathBuf> {
+        let name_end = if specifier.starts_with('@') {
+            specifier.match_indices('/').nth(1).map(|(index, _)| index)
+        } else {
+            specifier.find('/')
+        };
+        let (name, subpath) = match name_end {
+            Some(index) => specifier.split_at(index),
+            None => (specifier, ""),
+        };
+
+        if name.is_empty() || name.starts_with('.') || name.contains('\\') {
+            return None;
+        }
+
+        for directory in from.ancestors().skip(1) {
+            if directory.file_name() == Some("node_modules".as_ref()) {
+                continue;
+            }
+
+            let root = directory.join("node_modules").join(name);
+
+            if !fs.is_dir(&root) {
+                continue;
+            }
+
+            let exports = package_json(fs, &root).and_then(|mut package| package.remove("exports"));
+
+            if let Some(exports) = exports {
+                let condition = match kind {
+                    DependencyKind::Require => "require",
+                    _ => "import",
+                };
+                let subpath = format!(".{}", subpath);
+
+                return self.exports(&exports, &subpath, condition)
+                    .map(|target| normalize(&root.join(target)))
+                    .filter(|file| file.starts_with(&root) && fs.is_file(file));
+            }
+
+            let path = normalize(&root.join(subpath.trim_start_matches('/')));
+
+            if !path.starts_with(&root) {
+                return None;
+            }
+
+            let resolved = match subpath {
+                "" => self.directory(fs, &path),
+                _  => self.file(fs, &path).or_else(|| self.directory(fs, &path)),
+            };
+
+            if resolved.is_some() {
+                return resolved;
+            }
+        }
+
+        None
+    }
+
+    /// Find the target of the `subpath` of a package, starting with `.`,
+    /// in its `exports`.
+    fn exports(&self, exports: &Value, subpath: &str, condition: &str) -> Option<String> {
+        let subpaths = match *exports {
+            Value::Object(ref map) if map.keys().all(|key| key.starts_with('.')) => map,
+            _ if subpath == "." => return self.target(exports, "", condition),
+            _ => return None,
+        };
+
+        if let Some(target) = subpaths.get(subpath) {
+            return self.target(target, "", condition);
+        }
+
+        // Pattern with the longest part before the `*` matching the subpath
+        subpaths.iter().filter_map(|(key, target)| {
+            let star = key.find('*')?;
+            let (prefix, suffix) = (&key[..star], &key[star + 1..]);
+            let matched = subpath.len() >= prefix.len() + suffix.len()
+                && subpath.starts_with(prefix)
+                && subpath.ends_with(suffix);
+
+            if matched {
+                Some((prefix.len(), &subpath[prefix.len()..subpath.len() - suffix.len()], target))
+            } else {
+                None
+            }
+        }).max_by_key(|&(prefix, _, _)| prefix).and_then(|(_, star, target)| self.target(target, star, condition))
+    }
+
+    /// Resolve a target of `exports`, a path, the first of an array of them
+    /// that resolves, an object of conditions or `null`, with the `star`
+    /// matched by a pattern substituted in.
+    fn target(&self, target: &Value, star: &str, condition: &str) -> Option<String> {
+        match *target {
+            Value::String(ref path) if path.starts_with("./") => Some(path.replace('*', star)),
+            Value::Array(ref targets) => targets.iter().filter_map(|target| self.target(target, star, condition)).next(),
+            Value::Object(ref conditions) => {
+                conditions.iter()
+                    .filter(|&(key, _)| key == "default" || key == condition || self.conditions.iter().any(|active| active == key))
+                    .filter_map(|(_, target)| self.target(target, star, condition))
+                    .next()
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Resolver for NodeResolver {
+    fn resolve<F: FileSystem + ?Sized>(&self, fs: &F, specifier: &str, from: &Path, kind: DependencyKind) -> Option<PathBuf> {
+        match relative(specifier, from) {
+            Some(path) => self.file(fs, &path).or_else(|| self.directory(fs, &path)),
+            None => self.package(fs, specifier, from, kind),
+        }
+    }
+}
+
+/// Read the `package.json` in the `directory`, if it has a valid one.
+fn package_json<F: FileSystem + ?Sized>(fs: &F, directory: &Path) -> Option<serde_json::Map<String, Value>> {
+    let source = fs.read_to_string(&directory.join("package.json")).ok()?;
+
+    match serde_json::from_str(&source) {
+        Ok(Value::Object(package)) => Some(package),
+        _ => None,
+    }
+}
+
+/// Path named by a relative or absolute `specifier` found in the module
+/// at `from`, `None` for bare specifiers.
+pub fn relative(specifier: &str, from: &Path) -> Option<PathBuf> {
+    if specifier.starts_with('/') {
+        return Some(normalize(Path::new(specifier)));
+    }
+
+    let relative = specifier == "." || specifier == ".." || specifier.starts_with("./") || specifier.starts_with("../");
+
+    if !relative {
+        return None;
+    }
+
+    let directory = from.parent().unwrap_or_else(|| Path::new(""));
+
+    Some(normalize(&directory.join(specifier)))
+}
+
+/// Remove the `.` and `..` components of the `path`, without reading the
+/// file system, so that every module of a `Session` has a single path.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                },
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {},
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::{Overlay, OsFileSystem};
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize(Path::new("/a/./b/../c.js")), PathBuf::from("/a/c.js"));
+        assert_eq!(normalize(Path::new("/../a.js")), PathBuf::from("/a.js"));
+        assert_eq!(normalize(Path::new("a/../../b.js")), PathBuf::from("../b.js"));
+    }
+
+    #[test]
+    fn relative_specifiers() {
+        let from = Path::new("/project/src/index.js");
+
+        assert_eq!(relative("./a.js", from), Some(PathBuf::from("/project/src/a.js")));
+        assert_eq!(relative("../lib/b.js", from), Some(PathBuf::from("/project/lib/b.js")));
+        assert_eq!(relative("..", from), Some(PathBuf::from("/project")));
+        assert_eq!(relative("/c.js", from), Some(PathBuf::from("/c.js")));
+        assert_eq!(relative("lodash", from), None);
+        assert_eq!(relative(".hidden", from), None);
+    }
+
+    #[test]
+    fn resolves_existing_files() {
+        let mut fs = Overlay::new(OsFileSystem);
+
+        fs.insert("/project/src/a.js", String::new());
+
+        let from = Path::new("/project/src/index.js");
+
+        assert_eq!(RelativeResolver.resolve(&fs, "./a.js", from, DependencyKind::Import), Some(PathBuf::from("/project/src/a.js")));
+        assert_eq!(RelativeResolver.resolve(&fs, "./a", from, DependencyKind::Import), None);
+        assert_eq!(RelativeResolver.resolve(&fs, "a.js", from, DependencyKind::Import), None);
+    }
+
+    fn node_project() -> Overlay<OsFileSystem> {
+        let mut fs = Overlay::new(OsFileSystem);
+        let files = [
+            ("/project/src/a.js", ""),
+            ("/project/src/b.json", ""),
+            ("/project/src/c/index.js", ""),
+            ("/project/src/d/package.json", r#"{ "main": "./lib/main" }"#),
+            ("/project/src/d/lib/main.cjs", ""),
+            ("/project/node_modules/plain/package.json", r#"{ "main": "dist" }"#),
+            ("/project/node_modules/plain/dist/index.js", ""),
+            ("/project/node_modules/plain/util.js", ""),
+            ("/project/node_modules/@scope/pkg/index.mjs", ""),
+            ("/node_modules/outer/index.js", ""),
+            ("/project/node_modules/dual/package.json", r#"{
+                "main": "./legacy.js",
+                "exports": {
+                    ".": { "import": "./esm/index.mjs", "require": "./cjs/index.cjs" },
+                    "./features/*": "./src/features/*.js",
+                    "./features/internal/*": null,
+                    "./package.json": "./package.json"
+                }
+            }"#),
+            ("/project/node_modules/dual/legacy.js", ""),
+            ("/project/node_modules/dual/esm/index.mjs", ""),
+            ("/project/node_modules/dual/cjs/index.cjs", ""),
+            ("/project/node_modules/dual/src/features/x.js", ""),
+            ("/project/node_modules/dual/src/features/internal/y.js", ""),
+            ("/project/node_modules/sugar/package.json", r#"{ "exports": { "browser": "./browser.js", "default": "./node.js" } }"#),
+            ("/project/node_modules/sugar/browser.js", ""),
+            ("/project/node_modules/sugar/node.js", ""),
+        ];
+
+        for &(path, contents) in &files {
+            fs.insert(path, contents.into());
+        }
+
+        fs
+    }
+
+    fn node<R: Resolver>(resolver: &R, specifier: &str, kind: DependencyKind) -> Option<PathBuf> {
+        resolver.resolve(&node_project(), specifier, Path::new("/project/src/index.js"), kind)
+    }
+
+    #[test]
+    fn node_relative_specifiers() {
+        let resolver = NodeResolver::new();
+        let import = DependencyKind::Import;
+
+        assert_eq!(node(&resolver, "./a", import), Some("/project/src/a.js".into()));
+        assert_eq!(node(&resolver, "./a.js", import), Some("/project/src/a.js".into()));
+        assert_eq!(node(&resolver, "./b", import), Some("/project/src/b.json".into()));
+        assert_eq!(node(&resolver, "./c", import), Some("/project/src/c/index.js".into()));
+        assert_eq!(node(&resolver, "./d", import), Some("/project/src/d/lib/main.cjs".into()));
+        assert_eq!(node(&resolver, "./e", import), None);
+        assert_eq!(node(&resolver.clone().with_extensions(&[".mjs"]), "./a", import), None);
+    }
+
+    #[test]
+    fn node_packages() {
+        let resolver = NodeResolver::new();
+        let import = DependencyKind::Import;
+
+        assert_eq!(node(&resolver, "plain", import), Some("/project/node_modules/plain/dist/index.js".into()));
+        assert_eq!(node(&resolver, "plain/util", import), Some("/project/node_modules/plain/util.js".into()));
+        assert_eq!(node(&resolver, "@scope/pkg", import), Some("/project/node_modules/@scope/pkg/index.mjs".into()));
+        assert_eq!(node(&resolver, "outer", import), Some("/node_modules/outer/index.js".into()));
+        assert_eq!(node(&resolver, "fs", import), None);
+        assert_eq!(node(&resolver, "plain/../../src/a", import), None);
+    }
+
+    #[test]
+    fn node_package_exports() {
+        let resolver = NodeResolver::new();
+        let (import, require) = (DependencyKind::Import, DependencyKind::Require);
+
+        assert_eq!(node(&resolver, "dual", import), Some("/project/node_modules/dual/esm/index.mjs".into()));
+        assert_eq!(node(&resolver, "dual", require), Some("/project/node_modules/dual/cjs/index.cjs".into()));
+        assert_eq!(node(&resolver, "dual/features/x", import), Some("/project/node_modules/dual/src/features/x.js".into()));
+        assert_eq!(node(&resolver, "dual/features/internal/y", import), None);
+        assert_eq!(node(&resolver, "dual/package.json", import), Some("/project/node_modules/dual/package.json".into()));
+        assert_eq!(node(&resolver, "dual/legacy.js", import), None);
+
+        assert_eq!(node(&resolver, "sugar", import), Some("/project/node_modules/sugar/node.js".into()));
+        assert_eq!(node(&resolver.with_conditions(&["browser"]), "sugar", import), Some("/project/node_modules/sugar/browser.js".into()));
+    }
+}
@@ -0,0 +1,301 @@
+use ratel::ast::{NodeList, ExpressionNode, StatementNode, Statement, Pattern, PatternList};
+use ratel::ast::{Function, OptionalName, Block, DeclarationKind, Expression, OperatorKind};
+use ratel::ast::expression::{ArrowExpression, ArrowBody, FunctionExpression};
+use ratel::ast::expression::{CallExpression, MemberExpression, PrefixExpression};
+use ratel::ast::statement::{DeclarationStatement, ReturnStatement};
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+
+use scope::function_bindings;
+use Transformer;
+
+/// Codemod converting anonymous function expressions into arrow functions,
+/// where that doesn't change the meaning of `this`, `arguments` or
+/// `new.target` inside of them. A body made of a single `return` becomes
+/// an expression body.
+///
+/// Arrow functions can't be called with `new` and have no `prototype`.
+/// Functions called with `new` or with their `prototype` accessed right
+/// where they are defined are kept, other functions used as constructors
+/// must be named or kept out of the codemod.
+pub struct FunctionToArrow;
+
+impl<'ast> StaticVisitor<'ast> for FunctionToArrow {
+    type Context = Transformer<'ast>;
+
+    fn on_prefix_expression(prefix: &PrefixExpression<'ast>, _: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if prefix.operator != OperatorKind::New {
+            return;
+        }
+
+        let callee = match prefix.operand.item {
+            Expression::Call(CallExpression { callee, .. }) => callee,
+            _ => prefix.operand,
+        };
+
+        if let Expression::Function(_) = callee.item {
+            t.keep(&callee);
+        }
+    }
+
+    fn on_member_expression(member: &MemberExpression<'ast>, _: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Expression::Function(_) = member.object.item {
+            if member.property.item == "prototype" {
+                t.keep(&member.object);
+            }
+        }
+    }
+
+    fn on_function_expression(_: &FunctionExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Expression::Function(ref function) = ptr.item {
+            if let Some(arrow) = function_to_arrow(function, t) {
+                t.swap(ptr, arrow);
+            }
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_prefix_expression.push(FunctionToArrow::on_prefix_expression);
+        dv.on_member_expression.push(FunctionToArrow::on_member_expression);
+        dv.on_function_expression.push(FunctionToArrow::on_function_expression);
+    }
+}
+
+/// Codemod converting arrow functions into function expressions, where they
+/// don't use the `this`, `arguments` or `new.target` of the enclosing
+/// function. Arrow functions initializing a `const` declaration are named
+/// after it, so that they can refer to themselves and keep their name
+/// in stack traces.
+pub struct ArrowToFunction;
+
+impl<'ast> StaticVisitor<'ast> for ArrowToFunction {
+    type Context = Transformer<'ast>;
+
+    fn on_declaration_statement(_: &DeclarationStatement, node: &'ast StatementNode<'ast>, t: &mut Transformer<'ast>) {
+        let declaration = match node.item {
+            Statement::Declaration(ref declaration) => declaration,
+            _ => return,
+        };
+
+        if declaration.kind != DeclarationKind::Const {
+            return;
+        }
+
+        for declarator in &declaration.declarators {
+            let (name, init) = match (declarator.id.item, &declarator.init) {
+                (Pattern::Identifier(name), Some(init)) => (t.alloc_as_loc(declarator.id, name), init),
+                _ => continue,
+            };
+
+            if let Expression::Arrow(ref arrow) = init.get().item {
+                if let Some(function) = arrow_to_function(arrow, OptionalName(Some(name)), t) {
+                    t.swap(init, function);
+                }
+            }
+        }
+    }
+
+    fn on_arrow_expression(_: &ArrowExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let arrow = match ptr.item {
+            Expression::Arrow(ref arrow) => arrow,
+            _ => return,
+        };
+
+        if let Some(function) = arrow_to_function(arrow, OptionalName(None), t) {
+            t.swap(ptr, function);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_declaration_statement.push(ArrowToFunction::on_declaration_statement);
+        dv.on_arrow_expression.push(ArrowToFunction::on_arrow_expression);
+    }
+}
+
+/// Arrow function with the same parameters and body as the function, or
+/// `None` if it can't be converted.
+fn function_to_arrow<'ast>(function: &'ast FunctionExpression<'ast>, t: &Transformer<'ast>) -> Option<ArrowExpression<'ast>> {
+    // A named function can refer to itself by its name
+    if function.generator || function.name.0.is_some() || has_duplicates(function.params) {
+        return None;
+    }
+
+    if function_bindings(&function.params, &function.body.body).any() {
+        return None;
+    }
+
+    let body = match function.body.body.only_element().map(|statement| statement.item) {
+        Some(Statement::Return(ReturnStatement { value: Some(value) })) => {
+            // The visitor goes on into the `return` of the original body,
+            // so a function returned by it has to be converted here
+            let value = match value.get().item {
+                Expression::Function(ref function) => match function_to_arrow(function, t) {
+                    Some(arrow) => t.alloc_as_loc(value, arrow),
+                    None        => value,
+                },
+                _ => value,
+            };
+
+            ArrowBody::Expression(value)
+        },
+        _ => ArrowBody::Block(function.body),
+    };
+
+    Some(ArrowExpression {
+        is_async: function.is_async,
+        params: function.params,
+        body,
+    })
+}
+
+/// Function expression with the same parameters and body as the arrow
+/// function, or `None` if it would bind the arrow's `this` differently.
+fn arrow_to_function<'ast>(
+    arrow: &'ast ArrowExpression<'ast>,
+    name: OptionalName<'ast>,
+    t: &Transformer<'ast>,
+) -> Option<FunctionExpression<'ast>> {
+    if function_bindings(&arrow.params, &arrow.body).any() {
+        return None;
+    }
+
+    let body = match arrow.body {
+        ArrowBody::Block(block)     => block,
+        ArrowBody::Expression(expr) => {
+            // Same as in `function_to_arrow`, the visitor goes on into the
+            // original body
+            let expr = match expr.get().item {
+                Expression::Arrow(ref arrow) => match arrow_to_function(arrow, OptionalName(None), t) {
+                    Some(function) => t.alloc_as_loc(expr, function),
+                    None           => expr,
+                },
+                _ => expr,
+            };
+
+            let ret = t.alloc_as_loc(expr, ReturnStatement {
+                value: Some(expr)
+            });
+
+            t.alloc_as_loc(ret, Block {
+                body: NodeList::from(t.arena, ret)
+            })
+        }
+    };
+
+    Some(Function {
+        name,
+        generator: false,
+        is_async: arrow.is_async,
+        params: arrow.params,
+        body,
+    })
+}
+
+/// Check whether a parameter name is repeated, which is allowed in functions
+/// of sloppy mode code, with simple parameters only, but not in arrows.
+fn has_duplicates(params: PatternList) -> bool {
+    let names: Vec<_> = params.iter().filter_map(|param| match param.item {
+        Pattern::Identifier(name) => Some(name),
+        _ => None,
+    }).collect();
+
+    names.iter().enumerate().any(|(index, name)| names[..index].contains(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use pass::{Pass, Visit};
+    use ratel_visitor::Visitor;
+
+    fn assert_convert<V>(visitor: V, source: &str, expected: &str) where
+        V: for<'ast> Visitor<'ast, Context = Transformer<'ast>>,
+    {
+        let module = parse(source).unwrap();
+
+        Visit(visitor).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn function_to_arrow() {
+        assert_convert(FunctionToArrow, "foo(function (a) { return a * 2; });", "foo((a) => a * 2);");
+        assert_convert(FunctionToArrow, "foo(function (a, b) { bar(); });", "foo((a, b) => { bar(); });");
+        assert_convert(FunctionToArrow, "foo(function () { return {}; });", "foo(() => ({}));");
+        assert_convert(FunctionToArrow, "foo(async function (a) { return await a; });", "foo(async (a) => await a);");
+    }
+
+    #[test]
+    fn function_to_arrow_with_nested_functions() {
+        assert_convert(
+            FunctionToArrow,
+            "foo(function () { return function () { return this; }; });",
+            "foo(() => function () { return this; });"
+        );
+        assert_convert(
+            FunctionToArrow,
+            "new (function () { return function () { return 1; }; })();",
+            "new (function () { return () => 1; })();"
+        );
+        assert_convert(
+            FunctionToArrow,
+            "foo(function () { return function () { return 1; }; });",
+            "foo(() => () => 1);"
+        );
+    }
+
+    #[test]
+    fn function_not_converted_to_arrow() {
+        let sources = [
+            "foo(function () { return this; });",
+            "foo(function () { return () => this.bar; });",
+            "foo(function () { return arguments[0]; });",
+            "foo(function () { return new.target; });",
+            "foo(function bar() { return bar; });",
+            "foo(function* () {});",
+            "foo(function (a, a) {});",
+            "function foo() { return 1; }",
+            "new (function () { this.a = 1; })();",
+            "new (function () {});",
+            "(function () {}).prototype.foo = 1;",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(FunctionToArrow, source, source);
+        }
+    }
+
+    #[test]
+    fn arrow_to_function() {
+        assert_convert(ArrowToFunction, "foo(() => {});", "foo(function () {});");
+        assert_convert(ArrowToFunction, "foo((a) => a * 2);", "foo(function (a) { return a * 2; });");
+        assert_convert(ArrowToFunction, "let foo = () => 1;", "let foo = function () { return 1; };");
+        assert_convert(ArrowToFunction, "foo(() => () => 2);", "foo(function () { return function () { return 2; }; });");
+        assert_convert(ArrowToFunction, "foo(async a => a);", "foo(async function (a) { return a; });");
+    }
+
+    #[test]
+    fn arrow_to_function_named_by_const() {
+        assert_convert(
+            ArrowToFunction,
+            "const foo = (a) => a, bar = 1, baz = () => () => 2;",
+            "const foo = function foo(a) { return a; }, bar = 1, baz = function baz() { return function () { return 2; }; };"
+        );
+    }
+
+    #[test]
+    fn arrow_not_converted_to_function() {
+        let sources = [
+            "foo(() => this);",
+            "const foo = () => arguments;",
+            "foo(() => { return () => new.target; });",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(ArrowToFunction, source, source);
+        }
+    }
+}
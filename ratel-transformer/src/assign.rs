@@ -0,0 +1,194 @@
+use ratel::ast::{Expression, ExpressionNode, OperatorKind};
+use ratel::ast::expression::BinaryExpression;
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+
+use effects::is_repeatable;
+use Transformer;
+
+/// Pass expanding compound assignments, `a += b` into `a = a + b`, so
+/// that analyses only have to deal with `=`. Only assignments to targets
+/// which can be evaluated twice are expanded, see `effects::is_repeatable`.
+pub struct ExpandAssignments;
+
+impl<'ast> StaticVisitor<'ast> for ExpandAssignments {
+    type Context = Transformer<'ast>;
+
+    fn on_binary_expression(_: &BinaryExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Some(assignment) = expand(ptr, t) {
+            t.swap(ptr, assignment);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_binary_expression.push(ExpandAssignments::on_binary_expression);
+    }
+}
+
+/// Minifier pass contracting assignments of a binary operation on their
+/// own target into compound assignments, `a = a + b` into `a += b`, the
+/// reverse of `ExpandAssignments`. The operation has to be on the left, as
+/// operators such as `+` on strings aren't commutative.
+pub struct ContractAssignments;
+
+impl<'ast> StaticVisitor<'ast> for ContractAssignments {
+    type Context = Transformer<'ast>;
+
+    fn on_binary_expression(_: &BinaryExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Some(assignment) = contract(ptr, t) {
+            t.swap(ptr, assignment);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_binary_expression.push(ContractAssignments::on_binary_expression);
+    }
+}
+
+fn expand<'ast>(node: &ExpressionNode<'ast>, t: &Transformer<'ast>) -> Option<BinaryExpression<'ast>> {
+    let binary = match node.item {
+        Expression::Binary(binary) => binary,
+        _ => return None,
+    };
+
+    let operator = binary.operator.compound_operator()?;
+
+    if !is_repeatable(&binary.left.item) {
+        return None;
+    }
+
+    // The visitor goes on into the original assignment, so an assignment
+    // assigned by this one has to be expanded here
+    let value = match expand(&binary.right, t) {
+        Some(assignment) => t.alloc_as_loc(binary.right, assignment),
+        None => binary.right,
+    };
+
+    Some(BinaryExpression {
+        operator: OperatorKind::Assign,
+        left: binary.left,
+        right: t.alloc_as_loc(*node, BinaryExpression {
+            operator,
+            left: t.alloc_as_loc(binary.left, binary.left.item),
+            right: value,
+        }),
+    })
+}
+
+fn contract<'ast>(node: &ExpressionNode<'ast>, t: &Transformer<'ast>) -> Option<BinaryExpression<'ast>> {
+    let binary = match node.item {
+        Expression::Binary(binary) if binary.operator == OperatorKind::Assign => binary,
+        _ => return None,
+    };
+
+    let operation = match binary.right.item {
+        Expression::Binary(operation) => operation,
+        _ => return None,
+    };
+
+    let operator = operation.operator.compound_assignment()?;
+
+    // Comparing the items ignores the spans
+    if operation.left.item != binary.left.item || !is_repeatable(&binary.left.item) {
+        return None;
+    }
+
+    // Same as in `expand`, an assignment in the operand has to be
+    // contracted here
+    let value = match contract(&operation.right, t) {
+        Some(assignment) => t.alloc_as_loc(operation.right, assignment),
+        None => operation.right,
+    };
+
+    Some(BinaryExpression {
+        operator,
+        left: binary.left,
+        right: value,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use pass::{Pass, Visit};
+    use ratel_visitor::Visitor;
+
+    fn assert_convert<V>(visitor: V, source: &str, expected: &str) where
+        V: for<'ast> Visitor<'ast, Context = Transformer<'ast>>,
+    {
+        let module = parse(source).unwrap();
+
+        Visit(visitor).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn expand_assignments() {
+        assert_convert(ExpandAssignments, "a += 1;", "a = a + 1;");
+        assert_convert(ExpandAssignments, "a -= b; a *= b; a /= b; a %= b;", "a = a - b; a = a * b; a = a / b; a = a % b;");
+        assert_convert(ExpandAssignments, "a <<= b; a >>= b; a >>>= b;", "a = a << b; a = a >> b; a = a >>> b;");
+        assert_convert(ExpandAssignments, "a &= b; a ^= b; a |= b;", "a = a & b; a = a ^ b; a = a | b;");
+        assert_convert(ExpandAssignments, "a *= b + c;", "a = a * (b + c);");
+        assert_convert(ExpandAssignments, "a.b += 1; this.c -= 2; d['e'] |= 3; f[0] &= 4;", "a.b = a.b + 1; this.c = this.c - 2; d['e'] = d['e'] | 3; f[0] = f[0] & 4;");
+        assert_convert(ExpandAssignments, "a += (b += 1);", "a = a + (b = b + 1);");
+    }
+
+    #[test]
+    fn expand_assignments_with_side_effects() {
+        let sources = [
+            "f().a += 1;",
+            "a.b.c += 1;",
+            "a[b] += 1;",
+            "a[b()] += 1;",
+            "a = 1;",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(ExpandAssignments, source, source);
+        }
+    }
+
+    #[test]
+    fn contract_assignments() {
+        assert_convert(ContractAssignments, "a = a + 1;", "a += 1;");
+        assert_convert(ContractAssignments, "a = a - b; a = a * b; a = a / b; a = a % b;", "a -= b; a *= b; a /= b; a %= b;");
+        assert_convert(ContractAssignments, "a = a << b; a = a >> b; a = a >>> b;", "a <<= b; a >>= b; a >>>= b;");
+        assert_convert(ContractAssignments, "a = a & b; a = a ^ b; a = a | b;", "a &= b; a ^= b; a |= b;");
+        assert_convert(ContractAssignments, "a = a * (b + c); a = a - (b - c);", "a *= b + c; a -= b - c;");
+        assert_convert(ContractAssignments, "a.b = a.b + 1; this.c = this.c - 2; d['e'] = d['e'] | 3;", "a.b += 1; this.c -= 2; d['e'] |= 3;");
+        assert_convert(ContractAssignments, "a = a + (b = b + 1);", "a += (b += 1);");
+        assert_convert(ContractAssignments, "a = (b = b * 2);", "a = (b *= 2);");
+    }
+
+    #[test]
+    fn not_contract_assignments() {
+        let sources = [
+            "a = b + a;",
+            "a = a - b - c;",
+            "a = b + 1;",
+            "a.b = a.c + 1;",
+            "f().a = f().a + 1;",
+            "a[b] = a[b] + 1;",
+            "a = a && b;",
+            "a = a == b;",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(ContractAssignments, source, source);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let source = "a += 1; b.c *= d - e; this.f >>>= 2;";
+        let module = parse(source).unwrap();
+
+        Visit(ExpandAssignments).run(&module);
+        Visit(ContractAssignments).run(&module);
+
+        assert_eq!(module.body(), parse(source).unwrap().body());
+    }
+}
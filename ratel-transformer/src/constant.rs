@@ -0,0 +1,141 @@
+use ratel::ast::{Expression, Literal, OperatorKind};
+use ratel::ast::expression::{BinaryExpression, PrefixExpression};
+
+/// Value of a constant expression, see `evaluate`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Value<'ast> {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+
+    /// Contents of a string, only produced for strings without escapes
+    String(&'ast str),
+}
+
+impl<'ast> Value<'ast> {
+    /// Result of `===` on the two values.
+    #[inline]
+    pub fn strict_equals(&self, other: &Value) -> bool {
+        // Same as `PartialEq`, in that `NaN` is not equal to itself and
+        // `0` is equal to `-0`
+        self == other
+    }
+
+    /// Result of converting the value to a boolean.
+    #[inline]
+    pub fn is_truthy(&self) -> bool {
+        match *self {
+            Value::Undefined | Value::Null => false,
+            Value::Bool(value) => value,
+            Value::Number(value) => value != 0.0 && !value.is_nan(),
+            Value::String(value) => !value.is_empty(),
+        }
+    }
+
+    /// Result of `typeof` on the value.
+    #[inline]
+    pub fn type_of(&self) -> &'static str {
+        match *self {
+            Value::Undefined => "undefined",
+            Value::Null => "object",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+        }
+    }
+}
+
+/// Evaluate an expression made only of literals and side effect free
+/// operators on them. Returns `None` if the value can't be known without
+/// running the code.
+///
+/// The identifier `undefined` is taken to be the global binding, callers
+/// must check that the module doesn't shadow it, see
+/// `Scope::declares_anywhere`.
+pub fn evaluate<'ast>(expression: &Expression<'ast>) -> Option<Value<'ast>> {
+    match *expression {
+        Expression::Literal(ref literal) => literal_value(literal),
+        Expression::Identifier("undefined") => Some(Value::Undefined),
+        Expression::Prefix(PrefixExpression { operator, ref operand }) => {
+            let operand = evaluate(&operand.item)?;
+
+            match (operator, operand) {
+                (OperatorKind::LogicalNot, operand) => Some(Value::Bool(!operand.is_truthy())),
+                (OperatorKind::Void, _) => Some(Value::Undefined),
+                (OperatorKind::Typeof, operand) => Some(Value::String(operand.type_of())),
+                (OperatorKind::Subtraction, Value::Number(value)) => Some(Value::Number(-value)),
+                (OperatorKind::Addition, Value::Number(value)) => Some(Value::Number(value)),
+                _ => None,
+            }
+        },
+        Expression::Binary(BinaryExpression { operator, ref left, ref right }) => {
+            let equals = match operator {
+                OperatorKind::StrictEquality => true,
+                OperatorKind::StrictInequality => false,
+                _ => return None,
+            };
+
+            let left = evaluate(&left.item)?;
+            let right = evaluate(&right.item)?;
+
+            Some(Value::Bool(left.strict_equals(&right) == equals))
+        },
+        _ => None,
+    }
+}
+
+fn literal_value<'ast>(literal: &Literal<'ast>) -> Option<Value<'ast>> {
+    match *literal {
+        Literal::Null => Some(Value::Null),
+        Literal::Boolean(value) => Some(Value::Bool(value)),
+        Literal::Number(number) => Some(Value::Number(number.value)),
+        Literal::String(string) => Some(Value::String(string.value)),
+        Literal::RegEx(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel::ast::Statement;
+
+    fn eval(source: &str) -> Option<String> {
+        let module = parse(source).unwrap();
+
+        match module.body().only_element().unwrap().item {
+            Statement::Expression(expression) => evaluate(&expression.item).map(|value| format!("{:?}", value)),
+            _ => panic!("Expected an expression statement"),
+        }
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(eval("undefined;"), Some("Undefined".into()));
+        assert_eq!(eval("null;"), Some("Null".into()));
+        assert_eq!(eval("true;"), Some("Bool(true)".into()));
+        assert_eq!(eval("'foo';"), Some("String(\"foo\")".into()));
+        assert_eq!(eval("'f\\x6fo';"), Some("String(\"foo\")".into()));
+        assert_eq!(eval("1.5;"), Some("Number(1.5)".into()));
+        assert_eq!(eval("0xFF;"), Some("Number(255.0)".into()));
+        assert_eq!(eval("0b11;"), Some("Number(3.0)".into()));
+        assert_eq!(eval("017;"), Some("Number(15.0)".into()));
+        assert_eq!(eval("/foo/;"), None);
+        assert_eq!(eval("foo;"), None);
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(eval("!0;"), Some("Bool(true)".into()));
+        assert_eq!(eval("-1;"), Some("Number(-1.0)".into()));
+        assert_eq!(eval("void 0;"), Some("Undefined".into()));
+        assert_eq!(eval("typeof null;"), Some("String(\"object\")".into()));
+        assert_eq!(eval("1 === 1.0;"), Some("Bool(true)".into()));
+        assert_eq!(eval("'1' !== 1;"), Some("Bool(true)".into()));
+        assert_eq!(eval("void 0 === undefined;"), Some("Bool(true)".into()));
+        assert_eq!(eval("undefined === null;"), Some("Bool(false)".into()));
+        assert_eq!(eval("void foo();"), None);
+        assert_eq!(eval("1 + 1;"), None);
+    }
+}
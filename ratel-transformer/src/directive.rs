@@ -0,0 +1,159 @@
+use ratel::Module;
+use ratel::ast::StatementList;
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor};
+
+/// Disables passes for the statement following it.
+const DISABLE: &str = "ratel-disable-transform";
+
+/// Disables passes for the whole module.
+const DISABLE_FILE: &str = "ratel-disable-transform-file";
+
+/// A comment disabling passes for part of a module, such as
+/// `/* ratel-disable-transform es2015, fold-switch */` for the statement
+/// following it, including any function it declares, or
+/// `/* ratel-disable-transform-file es2015 */` for the entire module.
+/// Without names, all passes are disabled. The nodes around the statement,
+/// such as the function it's in, are left unchanged as well, as replacing
+/// them could drop or move it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Directive<'ast> {
+    /// `start`, `end` of the code the passes are disabled for
+    pub start: u32,
+    pub end: u32,
+
+    /// Names of the disabled passes, all passes if empty
+    pub passes: Vec<&'ast str>,
+}
+
+impl<'ast> Directive<'ast> {
+    /// Check whether the pass `name` is disabled by the directive.
+    #[inline]
+    pub fn disables(&self, name: &str) -> bool {
+        self.passes.is_empty() || self.passes.contains(&name)
+    }
+}
+
+/// Find the directives in the comments of the module, in source order.
+/// Directives followed by no statement are ignored.
+pub fn directives<'ast>(module: &'ast Module<'ast>) -> Vec<Directive<'ast>> {
+    let comments = module.comments();
+
+    if comments.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ctx = DirectiveContext {
+        lists: vec![module.body()],
+    };
+
+    module.traverse(&StatementLists, &mut ctx);
+
+    comments.iter().filter_map(|comment| {
+        let (name, passes) = parse_directive(comment.item)?;
+
+        let (start, end) = match name {
+            DISABLE_FILE => (0, u32::MAX),
+            _ => ctx.statement_after(comment.end)?,
+        };
+
+        Some(Directive { start, end, passes })
+    }).collect()
+}
+
+/// Split the comment into the directive name and pass names, if it's one
+/// of the directives.
+fn parse_directive(comment: &str) -> Option<(&str, Vec<&str>)> {
+    let text = match comment.get(..2)? {
+        "//" => &comment[2..],
+        _ => &comment[2..comment.len() - 2],
+    };
+
+    let mut words = text.split(|ch: char| ch == ',' || ch.is_whitespace()).filter(|word| !word.is_empty());
+
+    match words.next()? {
+        name @ DISABLE | name @ DISABLE_FILE => Some((name, words.collect())),
+        _ => None,
+    }
+}
+
+struct DirectiveContext<'ast> {
+    /// Statement lists of the module, outer lists first
+    lists: Vec<StatementList<'ast>>,
+}
+
+impl<'ast> DirectiveContext<'ast> {
+    /// Get the `start`, `end` of the first statement of any list starting
+    /// at or after `offset`, the outermost one if several start there.
+    fn statement_after(&self, offset: u32) -> Option<(u32, u32)> {
+        let mut found: Option<(u32, u32)> = None;
+
+        for list in &self.lists {
+            let statement = match list.iter().find(|statement| statement.start >= offset) {
+                Some(statement) => statement,
+                None => continue,
+            };
+
+            if found.is_none_or(|(start, _)| statement.start < start) {
+                found = Some((statement.start, statement.end));
+            }
+        }
+
+        found
+    }
+}
+
+struct StatementLists;
+
+impl<'ast> StaticVisitor<'ast> for StatementLists {
+    type Context = DirectiveContext<'ast>;
+
+    #[inline]
+    fn on_statement_list(body: StatementList<'ast>, ctx: &mut DirectiveContext<'ast>) {
+        ctx.lists.push(body);
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, DirectiveContext<'ast>>) {
+        dv.on_statement_list.push(Self::on_statement_list);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    fn assert_directives(source: &str, expected: &[(u32, u32, &[&str])]) {
+        let module = parse(source).unwrap();
+        let directives = directives(&module);
+
+        let found = directives.iter().map(|directive| (directive.start, directive.end, &directive.passes[..])).collect::<Vec<_>>();
+
+        assert_eq!(&found[..], expected);
+    }
+
+    #[test]
+    fn statement_directives() {
+        assert_directives("/* ratel-disable-transform es2015, fold-switch */ a; b;", &[
+            (50, 51, &["es2015", "fold-switch"]),
+        ]);
+        assert_directives("a;\n// ratel-disable-transform\nfunction f() { b; }", &[
+            (30, 49, &[]),
+        ]);
+        assert_directives("function f() { /* ratel-disable-transform x */ if (a) { b; } }", &[
+            (47, 60, &["x"]),
+        ]);
+    }
+
+    #[test]
+    fn file_directives() {
+        assert_directives("/* ratel-disable-transform-file es2015 */", &[
+            (0, u32::MAX, &["es2015"]),
+        ]);
+    }
+
+    #[test]
+    fn ignored_comments() {
+        assert_directives("/* ratel-disable-transforms */ a; /* a */ b; c; /* ratel-disable-transform */", &[]);
+    }
+}
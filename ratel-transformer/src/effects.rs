@@ -0,0 +1,336 @@
+//! Checks of whether expressions can be evaluated fewer or more times, or
+//! in another order, without changing what the code does. Passes that
+//! drop, duplicate, deduplicate or reorder evaluations go through these,
+//! so that they all agree on what is safe.
+//!
+//! The checks err on the side of `false`, with a few assumptions made by
+//! all of them: reading a property doesn't run a getter, converting an
+//! operand to a primitive doesn't run `valueOf` or `toString`, and names
+//! aren't aliased by anything, such as properties of the global object or
+//! of a `with` statement. Any call, and `new`, `delete`, `await` and
+//! `yield`, can do anything.
+
+use ratel::ast::{Expression, Identifier, OperatorKind, Property, PropertyKey};
+use ratel::ast::expression::{BinaryExpression, PrefixExpression, PostfixExpression};
+
+/// Check whether evaluating the expression has no side effects, so that it
+/// can be evaluated fewer times, or dropped if the value isn't used.
+pub fn is_pure(expression: &Expression) -> bool {
+    let effects = Effects::of(expression);
+
+    !effects.unknown && effects.writes.is_empty() && !effects.writes_properties
+}
+
+/// Check whether evaluating the assignment `target` twice has the same
+/// result, with no side effects, as evaluating it once: an identifier, or
+/// a property of an identifier or `this` with a name or a literal key.
+/// The object of any other member, such as in `f().a`, could be another
+/// one each time, and a computed key other than a literal could have side
+/// effects, including when converted to a property key.
+pub fn is_repeatable(target: &Expression) -> bool {
+    let object = match *target {
+        Expression::Identifier(_) => return true,
+        Expression::Member(member) => member.object,
+        Expression::PrivateMember(member) => member.object,
+        Expression::ComputedMember(member) if matches!(member.property.item, Expression::Literal(_)) => member.object,
+        _ => return false,
+    };
+
+    matches!(object.item, Expression::Identifier(_) | Expression::This(_))
+}
+
+/// Check whether `second` can be evaluated before `first` instead of
+/// after it: neither writes what the other reads or writes, and when one
+/// of them can do anything, the other reads nothing that could change.
+pub fn can_reorder(first: &Expression, second: &Expression) -> bool {
+    let first = Effects::of(first);
+    let second = Effects::of(second);
+
+    if first.unknown || second.unknown {
+        return (!first.unknown && first.is_constant()) || (!second.unknown && second.is_constant());
+    }
+
+    !first.conflicts(&second) && !second.conflicts(&first)
+}
+
+/// What evaluating an expression reads and writes, as far as it can be told.
+#[derive(Default)]
+struct Effects<'ast> {
+    reads: Vec<Identifier<'ast>>,
+    writes: Vec<Identifier<'ast>>,
+    reads_properties: bool,
+    writes_properties: bool,
+
+    /// Code that can do anything is run, such as a call
+    unknown: bool,
+}
+
+impl<'ast> Effects<'ast> {
+    fn of(expression: &Expression<'ast>) -> Self {
+        let mut effects = Effects::default();
+
+        effects.collect(expression);
+        effects
+    }
+
+    /// Neither writes nor reads anything that could change
+    #[inline]
+    fn is_constant(&self) -> bool {
+        self.reads.is_empty() && self.writes.is_empty() && !self.reads_properties && !self.writes_properties
+    }
+
+    /// Whether `self` writes anything `other` reads or writes
+    fn conflicts(&self, other: &Effects) -> bool {
+        let names = other.reads.iter().chain(other.writes.iter());
+
+        self.writes_properties && (other.reads_properties || other.writes_properties)
+            || names.into_iter().any(|name| self.writes.contains(name))
+    }
+
+    fn collect(&mut self, expression: &Expression<'ast>) {
+        match *expression {
+            Expression::Void             |
+            Expression::This(_)          |
+            Expression::Literal(_)       |
+            Expression::MetaProperty(_)  |
+            Expression::Arrow(_)         |
+            Expression::Function(_)      => {},
+            Expression::Identifier(name) => self.reads.push(name),
+            Expression::Chain(chain) => self.collect(&chain.expression),
+            Expression::Sequence(sequence) => {
+                for expression in &sequence.body {
+                    self.collect(expression);
+                }
+            },
+            Expression::Array(array) => {
+                for element in &array.body {
+                    self.collect(element);
+                }
+            },
+            Expression::Template(template) => {
+                for expression in &template.expressions {
+                    self.collect(expression);
+                }
+            },
+            Expression::Member(member) => {
+                self.reads_properties = true;
+                self.collect(&member.object);
+            },
+            Expression::PrivateMember(member) => {
+                self.reads_properties = true;
+                self.collect(&member.object);
+            },
+            Expression::ComputedMember(member) => {
+                self.reads_properties = true;
+                self.collect(&member.object);
+                self.collect(&member.property);
+            },
+            Expression::Binary(BinaryExpression { operator, left, right }) => {
+                if operator.assignment() {
+                    self.assign(&left.item, operator != OperatorKind::Assign);
+                } else {
+                    self.collect(&left);
+                }
+
+                self.collect(&right);
+            },
+            Expression::Prefix(PrefixExpression { operator, operand }) => match operator {
+                OperatorKind::Increment | OperatorKind::Decrement => self.assign(&operand.item, true),
+                OperatorKind::New | OperatorKind::Delete => self.unknown = true,
+                _ => self.collect(&operand),
+            },
+            Expression::Postfix(PostfixExpression { operand, .. }) => self.assign(&operand.item, true),
+            Expression::Conditional(conditional) => {
+                self.collect(&conditional.test);
+                self.collect(&conditional.consequent);
+                self.collect(&conditional.alternate);
+            },
+            Expression::Object(object) => {
+                for property in &object.body {
+                    match property.item {
+                        Property::Shorthand(name) => self.reads.push(name),
+                        Property::Literal { key, value } => {
+                            self.key(&key.item);
+                            self.collect(&value);
+                        },
+                        Property::Method { key, .. } => self.key(&key.item),
+
+                        // Spreading the properties can run getters, and
+                        // shorthand defaults are only valid in assignments
+                        Property::Spread { .. } | Property::ShorthandDefault { .. } => self.unknown = true,
+                    }
+                }
+            },
+
+            // Spread elements iterate, which runs code of the iterator, and
+            // classes can run code of their heritage and computed keys
+            Expression::Super(_)          |
+            Expression::Call(_)           |
+            Expression::TaggedTemplate(_) |
+            Expression::Spread(_)         |
+            Expression::Yield(_)          |
+            Expression::Await(_)          |
+            Expression::Class(_)          => self.unknown = true,
+        }
+    }
+
+    #[inline]
+    fn key(&mut self, key: &PropertyKey<'ast>) {
+        if let PropertyKey::Computed(key) = *key {
+            self.collect(&key);
+        }
+    }
+
+    /// Assignment to the `target`, which is also read by compound
+    /// assignments and updates, `a += 1` and `a++`
+    fn assign(&mut self, target: &Expression<'ast>, read: bool) {
+        match *target {
+            Expression::Identifier(name) => {
+                if read {
+                    self.reads.push(name);
+                }
+
+                self.writes.push(name);
+            },
+            Expression::Member(member) => {
+                self.reads_properties |= read;
+                self.writes_properties = true;
+                self.collect(&member.object);
+            },
+            Expression::PrivateMember(member) => {
+                self.reads_properties |= read;
+                self.writes_properties = true;
+                self.collect(&member.object);
+            },
+            Expression::ComputedMember(member) => {
+                self.reads_properties |= read;
+                self.writes_properties = true;
+                self.collect(&member.object);
+                self.collect(&member.property);
+            },
+
+            // Destructuring iterates arrays and can run getters
+            _ => self.unknown = true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::ast::Statement;
+    use ratel::{parse, Module};
+
+    fn expressions<'ast>(module: &'ast Module<'ast>) -> Vec<&'ast Expression<'ast>> {
+        module.body().iter().map(|statement| match statement.item {
+            Statement::Expression(ref expression) => &expression.item,
+            _ => panic!("Expected expression statements"),
+        }).collect()
+    }
+
+    fn pure(source: &str) -> bool {
+        let module = parse(source).unwrap();
+
+        is_pure(expressions(&module)[0])
+    }
+
+    fn reorder(source: &str) -> bool {
+        let module = parse(source).unwrap();
+        let expressions = expressions(&module);
+
+        can_reorder(expressions[0], expressions[1])
+    }
+
+    #[test]
+    fn pure_expressions() {
+        let sources = [
+            "a;",
+            "this.a.b;",
+            "a[0][b];",
+            "!a && b === c || typeof d;",
+            "[1, 'a', /a/, , b];",
+            "({ a, b: 1, [c]: d, e() {} });",
+            "`a${ b }c`;",
+            "a ? b : (c, d);",
+            "(function () { f(); });",
+            "() => f();",
+        ];
+
+        for source in sources.iter() {
+            assert!(pure(source), "{:?} should be pure", source);
+        }
+    }
+
+    #[test]
+    fn impure_expressions() {
+        let sources = [
+            "f();",
+            "a = 1;",
+            "a.b = 1;",
+            "a++;",
+            "--a.b;",
+            "a += 1;",
+            "new A;",
+            "delete a.b;",
+            "[...a];",
+            "({ ...a });",
+            "a[f()];",
+            "[a] = b;",
+            "(class extends f() {});",
+            "tag`a`;",
+            "x ? f() : 1;",
+        ];
+
+        for source in sources.iter() {
+            assert!(!pure(source), "{:?} should not be pure", source);
+        }
+    }
+
+    #[test]
+    fn repeatable_targets() {
+        let module = parse("a; a.b; this.c; d['e']; f[0]; f().a; a.b.c; a[b]; a[b()];").unwrap();
+        let repeatable: Vec<_> = expressions(&module).into_iter().map(is_repeatable).collect();
+
+        assert_eq!(repeatable, vec![true, true, true, true, true, false, false, false, false]);
+    }
+
+    #[test]
+    fn reorderable_expressions() {
+        let sources = [
+            "a; b;",
+            "a = 1; b;",
+            "a.b; c.d;",
+            "a = 1; b = 2;",
+            "f(); 1;",
+            "f(); () => a;",
+            "a.b = 1; c;",
+            "a++; b[0];",
+        ];
+
+        for source in sources.iter() {
+            assert!(reorder(source), "{:?} should be reorderable", source);
+        }
+    }
+
+    #[test]
+    fn not_reorderable_expressions() {
+        let sources = [
+            "a = 1; a;",
+            "a; a = 1;",
+            "a++; a++;",
+            "a = 1; a = 2;",
+            "f(); a;",
+            "a; f();",
+            "f(); g();",
+            "a.b = 1; c.d;",
+            "a.b; c[0] = 1;",
+            "a.b = 1; c.d = 2;",
+            "f(); this.a;",
+            "a += 1; b = a;",
+        ];
+
+        for source in sources.iter() {
+            assert!(!reorder(source), "{:?} should not be reorderable", source);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+/// Globals of the language, defined in every environment.
+pub const BUILTINS: &[&str] = &[
+    "Array", "ArrayBuffer", "Atomics", "BigInt", "BigInt64Array", "BigUint64Array", "Boolean",
+    "DataView", "Date", "Error", "EvalError", "Float32Array", "Float64Array", "Function",
+    "Infinity", "Int16Array", "Int32Array", "Int8Array", "Intl", "JSON", "Map", "Math", "NaN",
+    "Number", "Object", "Promise", "Proxy", "RangeError", "ReferenceError", "Reflect", "RegExp",
+    "Set", "SharedArrayBuffer", "String", "Symbol", "SyntaxError", "TypeError", "URIError",
+    "Uint16Array", "Uint32Array", "Uint8Array", "Uint8ClampedArray", "WeakMap", "WeakSet",
+    "decodeURI", "decodeURIComponent", "encodeURI", "encodeURIComponent", "escape", "eval",
+    "globalThis", "isFinite", "isNaN", "parseFloat", "parseInt", "undefined", "unescape",
+];
+
+/// Globals of web browsers.
+pub const BROWSER: &[&str] = &[
+    "Blob", "CustomEvent", "Document", "Element", "Event", "EventTarget", "File", "FileReader",
+    "FormData", "HTMLElement", "Headers", "Image", "MutationObserver", "Node", "Request",
+    "Response", "TextDecoder", "TextEncoder", "URL", "URLSearchParams", "WebSocket", "Worker",
+    "XMLHttpRequest", "addEventListener", "alert", "atob", "btoa", "cancelAnimationFrame",
+    "clearInterval", "clearTimeout", "confirm", "console", "crypto", "dispatchEvent", "document",
+    "fetch", "getComputedStyle", "history", "localStorage", "location", "navigator",
+    "performance", "prompt", "queueMicrotask", "removeEventListener", "requestAnimationFrame",
+    "screen", "self", "sessionStorage", "setInterval", "setTimeout", "window",
+];
+
+/// Globals of Node.js, including those of CommonJS modules.
+pub const NODE: &[&str] = &[
+    "Buffer", "TextDecoder", "TextEncoder", "URL", "URLSearchParams", "__dirname", "__filename",
+    "clearImmediate", "clearInterval", "clearTimeout", "console", "exports", "global", "module",
+    "process", "queueMicrotask", "require", "setImmediate", "setInterval", "setTimeout",
+];
+
+/// Globals defined where the code runs, which references of the code can
+/// use without declaring them. Only the builtins of the language are
+/// defined unless more are added, see `browser`, `node` and `with_globals`.
+#[derive(Debug, Clone)]
+pub struct Environment<'a> {
+    globals: HashSet<&'a str>,
+}
+
+impl<'a> Default for Environment<'a> {
+    fn default() -> Self {
+        Environment::new()
+    }
+}
+
+impl<'a> Environment<'a> {
+    /// Environment defining the `BUILTINS` only.
+    #[inline]
+    pub fn new() -> Self {
+        Environment {
+            globals: BUILTINS.iter().cloned().collect(),
+        }
+    }
+
+    /// Environment of web browsers.
+    #[inline]
+    pub fn browser() -> Self {
+        Environment::new().with_globals(BROWSER)
+    }
+
+    /// Environment of Node.js.
+    #[inline]
+    pub fn node() -> Self {
+        Environment::new().with_globals(NODE)
+    }
+
+    /// Environment of the given `name`, either `browser`, `node` or
+    /// `builtin` for the builtins only.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "builtin" => Some(Environment::new()),
+            "browser" => Some(Environment::browser()),
+            "node"    => Some(Environment::node()),
+            _ => None,
+        }
+    }
+
+    /// Also define the `globals`, such as those of libraries loaded
+    /// by a script tag.
+    #[inline]
+    pub fn with_globals(mut self, globals: &[&'a str]) -> Self {
+        self.globals.extend(globals.iter().cloned());
+        self
+    }
+
+    /// Check whether the global `name` is defined.
+    #[inline]
+    pub fn defines(&self, name: &str) -> bool {
+        self.globals.contains(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn environments() {
+        assert_eq!(Environment::new().defines("Math"), true);
+        assert_eq!(Environment::new().defines("window"), false);
+        assert_eq!(Environment::browser().defines("window"), true);
+        assert_eq!(Environment::browser().defines("Math"), true);
+        assert_eq!(Environment::browser().defines("process"), false);
+        assert_eq!(Environment::node().defines("require"), true);
+        assert_eq!(Environment::node().defines("document"), false);
+    }
+
+    #[test]
+    fn custom_globals() {
+        let env = Environment::browser().with_globals(&["jQuery", "$"]);
+
+        assert_eq!(env.defines("jQuery"), true);
+        assert_eq!(env.defines("$"), true);
+        assert_eq!(env.defines("document"), true);
+        assert_eq!(env.defines("_"), false);
+    }
+
+    #[test]
+    fn from_name() {
+        assert_eq!(Environment::from_name("node").map(|env| env.defines("process")), Some(true));
+        assert_eq!(Environment::from_name("builtin").map(|env| env.defines("process")), Some(false));
+        assert!(Environment::from_name("deno").is_none());
+    }
+}
@@ -0,0 +1,249 @@
+use ratel::Module;
+use ratel::diagnostic::{self, DiagnosticsSink, Severity};
+use ratel::ast::{Expression, ExpressionNode, Loc, OperatorKind};
+use ratel::ast::expression::BinaryExpression;
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor};
+
+use types::{infer, Type};
+
+/// Why `==` or `!=` was left as is by `strict_equality`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Reason {
+    /// An operand is `null` or `undefined`, which `==` considers equal
+    Nullish,
+
+    /// Operands have different types, which `==` converts before comparing
+    DifferentTypes,
+
+    /// The type of an operand can't be inferred
+    UnknownType,
+}
+
+/// An `==` or `!=` left as is by `strict_equality`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Diagnostic {
+    pub start: u32,
+    pub end: u32,
+    pub reason: Reason,
+
+    /// Inferred types of the operands
+    pub left: Option<Type>,
+    pub right: Option<Type>,
+}
+
+impl Diagnostic {
+    /// Describe the operator left as is as a warning of the
+    /// `strict-equality` lint.
+    pub fn to_diagnostic(&self) -> diagnostic::Diagnostic {
+        let message = match self.reason {
+            Reason::Nullish => "Loose equality with `null` or `undefined`, which it considers equal",
+            Reason::DifferentTypes => "Loose equality of operands of different types, converted before comparing",
+            Reason::UnknownType => "Loose equality of operands of unknown types",
+        };
+
+        let label = format!("{} compared to {}", type_name(self.left), type_name(self.right));
+
+        diagnostic::Diagnostic::new(Severity::Warning, "strict-equality", message)
+            .with_primary(self.start as usize, self.end as usize, label)
+    }
+}
+
+fn type_name(ty: Option<Type>) -> &'static str {
+    match ty {
+        Some(Type::Undefined) => "undefined",
+        Some(Type::Null) => "null",
+        Some(Type::Boolean) => "boolean",
+        Some(Type::Number) => "number",
+        Some(Type::String) => "string",
+        Some(Type::Object) => "object",
+        None => "unknown type",
+    }
+}
+
+/// Result of `strict_equality`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Report {
+    /// Number of operators converted
+    pub converted: usize,
+
+    /// Operators left as is, in source order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Codemod replacing `==` and `!=` by `===` and `!==`, where the operands
+/// are known to have the same type, see `types::infer`, so that they are
+/// compared the same way. Any other use of the operators is reported.
+pub fn strict_equality<'ast>(module: &'ast Module<'ast>) -> Report {
+    let mut ctx = EqualityContext {
+        nodes: Vec::new(),
+    };
+
+    module.traverse(&EqualityCollector, &mut ctx);
+
+    let mut report = Report::default();
+
+    // Nested operators are converted first, so that the converted ones
+    // keep them
+    for node in ctx.nodes.into_iter().rev() {
+        let binary = match node.item {
+            Expression::Binary(binary) => binary,
+            _ => continue,
+        };
+
+        let operator = match binary.operator {
+            OperatorKind::Equality => OperatorKind::StrictEquality,
+            OperatorKind::Inequality => OperatorKind::StrictInequality,
+            _ => continue,
+        };
+
+        let left = infer(&binary.left.item);
+        let right = infer(&binary.right.item);
+
+        let reason = match (left, right) {
+            (Some(Type::Null), _) | (Some(Type::Undefined), _) |
+            (_, Some(Type::Null)) | (_, Some(Type::Undefined)) => Reason::Nullish,
+            (Some(left), Some(right)) if left == right => {
+                node.set(module.arena().alloc(Loc::new(node.start, node.end, Expression::Binary(BinaryExpression {
+                    operator,
+                    ..binary
+                }))));
+
+                report.converted += 1;
+                continue;
+            },
+            (Some(_), Some(_)) => Reason::DifferentTypes,
+            _ => Reason::UnknownType,
+        };
+
+        report.diagnostics.push(Diagnostic {
+            start: node.start,
+            end: node.end,
+            reason,
+            left,
+            right,
+        });
+    }
+
+    report.diagnostics.sort_by_key(|diagnostic| diagnostic.start);
+    report
+}
+
+/// Same as `strict_equality`, reporting the operators left as is to the
+/// `sink` as a single batch of warnings.
+pub fn strict_equality_with_sink<'ast, S>(module: &'ast Module<'ast>, sink: &S) -> Report where
+    S: DiagnosticsSink + ?Sized,
+{
+    let report = strict_equality(module);
+
+    if !report.diagnostics.is_empty() {
+        let batch: Vec<_> = report.diagnostics.iter().map(Diagnostic::to_diagnostic).collect();
+
+        sink.report(&batch);
+    }
+
+    report
+}
+
+struct EqualityContext<'ast> {
+    /// Binary expressions with `==` or `!=`, parents before their children
+    nodes: Vec<&'ast ExpressionNode<'ast>>,
+}
+
+struct EqualityCollector;
+
+impl<'ast> StaticVisitor<'ast> for EqualityCollector {
+    type Context = EqualityContext<'ast>;
+
+    #[inline]
+    fn on_binary_expression(binary: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>, ctx: &mut EqualityContext<'ast>) {
+        if binary.operator == OperatorKind::Equality || binary.operator == OperatorKind::Inequality {
+            ctx.nodes.push(node);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, EqualityContext<'ast>>) {
+        dv.on_binary_expression.push(Self::on_binary_expression);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    fn assert_convert(source: &str, expected: &str) -> Vec<Reason> {
+        let module = parse(source).unwrap();
+        let report = strict_equality(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+
+        report.diagnostics.iter().map(|diagnostic| diagnostic.reason).collect()
+    }
+
+    #[test]
+    fn same_types() {
+        assert_convert("typeof a == 'string';", "typeof a === 'string';");
+        assert_convert("a.length - 1 != -b;", "a.length - 1 !== -b;");
+        assert_convert("!a == !b;", "!a === !b;");
+        assert_convert("(a == 1) == (b != 2);", "(a == 1) === (b != 2);");
+        assert_convert("(typeof a == 'b') == !c;", "(typeof a === 'b') === !c;");
+    }
+
+    #[test]
+    fn diagnostics() {
+        let reasons = assert_convert(
+            "a == null; undefined != b; 1 == '1'; a == b; f() == 1;",
+            "a == null; undefined != b; 1 == '1'; a == b; f() == 1;"
+        );
+
+        assert_eq!(reasons, vec![
+            Reason::Nullish,
+            Reason::Nullish,
+            Reason::DifferentTypes,
+            Reason::UnknownType,
+            Reason::UnknownType,
+        ]);
+    }
+
+    #[test]
+    fn reports_to_sink() {
+        use std::sync::Mutex;
+
+        /// Records the spans of each batch
+        struct Batches(Mutex<Vec<Vec<(usize, usize)>>>);
+
+        impl DiagnosticsSink for Batches {
+            fn report(&self, batch: &[diagnostic::Diagnostic]) {
+                self.0.lock().unwrap().push(batch.iter().filter_map(|d| d.primary()).map(|label| (label.start, label.end)).collect());
+            }
+        }
+
+        let sink = Batches(Mutex::new(Vec::new()));
+
+        strict_equality_with_sink(&parse("a == 1; 1 == 1; b != c;").unwrap(), &sink);
+        strict_equality_with_sink(&parse("1 == 1;").unwrap(), &sink);
+
+        assert_eq!(*sink.0.lock().unwrap(), vec![vec![(0, 6), (16, 22)]]);
+    }
+
+    #[test]
+    fn diagnostic_types() {
+        let module = parse("foo(a + 1 == 'b');").unwrap();
+        let report = strict_equality(&module);
+
+        assert_eq!(report.converted, 0);
+        assert_eq!(report.diagnostics, vec![Diagnostic {
+            start: 4,
+            end: 16,
+            reason: Reason::UnknownType,
+            left: None,
+            right: Some(Type::String),
+        }]);
+
+        let diagnostic = report.diagnostics[0].to_diagnostic();
+
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert_eq!(diagnostic.primary().map(|label| &*label.message), Some("unknown type compared to string"));
+    }
+}
@@ -1,16 +1,61 @@
-use ratel::ast::{NodeList, ExpressionNode, Function, Name, OptionalName, Block};
-use ratel::ast::expression::{ArrowExpression, ArrowBody};
+use ratel::ast::{NodeList, Expression, ExpressionNode, Identifier, Function, Name, OptionalName, Block, Pattern};
+use ratel::ast::expression::{ArrowExpression, ArrowBody, CallExpression, MetaPropertyExpression};
 use ratel::ast::statement::ReturnStatement;
-use ratel_visitor::{StaticVisitor, DynamicVisitor};
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, ScopeKind};
 
 use Transformer;
 
+/// Lowers arrow functions into function expressions. An arrow function
+/// using the `this`, `arguments` or `new.target` of the enclosing function
+/// gets them as parameters of a function wrapping it, called right away,
+/// named with `Transformer::names`.
 pub struct TransformArrow;
 
 impl<'ast> StaticVisitor<'ast> for TransformArrow {
     type Context = Transformer<'ast>;
 
-    fn on_arrow_expression(node: &ArrowExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+    fn on_arrow_expression(_: &ArrowExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let node = match ptr.item {
+            Expression::Arrow(ref arrow) => arrow,
+            _ => return,
+        };
+
+        let mut ctx = CaptureContext {
+            scopes: Vec::new(),
+            captures: [Vec::new(), Vec::new(), Vec::new()],
+        };
+
+        node.params.traverse(&CaptureCollector, &mut ctx);
+        node.body.traverse(&CaptureCollector, &mut ctx);
+
+        // Changing only some of the uses would change their meaning
+        if t.is_disabled(ptr) || ctx.captures.iter().flatten().any(|node| t.is_disabled(node)) {
+            return;
+        }
+
+        let mut params = Vec::new();
+        let mut arguments = Vec::new();
+
+        // Before the body is copied into the function
+        for uses in ctx.captures.iter().filter(|uses| !uses.is_empty()) {
+            let name = t.names.generate();
+
+            params.push(t.alloc_as_loc(*uses[0], Pattern::Identifier(name)));
+            arguments.push(t.alloc_as_loc(*uses[0], uses[0].item));
+
+            for node in uses {
+                t.swap(node, Expression::Identifier(name));
+            }
+        }
+
+        // The expression body is copied into a `return`, so an arrow there
+        // has to be lowered first, or the copy would keep it
+        if let ArrowBody::Expression(ref expr) = node.body {
+            if let Expression::Arrow(ref arrow) = expr.item {
+                TransformArrow::on_arrow_expression(arrow, expr, t);
+            }
+        }
+
         let body = match node.body {
             ArrowBody::Block(block)     => block,
             ArrowBody::Expression(expr) => {
@@ -24,11 +69,39 @@ impl<'ast> StaticVisitor<'ast> for TransformArrow {
             }
         };
 
-        t.swap(ptr, Function {
+        let function = Function {
             name: OptionalName::empty(),
             generator: false,
+            is_async: node.is_async,
             params: node.params,
             body,
+        };
+
+        if params.is_empty() {
+            return t.swap(ptr, function);
+        }
+
+        let function = t.alloc_as_loc(*ptr, function);
+        let ret = t.alloc_as_loc(*ptr, ReturnStatement {
+            value: Some(function)
+        });
+        let wrapper = Function {
+            name: OptionalName::empty(),
+            generator: false,
+            is_async: false,
+            params: t.list(params),
+            body: t.alloc_as_loc(*ptr, Block {
+                body: NodeList::from(t.arena, ret)
+            }),
+        };
+
+        let callee = t.alloc_as_loc(*ptr, wrapper);
+        let arguments = t.list(arguments);
+
+        t.swap(ptr, CallExpression {
+            callee,
+            arguments,
+            optional: false,
         });
     }
 
@@ -38,12 +111,117 @@ impl<'ast> StaticVisitor<'ast> for TransformArrow {
     }
 }
 
+struct CaptureContext<'ast> {
+    /// Kinds of the scopes entered so far
+    scopes: Vec<ScopeKind>,
+
+    /// Uses of `this`, `arguments` and `new.target` taken from the
+    /// enclosing function
+    captures: [Vec<&'ast ExpressionNode<'ast>>; 3],
+}
+
+impl<'ast> CaptureContext<'ast> {
+    #[inline]
+    fn capture(&mut self, index: usize, node: &'ast ExpressionNode<'ast>) {
+        if !self.scopes.contains(&ScopeKind::Function) {
+            self.captures[index].push(node);
+        }
+    }
+}
+
+struct CaptureCollector;
+
+impl<'ast> StaticVisitor<'ast> for CaptureCollector {
+    type Context = CaptureContext<'ast>;
+
+    #[inline]
+    fn on_enter_scope(kind: ScopeKind, ctx: &mut CaptureContext<'ast>) {
+        ctx.scopes.push(kind);
+    }
+
+    #[inline]
+    fn on_leave_scope(ctx: &mut CaptureContext<'ast>) {
+        ctx.scopes.pop();
+    }
+
+    #[inline]
+    fn on_this_expression(node: &'ast ExpressionNode<'ast>, ctx: &mut CaptureContext<'ast>) {
+        ctx.capture(0, node);
+    }
+
+    #[inline]
+    fn on_identifier_expression(ident: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, ctx: &mut CaptureContext<'ast>) {
+        if *ident == "arguments" {
+            ctx.capture(1, node);
+        }
+    }
+
+    #[inline]
+    fn on_meta_property(_: &MetaPropertyExpression<'ast>, node: &'ast ExpressionNode<'ast>, ctx: &mut CaptureContext<'ast>) {
+        ctx.capture(2, node);
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, CaptureContext<'ast>>) {
+        dv.on_enter_scope.push(Self::on_enter_scope);
+        dv.on_leave_scope.push(Self::on_leave_scope);
+        dv.on_this_expression.push(Self::on_this_expression);
+        dv.on_identifier_expression.push(Self::on_identifier_expression);
+        dv.on_meta_property.push(Self::on_meta_property);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use ratel::parse;
     use ratel_visitor::Visitor;
+    use pass::{Pass, Visit};
     use std::mem::size_of;
 
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+
+        Visit(TransformArrow).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn transform_arrow() {
+        assert_transform("foo((a, b) => a + b);", "foo(function (a, b) { return a + b; });");
+        assert_transform("foo(() => { bar(); });", "foo(function () { bar(); });");
+        assert_transform("foo(() => function () { return this; });", "foo(function () { return function () { return this; }; });");
+    }
+
+    #[test]
+    fn transform_arrow_capturing_bindings() {
+        assert_transform(
+            "foo(() => this.bar);",
+            "foo(function ($_ratel0) { return function () { return $_ratel0.bar; }; }(this));"
+        );
+        assert_transform(
+            "function f() { return (a = this) => arguments[0] + new.target; }",
+            "function f() { return function ($_ratel0, $_ratel1, $_ratel2) { return function (a = $_ratel0) { return $_ratel1[0] + $_ratel2; }; }(this, arguments, new.target); }"
+        );
+        assert_transform(
+            "var $_ratel0; foo(() => () => this);",
+            "var $_ratel0; foo(function ($_ratel1) { return function () { return function () { return $_ratel1; }; }; }(this));"
+        );
+    }
+
+    #[test]
+    fn transform_arrow_in_method() {
+        assert_transform(
+            "({ foo() { return () => this; } });",
+            "({ foo() { return function ($_ratel0) { return function () { return $_ratel0; }; }(this); } });"
+        );
+        assert_transform(
+            "class A { foo() { if (a) { return () => [this, arguments]; } } }",
+            "class A { foo() { if (a) { return function ($_ratel0, $_ratel1) { return function () { return [$_ratel0, $_ratel1]; }; }(this, arguments); } } }"
+        );
+    }
+
     #[test]
     fn transform_arrow_impls_visitor() {
         let _: &Visitor<Context = Transformer> = &TransformArrow;
@@ -0,0 +1,1259 @@
+use std::ptr;
+use ratel::ast::{NodeList, Expression, ExpressionNode, ExpressionList, StatementNode, Statement, Literal, Pattern};
+use ratel::ast::{NumberLiteral, StringLiteral};
+use ratel::ast::{Function, Class, Name, OptionalName, MandatoryName, Block, BlockNode, IdentifierNode};
+use ratel::ast::{ClassMember, MethodKind, Property, PropertyKey, OperatorKind, DeclarationKind};
+use ratel::ast::expression::{CallExpression, MemberExpression, ComputedMemberExpression, ObjectExpression};
+use ratel::ast::expression::{BinaryExpression, ConditionalExpression, ClassExpression, ThisExpression, PrefixExpression};
+use ratel::ast::statement::{ClassStatement, ReturnStatement, DeclarationStatement, Declarator, IfStatement, ForInit};
+use ratel::ast::statement::ExportStatement;
+use ratel::diagnostic::{Diagnostic, Severity};
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, ScopeKind, ParentNode};
+
+use Transformer;
+
+/// Lowers classes into constructor functions, declared in a function
+/// called right away, with the methods assigned to the prototype:
+///
+/// ```js
+/// var A = function ($_ratel0, $_ratel1) {
+///     "use strict";
+///     function A() { $_ratel0.apply(this, arguments); }
+///     A.prototype = Object.create($_ratel0.prototype, { ... });
+///     A.__proto__ = $_ratel0;
+///     A.prototype[$_ratel1] = function () {};
+///     return A;
+/// }(B, key());
+/// ```
+///
+/// The superclass and the keys of computed members are the arguments of
+/// the call, so they are evaluated in source order, exactly once, with the
+/// `this` and `arguments` of the code around the class. The members can
+/// then be defined in any order: static fields are set after all the
+/// methods, as they are in classes, and instance fields by a function the
+/// constructor calls, in the scope of the class rather than that of the
+/// constructor. Keys are converted to strings when the members are
+/// defined, rather than when they are evaluated.
+///
+/// Methods are assigned, so unlike those of classes they are enumerable,
+/// accessors are defined with `Object.defineProperty`.
+///
+/// `super(a)` becomes `$_ratel0.call(this, a)`, and properties of `super`
+/// are read by a helper declared in the function, starting the lookup at
+/// the prototype of the superclass, or at the superclass itself in static
+/// members, with the `this` of the member as the receiver of getters:
+///
+/// ```js
+/// // super.m(a)
+/// $_ratel2($_ratel0.prototype, "m", this).call(this, a)
+/// ```
+///
+/// Named classes exported with `export default class A {}` are left as is,
+/// as lowering them would take a declaration of `A` besides the export.
+/// Engines running modules support classes anyway.
+///
+/// Without helpers, see `Transformer::without_helpers`, a class reading
+/// properties of `super` is left as is and reported as an error. Classes
+/// assigning to properties of `super`, or using it in any other way the
+/// lowering doesn't support, are always left as is and reported.
+pub struct TransformClass;
+
+impl<'ast> StaticVisitor<'ast> for TransformClass {
+    type Context = Transformer<'ast>;
+
+    fn on_class_statement(class: &ClassStatement<'ast>, ptr: &'ast StatementNode<'ast>, t: &mut Transformer<'ast>) {
+        let MandatoryName(name) = class.name;
+
+        let call = match lower(class, name, t) {
+            Some(call) => call,
+            None => return,
+        };
+
+        let init = t.alloc_as_loc(*ptr, call);
+        let id = t.alloc_as_loc(name, Pattern::Identifier(name.item));
+        let declarator = t.alloc_as_loc(*ptr, Declarator {
+            id,
+            init: Some(init),
+        });
+
+        t.swap(ptr, DeclarationStatement {
+            kind: DeclarationKind::Var,
+            declarators: NodeList::from(t.arena, declarator),
+        });
+    }
+
+    fn on_class_expression(class: &ClassExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if t.is_disabled(ptr) {
+            return;
+        }
+
+        let name = match class.name {
+            OptionalName(Some(name)) => name,
+            OptionalName(None) => {
+                let name = t.names.generate();
+
+                t.alloc_as_loc(*ptr, name)
+            },
+        };
+
+        if let Some(call) = lower(class, name, t) {
+            t.swap(ptr, call);
+        }
+    }
+
+    fn on_export_statement(export: &ExportStatement<'ast>, _: &'ast StatementNode<'ast>, t: &mut Transformer<'ast>) {
+        if let ExportStatement::DefaultDeclaration(ref declaration) = *export {
+            if let Expression::Class(Class { name: OptionalName(Some(_)), .. }) = declaration.item {
+                t.keep(declaration);
+            }
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_class_statement.push(TransformClass::on_class_statement);
+        dv.on_class_expression.push(TransformClass::on_class_expression);
+        dv.on_export_statement.push(TransformClass::on_export_statement);
+    }
+}
+
+/// Key of a member, once computed keys are replaced by temporaries.
+#[derive(Clone, Copy)]
+enum Key<'ast> {
+    Name(&'ast str),
+    Computed(ExpressionNode<'ast>),
+}
+
+/// Allocates the nodes of a lowered class, at the location of its body.
+struct Lowering<'a, 'ast: 'a> {
+    t: &'a mut Transformer<'ast>,
+    loc: BlockNode<'ast, ClassMember<'ast>>,
+}
+
+impl<'a, 'ast> Lowering<'a, 'ast> {
+    #[inline]
+    fn expression<I: Into<Expression<'ast>>>(&self, item: I) -> ExpressionNode<'ast> {
+        self.t.alloc_as_loc(self.loc, item)
+    }
+
+    #[inline]
+    fn statement<I: Into<Statement<'ast>>>(&self, item: I) -> StatementNode<'ast> {
+        self.t.alloc_as_loc(self.loc, item)
+    }
+
+    #[inline]
+    fn member(&self, object: ExpressionNode<'ast>, key: Key<'ast>) -> ExpressionNode<'ast> {
+        match key {
+            Key::Name(name) => self.expression(MemberExpression {
+                object,
+                property: self.t.alloc_as_loc(self.loc, name),
+                optional: false,
+            }),
+            Key::Computed(property) => self.expression(ComputedMemberExpression {
+                object,
+                property,
+                optional: false,
+            }),
+        }
+    }
+
+    #[inline]
+    fn call(&mut self, callee: ExpressionNode<'ast>, arguments: &[ExpressionNode<'ast>]) -> ExpressionNode<'ast> {
+        let arguments = self.t.list(arguments);
+
+        self.expression(CallExpression {
+            callee,
+            arguments,
+            optional: false,
+        })
+    }
+
+    #[inline]
+    fn assign(&self, left: ExpressionNode<'ast>, right: ExpressionNode<'ast>) -> StatementNode<'ast> {
+        self.statement(self.expression(BinaryExpression {
+            operator: OperatorKind::Assign,
+            left,
+            right,
+        }))
+    }
+
+    /// The value of a field without an initializer, `void 0`.
+    #[inline]
+    fn undefined(&self) -> ExpressionNode<'ast> {
+        self.expression(PrefixExpression {
+            operator: OperatorKind::Void,
+            operand: self.expression(Literal::Number(NumberLiteral::new("0"))),
+        })
+    }
+
+    #[inline]
+    fn string(&self, value: &str) -> ExpressionNode<'ast> {
+        let raw = self.t.arena.alloc_string(format!("\"{}\"", value));
+
+        self.expression(Literal::String(StringLiteral { value: &raw[1..raw.len() - 1], raw }))
+    }
+
+    /// Object literal with the `properties`, the keys of which are names.
+    fn object(&mut self, properties: &[(&'ast str, ExpressionNode<'ast>)]) -> ExpressionNode<'ast> {
+        let properties: Vec<_> = properties.iter().map(|&(name, value)| {
+            let key = self.t.alloc_as_loc(self.loc, PropertyKey::Literal(name));
+
+            self.t.alloc_as_loc(self.loc, Property::Literal { key, value })
+        }).collect();
+
+        let body = self.t.list(properties);
+
+        self.expression(ObjectExpression { body })
+    }
+
+    #[inline]
+    fn function<N: Name<'ast>>(&mut self, name: N, params: NodeList<'ast, Pattern<'ast>>, body: &[StatementNode<'ast>]) -> Function<'ast, N> {
+        let body = self.t.list(body);
+
+        Function {
+            name,
+            generator: false,
+            is_async: false,
+            params,
+            body: self.t.alloc_as_loc(self.loc, Block { body }),
+        }
+    }
+
+    /// `function.call(receiver, arguments)`, or `function.apply(receiver, a)`
+    /// if the only argument is `...a`.
+    fn invoke(&mut self, function: ExpressionNode<'ast>, receiver: ExpressionNode<'ast>, arguments: ExpressionList<'ast>) -> ExpressionNode<'ast> {
+        if let Some(&Expression::Spread(spread)) = arguments.only_element().map(|argument| &argument.item) {
+            let callee = self.member(function, Key::Name("apply"));
+
+            return self.call(callee, &[receiver, spread.argument]);
+        }
+
+        let callee = self.member(function, Key::Name("call"));
+        let arguments: Vec<_> = Some(receiver).into_iter().chain(arguments.iter().cloned()).collect();
+
+        self.call(callee, &arguments)
+    }
+
+    /// Object the properties of `super` are looked up on, the prototype of
+    /// the `parent` for instance members, the `parent` for static ones.
+    fn home(&self, parent: Option<&'ast str>, is_static: bool) -> ExpressionNode<'ast> {
+        match (parent, is_static) {
+            (Some(parent), true) => self.expression(parent),
+            (Some(parent), false) => self.member(self.expression(parent), Key::Name("prototype")),
+            (None, true) => self.member(self.expression("Function"), Key::Name("prototype")),
+            (None, false) => self.member(self.expression("Object"), Key::Name("prototype")),
+        }
+    }
+
+    /// `getter(home, key, receiver)`, reading the property of `super` the
+    /// `member` reads.
+    fn get(&mut self, getter: &'ast str, parent: Option<&'ast str>, is_static: bool, member: Expression<'ast>, receiver: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        let key = match member {
+            Expression::Member(member) => self.string(member.property.item),
+            Expression::ComputedMember(member) => member.property,
+            _ => unreachable!("The parser leaves no other use of `super`"),
+        };
+        let home = self.home(parent, is_static);
+        let callee = self.expression(getter);
+
+        self.call(callee, &[home, key, receiver])
+    }
+
+    /// Helper reading the property `key` of `object` for the `receiver`, as
+    /// properties of `super` are read:
+    ///
+    /// ```js
+    /// function $_ratel0(object, key, receiver) {
+    ///     var descriptor = Object.getOwnPropertyDescriptor(object, key);
+    ///     if (descriptor) return descriptor.get ? descriptor.get.call(receiver) : descriptor.value;
+    ///     object = Object.getPrototypeOf(object);
+    ///     if (object !== null) return $_ratel0(object, key, receiver);
+    /// }
+    /// ```
+    fn getter(&mut self, name: &'ast str) -> StatementNode<'ast> {
+        let callee = self.expression("Object");
+        let callee = self.member(callee, Key::Name("getOwnPropertyDescriptor"));
+        let init = self.call(callee, &[self.expression("object"), self.expression("key")]);
+        let id = self.t.alloc_as_loc(self.loc, Pattern::Identifier("descriptor"));
+        let declarator = self.t.alloc_as_loc(self.loc, Declarator {
+            id,
+            init: Some(init),
+        });
+        let declaration = self.statement(DeclarationStatement {
+            kind: DeclarationKind::Var,
+            declarators: NodeList::from(self.t.arena, declarator),
+        });
+
+        let test = self.member(self.expression("descriptor"), Key::Name("get"));
+        let get = self.member(self.expression("descriptor"), Key::Name("get"));
+        let get = self.member(get, Key::Name("call"));
+        let consequent = self.call(get, &[self.expression("receiver")]);
+        let alternate = self.member(self.expression("descriptor"), Key::Name("value"));
+        let value = self.expression(ConditionalExpression { test, consequent, alternate });
+        let found = self.statement(IfStatement {
+            test: self.expression("descriptor"),
+            consequent: self.statement(ReturnStatement { value: Some(value) }),
+            alternate: None,
+        });
+
+        let callee = self.member(self.expression("Object"), Key::Name("getPrototypeOf"));
+        let prototype = self.call(callee, &[self.expression("object")]);
+        let next = self.assign(self.expression("object"), prototype);
+
+        let test = self.expression(BinaryExpression {
+            operator: OperatorKind::StrictInequality,
+            left: self.expression("object"),
+            right: self.expression(Literal::Null),
+        });
+        let recursion = self.call(self.expression(name), &[self.expression("object"), self.expression("key"), self.expression("receiver")]);
+        let lookup = self.statement(IfStatement {
+            test,
+            consequent: self.statement(ReturnStatement { value: Some(recursion) }),
+            alternate: None,
+        });
+
+        let params: Vec<_> = ["object", "key", "receiver"].iter().map(|&param| {
+            self.t.alloc_as_loc(self.loc, Pattern::Identifier(param))
+        }).collect();
+        let params = self.t.list(params);
+        let name = MandatoryName(self.t.alloc_as_loc(self.loc, name));
+        let function = self.function(name, params, &[declaration, found, next, lookup]);
+
+        self.statement(function)
+    }
+
+    /// `Object.defineProperty(object, key, { get: value, configurable: true })`
+    fn accessor(&mut self, object: ExpressionNode<'ast>, key: Key<'ast>, kind: MethodKind, value: ExpressionNode<'ast>) -> StatementNode<'ast> {
+        let key = match key {
+            Key::Name(name) => self.string(name),
+            Key::Computed(key) => key,
+        };
+        let kind = match kind {
+            MethodKind::Set => "set",
+            _               => "get",
+        };
+
+        let configurable = self.expression(Literal::Boolean(true));
+        let descriptor = self.object(&[(kind, value), ("configurable", configurable)]);
+        let callee = self.expression("Object");
+        let callee = self.member(callee, Key::Name("defineProperty"));
+
+        let call = self.call(callee, &[object, key, descriptor]);
+
+        self.statement(call)
+    }
+}
+
+/// Get the call of the function the class is lowered into, `None` if the
+/// class can't be lowered.
+fn lower<'ast, N>(class: &Class<'ast, N>, name: IdentifierNode<'ast>, t: &mut Transformer<'ast>) -> Option<CallExpression<'ast>> where
+    N: Name<'ast>,
+{
+    let members = class.body.body;
+
+    if members.iter().any(|member| matches!(member.item, ClassMember::Error)) {
+        return None;
+    }
+
+    // Private names are only accessible within the class body
+    let private: Vec<_> = members.iter().filter_map(|member| match member.item {
+        ClassMember::Method { key, .. } |
+        ClassMember::Property { key, .. } if matches!(key.item, PropertyKey::Private(_)) => Some(key),
+        _ => None,
+    }).collect();
+
+    if !private.is_empty() {
+        let message = "Private members can't be lowered, the class is left as is";
+        let diagnostic = private.iter().fold(Diagnostic::new(Severity::Error, "class-private", message), |diagnostic, key| {
+            diagnostic.with_primary(key.start as usize, key.end as usize, "private member")
+        });
+
+        t.report(diagnostic);
+
+        return None;
+    }
+
+    // `this` of the static fields, which is the class
+    let mut ctx = ThisContext {
+        scopes: Vec::new(),
+        uses: Vec::new(),
+    };
+
+    for member in members {
+        if let ClassMember::Property { is_static: true, ref value, .. } = member.item {
+            value.traverse(&ThisCollector, &mut ctx);
+        }
+    }
+
+    if ctx.uses.iter().any(|node| t.is_disabled(node)) {
+        return None;
+    }
+
+    // `super` of the members, with the kind of member it's used in
+    let mut supers = SuperContext {
+        scopes: Vec::new(),
+        parents: Vec::new(),
+        home: Home::Instance,
+        uses: Vec::new(),
+        unsupported: Vec::new(),
+    };
+
+    for member in members {
+        match member.item {
+            ClassMember::Method { is_static, kind, ref value, .. } => {
+                supers.home = match (is_static, kind) {
+                    (true, _) => Home::Static,
+                    (false, MethodKind::Constructor) => Home::Constructor,
+                    (false, _) => Home::Instance,
+                };
+
+                // Traversing the function itself would enter its scope
+                value.params.traverse(&SuperCollector, &mut supers);
+                value.body.body.traverse(&SuperCollector, &mut supers);
+            },
+            ClassMember::Property { is_static, ref value, .. } => {
+                supers.home = match is_static {
+                    true => Home::StaticField,
+                    false => Home::Instance,
+                };
+
+                value.traverse(&SuperCollector, &mut supers);
+            },
+            ClassMember::StaticBlock { ref body } => {
+                supers.home = Home::Static;
+
+                body.traverse(&SuperCollector, &mut supers);
+            },
+            ClassMember::Error => unreachable!(),
+        }
+    }
+
+    let calls_without_parent = class.extends.is_none() && supers.uses.iter().any(|usage| usage.kind == SuperKind::Call);
+
+    if !supers.unsupported.is_empty() {
+        let message = "`super` is used in a way that can't be lowered, the class is left as is";
+        let diagnostic = supers.unsupported.iter().fold(Diagnostic::new(Severity::Error, "class-super-unsupported", message), |diagnostic, node| {
+            diagnostic.with_primary(node.start as usize, node.end as usize, "unsupported use of `super`")
+        });
+
+        t.report(diagnostic);
+
+        return None;
+    }
+
+    if calls_without_parent || supers.uses.iter().any(|usage| t.is_disabled(usage.node)) {
+        return None;
+    }
+
+    let reads: Vec<_> = supers.uses.iter().filter(|usage| usage.kind != SuperKind::Call).collect();
+
+    if !reads.is_empty() && !t.helpers() {
+        let message = "Reading properties of `super` needs a helper, the class is left as is";
+        let diagnostic = reads.iter().fold(Diagnostic::new(Severity::Error, "class-super", message), |diagnostic, usage| {
+            diagnostic.with_primary(usage.node.start as usize, usage.node.end as usize, "property of `super`")
+        });
+
+        t.report(diagnostic);
+
+        return None;
+    }
+
+    let mut params = Vec::new();
+    let mut arguments = Vec::new();
+
+    let parent = class.extends.map(|extends| {
+        let parent = t.names.generate();
+
+        params.push(t.alloc_as_loc(extends, Pattern::Identifier(parent)));
+        arguments.push(extends);
+
+        parent
+    });
+
+    // Temporaries of the computed keys, in source order
+    let keys: Vec<Key> = members.iter().map(|member| {
+        let key = match member.item {
+            ClassMember::Method { key, .. } |
+            ClassMember::Property { key, .. } => key,
+
+            // Not used, static blocks have no key
+            ClassMember::StaticBlock { .. } => return Key::Name(""),
+            ClassMember::Error => unreachable!(),
+        };
+
+        match key.item {
+            PropertyKey::Computed(expression) => match expression.item {
+                Expression::Literal(_) => Key::Computed(expression),
+                _ => {
+                    let temporary = t.names.generate();
+
+                    params.push(t.alloc_as_loc(expression, Pattern::Identifier(temporary)));
+                    arguments.push(expression);
+
+                    Key::Computed(t.alloc_as_loc(expression, temporary))
+                },
+            },
+            PropertyKey::Binary(raw) => Key::Computed(t.alloc_as_loc(key, Literal::Number(NumberLiteral::new(raw)))),
+            PropertyKey::Literal(raw) => match raw.as_bytes()[0] {
+                b'"' | b'\'' => Key::Computed(t.alloc_as_loc(key, Literal::String(t.string_literal(raw)))),
+                b'0'..=b'9' | b'.' => Key::Computed(t.alloc_as_loc(key, Literal::Number(NumberLiteral::new(raw)))),
+                _ => Key::Name(raw),
+            },
+            PropertyKey::Private(_) => unreachable!(),
+        }
+    }).collect();
+
+    let has_fields = members.iter().any(|member| matches!(member.item, ClassMember::Property { is_static: false, .. }));
+    let fields_name = match has_fields {
+        true => Some(t.names.generate()),
+        false => None,
+    };
+    let getter = match reads.is_empty() {
+        true => None,
+        false => Some(t.names.generate()),
+    };
+
+    for node in &ctx.uses {
+        t.swap(node, Expression::Identifier(name.item));
+    }
+
+    let loc = class.body;
+    let mut l = Lowering { t, loc };
+
+    // Inner uses first, as in `super[super.a]`, so that the members read
+    // by the outer ones have them replaced already
+    for usage in supers.uses.iter().rev() {
+        let receiver = |l: &Lowering<'_, 'ast>| match usage.home {
+            Home::StaticField => l.expression(name.item),
+            _ => l.expression(ThisExpression),
+        };
+        let is_static = matches!(usage.home, Home::Static | Home::StaticField);
+
+        let call = match usage.node.item {
+            Expression::Call(call) => Some(call),
+            _ => None,
+        };
+
+        let replacement = match (usage.kind, call, getter) {
+            (SuperKind::Call, Some(call), _) => {
+                let parent = l.expression(parent.expect("Classes calling `super` without a parent aren't lowered"));
+                let this = receiver(&l);
+
+                l.invoke(parent, this, call.arguments)
+            },
+            (SuperKind::Get, _, Some(getter)) => {
+                let this = receiver(&l);
+
+                l.get(getter, parent, is_static, usage.node.item, this)
+            },
+            (SuperKind::Invoke, Some(call), Some(getter)) => {
+                let this = receiver(&l);
+                let method = l.get(getter, parent, is_static, call.callee.item, this);
+                let this = receiver(&l);
+
+                l.invoke(method, this, call.arguments)
+            },
+            _ => unreachable!(),
+        };
+
+        l.t.swap(usage.node, replacement.item);
+    }
+
+    let mut constructor = None;
+    let mut fields = Vec::new();
+    let mut methods = Vec::new();
+    let mut statics = Vec::new();
+
+    for (member, &key) in members.iter().zip(keys.iter()) {
+        match member.item {
+            ClassMember::Method { kind: MethodKind::Constructor, value, .. } => constructor = Some(value),
+            ClassMember::Method { is_static, kind, value, .. } => {
+                let object = l.expression(name.item);
+                let object = match is_static {
+                    true => object,
+                    false => l.member(object, Key::Name("prototype")),
+                };
+                let function = l.t.alloc_as_loc(value, Function {
+                    name: OptionalName::empty(),
+                    generator: value.generator,
+                    is_async: value.is_async,
+                    params: value.params,
+                    body: value.body,
+                });
+
+                methods.push(match kind {
+                    MethodKind::Method => {
+                        let left = l.member(object, key);
+
+                        l.assign(left, function)
+                    },
+                    _ => l.accessor(object, key, kind, function),
+                });
+            },
+            ClassMember::Property { is_static: false, value, .. } => {
+                let this = l.expression(ThisExpression);
+                let left = l.member(this, key);
+                let value = value.unwrap_or_else(|| l.undefined());
+
+                fields.push(l.assign(left, value));
+            },
+            ClassMember::Property { is_static: true, value, .. } => {
+                let object = l.expression(name.item);
+                let left = l.member(object, key);
+                let value = value.unwrap_or_else(|| l.undefined());
+
+                statics.push(l.assign(left, value));
+            },
+            // `function () { ... }.call(A)`, the block being a function scope
+            // with the class as `this`
+            ClassMember::StaticBlock { body } => {
+                let body: Vec<_> = body.iter().cloned().collect();
+                let function = l.function(OptionalName::empty(), NodeList::empty(), &body);
+                let function = l.expression(function);
+                let callee = l.member(function, Key::Name("call"));
+                let object = l.expression(name.item);
+                let call = l.call(callee, &[object]);
+
+                statics.push(l.statement(call));
+            },
+            ClassMember::Error => unreachable!(),
+        }
+    }
+
+    let directive = l.expression(Literal::String(StringLiteral { value: "use strict", raw: "\"use strict\"" }));
+    let mut body = vec![l.statement(directive)];
+
+    if let Some(getter) = getter {
+        body.push(l.getter(getter));
+    }
+    let mut constructor_body = Vec::new();
+
+    if let (None, Some(parent)) = (constructor, parent) {
+        let callee = l.expression(parent);
+        let callee = l.member(callee, Key::Name("apply"));
+        let this = l.expression(ThisExpression);
+        let arguments = l.expression("arguments");
+        let call = l.call(callee, &[this, arguments]);
+
+        constructor_body.push(l.statement(call));
+    }
+
+    if let Some(fields_name) = fields_name {
+        let callee = l.expression(fields_name);
+        let callee = l.member(callee, Key::Name("call"));
+        let this = l.expression(ThisExpression);
+        let call = l.call(callee, &[this]);
+
+        constructor_body.push(l.statement(call));
+    }
+
+    let params_list = match constructor {
+        Some(constructor) => {
+            constructor_body.extend(constructor.body.body.iter().cloned());
+            constructor.params
+        },
+        None => NodeList::empty(),
+    };
+
+    let function = l.function(MandatoryName(name), params_list, &constructor_body);
+
+    body.push(l.statement(function));
+
+    if let Some(fields_name) = fields_name {
+        let fields_name = MandatoryName(l.t.alloc_as_loc(loc, fields_name));
+        let function = l.function(fields_name, NodeList::empty(), &fields);
+
+        body.push(l.statement(function));
+    }
+
+    if let Some(parent) = parent {
+        // A.prototype = Object.create(parent.prototype, { constructor: ... })
+        let value = l.expression(name.item);
+        let writable = l.expression(Literal::Boolean(true));
+        let configurable = l.expression(Literal::Boolean(true));
+        let descriptor = l.object(&[("value", value), ("writable", writable), ("configurable", configurable)]);
+        let descriptors = l.object(&[("constructor", descriptor)]);
+        let prototype = l.expression(parent);
+        let prototype = l.member(prototype, Key::Name("prototype"));
+        let callee = l.expression("Object");
+        let callee = l.member(callee, Key::Name("create"));
+        let create = l.call(callee, &[prototype, descriptors]);
+        let object = l.expression(name.item);
+        let left = l.member(object, Key::Name("prototype"));
+
+        body.push(l.assign(left, create));
+
+        // A.__proto__ = parent, for the static members
+        let object = l.expression(name.item);
+        let left = l.member(object, Key::Name("__proto__"));
+        let right = l.expression(parent);
+
+        body.push(l.assign(left, right));
+    }
+
+    body.extend(methods);
+    body.extend(statics);
+
+    let value = l.expression(name.item);
+
+    body.push(l.statement(ReturnStatement { value: Some(value) }));
+
+    let params = l.t.list(params);
+    let wrapper = l.function(OptionalName::empty(), params, &body);
+    let callee = l.expression(wrapper);
+    let arguments = l.t.list(arguments);
+
+    Some(CallExpression {
+        callee,
+        arguments,
+        optional: false,
+    })
+}
+
+struct ThisContext<'ast> {
+    /// Kinds of the scopes entered so far
+    scopes: Vec<ScopeKind>,
+
+    /// Uses of `this` outside of functions
+    uses: Vec<&'ast ExpressionNode<'ast>>,
+}
+
+struct ThisCollector;
+
+impl<'ast> StaticVisitor<'ast> for ThisCollector {
+    type Context = ThisContext<'ast>;
+
+    #[inline]
+    fn on_enter_scope(kind: ScopeKind, ctx: &mut ThisContext<'ast>) {
+        ctx.scopes.push(kind);
+    }
+
+    #[inline]
+    fn on_leave_scope(ctx: &mut ThisContext<'ast>) {
+        ctx.scopes.pop();
+    }
+
+    #[inline]
+    fn on_this_expression(node: &'ast ExpressionNode<'ast>, ctx: &mut ThisContext<'ast>) {
+        if !ctx.scopes.contains(&ScopeKind::Function) {
+            ctx.uses.push(node);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, ThisContext<'ast>>) {
+        dv.on_enter_scope.push(Self::on_enter_scope);
+        dv.on_leave_scope.push(Self::on_leave_scope);
+        dv.on_this_expression.push(Self::on_this_expression);
+    }
+}
+
+/// Kind of member `super` is used in.
+#[derive(Clone, Copy, PartialEq)]
+enum Home {
+    Constructor,
+    Instance,
+    Static,
+    StaticField,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SuperKind {
+    /// `super(a)`, the node being the call
+    Call,
+
+    /// `super.a`, the node being the member
+    Get,
+
+    /// `super.a(b)`, the node being the call
+    Invoke,
+}
+
+struct SuperUse<'ast> {
+    node: &'ast ExpressionNode<'ast>,
+    kind: SuperKind,
+    home: Home,
+}
+
+struct SuperContext<'ast> {
+    /// Kinds of the scopes entered so far
+    scopes: Vec<ScopeKind>,
+
+    /// Nodes the current one is nested in, outermost first
+    parents: Vec<ParentNode<'ast>>,
+
+    /// Member being traversed
+    home: Home,
+
+    /// Uses of `super` outside of functions and nested classes
+    uses: Vec<SuperUse<'ast>>,
+
+    /// Uses of `super` the lowering doesn't support, such as assignments
+    /// to its properties
+    unsupported: Vec<&'ast ExpressionNode<'ast>>,
+}
+
+struct SuperCollector;
+
+impl<'ast> StaticVisitor<'ast> for SuperCollector {
+    type Context = SuperContext<'ast>;
+
+    #[inline]
+    fn push_parent(node: ParentNode<'ast>, ctx: &mut SuperContext<'ast>) {
+        ctx.parents.push(node);
+    }
+
+    #[inline]
+    fn pop_parent(ctx: &mut SuperContext<'ast>) {
+        ctx.parents.pop();
+    }
+
+    #[inline]
+    fn on_enter_scope(kind: ScopeKind, ctx: &mut SuperContext<'ast>) {
+        ctx.scopes.push(kind);
+    }
+
+    #[inline]
+    fn on_leave_scope(ctx: &mut SuperContext<'ast>) {
+        ctx.scopes.pop();
+    }
+
+    fn on_super_expression(node: &'ast ExpressionNode<'ast>, ctx: &mut SuperContext<'ast>) {
+        if ctx.scopes.contains(&ScopeKind::Function) || ctx.parents.iter().any(is_class) {
+            return;
+        }
+
+        let mut ancestors = ctx.parents.iter().rev().cloned();
+        let usage = match (ancestors.next(), ancestors.next()) {
+            (Some(ParentNode::Expression(parent)), grandparent) => classify(parent, grandparent, ctx.home),
+            _ => None,
+        };
+
+        match usage {
+            Some(usage) => ctx.uses.push(usage),
+            None => ctx.unsupported.push(node),
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, SuperContext<'ast>>) {
+        dv.push_parent.push(Self::push_parent);
+        dv.pop_parent.push(Self::pop_parent);
+        dv.on_enter_scope.push(Self::on_enter_scope);
+        dv.on_leave_scope.push(Self::on_leave_scope);
+        dv.on_super_expression.push(Self::on_super_expression);
+    }
+}
+
+#[inline]
+fn is_class(node: &ParentNode) -> bool {
+    match *node {
+        ParentNode::Statement(statement) => matches!(statement.item, Statement::Class(_)),
+        ParentNode::Expression(expression) => matches!(expression.item, Expression::Class(_)),
+    }
+}
+
+/// Use of `super` in the `parent`, `None` if it can't be lowered.
+fn classify<'ast>(parent: &'ast ExpressionNode<'ast>, grandparent: Option<ParentNode<'ast>>, home: Home) -> Option<SuperUse<'ast>> {
+    let kind = match parent.item {
+        Expression::Call(ref call) => match home {
+            Home::Constructor if spreads_lowered(call.arguments) => SuperKind::Call,
+            _ => return None,
+        },
+        Expression::Member(_) | Expression::ComputedMember(_) => match grandparent {
+            Some(ParentNode::Expression(grandparent)) => match grandparent.item {
+                Expression::Call(ref call) if ptr::eq(&call.callee, parent) => {
+                    if call.optional || !spreads_lowered(call.arguments) {
+                        return None;
+                    }
+
+                    return Some(SuperUse { node: grandparent, kind: SuperKind::Invoke, home });
+                },
+                Expression::Binary(ref binary) if binary.operator.assignment() && ptr::eq(&binary.left, parent) => return None,
+                Expression::Prefix(ref prefix) if ptr::eq(&prefix.operand, parent) => match prefix.operator {
+                    OperatorKind::Increment | OperatorKind::Decrement | OperatorKind::Delete => return None,
+                    _ => SuperKind::Get,
+                },
+                Expression::Postfix(ref postfix) if ptr::eq(&postfix.operand, parent) => return None,
+                Expression::TaggedTemplate(ref tagged) if ptr::eq(&tagged.tag, parent) => return None,
+                _ => SuperKind::Get,
+            },
+            Some(ParentNode::Statement(grandparent)) => match grandparent.item {
+                Statement::ForIn(ref statement) if is_left(&statement.left.item, parent) => return None,
+                Statement::ForOf(ref statement) if is_left(&statement.left.item, parent) => return None,
+                _ => SuperKind::Get,
+            },
+            None => SuperKind::Get,
+        },
+        _ => return None,
+    };
+
+    Some(SuperUse { node: parent, kind, home })
+}
+
+/// Check whether the spread `arguments` can be lowered by `Lowering::invoke`.
+#[inline]
+fn spreads_lowered(arguments: ExpressionList) -> bool {
+    let spreads = arguments.iter().filter(|argument| matches!(argument.item, Expression::Spread(_))).count();
+
+    spreads == 0 || (spreads == 1 && arguments.only_element().is_some())
+}
+
+#[inline]
+fn is_left<'ast>(left: &ForInit<'ast>, node: &ExpressionNode<'ast>) -> bool {
+    match *left {
+        ForInit::Expression(ref expression) => ptr::eq(expression, node),
+        ForInit::Declaration(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel_visitor::Visitor;
+    use pass::{Pass, Visit, VisitWith, Options};
+
+    fn assert_transform(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+
+        Visit(TransformClass).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    fn getter(name: &str) -> String {
+        format!(concat!(
+            "function {0}(object, key, receiver) {{ ",
+            "var descriptor = Object.getOwnPropertyDescriptor(object, key); ",
+            "if (descriptor) return descriptor.get ? descriptor.get.call(receiver) : descriptor.value; ",
+            "object = Object.getPrototypeOf(object); ",
+            "if (object !== null) return {0}(object, key, receiver); ",
+            "}} ",
+        ), name)
+    }
+
+    const PROTOTYPE: &str = concat!(
+        "A.prototype = Object.create($_ratel0.prototype, { constructor: { value: A, writable: true, configurable: true } }); ",
+        "A.__proto__ = $_ratel0; ",
+    );
+
+    #[test]
+    fn transform_class() {
+        assert_transform(
+            "class A { constructor(a) { this.a = a; } m() { return 1; } static s() {} }",
+            r#"var A = function () { "use strict"; function A(a) { this.a = a; } A.prototype.m = function () { return 1; }; A.s = function () {}; return A; }();"#
+        );
+        assert_transform(
+            "class A {}",
+            r#"var A = function () { "use strict"; function A() {} return A; }();"#
+        );
+    }
+
+    #[test]
+    fn transform_class_expression() {
+        assert_transform(
+            "foo(class B { m() {} });",
+            r#"foo(function () { "use strict"; function B() {} B.prototype.m = function () {}; return B; }());"#
+        );
+        assert_transform(
+            "foo(class {});",
+            r#"foo(function () { "use strict"; function $_ratel0() {} return $_ratel0; }());"#
+        );
+    }
+
+    #[test]
+    fn export_default_classes() {
+        use ratel_codegen::codegen;
+
+        // The class keeps its binding in the module, classes within it are
+        // still lowered
+        let source = "export default class A { m() { return class B {}; } } A.x = 1;";
+        let module = parse(source).unwrap();
+
+        Visit(TransformClass).run(&module);
+
+        let code = codegen(&module, true);
+
+        assert_eq!(code, r#"export default class A{m(){return function(){"use strict";function B(){}return B;}();}}A.x=1;"#);
+        assert_eq!(parse(&code).unwrap().body(), module.body());
+
+        let module = parse("export default class {} a;").unwrap();
+
+        Visit(TransformClass).run(&module);
+
+        let code = codegen(&module, true);
+
+        assert_eq!(code, r#"export default(function(){"use strict";function $_ratel0(){}return $_ratel0;}());a;"#);
+        assert!(parse(&code).is_ok());
+    }
+
+    #[test]
+    fn transform_class_extends() {
+        assert_transform(
+            "class A extends B {}",
+            concat!(
+                r#"var A = function ($_ratel0) { "use strict"; function A() { $_ratel0.apply(this, arguments); } "#,
+                "A.prototype = Object.create($_ratel0.prototype, { constructor: { value: A, writable: true, configurable: true } }); ",
+                "A.__proto__ = $_ratel0; return A; }(B);",
+            )
+        );
+    }
+
+    #[test]
+    fn transform_class_literal_keys() {
+        assert_transform(
+            "class A { 1() {} 0b1() {} ['c']() {} }",
+            r#"var A = function () { "use strict"; function A() {} A.prototype[1] = function () {}; A.prototype[0b1] = function () {}; A.prototype['c'] = function () {}; return A; }();"#
+        );
+    }
+
+    #[test]
+    fn transform_class_accessors() {
+        assert_transform(
+            "class A { get a() { return 1; } static set b(v) {} }",
+            concat!(
+                r#"var A = function () { "use strict"; function A() {} "#,
+                r#"Object.defineProperty(A.prototype, "a", { get: function () { return 1; }, configurable: true }); "#,
+                r#"Object.defineProperty(A, "b", { set: function (v) {}, configurable: true }); "#,
+                "return A; }();",
+            )
+        );
+    }
+
+    #[test]
+    fn computed_keys_are_evaluated_once_in_order() {
+        assert_transform(
+            "class A extends f() { [g()]() {} static [h()] = 1; [i()] = 2; [this.j]() {} }",
+            concat!(
+                r#"var A = function ($_ratel0, $_ratel1, $_ratel2, $_ratel3, $_ratel4) { "use strict"; "#,
+                "function A() { $_ratel0.apply(this, arguments); $_ratel5.call(this); } ",
+                "function $_ratel5() { this[$_ratel3] = 2; } ",
+                "A.prototype = Object.create($_ratel0.prototype, { constructor: { value: A, writable: true, configurable: true } }); ",
+                "A.__proto__ = $_ratel0; ",
+                "A.prototype[$_ratel1] = function () {}; A.prototype[$_ratel4] = function () {}; ",
+                "A[$_ratel2] = 1; ",
+                "return A; }(f(), g(), h(), i(), this.j);",
+            )
+        );
+    }
+
+    #[test]
+    fn transform_class_fields() {
+        assert_transform(
+            "class A { a = b; constructor(b) { this.c = b; } static d = this.a; static e = () => this; static f = function () { return this; }; }",
+            concat!(
+                r#"var A = function () { "use strict"; "#,
+                "function A(b) { $_ratel0.call(this); this.c = b; } ",
+                "function $_ratel0() { this.a = b; } ",
+                "A.d = A.a; A.e = () => A; A.f = function () { return this; }; ",
+                "return A; }();",
+            )
+        );
+    }
+
+    #[test]
+    fn transform_fields_without_initializer() {
+        assert_transform(
+            "class A { a; static b; }",
+            concat!(
+                r#"var A = function () { "use strict"; "#,
+                "function A() { $_ratel0.call(this); } ",
+                "function $_ratel0() { this.a = void 0; } ",
+                "A.b = void 0; ",
+                "return A; }();",
+            )
+        );
+    }
+
+    #[test]
+    fn static_fields_after_methods() {
+        assert_transform(
+            "class A { static a = A.m(); static m() {} }",
+            r#"var A = function () { "use strict"; function A() {} A.m = function () {}; A.a = A.m(); return A; }();"#
+        );
+    }
+
+    #[test]
+    fn transform_static_blocks() {
+        assert_transform(
+            "class A { static a = 1; static { this.b = this.a; } }",
+            r#"var A = function () { "use strict"; function A() {} A.a = 1; (function () { this.b = this.a; }.call(A)); return A; }();"#
+        );
+    }
+
+    #[test]
+    fn nested_classes() {
+        assert_transform(
+            "class A { m() { return class B {}; } }",
+            r#"var A = function () { "use strict"; function A() {} A.prototype.m = function () { return function () { "use strict"; function B() {} return B; }(); }; return A; }();"#
+        );
+    }
+
+    #[test]
+    fn super_calls() {
+        assert_transform(
+            "class A extends B { constructor(a) { super(a, 1); } }",
+            &[
+                r#"var A = function ($_ratel0) { "use strict"; function A(a) { $_ratel0.call(this, a, 1); } "#,
+                PROTOTYPE,
+                "return A; }(B);",
+            ].concat()
+        );
+        assert_transform(
+            "class A extends B { constructor() { let f = () => super(...arguments); f(); } }",
+            &[
+                r#"var A = function ($_ratel0) { "use strict"; function A() { let f = () => $_ratel0.apply(this, arguments); f(); } "#,
+                PROTOTYPE,
+                "return A; }(B);",
+            ].concat()
+        );
+    }
+
+    #[test]
+    fn super_properties() {
+        assert_transform(
+            "class A extends B { m() { return super.m(1) + super[k]; } get g() { return () => super.g; } }",
+            &[
+                r#"var A = function ($_ratel0) { "use strict"; "#,
+                &getter("$_ratel1"),
+                "function A() { $_ratel0.apply(this, arguments); } ",
+                PROTOTYPE,
+                r#"A.prototype.m = function () { return $_ratel1($_ratel0.prototype, "m", this).call(this, 1) + $_ratel1($_ratel0.prototype, k, this); }; "#,
+                r#"Object.defineProperty(A.prototype, "g", { get: function () { return () => $_ratel1($_ratel0.prototype, "g", this); }, configurable: true }); "#,
+                "return A; }(B);",
+            ].concat()
+        );
+    }
+
+    #[test]
+    fn super_properties_in_static_members() {
+        assert_transform(
+            "class A extends B { static s() { return super.s(...a); } static f = super.f; a = super.a; }",
+            &[
+                r#"var A = function ($_ratel0) { "use strict"; "#,
+                &getter("$_ratel2"),
+                "function A() { $_ratel0.apply(this, arguments); $_ratel1.call(this); } ",
+                r#"function $_ratel1() { this.a = $_ratel2($_ratel0.prototype, "a", this); } "#,
+                PROTOTYPE,
+                r#"A.s = function () { return $_ratel2($_ratel0, "s", this).apply(this, a); }; "#,
+                r#"A.f = $_ratel2($_ratel0, "f", A); "#,
+                "return A; }(B);",
+            ].concat()
+        );
+    }
+
+    #[test]
+    fn super_properties_without_parent() {
+        assert_transform(
+            "class A { m() { return super.toString(); } static n() { return super.name; } }",
+            &[
+                r#"var A = function () { "use strict"; "#,
+                &getter("$_ratel0"),
+                "function A() {} ",
+                r#"A.prototype.m = function () { return $_ratel0(Object.prototype, "toString", this).call(this); }; "#,
+                r#"A.n = function () { return $_ratel0(Function.prototype, "name", this); }; "#,
+                "return A; }();",
+            ].concat()
+        );
+    }
+
+    #[test]
+    fn nested_super_properties() {
+        assert_transform(
+            "class A extends B { m() { return super[super.k]; } }",
+            &[
+                r#"var A = function ($_ratel0) { "use strict"; "#,
+                &getter("$_ratel1"),
+                "function A() { $_ratel0.apply(this, arguments); } ",
+                PROTOTYPE,
+                r#"A.prototype.m = function () { return $_ratel1($_ratel0.prototype, $_ratel1($_ratel0.prototype, "k", this), this); }; "#,
+                "return A; }(B);",
+            ].concat()
+        );
+    }
+
+    #[test]
+    fn super_of_nested_code_is_left_to_it() {
+        assert_transform(
+            "class A { m() { return { n() { return super.n; } }; } }",
+            r#"var A = function () { "use strict"; function A() {} A.prototype.m = function () { return { n() { return super.n; } }; }; return A; }();"#
+        );
+    }
+
+    #[test]
+    fn super_assignments_are_left_as_is() {
+        for source in &[
+            "class A extends B { m() { super.a = 1; } }",
+            "class A extends B { m() { super[a]++; } }",
+            "class A extends B { m() { delete super.a; } }",
+            "class A extends B { m() { for (super.a in b); } }",
+            "class A extends B { m() { super.a`b`; } }",
+            "class A extends B { m() { super.a(...b, c); } }",
+        ] {
+            assert_transform(source, source);
+        }
+    }
+
+    #[test]
+    fn super_assignments_are_reported() {
+        let source = "class A extends B { m() { super.a = 1; super[b]++; return super.c; } }";
+        let module = parse(source).unwrap();
+        let mut pass = VisitWith::new(TransformClass, Options::default());
+
+        assert_eq!(pass.run(&module), false);
+        assert_eq!(module.body(), parse(source).unwrap().body());
+
+        let diagnostics = pass.diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "class-super-unsupported");
+        assert_eq!(diagnostics[0].labels.len(), 2);
+        assert_eq!(diagnostics[0].labels[0].start, 26);
+        assert_eq!(diagnostics[0].labels[0].end, 31);
+    }
+
+    #[test]
+    fn super_properties_without_helpers() {
+        let source = "class A extends B { constructor() { super(); } m() { return super.m() + super.a; } }";
+        let module = parse(source).unwrap();
+        let mut pass = VisitWith::new(TransformClass, Options { helpers: false });
+
+        assert_eq!(pass.run(&module), false);
+        assert_eq!(module.body(), parse(source).unwrap().body());
+
+        let diagnostics = pass.diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "class-super");
+        assert_eq!(diagnostics[0].labels.len(), 2);
+        assert_eq!(pass.diagnostics(), vec![]);
+
+        // Helpers aren't needed to call the parent
+        let module = parse("class A extends B { constructor() { super(); } }").unwrap();
+
+        assert_eq!(pass.run(&module), true);
+        assert_eq!(pass.diagnostics(), vec![]);
+    }
+
+    #[test]
+    fn private_members_are_left_as_is() {
+        let source = "class A { #a = 1; static #b() {} m() { return this.#a; } }";
+        let module = parse(source).unwrap();
+        let mut pass = VisitWith::new(TransformClass, Options::default());
+
+        assert_eq!(pass.run(&module), false);
+        assert_eq!(module.body(), parse(source).unwrap().body());
+
+        let diagnostics = pass.diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "class-private");
+        assert_eq!(diagnostics[0].labels.len(), 2);
+    }
+
+    #[test]
+    fn transform_class_impls_visitor() {
+        let _: &dyn Visitor<Context = Transformer> = &TransformClass;
+    }
+}
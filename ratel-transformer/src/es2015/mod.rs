@@ -1,5 +1,10 @@
 mod arrow;
+mod class;
 
 use self::arrow::TransformArrow;
+use self::class::TransformClass;
 
-pub type PresetES2015 = TransformArrow;
+pub type PresetES2015 = (TransformArrow, TransformClass);
+
+/// Transforms of `PresetES2015`, for `Visit`.
+pub const PRESET_ES2015: PresetES2015 = (TransformArrow, TransformClass);
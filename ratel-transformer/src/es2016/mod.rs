@@ -26,12 +26,14 @@ impl<'ast> StaticVisitor<'ast> for PresetES2016 {
                 let callee = t.alloc(MemberExpression {
                     object: Node::from_static(MATH),
                     property: Node::from_static(POW),
+                    optional: false,
                 });
                 let arguments = t.list([node.left, node.right]);
 
                 t.swap(ptr, CallExpression {
                     callee,
-                    arguments
+                    arguments,
+                    optional: false,
                 });
             },
 
@@ -39,11 +41,13 @@ impl<'ast> StaticVisitor<'ast> for PresetES2016 {
                 let callee = t.alloc(MemberExpression {
                     object: Node::from_static(MATH),
                     property: Node::from_static(POW),
+                    optional: false,
                 });
                 let arguments = t.list([node.left, node.right]);
                 let right = t.alloc(CallExpression {
                     callee,
-                    arguments
+                    arguments,
+                    optional: false,
                 });
 
                 t.swap(ptr, BinaryExpression {
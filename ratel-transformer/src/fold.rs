@@ -0,0 +1,227 @@
+use ratel::ast::{Statement, StatementNode, StatementList, DeclarationKind, BlockStatement};
+use ratel::ast::statement::{SwitchStatement, BreakStatement, ForInit};
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+
+use constant::evaluate;
+use Transformer;
+
+/// Replaces a `switch` with a constant discriminant by a block with the
+/// statements of the selected case, up to the first `break`, including
+/// those of the following cases it falls through to.
+///
+/// Nothing is folded in modules declaring a binding named `undefined`,
+/// which values of `undefined` can't be known in.
+pub struct FoldSwitch;
+
+impl<'ast> StaticVisitor<'ast> for FoldSwitch {
+    type Context = Transformer<'ast>;
+
+    fn on_switch_statement(_: &SwitchStatement, ptr: &'ast StatementNode<'ast>, t: &mut Transformer<'ast>) {
+        if t.scope.declares_anywhere("undefined") {
+            return;
+        }
+
+        let body = match ptr.item {
+            Statement::Switch(ref switch) => fold_switch(switch),
+            _ => return,
+        };
+
+        if let Some(body) = body {
+            let body = t.list(body);
+
+            t.swap(ptr, BlockStatement { body });
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_switch_statement.push(FoldSwitch::on_switch_statement);
+    }
+}
+
+/// Statements of the folded switch, or `None` if it can't be folded.
+fn fold_switch<'ast>(switch: &'ast SwitchStatement<'ast>) -> Option<Vec<StatementNode<'ast>>> {
+    let discriminant = evaluate(&switch.discriminant.item)?;
+    let cases: Vec<_> = switch.cases.body.iter().collect();
+
+    let mut selected = None;
+    let mut default = None;
+
+    // Tests are evaluated in order until one matches, they must all
+    // be known up to that one
+    for (index, case) in cases.iter().enumerate() {
+        match case.test {
+            Some(test) => {
+                if evaluate(&test.item)?.strict_equals(&discriminant) {
+                    selected = Some(index);
+                    break;
+                }
+            },
+            None => default = default.or(Some(index)),
+        }
+    }
+
+    // Without a match or a default, all statements are skipped
+    let start = selected.or(default).unwrap_or(cases.len());
+    let mut body = Vec::new();
+    let mut running = false;
+    let mut ended = false;
+
+    for (index, case) in cases.iter().enumerate() {
+        running |= index == start;
+
+        for statement in case.consequent {
+            if running && !ended {
+                if let Statement::Break(BreakStatement { label: None }) = statement.item {
+                    ended = true;
+                    continue;
+                }
+
+                if breaks(&statement.item) {
+                    return None;
+                }
+
+                body.push(*statement);
+            } else if declares(&statement.item) {
+                // Declarations are visible from the statements that run
+                return None;
+            }
+        }
+    }
+
+    Some(body)
+}
+
+/// Check whether the statement declares a name visible outside of it, that
+/// is a `let`, `const`, function or class at its top, or a `var` anywhere.
+fn declares(statement: &Statement) -> bool {
+    match *statement {
+        Statement::Declaration(_) |
+        Statement::Function(_)    |
+        Statement::Class(_)       => true,
+        _ => contains_var(statement),
+    }
+}
+
+fn contains_var(statement: &Statement) -> bool {
+    use self::Statement::*;
+
+    let for_init = |init: &ForInit| match *init {
+        ForInit::Declaration(ref declaration) => declaration.kind == DeclarationKind::Var,
+        ForInit::Expression(_) => false,
+    };
+
+    match *statement {
+        Declaration(ref declaration) => declaration.kind == DeclarationKind::Var,
+        If(ref stmt) => contains_var(&stmt.consequent.item) || stmt.alternate.is_some_and(|alternate| contains_var(&alternate.item)),
+        While(ref stmt) => contains_var(&stmt.body.item),
+        Do(ref stmt) => contains_var(&stmt.body.item),
+        For(ref stmt) => stmt.init.is_some_and(|init| for_init(&init.item)) || contains_var(&stmt.body.item),
+        ForIn(ref stmt) => for_init(&stmt.left.item) || contains_var(&stmt.body.item),
+        ForOf(ref stmt) => for_init(&stmt.left.item) || contains_var(&stmt.body.item),
+        Try(ref stmt) => {
+            list_contains_var(stmt.block.body) ||
+            stmt.handler.is_some_and(|handler| list_contains_var(handler.body.body)) ||
+            stmt.finalizer.is_some_and(|finalizer| list_contains_var(finalizer.body))
+        },
+        Block(ref block) => list_contains_var(block.body),
+        Labeled(ref stmt) => contains_var(&stmt.body.item),
+        Switch(ref stmt) => stmt.cases.body.iter().any(|case| list_contains_var(case.consequent)),
+        _ => false,
+    }
+}
+
+fn list_contains_var(list: StatementList) -> bool {
+    list.iter().any(|statement| contains_var(&statement.item))
+}
+
+/// Check whether the statement has an unlabeled `break` nested in it, which
+/// doesn't belong to a loop or `switch` inside of it.
+fn breaks(statement: &Statement) -> bool {
+    use self::Statement::*;
+
+    match *statement {
+        Break(BreakStatement { label: None }) => true,
+        If(ref stmt) => breaks(&stmt.consequent.item) || stmt.alternate.is_some_and(|alternate| breaks(&alternate.item)),
+        Try(ref stmt) => {
+            list_breaks(stmt.block.body) ||
+            stmt.handler.is_some_and(|handler| list_breaks(handler.body.body)) ||
+            stmt.finalizer.is_some_and(|finalizer| list_breaks(finalizer.body))
+        },
+        Block(ref block) => list_breaks(block.body),
+        Labeled(ref stmt) => breaks(&stmt.body.item),
+        _ => false,
+    }
+}
+
+fn list_breaks(list: StatementList) -> bool {
+    list.iter().any(|statement| breaks(&statement.item))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use pass::{Pass, Visit};
+
+    fn assert_fold(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+
+        Visit(FoldSwitch).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn selected_case() {
+        assert_fold("switch (1) { case 0: a(); break; case 1: b(); break; default: c(); }", "{ b(); }");
+        assert_fold("switch ('foo') { case 'bar': a(); case 'foo': b(); }", "{ b(); }");
+    }
+
+    #[test]
+    fn fallthrough() {
+        assert_fold("switch (1) { case 1: a(); case 2: b(); break; case 3: c(); }", "{ a(); b(); }");
+        assert_fold("switch (2) { case 1: a(); default: b(); case 3: c(); break; }", "{ b(); c(); }");
+    }
+
+    #[test]
+    fn no_match() {
+        assert_fold("switch (2) { case 1: a(); }", "{}");
+        assert_fold("switch (true) { case !0: a(); }", "{ a(); }");
+    }
+
+    #[test]
+    fn undefined() {
+        assert_fold("switch (void 0 === undefined) { case true: a(); }", "{ a(); }");
+        assert_fold("switch (undefined) { case null: a(); break; case void 0: b(); }", "{ b(); }");
+    }
+
+    #[test]
+    fn not_folded() {
+        let sources = [
+            "switch (foo) { case 1: a(); }",
+            "switch (1) { case foo: a(); case 1: b(); }",
+            "switch (1) { case 1: if (a) { break; } b(); }",
+            "switch (1) { case 0: var a; case 1: a(); }",
+            "switch (1) { case 0: function a() {} case 1: a(); }",
+            "switch (1) { case 1: a(); break; let b = 1; }",
+            "switch (undefined) { case void 0: a(); } var undefined = 1;",
+            "function f(undefined) { switch (undefined) { case void 0: a(); } }",
+        ];
+
+        for source in sources.iter() {
+            assert_fold(source, source);
+        }
+    }
+
+    #[test]
+    fn unknown_tests_after_match() {
+        assert_fold("switch (1) { case 1: a(); break; case foo: b(); }", "{ a(); }");
+    }
+
+    #[test]
+    fn nested_breaks() {
+        assert_fold("switch (1) { case 1: for (;;) { break; } a(); }", "{ for (;;) { break; } a(); }");
+        assert_fold("foo: switch (1) { case 1: { break foo; } }", "foo: { { break foo; } }");
+    }
+}
@@ -0,0 +1,162 @@
+use ratel::Module;
+use ratel::ast::{Statement, Expression, Literal, Pattern};
+use ratel::ast::statement::ExportStatement;
+use ratel::error::Error;
+
+/// Inserts helper declarations at the top of a `Module`, after the
+/// directive prologue, with each helper inserted at most once.
+pub struct Injector<'m, 'ast: 'm> {
+    module: &'m mut Module<'ast>,
+
+    /// Index in the body at which the next helper goes, so that helpers
+    /// keep the order in which they were injected
+    index: usize,
+}
+
+impl<'m, 'ast> Injector<'m, 'ast> {
+    pub fn new(module: &'m mut Module<'ast>) -> Self {
+        let index = module.body().iter().take_while(|statement| is_directive(&statement.item)).count();
+
+        Injector {
+            module,
+            index,
+        }
+    }
+
+    /// Insert the `source` of a helper which declares `name`, unless the
+    /// module already declares `name` at its top level. Returns whether
+    /// the helper was inserted.
+    pub fn helper(&mut self, name: &str, source: &str) -> Result<bool, Vec<Error>> {
+        if declares(self.module, name) {
+            return Ok(false);
+        }
+
+        let len = self.module.body().iter().count();
+
+        self.module.insert(self.index, source)?;
+        self.index += self.module.body().iter().count() - len;
+
+        Ok(true)
+    }
+}
+
+#[inline]
+fn is_directive(statement: &Statement) -> bool {
+    match *statement {
+        Statement::Expression(expression) => matches!(expression.item, Expression::Literal(Literal::String(_))),
+        _ => false,
+    }
+}
+
+/// Check if `name` is declared by a top level statement of the module.
+fn declares(module: &Module, name: &str) -> bool {
+    module.body().iter().any(|statement| declared_by(&statement.item, name))
+}
+
+/// Whether the top-level `statement` declares `name`
+fn declared_by(statement: &Statement, name: &str) -> bool {
+    match *statement {
+        Statement::Function(ref function) => function.name.0.item == name,
+        Statement::Class(ref class) => class.name.0.item == name,
+        Statement::Declaration(ref declaration) => {
+            declaration.declarators.iter().any(|declarator| match declarator.id.item {
+                Pattern::Identifier(ident) => ident == name,
+                _ => false,
+            })
+        },
+        Statement::Import(ref import) => {
+            import.specifiers.iter().any(|specifier| specifier.local().item == name)
+        },
+        Statement::Export(ExportStatement::Declaration(declaration)) => declared_by(&declaration.item, name),
+        Statement::Export(ExportStatement::DefaultDeclaration(declaration)) => match declaration.item {
+            Expression::Function(ref function) => function.name.0.is_some_and(|ident| ident.item == name),
+            Expression::Class(ref class) => class.name.0.is_some_and(|ident| ident.item == name),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    fn assert_body(module: &Module, expected: &str) {
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn inject_after_directives() {
+        let mut module = parse("'use strict'; foo();").unwrap();
+
+        assert_eq!(Injector::new(&mut module).helper("_a", "function _a() {}"), Ok(true));
+        assert_body(&module, "'use strict'; function _a() {} foo();");
+    }
+
+    #[test]
+    fn inject_in_order() {
+        let mut module = parse("foo();").unwrap();
+
+        {
+            let mut injector = Injector::new(&mut module);
+
+            injector.helper("_a", "function _a() {}").unwrap();
+            injector.helper("_b", "var _b = 1, _c = 2;").unwrap();
+            injector.helper("_d", "function _d() {}").unwrap();
+        }
+
+        assert_body(&module, "function _a() {} var _b = 1, _c = 2; function _d() {} foo();");
+    }
+
+    #[test]
+    fn inject_once() {
+        let mut module = parse("foo(); var _b;").unwrap();
+
+        {
+            let mut injector = Injector::new(&mut module);
+
+            assert_eq!(injector.helper("_a", "function _a() {}"), Ok(true));
+            assert_eq!(injector.helper("_a", "function _a() {}"), Ok(false));
+            assert_eq!(injector.helper("_b", "var _b = 1;"), Ok(false));
+        }
+
+        assert_body(&module, "function _a() {} foo(); var _b;");
+    }
+
+    #[test]
+    fn inject_skips_imported_names() {
+        let mut module = parse("import _a, { b as _b } from 'c';").unwrap();
+
+        {
+            let mut injector = Injector::new(&mut module);
+
+            assert_eq!(injector.helper("_a", "function _a() {}"), Ok(false));
+            assert_eq!(injector.helper("_b", "function _b() {}"), Ok(false));
+        }
+
+        assert_body(&module, "import _a, { b as _b } from 'c';");
+    }
+
+    #[test]
+    fn inject_skips_exported_declarations() {
+        let mut module = parse("export function _a() {} export default class _b {}").unwrap();
+
+        {
+            let mut injector = Injector::new(&mut module);
+
+            assert_eq!(injector.helper("_a", "function _a() {}"), Ok(false));
+            assert_eq!(injector.helper("_b", "function _b() {}"), Ok(false));
+        }
+
+        assert_body(&module, "export function _a() {} export default class _b {}");
+    }
+
+    #[test]
+    fn inject_invalid_source() {
+        let mut module = parse("foo();").unwrap();
+
+        assert!(Injector::new(&mut module).helper("_a", "function _a(").is_err());
+        assert_body(&module, "foo();");
+    }
+}
@@ -2,31 +2,191 @@
 #[macro_use]
 extern crate pretty_assertions;
 extern crate ratel;
+extern crate ratel_codegen;
 extern crate ratel_visitor;
 extern crate toolshed;
 
-use toolshed::Arena;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use toolshed::{Arena, CopyCell};
 use toolshed::list::ListBuilder;
-use ratel::ast::{Loc, Node, NodeList};
+use ratel::ast::{Loc, Node, NodeList, StringLiteral, decode_string};
+use ratel::diagnostic::Diagnostic;
+use ratel::quota::Budget;
+use ratel_codegen::{ToCode, SizeCache, CachedSizeGenerator};
 
 pub mod es2015;
 // pub mod es2016;
 
 pub mod scope;
+pub mod assign;
+pub mod constant;
+pub mod directive;
+pub mod effects;
+pub mod env;
+pub mod fold;
+pub mod inject;
+pub mod members;
+pub mod minify;
+pub mod names;
+pub mod pass;
+pub mod undefined;
+pub mod wrap;
+
+#[cfg(feature = "codemods")]
+pub mod arrow;
+#[cfg(feature = "codemods")]
+pub mod types;
+#[cfg(feature = "codemods")]
+pub mod equality;
+#[cfg(feature = "codemods")]
+pub mod object;
+#[cfg(feature = "codemods")]
+pub mod optional;
+#[cfg(feature = "codemods")]
+pub mod template;
+#[cfg(feature = "codemods")]
+pub mod var;
 
 use self::scope::Scope;
+use self::names::NameGenerator;
 
 pub struct Transformer<'ast> {
     pub arena: &'ast Arena,
     pub scope: &'ast Scope<'ast>,
+
+    /// Names for variables introduced by the transformations
+    pub names: NameGenerator<'ast>,
+
+    /// Whether any node was swapped
+    changed: CopyCell<bool>,
+
+    /// `start`, `end` of code left unchanged by `swap`, along with the
+    /// nodes around it
+    disabled: Vec<(u32, u32)>,
+
+    /// `start`, `end` of nodes left unchanged by `swap`, see `keep`
+    kept: Vec<(u32, u32)>,
+
+    /// Budget charged for the nodes allocated, see `with_budget`
+    budget: Option<&'ast Budget>,
+
+    /// Whether transforms can declare helper functions, see `without_helpers`
+    helpers: bool,
+
+    /// Problems met by the transforms, see `report`
+    diagnostics: Vec<Diagnostic>,
+
+    /// Sizes measured by `minified_size` since the last `swap`
+    sizes: RefCell<SizeCache<'static>>,
 }
 
 impl<'ast> Transformer<'ast> {
+    #[inline]
+    pub fn new(arena: &'ast Arena, scope: &'ast Scope<'ast>) -> Self {
+        Transformer {
+            arena,
+            scope,
+            names: NameGenerator::new(arena, scope),
+            changed: CopyCell::new(false),
+            disabled: Vec::new(),
+            kept: Vec::new(),
+            budget: None,
+            helpers: true,
+            diagnostics: Vec::new(),
+            sizes: RefCell::new(SizeCache::default()),
+        }
+    }
+
+    /// Charge the nodes allocated to the `budget`, usually the one of the
+    /// module being transformed. Once it's spent `swap` leaves the AST
+    /// unchanged, the pass finishes without further changes.
+    #[inline]
+    pub fn with_budget(mut self, budget: &'ast Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Have transforms needing a helper function, such as the lowering of
+    /// `super` properties in classes, leave the code unchanged and report
+    /// an error instead.
+    #[inline]
+    pub fn without_helpers(mut self) -> Self {
+        self.helpers = false;
+        self
+    }
+
+    /// Check whether transforms can declare helper functions.
+    #[inline]
+    pub fn helpers(&self) -> bool {
+        self.helpers
+    }
+
+    /// Report a problem with the code, such as code that can't be lowered.
+    #[inline]
+    pub fn report(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Get the problems reported so far, in the order they were reported.
+    #[inline]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Check whether the budget is spent.
+    #[inline]
+    pub fn exhausted(&self) -> bool {
+        self.budget.is_some_and(|budget| budget.check().is_err())
+    }
+
+    #[inline]
+    fn charge<T>(&self) {
+        if let Some(budget) = self.budget {
+            // Going over is seen by `exhausted`
+            let _ = budget.charge_node::<T>();
+        }
+    }
+
+    /// Length in bytes of the minified code of `item`, same as
+    /// `ratel_codegen::minified_size`. The sizes of the subtrees are kept
+    /// until the next `swap`, so that passes comparing many candidate
+    /// rewrites of the same code don't measure it again for each of them.
+    #[inline]
+    pub fn minified_size<T>(&self, item: &T) -> usize where
+        T: for<'c> ToCode<CachedSizeGenerator<'c, 'static>>,
+    {
+        self.sizes.borrow_mut().size(item)
+    }
+
+    /// Leave nodes within `start` and `end` unchanged by `swap`, as well
+    /// as the nodes around them, so that no replacement can drop or move
+    /// the disabled code.
+    #[inline]
+    pub fn disable(&mut self, start: u32, end: u32) {
+        self.disabled.push((start, end));
+    }
+
+    /// Leave the node, or any node with the same span, unchanged by `swap`.
+    /// Unlike with `disable` the nodes within it can still change.
+    #[inline]
+    pub fn keep<T>(&mut self, ptr: &Node<'ast, T>) {
+        self.kept.push((ptr.start, ptr.end));
+    }
+
+    /// Check whether the AST was changed through `swap`.
+    #[inline]
+    pub fn changed(&self) -> bool {
+        self.changed.get()
+    }
+
     #[inline]
     pub fn alloc<T, I>(&self, item: I) -> Node<'ast, T> where
         T: Copy,
         I: Into<T>,
     {
+        self.charge::<T>();
+
         Node::new(self.arena.alloc(Loc::new(0, 0, item.into())))
     }
 
@@ -35,9 +195,23 @@ impl<'ast> Transformer<'ast> {
         T: Copy + 'ast,
         I: Into<T>,
     {
+        self.charge::<T>();
+
         Node::new(self.arena.alloc(Loc::new(loc.start, loc.end, item.into())))
     }
 
+    /// String literal of the `raw` source, quotes included, with its
+    /// value decoded into the arena if it has escapes.
+    #[inline]
+    pub fn string_literal(&self, raw: &'ast str) -> StringLiteral<'ast> {
+        let value = match decode_string(raw) {
+            Cow::Borrowed(value) => value,
+            Cow::Owned(value) => self.arena.alloc_string(value),
+        };
+
+        StringLiteral { value, raw }
+    }
+
     #[inline]
     pub fn list<T, I>(&mut self, source: I) -> NodeList<'ast, T> where
         T: 'ast + Copy,
@@ -57,11 +231,25 @@ impl<'ast> Transformer<'ast> {
         builder.as_list()
     }
 
+    /// Check whether the node is left unchanged by `swap`, as it's kept or
+    /// overlaps disabled code.
+    #[inline]
+    pub fn is_disabled<T>(&self, ptr: &Node<'ast, T>) -> bool {
+        self.kept.contains(&(ptr.start, ptr.end)) ||
+        self.disabled.iter().any(|&(start, end)| ptr.start < end && ptr.end > start)
+    }
+
     #[inline]
     pub fn swap<T, I>(&self, ptr: &Node<'ast, T>, item: I) where
         T: Copy + 'ast,
         I: Into<T>,
     {
+        if self.is_disabled(ptr) || self.exhausted() {
+            return;
+        }
+
+        self.charge::<T>();
+
         let new = self.arena.alloc(Loc {
             start: ptr.start,
             end: ptr.end,
@@ -69,6 +257,10 @@ impl<'ast> Transformer<'ast> {
         });
 
         ptr.set(new);
+        self.changed.set(true);
+
+        // Parents of the node measured before have a different size now
+        self.sizes.borrow_mut().clear();
     }
 }
 
@@ -0,0 +1,372 @@
+use std::borrow::Cow;
+
+use ratel::ast::{Expression, ExpressionNode, Node, Block, BlockNode, NumberLiteral, decode_string};
+use ratel::ast::{ClassMember, MethodKind, Property, PropertyKey};
+use ratel::ast::expression::ObjectExpression;
+
+use effects::can_reorder;
+use Transformer;
+
+/// Why members were left in their order by `reorder_class_members` or
+/// `reorder_properties`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReorderError {
+    /// The order isn't a permutation of the indices of the members
+    InvalidOrder,
+
+    /// Two members would swap which evaluations, such as those of computed
+    /// keys or of property values, can't be reordered, see
+    /// `effects::can_reorder`, or one of them is a spread property
+    Evaluation {
+        start: u32,
+        end: u32,
+    },
+
+    /// Two members with the same key would swap, changing which one is
+    /// defined last and so takes effect
+    DuplicateKey {
+        start: u32,
+        end: u32,
+    },
+}
+
+/// Reorder the members of the class `body`, the member at `order[i]`
+/// becoming the `i`-th one, such as to hoist the constructor first or to
+/// group the static members. The members are moved as they are, keeping
+/// their spans, and so the comments within them. Nothing changes on an
+/// error, `start` and `end` of which are those of the member which would
+/// have been moved before another one.
+pub fn reorder_class_members<'ast>(body: &'ast BlockNode<'ast, ClassMember<'ast>>, order: &[usize], t: &mut Transformer<'ast>) -> Result<(), ReorderError> {
+    let members: Vec<Node<'ast, ClassMember<'ast>>> = body.body.iter().copied().collect();
+    let reordered = reorder(&members, order, member_conflict)?;
+
+    if let Some(reordered) = reordered {
+        let list = t.list(reordered);
+
+        t.swap(body, Block { body: list });
+    }
+
+    Ok(())
+}
+
+/// Same as `reorder_class_members`, ordering the members by the `key`,
+/// members with equal keys keeping their order.
+pub fn sort_class_members<'ast, K, F>(body: &'ast BlockNode<'ast, ClassMember<'ast>>, key: F, t: &mut Transformer<'ast>) -> Result<(), ReorderError> where
+    K: Ord,
+    F: FnMut(&ClassMember<'ast>) -> K,
+{
+    let order = sorted_order(body.body.iter().map(|member| member.item), key);
+
+    reorder_class_members(body, &order, t)
+}
+
+/// Same as `reorder_class_members` for the properties of the object
+/// literal `ptr`. Property values are evaluated in order, so they can't
+/// swap either unless independent.
+pub fn reorder_properties<'ast>(ptr: &'ast ExpressionNode<'ast>, order: &[usize], t: &mut Transformer<'ast>) -> Result<(), ReorderError> {
+    let object = match ptr.item {
+        Expression::Object(object) => object,
+        _ => return Err(ReorderError::InvalidOrder),
+    };
+
+    let properties: Vec<Node<'ast, Property<'ast>>> = object.body.iter().copied().collect();
+    let reordered = reorder(&properties, order, property_conflict)?;
+
+    if let Some(reordered) = reordered {
+        let body = t.list(reordered);
+
+        t.swap(ptr, ObjectExpression { body });
+    }
+
+    Ok(())
+}
+
+/// Same as `reorder_properties`, ordering the properties by the `key`,
+/// properties with equal keys keeping their order.
+pub fn sort_properties<'ast, K, F>(ptr: &'ast ExpressionNode<'ast>, key: F, t: &mut Transformer<'ast>) -> Result<(), ReorderError> where
+    K: Ord,
+    F: FnMut(&Property<'ast>) -> K,
+{
+    let order = match ptr.item {
+        Expression::Object(object) => sorted_order(object.body.iter().map(|property| property.item), key),
+        _ => return Err(ReorderError::InvalidOrder),
+    };
+
+    reorder_properties(ptr, &order, t)
+}
+
+#[inline]
+fn sorted_order<T, K, F>(items: impl Iterator<Item = T>, mut key: F) -> Vec<usize> where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    let mut keyed: Vec<(K, usize)> = items.enumerate().map(|(index, item)| (key(&item), index)).collect();
+
+    keyed.sort();
+    keyed.into_iter().map(|(_, index)| index).collect()
+}
+
+/// Check the `order` against every pair of `items` it swaps, giving the
+/// reordered items, `None` if the order is the same.
+fn reorder<'ast, T, F>(items: &[Node<'ast, T>], order: &[usize], conflict: F) -> Result<Option<Vec<Node<'ast, T>>>, ReorderError> where
+    T: Copy,
+    F: Fn(&T, &T) -> Option<Conflict>,
+{
+    let mut seen = vec![false; items.len()];
+
+    if order.len() != items.len() || order.iter().any(|&index| index >= items.len() || std::mem::replace(&mut seen[index], true)) {
+        return Err(ReorderError::InvalidOrder);
+    }
+
+    if order.iter().enumerate().all(|(position, &index)| position == index) {
+        return Ok(None);
+    }
+
+    for (position, &later) in order.iter().enumerate() {
+        // Items originally before `later` but now after it
+        for &earlier in order[position + 1..].iter().filter(|&&earlier| earlier < later) {
+            let moved = items[later];
+
+            match conflict(&items[earlier].item, &moved.item) {
+                Some(Conflict::Evaluation) => return Err(ReorderError::Evaluation { start: moved.start, end: moved.end }),
+                Some(Conflict::Key) => return Err(ReorderError::DuplicateKey { start: moved.start, end: moved.end }),
+                None => {},
+            }
+        }
+    }
+
+    Ok(Some(order.iter().map(|&index| items[index]).collect()))
+}
+
+enum Conflict {
+    Evaluation,
+    Key,
+}
+
+fn member_conflict(first: &ClassMember, second: &ClassMember) -> Option<Conflict> {
+    // Static blocks can run any code, only members evaluating nothing can
+    // move past them
+    if matches!(*first, ClassMember::StaticBlock { .. }) || matches!(*second, ClassMember::StaticBlock { .. }) {
+        return match evaluates(first) && evaluates(second) {
+            true => Some(Conflict::Evaluation),
+            false => None,
+        };
+    }
+
+    let (first_static, first_key, first_kind) = member_parts(first)?;
+    let (second_static, second_key, second_kind) = member_parts(second)?;
+
+    let first_evaluated = evaluated(first_key, member_value(first));
+    let second_evaluated = evaluated(second_key, member_value(second));
+
+    if !independent(&first_evaluated, &second_evaluated) {
+        return Some(Conflict::Evaluation);
+    }
+
+    // A getter and a setter of the same name define a single property
+    let accessors = matches!((first_kind, second_kind), (Some(MethodKind::Get), Some(MethodKind::Set)) | (Some(MethodKind::Set), Some(MethodKind::Get)));
+
+    if first_static == second_static && !accessors && same_key(&first_key.item, &second_key.item) {
+        return Some(Conflict::Key);
+    }
+
+    None
+}
+
+#[inline]
+fn member_parts<'ast>(member: &ClassMember<'ast>) -> Option<(bool, Node<'ast, PropertyKey<'ast>>, Option<MethodKind>)> {
+    match *member {
+        ClassMember::Method { is_static, key, kind, .. } => Some((is_static, key, Some(kind))),
+        ClassMember::Property { is_static, key, .. } => Some((is_static, key, None)),
+        ClassMember::StaticBlock { .. } | ClassMember::Error => None,
+    }
+}
+
+#[inline]
+fn evaluates(member: &ClassMember) -> bool {
+    match member_parts(member) {
+        Some((_, key, _)) => !evaluated(key, member_value(member)).is_empty(),
+        None => matches!(*member, ClassMember::StaticBlock { .. }),
+    }
+}
+
+#[inline]
+fn member_value<'ast>(member: &ClassMember<'ast>) -> Option<ExpressionNode<'ast>> {
+    match *member {
+        ClassMember::Property { value, .. } => value,
+        _ => None,
+    }
+}
+
+fn property_conflict(first: &Property, second: &Property) -> Option<Conflict> {
+    let (first_key, first_value) = match property_parts(first) {
+        Some(parts) => parts,
+        None => return Some(Conflict::Evaluation),
+    };
+    let (second_key, second_value) = match property_parts(second) {
+        Some(parts) => parts,
+        None => return Some(Conflict::Evaluation),
+    };
+
+    if !independent(&evaluated(first_key, first_value), &evaluated(second_key, second_value)) {
+        return Some(Conflict::Evaluation);
+    }
+
+    if same_key(&first_key.item, &second_key.item) {
+        return Some(Conflict::Key);
+    }
+
+    None
+}
+
+/// Key of the property and its value if it's evaluated, `None` for spread
+/// properties, which can't move past any other
+#[inline]
+fn property_parts<'ast>(property: &Property<'ast>) -> Option<(Node<'ast, PropertyKey<'ast>>, Option<ExpressionNode<'ast>>)> {
+    match *property {
+        Property::Literal { key, value } => Some((key, Some(value))),
+        Property::Method { key, .. } => Some((key, None)),
+        Property::Shorthand(_) | Property::ShorthandDefault { .. } | Property::Spread { .. } => None,
+    }
+}
+
+/// Expressions evaluated when the member is defined, in order
+#[inline]
+fn evaluated<'ast>(key: Node<'ast, PropertyKey<'ast>>, value: Option<ExpressionNode<'ast>>) -> Vec<Expression<'ast>> {
+    let key = match key.item {
+        PropertyKey::Computed(expression) => Some(expression.item),
+        _ => None,
+    };
+
+    key.into_iter().chain(value.map(|value| value.item)).collect()
+}
+
+#[inline]
+fn independent(first: &[Expression], second: &[Expression]) -> bool {
+    first.iter().all(|first| second.iter().all(|second| can_reorder(first, second)))
+}
+
+/// Check whether the keys could be the same, computed keys being known
+/// only when the code runs.
+fn same_key(first: &PropertyKey, second: &PropertyKey) -> bool {
+    match (key_name(first), key_name(second)) {
+        (Some(first), Some(second)) => first == second,
+        _ => true,
+    }
+}
+
+/// Name of the property a key defines, quotes decoded and numbers in
+/// their canonical form, so that `a`, `'a'` and `"a"`, or `1`, `1.0` and
+/// `'1'` are the same
+fn key_name<'ast>(key: &PropertyKey<'ast>) -> Option<Cow<'ast, str>> {
+    match *key {
+        PropertyKey::Computed(_) => None,
+        PropertyKey::Literal(raw) if raw.starts_with(['"', '\'']) => Some(decode_string(raw)),
+        PropertyKey::Literal(raw) if raw.starts_with(|byte: char| byte.is_ascii_digit() || byte == '.') => Some(number_name(raw)),
+        PropertyKey::Literal(raw) => Some(Cow::Borrowed(raw)),
+        PropertyKey::Binary(raw) => Some(number_name(raw)),
+        PropertyKey::Private(name) => Some(Cow::Owned(format!("#{}", name))),
+    }
+}
+
+#[inline]
+fn number_name<'ast>(raw: &str) -> Cow<'ast, str> {
+    Cow::Owned(NumberLiteral::new(raw).value.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::ast::Statement;
+    use ratel::{parse, Module};
+    use ratel_codegen::codegen;
+    use scope;
+
+    fn class_body<'ast>(module: &'ast Module<'ast>) -> &'ast BlockNode<'ast, ClassMember<'ast>> {
+        match module.body().first_element().map(|statement| &statement.get().item) {
+            Some(Statement::Class(class)) => &class.body,
+            _ => panic!("Expected a class"),
+        }
+    }
+
+    fn reorder_class(source: &str, order: &[usize]) -> Result<String, ReorderError> {
+        let module = parse(source).unwrap();
+        let mut t = Transformer::new(module.arena(), scope::analyze(&module));
+
+        reorder_class_members(class_body(&module), order, &mut t)?;
+
+        Ok(codegen(&module, true))
+    }
+
+    fn reorder_object(source: &str, order: &[usize]) -> Result<String, ReorderError> {
+        let module = parse(source).unwrap();
+        let mut t = Transformer::new(module.arena(), scope::analyze(&module));
+
+        let object = match module.body().first_element().map(|statement| &statement.get().item) {
+            Some(Statement::Expression(expression)) => expression,
+            _ => panic!("Expected an expression"),
+        };
+
+        reorder_properties(object, order, &mut t)?;
+
+        Ok(codegen(&module, true))
+    }
+
+    #[test]
+    fn class_members() {
+        assert_eq!(reorder_class("class A { a() {} constructor() {} }", &[1, 0]), Ok("class A{constructor(){}a(){}}".to_owned()));
+        assert_eq!(reorder_class("class A { a() {} static b() {} c() {} }", &[1, 0, 2]), Ok("class A{static b(){}a(){}c(){}}".to_owned()));
+        assert_eq!(reorder_class("class A { a() {} static a() {} }", &[1, 0]), Ok("class A{static a(){}a(){}}".to_owned()));
+        assert_eq!(reorder_class("class A { get a() {} b() {} set a(v) {} }", &[0, 2, 1]), Ok("class A{get a(){}set a(v){}b(){}}".to_owned()));
+        assert_eq!(reorder_class("class A { a() {} b() {} }", &[0, 1]), Ok("class A{a(){}b(){}}".to_owned()));
+        assert_eq!(reorder_class("class A { static { f(); } a() {} }", &[1, 0]), Ok("class A{a(){}static{f();}}".to_owned()));
+    }
+
+    #[test]
+    fn class_members_not_reordered() {
+        assert_eq!(reorder_class("class A { a() {} b() {} }", &[0, 0]), Err(ReorderError::InvalidOrder));
+        assert_eq!(reorder_class("class A { a() {} b() {} }", &[1]), Err(ReorderError::InvalidOrder));
+        assert_eq!(reorder_class("class A { a() {} b() {} }", &[2, 0]), Err(ReorderError::InvalidOrder));
+        assert_eq!(reorder_class("class A { [f()]() {} [g()]() {} }", &[1, 0]), Err(ReorderError::Evaluation { start: 21, end: 31 }));
+        assert_eq!(reorder_class("class A { [f()]() {} b() {} }", &[1, 0]), Err(ReorderError::DuplicateKey { start: 21, end: 27 }));
+        assert_eq!(reorder_class("class A { [a]() {} [b]() {} }", &[1, 0]), Err(ReorderError::DuplicateKey { start: 19, end: 27 }));
+        assert_eq!(reorder_class("class A { a() {} a() {} }", &[1, 0]), Err(ReorderError::DuplicateKey { start: 17, end: 23 }));
+        assert_eq!(reorder_class("class A { get a() {} get a() {} }", &[1, 0]), Err(ReorderError::DuplicateKey { start: 21, end: 31 }));
+        assert_eq!(reorder_class("class A { static a = 1; static { f(); } }", &[1, 0]), Err(ReorderError::Evaluation { start: 24, end: 39 }));
+        assert_eq!(reorder_class("class A { static { f(); } static { g(); } }", &[1, 0]), Err(ReorderError::Evaluation { start: 26, end: 41 }));
+    }
+
+    #[test]
+    fn sort_members() {
+        let module = parse("class A { static a() {} b() {} constructor() {} static c() {} }").unwrap();
+        let mut t = Transformer::new(module.arena(), scope::analyze(&module));
+        let key = |member: &ClassMember| match *member {
+            ClassMember::Method { kind: MethodKind::Constructor, .. } => 0,
+            ClassMember::Method { is_static: true, .. } => 1,
+            _ => 2,
+        };
+
+        sort_class_members(class_body(&module), key, &mut t).unwrap();
+
+        assert_eq!(codegen(&module, true), "class A{constructor(){}static a(){}static c(){}b(){}}");
+        assert!(t.changed());
+    }
+
+    #[test]
+    fn properties() {
+        assert_eq!(reorder_object("({ a: 1, b: c });", &[1, 0]), Ok("({b:c,a:1});".to_owned()));
+        assert_eq!(reorder_object("({ a() {}, b: c, d: e.f });", &[2, 1, 0]), Ok("({d:e.f,b:c,a(){}});".to_owned()));
+        assert_eq!(reorder_object("({ a: f(), b: 1 });", &[1, 0]), Ok("({b:1,a:f()});".to_owned()));
+    }
+
+    #[test]
+    fn properties_not_reordered() {
+        assert_eq!(reorder_object("({ a: f(), b: g() });", &[1, 0]), Err(ReorderError::Evaluation { start: 11, end: 17 }));
+        assert_eq!(reorder_object("({ a: b = 1, c: b });", &[1, 0]), Err(ReorderError::Evaluation { start: 13, end: 17 }));
+        assert_eq!(reorder_object("({ ...a, b: 1 });", &[1, 0]), Err(ReorderError::Evaluation { start: 9, end: 13 }));
+        assert_eq!(reorder_object("({ a, b: 1 });", &[1, 0]), Err(ReorderError::Evaluation { start: 6, end: 10 }));
+        assert_eq!(reorder_object("({ 1: a, '1': b });", &[1, 0]), Err(ReorderError::DuplicateKey { start: 9, end: 15 }));
+        assert_eq!(reorder_object("({ 0x1: a, 1.0: b });", &[1, 0]), Err(ReorderError::DuplicateKey { start: 11, end: 17 }));
+        assert_eq!(reorder_object("({ [a]: 1, b: 2 });", &[1, 0]), Err(ReorderError::DuplicateKey { start: 11, end: 15 }));
+    }
+}
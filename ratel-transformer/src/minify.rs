@@ -0,0 +1,402 @@
+use ratel::ast::{Expression, ExpressionNode, Statement, StatementNode, Block, BlockNode};
+use ratel::ast::{Function, Name, Property, ClassMember};
+use ratel::ast::expression::{ArrowExpression, ArrowBody, ConditionalExpression, FunctionExpression, ClassExpression, ObjectExpression};
+use ratel::ast::statement::{ReturnStatement, IfStatement, FunctionStatement, ClassStatement};
+use ratel::{parse, Module};
+use ratel::error::Error;
+use ratel_codegen::{codegen_with_options, CodegenOptions};
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+use std::thread;
+
+use directive::directives;
+use pass::{Pass, Visit};
+use Transformer;
+
+/// Minifier pass shortening the returns at the end of function bodies.
+/// A body ending with `if (x) { return a; } return b;` ends with
+/// `return x ? a : b;` instead, and arrow functions with a body made of a
+/// single `return a;` get the expression body `a`.
+///
+/// Rewrites are only done where the minified code, as measured by
+/// `Transformer::minified_size`, gets smaller.
+pub struct SimplifyReturns;
+
+impl<'ast> StaticVisitor<'ast> for SimplifyReturns {
+    type Context = Transformer<'ast>;
+
+    fn on_function_statement(_: &FunctionStatement, ptr: &'ast StatementNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Statement::Function(ref function) = ptr.item {
+            simplify_function(function, t);
+        }
+    }
+
+    fn on_class_statement(_: &ClassStatement, ptr: &'ast StatementNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Statement::Class(ref class) = ptr.item {
+            simplify_methods(&class.body, t);
+        }
+    }
+
+    fn on_function_expression(_: &FunctionExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Expression::Function(ref function) = ptr.item {
+            simplify_function(function, t);
+        }
+    }
+
+    fn on_class_expression(_: &ClassExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Expression::Class(ref class) = ptr.item {
+            simplify_methods(&class.body, t);
+        }
+    }
+
+    fn on_object_expression(_: &ObjectExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let object = match ptr.item {
+            Expression::Object(ref object) => object,
+            _ => return,
+        };
+
+        for property in &object.body {
+            if let Property::Method { ref value, .. } = property.item {
+                simplify_function(value, t);
+            }
+        }
+    }
+
+    fn on_arrow_expression(_: &ArrowExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let arrow = match ptr.item {
+            Expression::Arrow(ref arrow) => arrow,
+            _ => return,
+        };
+
+        let block = match arrow.body {
+            ArrowBody::Block(ref block) => block,
+            ArrowBody::Expression(_) => return,
+        };
+
+        simplify_body(block, t);
+
+        // Read again, as the body may have been simplified to a single return
+        let value = match block.body.only_element().map(|statement| statement.item) {
+            Some(Statement::Return(ReturnStatement { value: Some(value) })) => value,
+            _ => return,
+        };
+
+        if t.is_disabled(block) {
+            return;
+        }
+
+        let expression = ArrowExpression {
+            is_async: arrow.is_async,
+            params: arrow.params,
+            body: ArrowBody::Expression(value),
+        };
+
+        if t.minified_size(&expression) < t.minified_size(arrow) {
+            t.swap(ptr, expression);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_function_statement.push(SimplifyReturns::on_function_statement);
+        dv.on_class_statement.push(SimplifyReturns::on_class_statement);
+        dv.on_function_expression.push(SimplifyReturns::on_function_expression);
+        dv.on_class_expression.push(SimplifyReturns::on_class_expression);
+        dv.on_object_expression.push(SimplifyReturns::on_object_expression);
+        dv.on_arrow_expression.push(SimplifyReturns::on_arrow_expression);
+    }
+}
+
+#[inline]
+fn simplify_function<'ast, N: Name<'ast>>(function: &'ast Function<'ast, N>, t: &mut Transformer<'ast>) {
+    simplify_body(&function.body, t);
+}
+
+fn simplify_methods<'ast>(body: &'ast BlockNode<'ast, ClassMember<'ast>>, t: &mut Transformer<'ast>) {
+    for member in &body.body {
+        if let ClassMember::Method { ref value, .. } = member.item {
+            simplify_function(value, t);
+        }
+    }
+}
+
+/// Merge the trailing `if` statements returning a value with the `return`
+/// following them, for as long as that makes the code smaller.
+fn simplify_body<'ast>(body: &'ast BlockNode<'ast, Statement<'ast>>, t: &mut Transformer<'ast>) {
+    let mut statements: Vec<StatementNode<'ast>> = body.body.iter().copied().collect();
+    let mut merged = false;
+
+    while statements.len() >= 2 {
+        let last = statements[statements.len() - 1];
+        let guard = statements[statements.len() - 2];
+
+        if t.is_disabled(&last) || t.is_disabled(&guard) {
+            break;
+        }
+
+        let (test, consequent) = match guard.item {
+            Statement::If(IfStatement { test, consequent, alternate: None }) => match returned(consequent) {
+                Some(value) => (test, value),
+                None => break,
+            },
+            _ => break,
+        };
+
+        let alternate = match last.item {
+            Statement::Return(ReturnStatement { value: Some(value) }) => value,
+            _ => break,
+        };
+
+        let conditional = t.alloc_as_loc(test, ConditionalExpression {
+            test,
+            consequent,
+            alternate,
+        });
+
+        let merged_return: StatementNode<'ast> = t.alloc_as_loc(guard, ReturnStatement {
+            value: Some(conditional),
+        });
+
+        if t.minified_size(&merged_return) >= t.minified_size(&guard) + t.minified_size(&last) {
+            break;
+        }
+
+        statements.pop();
+        statements.pop();
+        statements.push(merged_return);
+        merged = true;
+    }
+
+    if merged {
+        let body_list = t.list(statements);
+
+        t.swap(body, Block { body: body_list });
+    }
+}
+
+/// Value of a `return a;` statement, on its own or as the only statement
+/// of a block.
+fn returned<'ast>(statement: StatementNode<'ast>) -> Option<ExpressionNode<'ast>> {
+    match statement.item {
+        Statement::Return(ReturnStatement { value }) => value,
+        Statement::Block(ref block) => match block.body.only_element()?.item {
+            Statement::Return(ReturnStatement { value }) => value,
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Name of `SimplifyReturns` in pipelines, checked by the directives.
+const SIMPLIFY_RETURNS: &str = "simplify-returns";
+
+/// Parse `source`, simplify its returns and generate the minified code,
+/// splitting the work between up to `threads` threads.
+///
+/// The top-level statements are split into runs of about the same length,
+/// each parsed, simplified and generated on its own thread, then joined
+/// in order. Function bodies don't share anything `SimplifyReturns` looks
+/// at, so the code is the same as with a single thread, which pays off on
+/// bundles made of many top-level functions. The directives of the whole
+/// module apply, and only the first run gets the `banner` of `options`.
+pub fn minify_parallel(source: &str, options: CodegenOptions, threads: usize) -> Result<String, Vec<Error>> {
+    let module = parse(source)?;
+
+    let disabled: Vec<(u32, u32)> = directives(&module)
+        .iter()
+        .filter(|directive| directive.disables(SIMPLIFY_RETURNS))
+        .map(|directive| (directive.start, directive.end))
+        .collect();
+
+    let options = CodegenOptions { minify: true, ..options };
+    let runs = split_runs(&module, source.len(), threads.max(1));
+
+    if runs.len() < 2 {
+        return Ok(minify_module(&module, options, &disabled));
+    }
+
+    let results: Vec<Result<String, Vec<Error>>> = thread::scope(|scope| {
+        let workers: Vec<_> = runs.iter().enumerate().map(|(index, &(start, end))| {
+            let disabled = &disabled;
+            let options = CodegenOptions {
+                banner: if index == 0 { options.banner } else { None },
+                ..options
+            };
+
+            scope.spawn(move || minify_run(&source[start..end], start, options, disabled))
+        }).collect();
+
+        workers.into_iter().map(|worker| worker.join().expect("Minifying thread panicked")).collect()
+    });
+
+    let mut code = String::with_capacity(source.len());
+
+    for result in results {
+        code.push_str(&result?);
+    }
+
+    Ok(code)
+}
+
+/// Split the top-level statements into at most `threads` runs of about
+/// the same length, as byte ranges of the source. Runs end where their
+/// last statement ends, so that the comments before a statement are in
+/// the same run, and together they cover the whole source.
+fn split_runs(module: &Module, len: usize, threads: usize) -> Vec<(usize, usize)> {
+    let target = len / threads + 1;
+    let mut runs = Vec::with_capacity(threads);
+    let mut start = 0;
+
+    for statement in module.body() {
+        let end = statement.end as usize;
+
+        if end - start >= target && runs.len() + 1 < threads {
+            runs.push((start, end));
+            start = end;
+        }
+    }
+
+    runs.push((start, len));
+    runs
+}
+
+/// Minify the run of the source starting at `offset`, with the `disabled`
+/// ranges of the whole module.
+fn minify_run(source: &str, offset: usize, options: CodegenOptions, disabled: &[(u32, u32)]) -> Result<String, Vec<Error>> {
+    let module = parse(source).map_err(|errors| {
+        errors.into_iter().map(|error| Error::new(error.kind, error.token, error.raw, error.start + offset, error.end + offset)).collect::<Vec<_>>()
+    })?;
+
+    let (offset, len) = (offset as u32, source.len() as u32);
+    let disabled: Vec<(u32, u32)> = disabled
+        .iter()
+        .filter(|&&(start, end)| start < offset + len && end > offset)
+        .map(|&(start, end)| (start.saturating_sub(offset), end.saturating_sub(offset).min(len)))
+        .collect();
+
+    Ok(minify_module(&module, options, &disabled))
+}
+
+fn minify_module<'ast>(module: &'ast Module<'ast>, options: CodegenOptions, disabled: &[(u32, u32)]) -> String {
+    Visit(SimplifyReturns).run_except(module, disabled);
+
+    codegen_with_options(module, options)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_simplify(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+
+        Visit(SimplifyReturns).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn trailing_returns() {
+        assert_simplify("function f() { if (x) { return a; } return b; }", "function f() { return x ? a : b; }");
+        assert_simplify("function f() { g(); if (x) return a; return b; }", "function f() { g(); return x ? a : b; }");
+        assert_simplify(
+            "function f() { if (x) return a; if (y) return b; return c; }",
+            "function f() { return x ? a : y ? b : c; }"
+        );
+        assert_simplify("({ f() { if (x) return a; return b; } });", "({ f() { return x ? a : b; } });");
+        assert_simplify("class A { f() { if (x) return a; return b; } }", "class A { f() { return x ? a : b; } }");
+        assert_simplify("(function () { if (a = b) return a; return c; });", "(function () { return (a = b) ? a : c; });");
+    }
+
+    #[test]
+    fn minified_size_after_swap() {
+        use ratel::ast::expression::BinaryExpression;
+        use scope;
+
+        let module = parse("(a, bc) + d;").unwrap();
+        let t = Transformer::new(module.arena(), scope::analyze(&module));
+
+        let expression = match module.body().first_element().map(|statement| statement.item) {
+            Some(Statement::Expression(expression)) => expression,
+            _ => panic!("Expected an expression"),
+        };
+
+        let left = match expression.get().item {
+            Expression::Binary(BinaryExpression { ref left, .. }) => left,
+            _ => panic!("Expected a binary expression"),
+        };
+
+        assert_eq!(t.minified_size(&expression), 8);
+        assert_eq!(t.minified_size(&expression), 8);
+
+        t.swap(left, Expression::Identifier("e"));
+
+        assert_eq!(t.minified_size(&expression), 3);
+    }
+
+    #[test]
+    fn implicit_returns() {
+        assert_simplify("f(() => { return a; });", "f(() => a);");
+        assert_simplify("f(() => { return {}; });", "f(() => ({}));");
+        assert_simplify("f(() => { if (x) return a; return b; });", "f(() => x ? a : b);");
+        assert_simplify("f(() => { if (x) return () => { return a; }; return b; });", "f(() => x ? () => a : b);");
+    }
+
+    #[test]
+    fn not_simplified() {
+        let sources = [
+            "function f() { if (x) return a; }",
+            "function f() { if (x) return; return b; }",
+            "function f() { if (x) return a; return; }",
+            "function f() { if (x) return a; else g(); return b; }",
+            "function f() { if (x) { g(); return a; } return b; }",
+            "function f() { if (x) return a; g(); }",
+            "f(() => { g(); return a; });",
+            "f(() => { return; });",
+        ];
+
+        for source in sources.iter() {
+            assert_simplify(source, source);
+        }
+    }
+
+    #[test]
+    fn parallel() {
+        let source = "
+            /*! banner */
+            function a() { if (x) return 1; return 2; }
+            var b = () => { return 3; };
+            // ratel-disable-transform simplify-returns
+            function c() { if (x) return 4; return 5; }
+            class D { e() { if (x) return 6; return 7; } }
+            function f() { if (x) { return 8; } return 9;}
+        ";
+        let options = CodegenOptions { banner: Some("/* built */"), preserve_comments: &["!"], ..CodegenOptions::default() };
+        let expected = minify_parallel(source, options, 1).unwrap();
+
+        assert!(expected.starts_with("/* built */\n/*! banner */\nfunction a(){return x?1:2;}"));
+        assert!(expected.contains("function c(){if(x)return 4;return 5;}"));
+
+        for threads in 2..8 {
+            assert_eq!(minify_parallel(source, options, threads).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn parallel_without_semicolons() {
+        let source = "\"use strict\";\nvar a = 1\nfunction f(){ return 1 }\nvar b = 2\nfunction g(x){ if (x) return 1; return 2 }\nlet c = 3\n";
+        let expected = minify_parallel(source, CodegenOptions::default(), 1).unwrap();
+
+        assert!(expected.ends_with("function g(x){return x?1:2;}let c=3;"));
+
+        for threads in 2..8 {
+            assert_eq!(minify_parallel(source, CodegenOptions::default(), threads).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn parallel_errors() {
+        let errors = minify_parallel("a;\nb(", CodegenOptions::default(), 4).err().unwrap();
+
+        assert_eq!(errors[0].start, 5);
+    }
+}
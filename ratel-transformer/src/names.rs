@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+
+use toolshed::Arena;
+
+use scope::Scope;
+
+/// Prefix of generated names unless set otherwise.
+pub const DEFAULT_PREFIX: &str = "$_ratel";
+
+/// Generates names for variables introduced by transformations, the prefix
+/// followed by a number, `$_ratel0`, `$_ratel1` and so on. Names used or
+/// declared anywhere in the analyzed module are skipped, as are the names
+/// generated before, so that they can't collide with any binding.
+///
+/// Names introduced by earlier passes are part of the module, and are
+/// found again by the scope analysis of the later passes.
+pub struct NameGenerator<'ast> {
+    arena: &'ast Arena,
+    scope: &'ast Scope<'ast>,
+    prefix: String,
+
+    /// Number following the prefix in the next name tried
+    next: usize,
+
+    /// Names of the scope tree and generated names, collected on first use
+    taken: Option<HashSet<&'ast str>>,
+}
+
+impl<'ast> NameGenerator<'ast> {
+    /// Create a generator avoiding the names of `scope` and its children,
+    /// which should be the root scope of the module.
+    #[inline]
+    pub fn new(arena: &'ast Arena, scope: &'ast Scope<'ast>) -> Self {
+        NameGenerator {
+            arena,
+            scope,
+            prefix: DEFAULT_PREFIX.to_owned(),
+            next: 0,
+            taken: None,
+        }
+    }
+
+    /// Set the prefix of the names generated from now on, which must be a
+    /// valid start of an identifier.
+    #[inline]
+    pub fn set_prefix(&mut self, prefix: &str) {
+        self.prefix = prefix.to_owned();
+        self.next = 0;
+    }
+
+    /// Generate a name not bound or referenced anywhere in the module.
+    pub fn generate(&mut self) -> &'ast str {
+        let scope = self.scope;
+        let taken = self.taken.get_or_insert_with(|| {
+            let mut taken = HashSet::new();
+
+            collect_names(scope, &mut taken);
+
+            taken
+        });
+
+        loop {
+            let name = format!("{}{}", self.prefix, self.next);
+
+            self.next += 1;
+
+            if !taken.contains(name.as_str()) {
+                let name = self.arena.alloc_string(name);
+
+                taken.insert(name);
+
+                return name;
+            }
+        }
+    }
+}
+
+fn collect_names<'ast>(scope: &'ast Scope<'ast>, names: &mut HashSet<&'ast str>) {
+    names.extend(scope.used_refs.iter().map(|(&name, _)| name));
+    names.extend(scope.declared_refs.iter().map(|(&name, _)| name));
+
+    for child in scope.children.as_list().iter() {
+        collect_names(child, names);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use scope::analyze;
+
+    #[test]
+    fn generates_names_in_order() {
+        let module = parse("foo;").unwrap();
+        let mut names = NameGenerator::new(module.arena(), analyze(&module));
+
+        assert_eq!(names.generate(), "$_ratel0");
+        assert_eq!(names.generate(), "$_ratel1");
+
+        names.set_prefix("_tmp");
+
+        assert_eq!(names.generate(), "_tmp0");
+    }
+
+    #[test]
+    fn skips_names_of_the_module() {
+        let module = parse("var $_ratel0; function f() { return () => { $_ratel1; let $_ratel3; }; }").unwrap();
+        let mut names = NameGenerator::new(module.arena(), analyze(&module));
+
+        assert_eq!(names.generate(), "$_ratel2");
+        assert_eq!(names.generate(), "$_ratel4");
+    }
+}
@@ -0,0 +1,183 @@
+use ratel::ast::{Expression, ExpressionNode, Property, PropertyKey, Function, EmptyName, OptionalName, OperatorKind};
+use ratel::ast::expression::{ObjectExpression, BinaryExpression};
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+
+use scope::function_bindings;
+use Transformer;
+
+/// Codemod converting properties of object literals to their shorthand
+/// forms, `{ a: a }` into `{ a }` and `{ f: function () {} }` into
+/// `{ f() {} }`.
+///
+/// Only anonymous functions, which can't refer to themselves by name,
+/// become methods. Methods can't be called with `new`, which can't be
+/// told from the object literal alone.
+pub struct ShorthandProperties;
+
+impl<'ast> StaticVisitor<'ast> for ShorthandProperties {
+    type Context = Transformer<'ast>;
+
+    fn on_object_expression(_: &ObjectExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let object = match ptr.item {
+            Expression::Object(ref object) => object,
+            _ => return,
+        };
+
+        for property in &object.body {
+            let (key, value) = match property.item {
+                Property::Literal { key, value } => (key, value),
+                _ => continue,
+            };
+
+            match (key.item, value.item) {
+                (PropertyKey::Literal(label), Expression::Identifier(name)) if label == name => {
+                    t.swap(property, Property::Shorthand(name));
+                },
+                (_, Expression::Function(function)) if function.name.0.is_none() && !function.generator => {
+                    let value = t.alloc_as_loc(value, Function {
+                        name: EmptyName,
+                        generator: false,
+                        is_async: function.is_async,
+                        params: function.params,
+                        body: function.body,
+                    });
+
+                    t.swap(property, Property::Method { key, value });
+                },
+                _ => {},
+            }
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_object_expression.push(ShorthandProperties::on_object_expression);
+    }
+}
+
+/// Codemod converting shorthand properties and methods of object literals
+/// to `key: value` properties, for targets without them.
+///
+/// Methods using `super` are kept, function expressions can't use it.
+pub struct LonghandProperties;
+
+impl<'ast> StaticVisitor<'ast> for LonghandProperties {
+    type Context = Transformer<'ast>;
+
+    fn on_object_expression(_: &ObjectExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        let object = match ptr.item {
+            Expression::Object(ref object) => object,
+            _ => return,
+        };
+
+        for property in &object.body {
+            match property.item {
+                Property::Shorthand(name) => {
+                    let key = t.alloc_as_loc(*property, PropertyKey::Literal(name));
+                    let value = t.alloc_as_loc(*property, Expression::Identifier(name));
+
+                    t.swap(property, Property::Literal { key, value });
+                },
+                Property::ShorthandDefault { name, value } => {
+                    let key = t.alloc_as_loc(name, PropertyKey::Literal(name.item));
+                    let left = t.alloc_as_loc(name, Expression::Identifier(name.item));
+                    let value = t.alloc_as_loc(*property, BinaryExpression {
+                        operator: OperatorKind::Assign,
+                        left,
+                        right: value,
+                    });
+
+                    t.swap(property, Property::Literal { key, value });
+                },
+                Property::Method { key, value } if !function_bindings(&value.params, &value.body.body).super_ => {
+                    let value = t.alloc_as_loc(value, Function {
+                        name: OptionalName(None),
+                        generator: value.generator,
+                        is_async: value.is_async,
+                        params: value.params,
+                        body: value.body,
+                    });
+
+                    t.swap(property, Property::Literal { key, value });
+                },
+                _ => {},
+            }
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_object_expression.push(LonghandProperties::on_object_expression);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use pass::{Pass, Visit};
+    use ratel_visitor::Visitor;
+
+    fn assert_convert<V>(visitor: V, source: &str, expected: &str) where
+        V: for<'ast> Visitor<'ast, Context = Transformer<'ast>>,
+    {
+        let module = parse(source).unwrap();
+
+        Visit(visitor).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn shorthand_properties() {
+        assert_convert(ShorthandProperties, "({ a: a, b: c, 'd': d });", "({ a, b: c, 'd': d });");
+        assert_convert(ShorthandProperties, "({ f: function (a) { return a; } });", "({ f(a) { return a; } });");
+        assert_convert(ShorthandProperties, "({ [f]: function () {}, 1: function () {} });", "({ [f]() {}, 1() {} });");
+    }
+
+    #[test]
+    fn shorthand_properties_nested() {
+        assert_convert(ShorthandProperties, "({ a: { b: b } });", "({ a: { b } });");
+        assert_convert(ShorthandProperties, "({ f: function () { return { a: a }; } });", "({ f() { return { a }; } });");
+    }
+
+    #[test]
+    fn not_shorthand_properties() {
+        let sources = [
+            "({ f: function g() {} });",
+            "({ f: function* () {} });",
+            "({ f: () => {} });",
+            "({ a: b.a });",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(ShorthandProperties, source, source);
+        }
+    }
+
+    #[test]
+    fn longhand_properties() {
+        assert_convert(LonghandProperties, "({ a, b: c });", "({ a: a, b: c });");
+        assert_convert(LonghandProperties, "({ f(a) { return a; }, [g]() {} });", "({ f: function (a) { return a; }, [g]: function () {} });");
+        assert_convert(LonghandProperties, "({ a = 1, b: { c = 2 } } = d);", "({ a: a = 1, b: { c: c = 2 } } = d);");
+    }
+
+    #[test]
+    fn not_longhand_properties() {
+        let sources = [
+            "({ f() { return super.f(); } });",
+            "({ f(a = super.a) {} });",
+            "({ f() { return () => super.a; } });",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(LonghandProperties, source, source);
+        }
+
+        assert_convert(
+            LonghandProperties,
+            "({ f() { return { g() { super.g(); } }; } });",
+            "({ f: function () { return { g() { super.g(); } }; } });"
+        );
+    }
+}
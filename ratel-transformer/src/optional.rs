@@ -0,0 +1,190 @@
+use ratel::ast::{Expression, ExpressionNode, Loc, Node, OperatorKind};
+use ratel::ast::expression::{BinaryExpression, MemberExpression, ComputedMemberExpression, PrivateMemberExpression, CallExpression};
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+
+use effects::is_pure;
+use Transformer;
+
+/// Codemod converting chains of `&&` guarding accesses of members and
+/// calls into optional chains, such as `a && a.b && a.b.c` into `a?.b?.c`.
+///
+/// The optional chain evaluates every guard once rather than once per use,
+/// so only guards without side effects, see `effects::is_pure`, are merged. Values differ
+/// when a guard is `null`, given as `undefined` by the optional chain, or
+/// another falsy value such as `0`, whose member is accessed rather than
+/// given as is.
+pub struct OptionalChaining;
+
+impl<'ast> StaticVisitor<'ast> for OptionalChaining {
+    type Context = Transformer<'ast>;
+
+    fn on_binary_expression(binary: &BinaryExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if binary.operator != OperatorKind::LogicalAnd {
+            return;
+        }
+
+        let mut operands = Vec::new();
+
+        flatten(ptr, &mut operands);
+
+        // Operands with the operands they guard merged into them
+        let mut chains: Vec<ExpressionNode<'ast>> = Vec::new();
+        let mut guard: Option<Expression<'ast>> = None;
+        let mut merged = false;
+
+        for operand in operands {
+            let item = operand.item;
+
+            let link = guard
+                .filter(is_pure)
+                .and_then(|guard| find_link(operand, &guard));
+
+            match (link, chains.last_mut()) {
+                (Some(link), Some(chain)) => {
+                    make_optional(link, *chain, t);
+
+                    *chain = *operand;
+                    merged = true;
+                },
+                _ => chains.push(*operand),
+            }
+
+            guard = Some(item);
+        }
+
+        if !merged {
+            return;
+        }
+
+        let mut chains = chains.into_iter();
+        let mut left = match chains.next() {
+            Some(chain) => chain,
+            None => return,
+        };
+
+        for right in chains {
+            left = Node::new(t.arena.alloc(Loc::new(left.start, right.end, Expression::Binary(BinaryExpression {
+                operator: OperatorKind::LogicalAnd,
+                left,
+                right,
+            }))));
+        }
+
+        t.swap(ptr, left.item);
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_binary_expression.push(OptionalChaining::on_binary_expression);
+    }
+}
+
+/// Collect the operands of nested `&&`, which is associative, in order.
+fn flatten<'ast>(node: &'ast ExpressionNode<'ast>, operands: &mut Vec<&'ast ExpressionNode<'ast>>) {
+    match node.get().item {
+        Expression::Binary(BinaryExpression { operator: OperatorKind::LogicalAnd, ref left, ref right }) => {
+            flatten(left, operands);
+            flatten(right, operands);
+        },
+        _ => operands.push(node),
+    }
+}
+
+/// Find the member access or call in the chain of `node` made directly on
+/// `guard`, as `a.b` in `a.b.c` guarded by `a`.
+fn find_link<'ast>(mut node: &'ast ExpressionNode<'ast>, guard: &Expression<'ast>) -> Option<&'ast ExpressionNode<'ast>> {
+    loop {
+        let object = match node.get().item {
+            Expression::Member(ref member) => &member.object,
+            Expression::ComputedMember(ref member) => &member.object,
+            Expression::PrivateMember(ref member) => &member.object,
+            Expression::Call(ref call) => &call.callee,
+            _ => return None,
+        };
+
+        if object.item == *guard {
+            return Some(node);
+        }
+
+        node = object;
+    }
+}
+
+/// Make the member access or call optional, on the already converted
+/// chain of its guard.
+fn make_optional<'ast>(link: &'ast ExpressionNode<'ast>, object: ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+    match link.item {
+        Expression::Member(member) => t.swap(link, MemberExpression {
+            object,
+            optional: true,
+            ..member
+        }),
+        Expression::ComputedMember(member) => t.swap(link, ComputedMemberExpression {
+            object,
+            optional: true,
+            ..member
+        }),
+        Expression::PrivateMember(member) => t.swap(link, PrivateMemberExpression {
+            object,
+            optional: true,
+            ..member
+        }),
+        Expression::Call(call) => t.swap(link, CallExpression {
+            callee: object,
+            optional: true,
+            ..call
+        }),
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use pass::{Pass, Visit};
+
+    fn assert_convert(source: &str, expected: &str) {
+        let module = parse(source).unwrap();
+
+        Visit(OptionalChaining).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn guard_chains() {
+        assert_convert("a && a.b;", "a?.b;");
+        assert_convert("a && a.b && a.b.c;", "a?.b?.c;");
+        assert_convert("a && a.b.c && a.b.c.d;", "a?.b.c?.d;");
+        assert_convert("a && a[0] && a[0][k];", "a?.[0]?.[k];");
+        assert_convert("a && a.b && a.b();", "a?.b?.();");
+        assert_convert("a && a.b(c);", "a?.b(c);");
+        assert_convert("if (a && (a.b && a.b.c)) {}", "if (a?.b?.c) {}");
+    }
+
+    #[test]
+    fn guard_chains_among_operands() {
+        assert_convert("x && a && a.b && y;", "x && a?.b && y;");
+        assert_convert("a && a.b && b && b.c;", "a?.b && b?.c;");
+        assert_convert("f(a && a.b) && c;", "f(a?.b) && c;");
+        assert_convert("a && a.b(c && c.d);", "a?.b(c?.d);");
+    }
+
+    #[test]
+    fn not_guard_chains() {
+        let sources = [
+            "a && b.c;",
+            "a || a.b;",
+            "a() && a().b;",
+            "a.b() && a.b().c;",
+            "a[f()] && a[f()].b;",
+            "a && new a.B();",
+            "a && a.b + 1;",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(source, source);
+        }
+    }
+}
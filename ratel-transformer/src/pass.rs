@@ -0,0 +1,712 @@
+use std::mem;
+use std::time::{Duration, Instant};
+
+use ratel::{Module, Output};
+use ratel::cancel::{CancellationToken, Cancelled};
+use ratel::diagnostic::{Diagnostic, DiagnosticsSink, Severity};
+use ratel::error::ErrorKind;
+use ratel_visitor::{Visitable, Visitor};
+
+use assign::{ExpandAssignments, ContractAssignments};
+use directive::directives;
+use es2015::PRESET_ES2015;
+use fold::FoldSwitch;
+use minify::SimplifyReturns;
+use scope::analyze;
+use Transformer;
+#[cfg(feature = "codemods")]
+use arrow::{FunctionToArrow, ArrowToFunction};
+#[cfg(feature = "codemods")]
+use equality::strict_equality;
+#[cfg(feature = "codemods")]
+use object::{ShorthandProperties, LonghandProperties};
+#[cfg(feature = "codemods")]
+use optional::OptionalChaining;
+#[cfg(feature = "codemods")]
+use template::{ConcatToTemplate, TemplateToConcat};
+#[cfg(feature = "codemods")]
+use var::var_to_let;
+
+/// A single transformation of a `Module`.
+pub trait Pass<'ast> {
+    /// Run the pass on the module, returning whether anything changed.
+    fn run(&mut self, module: &'ast Module<'ast>) -> bool;
+
+    /// Run the pass on the module, leaving code within each `start`, `end`
+    /// of `disabled` unchanged. By default a pass can only leave the whole
+    /// module unchanged, so it doesn't run if anything is disabled.
+    fn run_except(&mut self, module: &'ast Module<'ast>, disabled: &[(u32, u32)]) -> bool {
+        match disabled.is_empty() {
+            true => self.run(module),
+            false => false,
+        }
+    }
+
+    /// Take the diagnostics reported by the runs so far. By default a pass
+    /// doesn't report any.
+    fn diagnostics(&mut self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+impl<'ast, F> Pass<'ast> for F where
+    F: FnMut(&'ast Module<'ast>) -> bool,
+{
+    #[inline]
+    fn run(&mut self, module: &'ast Module<'ast>) -> bool {
+        self(module)
+    }
+}
+
+/// Runs a visitor over a `Module` as a `Pass`, with a fresh `Transformer`
+/// on each run. The pass has changed the module if the visitor swapped
+/// any node, disabled code and the nodes around it are left unchanged by
+/// `Transformer::swap`.
+pub struct Visit<V>(pub V);
+
+impl<'ast, V> Pass<'ast> for Visit<V> where
+    V: Visitor<'ast, Context = Transformer<'ast>>,
+{
+    #[inline]
+    fn run(&mut self, module: &'ast Module<'ast>) -> bool {
+        self.run_except(module, &[])
+    }
+
+    fn run_except(&mut self, module: &'ast Module<'ast>, disabled: &[(u32, u32)]) -> bool {
+        visit(&self.0, module, disabled, Options::default()).changed()
+    }
+}
+
+/// Options of the `Transformer` of a `VisitWith` pass.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Options {
+    /// Whether transforms can declare helper functions, see
+    /// `Transformer::without_helpers`. Defaults to `true`
+    pub helpers: bool,
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Options {
+            helpers: true,
+        }
+    }
+}
+
+/// Same as `Visit`, with the `Transformer` configured by the `Options`,
+/// and the diagnostics it reports kept for `Pass::diagnostics`.
+pub struct VisitWith<V> {
+    visitor: V,
+    options: Options,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<V> VisitWith<V> {
+    #[inline]
+    pub fn new(visitor: V, options: Options) -> Self {
+        VisitWith {
+            visitor,
+            options,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl<'ast, V> Pass<'ast> for VisitWith<V> where
+    V: Visitor<'ast, Context = Transformer<'ast>>,
+{
+    #[inline]
+    fn run(&mut self, module: &'ast Module<'ast>) -> bool {
+        self.run_except(module, &[])
+    }
+
+    fn run_except(&mut self, module: &'ast Module<'ast>, disabled: &[(u32, u32)]) -> bool {
+        let transformer = visit(&self.visitor, module, disabled, self.options);
+
+        self.diagnostics.extend(transformer.diagnostics().iter().cloned());
+
+        transformer.changed()
+    }
+
+    #[inline]
+    fn diagnostics(&mut self) -> Vec<Diagnostic> {
+        mem::take(&mut self.diagnostics)
+    }
+}
+
+/// Run the visitor over the module, giving the `Transformer` it used.
+fn visit<'ast, V>(visitor: &V, module: &'ast Module<'ast>, disabled: &[(u32, u32)], options: Options) -> Transformer<'ast> where
+    V: Visitor<'ast, Context = Transformer<'ast>>,
+{
+    let mut transformer = Transformer::new(module.arena(), analyze(module)).with_budget(module.budget());
+
+    if !options.helpers {
+        transformer = transformer.without_helpers();
+    }
+
+    for &(start, end) in disabled {
+        transformer.disable(start, end);
+    }
+
+    module.traverse(visitor, &mut transformer);
+
+    #[cfg(feature = "debug-invariants")]
+    ::ratel::invariants::assert_valid(module, ::std::any::type_name::<V>());
+
+    transformer
+}
+
+/// Get a built-in pass by name, one of `"es2015"`, `"fold-switch"`,
+/// `"simplify-returns"`, `"expand-assignments"`, `"contract-assignments"`,
+/// `"function-to-arrow"`, `"arrow-to-function"`, `"var-to-let"`,
+/// `"concat-to-template"`, `"template-to-concat"`, `"object-shorthand"`,
+/// `"object-longhand"`, `"strict-equality"` or `"optional-chaining"`.
+/// Only `"es2015"`, `"fold-switch"`, `"simplify-returns"` and the
+/// assignment passes are available without the `codemods` feature.
+pub fn builtin<'ast>(name: &str) -> Option<Box<dyn Pass<'ast> + 'ast>> {
+    match name {
+        "es2015"               => Some(Box::new(Visit(PRESET_ES2015))),
+        "fold-switch"          => Some(Box::new(Visit(FoldSwitch))),
+        "simplify-returns"     => Some(Box::new(Visit(SimplifyReturns))),
+        "expand-assignments"   => Some(Box::new(Visit(ExpandAssignments))),
+        "contract-assignments" => Some(Box::new(Visit(ContractAssignments))),
+        _                      => codemod(name),
+    }
+}
+
+#[cfg(feature = "codemods")]
+fn codemod<'ast>(name: &str) -> Option<Box<dyn Pass<'ast> + 'ast>> {
+    match name {
+        "function-to-arrow"  => Some(Box::new(Visit(FunctionToArrow))),
+        "arrow-to-function"  => Some(Box::new(Visit(ArrowToFunction))),
+        "var-to-let"         => Some(Box::new(|module: &'ast Module<'ast>| var_to_let(module).converted > 0)),
+        "concat-to-template" => Some(Box::new(Visit(ConcatToTemplate))),
+        "template-to-concat" => Some(Box::new(Visit(TemplateToConcat))),
+        "object-shorthand"   => Some(Box::new(Visit(ShorthandProperties))),
+        "object-longhand"    => Some(Box::new(Visit(LonghandProperties))),
+        "strict-equality"    => Some(Box::new(|module: &'ast Module<'ast>| strict_equality(module).converted > 0)),
+        "optional-chaining"  => Some(Box::new(Visit(OptionalChaining))),
+        _                    => None,
+    }
+}
+
+#[cfg(not(feature = "codemods"))]
+#[inline]
+fn codemod<'ast>(_: &str) -> Option<Box<dyn Pass<'ast> + 'ast>> {
+    None
+}
+
+/// How the `PassManager` runs its passes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    /// Run every pass once, in order
+    Once,
+
+    /// Run the passes in order, repeatedly, until none of them changes
+    /// anything, or until `max_rounds` rounds have run
+    Fixpoint { max_rounds: usize },
+}
+
+/// Statistics of a single pass, collected by `PassManager::run`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PassReport {
+    pub name: String,
+
+    /// Number of times the pass ran
+    pub runs: usize,
+
+    /// Number of runs which changed the module
+    pub changes: usize,
+
+    /// Total time spent in the pass
+    pub time: Duration,
+
+    /// Diagnostics of the runs, each reported once even if it was reported
+    /// again by later runs
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Callback of `PassManager::on_progress`.
+type Progress<'ast> = Box<dyn FnMut(&PassReport) + 'ast>;
+
+/// Runs a pipeline of named passes over a `Module`.
+///
+/// Built-in passes are deterministic: the same source and pipeline give
+/// the same module and reports, apart from `time`, on every run and
+/// platform. They never depend on addresses or on the iteration order of
+/// hash maps, which are only used for lookups.
+pub struct PassManager<'ast> {
+    mode: Mode,
+    passes: Vec<(String, Box<dyn Pass<'ast> + 'ast>)>,
+
+    /// Checked before every run of a pass, see `cancellation`
+    token: Option<CancellationToken>,
+
+    /// Called after every run of a pass, see `on_progress`
+    progress: Option<Progress<'ast>>,
+
+    /// Receives the diagnostics of every run of a pass, see `diagnostics_sink`
+    sink: Option<&'ast dyn DiagnosticsSink>,
+}
+
+impl<'ast> PassManager<'ast> {
+    #[inline]
+    pub fn new(mode: Mode) -> Self {
+        PassManager {
+            mode,
+            passes: Vec::new(),
+            token: None,
+            progress: None,
+            sink: None,
+        }
+    }
+
+    /// Stop running passes once `token` is cancelled. Passes aren't
+    /// interrupted, the pipeline stops before the next one would run.
+    pub fn cancellation(&mut self, token: CancellationToken) -> &mut Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Call `callback` after every run of a pass with its report so far.
+    pub fn on_progress<F>(&mut self, callback: F) -> &mut Self where
+        F: FnMut(&PassReport) + 'ast,
+    {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Report the diagnostics of every run of a pass to `sink` once the run
+    /// is over, as a batch, leaving out those already in the report of the
+    /// pass. They are still kept in the reports.
+    pub fn diagnostics_sink(&mut self, sink: &'ast dyn DiagnosticsSink) -> &mut Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Add a pass at the end of the pipeline.
+    pub fn add<P>(&mut self, name: &str, pass: P) -> &mut Self where
+        P: Pass<'ast> + 'ast,
+    {
+        self.passes.push((name.to_owned(), Box::new(pass)));
+        self
+    }
+
+    /// Add the built-in pass `name` at the end of the pipeline, returns
+    /// `false` if there is no such pass.
+    pub fn add_builtin(&mut self, name: &str) -> bool {
+        match builtin(name) {
+            Some(pass) => {
+                self.passes.push((name.to_owned(), pass));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Names of the passes, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.passes.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Run the pipeline on the module, producing a report for each pass,
+    /// in pipeline order. Passes are disabled for parts of the module by
+    /// the directives in its comments, see `directive::Directive`.
+    ///
+    /// In `Fixpoint` mode the pipeline stops as soon as every pass has run
+    /// once without changes since the last change, even in the middle of
+    /// a round. It also stops when cancelled, see `try_run` to tell, and
+    /// once the quota of the module is exceeded, see `Module::budget`.
+    pub fn run(&mut self, module: &'ast Module<'ast>) -> Vec<PassReport> {
+        self.run_until_cancelled(module).0
+    }
+
+    /// Same as `run`, but gives the module as an `Output`, with the time
+    /// spent in each pass as its timings, and the diagnostics of the passes.
+    /// Reaching the quota of the module is reported as an error.
+    pub fn run_output(&mut self, module: &'ast Module<'ast>) -> Output<'ast, &'ast Module<'ast>> {
+        let reports = self.run(module);
+        let mut output = Output::new(module, module.features());
+
+        for report in reports {
+            output.diagnostics.extend(report.diagnostics);
+            output = output.with_timing(report.name, report.time);
+        }
+
+        if let Err(err) = module.budget().check() {
+            let kind = ErrorKind::QuotaExceeded { resource: err.resource, limit: err.limit };
+            let message = format!("{}, transforms stopped", err);
+
+            output.diagnostics.push(Diagnostic::new(Severity::Error, kind.code(), message));
+        }
+
+        output
+    }
+
+    /// Same as `run`, but gives `Err(Cancelled)` if the pipeline was
+    /// stopped by the `cancellation` token, leaving the module with the
+    /// changes of the passes that ran.
+    pub fn try_run(&mut self, module: &'ast Module<'ast>) -> Result<Vec<PassReport>, Cancelled> {
+        match self.run_until_cancelled(module) {
+            (_, true) => Err(Cancelled),
+            (reports, false) => Ok(reports),
+        }
+    }
+
+    /// Run the pipeline, returning its reports and whether it was cancelled.
+    fn run_until_cancelled(&mut self, module: &'ast Module<'ast>) -> (Vec<PassReport>, bool) {
+        let mut reports: Vec<PassReport> = self.passes.iter().map(|(name, _)| PassReport {
+            name: name.clone(),
+            runs: 0,
+            changes: 0,
+            time: Duration::from_secs(0),
+            diagnostics: Vec::new(),
+        }).collect();
+
+        let max_rounds = match self.mode {
+            Mode::Once => 1,
+            Mode::Fixpoint { max_rounds } => max_rounds,
+        };
+
+        let directives = directives(module);
+        let disabled: Vec<Vec<(u32, u32)>> = self.passes.iter().map(|(name, _)| {
+            directives
+                .iter()
+                .filter(|directive| directive.disables(name))
+                .map(|directive| (directive.start, directive.end))
+                .collect()
+        }).collect();
+
+        let len = self.passes.len();
+
+        // Number of consecutive runs without changes
+        let mut clean = 0;
+
+        'rounds: for _ in 0..max_rounds {
+            for (((_, pass), report), disabled) in self.passes.iter_mut().zip(reports.iter_mut()).zip(&disabled) {
+                if clean == len {
+                    break 'rounds;
+                }
+
+                if self.token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                    return (reports, true);
+                }
+
+                if module.budget().check().is_err() {
+                    break 'rounds;
+                }
+
+                let start = Instant::now();
+                let changed = pass.run_except(module, disabled);
+
+                report.time += start.elapsed();
+
+                #[cfg(feature = "debug-invariants")]
+                ::ratel::invariants::assert_valid(module, &report.name);
+                report.runs += 1;
+
+                let reported = report.diagnostics.len();
+
+                for diagnostic in pass.diagnostics() {
+                    if !report.diagnostics.contains(&diagnostic) {
+                        report.diagnostics.push(diagnostic);
+                    }
+                }
+
+                if let Some(sink) = self.sink {
+                    if report.diagnostics.len() > reported {
+                        sink.report(&report.diagnostics[reported..]);
+                    }
+                }
+
+                if changed {
+                    report.changes += 1;
+                    clean = 0;
+                } else {
+                    clean += 1;
+                }
+
+                if let Some(ref mut progress) = self.progress {
+                    progress(report);
+                }
+            }
+        }
+
+        (reports, false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use ratel::{parse, FunctionKind};
+
+    #[test]
+    fn runs_passes_in_order_once() {
+        let module = parse("foo;").unwrap();
+        let order = Cell::new(0);
+        let mut manager = PassManager::new(Mode::Once);
+
+        manager
+            .add("first", |_: &Module| { assert_eq!(order.get(), 0); order.set(1); true })
+            .add("second", |_: &Module| { assert_eq!(order.get(), 1); order.set(2); false });
+
+        let reports = manager.run(&module);
+
+        assert_eq!(manager.names(), vec!["first", "second"]);
+        assert_eq!(order.get(), 2);
+        assert_eq!(reports.iter().map(|r| (r.name.as_str(), r.runs, r.changes)).collect::<Vec<_>>(), vec![
+            ("first", 1, 1),
+            ("second", 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn fixpoint_stops_when_nothing_changes() {
+        let module = parse("foo;").unwrap();
+        let left = Cell::new(3);
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+        manager
+            .add("noop", |_: &Module| false)
+            .add("countdown", |_: &Module| {
+                if left.get() == 0 {
+                    return false;
+                }
+                left.set(left.get() - 1);
+                true
+            })
+            .add("last", |_: &Module| false);
+
+        let reports = manager.run(&module);
+
+        // The fourth round stops before `last`, which has already seen the
+        // module since the last change in `countdown`
+        assert_eq!(reports.iter().map(|r| (r.runs, r.changes)).collect::<Vec<_>>(), vec![
+            (4, 0),
+            (4, 3),
+            (3, 0),
+        ]);
+    }
+
+    #[test]
+    fn fixpoint_respects_max_rounds() {
+        let module = parse("foo;").unwrap();
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 3 });
+
+        manager.add("always", |_: &Module| true);
+
+        assert_eq!(manager.run(&module)[0].runs, 3);
+    }
+
+    #[test]
+    fn cancellation_stops_between_passes() {
+        let module = parse("foo;").unwrap();
+        let token = CancellationToken::new();
+        let host = token.clone();
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+        manager
+            .cancellation(token)
+            .add("cancel", move |_: &Module| { host.cancel(); true })
+            .add("never", |_: &Module| true);
+
+        assert_eq!(manager.try_run(&module), Err(Cancelled));
+        assert_eq!(manager.run(&module).iter().map(|r| r.runs).collect::<Vec<_>>(), vec![0, 0]);
+    }
+
+    #[test]
+    fn reports_progress() {
+        let module = parse("foo;").unwrap();
+        let progress = Cell::new(0);
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 2 });
+
+        manager
+            .on_progress(|report| {
+                progress.set(progress.get() + 1);
+                assert!(report.runs > 0);
+            })
+            .add("first", |_: &Module| true)
+            .add("second", |_: &Module| false);
+
+        assert_eq!(manager.try_run(&module).map(|reports| reports.len()), Ok(2));
+        assert_eq!(progress.get(), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "codemods")]
+    fn directives_disable_passes() {
+        let module = parse("/* ratel-disable-transform function-to-arrow */ a(function () {}); b(function () {});").unwrap();
+        let mut manager = PassManager::new(Mode::Once);
+
+        manager.add_builtin("function-to-arrow");
+        manager.add("custom", |_: &Module| true);
+
+        let reports = manager.run(&module);
+
+        assert_eq!(reports.iter().map(|r| r.changes).collect::<Vec<_>>(), vec![1, 1]);
+        assert_eq!(module.body(), parse("a(function () {}); b(() => {});").unwrap().body());
+    }
+
+    #[test]
+    #[cfg(feature = "codemods")]
+    fn directives_keep_enclosing_nodes() {
+        let source = "var f = function () { /* ratel-disable-transform */ return function () { return 1; }; };";
+        let module = parse(source).unwrap();
+        let mut manager = PassManager::new(Mode::Once);
+
+        manager.add_builtin("function-to-arrow");
+
+        assert_eq!(manager.run(&module)[0].changes, 0);
+        assert_eq!(module.body(), parse(source).unwrap().body());
+    }
+
+    #[test]
+    fn directives_skip_custom_passes() {
+        let module = parse("a; // ratel-disable-transform\nb;").unwrap();
+        let mut manager = PassManager::new(Mode::Once);
+
+        manager.add("custom", |_: &Module| true);
+
+        assert_eq!(manager.run(&module)[0].changes, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "codemods")]
+    fn builtin_passes_are_deterministic() {
+        let source = "var a = 1; if (a == 1) { var b = a && a.c; } var f = function () { return 'x' + b + `${a}`; }; ({ a: a });";
+        let run = || {
+            let module = parse(source).unwrap();
+            let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+            for name in &["var-to-let", "strict-equality", "optional-chaining", "concat-to-template", "function-to-arrow", "object-shorthand", "es2015"] {
+                assert!(manager.add_builtin(name));
+            }
+
+            let reports = manager.run(&module).into_iter().map(|r| (r.name, r.runs, r.changes)).collect::<Vec<_>>();
+
+            (format!("{:?}", module.body()), reports)
+        };
+
+        let first = run();
+
+        // Hold on to another module, so that the second run gets different
+        // addresses
+        let _other = parse(source).unwrap();
+
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    fn builtin_es2015() {
+        let module = parse("let f = () => 1;").unwrap();
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+        assert_eq!(manager.add_builtin("es2015"), true);
+        assert_eq!(manager.add_builtin("nope"), false);
+
+        let reports = manager.run(&module);
+
+        assert_eq!((reports[0].runs, reports[0].changes), (2, 1));
+        assert_eq!(module.functions().map(|f| f.kind).collect::<Vec<_>>(), vec![FunctionKind::Expression]);
+    }
+
+    #[test]
+    fn stops_at_the_quota() {
+        use ratel::{parse_with_options, ParserOptions};
+        use ratel::quota::Quota;
+
+        let source = "a(() => b);";
+        let usage = parse(source).unwrap().budget().usage();
+        let options = ParserOptions::default().with_quota(Quota { max_nodes: Some(usage.nodes + 1), max_arena_bytes: None });
+        let module = parse_with_options(source, options).unwrap();
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+        manager.add_builtin("es2015");
+        manager.add("never", |_: &Module| true);
+
+        let reports = manager.run(&module);
+
+        assert!(module.budget().check().is_err());
+        assert_eq!(reports.iter().map(|r| (r.runs, r.changes)).collect::<Vec<_>>(), vec![(1, 0), (0, 0)]);
+        assert_eq!(module.body(), parse(source).unwrap().body());
+
+        let output = manager.run_output(&module);
+
+        assert_eq!(output.diagnostics.iter().map(|d| d.code).collect::<Vec<_>>(), vec!["E0009"]);
+    }
+
+    #[test]
+    fn reports_diagnostics_once() {
+        let module = parse("class A extends B { m() { return super.m(); } } f(() => 1);").unwrap();
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+        manager.add("es2015", VisitWith::new(PRESET_ES2015, Options { helpers: false }));
+
+        let reports = manager.run(&module);
+
+        assert_eq!((reports[0].runs, reports[0].changes), (2, 1));
+        assert_eq!(reports[0].diagnostics.iter().map(|d| d.code).collect::<Vec<_>>(), vec!["class-super"]);
+
+        let module = parse("class A extends B { m() { return super.m(); } }").unwrap();
+        let mut manager = PassManager::new(Mode::Once);
+
+        manager.add("es2015", VisitWith::new(PRESET_ES2015, Options { helpers: false }));
+
+        let output = manager.run_output(&module);
+
+        assert!(output.has_errors());
+        assert_eq!(output.diagnostics.iter().map(|d| d.code).collect::<Vec<_>>(), vec!["class-super"]);
+    }
+
+    #[test]
+    fn streams_diagnostics_to_a_sink() {
+        use std::sync::Mutex;
+
+        /// Records the codes of each batch
+        struct Batches(Mutex<Vec<Vec<&'static str>>>);
+
+        impl DiagnosticsSink for Batches {
+            fn report(&self, batch: &[Diagnostic]) {
+                self.0.lock().unwrap().push(batch.iter().map(|d| d.code).collect());
+            }
+        }
+
+        let module = parse("class A extends B { m() { return super.m(); } } f(() => 1);").unwrap();
+        let sink = Batches(Mutex::new(Vec::new()));
+        let mut manager = PassManager::new(Mode::Fixpoint { max_rounds: 10 });
+
+        manager
+            .diagnostics_sink(&sink)
+            .add("es2015", VisitWith::new(PRESET_ES2015, Options { helpers: false }))
+            .add("custom", |_: &Module| false);
+
+        let reports = manager.run(&module);
+
+        // The second run of `es2015` reports the same diagnostic again
+        assert_eq!(reports[0].runs, 2);
+        assert_eq!(reports[0].diagnostics.len(), 1);
+        assert_eq!(*sink.0.lock().unwrap(), vec![vec!["class-super"]]);
+    }
+
+    #[test]
+    fn runs_to_an_output() {
+        use ratel::{parse_output, ParserOptions, Features};
+
+        let parsed = parse_output("017; a(() => b);", ParserOptions::default());
+        let mut manager = PassManager::new(Mode::Once);
+
+        manager.add_builtin("es2015");
+
+        let output = parsed.as_borrowed().and_then(|module| manager.run_output(module));
+
+        assert!(!output.has_errors());
+        assert_eq!(output.features, Features::ARROW_FUNCTIONS);
+        assert_eq!(output.diagnostics.iter().map(|d| d.code).collect::<Vec<_>>(), vec!["E0007"]);
+        // Parsing is only timed with the `std` feature of ratel
+        assert_eq!(output.timings.last().map(|t| t.name.as_str()), Some("es2015"));
+        assert_eq!(output.value.body().iter().count(), 2);
+    }
+}
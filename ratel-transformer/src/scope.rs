@@ -1,18 +1,24 @@
 use std::fmt::{self, Debug};
+use std::collections::HashSet;
 
 use ratel::Module;
-use ratel::ast::{Identifier, ExpressionNode};
+use ratel::ast::{Identifier, ExpressionNode, StatementNode, Statement, Pattern, PatternList, Loc};
+use ratel::ast::DeclarationKind;
+use ratel::ast::expression::MetaPropertyExpression;
+use ratel::ast::statement::{DeclarationStatement, FunctionStatement};
 use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, ScopeKind};
 use toolshed::{Arena, CopyCell};
 use toolshed::list::GrowableList;
 use toolshed::map::BloomMap;
 
-/// Traverse the AST and produce a tree of `Scope`s.
+/// Traverse the AST and produce a tree of `Scope`s. Children and
+/// references of every scope are kept in source order.
 #[inline]
 pub fn analyze<'ast>(module: &'ast Module<'ast>) -> &'ast Scope<'ast> {
-    let mut ctx = ScopeContext::new(module.arena());
+    let mut ctx = ScopeContext::new(module.arena(), module.block_functions());
 
     module.traverse(&ScopeAnalizer, &mut ctx);
+    ctx.hoist_block_functions();
 
     ctx.current.get()
 }
@@ -27,15 +33,30 @@ pub struct Scope<'ast> {
     /// Whether or not the `super` keyword was used
     pub used_super: CopyCell<bool>,
 
-    /// Whether or not the `this` keyword was used
+    /// Only on `Function` scopes, whether or not the `this` keyword was
+    /// used, including in arrow functions nested in the function, which
+    /// take `this` from it
     pub used_this: CopyCell<bool>,
 
+    /// Only on `Function` scopes, whether or not `arguments` was used,
+    /// including in nested arrow functions as for `used_this`
+    pub used_arguments: CopyCell<bool>,
+
     /// All references used in this scope
     pub used_refs: BloomMap<'ast, &'ast str, ReferenceData>,
 
-    /// All references declared in this scope
+    /// All references declared in this scope, with `var` declarations
+    /// hoisted to the closest `Function` scope. Function declarations in
+    /// blocks of sloppy mode code are declared in both, see
+    /// `Module::block_functions`.
     pub declared_refs: BloomMap<'ast, &'ast str, ReferenceData>,
 
+    /// Only on `Catch` scopes, parameters also declared by a `var` inside
+    /// the catch clause. Per Annex B.3.5 this is not an error: the `var`
+    /// is hoisted to the function, but within the clause the name, along
+    /// with any assignment done by the declaration, refers to the parameter.
+    pub var_redeclared_refs: BloomMap<'ast, &'ast str, ReferenceData>,
+
     /// Parent scope of this scope
     pub parent: Option<&'ast Scope<'ast>>,
 
@@ -50,8 +71,10 @@ impl<'ast> Scope<'ast> {
             kind,
             used_super: CopyCell::new(false),
             used_this: CopyCell::new(false),
+            used_arguments: CopyCell::new(false),
             used_refs: BloomMap::new(),
             declared_refs: BloomMap::new(),
+            var_redeclared_refs: BloomMap::new(),
             parent,
             children: GrowableList::new(),
         }
@@ -66,6 +89,33 @@ impl<'ast> Scope<'ast> {
     pub unsafe fn from_usize(ptr: usize) -> &'ast Self {
         &*(ptr as *const Scope)
     }
+
+    /// Check whether `name` is declared in this scope or any scope nested
+    /// in it, such as a `var undefined` or a parameter named `undefined`
+    /// shadowing the global.
+    pub fn declares_anywhere(&self, name: &'ast str) -> bool {
+        self.declared_refs.contains_key(name) || self.children.as_list().iter().any(|child| child.declares_anywhere(name))
+    }
+
+    /// Check whether `name` is declared in this scope or any of its
+    /// parents, so that a reference to it used in this scope resolves to
+    /// a declaration of the code rather than a global.
+    pub fn declares(&self, name: &'ast str) -> bool {
+        self.declared_refs.contains_key(name) || self.parent.is_some_and(|parent| parent.declares(name))
+    }
+
+    /// Get the closest `Function` scope, this one or a parent, which is
+    /// the one providing `this` and `arguments`.
+    #[inline]
+    pub fn function_scope(&'ast self) -> &'ast Scope<'ast> {
+        let mut scope = self;
+
+        while scope.kind != ScopeKind::Function {
+            scope = scope.parent.expect("Root scope is a function scope");
+        }
+
+        scope
+    }
 }
 
 /// Need to manually implement Debug to avoid circular reference on `parent`
@@ -75,8 +125,10 @@ impl<'ast> Debug for Scope<'ast> {
             .field("kind", &self.kind)
             .field("used_super", &self.used_super)
             .field("used_this", &self.used_this)
+            .field("used_arguments", &self.used_arguments)
             .field("used_refs", &self.used_refs)
             .field("declared_refs", &self.declared_refs)
+            .field("var_redeclared_refs", &self.var_redeclared_refs)
             .field("children", &self.children)
             .finish()
     }
@@ -93,11 +145,23 @@ impl<'ast> PartialEq for &'ast Scope<'ast> {
 struct ScopeContext<'ast> {
     arena: &'ast Arena,
     pub current: CopyCell<&'ast Scope<'ast>>,
+
+    /// Addresses of identifiers of `var` declarations already declared
+    /// in their function scope, to be skipped once traversed. Never
+    /// iterated, as the order would change with the addresses
+    hoisted: HashSet<usize>,
+
+    /// `start`, `end` of function declarations subject to Annex B.3.3
+    block_functions: &'ast [(u32, u32)],
+
+    /// Names of the block functions traversed so far, with the scope they
+    /// are declared in. Hoisted once all the blocks are fully declared.
+    pending: Vec<(&'ast str, &'ast Scope<'ast>)>,
 }
 
 impl<'ast> ScopeContext<'ast> {
     #[inline]
-    fn new(arena: &'ast Arena) -> Self {
+    fn new(arena: &'ast Arena, block_functions: &'ast [(u32, u32)]) -> Self {
         let current = CopyCell::new(
             arena.alloc(Scope::new(ScopeKind::Function, None))
         );
@@ -105,6 +169,28 @@ impl<'ast> ScopeContext<'ast> {
         ScopeContext {
             arena,
             current,
+            hoisted: HashSet::new(),
+            block_functions,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Also var-declare the block functions, unless that would conflict with
+    /// a lexical declaration in any of the enclosing blocks, wherever in the
+    /// block it is.
+    fn hoist_block_functions(&mut self) {
+        'functions: for &(name, mut scope) in &self.pending {
+            while scope.kind != ScopeKind::Function {
+                let parent = scope.parent.expect("Root scope is a function scope");
+
+                if parent.kind == ScopeKind::Block && parent.declared_refs.contains_key(name) {
+                    continue 'functions;
+                }
+
+                scope = parent;
+            }
+
+            scope.declared_refs.insert(self.arena, name, ());
         }
     }
 }
@@ -132,16 +218,49 @@ impl<'ast> StaticVisitor<'ast> for ScopeAnalizer {
     #[inline]
     fn on_reference_use(ident: &Identifier<'ast>, ctx: &mut Self::Context) {
         ctx.current.get().used_refs.insert(ctx.arena, *ident, ());
+
+        if *ident == "arguments" {
+            ctx.current.get().function_scope().used_arguments.set(true);
+        }
     }
 
     #[inline]
     fn on_reference_declaration(ident: &Identifier<'ast>, ctx: &mut Self::Context) {
+        if ctx.hoisted.remove(&(ident as *const Identifier as usize)) {
+            return;
+        }
+
         ctx.current.get().declared_refs.insert(ctx.arena, *ident, ());
     }
 
+    #[inline]
+    fn on_declaration_statement(_: &DeclarationStatement, node: &'ast StatementNode<'ast>, ctx: &mut Self::Context) {
+        let declaration = match node.item {
+            Statement::Declaration(ref declaration) => declaration,
+            _ => return,
+        };
+
+        if declaration.kind != DeclarationKind::Var {
+            return;
+        }
+
+        for declarator in &declaration.declarators {
+            hoist(&declarator.id, ctx);
+        }
+    }
+
+    #[inline]
+    fn on_function_statement(function: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>, ctx: &mut Self::Context) {
+        if ctx.block_functions.binary_search(&(node.start, node.end)).is_err() {
+            return;
+        }
+
+        ctx.pending.push((function.name.0.item, ctx.current.get()));
+    }
+
     #[inline]
     fn on_this_expression(_: &ExpressionNode<'ast>, ctx: &mut Self::Context) {
-        ctx.current.get().used_this.set(true);
+        ctx.current.get().function_scope().used_this.set(true);
     }
 
     #[inline]
@@ -150,7 +269,141 @@ impl<'ast> StaticVisitor<'ast> for ScopeAnalizer {
         dv.on_leave_scope.push(Self::on_leave_scope);
         dv.on_reference_use.push(Self::on_reference_use);
         dv.on_reference_declaration.push(Self::on_reference_declaration);
+        dv.on_declaration_statement.push(Self::on_declaration_statement);
+        dv.on_function_statement.push(Self::on_function_statement);
+        dv.on_this_expression.push(Self::on_this_expression);
+    }
+}
+
+/// Declare the names bound by a `var` pattern in the closest function scope.
+fn hoist<'ast>(pattern: &'ast Loc<Pattern<'ast>>, ctx: &mut ScopeContext<'ast>) {
+    match pattern.item {
+        Pattern::Identifier(ref ident) => {
+            let mut scope = ctx.current.get();
+
+            while scope.kind != ScopeKind::Function {
+                if scope.kind == ScopeKind::Catch && scope.declared_refs.contains_key(*ident) {
+                    scope.var_redeclared_refs.insert(ctx.arena, *ident, ());
+                }
+
+                scope = scope.parent.expect("Root scope is a function scope");
+            }
+
+            scope.declared_refs.insert(ctx.arena, *ident, ());
+            ctx.hoisted.insert(ident as *const Identifier as usize);
+        },
+        Pattern::ArrayPattern { ref elements } => {
+            for element in elements {
+                hoist(element, ctx);
+            }
+        },
+        Pattern::AssignmentPattern { ref left, .. } => hoist(left, ctx),
+        _ => {},
+    }
+}
+
+/// Uses of the bindings that every function, other than an arrow function,
+/// provides to its parameters and body. Arrow functions take them from the
+/// enclosing function instead, see `function_bindings`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct FunctionBindings {
+    pub this: bool,
+    pub arguments: bool,
+    pub new_target: bool,
+
+    /// `super`, which only methods provide
+    pub super_: bool,
+}
+
+impl FunctionBindings {
+    /// Check whether any of the bindings is used.
+    #[inline]
+    pub fn any(&self) -> bool {
+        self.this || self.arguments || self.new_target || self.super_
+    }
+}
+
+/// Find which of the `FunctionBindings` the `params` and `body` of a function
+/// or arrow function use, including from arrow functions nested in them,
+/// but not from nested functions which provide their own.
+pub fn function_bindings<'ast, B>(params: &'ast PatternList<'ast>, body: &'ast B) -> FunctionBindings where
+    B: Visitable<'ast>,
+{
+    let mut ctx = BindingsContext {
+        scopes: Vec::new(),
+        bindings: FunctionBindings::default(),
+    };
+
+    params.traverse(&BindingsAnalyzer, &mut ctx);
+    body.traverse(&BindingsAnalyzer, &mut ctx);
+
+    ctx.bindings
+}
+
+struct BindingsContext {
+    /// Kinds of the scopes entered so far
+    scopes: Vec<ScopeKind>,
+    bindings: FunctionBindings,
+}
+
+impl BindingsContext {
+    #[inline]
+    fn in_function(&self) -> bool {
+        self.scopes.contains(&ScopeKind::Function)
+    }
+}
+
+struct BindingsAnalyzer;
+
+impl<'ast> StaticVisitor<'ast> for BindingsAnalyzer {
+    type Context = BindingsContext;
+
+    #[inline]
+    fn on_enter_scope(kind: ScopeKind, ctx: &mut BindingsContext) {
+        ctx.scopes.push(kind);
+    }
+
+    #[inline]
+    fn on_leave_scope(ctx: &mut BindingsContext) {
+        ctx.scopes.pop();
+    }
+
+    #[inline]
+    fn on_this_expression(_: &ExpressionNode<'ast>, ctx: &mut BindingsContext) {
+        if !ctx.in_function() {
+            ctx.bindings.this = true;
+        }
+    }
+
+    #[inline]
+    fn on_reference_use(ident: &Identifier<'ast>, ctx: &mut BindingsContext) {
+        if *ident == "arguments" && !ctx.in_function() {
+            ctx.bindings.arguments = true;
+        }
+    }
+
+    #[inline]
+    fn on_meta_property(_: &MetaPropertyExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut BindingsContext) {
+        if !ctx.in_function() {
+            ctx.bindings.new_target = true;
+        }
+    }
+
+    #[inline]
+    fn on_super_expression(_: &'ast ExpressionNode<'ast>, ctx: &mut BindingsContext) {
+        if !ctx.in_function() {
+            ctx.bindings.super_ = true;
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, BindingsContext>) {
+        dv.on_enter_scope.push(Self::on_enter_scope);
+        dv.on_leave_scope.push(Self::on_leave_scope);
         dv.on_this_expression.push(Self::on_this_expression);
+        dv.on_reference_use.push(Self::on_reference_use);
+        dv.on_meta_property.push(Self::on_meta_property);
+        dv.on_super_expression.push(Self::on_super_expression);
     }
 }
 
@@ -159,6 +412,15 @@ mod test {
     use super::*;
     use ratel::parse;
 
+    #[test]
+    fn scope_analysis_is_deterministic() {
+        let source = "var a = 1; function f(b) { let c = a + b; { let d; } return () => this; } class E { g() { h; } }";
+        let first = parse(source).unwrap();
+        let second = parse(source).unwrap();
+
+        assert_eq!(format!("{:?}", analyze(&first)), format!("{:?}", analyze(&second)));
+    }
+
     #[test]
     fn scope_analysis() {
         let module = parse("function foo(bar) { doge; { moon; } return 10; }").unwrap();
@@ -184,4 +446,136 @@ mod test {
         assert_eq!(moon.declared_refs.is_empty(), true);
         assert_eq!(moon.children.as_list().is_empty(), true);
     }
+
+    #[test]
+    fn var_is_hoisted_to_function_scope() {
+        let module = parse("{ var [a, b = 1] = c; let d; }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.declared_refs.contains_key("a"), true);
+        assert_eq!(root.declared_refs.contains_key("b"), true);
+        assert_eq!(root.declared_refs.contains_key("d"), false);
+
+        let block = *root.children.as_list().only_element().unwrap();
+
+        assert_eq!(block.kind, ScopeKind::Block);
+        assert_eq!(block.declared_refs.contains_key("a"), false);
+        assert_eq!(block.declared_refs.contains_key("d"), true);
+    }
+
+    #[test]
+    fn catch_scope() {
+        let module = parse("try {} catch (err) { var err = 1, foo; let bar; }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.declared_refs.contains_key("err"), true);
+        assert_eq!(root.declared_refs.contains_key("foo"), true);
+
+        let mut children = root.children.as_list().iter();
+        let _try = children.next().unwrap();
+        let catch = *children.next().unwrap();
+
+        assert_eq!(catch.kind, ScopeKind::Catch);
+        assert_eq!(catch.parent, Some(root));
+        assert_eq!(catch.declared_refs.contains_key("err"), true);
+        assert_eq!(catch.declared_refs.contains_key("foo"), false);
+        assert_eq!(catch.var_redeclared_refs.contains_key("err"), true);
+        assert_eq!(catch.var_redeclared_refs.contains_key("foo"), false);
+
+        let body = *catch.children.as_list().only_element().unwrap();
+
+        assert_eq!(body.kind, ScopeKind::Block);
+        assert_eq!(body.declared_refs.contains_key("bar"), true);
+        assert_eq!(body.declared_refs.contains_key("err"), false);
+    }
+
+    #[test]
+    fn block_function_is_hoisted_in_sloppy_mode() {
+        let module = parse("{ function foo() {} } { let bar; { function bar() {} } }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.declared_refs.contains_key("foo"), true);
+        assert_eq!(root.declared_refs.contains_key("bar"), false);
+
+        let block = *root.children.as_list().iter().next().unwrap();
+
+        assert_eq!(block.declared_refs.contains_key("foo"), true);
+    }
+
+    #[test]
+    fn block_function_is_not_hoisted_past_later_lexical_declaration() {
+        let module = parse("{ { function bar() {} } let bar; } { { function baz() {} } class baz {} }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.declared_refs.contains_key("bar"), false);
+        assert_eq!(root.declared_refs.contains_key("baz"), false);
+    }
+
+    #[test]
+    fn block_function_is_not_hoisted_in_strict_mode() {
+        let module = parse("'use strict'; { function foo() {} }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.declared_refs.contains_key("foo"), false);
+    }
+
+    #[test]
+    fn this_and_arguments_belong_to_function_scopes() {
+        let module = parse("this; function f() { if (a) { () => arguments; } } class A { m() { return () => () => this; } }").unwrap();
+        let root = analyze(&module);
+
+        assert_eq!(root.used_this.get(), true);
+        assert_eq!(root.used_arguments.get(), false);
+
+        let mut children = root.children.as_list().iter();
+        let f = *children.next().unwrap();
+        let block = *f.children.as_list().only_element().unwrap();
+
+        assert_eq!(f.used_arguments.get(), true);
+        assert_eq!(f.used_this.get(), false);
+        assert_eq!(block.used_arguments.get(), false);
+
+        let m = children.copied().find(|scope| scope.kind == ScopeKind::Function).unwrap();
+
+        assert_eq!(m.used_this.get(), true);
+        assert_eq!(m.used_arguments.get(), false);
+    }
+
+    #[test]
+    fn this_in_nested_functions() {
+        let module = parse("function f() { return function () { this; }; }").unwrap();
+        let root = analyze(&module);
+        let f = *root.children.as_list().only_element().unwrap();
+        let inner = *f.children.as_list().only_element().unwrap();
+
+        assert_eq!(root.used_this.get(), false);
+        assert_eq!(f.used_this.get(), false);
+        assert_eq!(inner.used_this.get(), true);
+    }
+
+    fn bindings(source: &str) -> FunctionBindings {
+        let module = parse(source).unwrap();
+
+        match module.body().only_element().unwrap().item {
+            Statement::Function(ref function) => function_bindings(&function.params, &function.body.body),
+            _ => panic!("Expected a function statement"),
+        }
+    }
+
+    #[test]
+    fn function_bindings_are_found() {
+        assert_eq!(bindings("function f() { this; }").this, true);
+        assert_eq!(bindings("function f(a = arguments) {}").arguments, true);
+        assert_eq!(bindings("function f() { new.target; }").new_target, true);
+        assert_eq!(bindings("function f() { return () => this; }").this, true);
+        assert_eq!(bindings("function f() { { arguments; } }").arguments, true);
+    }
+
+    #[test]
+    fn function_bindings_are_not_found_in_nested_functions() {
+        assert_eq!(bindings("function f() { this.foo; }").any(), true);
+        assert_eq!(bindings("function f() { return function () { this; arguments; new.target; }; }").any(), false);
+        assert_eq!(bindings("function f() { class A { foo() { this; } } }").any(), false);
+        assert_eq!(bindings("function f(a) { a.arguments; }").any(), false);
+    }
 }
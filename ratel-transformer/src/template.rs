@@ -0,0 +1,373 @@
+use std::char;
+
+use ratel::ast::{Expression, ExpressionNode, Literal, StringLiteral, OperatorKind};
+use ratel::ast::expression::{BinaryExpression, TemplateLiteral};
+use ratel_visitor::{StaticVisitor, DynamicVisitor};
+
+use Transformer;
+
+/// Codemod converting chains of `+` concatenating strings into template
+/// literals, such as `'a' + b + 'c'` into `` `a${b}c` ``. Operands added
+/// before the first string stay added together, `a + b + 'c'` becomes
+/// `` `${a + b}c` ``.
+///
+/// Operands are evaluated in the same order, but template literals convert
+/// them to strings with `toString` first rather than `valueOf`, which only
+/// matters for objects giving different results for the two.
+pub struct ConcatToTemplate;
+
+impl<'ast> StaticVisitor<'ast> for ConcatToTemplate {
+    type Context = Transformer<'ast>;
+
+    fn on_binary_expression(_: &BinaryExpression, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Some(template) = concat_to_template(ptr, t) {
+            t.swap(ptr, template);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_binary_expression.push(ConcatToTemplate::on_binary_expression);
+    }
+}
+
+/// Codemod converting template literals into concatenations of strings,
+/// for targets without template literals. Expressions in the template are
+/// parenthesized as needed by the generated code.
+pub struct TemplateToConcat;
+
+impl<'ast> StaticVisitor<'ast> for TemplateToConcat {
+    type Context = Transformer<'ast>;
+
+    fn on_template_literal(_: &TemplateLiteral, ptr: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) {
+        if let Some(concat) = template_to_concat(ptr, t) {
+            t.swap(ptr, concat);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Transformer<'ast>>) {
+        dv.on_template_literal.push(TemplateToConcat::on_template_literal);
+    }
+}
+
+enum Part<'ast> {
+    /// Raw contents of a template literal
+    Text(String),
+    Expression(ExpressionNode<'ast>),
+}
+
+fn concat_to_template<'ast>(node: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> Option<TemplateLiteral<'ast>> {
+    // Binary expressions of the chain, innermost first
+    let mut chain = Vec::new();
+    let mut current = node.get();
+
+    while let Expression::Binary(ref binary) = current.item {
+        if binary.operator != OperatorKind::Addition {
+            break;
+        }
+
+        chain.push(binary);
+        current = binary.left.get();
+    }
+
+    chain.reverse();
+
+    let first = *chain.first()?;
+    let mut operands = vec![&first.left];
+
+    operands.extend(chain.iter().map(|binary| &binary.right));
+
+    // After a string, every `+` concatenates strings
+    let string = operands.iter().position(|operand| {
+        matches!(operand.item, Expression::Literal(Literal::String(_)) | Expression::Template(_))
+    })?;
+
+    let mut parts = Vec::new();
+
+    if string > 0 {
+        parts.push(Part::Expression(chain[string - 1].left));
+    }
+
+    for &operand in &operands[string..] {
+        // The visitor goes on into the original chain, so nested chains
+        // have to be converted here
+        let operand = match concat_to_template(operand, t) {
+            Some(template) => t.alloc_as_loc(*operand, template),
+            None => *operand,
+        };
+
+        match operand.item {
+            Expression::Literal(Literal::String(StringLiteral { raw, .. })) => {
+                parts.push(Part::Text(string_to_quasi(&raw[1..raw.len() - 1])?));
+            },
+            Expression::Template(template) => {
+                let mut quasis = template.quasis.iter();
+
+                if let Some(quasi) = quasis.next() {
+                    parts.push(Part::Text(quasi.item.to_owned()));
+                }
+
+                for (expression, quasi) in template.expressions.iter().zip(quasis) {
+                    parts.push(Part::Expression(*expression));
+                    parts.push(Part::Text(quasi.item.to_owned()));
+                }
+            },
+            _ => parts.push(Part::Expression(operand)),
+        }
+    }
+
+    // Leave concatenations of literals alone
+    if !parts.iter().any(|part| matches!(*part, Part::Expression(_))) {
+        return None;
+    }
+
+    let mut quasis = Vec::new();
+    let mut expressions = Vec::new();
+    let mut text = String::new();
+
+    for part in parts {
+        match part {
+            Part::Text(part) => text.push_str(&part),
+            Part::Expression(expression) => {
+                quasis.push(t.alloc(t.arena.alloc_string(text)));
+                expressions.push(expression);
+                text = String::new();
+            },
+        }
+    }
+
+    quasis.push(t.alloc(t.arena.alloc_string(text)));
+
+    Some(TemplateLiteral {
+        expressions: t.list(expressions),
+        quasis: t.list(quasis),
+    })
+}
+
+fn template_to_concat<'ast>(node: &'ast ExpressionNode<'ast>, t: &mut Transformer<'ast>) -> Option<Expression<'ast>> {
+    let template = match node.item {
+        Expression::Template(template) => template,
+        _ => return None,
+    };
+
+    let mut quasis = template.quasis.iter().map(|quasi| quasi.item);
+    let mut expressions = template.expressions.iter().map(|expression| {
+        // Same as in `concat_to_template`, nested templates have to be
+        // converted here
+        match template_to_concat(expression, t) {
+            Some(concat) => t.alloc_as_loc(*expression, concat),
+            None => *expression,
+        }
+    }).collect::<Vec<_>>().into_iter();
+
+    let head = quasis.next()?;
+    let quasis: Vec<_> = quasis.collect();
+
+    let mut concat = match (head, expressions.next()) {
+        (head, None) => return Some(string(head, t)),
+
+        // `a + 'b'` concatenates strings already, `'' + a` is needed
+        // for `${a}${b}`
+        ("", Some(expression)) if !quasis[0].is_empty() => expression,
+        (head, Some(expression)) => add(t.alloc_as_loc(*node, string(head, t)), expression, t),
+    };
+
+    for quasi in quasis {
+        if !quasi.is_empty() {
+            let quasi = t.alloc_as_loc(*node, string(quasi, t));
+
+            concat = add(concat, quasi, t);
+        }
+
+        if let Some(expression) = expressions.next() {
+            concat = add(concat, expression, t);
+        }
+    }
+
+    Some(concat.item)
+}
+
+fn add<'ast>(left: ExpressionNode<'ast>, right: ExpressionNode<'ast>, t: &Transformer<'ast>) -> ExpressionNode<'ast> {
+    t.alloc_as_loc(left, BinaryExpression {
+        operator: OperatorKind::Addition,
+        left,
+        right,
+    })
+}
+
+/// String literal with the value of the raw contents of a template literal.
+fn string<'ast>(quasi: &str, t: &Transformer<'ast>) -> Expression<'ast> {
+    let raw = t.arena.alloc_string(format!("'{}'", quasi_to_string(quasi)));
+
+    Expression::Literal(Literal::String(t.string_literal(raw)))
+}
+
+/// Raw contents of a template literal for the raw contents of a string
+/// literal, or `None` if it has escapes not allowed in templates.
+fn string_to_quasi(raw: &str) -> Option<String> {
+    let mut quasi = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => {
+                let escaped = chars.next()?;
+
+                // Only `\0` is allowed, legacy octal escapes aren't
+                if escaped.is_ascii_digit() && (escaped != '0' || chars.peek().is_some_and(|next| next.is_ascii_digit())) {
+                    return None;
+                }
+
+                quasi.push('\\');
+                quasi.push(escaped);
+            },
+            '`' => quasi.push_str("\\`"),
+            '$' if chars.peek() == Some(&'{') => quasi.push_str("\\$"),
+            character => quasi.push(character),
+        }
+    }
+
+    Some(quasi)
+}
+
+/// Raw contents of a single quoted string literal for the raw contents of
+/// a template literal. Line terminators become escapes, and so do code
+/// point escapes, which only exist since ES2015.
+fn quasi_to_string(quasi: &str) -> String {
+    let mut raw = String::with_capacity(quasi.len());
+    let mut chars = quasi.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => match chars.next() {
+                Some('u') if chars.peek() == Some(&'{') => {
+                    chars.next();
+
+                    let digits: String = chars.by_ref().take_while(|&character| character != '}').collect();
+                    let code_point = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32).unwrap_or('\u{FFFD}');
+
+                    for unit in code_point.encode_utf16(&mut [0; 2]) {
+                        raw.push_str(&format!("\\u{:04X}", unit));
+                    }
+                },
+                Some('\r') => {
+                    // Line continuation
+                    raw.push_str("\\\r");
+
+                    if chars.peek() == Some(&'\n') {
+                        raw.push(chars.next().unwrap());
+                    }
+                },
+                Some(escaped) => {
+                    raw.push('\\');
+                    raw.push(escaped);
+                },
+                None => raw.push('\\'),
+            },
+            '\'' => raw.push_str("\\'"),
+            '\r' => {
+                // Both `\r\n` and `\r` are read as `\n` in templates
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+
+                raw.push_str("\\n");
+            },
+            '\n' => raw.push_str("\\n"),
+            '\u{2028}' => raw.push_str("\\u2028"),
+            '\u{2029}' => raw.push_str("\\u2029"),
+            character => raw.push(character),
+        }
+    }
+
+    raw
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use pass::{Pass, Visit};
+    use ratel_visitor::Visitor;
+
+    fn assert_convert<V>(visitor: V, source: &str, expected: &str) where
+        V: for<'ast> Visitor<'ast, Context = Transformer<'ast>>,
+    {
+        let module = parse(source).unwrap();
+
+        Visit(visitor).run(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn concat_to_template() {
+        assert_convert(ConcatToTemplate, "'a' + b + \"c\";", "`a${b}c`;");
+        assert_convert(ConcatToTemplate, "a + 'b';", "`${a}b`;");
+        assert_convert(ConcatToTemplate, "a + b + 'c' + d;", "`${a + b}c${d}`;");
+        assert_convert(ConcatToTemplate, "'a' + b - c;", "`a${b}` - c;");
+    }
+
+    #[test]
+    fn concat_to_template_operands() {
+        assert_convert(ConcatToTemplate, "'a' + (b ? 1 : 2);", "`a${b ? 1 : 2}`;");
+        assert_convert(ConcatToTemplate, "'a' + (b = 1);", "`a${b = 1}`;");
+        assert_convert(ConcatToTemplate, "'a' + ('b' + c);", "`ab${c}`;");
+        assert_convert(ConcatToTemplate, "`a${b}` + 'c' + d;", "`a${b}c${d}`;");
+        assert_convert(ConcatToTemplate, "f('a' + b, c + 'd');", "f(`a${b}`, `${c}d`);");
+    }
+
+    #[test]
+    fn concat_to_template_escapes() {
+        assert_convert(ConcatToTemplate, "'`${' + a + '\\n\\0';", "`\\`\\${${a}\\n\\0`;");
+    }
+
+    #[test]
+    fn concat_not_converted_to_template() {
+        let sources = [
+            "a + b;",
+            "1 + 2 + a;",
+            "'a' + 'b';",
+            "'\\01' + a;",
+            "a - 'b';",
+        ];
+
+        for source in sources.iter() {
+            assert_convert(ConcatToTemplate, source, source);
+        }
+    }
+
+    #[test]
+    fn template_to_concat() {
+        assert_convert(TemplateToConcat, "`a${b}c`;", "'a' + b + 'c';");
+        assert_convert(TemplateToConcat, "`${a}${b}`;", "'' + a + b;");
+        assert_convert(TemplateToConcat, "`${a}b${c}`;", "a + 'b' + c;");
+        assert_convert(TemplateToConcat, "`a`;", "'a';");
+        assert_convert(TemplateToConcat, "`a${`b${c}`}`;", "'a' + ('b' + c);");
+    }
+
+    #[test]
+    fn template_to_concat_operands() {
+        assert_convert(TemplateToConcat, "`a${b ? 1 : 2}`;", "'a' + (b ? 1 : 2);");
+        assert_convert(TemplateToConcat, "`a${b + c}`;", "'a' + (b + c);");
+        assert_convert(TemplateToConcat, "`a${b}`.length;", "('a' + b).length;");
+        assert_convert(TemplateToConcat, "foo`a${b}`;", "foo`a${b}`;");
+    }
+
+    #[test]
+    fn template_to_concat_escapes() {
+        assert_convert(TemplateToConcat, "`it's\nfine\\``;", "'it\\'s\\nfine\\`';");
+        assert_convert(TemplateToConcat, "`\\u{1F600}${a}`;", "'\\uD83D\\uDE00' + a;");
+    }
+
+    #[test]
+    fn round_trip() {
+        let module = parse("'a' + b + 'c' + (d ? 'e' : f);").unwrap();
+
+        Visit(ConcatToTemplate).run(&module);
+        Visit(TemplateToConcat).run(&module);
+
+        assert_eq!(module.body(), parse("'a' + b + 'c' + (d ? 'e' : f);").unwrap().body());
+    }
+}
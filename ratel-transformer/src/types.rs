@@ -0,0 +1,156 @@
+use ratel::ast::{Expression, Literal, OperatorKind};
+use ratel::ast::expression::{BinaryExpression, PrefixExpression, ConditionalExpression};
+
+/// Type of the value of an expression, as given by `typeof`, except for
+/// `null` and functions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Type {
+    Undefined,
+    Null,
+    Boolean,
+    Number,
+    String,
+
+    /// Objects, including arrays and functions
+    Object,
+}
+
+/// Infer the type of the value of an expression from its syntax alone,
+/// returns `None` if it can't be known without knowing the values of
+/// variables or the results of calls. The identifier `undefined` is taken
+/// to be the global binding.
+pub fn infer(expression: &Expression) -> Option<Type> {
+    match *expression {
+        Expression::Literal(ref literal) => Some(literal_type(literal)),
+        Expression::Identifier("undefined") => Some(Type::Undefined),
+        Expression::Template(_) => Some(Type::String),
+        Expression::Array(_)    |
+        Expression::Object(_)   |
+        Expression::Function(_) |
+        Expression::Arrow(_)    |
+        Expression::Class(_) => Some(Type::Object),
+        Expression::Sequence(ref sequence) => sequence.body.iter().last().and_then(|last| infer(&last.item)),
+        Expression::Prefix(PrefixExpression { operator, .. }) => match operator {
+            OperatorKind::LogicalNot | OperatorKind::Delete => Some(Type::Boolean),
+            OperatorKind::Typeof => Some(Type::String),
+            OperatorKind::Void => Some(Type::Undefined),
+            OperatorKind::New => Some(Type::Object),
+
+            // Without BigInt, numeric operators always produce numbers
+            OperatorKind::Addition    |
+            OperatorKind::Subtraction |
+            OperatorKind::BitwiseNot  |
+            OperatorKind::Increment   |
+            OperatorKind::Decrement   => Some(Type::Number),
+            _ => None,
+        },
+        Expression::Postfix(_) => Some(Type::Number),
+        Expression::Binary(BinaryExpression { operator, ref left, ref right }) => {
+            use self::OperatorKind::*;
+
+            match operator {
+                Equality | Inequality | StrictEquality | StrictInequality |
+                Lesser | LesserEquals | Greater | GreaterEquals |
+                Instanceof | In => Some(Type::Boolean),
+
+                Subtraction | Multiplication | Division | Remainder | Exponent |
+                BitShiftLeft | BitShiftRight | UBitShiftRight |
+                BitwiseAnd | BitwiseXor | BitwiseOr |
+                SubtractAssign | MultiplyAssign | DivideAssign | RemainderAssign | ExponentAssign |
+                BSLAssign | BSRAssign | UBSRAssign |
+                BitAndAssign | BitXorAssign | BitOrAssign => Some(Type::Number),
+
+                Addition => {
+                    let left = infer(&left.item);
+                    let right = infer(&right.item);
+
+                    if left == Some(Type::String) || right == Some(Type::String) {
+                        return Some(Type::String);
+                    }
+
+                    // Primitives other than strings are added as numbers
+                    match (left?, right?) {
+                        (Type::Object, _) | (_, Type::Object) => None,
+                        _ => Some(Type::Number),
+                    }
+                },
+                Assign => infer(&right.item),
+
+                // Either side can be the result
+                LogicalAnd | LogicalOr => same(infer(&left.item)?, infer(&right.item)?),
+                _ => None,
+            }
+        },
+        Expression::Conditional(ConditionalExpression { ref consequent, ref alternate, .. }) => {
+            same(infer(&consequent.item)?, infer(&alternate.item)?)
+        },
+        _ => None,
+    }
+}
+
+fn literal_type(literal: &Literal) -> Type {
+    match *literal {
+        Literal::Null => Type::Null,
+        Literal::Boolean(_) => Type::Boolean,
+        Literal::Number(_) => Type::Number,
+        Literal::String(_) => Type::String,
+        Literal::RegEx(_) => Type::Object,
+    }
+}
+
+#[inline]
+fn same(left: Type, right: Type) -> Option<Type> {
+    match left == right {
+        true => Some(left),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+    use ratel::ast::Statement;
+
+    fn infer_source(source: &str) -> Option<Type> {
+        let module = parse(source).unwrap();
+
+        match module.body().only_element().unwrap().item {
+            Statement::Expression(expression) => infer(&expression.item),
+            _ => panic!("Expected an expression statement"),
+        }
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(infer_source("undefined;"), Some(Type::Undefined));
+        assert_eq!(infer_source("null;"), Some(Type::Null));
+        assert_eq!(infer_source("true;"), Some(Type::Boolean));
+        assert_eq!(infer_source("0x10;"), Some(Type::Number));
+        assert_eq!(infer_source("'a';"), Some(Type::String));
+        assert_eq!(infer_source("`a${b}`;"), Some(Type::String));
+        assert_eq!(infer_source("/a/;"), Some(Type::Object));
+        assert_eq!(infer_source("[];"), Some(Type::Object));
+        assert_eq!(infer_source("a;"), None);
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(infer_source("typeof a;"), Some(Type::String));
+        assert_eq!(infer_source("!a;"), Some(Type::Boolean));
+        assert_eq!(infer_source("-a;"), Some(Type::Number));
+        assert_eq!(infer_source("a++;"), Some(Type::Number));
+        assert_eq!(infer_source("new A();"), Some(Type::Object));
+        assert_eq!(infer_source("a * b;"), Some(Type::Number));
+        assert_eq!(infer_source("a < b;"), Some(Type::Boolean));
+        assert_eq!(infer_source("a + 'b';"), Some(Type::String));
+        assert_eq!(infer_source("1 + true;"), Some(Type::Number));
+        assert_eq!(infer_source("1 + a;"), None);
+        assert_eq!(infer_source("1 + [];"), None);
+        assert_eq!(infer_source("a ? 1 : 2;"), Some(Type::Number));
+        assert_eq!(infer_source("a ? 1 : '2';"), None);
+        assert_eq!(infer_source("a = 'b';"), Some(Type::String));
+        assert_eq!(infer_source("a || 'b';"), None);
+        assert_eq!(infer_source("(a, 1);"), Some(Type::Number));
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+
+use ratel::Module;
+use ratel::diagnostic::{Diagnostic, DiagnosticsSink, Severity};
+use ratel::ast::{Expression, ExpressionNode, StatementNode, Identifier, OperatorKind, Property};
+use ratel::ast::expression::{ObjectExpression, PrefixExpression, BinaryExpression};
+use ratel::ast::statement::{ForInStatement, ForOfStatement, ForInit};
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, ScopeKind};
+use toolshed::list::ListIter;
+
+use env::Environment;
+use scope::{self, Scope};
+
+/// A reference which is neither declared by the code nor a global of the
+/// environment, see `undefined_references`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct UndefinedReference<'ast> {
+    pub start: u32,
+    pub end: u32,
+    pub name: &'ast str,
+}
+
+impl<'ast> UndefinedReference<'ast> {
+    /// Describe the reference as a warning of the `undefined-reference` lint.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, "undefined-reference", format!("`{}` is not defined", self.name))
+            .with_primary(self.start as usize, self.end as usize, "neither declared nor a global of the environment")
+    }
+}
+
+/// Find the references used by the module which resolve to none of its
+/// declarations, see `scope::analyze`, nor to a global of the `env`, in
+/// source order. Operands of `typeof`, which is how code checks whether
+/// a global is defined, aren't reported.
+pub fn undefined_references<'ast>(module: &'ast Module<'ast>, env: &Environment) -> Vec<UndefinedReference<'ast>> {
+    let root = scope::analyze(module);
+
+    let mut ctx = UndefinedContext {
+        scopes: vec![(root, root.children.as_list().iter())],
+        typeof_operand: None,
+        references: Vec::new(),
+    };
+
+    module.traverse(&UndefinedCollector, &mut ctx);
+
+    ctx.references.retain(|reference| !env.defines(reference.name));
+    ctx.references.sort_by_key(|reference| reference.start);
+    ctx.references
+}
+
+/// Same as `undefined_references`, reporting the references to the `sink`
+/// as a single batch of warnings.
+pub fn undefined_references_with_sink<'ast, S>(module: &'ast Module<'ast>, env: &Environment, sink: &S) -> Vec<UndefinedReference<'ast>> where
+    S: DiagnosticsSink + ?Sized,
+{
+    let references = undefined_references(module, env);
+
+    if !references.is_empty() {
+        let batch: Vec<_> = references.iter().map(UndefinedReference::to_diagnostic).collect();
+
+        sink.report(&batch);
+    }
+
+    references
+}
+
+/// An assignment to an undefined reference, see `implicit_globals`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ImplicitGlobal<'ast> {
+    pub start: u32,
+    pub end: u32,
+    pub name: &'ast str,
+
+    /// Whether `let` can be inserted before the name to declare it, as in
+    /// `a = 1;` or `for (a of b)`, and this is the first such assignment
+    /// to the name
+    pub declarable: bool,
+}
+
+impl<'ast> ImplicitGlobal<'ast> {
+    /// Describe the assignment as a warning of the `implicit-global` lint,
+    /// suggesting to declare the name with `let` if it's `declarable`.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let (start, end) = (self.start as usize, self.end as usize);
+        let diagnostic = Diagnostic::new(Severity::Warning, "implicit-global", format!("Assignment to undeclared `{}`", self.name))
+            .with_primary(start, end, "creates a global in sloppy mode code, throws in strict mode code");
+
+        match self.declarable {
+            true  => diagnostic.with_suggestion(start, start, "let ", format!("declare `{}` with `let`", self.name)),
+            false => diagnostic,
+        }
+    }
+}
+
+/// Find the assignments to undefined references, see `undefined_references`,
+/// in source order. In sloppy mode code they implicitly create globals,
+/// which is usually a missing declaration. Only plain assignments and the
+/// targets of `for`-`in` and `for`-`of` loops are reported, compound
+/// assignments and updates read the name first, which throws instead.
+///
+/// The `source` of the module tells parenthesized assignments, which
+/// their spans leave out the parentheses of, from declarable ones.
+pub fn implicit_globals<'ast>(module: &'ast Module<'ast>, source: &str, env: &Environment) -> Vec<ImplicitGlobal<'ast>> {
+    let mut targets = HashMap::new();
+
+    module.traverse(&TargetCollector, &mut targets);
+
+    let mut declared = Vec::new();
+
+    undefined_references(module, env).into_iter().filter_map(|reference| {
+        let declarable = match *targets.get(&(reference.start, reference.end))? {
+            Target::Nested => false,
+            Target::Statement => !source[..reference.start as usize].trim_end().ends_with('('),
+            Target::Loop => true,
+        } && !declared.contains(&reference.name);
+
+        if declarable {
+            declared.push(reference.name);
+        }
+
+        Some(ImplicitGlobal {
+            start: reference.start,
+            end: reference.end,
+            name: reference.name,
+            declarable,
+        })
+    }).collect()
+}
+
+/// Same as `implicit_globals`, reporting the assignments to the `sink` as
+/// a single batch of warnings.
+pub fn implicit_globals_with_sink<'ast, S>(module: &'ast Module<'ast>, source: &str, env: &Environment, sink: &S) -> Vec<ImplicitGlobal<'ast>> where
+    S: DiagnosticsSink + ?Sized,
+{
+    let globals = implicit_globals(module, source, env);
+
+    if !globals.is_empty() {
+        let batch: Vec<_> = globals.iter().map(ImplicitGlobal::to_diagnostic).collect();
+
+        sink.report(&batch);
+    }
+
+    globals
+}
+
+/// Where an identifier assigned to is, which tells whether `let` can be
+/// inserted before it
+#[derive(Clone, Copy)]
+enum Target {
+    Nested,
+    Statement,
+    Loop,
+}
+
+/// Spans of the identifiers assigned to
+type Targets = HashMap<(u32, u32), Target>;
+
+struct TargetCollector;
+
+impl TargetCollector {
+    #[inline]
+    fn target(target: &ExpressionNode, kind: Target, targets: &mut Targets) {
+        if let Expression::Identifier(_) = target.item {
+            let entry = targets.entry((target.start, target.end)).or_insert(kind);
+
+            // The assignment of an expression statement is visited twice
+            if let Target::Nested = *entry {
+                *entry = kind;
+            }
+        }
+    }
+
+    #[inline]
+    fn loop_target(left: &ForInit, targets: &mut Targets) {
+        if let ForInit::Expression(ref target) = *left {
+            TargetCollector::target(target, Target::Loop, targets);
+        }
+    }
+}
+
+impl<'ast> StaticVisitor<'ast> for TargetCollector {
+    type Context = Targets;
+
+    #[inline]
+    fn on_binary_expression(binary: &BinaryExpression<'ast>, _: &'ast ExpressionNode<'ast>, targets: &mut Targets) {
+        if binary.operator == OperatorKind::Assign {
+            TargetCollector::target(&binary.left, Target::Nested, targets);
+        }
+    }
+
+    #[inline]
+    fn on_expression_statement(expression: &'ast ExpressionNode<'ast>, statement: &'ast StatementNode<'ast>, targets: &mut Targets) {
+        // Not `(a = 1);`, nor `a = 1, b = 2;`
+        if let Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, ref left, .. }) = expression.item {
+            if left.start == statement.start {
+                TargetCollector::target(left, Target::Statement, targets);
+            }
+        }
+    }
+
+    #[inline]
+    fn on_for_in_statement(item: &ForInStatement, _: &'ast StatementNode<'ast>, targets: &mut Targets) {
+        TargetCollector::loop_target(&item.left.item, targets);
+    }
+
+    #[inline]
+    fn on_for_of_statement(item: &ForOfStatement, _: &'ast StatementNode<'ast>, targets: &mut Targets) {
+        TargetCollector::loop_target(&item.left.item, targets);
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, Targets>) {
+        dv.on_binary_expression.push(Self::on_binary_expression);
+        dv.on_expression_statement.push(Self::on_expression_statement);
+        dv.on_for_in_statement.push(Self::on_for_in_statement);
+        dv.on_for_of_statement.push(Self::on_for_of_statement);
+    }
+}
+
+struct UndefinedContext<'ast> {
+    /// Scopes entered, the same the analysis made, with their children
+    /// left to enter
+    scopes: Vec<(&'ast Scope<'ast>, ListIter<'ast, &'ast Scope<'ast>>)>,
+
+    /// Address of the identifier of a `typeof` operand, to be skipped
+    typeof_operand: Option<usize>,
+
+    /// References not declared by the module
+    references: Vec<UndefinedReference<'ast>>,
+}
+
+impl<'ast> UndefinedContext<'ast> {
+    #[inline]
+    fn check(&mut self, name: Identifier<'ast>, start: u32, end: u32) {
+        let scope = self.scopes.last().expect("Root scope is never left").0;
+
+        if scope.declares(name) {
+            return;
+        }
+
+        // Declared by every function, other than the root scope
+        if name == "arguments" && scope.function_scope().parent.is_some() {
+            return;
+        }
+
+        self.references.push(UndefinedReference { start, end, name });
+    }
+}
+
+struct UndefinedCollector;
+
+impl<'ast> StaticVisitor<'ast> for UndefinedCollector {
+    type Context = UndefinedContext<'ast>;
+
+    #[inline]
+    fn on_enter_scope(_: ScopeKind, ctx: &mut UndefinedContext<'ast>) {
+        let child = {
+            let children = &mut ctx.scopes.last_mut().expect("Root scope is never left").1;
+
+            *children.next().expect("Scopes are entered as analyzed")
+        };
+
+        ctx.scopes.push((child, child.children.as_list().iter()));
+    }
+
+    #[inline]
+    fn on_leave_scope(ctx: &mut UndefinedContext<'ast>) {
+        ctx.scopes.pop();
+    }
+
+    #[inline]
+    fn on_prefix_expression(prefix: &PrefixExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut UndefinedContext<'ast>) {
+        if prefix.operator == OperatorKind::Typeof {
+            ctx.typeof_operand = Some(&prefix.operand as *const ExpressionNode as usize);
+        }
+    }
+
+    #[inline]
+    fn on_identifier_expression(ident: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, ctx: &mut UndefinedContext<'ast>) {
+        if ctx.typeof_operand == Some(node as *const ExpressionNode as usize) {
+            return;
+        }
+
+        ctx.check(ident, node.start, node.end);
+    }
+
+    #[inline]
+    fn on_object_expression(object: &ObjectExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut UndefinedContext<'ast>) {
+        // Shorthand properties use references without an identifier expression
+        for property in &object.body {
+            match property.item {
+                Property::Shorthand(name) => ctx.check(name, property.start, property.end),
+                Property::ShorthandDefault { name, .. } => ctx.check(name.item, name.start, name.end),
+                _ => {},
+            }
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, UndefinedContext<'ast>>) {
+        dv.on_enter_scope.push(Self::on_enter_scope);
+        dv.on_leave_scope.push(Self::on_leave_scope);
+        dv.on_prefix_expression.push(Self::on_prefix_expression);
+        dv.on_identifier_expression.push(Self::on_identifier_expression);
+        dv.on_object_expression.push(Self::on_object_expression);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    fn undefined(source: &str, env: &Environment) -> Vec<String> {
+        let module = parse(source).unwrap();
+
+        undefined_references(&module, env).iter().map(|reference| reference.name.to_owned()).collect()
+    }
+
+    #[test]
+    fn declared_references() {
+        let env = Environment::new();
+
+        assert_eq!(undefined("var a; a; f(); function f(b) { return a + b + c; }", &env), vec!["c"]);
+        assert_eq!(undefined("{ let a; } a;", &env), vec!["a"]);
+        assert_eq!(undefined("try {} catch (e) { e; } e;", &env), vec!["e"]);
+        assert_eq!(undefined("x = () => arguments; function g() { return arguments; }", &env), vec!["x", "arguments"]);
+        assert_eq!(undefined("({ a, b = c } = d);", &env), vec!["a", "b", "c", "d"]);
+        assert_eq!(undefined("({ a: b, c });", &env), vec!["b", "c"]);
+        assert_eq!(undefined("class A { m() { return A; } } new A; Math.max(B.c);", &env), vec!["B"]);
+    }
+
+    #[test]
+    fn environment_globals() {
+        let source = "window.x = process.env; require('a'); jQuery(document);";
+
+        assert_eq!(undefined(source, &Environment::new()), vec!["window", "process", "require", "jQuery", "document"]);
+        assert_eq!(undefined(source, &Environment::browser()), vec!["process", "require", "jQuery"]);
+        assert_eq!(undefined(source, &Environment::node()), vec!["window", "jQuery", "document"]);
+        assert_eq!(undefined(source, &Environment::browser().with_globals(&["process", "require", "jQuery"])), Vec::<String>::new());
+    }
+
+    #[test]
+    fn typeof_operands() {
+        assert_eq!(undefined("typeof window; typeof window.document; typeof (a);", &Environment::new()), vec!["window"]);
+    }
+
+    fn implicit(source: &str) -> Vec<(String, bool)> {
+        let module = parse(source).unwrap();
+
+        implicit_globals(&module, source, &Environment::browser()).iter().map(|global| (global.name.to_owned(), global.declarable)).collect()
+    }
+
+    #[test]
+    fn implicit_global_assignments() {
+        assert_eq!(implicit("var a; a = 1; b = 2; function f() { c = a; }"), vec![("b".to_owned(), true), ("c".to_owned(), true)]);
+        assert_eq!(implicit("a = 1; a = 2;"), vec![("a".to_owned(), true), ("a".to_owned(), false)]);
+        assert_eq!(implicit("f(a = 1); (b = 2); c = 3, d = 4;"), vec![
+            ("a".to_owned(), false),
+            ("b".to_owned(), false),
+            ("c".to_owned(), false),
+            ("d".to_owned(), false),
+        ]);
+        assert_eq!(implicit("for (a in b) {} for (c of d) {}"), vec![("a".to_owned(), true), ("c".to_owned(), true)]);
+    }
+
+    #[test]
+    fn not_implicit_globals() {
+        assert_eq!(implicit("let a; a = 1; window.b = 2; c.d = 3; e += 1; f++; g; location = '/';"), vec![]);
+        assert_eq!(implicit("function f(a) { a = 1; } try {} catch (e) { e = 1; }"), vec![]);
+    }
+
+    #[test]
+    fn implicit_global_diagnostics() {
+        let module = parse("x = 1;").unwrap();
+        let globals = implicit_globals(&module, "x = 1;", &Environment::new());
+        let diagnostic = globals[0].to_diagnostic();
+
+        assert_eq!(diagnostic.code, "implicit-global");
+        assert_eq!(diagnostic.message, "Assignment to undeclared `x`");
+        assert_eq!(diagnostic.primary().map(|label| (label.start, label.end)), Some((0, 1)));
+        assert_eq!(diagnostic.suggestions.iter().map(|fix| (fix.start, fix.end, &*fix.replacement)).collect::<Vec<_>>(), vec![(0, 0, "let ")]);
+
+        let module = parse("f(x = 1);").unwrap();
+
+        assert!(implicit_globals(&module, "f(x = 1);", &Environment::new())[0].to_diagnostic().suggestions.is_empty());
+    }
+
+    #[test]
+    fn diagnostics() {
+        let module = parse("var a = b;").unwrap();
+        let references = undefined_references(&module, &Environment::new());
+
+        assert_eq!(references, vec![UndefinedReference { start: 8, end: 9, name: "b" }]);
+
+        let diagnostic = references[0].to_diagnostic();
+
+        assert_eq!(diagnostic.code, "undefined-reference");
+        assert_eq!(diagnostic.message, "`b` is not defined");
+        assert_eq!(diagnostic.primary().map(|label| (label.start, label.end)), Some((8, 9)));
+    }
+
+    #[test]
+    fn reports_to_sink() {
+        use std::sync::Mutex;
+
+        /// Records the codes of each batch
+        struct Batches(Mutex<Vec<Vec<&'static str>>>);
+
+        impl DiagnosticsSink for Batches {
+            fn report(&self, batch: &[Diagnostic]) {
+                self.0.lock().unwrap().push(batch.iter().map(|d| d.code).collect());
+            }
+        }
+
+        let sink = Batches(Mutex::new(Vec::new()));
+        let module = parse("a = b;").unwrap();
+
+        undefined_references_with_sink(&module, &Environment::new(), &sink);
+        implicit_globals_with_sink(&module, "a = b;", &Environment::new(), &sink);
+        undefined_references_with_sink(&parse("var c;").unwrap(), &Environment::new(), &sink);
+
+        assert_eq!(*sink.0.lock().unwrap(), vec![
+            vec!["undefined-reference", "undefined-reference"],
+            vec!["implicit-global"],
+        ]);
+    }
+}
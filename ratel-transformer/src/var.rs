@@ -0,0 +1,545 @@
+use std::ptr;
+use std::collections::{HashMap, HashSet};
+
+use ratel::Module;
+use ratel::ast::{Identifier, Expression, ExpressionNode, Statement, StatementNode, StatementList};
+use ratel::ast::{Pattern, Property, Loc, Node, DeclarationKind, OperatorKind};
+use ratel::ast::expression::{ArrowExpression, BinaryExpression, PrefixExpression, PostfixExpression};
+use ratel::ast::statement::{DeclarationStatement, FunctionStatement, ForStatement, ForInStatement, ForOfStatement, ForInit};
+use ratel_visitor::{Visitable, StaticVisitor, DynamicVisitor, ScopeKind, ParentNode};
+use toolshed::Arena;
+
+/// Why a `var` declaration was left as is by `var_to_let`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Reason {
+    /// Not directly in the body of a function or of the program, such as
+    /// in a block or a loop, where `let` would have a narrower scope
+    Nested,
+
+    /// A name is used before the declaration, or in a function declaration
+    /// which can be called before it
+    UsedBeforeDeclaration,
+
+    /// A name is declared more than once in the function, including by
+    /// nested functions and blocks shadowing it
+    Redeclared,
+
+    /// The declaration uses object or rest patterns
+    Pattern,
+}
+
+/// A `var` declaration left as is by `var_to_let`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Unconverted<'ast> {
+    pub start: u32,
+    pub end: u32,
+
+    /// The name the reason applies to, if it applies to a single one
+    pub name: Option<&'ast str>,
+    pub reason: Reason,
+}
+
+/// Result of `var_to_let`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Report<'ast> {
+    /// Number of declarations converted
+    pub converted: usize,
+
+    /// Declarations left as `var`, in source order
+    pub unconverted: Vec<Unconverted<'ast>>,
+}
+
+/// Codemod replacing `var` declarations by `let`, or `const` when all of
+/// the names are initialized and never assigned again. A declaration is
+/// converted when it is directly in the body of a function or of the
+/// program, it is the only declaration of its names in that function,
+/// and the names are not used before it.
+///
+/// At the top of a script, `var` declarations create properties of the
+/// global object while `let` declarations don't, which is not checked.
+pub fn var_to_let<'ast>(module: &'ast Module<'ast>) -> Report<'ast> {
+    let mut root = Frame::new(false, None);
+
+    root.body = Some(module.body());
+
+    let mut ctx = VarContext {
+        frames: vec![root],
+        finished: Vec::new(),
+        scopes: Vec::new(),
+        depth: 0,
+        position: 0,
+        declaration: false,
+        nested: Vec::new(),
+    };
+
+    module.traverse(&VarAnalyzer, &mut ctx);
+
+    let root = ctx.frames.pop().expect("Root frame");
+    ctx.finished.push(root);
+
+    let mut report = Report {
+        converted: 0,
+        unconverted: ctx.nested,
+    };
+
+    for frame in &ctx.finished {
+        frame.convert(module.arena(), &mut report);
+    }
+
+    report.unconverted.sort_by_key(|unconverted| unconverted.start);
+    report
+}
+
+/// Function or arrow function, or the program, and everything used in it,
+/// including from nested functions.
+struct Frame<'ast> {
+    /// Function declarations are hoisted, their code can run before
+    /// any of the statements around them
+    hoisted: bool,
+
+    /// Only on arrow functions, the depth of their parent node
+    arrow_depth: Option<usize>,
+
+    body: Option<StatementList<'ast>>,
+    vars: Vec<&'ast StatementNode<'ast>>,
+
+    /// Number of declarations of every name, only looked up so that the
+    /// order of the report doesn't depend on hashing
+    declarations: HashMap<&'ast str, usize>,
+
+    /// Names used, and a position no further than the use
+    references: Vec<(&'ast str, u32)>,
+    assigned: HashSet<&'ast str>,
+}
+
+impl<'ast> Frame<'ast> {
+    #[inline]
+    fn new(hoisted: bool, arrow_depth: Option<usize>) -> Self {
+        Frame {
+            hoisted,
+            arrow_depth,
+            body: None,
+            vars: Vec::new(),
+            declarations: HashMap::new(),
+            references: Vec::new(),
+            assigned: HashSet::new(),
+        }
+    }
+
+    fn convert(&self, arena: &'ast Arena, report: &mut Report<'ast>) {
+        for &node in &self.vars {
+            let declaration = match node.item {
+                Statement::Declaration(declaration) => declaration,
+                _ => continue,
+            };
+
+            let unconverted = |name, reason| Unconverted { start: node.start, end: node.end, name, reason };
+
+            let at_top = self.body.is_some_and(|body| body.iter().any(|statement| ptr::eq(statement, node)));
+
+            if !at_top {
+                report.unconverted.push(unconverted(None, Reason::Nested));
+                continue;
+            }
+
+            let mut names = Vec::new();
+
+            if !declaration.declarators.iter().all(|declarator| bound_names(&declarator.id.item, &mut names)) {
+                report.unconverted.push(unconverted(None, Reason::Pattern));
+                continue;
+            }
+
+            let reason = names.iter().filter_map(|&name| {
+                if self.declarations.get(name) != Some(&1) {
+                    Some((name, Reason::Redeclared))
+                } else if self.references.iter().any(|&(used, position)| used == name && position < node.end) {
+                    Some((name, Reason::UsedBeforeDeclaration))
+                } else {
+                    None
+                }
+            }).next();
+
+            if let Some((name, reason)) = reason {
+                report.unconverted.push(unconverted(Some(name), reason));
+                continue;
+            }
+
+            let constant = declaration.declarators.iter().all(|declarator| declarator.init.is_some()) &&
+                           !names.iter().any(|name| self.assigned.contains(name));
+
+            let kind = match constant {
+                true  => DeclarationKind::Const,
+                false => DeclarationKind::Let,
+            };
+
+            node.set(arena.alloc(Loc::new(node.start, node.end, Statement::Declaration(DeclarationStatement {
+                kind,
+                declarators: declaration.declarators,
+            }))));
+
+            report.converted += 1;
+        }
+    }
+}
+
+/// Collect the names bound by a pattern, returns `false` for the patterns
+/// that aren't supported.
+fn bound_names<'ast>(pattern: &Pattern<'ast>, names: &mut Vec<&'ast str>) -> bool {
+    match *pattern {
+        Pattern::Void => true,
+        Pattern::Identifier(name) => {
+            names.push(name);
+            true
+        },
+        Pattern::ArrayPattern { elements } => elements.iter().all(|element| bound_names(&element.item, names)),
+        Pattern::AssignmentPattern { left, .. } => bound_names(&left.item, names),
+        Pattern::ObjectPattern { .. } | Pattern::RestElement { .. } => false,
+    }
+}
+
+/// Collect the names assigned to by an assignment target.
+fn assigned_names<'ast>(target: &Expression<'ast>, names: &mut Vec<&'ast str>) {
+    match *target {
+        Expression::Identifier(name) => names.push(name),
+        Expression::Array(ref array) => {
+            for element in array.body {
+                assigned_names(&element.item, names);
+            }
+        },
+        Expression::Object(ref object) => {
+            for property in object.body {
+                match property.item {
+                    Property::Shorthand(name) => names.push(name),
+                    Property::ShorthandDefault { name, .. } => names.push(name.item),
+                    Property::Literal { value, .. } => assigned_names(&value.item, names),
+                    Property::Spread { argument } => assigned_names(&argument.item, names),
+                    Property::Method { .. } => {},
+                }
+            }
+        },
+        Expression::Spread(ref spread) => assigned_names(&spread.argument.item, names),
+        Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, left, .. }) => {
+            assigned_names(&left.item, names);
+        },
+        _ => {},
+    }
+}
+
+struct VarContext<'ast> {
+    /// Frames entered, the innermost last
+    frames: Vec<Frame<'ast>>,
+
+    finished: Vec<Frame<'ast>>,
+    scopes: Vec<ScopeKind>,
+
+    /// Number of parent nodes entered
+    depth: usize,
+
+    /// Start of the node entered last
+    position: u32,
+
+    /// Set by a function declaration, for the scope of its function
+    declaration: bool,
+
+    /// `var` declarations in the head of loops
+    nested: Vec<Unconverted<'ast>>,
+}
+
+impl<'ast> VarContext<'ast> {
+    #[inline]
+    fn frame(&mut self) -> &mut Frame<'ast> {
+        self.frames.last_mut().expect("Root frame")
+    }
+
+    fn finish(&mut self) {
+        let frame = self.frames.pop().expect("Root frame");
+
+        self.finished.push(frame);
+    }
+
+    fn assign(&mut self, names: Vec<&'ast str>) {
+        for frame in &mut self.frames {
+            frame.assigned.extend(names.iter().cloned());
+        }
+    }
+
+    fn for_init(&mut self, init: Node<'ast, ForInit<'ast>>) {
+        match init.item {
+            ForInit::Declaration(DeclarationStatement { kind: DeclarationKind::Var, .. }) => {
+                self.nested.push(Unconverted {
+                    start: init.start,
+                    end: init.end,
+                    name: None,
+                    reason: Reason::Nested,
+                });
+            },
+            ForInit::Expression(expression) => {
+                let mut names = Vec::new();
+
+                assigned_names(&expression.item, &mut names);
+                self.assign(names);
+            },
+            _ => {},
+        }
+    }
+}
+
+struct VarAnalyzer;
+
+impl<'ast> StaticVisitor<'ast> for VarAnalyzer {
+    type Context = VarContext<'ast>;
+
+    #[inline]
+    fn push_parent(node: ParentNode<'ast>, ctx: &mut VarContext<'ast>) {
+        ctx.depth += 1;
+        ctx.position = match node {
+            ParentNode::Statement(node) => node.start,
+            ParentNode::Expression(node) => node.start,
+        };
+    }
+
+    #[inline]
+    fn pop_parent(ctx: &mut VarContext<'ast>) {
+        ctx.depth -= 1;
+
+        let depth = ctx.depth;
+
+        if ctx.frame().arrow_depth == Some(depth) {
+            ctx.finish();
+        }
+    }
+
+    #[inline]
+    fn on_statement_list(body: StatementList<'ast>, ctx: &mut VarContext<'ast>) {
+        // The first list of a function is its body, nested functions
+        // have frames of their own
+        let frame = ctx.frame();
+
+        if frame.body.is_none() {
+            frame.body = Some(body);
+        }
+    }
+
+    #[inline]
+    fn on_enter_scope(kind: ScopeKind, ctx: &mut VarContext<'ast>) {
+        ctx.scopes.push(kind);
+
+        if kind == ScopeKind::Function {
+            let hoisted = ctx.declaration;
+
+            ctx.declaration = false;
+            ctx.frames.push(Frame::new(hoisted, None));
+        }
+    }
+
+    #[inline]
+    fn on_leave_scope(ctx: &mut VarContext<'ast>) {
+        if ctx.scopes.pop() == Some(ScopeKind::Function) {
+            ctx.finish();
+        }
+    }
+
+    #[inline]
+    fn on_reference_use(ident: &Identifier<'ast>, ctx: &mut VarContext<'ast>) {
+        let mut position = ctx.position;
+
+        // Code in a function declaration can run from the start of the
+        // functions around it
+        for frame in ctx.frames.iter_mut().rev() {
+            frame.references.push((*ident, position));
+
+            if frame.hoisted {
+                position = 0;
+            }
+        }
+    }
+
+    #[inline]
+    fn on_reference_declaration(ident: &Identifier<'ast>, ctx: &mut VarContext<'ast>) {
+        for frame in &mut ctx.frames {
+            *frame.declarations.entry(*ident).or_insert(0) += 1;
+        }
+    }
+
+    #[inline]
+    fn on_identifier_expression(_: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, ctx: &mut VarContext<'ast>) {
+        ctx.position = node.start;
+    }
+
+    #[inline]
+    fn on_arrow_expression(_: &ArrowExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut VarContext<'ast>) {
+        let depth = ctx.depth;
+
+        ctx.frames.push(Frame::new(false, Some(depth)));
+    }
+
+    #[inline]
+    fn on_binary_expression(binary: &BinaryExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut VarContext<'ast>) {
+        if binary.operator.assignment() {
+            let mut names = Vec::new();
+
+            assigned_names(&binary.left.item, &mut names);
+            ctx.assign(names);
+        }
+    }
+
+    #[inline]
+    fn on_prefix_expression(prefix: &PrefixExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut VarContext<'ast>) {
+        update(prefix.operator, &prefix.operand.item, ctx);
+    }
+
+    #[inline]
+    fn on_postfix_expression(postfix: &PostfixExpression<'ast>, _: &'ast ExpressionNode<'ast>, ctx: &mut VarContext<'ast>) {
+        update(postfix.operator, &postfix.operand.item, ctx);
+    }
+
+    #[inline]
+    fn on_declaration_statement(declaration: &DeclarationStatement, node: &'ast StatementNode<'ast>, ctx: &mut VarContext<'ast>) {
+        if declaration.kind == DeclarationKind::Var {
+            ctx.frame().vars.push(node);
+        }
+    }
+
+    #[inline]
+    fn on_function_statement(_: &FunctionStatement<'ast>, _: &'ast StatementNode<'ast>, ctx: &mut VarContext<'ast>) {
+        ctx.declaration = true;
+    }
+
+    #[inline]
+    fn on_for_statement(_: &ForStatement, node: &'ast StatementNode<'ast>, ctx: &mut VarContext<'ast>) {
+        if let Statement::For(ForStatement { init: Some(init), .. }) = node.item {
+            ctx.for_init(init);
+        }
+    }
+
+    #[inline]
+    fn on_for_in_statement(_: &ForInStatement, node: &'ast StatementNode<'ast>, ctx: &mut VarContext<'ast>) {
+        if let Statement::ForIn(ForInStatement { left, .. }) = node.item {
+            ctx.for_init(left);
+        }
+    }
+
+    #[inline]
+    fn on_for_of_statement(_: &ForOfStatement, node: &'ast StatementNode<'ast>, ctx: &mut VarContext<'ast>) {
+        if let Statement::ForOf(ForOfStatement { left, .. }) = node.item {
+            ctx.for_init(left);
+        }
+    }
+
+    #[inline]
+    fn register(dv: &mut DynamicVisitor<'ast, VarContext<'ast>>) {
+        dv.push_parent.push(Self::push_parent);
+        dv.pop_parent.push(Self::pop_parent);
+        dv.on_statement_list.push(Self::on_statement_list);
+        dv.on_enter_scope.push(Self::on_enter_scope);
+        dv.on_leave_scope.push(Self::on_leave_scope);
+        dv.on_reference_use.push(Self::on_reference_use);
+        dv.on_reference_declaration.push(Self::on_reference_declaration);
+        dv.on_identifier_expression.push(Self::on_identifier_expression);
+        dv.on_arrow_expression.push(Self::on_arrow_expression);
+        dv.on_binary_expression.push(Self::on_binary_expression);
+        dv.on_prefix_expression.push(Self::on_prefix_expression);
+        dv.on_postfix_expression.push(Self::on_postfix_expression);
+        dv.on_declaration_statement.push(Self::on_declaration_statement);
+        dv.on_function_statement.push(Self::on_function_statement);
+        dv.on_for_statement.push(Self::on_for_statement);
+        dv.on_for_in_statement.push(Self::on_for_in_statement);
+        dv.on_for_of_statement.push(Self::on_for_of_statement);
+    }
+}
+
+fn update<'ast>(operator: OperatorKind, operand: &Expression<'ast>, ctx: &mut VarContext<'ast>) {
+    if let (OperatorKind::Increment, &Expression::Identifier(name)) |
+           (OperatorKind::Decrement, &Expression::Identifier(name)) = (operator, operand) {
+        ctx.assign(vec![name]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    fn assert_convert(source: &str, expected: &str) -> Vec<(Option<String>, Reason)> {
+        let module = parse(source).unwrap();
+        let report = var_to_let(&module);
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+
+        report.unconverted.iter().map(|unconverted| (unconverted.name.map(String::from), unconverted.reason)).collect()
+    }
+
+    #[test]
+    fn let_and_const() {
+        assert_convert("var a = 1; a += 1;", "let a = 1; a += 1;");
+        assert_convert("var a = 1, b; b = a;", "let a = 1, b; b = a;");
+        assert_convert("var a = 1, b = 2; foo(a, b);", "const a = 1, b = 2; foo(a, b);");
+        assert_convert("var [a, b = 1] = c; a++;", "let [a, b = 1] = c; a++;");
+        assert_convert("var a = 1; [a] = b;", "let a = 1; [a] = b;");
+        assert_convert("var a = 1; ({ a } = b);", "let a = 1; ({ a } = b);");
+        assert_convert("var a = {}; for (a in b) {}", "let a = {}; for (a in b) {}");
+    }
+
+    #[test]
+    fn in_functions() {
+        assert_convert(
+            "function foo(b) { var a = b; return () => { var c = 1; return a + c; }; }",
+            "function foo(b) { const a = b; return () => { const c = 1; return a + c; }; }"
+        );
+        assert_convert(
+            "var a = 1; foo(function () { a = 2; });",
+            "let a = 1; foo(function () { a = 2; });"
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let unconverted = assert_convert(
+            "if (a) { var b = 1; } for (var i = 0; i < 1; i++) {} while (a) var c; d: var d;",
+            "if (a) { var b = 1; } for (var i = 0; i < 1; i++) {} while (a) var c; d: var d;"
+        );
+
+        assert_eq!(unconverted, vec![
+            (None, Reason::Nested),
+            (None, Reason::Nested),
+            (None, Reason::Nested),
+            (None, Reason::Nested),
+        ]);
+    }
+
+    #[test]
+    fn used_before_declaration() {
+        let unconverted = assert_convert(
+            "foo(a); var a = 1; var b = b; var c = 1; function bar() { return c; } var d = 1; (function () { return d; });",
+            "foo(a); var a = 1; var b = b; var c = 1; function bar() { return c; } const d = 1; (function () { return d; });"
+        );
+
+        assert_eq!(unconverted, vec![
+            (Some("a".into()), Reason::UsedBeforeDeclaration),
+            (Some("b".into()), Reason::UsedBeforeDeclaration),
+            (Some("c".into()), Reason::UsedBeforeDeclaration),
+        ]);
+    }
+
+    #[test]
+    fn redeclared() {
+        let unconverted = assert_convert(
+            "var a = 1; var a = 2; var b = 1; function foo(b) {} var c = 1; { let c; }",
+            "var a = 1; var a = 2; var b = 1; function foo(b) {} var c = 1; { let c; }"
+        );
+
+        assert_eq!(unconverted, vec![
+            (Some("a".into()), Reason::Redeclared),
+            (Some("a".into()), Reason::Redeclared),
+            (Some("b".into()), Reason::Redeclared),
+            (Some("c".into()), Reason::Redeclared),
+        ]);
+    }
+
+    #[test]
+    fn patterns() {
+        let unconverted = assert_convert("var { a } = b;", "var { a } = b;");
+
+        assert_eq!(unconverted, vec![(None, Reason::Pattern)]);
+    }
+}
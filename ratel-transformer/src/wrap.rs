@@ -0,0 +1,129 @@
+use ratel::Module;
+use ratel::error::Error;
+
+/// Envelope around the body of a module, see `wrap`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Envelope<'a> {
+    /// `(function () { ... }).call(this);`, keeping the value of `this`.
+    /// With a `name`, the exports are assigned to `var name`.
+    Iife { name: Option<&'a str> },
+
+    /// `function name(require, module, exports) { ... }`, a CommonJS module
+    /// factory as used in bundles. Exports are assigned to `module.exports`.
+    CommonJs { name: &'a str },
+
+    /// UMD wrapper, exporting with AMD `define` or CommonJS `module.exports`
+    /// when available, or else as the `name` global.
+    Umd { name: &'a str },
+}
+
+/// Wrap the body of the `module` in the `envelope`, so that its declarations
+/// become local to a function. Leading directives stay in the prologue of
+/// that function. The `exports`, if any, is the source of an expression
+/// evaluated after the body, such as the name of a declared variable,
+/// which is exported by the envelope.
+pub fn wrap(module: &mut Module, envelope: Envelope, exports: Option<&str>) -> Result<(), Vec<Error>> {
+    let (head, tail) = match envelope {
+        Envelope::Iife { name } => {
+            let head = match name {
+                Some(name) => format!("var {} = (function () ", name),
+                None       => "(function () ".to_owned(),
+            };
+
+            (head, ").call(this);".to_owned())
+        },
+        Envelope::CommonJs { name } => {
+            (format!("function {}(require, module, exports) ", name), String::new())
+        },
+        Envelope::Umd { name } => {
+            let head = format!(concat!(
+                "(function (root, factory) {{",
+                    "if (typeof define === 'function' && define.amd) {{",
+                        "define([], factory);",
+                    "}} else if (typeof module === 'object' && module.exports) {{",
+                        "module.exports = factory();",
+                    "}} else {{",
+                        "root.{} = factory();",
+                    "}}",
+                "}}(this, function () "
+            ), name);
+
+            (head, "));".to_owned())
+        },
+    };
+
+    let epilogue = match (envelope, exports) {
+        (_, None) => String::new(),
+        (Envelope::CommonJs { .. }, Some(exports)) => format!("module.exports = {};", exports),
+        (_, Some(exports)) => format!("return {};", exports),
+    };
+
+    let source = format!("{}{{{}}}{}", head, epilogue, tail);
+
+    module.wrap(&source, head.len() as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse;
+
+    fn assert_wrap(source: &str, envelope: Envelope, exports: Option<&str>, expected: &str) {
+        let mut module = parse(source).unwrap();
+
+        wrap(&mut module, envelope, exports).unwrap();
+
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn iife() {
+        assert_wrap(
+            "'use strict'; var a = 1;",
+            Envelope::Iife { name: None },
+            None,
+            "(function () { 'use strict'; var a = 1; }).call(this);"
+        );
+        assert_wrap(
+            "var a = 1;",
+            Envelope::Iife { name: Some("lib") },
+            Some("a"),
+            "var lib = (function () { var a = 1; return a; }).call(this);"
+        );
+    }
+
+    #[test]
+    fn commonjs() {
+        assert_wrap(
+            "var a = require('a');",
+            Envelope::CommonJs { name: "moduleA" },
+            Some("{ a: a }"),
+            "function moduleA(require, module, exports) { var a = require('a'); module.exports = { a: a }; }"
+        );
+    }
+
+    #[test]
+    fn umd() {
+        assert_wrap(
+            "function a() {}",
+            Envelope::Umd { name: "lib" },
+            Some("a"),
+            "(function (root, factory) {
+                if (typeof define === 'function' && define.amd) {
+                    define([], factory);
+                } else if (typeof module === 'object' && module.exports) {
+                    module.exports = factory();
+                } else {
+                    root.lib = factory();
+                }
+            }(this, function () { function a() {} return a; }));"
+        );
+    }
+
+    #[test]
+    fn invalid_name() {
+        let mut module = parse("a;").unwrap();
+
+        assert!(wrap(&mut module, Envelope::CommonJs { name: "1" }, None).is_err());
+    }
+}
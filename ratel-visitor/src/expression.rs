@@ -23,6 +23,10 @@ impl<'ast> Visitable<'ast> for ExpressionNode<'ast> {
                 visitor.on_this_expression(&self, ctx);
                 return;
             },
+            Super(_) => {
+                visitor.on_super_expression(self, ctx);
+                return;
+            },
             Identifier(ref ident) => {
                 visitor.on_identifier_expression(ident, self, ctx);
                 visitor.push_parent(ParentNode::from(self), ctx);
@@ -52,6 +56,16 @@ impl<'ast> Visitable<'ast> for ExpressionNode<'ast> {
                 visitor.push_parent(ParentNode::from(self), ctx);
                 computed.traverse(visitor, ctx);
             },
+            PrivateMember(ref member) => {
+                visitor.on_private_member_expression(member, self, ctx);
+                visitor.push_parent(ParentNode::from(self), ctx);
+                member.traverse(visitor, ctx);
+            },
+            Chain(ref chain) => {
+                visitor.on_chain_expression(chain, self, ctx);
+                visitor.push_parent(ParentNode::from(self), ctx);
+                chain.traverse(visitor, ctx);
+            },
             MetaProperty(ref property) => {
                 visitor.on_meta_property(property, self, ctx);
                 visitor.push_parent(ParentNode::from(self), ctx);
@@ -97,6 +111,16 @@ impl<'ast> Visitable<'ast> for ExpressionNode<'ast> {
                 visitor.push_parent(ParentNode::from(self), ctx);
                 spread.traverse(visitor, ctx);
             },
+            Await(ref await_expression) => {
+                visitor.on_await_expression(await_expression, self, ctx);
+                visitor.push_parent(ParentNode::from(self), ctx);
+                await_expression.traverse(visitor, ctx);
+            },
+            Yield(ref yield_expression) => {
+                visitor.on_yield_expression(yield_expression, self, ctx);
+                visitor.push_parent(ParentNode::from(self), ctx);
+                yield_expression.traverse(visitor, ctx);
+            },
             Arrow(ref arrow) => {
                 visitor.on_arrow_expression(arrow, self, ctx);
                 visitor.push_parent(ParentNode::from(self), ctx);
@@ -129,6 +153,13 @@ impl<'ast> Visitable<'ast> for ThisExpression {
     fn traverse<V: Visitor<'ast>>(&self, _: &V, _: &mut V::Context) {}
 }
 
+impl<'ast> Visitable<'ast> for SuperExpression {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn traverse<V: Visitor<'ast>>(&self, _: &V, _: &mut V::Context) {}
+}
+
 impl<'ast> Visitable<'ast> for Identifier<'ast> {
     type Parent = ExpressionNode<'ast>;
 
@@ -194,6 +225,30 @@ impl<'ast> Visitable<'ast> for ComputedMemberExpression<'ast> {
     }
 }
 
+impl<'ast> Visitable<'ast> for PrivateMemberExpression<'ast> {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn traverse<V>(&'ast self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        self.object.traverse(visitor, ctx);
+    }
+}
+
+impl<'ast> Visitable<'ast> for ChainExpression<'ast> {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn traverse<V>(&'ast self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        self.expression.traverse(visitor, ctx);
+    }
+}
+
 impl<'ast> Visitable<'ast> for MetaPropertyExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
@@ -308,6 +363,30 @@ impl<'ast> Visitable<'ast> for SpreadExpression<'ast> {
     }
 }
 
+impl<'ast> Visitable<'ast> for AwaitExpression<'ast> {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn traverse<V>(&'ast self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        self.argument.traverse(visitor, ctx);
+    }
+}
+
+impl<'ast> Visitable<'ast> for YieldExpression<'ast> {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn traverse<V>(&'ast self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        self.argument.traverse(visitor, ctx);
+    }
+}
+
 impl<'ast> Visitable<'ast> for ArrowBody<'ast> {
     type Parent = ExpressionNode<'ast>;
 
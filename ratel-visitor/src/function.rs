@@ -78,7 +78,7 @@ impl<'ast> Visitable<'ast> for ClassMember<'ast> {
                 key.traverse(visitor, ctx);
                 value.traverse(visitor, ctx);
             },
-            Literal {
+            Property {
                 ref key,
                 ref value,
                 ..
@@ -86,6 +86,12 @@ impl<'ast> Visitable<'ast> for ClassMember<'ast> {
                 key.traverse(visitor, ctx);
                 value.traverse(visitor, ctx);
             },
+            // Declarations with `var` are scoped to the block, as in a function
+            StaticBlock { ref body } => {
+                visitor.on_enter_scope(ScopeKind::Function, ctx);
+                body.traverse(visitor, ctx);
+                visitor.on_leave_scope(ctx);
+            },
         }
     }
 }
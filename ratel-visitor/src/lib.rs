@@ -20,6 +20,9 @@ mod statement;
 pub enum ScopeKind {
     Function,
     Block,
+    /// Holds the parameter of a catch clause, the body of the clause is a
+    /// separate `Block` scope nested in it
+    Catch,
 }
 
 // Like Batman!
@@ -48,13 +51,16 @@ build! {
 
     // expressions
     fn on_this_expression(node: &'ast ExpressionNode<'ast>);
+    fn on_super_expression(node: &'ast ExpressionNode<'ast>);
     fn on_identifier_expression(item: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_literal_expression(item: &Literal<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_sequence_expression(item: &SequenceExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_array_expression(item: &ArrayExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_member_expression(item: &MemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_computed_member_expression(item: &ComputedMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
-    fn on_meta_property(item: &MetaPropertyExpression<'ast>, node: &ExpressionNode<'ast>);
+    fn on_private_member_expression(item: &PrivateMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+    fn on_chain_expression(item: &ChainExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+    fn on_meta_property(item: &MetaPropertyExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_call_expression(item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_binary_expression(item: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_prefix_expression(item: &PrefixExpression<'ast>, node: &'ast ExpressionNode<'ast>);
@@ -63,6 +69,8 @@ build! {
     fn on_template_literal(item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_tagged_template_expression(item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_spread_expression(item: &SpreadExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+    fn on_yield_expression(item: &YieldExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+    fn on_await_expression(item: &AwaitExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_arrow_expression(item: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_object_expression(item: &ObjectExpression<'ast>, node: &'ast ExpressionNode<'ast>);
     fn on_function_expression(item: &FunctionExpression<'ast>, node: &'ast ExpressionNode<'ast>);
@@ -87,6 +95,8 @@ build! {
     fn on_switch_statement(item: &SwitchStatement, node: &'ast StatementNode<'ast>);
     fn on_function_statement(item: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>);
     fn on_class_statement(item: &ClassStatement<'ast>, node: &'ast StatementNode<'ast>);
+    fn on_import_statement(item: &ImportStatement<'ast>, node: &'ast StatementNode<'ast>);
+    fn on_export_statement(item: &ExportStatement<'ast>, node: &'ast StatementNode<'ast>);
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -188,7 +198,7 @@ impl<'ast> Visitable<'ast> for PropertyKey<'ast> {
     {
         match *self {
             PropertyKey::Computed(ref expression) => expression.traverse(visitor, ctx),
-            PropertyKey::Literal(_) | PropertyKey::Binary(_) => {}
+            PropertyKey::Literal(_) | PropertyKey::Binary(_) | PropertyKey::Private(_) => {}
         }
     }
 }
@@ -203,6 +213,10 @@ impl<'ast> Visitable<'ast> for Property<'ast> {
     {
         match *self {
             Property::Shorthand(ref ident) => visitor.on_reference_use(ident, ctx),
+            Property::ShorthandDefault { ref name, ref value } => {
+                visitor.on_reference_use(name, ctx);
+                value.traverse(visitor, ctx);
+            },
             Property::Literal { ref key, ref value } => {
                 key.traverse(visitor, ctx);
                 value.traverse(visitor, ctx);
@@ -298,6 +312,16 @@ impl<'ast> Visitable<'ast> for StatementList<'ast> {
     }
 }
 
+/// Stable part of the API along with `ratel::prelude`, see there, to be
+/// imported with `use ratel_visitor::prelude::v1::*;`.
+pub mod prelude {
+    /// First version of the prelude, including `ratel::prelude::v1`.
+    pub mod v1 {
+        pub use ratel::prelude::v1::*;
+        pub use {Visitable, Visitor, StaticVisitor, DynamicVisitor, ParentNode, ScopeKind};
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -428,6 +452,31 @@ mod test {
         assert_eq!(ctx.declared_vars, &[]);
     }
 
+    #[test]
+    fn keeps_track_of_module_bindings() {
+        let module = parse("import a, { b as c } from 'd'; export default class E {} export { a, f as g }; export { h } from 'i';").unwrap();
+        let mut ctx = TestContext::new();
+
+        module.traverse(&ScopeTest, &mut ctx);
+
+        assert_eq!(ctx.used_vars, &[("a", 0), ("f", 0)]);
+        assert_eq!(ctx.declared_vars, &[("a", 0), ("c", 0), ("E", 0)]);
+    }
+
+    #[test]
+    fn catch_parameter_has_own_scope() {
+        let module = parse("try { a; } catch (err) { b; }").unwrap();
+        let mut ctx = TestContext::new();
+
+        module.traverse(&ScopeTest, &mut ctx);
+
+        assert_eq!(ctx.scopes, &[Block, Catch, Block]);
+        assert_eq!(ctx.depth, 0);
+        assert_eq!(ctx.max_depth, 2);
+        assert_eq!(ctx.used_vars, &[("a", 1), ("b", 2)]);
+        assert_eq!(ctx.declared_vars, &[("err", 1)]);
+    }
+
     #[test]
     fn function_and_class_are_declarations() {
         let module = parse("function foo() {} class Bar {}").unwrap();
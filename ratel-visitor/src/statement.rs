@@ -1,4 +1,4 @@
-use ratel::ast::{Node, Statement, StatementNode};
+use ratel::ast::{Node, Statement, StatementNode, Expression};
 use ratel::ast::statement::*;
 
 use {Visitor, Visitable, ParentNode, ScopeKind, NoParent};
@@ -108,6 +108,16 @@ impl<'ast> Visitable<'ast> for StatementNode<'ast> {
                 visitor.on_class_statement(class, self, ctx);
                 visitor.push_parent(ParentNode::from(self), ctx);
                 class.traverse(visitor, ctx);
+            },
+            Import(ref import) => {
+                visitor.on_import_statement(import, self, ctx);
+                visitor.push_parent(ParentNode::from(self), ctx);
+                import.traverse(visitor, ctx);
+            },
+            Export(ref export) => {
+                visitor.on_export_statement(export, self, ctx);
+                visitor.push_parent(ParentNode::from(self), ctx);
+                export.traverse(visitor, ctx);
             }
         }
         visitor.pop_parent(ctx);
@@ -317,8 +327,10 @@ impl<'ast> Visitable<'ast> for CatchClause<'ast> {
     where
         V: Visitor<'ast>,
     {
+        visitor.on_enter_scope(ScopeKind::Catch, ctx);
         self.param.traverse(visitor, ctx);
         self.body.traverse(visitor, ctx);
+        visitor.on_leave_scope(ctx);
     }
 }
 
@@ -362,6 +374,55 @@ impl<'ast> Visitable<'ast> for SwitchCase<'ast> {
     }
 }
 
+impl<'ast> Visitable<'ast> for ImportStatement<'ast> {
+    type Parent = StatementNode<'ast>;
+
+    #[inline]
+    fn traverse<V>(&'ast self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        for specifier in self.specifiers {
+            visitor.on_reference_declaration(&specifier.local().item, ctx);
+        }
+    }
+}
+
+impl<'ast> Visitable<'ast> for ExportStatement<'ast> {
+    type Parent = StatementNode<'ast>;
+
+    #[inline]
+    fn traverse<V>(&'ast self, visitor: &V, ctx: &mut V::Context)
+    where
+        V: Visitor<'ast>,
+    {
+        match *self {
+            ExportStatement::Declaration(ref declaration) => declaration.traverse(visitor, ctx),
+            ExportStatement::DefaultDeclaration(ref declaration) => {
+                // The name of the function or class is declared in the module
+                let name = match declaration.item {
+                    Expression::Function(ref function) => function.name.0,
+                    Expression::Class(ref class) => class.name.0,
+                    _ => None,
+                };
+
+                if let Some(name) = name {
+                    visitor.on_reference_declaration(&name.item, ctx);
+                }
+
+                declaration.traverse(visitor, ctx);
+            },
+            ExportStatement::Default(ref expression) => expression.traverse(visitor, ctx),
+            ExportStatement::Named { specifiers, source: None } => {
+                for specifier in specifiers {
+                    visitor.on_reference_use(&specifier.local.item, ctx);
+                }
+            },
+            ExportStatement::Named { .. } | ExportStatement::All { .. } => {},
+        }
+    }
+}
+
 impl<'ast> Visitable<'ast> for SwitchStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
@@ -6,6 +6,7 @@ use std::os::raw::c_char;
 use std::ffi::CStr;
 use std::ffi::CString;
 use ratel::error::{Error, ParseError};
+use serde_json::Value;
 
 fn main() {}
 
@@ -26,11 +27,12 @@ fn format_errors(errors: Vec<Error>, source: String) -> String {
 	format!("Error: {}", error)
 }
 
+/// # Safety
+///
+/// `i` must point at a zero terminated UTF-8 string.
 #[no_mangle]
-pub fn transform(i: *const c_char, minify: bool) -> *const c_char {
-	let data = unsafe {
-        CStr::from_ptr(i).to_str().unwrap()
-    };
+pub unsafe fn transform(i: *const c_char, minify: bool) -> *const c_char {
+	let data = CStr::from_ptr(i).to_str().unwrap();
 
 	let result = match ratel::parse(&data) {
 		Ok(module) => {
@@ -42,11 +44,12 @@ pub fn transform(i: *const c_char, minify: bool) -> *const c_char {
 	CString::new(result.as_str()).unwrap().into_raw()
 }
 
+/// # Safety
+///
+/// `i` must point at a zero terminated UTF-8 string.
 #[no_mangle]
-pub fn generate_ast(i: *const c_char, minify: bool) -> *const c_char {
-	let data = unsafe {
-        CStr::from_ptr(i).to_str().unwrap()
-    };
+pub unsafe fn generate_ast(i: *const c_char, minify: bool) -> *const c_char {
+	let data = CStr::from_ptr(i).to_str().unwrap();
 
 	let result = match ratel::parse(&data) {
 		Ok(module) => {
@@ -62,11 +65,12 @@ pub fn generate_ast(i: *const c_char, minify: bool) -> *const c_char {
 	CString::new(result.as_str()).unwrap().into_raw()
 }
 
+/// # Safety
+///
+/// `i` must point at a zero terminated UTF-8 string.
 #[no_mangle]
-pub fn generate_ast_estree(i: *const c_char, minify: bool) -> *const c_char {
-	let data = unsafe {
-        CStr::from_ptr(i).to_str().unwrap()
-    };
+pub unsafe fn generate_ast_estree(i: *const c_char, minify: bool) -> *const c_char {
+	let data = CStr::from_ptr(i).to_str().unwrap();
 
 	let result = match ratel::parse(&data) {
 		Ok(module) => {
@@ -81,3 +85,92 @@ pub fn generate_ast_estree(i: *const c_char, minify: bool) -> *const c_char {
 
 	CString::new(result.as_str()).unwrap().into_raw()
 }
+
+/// Recursively sort object keys so that the output only depends on the AST,
+/// not on the order in which the serializer happened to write the fields.
+fn canonicalize(value: Value) -> Value {
+	match value {
+		Value::Object(map) => {
+			let mut entries: Vec<_> = map.into_iter().collect();
+
+			entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+			Value::Object(entries.into_iter().map(|(key, value)| (key, canonicalize(value))).collect())
+		},
+		Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+		value => value,
+	}
+}
+
+/// Same as `generate_ast_estree`, but with a canonical field order, suitable
+/// for byte comparison of the output across runs and versions.
+///
+/// # Safety
+///
+/// `i` must point at a zero terminated UTF-8 string.
+#[no_mangle]
+pub unsafe fn generate_ast_estree_canonical(i: *const c_char, minify: bool) -> *const c_char {
+	let data = CStr::from_ptr(i).to_str().unwrap();
+
+	let result = match ratel::parse(&data) {
+		Ok(module) => {
+			let value = canonicalize(serde_json::to_value(&module).unwrap());
+
+			if minify {
+				serde_json::to_string(&value).unwrap()
+			} else {
+				serde_json::to_string_pretty(&value).unwrap()
+			}
+		},
+		Err(errors) => format_errors(errors, data.to_string())
+	};
+
+	CString::new(result.as_str()).unwrap().into_raw()
+}
+
+/// # Safety
+///
+/// `i` must point at a zero terminated UTF-8 string.
+#[no_mangle]
+pub unsafe fn generate_ast_shift(i: *const c_char, minify: bool) -> *const c_char {
+	let data = CStr::from_ptr(i).to_str().unwrap();
+
+	let result = match ratel::parse(&data) {
+		Ok(module) => {
+			let shift = ratel::InDialect(&module, &ratel::Shift);
+
+			if minify {
+				serde_json::to_string(&shift).unwrap()
+			} else {
+				serde_json::to_string_pretty(&shift).unwrap()
+			}
+		},
+		Err(errors) => format_errors(errors, data.to_string())
+	};
+
+	CString::new(result.as_str()).unwrap().into_raw()
+}
+
+/// # Safety
+///
+/// `i` must point at a zero terminated UTF-8 string.
+#[no_mangle]
+pub unsafe fn generate_ast_babel(i: *const c_char, minify: bool) -> *const c_char {
+	let data = CStr::from_ptr(i).to_str().unwrap();
+
+	let result = match ratel::parse(&data) {
+		Ok(module) => {
+			let babel = ratel::Babel::new(data);
+			let babel = ratel::InDialect(&module, &babel);
+
+			if minify {
+				serde_json::to_string(&babel).unwrap()
+			} else {
+				serde_json::to_string_pretty(&babel).unwrap()
+			}
+		},
+		Err(errors) => format_errors(errors, data.to_string())
+	};
+
+	CString::new(result.as_str()).unwrap().into_raw()
+}
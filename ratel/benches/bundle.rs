@@ -0,0 +1,72 @@
+#![feature(test)]
+
+extern crate test;
+extern crate ratel;
+
+use test::Bencher;
+
+/// Development bundle of webpack, with the runtime lines marked by `/******/`
+/// around a table of `modules` modules requiring each other.
+fn webpack_bundle(modules: usize) -> String {
+    let mut bundle = String::from("/******/ (() => { // webpackBootstrap\n/******/ \tvar __webpack_modules__ = ({\n\n");
+
+    for index in 0..modules {
+        bundle.push_str(&format!(concat!(
+            "/***/ \"./src/module{0}.js\":\n",
+            "/*!*************************!*\\\n",
+            "  !*** ./src/module{0}.js ***!\n",
+            "  \\*************************/\n",
+            "/***/ ((module, __unused_webpack_exports, __webpack_require__) => {{\n\n",
+            "const next = __webpack_require__(/*! ./module{1} */ \"./src/module{1}.js\");\n",
+            "const pattern = /[a-z]+\\/{0}/g;\n\n",
+            "module.exports = function (value) {{\n",
+            "    return `${{value}}-{0}`.replace(pattern, next) / 2;\n",
+            "}};\n\n",
+            "/***/ }}),\n\n",
+        ), index, index + 1));
+    }
+
+    bundle.push_str(concat!(
+        "/******/ \t});\n",
+        "/******/ \t// The module cache\n",
+        "/******/ \tvar __webpack_module_cache__ = {};\n",
+        "/******/ \t\n",
+        "/******/ \t// The require function\n",
+        "/******/ \tfunction __webpack_require__(moduleId) {\n",
+        "/******/ \t\tvar cachedModule = __webpack_module_cache__[moduleId];\n",
+        "/******/ \t\tif (cachedModule !== undefined) {\n",
+        "/******/ \t\t\treturn cachedModule.exports;\n",
+        "/******/ \t\t}\n",
+        "/******/ \t\tvar module = __webpack_module_cache__[moduleId] = {\n",
+        "/******/ \t\t\texports: {}\n",
+        "/******/ \t\t};\n",
+        "/******/ \t\t__webpack_modules__[moduleId](module, module.exports, __webpack_require__);\n",
+        "/******/ \t\treturn module.exports;\n",
+        "/******/ \t}\n",
+        "/******/ \t\n",
+        "/******/ \tvar __webpack_exports__ = __webpack_require__(\"./src/module0.js\");\n",
+        "/******/ })()\n",
+    ));
+
+    bundle
+}
+
+#[bench]
+fn scan_dependencies(b: &mut Bencher) {
+    let bundle = webpack_bundle(500);
+    b.bytes = bundle.len() as u64;
+
+    b.iter(|| {
+        ratel::scan_dependencies(&bundle)
+    });
+}
+
+#[bench]
+fn parse_to_ast(b: &mut Bencher) {
+    let bundle = webpack_bundle(500);
+    b.bytes = bundle.len() as u64;
+
+    b.iter(|| {
+        let _module = ratel::parse(&bundle).expect("Must parse");
+    });
+}
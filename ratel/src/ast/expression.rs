@@ -1,16 +1,28 @@
 use ast::{Node, NodeList, Literal, OperatorKind, Function, Class, EmptyName, OptionalName};
 use ast::{Identifier, IdentifierNode, BlockNode, ExpressionNode, Statement, ExpressionList, Pattern};
+use std::iter::Peekable;
+use std::ops::ControlFlow;
+use toolshed::list::ListIter;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum PropertyKey<'ast> {
     Computed(ExpressionNode<'ast>),
     Literal(&'ast str),
     Binary(&'ast str),
+    /// Private name of a class member, `#a`, without the `#`
+    Private(&'ast str),
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Property<'ast> {
     Shorthand(&'ast str),
+    /// `{ a = 1 }`, only valid in object patterns. In an object literal it
+    /// covers an assignment target, and is an error unless the literal
+    /// turns out to be one, as in `({ a = 1 } = b)`.
+    ShorthandDefault {
+        name: IdentifierNode<'ast>,
+        value: ExpressionNode<'ast>,
+    },
     Literal {
         key: Node<'ast, PropertyKey<'ast>>,
         value: ExpressionNode<'ast>,
@@ -29,20 +41,79 @@ pub enum Property<'ast> {
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct ThisExpression;
 
+/// The `super` keyword, only ever found as the callee of a call, as in
+/// `super(a)`, or the object of a member, as in `super.foo`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SuperExpression;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct SequenceExpression<'ast> {
     pub body: ExpressionList<'ast>
 }
 
+/// Holes are stored as `Expression::Void`. A trailing comma is stored as
+/// one extra `Void` at the end, so that the elision is kept when printing
+/// the array back; use `elements` to get the elements counting towards
+/// the `length` of the array.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct ArrayExpression<'ast> {
     pub body: ExpressionList<'ast>
 }
 
+impl<'ast> ArrayExpression<'ast> {
+    /// Elements of the array, holes are `None`.
+    pub fn elements(&self) -> ArrayElements<'ast> {
+        ArrayElements {
+            iter: self.body.iter().peekable(),
+        }
+    }
+}
+
+pub struct ArrayElements<'ast> {
+    iter: Peekable<ListIter<'ast, ExpressionNode<'ast>>>,
+}
+
+impl<'ast> Iterator for ArrayElements<'ast> {
+    type Item = Option<&'ast ExpressionNode<'ast>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.iter.next()?;
+
+        match element.item {
+            Expression::Void if self.iter.peek().is_none() => None,
+            Expression::Void => Some(None),
+            _ => Some(Some(element)),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct MemberExpression<'ast> {
     pub object: ExpressionNode<'ast>,
     pub property: IdentifierNode<'ast>,
+
+    /// Whether it's accessed with `?.`
+    pub optional: bool,
+}
+
+/// Access to a private member of a class, `this.#a`. The `property` is
+/// the name without the `#`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PrivateMemberExpression<'ast> {
+    pub object: ExpressionNode<'ast>,
+    pub property: IdentifierNode<'ast>,
+
+    /// Whether it's accessed with `?.`
+    pub optional: bool,
+}
+
+/// Optional chain in parentheses, followed by a member access or a call,
+/// as in `(a?.b).c`, where `?.` only short-circuits up to the parenthesis.
+/// Its span is the one of the chain, without the parentheses.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ChainExpression<'ast> {
+    pub expression: ExpressionNode<'ast>,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -55,12 +126,18 @@ pub struct MetaPropertyExpression<'ast> {
 pub struct ComputedMemberExpression<'ast> {
     pub object: ExpressionNode<'ast>,
     pub property: ExpressionNode<'ast>,
+
+    /// Whether it's accessed with `?.`
+    pub optional: bool,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct CallExpression<'ast> {
     pub callee: ExpressionNode<'ast>,
     pub arguments: ExpressionList<'ast>,
+
+    /// Whether it's called with `?.`
+    pub optional: bool,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -106,6 +183,19 @@ pub struct SpreadExpression<'ast> {
     pub argument: ExpressionNode<'ast>
 }
 
+/// `await` within an async function.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AwaitExpression<'ast> {
+    pub argument: ExpressionNode<'ast>,
+}
+
+/// `yield` within a generator, `yield*` delegating to another iterable.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct YieldExpression<'ast> {
+    pub argument: Option<ExpressionNode<'ast>>,
+    pub delegate: bool,
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ArrowBody<'ast> {
     Expression(ExpressionNode<'ast>),
@@ -114,6 +204,8 @@ pub enum ArrowBody<'ast> {
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct ArrowExpression<'ast> {
+    /// Whether it's an async arrow function, `async x => x`
+    pub is_async: bool,
     pub params: NodeList<'ast, Pattern<'ast>>,
     pub body: ArrowBody<'ast>,
 }
@@ -128,14 +220,21 @@ pub type ClassExpression<'ast> = Class<'ast, OptionalName<'ast>>;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Expression<'ast> {
+    /// Not an expression of the source, but a hole of an array, such as
+    /// in `[a,,b]`, or a placeholder left by a parse error. The `void`
+    /// operator is an `OperatorKind::Void` prefix expression, and
+    /// `undefined` an identifier.
     Void,
     This(ThisExpression),
+    Super(SuperExpression),
     Identifier(Identifier<'ast>),
     Literal(Literal<'ast>),
     Sequence(SequenceExpression<'ast>),
     Array(ArrayExpression<'ast>),
     Member(MemberExpression<'ast>),
     ComputedMember(ComputedMemberExpression<'ast>),
+    PrivateMember(PrivateMemberExpression<'ast>),
+    Chain(ChainExpression<'ast>),
     MetaProperty(MetaPropertyExpression<'ast>),
     Call(CallExpression<'ast>),
     Binary(BinaryExpression<'ast>),
@@ -145,12 +244,45 @@ pub enum Expression<'ast> {
     Template(TemplateLiteral<'ast>),
     TaggedTemplate(TaggedTemplateExpression<'ast>),
     Spread(SpreadExpression<'ast>),
+    Yield(YieldExpression<'ast>),
+    Await(AwaitExpression<'ast>),
     Arrow(ArrowExpression<'ast>),
     Object(ObjectExpression<'ast>),
     Function(FunctionExpression<'ast>),
     Class(ClassExpression<'ast>),
 }
 
+/// Payload-free discriminant of an `Expression`, cheap to copy, hash and compare.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ExpressionKind {
+    Void,
+    This,
+    Super,
+    Identifier,
+    Literal,
+    Sequence,
+    Array,
+    Member,
+    ComputedMember,
+    PrivateMember,
+    Chain,
+    MetaProperty,
+    Call,
+    Binary,
+    Prefix,
+    Postfix,
+    Conditional,
+    Template,
+    TaggedTemplate,
+    Spread,
+    Yield,
+    Await,
+    Arrow,
+    Object,
+    Function,
+    Class,
+}
+
 macro_rules! impl_from {
     ($( $type:ty => $variant:ident ),*) => ($(
         impl<'ast> From<$type> for Expression<'ast> {
@@ -164,12 +296,15 @@ macro_rules! impl_from {
 
 impl_from! {
     ThisExpression => This,
+    SuperExpression => Super,
     Identifier<'ast> => Identifier,
     Literal<'ast> => Literal,
     SequenceExpression<'ast> => Sequence,
     ArrayExpression<'ast> => Array,
     MemberExpression<'ast> => Member,
     ComputedMemberExpression<'ast> => ComputedMember,
+    PrivateMemberExpression<'ast> => PrivateMember,
+    ChainExpression<'ast> => Chain,
     MetaPropertyExpression<'ast> => MetaProperty,
     CallExpression<'ast> => Call,
     BinaryExpression<'ast> => Binary,
@@ -179,6 +314,8 @@ impl_from! {
     TemplateLiteral<'ast> => Template,
     TaggedTemplateExpression<'ast> => TaggedTemplate,
     SpreadExpression<'ast> => Spread,
+    YieldExpression<'ast> => Yield,
+    AwaitExpression<'ast> => Await,
     ArrowExpression<'ast> => Arrow,
     ObjectExpression<'ast> => Object,
     FunctionExpression<'ast> => Function,
@@ -186,37 +323,181 @@ impl_from! {
 }
 
 impl<'ast> Expression<'ast> {
+    #[inline]
+    pub fn kind(&self) -> ExpressionKind {
+        use self::Expression::*;
+
+        match *self {
+            Void              => ExpressionKind::Void,
+            This(_)           => ExpressionKind::This,
+            Super(_)          => ExpressionKind::Super,
+            Identifier(_)     => ExpressionKind::Identifier,
+            Literal(_)        => ExpressionKind::Literal,
+            Sequence(_)       => ExpressionKind::Sequence,
+            Array(_)          => ExpressionKind::Array,
+            Member(_)         => ExpressionKind::Member,
+            ComputedMember(_) => ExpressionKind::ComputedMember,
+            PrivateMember(_)  => ExpressionKind::PrivateMember,
+            Chain(_)          => ExpressionKind::Chain,
+            MetaProperty(_)   => ExpressionKind::MetaProperty,
+            Call(_)           => ExpressionKind::Call,
+            Binary(_)         => ExpressionKind::Binary,
+            Prefix(_)         => ExpressionKind::Prefix,
+            Postfix(_)        => ExpressionKind::Postfix,
+            Conditional(_)    => ExpressionKind::Conditional,
+            Template(_)       => ExpressionKind::Template,
+            TaggedTemplate(_) => ExpressionKind::TaggedTemplate,
+            Spread(_)         => ExpressionKind::Spread,
+            Yield(_)          => ExpressionKind::Yield,
+            Await(_)          => ExpressionKind::Await,
+            Arrow(_)          => ExpressionKind::Arrow,
+            Object(_)         => ExpressionKind::Object,
+            Function(_)       => ExpressionKind::Function,
+            Class(_)          => ExpressionKind::Class,
+        }
+    }
+
     #[inline]
     pub fn binding_power(&self) -> u8 {
         use self::Expression::*;
 
         match *self {
-            Member(_) | MetaProperty(_) | Arrow(_) => 18,
+            Member(_) | MetaProperty(_) => 18,
 
             Call(_) => 17,
 
-            Prefix(_) => 15,
+            Prefix(_) | Await(_) => 15,
 
             Binary(BinaryExpression { ref operator, .. })   |
             Postfix(PostfixExpression { ref operator, .. }) => operator.binding_power(),
 
             Conditional(_) => 4,
 
+            Arrow(_) | Yield(_) => 3,
+
             Sequence(_) => 0,
 
             _  => 100,
         }
     }
 
+    /// Get the sub-expression printed first when this expression is printed
+    /// without parentheses, if any.
     #[inline]
-    pub fn is_allowed_as_bare_statement(&self) -> bool {
+    pub fn left(&self) -> Option<&Expression<'ast>> {
         use self::Expression::*;
 
         match *self {
-            Object(_)   |
-            Function(_) |
-            Class(_)    => false,
-            _           => true,
+            Sequence(ref sequence)       => sequence.body.iter().next().map(|expr| &expr.item),
+            Member(ref member)           => Some(&member.object.item),
+            ComputedMember(ref member)   => Some(&member.object.item),
+            PrivateMember(ref member)    => Some(&member.object.item),
+            Call(ref call)               => Some(&call.callee.item),
+            Binary(ref binary)           => Some(&binary.left.item),
+            Postfix(ref postfix)         => Some(&postfix.operand.item),
+            Conditional(ref conditional) => Some(&conditional.test.item),
+            TaggedTemplate(ref tagged)   => Some(&tagged.tag.item),
+            _                            => None,
+        }
+    }
+
+    /// Check whether the expression can be printed as an expression statement
+    /// without parentheses, that is it doesn't start with `{`, `function`,
+    /// `class` or `let [`.
+    #[inline]
+    pub fn is_allowed_as_bare_statement(&self) -> bool {
+        use self::Expression::*;
+
+        let mut expression = self;
+
+        loop {
+            match *expression {
+                Object(_)   |
+                Function(_) |
+                Class(_)    => return false,
+                ComputedMember(ComputedMemberExpression { ref object, .. }) if object.item == Identifier("let") => {
+                    return false
+                },
+                _ => {},
+            }
+
+            match expression.left() {
+                Some(left) => expression = left,
+                None       => return true,
+            }
+        }
+    }
+
+    /// Check whether the expression can be printed as the body of an arrow
+    /// function without parentheses, that is it doesn't start with `{` and
+    /// isn't a sequence.
+    #[inline]
+    pub fn is_allowed_as_arrow_body(&self) -> bool {
+        use self::Expression::*;
+
+        if let Sequence(_) = *self {
+            return false;
+        }
+
+        let mut expression = self;
+
+        loop {
+            if let Object(_) = *expression {
+                return false;
+            }
+
+            match expression.left() {
+                Some(left) => expression = left,
+                None       => return true,
+            }
+        }
+    }
+
+    /// Check whether the expression can be printed as the initializer of
+    /// a `for` loop, or the left side of a `for`-`in` loop, without
+    /// parentheses, that is it doesn't start with `let [`.
+    #[inline]
+    pub fn is_allowed_as_for_init(&self) -> bool {
+        use self::Expression::*;
+
+        let mut expression = self;
+
+        loop {
+            if let ComputedMember(ComputedMemberExpression { ref object, .. }) = *expression {
+                if object.item == Identifier("let") {
+                    return false;
+                }
+            }
+
+            match expression.left() {
+                Some(left) => expression = left,
+                None       => return true,
+            }
+        }
+    }
+
+    /// Check whether the expression can be printed as the left side of a
+    /// `for`-`of` loop without parentheses, that is it doesn't start with
+    /// `let` and isn't `async`, which would be read as an arrow function.
+    #[inline]
+    pub fn is_allowed_as_for_of_left(&self) -> bool {
+        use self::Expression::*;
+
+        if let Identifier("async") = *self {
+            return false;
+        }
+
+        let mut expression = self;
+
+        loop {
+            if let Identifier("let") = *expression {
+                return false;
+            }
+
+            match expression.left() {
+                Some(left) => expression = left,
+                None       => return true,
+            }
         }
     }
 
@@ -225,12 +506,215 @@ impl<'ast> Expression<'ast> {
         use self::Expression::*;
 
         match *self {
-            Identifier(_) |
-            Member(_)     |
-            Object(_)     |
-            Array(_)      |
-            Spread(_)     => true,
-            _             => false
+            Identifier(_)     |
+            Object(_)         |
+            Array(_)          |
+            Spread(_)         => true,
+            Member(_)         |
+            ComputedMember(_) |
+            PrivateMember(_)  => !self.is_optional_chain(),
+            _                 => false
+        }
+    }
+
+    /// Check whether the expression is part of an optional chain, that is
+    /// `?.` is used on it or on the objects and callees it's made of, as
+    /// in `a?.b.c`.
+    #[inline]
+    pub fn is_optional_chain(&self) -> bool {
+        use self::Expression::*;
+
+        let mut expression = self;
+
+        loop {
+            expression = match *expression {
+                Member(MemberExpression { optional: true, .. })                 |
+                ComputedMember(ComputedMemberExpression { optional: true, .. }) |
+                PrivateMember(PrivateMemberExpression { optional: true, .. })   |
+                Call(CallExpression { optional: true, .. })                     => return true,
+                Member(ref member)                                              => &member.object.item,
+                ComputedMember(ref member)                                      => &member.object.item,
+                PrivateMember(ref member)                                       => &member.object.item,
+                Call(ref call)                                                  => &call.callee.item,
+                _                                                               => return false,
+            }
         }
     }
 }
+
+impl<'ast> Node<'ast, Expression<'ast>> {
+    /// Call `f` on this expression and then on each of its sub-expressions,
+    /// parents before children in source order, until `f` breaks. Quicker
+    /// than a `Visitor` for queries such as whether an expression awaits,
+    /// as there is no dynamic dispatch. Functions, arrow functions and
+    /// classes are passed to `f` but not walked into, as they are scopes of
+    /// their own with statements; references of shorthand properties have
+    /// no expression to be passed.
+    #[inline]
+    pub fn walk<B, F>(self, mut f: F) -> ControlFlow<B> where
+        F: FnMut(ExpressionNode<'ast>) -> ControlFlow<B>,
+    {
+        walk(self, &mut f)
+    }
+}
+
+fn walk<'ast, B, F>(node: ExpressionNode<'ast>, f: &mut F) -> ControlFlow<B> where
+    F: FnMut(ExpressionNode<'ast>) -> ControlFlow<B>,
+{
+    use self::Expression::*;
+
+    f(node)?;
+
+    match node.get().item {
+        Sequence(SequenceExpression { body })   |
+        Array(ArrayExpression { body })         => walk_list(body, f),
+        Member(member)                          => walk(member.object, f),
+        PrivateMember(member)                   => walk(member.object, f),
+        Chain(chain)                            => walk(chain.expression, f),
+        ComputedMember(member)                  => {
+            walk(member.object, f)?;
+            walk(member.property, f)
+        },
+        Call(call)                              => {
+            walk(call.callee, f)?;
+            walk_list(call.arguments, f)
+        },
+        Binary(binary)                          => {
+            walk(binary.left, f)?;
+            walk(binary.right, f)
+        },
+        Prefix(PrefixExpression { operand, .. })   |
+        Postfix(PostfixExpression { operand, .. }) => walk(operand, f),
+        Conditional(conditional)                => {
+            walk(conditional.test, f)?;
+            walk(conditional.consequent, f)?;
+            walk(conditional.alternate, f)
+        },
+        Template(template)                      => walk_list(template.expressions, f),
+        TaggedTemplate(tagged)                  => {
+            walk(tagged.tag, f)?;
+            walk_list(tagged.quasi.expressions, f)
+        },
+        Spread(SpreadExpression { argument })   |
+        Await(AwaitExpression { argument })     => walk(argument, f),
+        Yield(YieldExpression { argument, .. }) => match argument {
+            Some(argument) => walk(argument, f),
+            None           => ControlFlow::Continue(()),
+        },
+        Object(object)                          => {
+            for property in &object.body {
+                match property.item {
+                    Property::ShorthandDefault { value, .. } => walk(value, f)?,
+                    Property::Literal { key, value }         => {
+                        if let PropertyKey::Computed(key) = key.item {
+                            walk(key, f)?;
+                        }
+                        walk(value, f)?;
+                    },
+                    Property::Method { key, .. }             => {
+                        if let PropertyKey::Computed(key) = key.item {
+                            walk(key, f)?;
+                        }
+                    },
+                    Property::Spread { argument }            => walk(argument, f)?,
+                    Property::Shorthand(_)                   => {},
+                }
+            }
+
+            ControlFlow::Continue(())
+        },
+        Void              |
+        This(_)           |
+        Super(_)          |
+        Identifier(_)     |
+        Literal(_)        |
+        MetaProperty(_)   |
+        Arrow(_)          |
+        Function(_)       |
+        Class(_)          => ControlFlow::Continue(()),
+    }
+}
+
+#[inline]
+fn walk_list<'ast, B, F>(list: ExpressionList<'ast>, f: &mut F) -> ControlFlow<B> where
+    F: FnMut(ExpressionNode<'ast>) -> ControlFlow<B>,
+{
+    for expression in &list {
+        walk(*expression, f)?;
+    }
+
+    ControlFlow::Continue(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use module::Module;
+    use parser::parse;
+    use allocated::*;
+
+    fn expression<'ast>(module: &'ast Module<'ast>) -> ExpressionNode<'ast> {
+        match module.body().first_element().unwrap().item {
+            Statement::Expression(expression) => expression,
+            _ => panic!("Expected an expression statement"),
+        }
+    }
+
+    fn walked(source: &str) -> Vec<ExpressionKind> {
+        let module = parse(source).unwrap();
+        let mut kinds = Vec::new();
+
+        let walk = expression(&module).walk::<(), _>(|node| {
+            kinds.push(node.kind());
+            ControlFlow::Continue(())
+        });
+
+        assert!(walk.is_continue());
+
+        kinds
+    }
+
+    #[test]
+    fn walk_order() {
+        use self::ExpressionKind::*;
+
+        assert_eq!(walked("a.b(c + 1, ...d)"), vec![Call, Member, Identifier, Binary, Identifier, Literal, Spread, Identifier]);
+        assert_eq!(walked("a ? `${b}` : c[d]"), vec![Conditional, Identifier, Template, Identifier, ComputedMember, Identifier, Identifier]);
+        assert_eq!(walked("({ a, [b]: c, d() {}, ...e })"), vec![Object, Identifier, Identifier, Identifier]);
+        assert_eq!(walked("[f(() => a, function () { b }), class extends c {}]"), vec![Array, Call, Identifier, Arrow, Function, Class]);
+    }
+
+    #[test]
+    fn walk_early_exit() {
+        let module = parse("async () => a + f(b, await c, d)").unwrap();
+        let body = match expression(&module).item {
+            Expression::Arrow(ArrowExpression { body: ArrowBody::Expression(body), .. }) => body,
+            _ => panic!("Expected an arrow function"),
+        };
+        let mut count = 0;
+
+        let found = body.walk(|node| {
+            count += 1;
+
+            match node.item {
+                Expression::Await(await) => ControlFlow::Break(await.argument),
+                _                        => ControlFlow::Continue(()),
+            }
+        });
+
+        assert_eq!(found.break_value().map(|argument| argument.item), Some(Expression::Identifier("c")));
+        assert_eq!(count, 6);
+
+        let references_x = |source| {
+            let module = parse(source).unwrap();
+
+            expression(&module).walk(|node| match node.item {
+                Expression::Identifier("x") => ControlFlow::Break(()),
+                _                           => ControlFlow::Continue(()),
+            }).is_break()
+        };
+
+        assert!(references_x("a[b] = x.y"));
+        assert!(!references_x("a = () => x"));
+    }
+}
@@ -1,5 +1,5 @@
 use ast::{Node, Loc, IdentifierNode, ExpressionNode};
-use ast::{BlockNode, Statement, PatternList, PropertyKey};
+use ast::{BlockNode, Statement, StatementList, PatternList, PropertyKey};
 
 pub trait Name<'ast>: Copy {
     fn empty() -> Self;
@@ -66,6 +66,9 @@ impl<'ast> From<Option<IdentifierNode<'ast>>> for OptionalName<'ast> {
 pub struct Function<'ast, N: Name<'ast>> {
     pub name: N,
     pub generator: bool,
+
+    /// Whether it's an `async` function, `async` being a keyword of Rust
+    pub is_async: bool,
     pub params: PatternList<'ast>,
     pub body: BlockNode<'ast, Statement<'ast>>,
 }
@@ -87,10 +90,15 @@ pub enum ClassMember<'ast> {
         kind: MethodKind,
         value: Node<'ast, Function<'ast, EmptyName>>,
     },
-    Literal {
+    /// Field, `x = 1;` or `static x;`, the `value` being its initializer.
+    Property {
         is_static: bool,
         key: Node<'ast, PropertyKey<'ast>>,
-        value: ExpressionNode<'ast>,
+        value: Option<ExpressionNode<'ast>>,
+    },
+    /// Static initialization block, `static { init(); }`.
+    StaticBlock {
+        body: StatementList<'ast>,
     }
 }
 
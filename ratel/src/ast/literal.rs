@@ -1,11 +1,252 @@
+use std::borrow::Cow;
+use std::char;
+use allocated::*;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Literal<'ast> {
-    Undefined,
     Null,
-    True,
-    False,
-    Number(&'ast str),
-    Binary(&'ast str),
-    String(&'ast str),
+    Boolean(bool),
+    Number(NumberLiteral<'ast>),
+    String(StringLiteral<'ast>),
     RegEx(&'ast str),
 }
+
+/// Number literal in any base, with its value.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NumberLiteral<'ast> {
+    pub value: f64,
+
+    /// Source of the literal, such as `0x1F` or `1e3`
+    pub raw: &'ast str,
+}
+
+/// String literal with its value, see `decode_string`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StringLiteral<'ast> {
+    pub value: &'ast str,
+
+    /// Source of the literal, quotes included
+    pub raw: &'ast str,
+}
+
+impl<'ast> NumberLiteral<'ast> {
+    /// Literal of the `raw` source, which must be a valid number literal.
+    #[inline]
+    pub fn new(raw: &'ast str) -> Self {
+        NumberLiteral {
+            value: number_value(raw),
+            raw,
+        }
+    }
+}
+
+impl<'ast> Literal<'ast> {
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Literal::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_number(&self) -> Option<f64> {
+        match *self {
+            Literal::Number(number) => Some(number.value),
+            _ => None,
+        }
+    }
+
+    /// Value of a string literal, with the escapes decoded.
+    #[inline]
+    pub fn as_str(&self) -> Option<&'ast str> {
+        match *self {
+            Literal::String(string) => Some(string.value),
+            _ => None,
+        }
+    }
+
+    /// Source of the literal.
+    #[inline]
+    pub fn raw(&self) -> &'ast str {
+        match *self {
+            Literal::Null => "null",
+            Literal::Boolean(true) => "true",
+            Literal::Boolean(false) => "false",
+            Literal::Number(number) => number.raw,
+            Literal::String(string) => string.raw,
+            Literal::RegEx(raw) => raw,
+        }
+    }
+}
+
+/// Value of a number literal, `0x`, `0o` and `0b` prefixes and legacy
+/// octal literals such as `017` included.
+pub fn number_value(raw: &str) -> f64 {
+    let (digits, radix) = match raw.get(..2) {
+        Some("0x") | Some("0X") => (&raw[2..], 16),
+        Some("0o") | Some("0O") => (&raw[2..], 8),
+        Some("0b") | Some("0B") => (&raw[2..], 2),
+        _ if raw.len() > 1 && raw.starts_with('0') && raw.bytes().all(|byte| matches!(byte, b'0'..=b'7')) => {
+            (&raw[1..], 8)
+        },
+        _ => return raw.parse().unwrap_or(0.0),
+    };
+
+    // Folded as a float, integers past 2^64 are valid literals
+    digits.chars().fold(0.0, |value, digit| {
+        value * radix as f64 + digit.to_digit(radix).unwrap_or(0) as f64
+    })
+}
+
+/// Value of a string literal from its `raw` source, quotes included.
+/// Borrows the contents of the literal if it has no escapes. Lone
+/// surrogates, which a `str` can't hold, are replaced by U+FFFD.
+pub fn decode_string(raw: &str) -> Cow<'_, str> {
+    let value = &raw[1..raw.len() - 1];
+
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut cooked = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            cooked.push(ch);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some(escaped) => escaped,
+            None => break,
+        };
+
+        let code = match escaped {
+            'b' => 0x08,
+            't' => 0x09,
+            'n' => 0x0A,
+            'v' => 0x0B,
+            'f' => 0x0C,
+            'r' => {
+                cooked.push('\r');
+                continue;
+            },
+            // Line continuations
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                continue;
+            },
+            '\n' | '\u{2028}' | '\u{2029}' => continue,
+            'x' => hex(&mut chars, 2),
+            'u' if chars.peek() == Some(&'{') => {
+                chars.next();
+
+                let mut code = 0;
+
+                for digit in chars.by_ref() {
+                    match digit.to_digit(16) {
+                        Some(digit) => code = code * 16 + digit,
+                        None => break,
+                    }
+                }
+
+                code
+            },
+            'u' => hex(&mut chars, 4),
+            // Legacy octal escapes, of up to three digits below `\377`
+            '0'..='7' => {
+                let mut code = escaped.to_digit(8).unwrap_or(0);
+                let max = if escaped <= '3' { 2 } else { 1 };
+
+                for _ in 0..max {
+                    match chars.peek().and_then(|digit| digit.to_digit(8)) {
+                        Some(digit) => {
+                            code = code * 8 + digit;
+                            chars.next();
+                        },
+                        None => break,
+                    }
+                }
+
+                code
+            },
+            escaped => {
+                cooked.push(escaped);
+                continue;
+            },
+        };
+
+        // High surrogate followed by an escaped low surrogate
+        if (0xD800..0xDC00).contains(&code) {
+            let mut lookahead = chars.clone();
+
+            if lookahead.next() == Some('\\') && lookahead.next() == Some('u') {
+                let low = hex(&mut lookahead, 4);
+
+                if (0xDC00..0xE000).contains(&low) {
+                    chars = lookahead;
+                    cooked.push(char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)).unwrap_or('\u{FFFD}'));
+                    continue;
+                }
+            }
+        }
+
+        cooked.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+    }
+
+    Cow::Owned(cooked)
+}
+
+fn hex<I: Iterator<Item = char>>(chars: &mut I, digits: usize) -> u32 {
+    chars.take(digits).fold(0, |code, digit| code * 16 + digit.to_digit(16).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number_value("0"), 0.0);
+        assert_eq!(number_value("42"), 42.0);
+        assert_eq!(number_value("1.5e3"), 1500.0);
+        assert_eq!(number_value(".5"), 0.5);
+        assert_eq!(number_value("5."), 5.0);
+        assert_eq!(number_value("0xFF"), 255.0);
+        assert_eq!(number_value("0o17"), 15.0);
+        assert_eq!(number_value("0b101"), 5.0);
+        assert_eq!(number_value("017"), 15.0);
+        assert_eq!(number_value("019"), 19.0);
+        assert_eq!(number_value("0x10000000000000000"), 18446744073709551616.0);
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(decode_string("'foo'"), Cow::Borrowed("foo"));
+        assert_eq!(decode_string(r#""a\"b""#), "a\"b");
+        assert_eq!(decode_string(r"'\b\t\n\v\f\r\\'"), "\u{8}\t\n\u{b}\u{c}\r\\");
+        assert_eq!(decode_string(r"'\x41B\u{43}\u{1F600}'"), "ABC\u{1F600}");
+        assert_eq!(decode_string(r"'😀'"), "\u{1F600}");
+        assert_eq!(decode_string(r"'\uD83D!'"), "\u{FFFD}!");
+        assert_eq!(decode_string(r"'\0\07\101\400\8'"), "\0\u{7}A\u{20}08");
+        assert_eq!(decode_string("'a\\\nb\\\r\nc'"), "abc");
+        assert_eq!(decode_string(r"'\a\'\q'"), "a'q");
+    }
+
+    #[test]
+    fn accessors() {
+        let string = Literal::String(StringLiteral { value: "foo", raw: "'foo'" });
+
+        assert_eq!(string.as_str(), Some("foo"));
+        assert_eq!(string.raw(), "'foo'");
+        assert_eq!(string.as_number(), None);
+        assert_eq!(Literal::Number(NumberLiteral::new("0x10")).as_number(), Some(16.0));
+        assert_eq!(Literal::Boolean(false).as_bool(), Some(false));
+        assert_eq!(Literal::Boolean(false).raw(), "false");
+        assert_eq!(Literal::Null.raw(), "null");
+    }
+}
@@ -15,11 +15,11 @@ pub use ast::variable::*;
 pub use ast::operator::*;
 pub use ast::node::Node;
 // pub use ast::types::{Type, Primitive};
-pub use ast::expression::{Expression, Property, PropertyKey};
-pub use ast::statement::{Statement, Declarator, BlockStatement};
+pub use ast::expression::{Expression, ExpressionKind, Property, PropertyKey};
+pub use ast::statement::{Statement, StatementKind, Terminator, Declarator, BlockStatement};
 pub use ast::function::{Function, Class, ClassMember, Method, MethodKind};
 pub use ast::function::{Name, EmptyName, OptionalName, MandatoryName};
-pub use ast::literal::Literal;
+pub use ast::literal::{Literal, NumberLiteral, StringLiteral, number_value, decode_string};
 
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -18,6 +18,13 @@ impl<'ast, T: 'ast> Node<'ast, T> {
         }
     }
 
+    /// Get the pointed to `Loc`, borrowed for the lifetime of the AST
+    /// rather than of the `Node`.
+    #[inline]
+    pub fn get(&self) -> &'ast Loc<T> {
+        self.inner.get()
+    }
+
     #[inline]
     pub fn set(&self, ptr: &'ast Loc<T>) {
         self.inner.set(ptr)
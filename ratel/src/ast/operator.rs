@@ -190,12 +190,12 @@ impl OperatorKind {
             BitwiseNot       |
             Typeof           |
             Void             |
-            Delete           => 15,
+            Delete           |
+            Exponent         => 15,
 
             Multiplication   |
             Division         |
-            Remainder        |
-            Exponent         => 14,
+            Remainder        => 14,
 
             Addition         |
             Subtraction      => 13,
@@ -328,6 +328,50 @@ impl OperatorKind {
         }
     }
 
+    /// Binary operator applied by a compound assignment, such as `+`
+    /// for `+=`.
+    #[inline]
+    pub fn compound_operator(&self) -> Option<OperatorKind> {
+        match *self {
+            AddAssign        => Some(Addition),
+            SubtractAssign   => Some(Subtraction),
+            ExponentAssign   => Some(Exponent),
+            MultiplyAssign   => Some(Multiplication),
+            DivideAssign     => Some(Division),
+            RemainderAssign  => Some(Remainder),
+            BSLAssign        => Some(BitShiftLeft),
+            BSRAssign        => Some(BitShiftRight),
+            UBSRAssign       => Some(UBitShiftRight),
+            BitAndAssign     => Some(BitwiseAnd),
+            BitXorAssign     => Some(BitwiseXor),
+            BitOrAssign      => Some(BitwiseOr),
+
+            _                => None
+        }
+    }
+
+    /// Compound assignment applying this binary operator, such as `+=`
+    /// for `+`, the reverse of `compound_operator`.
+    #[inline]
+    pub fn compound_assignment(&self) -> Option<OperatorKind> {
+        match *self {
+            Addition         => Some(AddAssign),
+            Subtraction      => Some(SubtractAssign),
+            Exponent         => Some(ExponentAssign),
+            Multiplication   => Some(MultiplyAssign),
+            Division         => Some(DivideAssign),
+            Remainder        => Some(RemainderAssign),
+            BitShiftLeft     => Some(BSLAssign),
+            BitShiftRight    => Some(BSRAssign),
+            UBitShiftRight   => Some(UBSRAssign),
+            BitwiseAnd       => Some(BitAndAssign),
+            BitwiseXor       => Some(BitXorAssign),
+            BitwiseOr        => Some(BitOrAssign),
+
+            _                => None
+        }
+    }
+
     #[inline]
     pub fn category(&self) -> OperatorCategory {
         match *self {
@@ -1,5 +1,5 @@
 use ast::{Node, NodeList, DeclarationKind, Function, Class, MandatoryName, IdentifierNode};
-use ast::{ExpressionNode, StatementNode, StatementList, Block, BlockNode, Pattern};
+use ast::{ExpressionNode, StatementNode, StatementList, Block, BlockNode, Pattern, StringLiteral};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Declarator<'ast> {
@@ -97,7 +97,7 @@ pub struct TryStatement<'ast> {
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct LabeledStatement<'ast> {
-    pub label: &'ast str,
+    pub label: IdentifierNode<'ast>,
     pub body: StatementNode<'ast>,
 }
 
@@ -113,6 +113,75 @@ pub struct SwitchCase<'ast> {
     pub consequent: StatementList<'ast>,
 }
 
+/// `import a, { b as c } from "d"`, or `import "d"` without specifiers.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ImportStatement<'ast> {
+    pub specifiers: NodeList<'ast, ImportSpecifier<'ast>>,
+    pub source: Node<'ast, StringLiteral<'ast>>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImportSpecifier<'ast> {
+    /// `a` of `import a from "b"`
+    Default(IdentifierNode<'ast>),
+
+    /// `* as a`
+    Namespace(IdentifierNode<'ast>),
+
+    /// `a as b` within braces, `imported` and `local` are the same node if
+    /// there is no `as`
+    Named {
+        imported: IdentifierNode<'ast>,
+        local: IdentifierNode<'ast>,
+    },
+}
+
+impl<'ast> ImportSpecifier<'ast> {
+    /// Name the specifier binds in the module.
+    #[inline]
+    pub fn local(&self) -> IdentifierNode<'ast> {
+        match *self {
+            ImportSpecifier::Default(local) |
+            ImportSpecifier::Namespace(local) |
+            ImportSpecifier::Named { local, .. } => local,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportStatement<'ast> {
+    /// `export var a = 1`, `export function a() {}` or `export class A {}`
+    Declaration(StatementNode<'ast>),
+
+    /// `export default function () {}` or `export default class {}`, a
+    /// function or class expression as the name is optional, but declaring
+    /// its name in the module if it has one
+    DefaultDeclaration(ExpressionNode<'ast>),
+
+    /// `export default a`
+    Default(ExpressionNode<'ast>),
+
+    /// `export { a, b as c }`, re-exported from the `source` if any
+    Named {
+        specifiers: NodeList<'ast, ExportSpecifier<'ast>>,
+        source: Option<Node<'ast, StringLiteral<'ast>>>,
+    },
+
+    /// `export * from "a"`, or `export * as b from "a"`
+    All {
+        exported: Option<IdentifierNode<'ast>>,
+        source: Node<'ast, StringLiteral<'ast>>,
+    },
+}
+
+/// `a as b` within the braces of an `export`, `local` and `exported` are the
+/// same node if there is no `as`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ExportSpecifier<'ast> {
+    pub local: IdentifierNode<'ast>,
+    pub exported: IdentifierNode<'ast>,
+}
+
 pub type BlockStatement<'ast> = Block<'ast, Statement<'ast>>;
 pub type FunctionStatement<'ast> = Function<'ast, MandatoryName<'ast>>;
 pub type ClassStatement<'ast> = Class<'ast, MandatoryName<'ast>>;
@@ -137,7 +206,49 @@ pub enum Statement<'ast> {
     Labeled(LabeledStatement<'ast>),
     Function(FunctionStatement<'ast>),
     Class(ClassStatement<'ast>),
-    Switch(SwitchStatement<'ast>)
+    Switch(SwitchStatement<'ast>),
+    Import(ImportStatement<'ast>),
+    Export(ExportStatement<'ast>),
+}
+
+/// How a statement that requires a semicolon was terminated, see
+/// `ParserOptions::terminators` and `Module::terminator`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Terminator {
+    /// Explicit `;`
+    Semicolon,
+
+    /// Semicolon inserted by ASI, after a line break or at the end of program
+    Asi,
+
+    /// Semicolon inserted by ASI before the `}` closing the enclosing block
+    BraceClose,
+}
+
+/// Payload-free discriminant of a `Statement`, cheap to copy, hash and compare.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum StatementKind {
+    Empty,
+    Expression,
+    Declaration,
+    Return,
+    Break,
+    Continue,
+    Throw,
+    If,
+    While,
+    Do,
+    For,
+    ForIn,
+    ForOf,
+    Try,
+    Block,
+    Labeled,
+    Function,
+    Class,
+    Switch,
+    Import,
+    Export,
 }
 
 macro_rules! impl_from {
@@ -169,7 +280,9 @@ impl_from! {
     ContinueStatement => Continue,
     FunctionStatement => Function,
     ClassStatement => Class,
-    SwitchStatement => Switch
+    SwitchStatement => Switch,
+    ImportStatement => Import,
+    ExportStatement => Export
 }
 
 impl<'ast> From<DeclarationStatement<'ast>> for ForInit<'ast> {
@@ -187,6 +300,35 @@ impl<'ast> From<ExpressionNode<'ast>> for ForInit<'ast> {
 }
 
 impl<'ast> Statement<'ast> {
+    #[inline]
+    pub fn kind(&self) -> StatementKind {
+        use self::Statement::*;
+
+        match *self {
+            Empty          => StatementKind::Empty,
+            Expression(_)  => StatementKind::Expression,
+            Declaration(_) => StatementKind::Declaration,
+            Return(_)      => StatementKind::Return,
+            Break(_)       => StatementKind::Break,
+            Continue(_)    => StatementKind::Continue,
+            Throw(_)       => StatementKind::Throw,
+            If(_)          => StatementKind::If,
+            While(_)       => StatementKind::While,
+            Do(_)          => StatementKind::Do,
+            For(_)         => StatementKind::For,
+            ForIn(_)       => StatementKind::ForIn,
+            ForOf(_)       => StatementKind::ForOf,
+            Try(_)         => StatementKind::Try,
+            Block(_)       => StatementKind::Block,
+            Labeled(_)     => StatementKind::Labeled,
+            Function(_)    => StatementKind::Function,
+            Class(_)       => StatementKind::Class,
+            Switch(_)      => StatementKind::Switch,
+            Import(_)      => StatementKind::Import,
+            Export(_)      => StatementKind::Export,
+        }
+    }
+
     #[inline]
     pub fn is_block(&self) -> bool {
         match *self {
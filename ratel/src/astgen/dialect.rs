@@ -0,0 +1,1313 @@
+//! Pluggable backends for the AST serialization. The `Serialize` impls in
+//! `astgen` always walk the tree as ESTree; a `Dialect` wraps the target
+//! `Serializer` and renames node types and fields on the fly, so every
+//! output format shares the one traversal and still streams.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use serde::ser::{self, Serialize, Serializer, SerializeStruct};
+
+/// How a node should be written in a given `Dialect`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Shape {
+    /// Write the node as ESTree would
+    Keep,
+
+    /// Write the node with a different `type`
+    Node(&'static str),
+
+    /// Replace the node with the value of one of its fields
+    Unwrap(&'static str),
+}
+
+pub trait Dialect {
+    /// Shape of a node of `kind` (struct name given to the `Serializer`,
+    /// see `SerializeInLoc::in_loc_as`), written as `field` of a `parent`
+    /// node. `parent` and `field` are empty for the root node.
+    fn node(&self, kind: &'static str, parent: &'static str, field: &'static str) -> Shape;
+
+    /// Key of `field` of a node of `kind`, `None` drops the field.
+    fn field(&self, kind: &'static str, field: &'static str) -> Option<&'static str>;
+
+    /// Whether the node held by `field` of a node of `kind` is written as
+    /// fields of that node instead, such as the function of a method. The
+    /// `type`, `start` and `end` of the flattened node are dropped.
+    fn flatten(&self, _kind: &'static str, _field: &'static str) -> bool {
+        false
+    }
+
+    /// String to write as `field` of a node of `kind` in place of the value
+    /// ESTree has, `None` keeps the value.
+    fn value(&self, _kind: &'static str, _field: &'static str) -> Option<&'static str> {
+        None
+    }
+
+    /// Node to wrap the root node in, if any.
+    fn wrapper(&self) -> Option<Wrapper> {
+        None
+    }
+
+    /// Line and column of the byte `offset`. When provided, every node gets a
+    /// `loc` field next to `start` and `end`.
+    fn position(&self, _offset: u32) -> Option<Position> {
+        None
+    }
+
+    /// Id of the node of `kind` spanning `start..end`. When provided, the
+    /// node gets a `nodeId` field after `start`, `end` and `loc`. Called
+    /// once per node, in the order the nodes end, see `StableIds`.
+    fn id(&self, _kind: &'static str, _start: u32, _end: u32) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Wrapper {
+    /// Type of the wrapping node
+    pub kind: &'static str,
+
+    /// Field of the wrapping node that holds the root node
+    pub field: &'static str,
+
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Lines start at 1, columns at 0 and are counted in UTF-16 code units.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+struct SourceLocation<'a> {
+    start: Position,
+    end: Position,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifierName: Option<&'a str>,
+}
+
+/// Plain ESTree, same as serializing the AST directly.
+#[derive(Debug, Clone, Copy)]
+pub struct Estree;
+
+impl Dialect for Estree {
+    fn node(&self, _: &'static str, _: &'static str, _: &'static str) -> Shape {
+        Shape::Keep
+    }
+
+    fn field(&self, _: &'static str, field: &'static str) -> Option<&'static str> {
+        Some(field)
+    }
+}
+
+/// Shift AST (http://shift-ast.org/). Nodes that don't have a counterpart
+/// in Shift, or that Shift splits into several nodes, are kept as ESTree.
+/// Locations are kept as the `start` and `end` fields.
+#[derive(Debug, Clone, Copy)]
+pub struct Shift;
+
+impl Dialect for Shift {
+    fn node(&self, kind: &'static str, parent: &'static str, field: &'static str) -> Shape {
+        use self::Shape::*;
+
+        match (kind, parent, field) {
+            ("Identifier", "MemberExpression", "property")   => Unwrap("name"),
+            ("Identifier", "AssignmentExpression", "left")   |
+            ("Identifier", "CompoundAssignmentExpression", "left") => Node("AssignmentTargetIdentifier"),
+            ("Identifier", _, "params")                      |
+            ("Identifier", _, "id")                          |
+            ("Identifier", "CatchClause", "param")           |
+            ("Identifier", "AssignmentPattern", "left")      |
+            ("Identifier", "RestElement", "argument")        => Node("BindingIdentifier"),
+            ("Identifier", _, _)                             => Node("IdentifierExpression"),
+            ("Program", _, _)                                => Node("Script"),
+            ("StringLiteral", _, _)                          => Node("LiteralStringExpression"),
+            ("NumericLiteral", _, _)                         => Node("LiteralNumericExpression"),
+            ("BooleanLiteral", _, _)                         => Node("LiteralBooleanExpression"),
+            ("NullLiteral", _, _)                            => Node("LiteralNullExpression"),
+            ("RegExpLiteral", _, _)                          => Node("LiteralRegExpExpression"),
+            ("MemberExpression", _, _)                       => Node("StaticMemberExpression"),
+            ("ComputedMemberExpression", _, _)               => Node("ComputedMemberExpression"),
+            ("CompoundAssignmentExpression", _, _)           => Node("CompoundAssignmentExpression"),
+            ("LogicalExpression", _, _)                      => Node("BinaryExpression"),
+            ("AssignmentExpression", _, _)                   => Node("AssignmentExpression"),
+            ("ArrowFunctionExpression", _, _)                => Node("ArrowExpression"),
+            ("Property", _, _)                               |
+            ("MethodProperty", _, _)                         => Node("DataProperty"),
+            ("CatchClause", _, _)                            => Node("CatchClause"),
+            _                                                => Keep,
+        }
+    }
+
+    fn field(&self, kind: &'static str, field: &'static str) -> Option<&'static str> {
+        match (kind, field) {
+            (_, "raw")                                       |
+            ("NullLiteral", "value")                         |
+            ("MemberExpression", "computed")                 |
+            ("ComputedMemberExpression", "computed")         |
+            ("UnaryExpression", "prefix")                    |
+            ("Property", "computed")                         |
+            ("Property", "shorthand")                        |
+            ("Property", "method")                           |
+            ("Property", "kind")                             |
+            ("MethodProperty", "computed")                   |
+            ("MethodProperty", "shorthand")                  |
+            ("MethodProperty", "method")                     |
+            ("MethodProperty", "kind")                       => None,
+            ("Program", "body")                              => Some("statements"),
+            ("ComputedMemberExpression", "property")         => Some("expression"),
+            ("AssignmentExpression", "left")                 |
+            ("CompoundAssignmentExpression", "left")         => Some("binding"),
+            ("AssignmentExpression", "right")                |
+            ("CompoundAssignmentExpression", "right")        => Some("expression"),
+            ("UnaryExpression", "argument")                  |
+            ("UpdateExpression", "argument")                 => Some("operand"),
+            ("UpdateExpression", "prefix")                   => Some("isPrefix"),
+            ("VariableDeclaration", "declarations")          => Some("declarators"),
+            ("VariableDeclarator", "id")                     => Some("binding"),
+            ("FunctionDeclaration", "id")                    |
+            ("FunctionExpression", "id")                     |
+            ("ClassDeclaration", "id")                       |
+            ("ClassExpression", "id")                        => Some("name"),
+            ("FunctionDeclaration", "generator")             |
+            ("FunctionExpression", "generator")              => Some("isGenerator"),
+            ("FunctionDeclaration", "async")                 |
+            ("FunctionExpression", "async")                  |
+            ("ArrowFunctionExpression", "async")             => Some("isAsync"),
+            ("ClassDeclaration", "superClass")               |
+            ("ClassExpression", "superClass")                => Some("super"),
+            ("ReturnStatement", "argument")                  |
+            ("ThrowStatement", "argument")                   => Some("expression"),
+            ("TryStatement", "handler")                      => Some("catchClause"),
+            ("CatchClause", "param")                         => Some("binding"),
+            ("Property", "key")                              |
+            ("MethodProperty", "key")                        => Some("name"),
+            ("Property", "value")                            |
+            ("MethodProperty", "value")                      => Some("expression"),
+            _                                                => Some(field),
+        }
+    }
+}
+
+/// Babel's flavor of ESTree, see `Babel::new`. The module is wrapped in a
+/// `File` node, and nodes get a `loc` with the line and column. Literals
+/// are written without their `raw` value, and optional chains without a
+/// `ChainExpression`. Object methods and class members are written as an
+/// `ObjectMethod`, `ClassMethod`, `ClassProperty` or their private
+/// counterparts, with the fields of the function in the method itself.
+/// Private names are kept as the `PrivateIdentifier` of ESTree.
+#[derive(Debug, Clone)]
+pub struct Babel<'src> {
+    source: &'src str,
+
+    /// Byte offsets at which the lines start
+    lines: Vec<u32>,
+}
+
+impl<'src> Babel<'src> {
+    /// `source` must be the source the serialized AST was parsed from.
+    pub fn new(source: &'src str) -> Self {
+        let mut lines = vec![0];
+        let mut bytes = source.bytes().enumerate().peekable();
+
+        while let Some((index, byte)) = bytes.next() {
+            match byte {
+                b'\r' if bytes.peek().map(|&(_, next)| next) == Some(b'\n') => continue,
+                b'\r' | b'\n' => lines.push(index as u32 + 1),
+                _ => {},
+            }
+        }
+
+        Babel { source, lines }
+    }
+}
+
+impl<'src> Dialect for Babel<'src> {
+    fn node(&self, kind: &'static str, _: &'static str, _: &'static str) -> Shape {
+        use self::Shape::*;
+
+        match kind {
+            "StringLiteral"    |
+            "NumericLiteral"   |
+            "BooleanLiteral"   |
+            "NullLiteral"      |
+            "RegExpLiteral"    => Node(kind),
+            "Property"         => Node("ObjectProperty"),
+            "MethodProperty"   => Node("ObjectMethod"),
+            "MethodDefinition" => Node("ClassMethod"),
+            "PrivateMethodDefinition" => Node("ClassPrivateMethod"),
+            "PropertyDefinition" => Node("ClassProperty"),
+            "PrivatePropertyDefinition" => Node("ClassPrivateProperty"),
+            "SpreadProperty"   => Node("SpreadElement"),
+            "ChainExpression"  => Unwrap("expression"),
+            _                  => Keep,
+        }
+    }
+
+    fn field(&self, kind: &'static str, field: &'static str) -> Option<&'static str> {
+        match (kind, field) {
+            (_, "raw")                    |
+            ("NullLiteral", "value")      |
+            ("Property", "kind")          |
+            ("MethodProperty", "shorthand") |
+            ("PrivatePropertyDefinition", "computed") => None,
+            _                             => Some(field),
+        }
+    }
+
+    fn flatten(&self, kind: &'static str, field: &'static str) -> bool {
+        matches!((kind, field),
+            ("MethodProperty", "value")   |
+            ("MethodDefinition", "value") |
+            ("PrivateMethodDefinition", "value")
+        )
+    }
+
+    fn value(&self, kind: &'static str, field: &'static str) -> Option<&'static str> {
+        match (kind, field) {
+            ("MethodProperty", "kind") => Some("method"),
+            _                          => None,
+        }
+    }
+
+    fn wrapper(&self) -> Option<Wrapper> {
+        Some(Wrapper {
+            kind: "File",
+            field: "program",
+            start: 0,
+            end: self.source.len() as u32,
+        })
+    }
+
+    fn position(&self, offset: u32) -> Option<Position> {
+        let line = match self.lines.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let start = self.lines[line] as usize;
+        let offset = (offset as usize).min(self.source.len());
+        let column = self.source.get(start..offset).map_or(0, |slice| slice.encode_utf16().count());
+
+        Some(Position {
+            line: line + 1,
+            column,
+        })
+    }
+}
+
+/// Adds a `nodeId` to every node written in the `Dialect` it wraps, so that
+/// tools can tell which nodes of two versions of a file are the same, eg.:
+/// `serde_json::to_string(&InDialect(&module, &StableIds::new(source, Estree)))`.
+///
+/// The id of a node is derived from its content only: its kind and its
+/// source text, followed by `-n` if `n` nodes of the same kind and text
+/// ended before it, eg. `"9e4d8b1c6c8a2f45"` or `"9e4d8b1c6c8a2f45-1"`.
+/// Between two versions of a file, a node keeps its id as long as:
+///
+/// - its own source text doesn't change, including the whitespace and the
+///   comments inside it, which covers every node it contains;
+/// - no node of the same kind and text is added or removed before it.
+///
+/// Edits to other parts of the file, moving the node around, or inserting
+/// lines before it don't change the id, as its offsets aren't used. The
+/// hash (64 bits of FNV-1a over the kind, a zero byte and the text) is part
+/// of the API: ids written by any release with the same major version are
+/// the same for the same source and dialect. A `StableIds` counts the nodes
+/// it has seen, use a new one for each serialization.
+#[derive(Debug)]
+pub struct StableIds<'src, D: Dialect> {
+    source: &'src str,
+    dialect: D,
+
+    /// Nodes seen so far, by hash
+    seen: RefCell<HashMap<u64, u32>>,
+}
+
+impl<'src, D: Dialect> StableIds<'src, D> {
+    /// `source` must be the source the serialized AST was parsed from.
+    pub fn new(source: &'src str, dialect: D) -> Self {
+        StableIds {
+            source,
+            dialect,
+            seen: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+/// 64-bit FNV-1a, unlike the hasher of `std` it's the same in every release
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+impl<'src, D: Dialect> Dialect for StableIds<'src, D> {
+    #[inline]
+    fn node(&self, kind: &'static str, parent: &'static str, field: &'static str) -> Shape {
+        self.dialect.node(kind, parent, field)
+    }
+
+    #[inline]
+    fn field(&self, kind: &'static str, field: &'static str) -> Option<&'static str> {
+        self.dialect.field(kind, field)
+    }
+
+    #[inline]
+    fn flatten(&self, kind: &'static str, field: &'static str) -> bool {
+        self.dialect.flatten(kind, field)
+    }
+
+    #[inline]
+    fn value(&self, kind: &'static str, field: &'static str) -> Option<&'static str> {
+        self.dialect.value(kind, field)
+    }
+
+    #[inline]
+    fn wrapper(&self) -> Option<Wrapper> {
+        self.dialect.wrapper()
+    }
+
+    #[inline]
+    fn position(&self, offset: u32) -> Option<Position> {
+        self.dialect.position(offset)
+    }
+
+    fn id(&self, kind: &'static str, start: u32, end: u32) -> Option<String> {
+        let text = self.source.get(start as usize..end as usize).unwrap_or("");
+        let hash = fnv1a(fnv1a(fnv1a(0xcbf29ce484222325, kind.as_bytes()), &[0]), text.as_bytes());
+
+        let mut seen = self.seen.borrow_mut();
+        let count = seen.entry(hash).or_insert(0);
+        let id = match *count {
+            0 => format!("{:016x}", hash),
+            n => format!("{:016x}-{}", hash, n),
+        };
+
+        *count += 1;
+
+        Some(id)
+    }
+}
+
+/// Serialize `value` in the given `Dialect`, eg.:
+/// `serde_json::to_string(&InDialect(&module, &Shift))`.
+pub struct InDialect<'a, T: 'a + ?Sized, D: 'a + ?Sized>(pub &'a T, pub &'a D);
+
+impl<'a, T, D> Serialize for InDialect<'a, T, D>
+where
+    T: Serialize + ?Sized,
+    D: Dialect + ?Sized,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let dialect = self.1;
+        let wrapper = match dialect.wrapper() {
+            Some(wrapper) => wrapper,
+            None => return self.0.serialize(Translate {
+                serializer,
+                dialect,
+                parent: "",
+                field: "",
+            }),
+        };
+
+        let mut state = serializer.serialize_struct(wrapper.kind, 4)?;
+        state.serialize_field("type", wrapper.kind)?;
+        state.serialize_field(wrapper.field, &Translated {
+            value: self.0,
+            dialect,
+            parent: wrapper.kind,
+            field: wrapper.field,
+        })?;
+        state.serialize_field("start", &wrapper.start)?;
+        state.serialize_field("end", &wrapper.end)?;
+
+        if let (Some(start), Some(end)) = (dialect.position(wrapper.start), dialect.position(wrapper.end)) {
+            state.serialize_field("loc", &SourceLocation { start, end, identifierName: None })?;
+        }
+
+        state.end()
+    }
+}
+
+/// `Serializer` applying the `Dialect` to everything written into it.
+pub struct Translate<'d, S, D: 'd + ?Sized> {
+    serializer: S,
+    dialect: &'d D,
+    parent: &'static str,
+    field: &'static str,
+}
+
+/// Value written through a `Translate`, as the `field` of the `parent` node.
+struct Translated<'d, 'v, T: 'v + ?Sized, D: 'd + ?Sized> {
+    value: &'v T,
+    dialect: &'d D,
+    parent: &'static str,
+    field: &'static str,
+}
+
+impl<'d, 'v, T, D> Serialize for Translated<'d, 'v, T, D>
+where
+    T: Serialize + ?Sized,
+    D: Dialect + ?Sized,
+{
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(Translate {
+            serializer,
+            dialect: self.dialect,
+            parent: self.parent,
+            field: self.field,
+        })
+    }
+}
+
+/// Sequences and maps pass the context of the enclosing field through.
+pub struct Compound<'d, C, D: 'd + ?Sized> {
+    compound: C,
+    dialect: &'d D,
+    parent: &'static str,
+    field: &'static str,
+}
+
+impl<'d, C, D: ?Sized> Compound<'d, C, D> {
+    #[inline]
+    fn wrap<'v, T: ?Sized>(&self, value: &'v T) -> Translated<'d, 'v, T, D> {
+        Translated {
+            value,
+            dialect: self.dialect,
+            parent: self.parent,
+            field: self.field,
+        }
+    }
+}
+
+pub enum Struct<'d, S: Serializer, D: 'd + ?Sized> {
+    Node {
+        state: S::SerializeStruct,
+        dialect: &'d D,
+        kind: &'static str,
+        ty: Option<&'static str>,
+
+        /// Values needed for the `loc` and `nodeId` fields
+        captured: Captured,
+    },
+    Unwrap {
+        serializer: Option<S>,
+        ok: Option<S::Ok>,
+        field: &'static str,
+    },
+}
+
+impl<'d, S, D> Serializer for Translate<'d, S, D>
+where
+    S: Serializer,
+    D: Dialect + ?Sized,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    type SerializeSeq = Compound<'d, S::SerializeSeq, D>;
+    type SerializeTuple = Compound<'d, S::SerializeTuple, D>;
+    type SerializeTupleStruct = Compound<'d, S::SerializeTupleStruct, D>;
+    type SerializeTupleVariant = Compound<'d, S::SerializeTupleVariant, D>;
+    type SerializeMap = Compound<'d, S::SerializeMap, D>;
+    type SerializeStruct = Struct<'d, S, D>;
+    type SerializeStructVariant = Compound<'d, S::SerializeStructVariant, D>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> { self.serializer.serialize_bool(v) }
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> { self.serializer.serialize_i8(v) }
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> { self.serializer.serialize_i16(v) }
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> { self.serializer.serialize_i32(v) }
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> { self.serializer.serialize_i64(v) }
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> { self.serializer.serialize_u8(v) }
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> { self.serializer.serialize_u16(v) }
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> { self.serializer.serialize_u32(v) }
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> { self.serializer.serialize_u64(v) }
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> { self.serializer.serialize_f32(v) }
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> { self.serializer.serialize_f64(v) }
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> { self.serializer.serialize_char(v) }
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> { self.serializer.serialize_str(v) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> { self.serializer.serialize_bytes(v) }
+    fn serialize_none(self) -> Result<S::Ok, S::Error> { self.serializer.serialize_none() }
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> { self.serializer.serialize_unit() }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<S::Ok, S::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.serializer.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(self, name: &'static str, index: u32, variant: &'static str) -> Result<S::Ok, S::Error> {
+        self.serializer.serialize_unit_variant(name, index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<S::Ok, S::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T
+    ) -> Result<S::Ok, S::Error> {
+        let value = Translated { value, dialect: self.dialect, parent: self.parent, field: self.field };
+
+        self.serializer.serialize_newtype_variant(name, index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let seq = self.serializer.serialize_seq(len)?;
+        Ok(Compound { compound: seq, dialect: self.dialect, parent: self.parent, field: self.field })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let tuple = self.serializer.serialize_tuple(len)?;
+        Ok(Compound { compound: tuple, dialect: self.dialect, parent: self.parent, field: self.field })
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let tuple = self.serializer.serialize_tuple_struct(name, len)?;
+        Ok(Compound { compound: tuple, dialect: self.dialect, parent: self.parent, field: self.field })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let tuple = self.serializer.serialize_tuple_variant(name, index, variant, len)?;
+        Ok(Compound { compound: tuple, dialect: self.dialect, parent: self.parent, field: self.field })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let map = self.serializer.serialize_map(len)?;
+        Ok(Compound { compound: map, dialect: self.dialect, parent: self.parent, field: self.field })
+    }
+
+    fn serialize_struct(self, kind: &'static str, len: usize) -> Result<Self::SerializeStruct, S::Error> {
+        let ty = match self.dialect.node(kind, self.parent, self.field) {
+            Shape::Keep => None,
+            Shape::Node(ty) => Some(ty),
+            Shape::Unwrap(field) => {
+                return Ok(Struct::Unwrap {
+                    serializer: Some(self.serializer),
+                    ok: None,
+                    field,
+                });
+            }
+        };
+
+        let captured = Captured {
+            positions: self.dialect.position(0).is_some(),
+            ..Captured::default()
+        };
+
+        Ok(Struct::Node {
+            state: self.serializer.serialize_struct(kind, len)?,
+            dialect: self.dialect,
+            kind,
+            ty,
+            captured,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let state = self.serializer.serialize_struct_variant(name, index, variant, len)?;
+        Ok(Compound { compound: state, dialect: self.dialect, parent: self.parent, field: self.field })
+    }
+}
+
+macro_rules! compound {
+    ($trait:ident, $method:ident) => {
+        impl<'d, C, D> ser::$trait for Compound<'d, C, D>
+        where
+            C: ser::$trait,
+            D: Dialect + ?Sized,
+        {
+            type Ok = C::Ok;
+            type Error = C::Error;
+
+            fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+                let value = self.wrap(value);
+                self.compound.$method(&value)
+            }
+
+            fn end(self) -> Result<C::Ok, C::Error> {
+                self.compound.end()
+            }
+        }
+    };
+}
+
+compound!(SerializeSeq, serialize_element);
+compound!(SerializeTuple, serialize_element);
+compound!(SerializeTupleStruct, serialize_field);
+compound!(SerializeTupleVariant, serialize_field);
+
+impl<'d, C, D> ser::SerializeMap for Compound<'d, C, D>
+where
+    C: ser::SerializeMap,
+    D: Dialect + ?Sized,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.compound.serialize_key(key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.wrap(value);
+        self.compound.serialize_value(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.compound.end()
+    }
+}
+
+impl<'d, C, D> ser::SerializeStructVariant for Compound<'d, C, D>
+where
+    C: ser::SerializeStructVariant,
+    D: Dialect + ?Sized,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), C::Error> {
+        let value = self.wrap(value);
+        self.compound.serialize_field(key, &value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.compound.end()
+    }
+}
+
+impl<'d, S, D> ser::SerializeStruct for Struct<'d, S, D>
+where
+    S: Serializer,
+    D: Dialect + ?Sized,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), S::Error> {
+        match *self {
+            Struct::Node { ref mut state, dialect, kind, ty, ref mut captured } => {
+                if key == "type" {
+                    if let Some(ty) = ty {
+                        return state.serialize_field("type", ty);
+                    }
+                }
+
+                if dialect.flatten(kind, key) {
+                    return value.serialize(Flatten { state, dialect });
+                }
+
+                write_field(state, dialect, kind, key, value)?;
+
+                match key {
+                    "start" => captured.start = value.serialize(Capture).ok().and_then(Capture::offset),
+                    "name" if captured.positions => {
+                        captured.name = value.serialize(Capture).ok().and_then(Capture::string);
+                    },
+                    "end"   => {
+                        let end = value.serialize(Capture).ok().and_then(Capture::offset);
+                        let (start, end) = match (captured.start, end) {
+                            (Some(start), Some(end)) => (start, end),
+                            _ => return Ok(()),
+                        };
+
+                        if captured.positions {
+                            if let (Some(start), Some(end)) = (dialect.position(start), dialect.position(end)) {
+                                let identifier_name = match kind {
+                                    "Identifier" => captured.name.as_deref(),
+                                    _            => None,
+                                };
+
+                                state.serialize_field("loc", &SourceLocation {
+                                    start,
+                                    end,
+                                    identifierName: identifier_name,
+                                })?;
+                            }
+                        }
+
+                        if let Some(id) = dialect.id(kind, start, end) {
+                            state.serialize_field("nodeId", &id)?;
+                        }
+                    },
+                    _ => {},
+                }
+
+                Ok(())
+            },
+            Struct::Unwrap { ref mut serializer, ref mut ok, field } => {
+                if key == field {
+                    if let Some(serializer) = serializer.take() {
+                        *ok = Some(value.serialize(serializer)?);
+                    }
+                }
+
+                Ok(())
+            },
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            Struct::Node { state, .. } => state.end(),
+            Struct::Unwrap { ok: Some(ok), .. } => Ok(ok),
+            Struct::Unwrap { serializer: Some(serializer), .. } => serializer.serialize_none(),
+            Struct::Unwrap { .. } => Err(ser::Error::custom("Unwrapped node was already written")),
+        }
+    }
+}
+
+/// Write `field` of a node of `kind` as the `Dialect` renames it, with the
+/// value it replaces it with, if any.
+fn write_field<S, D, T>(state: &mut S, dialect: &D, kind: &'static str, field: &'static str, value: &T) -> Result<(), S::Error>
+where
+    S: SerializeStruct,
+    D: Dialect + ?Sized,
+    T: Serialize + ?Sized,
+{
+    match (dialect.field(kind, field), dialect.value(kind, field)) {
+        (Some(renamed), Some(replaced)) => state.serialize_field(renamed, replaced),
+        (Some(renamed), None) => state.serialize_field(renamed, &Translated { value, dialect, parent: kind, field }),
+        (None, _) => state.skip_field(field),
+    }
+}
+
+/// `Serializer` writing the fields of a node into the node holding it, see
+/// `Dialect::flatten`.
+struct Flatten<'s, 'd, S: 's, D: 'd + ?Sized> {
+    state: &'s mut S,
+    dialect: &'d D,
+}
+
+/// Fields of a flattened node of `kind`.
+struct Flattened<'s, 'd, S: 's, D: 'd + ?Sized> {
+    state: &'s mut S,
+    dialect: &'d D,
+    kind: &'static str,
+}
+
+type NotFlattened<E> = ser::Impossible<(), E>;
+
+#[inline]
+fn not_a_node<T, E: ser::Error>() -> Result<T, E> {
+    Err(E::custom("Only nodes can be flattened"))
+}
+
+impl<'s, 'd, S, D> Serializer for Flatten<'s, 'd, S, D>
+where
+    S: SerializeStruct,
+    D: Dialect + ?Sized,
+{
+    type Ok = ();
+    type Error = S::Error;
+
+    type SerializeSeq = NotFlattened<S::Error>;
+    type SerializeTuple = NotFlattened<S::Error>;
+    type SerializeTupleStruct = NotFlattened<S::Error>;
+    type SerializeTupleVariant = NotFlattened<S::Error>;
+    type SerializeMap = NotFlattened<S::Error>;
+    type SerializeStruct = Flattened<'s, 'd, S, D>;
+    type SerializeStructVariant = NotFlattened<S::Error>;
+
+    fn serialize_bool(self, _: bool) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_i8(self, _: i8) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_i16(self, _: i16) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_i32(self, _: i32) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_i64(self, _: i64) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_u8(self, _: u8) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_u16(self, _: u16) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_u32(self, _: u32) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_u64(self, _: u64) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_f32(self, _: f32) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_f64(self, _: f64) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_char(self, _: char) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_str(self, _: &str) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_none(self) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_unit(self) -> Result<(), S::Error> { not_a_node() }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), S::Error> { not_a_node() }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), S::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), S::Error> {
+        not_a_node()
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<(), S::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<(), S::Error> {
+        not_a_node()
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, S::Error> { not_a_node() }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, S::Error> { not_a_node() }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, S::Error> { not_a_node() }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, S::Error> {
+        not_a_node()
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, S::Error> { not_a_node() }
+
+    fn serialize_struct(self, kind: &'static str, _: usize) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(Flattened {
+            state: self.state,
+            dialect: self.dialect,
+            kind,
+        })
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, S::Error> {
+        not_a_node()
+    }
+}
+
+impl<'s, 'd, S, D> SerializeStruct for Flattened<'s, 'd, S, D>
+where
+    S: SerializeStruct,
+    D: Dialect + ?Sized,
+{
+    type Ok = ();
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), S::Error> {
+        match key {
+            "type" | "start" | "end" => Ok(()),
+            _ => write_field(self.state, self.dialect, self.kind, key, value),
+        }
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), S::Error> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Captured {
+    /// Whether the `Dialect` writes the `loc` field
+    positions: bool,
+    start: Option<u32>,
+    name: Option<String>,
+}
+
+/// Reads back a plain number or string written by the traversal.
+struct Capture;
+
+enum CapturedValue {
+    Offset(u32),
+    String(String),
+    Other,
+}
+
+impl Capture {
+    fn offset(value: CapturedValue) -> Option<u32> {
+        match value {
+            CapturedValue::Offset(offset) => Some(offset),
+            _ => None,
+        }
+    }
+
+    fn string(value: CapturedValue) -> Option<String> {
+        match value {
+            CapturedValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CaptureError;
+
+impl ::std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str("Value can't be captured")
+    }
+}
+
+impl ::std::error::Error for CaptureError {}
+
+impl ser::Error for CaptureError {
+    fn custom<T: ::std::fmt::Display>(_: T) -> Self {
+        CaptureError
+    }
+}
+
+type CaptureResult<T = CapturedValue> = Result<T, CaptureError>;
+type Impossible = ser::Impossible<CapturedValue, CaptureError>;
+
+impl Serializer for Capture {
+    type Ok = CapturedValue;
+    type Error = CaptureError;
+
+    type SerializeSeq = Impossible;
+    type SerializeTuple = Impossible;
+    type SerializeTupleStruct = Impossible;
+    type SerializeTupleVariant = Impossible;
+    type SerializeMap = Impossible;
+    type SerializeStruct = Impossible;
+    type SerializeStructVariant = Impossible;
+
+    fn serialize_u32(self, v: u32) -> CaptureResult { Ok(CapturedValue::Offset(v)) }
+    fn serialize_str(self, v: &str) -> CaptureResult { Ok(CapturedValue::String(v.to_owned())) }
+
+    fn serialize_bool(self, _: bool) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_i8(self, _: i8) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_i16(self, _: i16) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_i32(self, _: i32) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_i64(self, _: i64) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_u8(self, _: u8) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_u16(self, _: u16) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_u64(self, _: u64) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_f32(self, _: f32) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_f64(self, _: f64) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_char(self, _: char) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_bytes(self, _: &[u8]) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_none(self) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_unit(self) -> CaptureResult { Ok(CapturedValue::Other) }
+    fn serialize_unit_struct(self, _: &'static str) -> CaptureResult { Ok(CapturedValue::Other) }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> CaptureResult {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> CaptureResult {
+        Ok(CapturedValue::Other)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> CaptureResult {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> CaptureResult {
+        Ok(CapturedValue::Other)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> CaptureResult<Impossible> { Err(CaptureError) }
+    fn serialize_tuple(self, _: usize) -> CaptureResult<Impossible> { Err(CaptureError) }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> CaptureResult<Impossible> { Err(CaptureError) }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> CaptureResult<Impossible> {
+        Err(CaptureError)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> CaptureResult<Impossible> { Err(CaptureError) }
+    fn serialize_struct(self, _: &'static str, _: usize) -> CaptureResult<Impossible> { Err(CaptureError) }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> CaptureResult<Impossible> {
+        Err(CaptureError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+    use serde_json::to_value;
+
+    #[test]
+    fn estree_is_unchanged() {
+        let module = parse("a.b = c[d] || 'e'; function f(g, ...h) { return -1 }").unwrap();
+
+        assert_eq!(to_value(InDialect(&module, &Estree)).unwrap(), to_value(&module).unwrap());
+    }
+
+    #[test]
+    fn shift() {
+        let module = parse("a = b.c + d[0];").unwrap();
+
+        assert_eq!(to_value(InDialect(&module, &Shift)).unwrap(), json!({
+            "type": "Script",
+            "statements": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "AssignmentExpression",
+                        "operator": "=",
+                        "binding": {
+                            "type": "AssignmentTargetIdentifier",
+                            "name": "a",
+                            "start": 0,
+                            "end": 1,
+                        },
+                        "expression": {
+                            "type": "BinaryExpression",
+                            "operator": "+",
+                            "left": {
+                                "type": "StaticMemberExpression",
+                                "object": {
+                                    "type": "IdentifierExpression",
+                                    "name": "b",
+                                    "start": 4,
+                                    "end": 5,
+                                },
+                                "property": "c",
+                                "start": 4,
+                                "end": 9,
+                            },
+                            "right": {
+                                "type": "ComputedMemberExpression",
+                                "object": {
+                                    "type": "IdentifierExpression",
+                                    "name": "d",
+                                    "start": 10,
+                                    "end": 11,
+                                },
+                                "expression": {
+                                    "type": "LiteralNumericExpression",
+                                    "value": 0,
+                                    "start": 12,
+                                    "end": 13,
+                                },
+                                "start": 10,
+                                "end": 15,
+                            },
+                            "start": 4,
+                            "end": 15,
+                        },
+                        "start": 0,
+                        "end": 15,
+                    },
+                    "start": 0,
+                    "end": 15,
+                }
+            ],
+            "start": 0,
+            "end": 15,
+        }));
+    }
+
+    #[test]
+    fn shift_bindings() {
+        let module = parse("function f(a, ...b) { return a; }").unwrap();
+        let value = to_value(InDialect(&module, &Shift)).unwrap();
+        let function = &value["statements"][0];
+
+        assert_eq!(function["name"]["type"], "BindingIdentifier");
+        assert_eq!(function["isGenerator"], false);
+        assert_eq!(function["params"][0]["type"], "BindingIdentifier");
+        assert_eq!(function["params"][1]["argument"]["type"], "BindingIdentifier");
+        assert_eq!(function["body"]["body"][0]["expression"]["type"], "IdentifierExpression");
+    }
+
+    #[test]
+    fn babel() {
+        let source = "foo(\n  'bar', 1);";
+        let module = parse(source).unwrap();
+
+        assert_eq!(to_value(InDialect(&module, &Babel::new(source))).unwrap(), json!({
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "CallExpression",
+                            "callee": {
+                                "type": "Identifier",
+                                "name": "foo",
+                                "start": 0,
+                                "end": 3,
+                                "loc": {
+                                    "start": { "line": 1, "column": 0 },
+                                    "end": { "line": 1, "column": 3 },
+                                    "identifierName": "foo",
+                                },
+                            },
+                            "arguments": [
+                                {
+                                    "type": "StringLiteral",
+                                    "value": "bar",
+                                    "start": 7,
+                                    "end": 12,
+                                    "loc": {
+                                        "start": { "line": 2, "column": 2 },
+                                        "end": { "line": 2, "column": 7 },
+                                    },
+                                },
+                                {
+                                    "type": "NumericLiteral",
+                                    "value": 1,
+                                    "start": 14,
+                                    "end": 15,
+                                    "loc": {
+                                        "start": { "line": 2, "column": 9 },
+                                        "end": { "line": 2, "column": 10 },
+                                    },
+                                },
+                            ],
+                            "start": 3,
+                            "end": 16,
+                            "loc": {
+                                "start": { "line": 1, "column": 3 },
+                                "end": { "line": 2, "column": 11 },
+                            },
+                        },
+                        "start": 0,
+                        "end": 16,
+                        "loc": {
+                            "start": { "line": 1, "column": 0 },
+                            "end": { "line": 2, "column": 11 },
+                        },
+                    }
+                ],
+                "start": 0,
+                "end": 16,
+                "loc": {
+                    "start": { "line": 1, "column": 0 },
+                    "end": { "line": 2, "column": 11 },
+                },
+            },
+            "start": 0,
+            "end": 17,
+            "loc": {
+                "start": { "line": 1, "column": 0 },
+                "end": { "line": 2, "column": 12 },
+            },
+        }));
+    }
+
+    #[test]
+    fn babel_object_properties() {
+        let source = "({ a: 1, ...b, c() {} });";
+        let module = parse(source).unwrap();
+        let value = to_value(InDialect(&module, &Babel::new(source))).unwrap();
+        let properties = &value["program"]["body"][0]["expression"]["properties"];
+
+        assert_eq!(properties[0]["type"], "ObjectProperty");
+        assert_eq!(properties[0].get("kind"), None);
+        assert_eq!(properties[0]["method"], false);
+        assert_eq!(properties[1]["type"], "SpreadElement");
+        assert_eq!(properties[2]["type"], "ObjectMethod");
+        assert_eq!(properties[2]["kind"], "method");
+        assert_eq!(properties[2]["method"], true);
+        assert_eq!(properties[2]["params"], json!([]));
+        assert_eq!(properties[2]["body"]["type"], "BlockStatement");
+        assert_eq!(properties[2].get("value"), None);
+        assert_eq!(properties[2].get("shorthand"), None);
+    }
+
+    #[test]
+    fn babel_class_members() {
+        let source = "class A { a = 1; #b; static c() {} get #d() {} }";
+        let module = parse(source).unwrap();
+        let value = to_value(InDialect(&module, &Babel::new(source))).unwrap();
+        let members = &value["program"]["body"][0]["body"]["body"];
+        let types: Vec<_> = members.as_array().unwrap().iter().map(|member| member["type"].clone()).collect();
+
+        assert_eq!(types, vec!["ClassProperty", "ClassPrivateProperty", "ClassMethod", "ClassPrivateMethod"]);
+        assert_eq!(members[1].get("computed"), None);
+        assert_eq!(members[2]["static"], true);
+        assert_eq!(members[2]["params"], json!([]));
+        assert_eq!(members[2].get("value"), None);
+        assert_eq!(members[3]["kind"], "get");
+
+        // ESTree doesn't tell private members apart
+        let estree = to_value(&module).unwrap();
+        let members = &estree["body"][0]["body"]["body"];
+
+        assert_eq!(members[1]["type"], "PropertyDefinition");
+        assert_eq!(members[3]["type"], "MethodDefinition");
+    }
+
+    #[test]
+    fn babel_optional_chains() {
+        let source = "a?.b.c;";
+        let module = parse(source).unwrap();
+        let value = to_value(InDialect(&module, &Babel::new(source))).unwrap();
+        let chain = &value["program"]["body"][0]["expression"];
+
+        assert_eq!(chain["type"], "MemberExpression");
+        assert_eq!(chain["object"]["type"], "MemberExpression");
+        assert_eq!(chain["object"]["optional"], true);
+    }
+
+    #[test]
+    fn stable_ids() {
+        let source = "a + a;";
+        let module = parse(source).unwrap();
+        let value = to_value(InDialect(&module, &StableIds::new(source, Estree))).unwrap();
+        let binary = &value["body"][0]["expression"];
+        let left = binary["left"]["nodeId"].as_str().unwrap();
+
+        assert_eq!(left.len(), 16);
+        assert!(left.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_eq!(binary["right"]["nodeId"], format!("{}-1", left));
+        assert_ne!(binary["nodeId"], binary["left"]["nodeId"]);
+        assert_eq!(binary["left"]["name"], "a");
+
+        // Same content in a fresh serialization, with no other changes
+        let again = to_value(InDialect(&module, &StableIds::new(source, Estree))).unwrap();
+
+        assert_eq!(again, value);
+    }
+
+    #[test]
+    fn stable_ids_across_versions() {
+        let before = "foo(1);\nbar(2);";
+        let after = "// moved\nbaz();\n\n  bar(2);";
+
+        let ids = |source| {
+            let module = parse(source).unwrap();
+            let value = to_value(InDialect(&module, &StableIds::new(source, Estree))).unwrap();
+            let statement = &value["body"][1];
+
+            (statement["nodeId"].clone(), statement["expression"]["arguments"][0]["nodeId"].clone())
+        };
+
+        assert_eq!(ids(before), ids(after));
+        assert_ne!(ids(before), ids("foo(1);\nbar(3);"));
+    }
+
+    #[test]
+    fn stable_ids_in_dialects() {
+        let source = "a.b;";
+        let module = parse(source).unwrap();
+        let value = to_value(InDialect(&module, &StableIds::new(source, Babel::new(source)))).unwrap();
+        let statement = &value["program"]["body"][0];
+
+        assert!(value.get("nodeId").is_none());
+        assert!(value["program"]["nodeId"].is_string());
+        assert!(statement["nodeId"].is_string());
+        assert_eq!(statement["loc"]["end"]["column"], 4);
+        assert_eq!(value["program"]["body"][0]["expression"]["type"], "MemberExpression");
+
+        let shift = to_value(InDialect(&module, &StableIds::new(source, Shift))).unwrap();
+
+        assert_eq!(shift["type"], "Script");
+        assert!(shift["statements"][0]["nodeId"].is_string());
+    }
+}
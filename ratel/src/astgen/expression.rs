@@ -1,7 +1,8 @@
 use serde::ser::{Serialize, Serializer, SerializeStruct};
-use ast::{Expression, Loc, OperatorKind};
+use ast::{Expression, ExpressionNode, Loc, OperatorKind};
 use ast::expression::*;
 use astgen::SerializeInLoc;
+use astgen::value::{as_pattern, PrivateName};
 
 #[inline]
 fn expression_type<'ast>(operator: OperatorKind, prefix: bool) -> &'static str {
@@ -37,6 +38,49 @@ fn expression_type<'ast>(operator: OperatorKind, prefix: bool) -> &'static str {
     }
 }
 
+/// Kind of the binary expression, for `in_loc_as`
+#[inline]
+fn binary_kind(operator: OperatorKind) -> &'static str {
+    match expression_type(operator, false) {
+        "AssignmentExpression" if operator != OperatorKind::Assign => "CompoundAssignmentExpression",
+        "AssignmentExpression" => "AssignmentExpression",
+        "LogicalExpression"    => "LogicalExpression",
+        _                      => "BinaryExpression",
+    }
+}
+
+/// Serialize the `optional` field of members and calls, omitted unless
+/// `?.` is used
+#[inline]
+fn optional<S: SerializeStruct>(state: &mut S, optional: bool) -> Result<(), S::Error> {
+    match optional {
+        true  => state.serialize_field("optional", &true),
+        false => Ok(()),
+    }
+}
+
+/// Object or callee of a member or a call, written without the
+/// `ChainExpression` of an optional chain, which only wraps its top
+struct Chained<'a, 'ast: 'a>(&'a Loc<Expression<'ast>>);
+
+impl<'a, 'ast> Serialize for Chained<'a, 'ast> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = self.0.item.serialize(serializer)?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+#[inline]
+fn chained<'a, 'ast>(node: &'a ExpressionNode<'ast>) -> Chained<'a, 'ast> {
+    Chained(node.get())
+}
+
 impl<'ast> SerializeInLoc for SpreadExpression<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -48,14 +92,38 @@ impl<'ast> SerializeInLoc for SpreadExpression<'ast> {
     }
 }
 
+impl<'ast> SerializeInLoc for AwaitExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "AwaitExpression", 1, |state| {
+            state.serialize_field("argument", &self.argument)
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for YieldExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "YieldExpression", 2, |state| {
+            state.serialize_field("argument", &self.argument)?;
+            state.serialize_field("delegate", &self.delegate)
+        })
+    }
+}
+
 impl<'ast> SerializeInLoc for MemberExpression<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "MemberExpression", 3, |state| {
-                state.serialize_field("object", &self.object)?;
+        self.in_loc(serializer, "MemberExpression", 3 + self.optional as usize, |state| {
+                state.serialize_field("object", &chained(&self.object))?;
                 state.serialize_field("property", &self.property)?;
+                optional(state, self.optional)?;
                 state.serialize_field("computed", &false)
         })
     }
@@ -66,14 +134,42 @@ impl<'ast> SerializeInLoc for ComputedMemberExpression<'ast> {
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "MemberExpression", 3, |state| {
-                state.serialize_field("object", &self.object)?;
+        self.in_loc_as(serializer, "MemberExpression", "ComputedMemberExpression", 3 + self.optional as usize, |state| {
+                state.serialize_field("object", &chained(&self.object))?;
                 state.serialize_field("property", &self.property)?;
+                optional(state, self.optional)?;
                 state.serialize_field("computed", &true)
         })
     }
 }
 
+impl<'ast> SerializeInLoc for ChainExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ChainExpression", 1, |state| {
+            state.serialize_field("expression", &chained(&self.expression))
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for PrivateMemberExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        let property = Loc::new(self.property.start, self.property.end, PrivateName(self.property.item));
+
+        self.in_loc_as(serializer, "MemberExpression", "PrivateMemberExpression", 3 + self.optional as usize, |state| {
+                state.serialize_field("object", &chained(&self.object))?;
+                state.serialize_field("property", &property)?;
+                optional(state, self.optional)?;
+                state.serialize_field("computed", &false)
+        })
+    }
+}
+
 impl<'ast> SerializeInLoc for MetaPropertyExpression<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -91,8 +187,9 @@ impl<'ast> SerializeInLoc for CallExpression<'ast> {
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "CallExpression", 2, |state| {
-            state.serialize_field("callee", &self.callee)?;
+        self.in_loc(serializer, "CallExpression", 2 + self.optional as usize, |state| {
+            state.serialize_field("callee", &chained(&self.callee))?;
+            optional(state, self.optional)?;
             state.serialize_field("arguments", &self.arguments)
         })
     }
@@ -129,7 +226,12 @@ impl<'ast> SerializeInLoc for ArrowExpression<'ast> {
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "ArrowFunctionExpression", 2, |state| {
+        // `async` is omitted unless set, the same way as for functions
+        self.in_loc(serializer, "ArrowFunctionExpression", 2 + self.is_async as usize, |state| {
+            if self.is_async {
+                state.serialize_field("async", &true)?;
+            }
+
             state.serialize_field("params", &self.params)?;
             state.serialize_field("body", &Loc::new(0, 0, self.body))
         })
@@ -158,7 +260,7 @@ impl<'ast> SerializeInLoc for PrefixExpression<'ast> {
         if let OperatorKind::New = self.operator {
             use self::Expression::*;
             match self.operand.item {
-                Call(CallExpression { ref callee, ref arguments }) => {
+                Call(CallExpression { ref callee, ref arguments, .. }) => {
                     self.in_loc(serializer, expr_type, 2, |state| {
                         state.serialize_field("callee", callee)?;
                         state.serialize_field("arguments", arguments)
@@ -225,23 +327,9 @@ impl<'ast> SerializeInLoc for ArrayExpression<'ast> {
         S: Serializer,
     {
         self.in_loc(serializer, "ArrayExpression", 1, |state| {
+            let elements = self.elements().collect::<Vec<_>>();
 
-            let mut elems = self.body.iter()
-                                     .map(|loc| if loc.item != Expression::Void { Some(loc) } else { None } )
-                                     .collect::<Vec<_>>();
-            loop {
-                let should_pop = match elems.last() {
-                    Some(&None) => true,
-                    Some(&Some(_)) => break,
-                    None => break,
-                };
-
-                if should_pop {
-                    elems.pop();
-                }
-            }
-
-            state.serialize_field("elements", &elems)
+            state.serialize_field("elements", &elements)
         })
     }
 }
@@ -251,9 +339,15 @@ impl<'ast> SerializeInLoc for BinaryExpression<'ast> {
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "BinaryExpression", 3, |state| {
+        self.in_loc_as(serializer, "BinaryExpression", binary_kind(self.operator), 3, |state| {
             state.serialize_field("operator", &self.operator)?;
-            state.serialize_field("left", &self.left)?;
+
+            // Object and array literals destructured by `=` are patterns
+            match self.operator {
+                OperatorKind::Assign => state.serialize_field("left", &as_pattern(self.left))?,
+                _ => state.serialize_field("left", &self.left)?,
+            }
+
             state.serialize_field("right", &self.right)
         })
     }
@@ -269,6 +363,7 @@ impl<'ast> SerializeInLoc for Expression<'ast> {
         match *self {
             Void => unreachable!(),
             This(_) => self.in_loc(serializer, "ThisExpression", 0, |_| Ok(())),
+            Super(_) => self.in_loc(serializer, "Super", 0, |_| Ok(())),
             Identifier(ref ident) => {
                 self.in_loc(serializer, "Identifier", 1, |state| {
                     state.serialize_field("name", ident)
@@ -284,8 +379,12 @@ impl<'ast> SerializeInLoc for Expression<'ast> {
             Template(ref expression)       => expression.serialize(serializer),
             TaggedTemplate(ref expression) => expression.serialize(serializer),
             Spread(ref expression)         => expression.serialize(serializer),
+            Yield(ref expression)          => expression.serialize(serializer),
+            Await(ref expression)          => expression.serialize(serializer),
             Member(ref expression)         => expression.serialize(serializer),
             ComputedMember(ref expression) => expression.serialize(serializer),
+            PrivateMember(ref expression)  => expression.serialize(serializer),
+            Chain(ref expression)          => expression.serialize(serializer),
             MetaProperty(ref expression)   => expression.serialize(serializer),
             Call(ref expression)           => expression.serialize(serializer),
             Conditional(ref expression)    => expression.serialize(serializer),
@@ -294,6 +393,25 @@ impl<'ast> SerializeInLoc for Expression<'ast> {
             Class(ref expression)          => expression.serialize(serializer),
         }
     }
+
+    /// Optional chains are wrapped in a `ChainExpression` of the same span,
+    /// as `?.` short-circuits the rest of the chain only
+    fn serialize_loc<S>(loc: &Loc<Self>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if !loc.item.is_optional_chain() {
+            return Chained(loc).serialize(serializer);
+        }
+
+        let name = "ChainExpression";
+        let mut state = serializer.serialize_struct(name, 4)?;
+        state.serialize_field("type", &name)?;
+        state.serialize_field("expression", &Chained(loc))?;
+        state.serialize_field("start", &loc.start)?;
+        state.serialize_field("end", &loc.end)?;
+        state.end()
+    }
 }
 
 #[cfg(test)]
@@ -342,6 +460,7 @@ mod test {
                                 "start": 1,
                                 "end": 2
                             },
+                            null,
                         ],
                         "start": 0,
                         "end": 5
@@ -383,6 +502,58 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_sparse_array_expression() {
+        expect_parse!("[,,1,,]", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "ArrayExpression",
+                        "elements": [
+                            null,
+                            null,
+                            {
+                                "type": "Literal",
+                                "value": 1,
+                                "raw": "1",
+                                "start": 3,
+                                "end": 4
+                            },
+                            null,
+                        ],
+                        "start": 0,
+                        "end": 7
+                    },
+                    "start": 0,
+                    "end": 7
+                }
+            ],
+            "start": 0,
+            "end": 7
+        });
+
+        expect_parse!("[,]", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "ArrayExpression",
+                        "elements": [null],
+                        "start": 0,
+                        "end": 3
+                    },
+                    "start": 0,
+                    "end": 3
+                }
+            ],
+            "start": 0,
+            "end": 3
+        });
+    }
+
     #[test]
     fn test_this_expression() {
         expect_parse!("this;", {
@@ -1052,6 +1223,197 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_optional_member_expression () {
+        expect_parse!("foo?.bar", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "ChainExpression",
+                        "expression": {
+                            "type": "MemberExpression",
+                            "object": {
+                                "type": "Identifier",
+                                "name": "foo",
+                                "start": 0,
+                                "end": 3
+                            },
+                            "property": {
+                                "type": "Identifier",
+                                "name": "bar",
+                                "start": 5,
+                                "end": 8
+                            },
+                            "optional": true,
+                            "computed": false,
+                            "start": 0,
+                            "end": 8
+                        },
+                        "start": 0,
+                        "end": 8
+                    },
+                    "start": 0,
+                    "end": 8
+                }
+            ],
+            "start": 0,
+            "end": 8
+        });
+    }
+
+    #[test]
+    fn test_optional_chain () {
+        expect_parse!("a?.b()", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "ChainExpression",
+                        "expression": {
+                            "type": "CallExpression",
+                            "callee": {
+                                "type": "MemberExpression",
+                                "object": {
+                                    "type": "Identifier",
+                                    "name": "a",
+                                    "start": 0,
+                                    "end": 1
+                                },
+                                "property": {
+                                    "type": "Identifier",
+                                    "name": "b",
+                                    "start": 3,
+                                    "end": 4
+                                },
+                                "optional": true,
+                                "computed": false,
+                                "start": 0,
+                                "end": 4
+                            },
+                            "arguments": [],
+                            "start": 4,
+                            "end": 6
+                        },
+                        "start": 4,
+                        "end": 6
+                    },
+                    "start": 0,
+                    "end": 6
+                }
+            ],
+            "start": 0,
+            "end": 6
+        });
+    }
+
+    #[test]
+    fn test_parenthesized_optional_chain () {
+        expect_parse!("(a?.b).c", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "MemberExpression",
+                        "object": {
+                            "type": "ChainExpression",
+                            "expression": {
+                                "type": "MemberExpression",
+                                "object": {
+                                    "type": "Identifier",
+                                    "name": "a",
+                                    "start": 1,
+                                    "end": 2
+                                },
+                                "property": {
+                                    "type": "Identifier",
+                                    "name": "b",
+                                    "start": 4,
+                                    "end": 5
+                                },
+                                "optional": true,
+                                "computed": false,
+                                "start": 1,
+                                "end": 5
+                            },
+                            "start": 1,
+                            "end": 5
+                        },
+                        "property": {
+                            "type": "Identifier",
+                            "name": "c",
+                            "start": 8,
+                            "end": 8
+                        },
+                        "computed": false,
+                        "start": 1,
+                        "end": 8
+                    },
+                    "start": 1,
+                    "end": 8
+                }
+            ],
+            "start": 1,
+            "end": 8
+        });
+    }
+
+    #[test]
+    fn test_optional_call_in_chain () {
+        expect_parse!("g(f?.()())", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "CallExpression",
+                        "callee": {
+                            "type": "Identifier",
+                            "name": "g",
+                            "start": 0,
+                            "end": 1
+                        },
+                        "arguments": [
+                            {
+                                "type": "ChainExpression",
+                                "expression": {
+                                    "type": "CallExpression",
+                                    "callee": {
+                                        "type": "CallExpression",
+                                        "callee": {
+                                            "type": "Identifier",
+                                            "name": "f",
+                                            "start": 2,
+                                            "end": 3
+                                        },
+                                        "optional": true,
+                                        "arguments": [],
+                                        "start": 2,
+                                        "end": 7
+                                    },
+                                    "arguments": [],
+                                    "start": 7,
+                                    "end": 9
+                                },
+                                "start": 7,
+                                "end": 9
+                            }
+                        ],
+                        "start": 1,
+                        "end": 10
+                    },
+                    "start": 0,
+                    "end": 10
+                }
+            ],
+            "start": 0,
+            "end": 10
+        });
+    }
+
     #[test]
     fn test_computed_member_expression () {
         expect_parse!("foo[bar]", {
@@ -1367,6 +1729,48 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_arrow_function_bindings() {
+        // Arrows take `this` and `arguments` from the enclosing function,
+        // they are the same nodes as anywhere else
+        expect_parse!("() => [this, arguments]", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "ArrowFunctionExpression",
+                        "params": [],
+                        "body": {
+                            "type": "ArrayExpression",
+                            "elements": [
+                                {
+                                    "type": "ThisExpression",
+                                    "start": 7,
+                                    "end": 11
+                                },
+                                {
+                                    "type": "Identifier",
+                                    "name": "arguments",
+                                    "start": 13,
+                                    "end": 22
+                                }
+                            ],
+                            "start": 6,
+                            "end": 23
+                        },
+                        "start": 0,
+                        "end": 23
+                    },
+                    "start": 0,
+                    "end": 23
+                }
+              ],
+              "start": 0,
+              "end": 23,
+        });
+    }
+
     #[test]
     fn test_function_expression () {
         expect_parse!("function foo () {}", {
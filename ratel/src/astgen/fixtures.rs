@@ -0,0 +1,168 @@
+//! Fixture tests: every `.js` file under `tests/fixtures` is parsed and its
+//! ESTree output compared with the JSON file next to it, the same way as
+//! `expect_parse!` does. Fixtures can be added without writing any Rust:
+//!
+//! - `name.json` or `name.tree.json` holds the expected AST. Besides the
+//!   output of ratel, the format of the esprima test fixtures is accepted,
+//!   with a `range` instead of `start` and `end`, and `loc` being ignored.
+//! - `name.failure.json` marks a source that must fail to parse, its
+//!   content is not checked.
+//! - `name.babel.json`, if any, holds the expected AST in the `Babel`
+//!   dialect, `loc` included.
+//!
+//! Running the tests with `RATEL_BLESS=1` writes the output of ratel to
+//! `name.json` for the fixtures without an expected file.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{self, Value, Map};
+
+use parser::parse;
+use astgen::dialect::{InDialect, Babel};
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+/// Collect the `.js` files under `dir`, sorted.
+fn collect_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_sources(&path, sources);
+        } else if path.extension().is_some_and(|extension| extension == "js") {
+            sources.push(path);
+        }
+    }
+}
+
+/// Rewrite the esprima `range` of every node to `start` and `end`, and drop
+/// its `loc`.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut normalized = Map::new();
+
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("loc", _) => {},
+                    ("range", Value::Array(ref range)) if range.len() == 2 => {
+                        normalized.insert("start".into(), range[0].clone());
+                        normalized.insert("end".into(), range[1].clone());
+                    },
+                    (_, value) => {
+                        normalized.insert(key, normalize(value));
+                    },
+                }
+            }
+
+            Value::Object(normalized)
+        },
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize).collect()),
+        value => value,
+    }
+}
+
+/// Check a single fixture, returning `Err` with a description of the
+/// mismatch.
+fn check_fixture(path: &Path, bless: bool) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let with_extension = |extension: &str| path.with_extension(extension);
+
+    if with_extension("failure.json").exists() {
+        return match parse(&source) {
+            Ok(_) => Err("parsed, but a failure was expected".into()),
+            Err(_) => Ok(()),
+        };
+    }
+
+    let module = parse(&source).map_err(|errors| format!("failed to parse: {:?}", errors))?;
+
+    if let Err(err) = super::schema::check(&module) {
+        return Err(format!("invalid schema: {}", err));
+    }
+
+    let result = serde_json::to_value(&module).map_err(|err| err.to_string())?;
+
+    let expected = [with_extension("json"), with_extension("tree.json")].iter().find(|path| path.exists()).cloned();
+
+    let expected = match expected {
+        Some(expected) => expected,
+        None if bless => {
+            let json = serde_json::to_string_pretty(&result).map_err(|err| err.to_string())?;
+
+            return fs::write(with_extension("json"), json + "\n").map_err(|err| err.to_string());
+        },
+        None => return Err("no expected `.json` file, run with `RATEL_BLESS=1` to create it".into()),
+    };
+
+    compare(&expected, normalize, &result)?;
+
+    let babel = with_extension("babel.json");
+
+    if babel.exists() {
+        let result = serde_json::to_value(InDialect(&module, &Babel::new(&source))).map_err(|err| err.to_string())?;
+
+        compare(&babel, |value| value, &result).map_err(|err| format!("Babel {}", err))?;
+    }
+
+    Ok(())
+}
+
+/// Compare the `result` with the JSON of the `expected` file, passed through
+/// `normalize`.
+fn compare<F: FnOnce(Value) -> Value>(expected: &Path, normalize: F, result: &Value) -> Result<(), String> {
+    let expected = fs::read_to_string(expected).map_err(|err| err.to_string())?;
+    let expected: Value = serde_json::from_str(&expected).map_err(|err| format!("invalid expected JSON: {}", err))?;
+
+    if normalize(expected.clone()) != *result {
+        return Err(format!(
+            "AST differs\n\nexpected:\n{}\n\nfound:\n{}",
+            serde_json::to_string_pretty(&expected).unwrap_or_default(),
+            serde_json::to_string_pretty(result).unwrap_or_default(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fixtures() {
+    let dir = fixtures_dir();
+    let bless = env::var_os("RATEL_BLESS").is_some();
+    let mut sources = Vec::new();
+
+    collect_sources(&dir, &mut sources);
+
+    let failures: Vec<String> = sources.iter().filter_map(|path| {
+        let name = path.strip_prefix(&dir).unwrap_or(path).display();
+
+        check_fixture(path, bless).err().map(|err| format!("{}: {}", name, err))
+    }).collect();
+
+    if !failures.is_empty() {
+        panic!("{} of {} fixtures failed:\n\n{}", failures.len(), sources.len(), failures.join("\n\n"));
+    }
+}
+
+#[test]
+fn normalizes_esprima_ranges() {
+    let esprima = json!({
+        "type": "Identifier",
+        "name": "a",
+        "range": [0, 1],
+        "loc": { "start": { "line": 1, "column": 0 }, "end": { "line": 1, "column": 1 } },
+    });
+
+    assert_eq!(normalize(esprima), json!({ "type": "Identifier", "name": "a", "start": 0, "end": 1 }));
+}
@@ -1,6 +1,6 @@
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use astgen::SerializeInLoc;
-use ast::{Function, Class, Name, MandatoryName, OptionalName, EmptyName, ClassMember, Block};
+use ast::{Function, Class, Name, MandatoryName, OptionalName, EmptyName, ClassMember, Block, PropertyKey};
 use ast::MethodKind;
 
 impl<'ast> Serialize for MethodKind {
@@ -26,19 +26,41 @@ impl<'ast> SerializeInLoc for ClassMember<'ast> {
         use self::ClassMember::*;
 
         match *self {
-            Error { .. } => panic!("Module contains errors"),
+            // Only reachable with error recovery, emit a placeholder so
+            // partial ASTs can still be handed over to tooling.
+            Error { .. } => self.in_loc(serializer, "ErrorClassMember", 0, |_| Ok(())),
             Method { is_static, key, kind, value } => {
-                self.in_loc(serializer, "MethodDefinition", 5, |state| {
+                let member = match key.item {
+                    PropertyKey::Private(_) => "PrivateMethodDefinition",
+                    _                       => "MethodDefinition",
+                };
+
+                self.in_loc_as(serializer, "MethodDefinition", member, 5, |state| {
                     state.serialize_field("kind", &kind)?;
                     state.serialize_field("static", &is_static)?;
-                    state.serialize_field("computed", &false)?;
+                    state.serialize_field("computed", &matches!(key.item, PropertyKey::Computed(_)))?;
+                    state.serialize_field("key", &*key)?;
+                    state.serialize_field("value", &value)
+                })
+            },
+            Property { is_static, key, value } => {
+                let member = match key.item {
+                    PropertyKey::Private(_) => "PrivatePropertyDefinition",
+                    _                       => "PropertyDefinition",
+                };
+
+                self.in_loc_as(serializer, "PropertyDefinition", member, 4, |state| {
+                    state.serialize_field("static", &is_static)?;
+                    state.serialize_field("computed", &matches!(key.item, PropertyKey::Computed(_)))?;
                     state.serialize_field("key", &*key)?;
                     state.serialize_field("value", &value)
                 })
             },
-            Literal { is_static: _, key: _, value: _ } => {
-                unimplemented!()
-            }
+            StaticBlock { body } => {
+                self.in_loc(serializer, "StaticBlock", 1, |state| {
+                    state.serialize_field("body", &body)
+                })
+            },
         }
     }
 }
@@ -104,11 +126,7 @@ where
     where
         S: Serializer,
     {
-        self.in_loc(serializer, N::IN_CLASS, 3, |state| {
-            state.serialize_field("id", &self.name)?;
-            state.serialize_field("superClass", &self.extends)?;
-            state.serialize_field("body", &self.body)
-        })
+        serialize_class(self, N::IN_CLASS, serializer)
     }
 }
 
@@ -120,11 +138,40 @@ where
     where
         S: Serializer,
     {
-        self.in_loc(serializer, N::IN_FUNCTION, 3, |state| {
-            state.serialize_field("generator", &self.generator)?;
-            state.serialize_field("id", &self.name)?;
-            state.serialize_field("params", &self.params)?;
-            state.serialize_field("body", &self.body)
-        })
+        serialize_function(self, N::IN_FUNCTION, serializer)
     }
+}
+
+/// Serialize the `class` as the type `name` rather than the one of its kind
+/// of name, such as the anonymous class declaration of `export default`.
+pub fn serialize_class<'ast, N, S>(class: &Class<'ast, N>, name: &'static str, serializer: S) -> Result<S::SerializeStruct, S::Error>
+where
+    N: Serialize + NameType<'ast>,
+    S: Serializer,
+{
+    class.in_loc(serializer, name, 3, |state| {
+        state.serialize_field("id", &class.name)?;
+        state.serialize_field("superClass", &class.extends)?;
+        state.serialize_field("body", &class.body)
+    })
+}
+
+/// Same as `serialize_class`, for a `function`.
+pub fn serialize_function<'ast, N, S>(function: &Function<'ast, N>, name: &'static str, serializer: S) -> Result<S::SerializeStruct, S::Error>
+where
+    N: Serialize + NameType<'ast>,
+    S: Serializer,
+{
+    // `async` is omitted unless set, the same way as `optional` of members
+    function.in_loc(serializer, name, 4 + function.is_async as usize, |state| {
+        state.serialize_field("generator", &function.generator)?;
+
+        if function.is_async {
+            state.serialize_field("async", &true)?;
+        }
+
+        state.serialize_field("id", &function.name)?;
+        state.serialize_field("params", &function.params)?;
+        state.serialize_field("body", &function.body)
+    })
 }
\ No newline at end of file
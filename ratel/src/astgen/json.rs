@@ -0,0 +1,83 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json;
+
+/// Serialize `value`, usually a `Module` or an `InDialect`, as JSON into
+/// `writer`. Nothing is buffered besides the current path through the
+/// tree, so this is suitable for dumping very large ASTs into a file or
+/// a socket. Wrap unbuffered writers in a `BufWriter`.
+pub fn write_json<W, T>(writer: W, value: &T, pretty: bool) -> io::Result<()>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    let result = if pretty {
+        serde_json::to_writer_pretty(writer, value)
+    } else {
+        serde_json::to_writer(writer, value)
+    };
+
+    result.map_err(io::Error::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+    use astgen::dialect::{InDialect, Shift};
+    use serde_json::{from_slice, to_value, Value};
+
+    /// Fails once `limit` bytes were written
+    struct Limited {
+        written: usize,
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written + buf.len() > self.limit {
+                return Err(io::Error::other("limit reached"));
+            }
+
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_module() {
+        let module = parse("foo(bar, 1); `baz${qux}`").unwrap();
+
+        for &pretty in &[false, true] {
+            let mut out = Vec::new();
+
+            write_json(&mut out, &module, pretty).unwrap();
+
+            assert_eq!(from_slice::<Value>(&out).unwrap(), to_value(&module).unwrap());
+        }
+    }
+
+    #[test]
+    fn writes_dialects() {
+        let module = parse("foo.bar;").unwrap();
+        let shift = InDialect(&module, &Shift);
+        let mut out = Vec::new();
+
+        write_json(&mut out, &shift, false).unwrap();
+
+        assert_eq!(from_slice::<Value>(&out).unwrap(), to_value(&shift).unwrap());
+    }
+
+    #[test]
+    fn propagates_io_errors() {
+        let module = parse("foo; bar; baz;").unwrap();
+        let err = write_json(Limited { written: 0, limit: 64 }, &module, false).unwrap_err();
+
+        assert_eq!(err.to_string(), "limit reached");
+    }
+}
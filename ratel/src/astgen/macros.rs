@@ -1,6 +1,6 @@
 /// Parses the given input string into an AST and compares it
-/// with the given JSON input.
-
+/// with the given JSON input. The AST is also run through the
+/// schema checker to catch miscounted or duplicate fields.
 #[cfg(test)]
 #[macro_export]
 macro_rules! expect_parse {
@@ -9,6 +9,9 @@ macro_rules! expect_parse {
         use $crate::serde_json::to_value;
 
         let module = parse($expr).unwrap();
+        if let Err(err) = $crate::astgen::schema::check(&module) {
+            panic!("Invalid schema for {:?}: {}", $expr, err);
+        }
         let result = to_value(&module).unwrap();
         let expected = json!($expected);
         assert_eq!(result, expected);
@@ -4,6 +4,13 @@ mod statement;
 mod expression;
 mod function;
 mod value;
+pub mod dialect;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(test)]
+mod schema;
+#[cfg(test)]
+mod fixtures;
 
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use ast::{Loc, Node};
@@ -16,13 +23,41 @@ pub trait SerializeInLoc {
         S: Serializer,
         F: FnOnce(&mut S::SerializeStruct) -> Result<(), S::Error>
     {
-        let mut state = serializer.serialize_struct(name, length + 3)?;
+        self.in_loc_as(serializer, name, name, length, build)
+    }
+
+    /// Same as `in_loc`, but passes a more specific `kind` of the node as the
+    /// struct name to the `Serializer`. ESTree output only uses `name` for the
+    /// `type` field, a `Dialect` can use `kind` to tell apart nodes ESTree
+    /// lumps together under one type.
+    #[inline]
+    fn in_loc_as<S, F>(&self, serializer: S, name: &'static str, kind: &'static str, length: usize, build: F) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+        F: FnOnce(&mut S::SerializeStruct) -> Result<(), S::Error>
+    {
+        let mut state = serializer.serialize_struct(kind, length + 3)?;
         state.serialize_field("type", name)?;
         build(&mut state).map(move |_| state)
     }
 
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where S: Serializer;
+
+    /// Serialize the node of the `loc` with its `start` and `end`. Nodes
+    /// written within another node of the same span, such as the top of an
+    /// optional chain within a `ChainExpression`, do the wrapping here.
+    #[inline]
+    fn serialize_loc<S>(loc: &Loc<Self>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        Self: Sized,
+    {
+        let mut state = loc.item.serialize(serializer)?;
+        state.serialize_field("start", &loc.start)?;
+        state.serialize_field("end", &loc.end)?;
+        state.end()
+    }
 }
 
 impl<'ast, T: SerializeInLoc> Serialize for Loc<T> {
@@ -30,10 +65,7 @@ impl<'ast, T: SerializeInLoc> Serialize for Loc<T> {
     where
         S: Serializer
     {
-        let mut state = self.item.serialize(serializer)?;
-        state.serialize_field("start", &self.start)?;
-        state.serialize_field("end", &self.end)?;
-        state.end()
+        T::serialize_loc(self, serializer)
     }
 }
 
@@ -107,4 +139,53 @@ mod test {
               "end": 4,
         });
     }
+
+    #[test]
+    fn test_generate_ast_error_class_member() {
+        use ast::{Loc, ClassMember};
+        use serde_json::to_value;
+
+        let member = Loc::new(3, 7, ClassMember::Error);
+
+        assert_eq!(to_value(member).unwrap(), json!({
+            "type": "ErrorClassMember",
+            "start": 3,
+            "end": 7,
+        }));
+    }
+
+    #[test]
+    fn test_generate_ast_mock_spans() {
+        use ast::{Expression, ExpressionNode};
+        use ast::expression::BinaryExpression;
+        use ast::OperatorKind::Addition;
+        use serde_json::to_value;
+        use testing::Mock;
+
+        let mock = Mock::new();
+        let expression: ExpressionNode = mock.ptr_at(0, 5, BinaryExpression {
+            operator: Addition,
+            left: mock.ptr_at(0, 1, "a"),
+            right: mock.ptr_at::<Expression, _>(4, 5, "b"),
+        });
+
+        assert_eq!(to_value(expression).unwrap(), json!({
+            "type": "BinaryExpression",
+            "operator": "+",
+            "left": {
+                "type": "Identifier",
+                "name": "a",
+                "start": 0,
+                "end": 1,
+            },
+            "right": {
+                "type": "Identifier",
+                "name": "b",
+                "start": 4,
+                "end": 5,
+            },
+            "start": 0,
+            "end": 5,
+        }));
+    }
 }
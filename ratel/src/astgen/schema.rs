@@ -0,0 +1,270 @@
+//! Test-only `Serializer` that checks the shape of the emitted AST rather
+//! than producing any output. Every struct must emit exactly as many fields
+//! as it declared to `serialize_struct`, and no field name may repeat.
+
+use std::fmt::{self, Display};
+use std::error;
+
+use serde::ser::{self, Serialize};
+
+#[derive(Debug)]
+pub struct SchemaError(String);
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl error::Error for SchemaError {}
+
+impl ser::Error for SchemaError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SchemaError(msg.to_string())
+    }
+}
+
+/// Walk `value`, returning an error for the first struct whose declared field
+/// count or field names don't match what was actually written.
+pub fn check<T: Serialize>(value: &T) -> Result<(), SchemaError> {
+    value.serialize(SchemaChecker)
+}
+
+pub struct SchemaChecker;
+
+pub struct StructChecker {
+    name: &'static str,
+    declared: usize,
+    fields: Vec<&'static str>,
+}
+
+type Result<T, E = SchemaError> = ::std::result::Result<T, E>;
+
+impl ser::Serializer for SchemaChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = StructChecker;
+    type SerializeStructVariant = StructChecker;
+
+    fn serialize_bool(self, _: bool) -> Result<()> { Ok(()) }
+    fn serialize_i8(self, _: i8) -> Result<()> { Ok(()) }
+    fn serialize_i16(self, _: i16) -> Result<()> { Ok(()) }
+    fn serialize_i32(self, _: i32) -> Result<()> { Ok(()) }
+    fn serialize_i64(self, _: i64) -> Result<()> { Ok(()) }
+    fn serialize_u8(self, _: u8) -> Result<()> { Ok(()) }
+    fn serialize_u16(self, _: u16) -> Result<()> { Ok(()) }
+    fn serialize_u32(self, _: u32) -> Result<()> { Ok(()) }
+    fn serialize_u64(self, _: u64) -> Result<()> { Ok(()) }
+    fn serialize_f32(self, _: f32) -> Result<()> { Ok(()) }
+    fn serialize_f64(self, _: f64) -> Result<()> { Ok(()) }
+    fn serialize_char(self, _: char) -> Result<()> { Ok(()) }
+    fn serialize_str(self, _: &str) -> Result<()> { Ok(()) }
+    fn serialize_bytes(self, _: &[u8]) -> Result<()> { Ok(()) }
+    fn serialize_none(self) -> Result<()> { Ok(()) }
+    fn serialize_unit(self) -> Result<()> { Ok(()) }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<()> { Ok(()) }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self> { Ok(self) }
+    fn serialize_tuple(self, _: usize) -> Result<Self> { Ok(self) }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self> { Ok(self) }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self> { Ok(self) }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<StructChecker> {
+        Ok(StructChecker {
+            name,
+            declared: len,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(self, name: &'static str, _: u32, _: &'static str, len: usize) -> Result<StructChecker> {
+        self.serialize_struct(name, len)
+    }
+}
+
+impl ser::SerializeSeq for SchemaChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(SchemaChecker)
+    }
+
+    fn end(self) -> Result<()> { Ok(()) }
+}
+
+impl ser::SerializeTuple for SchemaChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(SchemaChecker)
+    }
+
+    fn end(self) -> Result<()> { Ok(()) }
+}
+
+impl ser::SerializeTupleStruct for SchemaChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(SchemaChecker)
+    }
+
+    fn end(self) -> Result<()> { Ok(()) }
+}
+
+impl ser::SerializeTupleVariant for SchemaChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(SchemaChecker)
+    }
+
+    fn end(self) -> Result<()> { Ok(()) }
+}
+
+impl ser::SerializeMap for SchemaChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        key.serialize(SchemaChecker)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(SchemaChecker)
+    }
+
+    fn end(self) -> Result<()> { Ok(()) }
+}
+
+impl StructChecker {
+    fn field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        if self.fields.contains(&key) {
+            return Err(SchemaError(format!("`{}` writes the field `{}` twice", self.name, key)));
+        }
+
+        self.fields.push(key);
+        value.serialize(SchemaChecker)
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.fields.len() != self.declared {
+            return Err(SchemaError(format!(
+                "`{}` declares {} fields, but writes {}: {:?}",
+                self.name,
+                self.declared,
+                self.fields.len(),
+                self.fields
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for StructChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for StructChecker {
+    type Ok = ();
+    type Error = SchemaError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::ser::{Serializer, SerializeStruct};
+
+    struct Duplicate;
+
+    impl Serialize for Duplicate {
+        fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("Duplicate", 2)?;
+            state.serialize_field("self", &1)?;
+            state.serialize_field("self", &2)?;
+            state.end()
+        }
+    }
+
+    struct Miscounted;
+
+    impl Serialize for Miscounted {
+        fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("Miscounted", 1)?;
+            state.serialize_field("foo", &1)?;
+            state.serialize_field("bar", &2)?;
+            state.end()
+        }
+    }
+
+    #[test]
+    fn detects_duplicate_fields() {
+        assert_eq!(
+            check(&Duplicate).unwrap_err().to_string(),
+            "`Duplicate` writes the field `self` twice"
+        );
+    }
+
+    #[test]
+    fn detects_miscounted_fields() {
+        assert_eq!(
+            check(&Miscounted).unwrap_err().to_string(),
+            "`Miscounted` declares 1 fields, but writes 2: [\"foo\", \"bar\"]"
+        );
+    }
+}
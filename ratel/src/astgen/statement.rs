@@ -1,6 +1,8 @@
 use ast::statement::*;
-use ast::{Block, DeclarationKind, Declarator, Loc, Statement};
+use ast::{Block, DeclarationKind, Declarator, Expression, ExpressionNode, Literal, Loc, Node, Statement, StringLiteral};
 use astgen::SerializeInLoc;
+use astgen::function::{serialize_class, serialize_function};
+use astgen::value::as_pattern;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
 // TODO: DRY with BlockStatement
@@ -165,13 +167,23 @@ impl<'ast> SerializeInLoc for ForStatement<'ast> {
     }
 }
 
+/// Serialize the `left` field of `for`-`in` and `for`-`of` loops, object
+/// and array literals destructured by the loop being patterns.
+#[inline]
+fn left<S: SerializeStruct>(state: &mut S, left: Node<ForInit>) -> Result<(), S::Error> {
+    match left.item {
+        ForInit::Expression(expression) => state.serialize_field("left", &as_pattern(expression)),
+        ForInit::Declaration(_) => state.serialize_field("left", &left),
+    }
+}
+
 impl<'ast> SerializeInLoc for ForInStatement<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
         S: Serializer,
     {
         self.in_loc(serializer, "ForInStatement", 3, |state| {
-            state.serialize_field("left", &self.left)?;
+            left(state, self.left)?;
             state.serialize_field("right", &self.right)?;
             state.serialize_field("body", &self.body)
         })
@@ -184,7 +196,7 @@ impl<'ast> SerializeInLoc for ForOfStatement<'ast> {
         S: Serializer,
     {
         self.in_loc(serializer, "ForOfStatement", 3, |state| {
-            state.serialize_field("left", &self.left)?;
+            left(state, self.left)?;
             state.serialize_field("right", &self.right)?;
             state.serialize_field("body", &self.body)
         })
@@ -203,6 +215,110 @@ impl<'ast> SerializeInLoc for IfStatement<'ast> {
     }
 }
 
+impl<'ast> SerializeInLoc for ImportStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ImportDeclaration", 2, |state| {
+            state.serialize_field("specifiers", &self.specifiers)?;
+            state.serialize_field("source", &source(self.source))
+        })
+    }
+}
+
+impl<'ast> SerializeInLoc for ImportSpecifier<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::ImportSpecifier::*;
+
+        match *self {
+            Default(ref local) => self.in_loc(serializer, "ImportDefaultSpecifier", 1, |state| {
+                state.serialize_field("local", local)
+            }),
+            Namespace(ref local) => self.in_loc(serializer, "ImportNamespaceSpecifier", 1, |state| {
+                state.serialize_field("local", local)
+            }),
+            Named { ref imported, ref local } => self.in_loc(serializer, "ImportSpecifier", 2, |state| {
+                state.serialize_field("imported", imported)?;
+                state.serialize_field("local", local)
+            }),
+        }
+    }
+}
+
+/// Module name of an `import` or `export`, as a string `Literal`.
+#[inline]
+fn source<'ast>(source: Node<'ast, StringLiteral<'ast>>) -> Loc<Literal<'ast>> {
+    Loc::new(source.start, source.end, Literal::String(source.item))
+}
+
+/// Function or class of `export default`, typed as a declaration.
+struct DefaultDeclaration<'ast>(ExpressionNode<'ast>);
+
+impl<'ast> Serialize for DefaultDeclaration<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = match self.0.item {
+            Expression::Function(ref function) => serialize_function(function, "FunctionDeclaration", serializer)?,
+            Expression::Class(ref class) => serialize_class(class, "ClassDeclaration", serializer)?,
+            _ => return self.0.serialize(serializer),
+        };
+
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+impl<'ast> SerializeInLoc for ExportStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::ExportStatement::*;
+
+        match *self {
+            Declaration(ref declaration) => self.in_loc(serializer, "ExportNamedDeclaration", 3, |state| {
+                state.serialize_field("declaration", declaration)?;
+                state.serialize_field("specifiers", &[] as &[()])?;
+                state.serialize_field("source", &())
+            }),
+            DefaultDeclaration(declaration) => self.in_loc(serializer, "ExportDefaultDeclaration", 1, |state| {
+                state.serialize_field("declaration", &self::DefaultDeclaration(declaration))
+            }),
+            Default(ref expression) => self.in_loc(serializer, "ExportDefaultDeclaration", 1, |state| {
+                state.serialize_field("declaration", expression)
+            }),
+            Named { ref specifiers, source: module } => self.in_loc(serializer, "ExportNamedDeclaration", 3, |state| {
+                state.serialize_field("declaration", &())?;
+                state.serialize_field("specifiers", specifiers)?;
+                state.serialize_field("source", &module.map(source))
+            }),
+            All { ref exported, source: module } => self.in_loc(serializer, "ExportAllDeclaration", 2, |state| {
+                state.serialize_field("exported", exported)?;
+                state.serialize_field("source", &source(module))
+            }),
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for ExportSpecifier<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ExportSpecifier", 2, |state| {
+            state.serialize_field("local", &self.local)?;
+            state.serialize_field("exported", &self.exported)
+        })
+    }
+}
+
 impl<'ast> SerializeInLoc for Statement<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -260,6 +376,8 @@ impl<'ast> SerializeInLoc for Statement<'ast> {
             Function(statement) => statement.serialize(serializer),
             Class(statement) => statement.serialize(serializer),
             Switch(statement) => statement.serialize(serializer),
+            Import(statement) => statement.serialize(serializer),
+            Export(statement) => statement.serialize(serializer),
         }
     }
 }
@@ -582,6 +700,54 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_statement_labeled_statement() {
+        expect_parse!("foo: for (;;) { break foo; }", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "LabeledStatement",
+                    "label": {
+                        "type": "Identifier",
+                        "name": "foo",
+                        "start": 0,
+                        "end": 3
+                    },
+                    "body": {
+                        "type": "ForStatement",
+                        "init": null,
+                        "test": null,
+                        "update": null,
+                        "body": {
+                            "type": "BlockStatement",
+                            "body": [
+                                {
+                                    "type": "BreakStatement",
+                                    "label": {
+                                        "type": "Identifier",
+                                        "name": "foo",
+                                        "start": 22,
+                                        "end": 25
+                                    },
+                                    "start": 16,
+                                    "end": 25,
+                                }
+                            ],
+                            "start": 14,
+                            "end": 28,
+                        },
+                        "start": 5,
+                        "end": 28,
+                    },
+                    "start": 0,
+                    "end": 28,
+                }
+              ],
+              "start": 0,
+              "end": 28,
+        });
+    }
+
     #[test]
     fn test_statement_throw_statement() {
         expect_parse!("throw foo;", {
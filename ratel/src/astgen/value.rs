@@ -1,5 +1,7 @@
 use ast::expression::{PropertyKey, TaggedTemplateExpression, TemplateLiteral};
-use ast::{Expression, Literal, Loc, NodeList, Pattern, Property};
+use ast::expression::{ObjectExpression, BinaryExpression, SpreadExpression};
+use ast::{Expression, ExpressionNode, Literal, NumberLiteral, StringLiteral};
+use ast::{Loc, Node, NodeList, OperatorKind, Pattern, Property};
 use astgen::SerializeInLoc;
 use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
 
@@ -84,6 +86,20 @@ impl<'ast> SerializeInLoc for &'ast str {
     }
 }
 
+/// Private name of a class member, serialized without the `#`
+pub struct PrivateName<'ast>(pub &'ast str);
+
+impl<'ast> SerializeInLoc for PrivateName<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "PrivateIdentifier", 1, |state| {
+            state.serialize_field("name", self.0)
+        })
+    }
+}
+
 impl<'ast> SerializeInLoc for TemplateLiteral<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -119,6 +135,10 @@ impl<'ast> SerializeInLoc for Property<'ast> {
                 let state = Expression::Identifier(value).serialize(serializer);
                 state
             }
+            ShorthandDefault { name, value } => self.in_loc(serializer, "AssignmentPattern", 2, |state| {
+                state.serialize_field("left", &Loc::new(name.start, name.end, Expression::Identifier(name.item)))?;
+                state.serialize_field("right", &*value)
+            }),
             Literal { key, value } => {
                 let computed = if let PropertyKey::Computed(_) = key.item {
                     true
@@ -135,11 +155,11 @@ impl<'ast> SerializeInLoc for Property<'ast> {
                     state.serialize_field("kind", &"init")
                 })
             }
-            Method { key, value } => self.in_loc(serializer, "Property", 6, |state| {
+            Method { key, value } => self.in_loc_as(serializer, "Property", "MethodProperty", 6, |state| {
                 state.serialize_field("key", &*key)?;
                 state.serialize_field("method", &true)?;
                 state.serialize_field("shorthand", &false)?;
-                state.serialize_field("computed", &false)?;
+                state.serialize_field("computed", &matches!(key.item, PropertyKey::Computed(_)))?;
                 state.serialize_field("value", &value)?;
                 state.serialize_field("kind", &"init")
             }),
@@ -169,6 +189,11 @@ impl<'ast> Serialize for Loc<PropertyKey<'ast>> {
                 self.end,
                 Expression::Identifier(value),
             )),
+            Private(name) => serializer.serialize_some(&Loc::new(
+                self.start,
+                self.end,
+                PrivateName(name),
+            )),
         }
     }
 }
@@ -180,67 +205,37 @@ impl<'ast> SerializeInLoc for Literal<'ast> {
     {
         use self::Literal::*;
 
-        // FIXME: StringLiteral vs. Literal
-        let literal_type = match *self {
-            String(_) => "Literal",
-            _ => "Literal",
+        let kind = match *self {
+            Null           => "NullLiteral",
+            Boolean(_)     => "BooleanLiteral",
+            Number(_)      => "NumericLiteral",
+            String(_)      => "StringLiteral",
+            RegEx(_)       => "RegExpLiteral",
         };
 
-        self.in_loc(serializer, literal_type, 1, |state| {
+        self.in_loc_as(serializer, "Literal", kind, 2, |state| {
             match *self {
-                Undefined => {
-                    state.serialize_field("raw", &"undefined")
-                },
                 Null => {
                     state.serialize_field("value", &())?;
                     state.serialize_field("raw", &"null")
                 },
-                True => {
-                    state.serialize_field("value", &true)?;
-                    state.serialize_field("raw", &"true")
-                },
-                False => {
-                    state.serialize_field("value", &false)?;
-                    state.serialize_field("raw", &"false")
+                Boolean(value) => {
+                    state.serialize_field("value", &value)?;
+                    state.serialize_field("raw", &self.raw())
                 },
-                Number(number) => {
-                    if number.len() > 2 {
-                        let prefix = &number[0..2];
-                        let is_hexdecimal = prefix == "0x" || prefix == "0X";
-                        let is_octal = prefix == "0o" || prefix == "0O";
-                        if is_hexdecimal || is_octal {
-                            let value = unsafe { number.slice_unchecked(2, number.len()) };
-                            let radix = if is_hexdecimal { 16 } else { 8 };
-                            state.serialize_field(
-                                "value",
-                                &i32::from_str_radix(value, radix).expect("Invalid number"),
-                            )?;
-                            return state.serialize_field("raw", &number)
-                        }
-                    }
-
-                    if is_float(number) {
-                        let value: f64 = number.parse().expect("Invalid number");
+                Number(NumberLiteral { value, raw }) => {
+                    // Integers are written without a fraction, unless
+                    // the source has one
+                    if !is_float(raw) && value.fract() == 0.0 && value.abs() < 9007199254740992.0 {
+                        state.serialize_field("value", &(value as i64))?;
+                    } else {
                         state.serialize_field("value", &value)?;
-                        return state.serialize_field("raw", &number)
                     }
-
-                    let value: i64 = number.parse().expect("Invalid number");
+                    state.serialize_field("raw", &raw)
+                },
+                String(StringLiteral { value, raw }) => {
                     state.serialize_field("value", &value)?;
-                    state.serialize_field("raw", &number)
-                }
-                Binary(number) => {
-                    let value = unsafe { number.slice_unchecked(2, number.len()) };
-                    state.serialize_field(
-                        "value",
-                        &i32::from_str_radix(value, 2).expect("Invalid number"),
-                    )?;
-                    state.serialize_field("raw", &number)
-                }
-                String(value) => {
-                    let parsed_value = unsafe { value.slice_unchecked(1, value.len() - 1) };
-                    state.serialize_field("value", &parsed_value)?;
-                    state.serialize_field("raw", &value)
+                    state.serialize_field("raw", &raw)
                 },
                 RegEx(value) => {
                     state.serialize_field("regex", &parse_regex(value))?;
@@ -262,10 +257,22 @@ impl<'ast> SerializeInLoc for Pattern<'ast> {
             Void => unreachable!(),
             Identifier(a) => Expression::Identifier(a).serialize(serializer),
             ObjectPattern { properties } => self.in_loc(serializer, "ObjectPattern", 1, |state| {
-                state.serialize_field("keys", &properties)
+                state.serialize_field("properties", &pattern_properties(properties))
             }),
             ArrayPattern { elements } => self.in_loc(serializer, "ArrayPattern", 1, |state| {
-                state.serialize_field("elements", &elements)
+                let mut elements = elements.iter().peekable();
+                let mut holes = Vec::new();
+
+                while let Some(element) = elements.next() {
+                    match element.item {
+                        // Trailing comma, as in `ArrayExpression::elements`
+                        Void if elements.peek().is_none() => {},
+                        Void => holes.push(None),
+                        _ => holes.push(Some(element)),
+                    }
+                }
+
+                state.serialize_field("elements", &holes)
             }),
             AssignmentPattern { left, right } => {
                 self.in_loc(serializer, "AssignmentPattern", 2, |state| {
@@ -280,6 +287,96 @@ impl<'ast> SerializeInLoc for Pattern<'ast> {
     }
 }
 
+/// Expression standing for a pattern, such as the value of a property of
+/// an `ObjectPattern`, serialized as the pattern.
+pub(super) struct PatternExpression<'ast>(Expression<'ast>);
+
+/// Property of an `ObjectPattern`, the value of which is a pattern.
+struct PatternProperty<'ast>(Node<'ast, Property<'ast>>);
+
+#[inline]
+pub(super) fn as_pattern<'ast>(expression: ExpressionNode<'ast>) -> Loc<PatternExpression<'ast>> {
+    Loc::new(expression.start, expression.end, PatternExpression(expression.item))
+}
+
+#[inline]
+fn pattern_properties<'ast>(properties: NodeList<'ast, Property<'ast>>) -> Vec<Loc<PatternProperty<'ast>>> {
+    properties.iter().map(|&property| Loc::new(property.start, property.end, PatternProperty(property))).collect()
+}
+
+impl<'ast> SerializeInLoc for PatternExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Expression::Object(ObjectExpression { body }) => self.in_loc(serializer, "ObjectPattern", 1, |state| {
+                state.serialize_field("properties", &pattern_properties(body))
+            }),
+            Expression::Array(ref array) => self.in_loc(serializer, "ArrayPattern", 1, |state| {
+                let elements = array.elements().map(|element| element.map(|&element| as_pattern(element))).collect::<Vec<_>>();
+
+                state.serialize_field("elements", &elements)
+            }),
+            Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, left, right }) => {
+                self.in_loc(serializer, "AssignmentPattern", 2, |state| {
+                    state.serialize_field("left", &as_pattern(left))?;
+                    state.serialize_field("right", &*right)
+                })
+            },
+            Expression::Spread(SpreadExpression { argument }) => self.in_loc(serializer, "RestElement", 1, |state| {
+                state.serialize_field("argument", &as_pattern(argument))
+            }),
+            ref expression => expression.serialize(serializer),
+        }
+    }
+}
+
+impl<'ast> SerializeInLoc for PatternProperty<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        let property = self.0;
+
+        match property.item {
+            Property::Shorthand(name) => self.in_loc(serializer, "Property", 6, |state| {
+                let name = Loc::new(property.start, property.end, Expression::Identifier(name));
+
+                state.serialize_field("key", &name)?;
+                state.serialize_field("method", &false)?;
+                state.serialize_field("shorthand", &true)?;
+                state.serialize_field("computed", &false)?;
+                state.serialize_field("value", &name)?;
+                state.serialize_field("kind", &"init")
+            }),
+            // The property on its own is serialized as an `AssignmentPattern`
+            Property::ShorthandDefault { name, .. } => self.in_loc(serializer, "Property", 6, |state| {
+                state.serialize_field("key", &Loc::new(name.start, name.end, Expression::Identifier(name.item)))?;
+                state.serialize_field("method", &false)?;
+                state.serialize_field("shorthand", &true)?;
+                state.serialize_field("computed", &false)?;
+                state.serialize_field("value", &property)?;
+                state.serialize_field("kind", &"init")
+            }),
+            Property::Literal { key, value } => self.in_loc(serializer, "Property", 6, |state| {
+                let computed = matches!(key.item, PropertyKey::Computed(_));
+
+                state.serialize_field("key", &*key)?;
+                state.serialize_field("method", &false)?;
+                state.serialize_field("shorthand", &false)?;
+                state.serialize_field("computed", &computed)?;
+                state.serialize_field("value", &as_pattern(value))?;
+                state.serialize_field("kind", &"init")
+            }),
+            Property::Spread { argument } => self.in_loc(serializer, "RestElement", 1, |state| {
+                state.serialize_field("argument", &as_pattern(argument))
+            }),
+            Property::Method { .. } => property.item.serialize(serializer),
+        }
+    }
+}
+
 impl<'ast> SerializeInLoc for TemplateElement<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
@@ -329,14 +426,15 @@ mod test {
 
     #[test]
     fn test_value_undefined() {
+        // `undefined` is a global binding, not a literal
         expect_parse!("undefined", {
             "type": "Program",
             "body": [
                 {
                     "type": "ExpressionStatement",
                     "expression": {
-                        "type": "Literal",
-                        "raw": "undefined",
+                        "type": "Identifier",
+                        "name": "undefined",
                         "start": 0,
                         "end": 9
                     },
@@ -564,6 +662,29 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_value_string_line_continuation() {
+        expect_parse!("'foo\\\r\nbar'", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "Literal",
+                        "value": "foobar",
+                        "raw": "'foo\\\r\nbar'",
+                        "start": 0,
+                        "end": 11
+                    },
+                    "start": 0,
+                    "end": 11,
+                }
+            ],
+            "start": 0,
+            "end": 11,
+        });
+    }
+
     #[test]
     fn test_regex() {
         expect_parse!(r#"/^\b\w+/m"#, {
@@ -759,16 +880,46 @@ mod test {
                             "type":"VariableDeclarator",
                             "id":{
                                 "type":"ObjectPattern",
-                                "keys":[
+                                "properties":[
                                     {
-                                        "type":"Identifier",
-                                        "name":"a",
+                                        "type":"Property",
+                                        "key":{
+                                            "type":"Identifier",
+                                            "name":"a",
+                                            "start":5,
+                                            "end":6
+                                        },
+                                        "method":false,
+                                        "shorthand":true,
+                                        "computed":false,
+                                        "value":{
+                                            "type":"Identifier",
+                                            "name":"a",
+                                            "start":5,
+                                            "end":6
+                                        },
+                                        "kind":"init",
                                         "start":5,
                                         "end":6
                                     },
                                     {
-                                        "type":"Identifier",
-                                        "name":"b",
+                                        "type":"Property",
+                                        "key":{
+                                            "type":"Identifier",
+                                            "name":"b",
+                                            "start":8,
+                                            "end":9
+                                        },
+                                        "method":false,
+                                        "shorthand":true,
+                                        "computed":false,
+                                        "value":{
+                                            "type":"Identifier",
+                                            "name":"b",
+                                            "start":8,
+                                            "end":9
+                                        },
+                                        "kind":"init",
                                         "start":8,
                                         "end":9
                                     }
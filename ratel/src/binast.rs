@@ -0,0 +1,556 @@
+//! Experimental binary encoding of the AST.
+//!
+//! The encoder walks the same tree as the ESTree JSON output, but writes it as
+//! a compact, pre-tokenized stream: every string (node types, field names,
+//! identifiers, raw literals) is stored once in a string table and referenced
+//! by index, and numbers are written as varints. The decoder reads the stream
+//! back into a generic `Value` tree.
+//!
+//! Layout: `MAGIC`, `VERSION`, string count, strings (length + UTF-8 bytes),
+//! then a single encoded value for the `Program` node.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::{error, str};
+
+use serde::ser::{self, Serialize};
+use module::Module;
+
+pub const MAGIC: &[u8; 5] = b"RBAST";
+pub const VERSION: u8 = 1;
+
+/// Deepest nesting of lists and nodes `decode` reads before giving up
+/// with `BinAstError::TooDeep`, so that no input can overflow the stack.
+pub const MAX_DEPTH: usize = 1024;
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const UNSIGNED: u8 = 3;
+const SIGNED: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+const LIST: u8 = 7;
+const NODE: u8 = 8;
+const END: u8 = 9;
+
+/// Decoded value, mirrors the structure of the ESTree JSON output.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    /// Node or any other struct, fields are kept in the order they were written
+    Node(Vec<(String, Value)>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BinAstError {
+    /// Input doesn't start with `MAGIC` or has a different `VERSION`
+    InvalidHeader,
+    UnexpectedEnd,
+    InvalidTag(u8),
+    InvalidString(usize),
+    /// Lists and nodes nested deeper than `MAX_DEPTH`
+    TooDeep,
+    /// Only produced by the encoder, for values the AST never contains
+    Unsupported(String),
+}
+
+impl Display for BinAstError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BinAstError::InvalidHeader => write!(f, "Invalid binary AST header"),
+            BinAstError::UnexpectedEnd => write!(f, "Unexpected end of binary AST"),
+            BinAstError::InvalidTag(tag) => write!(f, "Invalid tag {} in binary AST", tag),
+            BinAstError::InvalidString(index) => write!(f, "Invalid string {} in binary AST", index),
+            BinAstError::TooDeep => write!(f, "Binary AST nested deeper than {} levels", MAX_DEPTH),
+            BinAstError::Unsupported(ref msg) => write!(f, "Unsupported value: {}", msg),
+        }
+    }
+}
+
+impl error::Error for BinAstError {}
+
+impl ser::Error for BinAstError {
+    fn custom<T: Display>(msg: T) -> Self {
+        BinAstError::Unsupported(msg.to_string())
+    }
+}
+
+/// Encode the `Module` into the binary AST format.
+pub fn encode(module: &Module) -> Vec<u8> {
+    let mut encoder = Encoder {
+        strings: HashMap::new(),
+        table: Vec::new(),
+        stream: Vec::with_capacity(1024),
+    };
+
+    module.serialize(&mut encoder).expect("AST can always be encoded");
+
+    let mut out = Vec::with_capacity(encoder.stream.len() + 64);
+
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    write_varint(&mut out, encoder.table.len() as u64);
+
+    for string in &encoder.table {
+        write_varint(&mut out, string.len() as u64);
+        out.extend_from_slice(string.as_bytes());
+    }
+
+    out.extend_from_slice(&encoder.stream);
+    out
+}
+
+/// Decode the binary AST format produced by `encode`.
+pub fn decode(bytes: &[u8]) -> Result<Value, BinAstError> {
+    if bytes.len() < MAGIC.len() + 1 || &bytes[..MAGIC.len()] != MAGIC || bytes[MAGIC.len()] != VERSION {
+        return Err(BinAstError::InvalidHeader);
+    }
+
+    let mut decoder = Decoder {
+        bytes,
+        index: MAGIC.len() + 1,
+        table: Vec::new(),
+        depth: 0,
+    };
+
+    let count = decoder.varint()?;
+
+    for _ in 0..count {
+        let len = decoder.varint()? as usize;
+        let start = decoder.index;
+        let end = start.checked_add(len).filter(|&end| end <= bytes.len()).ok_or(BinAstError::UnexpectedEnd)?;
+        let string = str::from_utf8(&bytes[start..end]).map_err(|_| BinAstError::InvalidString(decoder.table.len()))?;
+
+        decoder.table.push(string);
+        decoder.index = end;
+    }
+
+    decoder.value()
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Encoder {
+    /// Index of every string in the table, given in order of first use
+    strings: HashMap<String, u64>,
+    table: Vec<String>,
+    stream: Vec<u8>,
+}
+
+impl Encoder {
+    fn tag(&mut self, tag: u8) {
+        self.stream.push(tag);
+    }
+
+    fn varint(&mut self, value: u64) {
+        write_varint(&mut self.stream, value);
+    }
+
+    fn string(&mut self, value: &str) {
+        let index = match self.strings.get(value) {
+            Some(&index) => index,
+            None => {
+                let index = self.table.len() as u64;
+
+                self.strings.insert(value.to_owned(), index);
+                self.table.push(value.to_owned());
+                index
+            }
+        };
+
+        self.tag(STRING);
+        self.varint(index);
+    }
+
+    fn unsupported<T>(what: &str) -> Result<T, BinAstError> {
+        Err(BinAstError::Unsupported(what.to_owned()))
+    }
+}
+
+type Result<T, E = BinAstError> = ::std::result::Result<T, E>;
+
+impl ser::Serializer for &mut Encoder {
+    type Ok = ();
+    type Error = BinAstError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = ser::Impossible<(), BinAstError>;
+    type SerializeMap = ser::Impossible<(), BinAstError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = ser::Impossible<(), BinAstError>;
+
+    fn serialize_bool(self, value: bool) -> Result<()> {
+        self.tag(if value { TRUE } else { FALSE });
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<()> { self.serialize_i64(value as i64) }
+    fn serialize_i16(self, value: i16) -> Result<()> { self.serialize_i64(value as i64) }
+    fn serialize_i32(self, value: i32) -> Result<()> { self.serialize_i64(value as i64) }
+
+    fn serialize_i64(self, value: i64) -> Result<()> {
+        if value >= 0 {
+            return self.serialize_u64(value as u64);
+        }
+
+        self.tag(SIGNED);
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<()> { self.serialize_u64(value as u64) }
+    fn serialize_u16(self, value: u16) -> Result<()> { self.serialize_u64(value as u64) }
+    fn serialize_u32(self, value: u32) -> Result<()> { self.serialize_u64(value as u64) }
+
+    fn serialize_u64(self, value: u64) -> Result<()> {
+        self.tag(UNSIGNED);
+        self.varint(value);
+        Ok(())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<()> { self.serialize_f64(value as f64) }
+
+    fn serialize_f64(self, value: f64) -> Result<()> {
+        self.tag(FLOAT);
+        self.stream.extend_from_slice(&value.to_bits().to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> Result<()> {
+        let mut buf = [0; 4];
+        self.serialize_str(value.encode_utf8(&mut buf))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<()> {
+        self.string(value);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<()> { Encoder::unsupported("bytes") }
+
+    fn serialize_none(self) -> Result<()> {
+        self.tag(NULL);
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> { self.serialize_none() }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<()> { self.serialize_none() }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<()> {
+        Encoder::unsupported("newtype variant")
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self> {
+        self.tag(LIST);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self> { self.serialize_seq(Some(len)) }
+
+    fn serialize_tuple_struct(self, _: &'static str, len: usize) -> Result<Self> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant> {
+        Encoder::unsupported("tuple variant")
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap> {
+        Encoder::unsupported("map")
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self> {
+        self.tag(NODE);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant> {
+        Encoder::unsupported("struct variant")
+    }
+}
+
+impl ser::SerializeSeq for &mut Encoder {
+    type Ok = ();
+    type Error = BinAstError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Encoder {
+    type Ok = ();
+    type Error = BinAstError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Encoder {
+    type Ok = ();
+    type Error = BinAstError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Encoder {
+    type Ok = ();
+    type Error = BinAstError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.string(key);
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    table: Vec<&'a str>,
+
+    /// Number of lists and nodes being read
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn byte(&mut self) -> Result<u8> {
+        let byte = *self.bytes.get(self.index).ok_or(BinAstError::UnexpectedEnd)?;
+
+        self.index += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+
+            if shift < 64 {
+                value |= ((byte & 0x7F) as u64) << shift;
+            }
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn string_at(&self, index: u64) -> Result<String> {
+        self.table
+            .get(index as usize)
+            .map(|string| (*string).to_owned())
+            .ok_or(BinAstError::InvalidString(index as usize))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.byte()? {
+            STRING => {
+                let index = self.varint()?;
+                self.string_at(index)
+            },
+            tag => Err(BinAstError::InvalidTag(tag)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let tag = self.byte()?;
+
+        self.value_from_tag(tag)
+    }
+
+    /// Go one list or node deeper, up to `MAX_DEPTH`.
+    fn enter(&mut self) -> Result<()> {
+        if self.depth == MAX_DEPTH {
+            return Err(BinAstError::TooDeep);
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn value_from_tag(&mut self, tag: u8) -> Result<Value> {
+        Ok(match tag {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            UNSIGNED => Value::Unsigned(self.varint()?),
+            SIGNED => {
+                let value = self.varint()?;
+                Value::Signed(((value >> 1) as i64) ^ -((value & 1) as i64))
+            },
+            FLOAT => {
+                let mut bits = [0; 8];
+
+                for byte in bits.iter_mut() {
+                    *byte = self.byte()?;
+                }
+
+                Value::Float(f64::from_bits(u64::from_le_bytes(bits)))
+            },
+            STRING => {
+                let index = self.varint()?;
+                Value::String(self.string_at(index)?)
+            },
+            LIST => {
+                self.enter()?;
+
+                let mut values = Vec::new();
+
+                loop {
+                    match self.byte()? {
+                        END => break,
+                        tag => values.push(self.value_from_tag(tag)?),
+                    }
+                }
+
+                self.depth -= 1;
+                Value::List(values)
+            },
+            NODE => {
+                self.enter()?;
+
+                let mut fields = Vec::new();
+
+                loop {
+                    match self.bytes.get(self.index) {
+                        Some(&END) => {
+                            self.index += 1;
+                            break;
+                        },
+                        Some(_) => {
+                            let key = self.string()?;
+                            let value = self.value()?;
+
+                            fields.push((key, value));
+                        },
+                        None => return Err(BinAstError::UnexpectedEnd),
+                    }
+                }
+
+                self.depth -= 1;
+                Value::Node(fields)
+            },
+            tag => return Err(BinAstError::InvalidTag(tag)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+    use serde_json::{self, to_value};
+
+    fn to_json(value: Value) -> serde_json::Value {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(value) => value.into(),
+            Value::Unsigned(value) => value.into(),
+            Value::Signed(value) => value.into(),
+            Value::Float(value) => value.into(),
+            Value::String(value) => value.into(),
+            Value::List(values) => values.into_iter().map(to_json).collect::<Vec<_>>().into(),
+            Value::Node(fields) => {
+                serde_json::Value::Object(fields.into_iter().map(|(key, value)| (key, to_json(value))).collect())
+            },
+        }
+    }
+
+    #[test]
+    fn round_trip_matches_estree() {
+        let source = "function foo(a, b = 2, ...c) { return a ** -1 + 3.14 + 0x10 + 'bar'; } class Baz extends Foo { get qux() { return `${this}` } }";
+        let module = parse(source).unwrap();
+        let decoded = decode(&encode(&module)).unwrap();
+
+        assert_eq!(to_json(decoded), to_value(&module).unwrap());
+    }
+
+    #[test]
+    fn deduplicates_strings() {
+        let encoded = encode(&parse("foo;foo;foo;foo;").unwrap());
+        let occurrences = encoded.windows(3).filter(|window| window == b"foo").count();
+
+        assert_eq!(occurrences, 1);
+    }
+
+    #[test]
+    fn encodes_deterministically() {
+        let source = "let a = { b: 'c', d: [e, f] }; function g(h) { return h.i + 'c'; }";
+
+        assert_eq!(encode(&parse(source).unwrap()), encode(&parse(source).unwrap()));
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(decode(b"foo"), Err(BinAstError::InvalidHeader));
+        assert_eq!(decode(b"RBAST\x01\x01\x03fo"), Err(BinAstError::UnexpectedEnd));
+        assert_eq!(decode(b"RBAST\x01\x00\x0F"), Err(BinAstError::InvalidTag(0x0F)));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| {
+            let mut bytes = b"RBAST\x01\x00".to_vec();
+
+            bytes.extend((0..depth).map(|_| LIST));
+            bytes.extend((0..depth).map(|_| END));
+            bytes
+        };
+
+        assert!(decode(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(decode(&nested(MAX_DEPTH + 1)), Err(BinAstError::TooDeep));
+        assert_eq!(decode(&nested(1_000_000)), Err(BinAstError::TooDeep));
+    }
+}
@@ -0,0 +1,73 @@
+//! Cooperative cancellation of long-running work, such as running passes
+//! over a large bundle, so that editors and other hosts can abandon it
+//! when the source changes again before it's done.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared flag telling work to stop. Clones share the flag, so a host
+/// keeps one to call `cancel`, possibly from another thread, and hands
+/// another to the work, which checks it between steps.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    #[inline]
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Ask the work holding a clone of the token to stop.
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether `cancel` was called on the token or any of its clones.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Get `Err(Cancelled)` if the token was cancelled, for use with `?`
+    /// between steps of the work.
+    #[inline]
+    pub fn check(&self) -> Result<(), Cancelled> {
+        match self.is_cancelled() {
+            true => Err(Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Error of work stopped by a `CancellationToken`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cancelled")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let work = token.clone();
+
+        assert_eq!(work.check(), Ok(()));
+
+        thread::spawn(move || token.cancel()).join().unwrap();
+
+        assert_eq!(work.is_cancelled(), true);
+        assert_eq!(work.check(), Err(Cancelled));
+    }
+}
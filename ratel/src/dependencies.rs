@@ -0,0 +1,314 @@
+use allocated::*;
+use toolshed::Arena;
+use lexer::Lexer;
+use lexer::Token::{self, *};
+
+/// Kind of a `Dependency`, see `scan_dependencies`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum DependencyKind {
+    /// `require('a')`, or the `__webpack_require__('a')` of a bundle
+    Require,
+
+    /// `import a from 'a'` or `import 'a'`
+    Import,
+
+    /// `export { a } from 'a'` or `export * from 'a'`
+    Export,
+
+    /// `import('a')`
+    DynamicImport,
+}
+
+/// Module specifier found by `scan_dependencies`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Dependency<'src> {
+    pub kind: DependencyKind,
+
+    /// Specifier without the quotes, escapes are left as they are
+    pub specifier: &'src str,
+
+    /// `start` of the string literal of the specifier
+    pub start: u32,
+
+    /// `end` of the string literal of the specifier
+    pub end: u32,
+}
+
+/// Find the specifiers of all `require` calls, `import` and `export`
+/// declarations and `import()` expressions in the `source`, in source
+/// order, straight from the tokens without building the AST. Only string
+/// literal specifiers are found.
+///
+/// Lines of the runtime of webpack bundles, which start with its `/******/`
+/// and `/***/` markers, including the arrow IIFE wrapping the module table,
+/// are skipped without being tokenized, so that the scan goes straight to
+/// the code of the bundled modules.
+///
+/// Whether a `/` starts a regular expression is told apart by the previous
+/// token, as the scanner doesn't know the syntax around it: `/` following
+/// an identifier, a literal or a closing bracket of any kind is a division.
+pub fn scan_dependencies<'src>(source: &'src str) -> Vec<Dependency<'src>> {
+    let arena = Arena::new();
+    let mut scanner = Scanner {
+        source,
+        lexer: Lexer::new(&arena, source),
+        previous: Semicolon,
+        braces: Vec::new(),
+        dependencies: Vec::new(),
+    };
+
+    scanner.token();
+    scanner.scan();
+    scanner.dependencies
+}
+
+/// Kind of an open `{` or `${`
+#[derive(PartialEq, Clone, Copy)]
+enum Brace {
+    Block,
+    Template,
+}
+
+struct Scanner<'src, 'arena> {
+    source: &'src str,
+    lexer: Lexer<'arena>,
+
+    /// Token before the current one, skipped lines aside
+    previous: Token,
+
+    /// Braces opened and not closed yet
+    braces: Vec<Brace>,
+
+    dependencies: Vec<Dependency<'src>>,
+}
+
+impl<'src, 'arena> Scanner<'src, 'arena> {
+    fn scan(&mut self) {
+        loop {
+            match self.lexer.token {
+                EndOfProgram | UnexpectedEndOfProgram => return,
+                Identifier if self.previous != OperatorOptionalChain => {
+                    match self.lexer.token_as_str() {
+                        "require" | "__webpack_require__" => {
+                            self.next();
+                            self.call(DependencyKind::Require);
+                        },
+                        _ => self.next(),
+                    }
+                },
+                Import => {
+                    self.next();
+
+                    match self.lexer.token {
+                        ParenOpen => self.call(DependencyKind::DynamicImport),
+                        LiteralString => self.string(DependencyKind::Import),
+                        Accessor => {},
+                        _ => self.specifier_after_from(DependencyKind::Import),
+                    }
+                },
+                Export => {
+                    self.next();
+
+                    if let OperatorMultiplication | BraceOpen = self.lexer.token {
+                        self.specifier_after_from(DependencyKind::Export);
+                    }
+                },
+                _ => self.next(),
+            }
+        }
+    }
+
+    /// Advance to the next token, reading regular expressions and the rest
+    /// of template literals where they are.
+    fn next(&mut self) {
+        self.previous = self.lexer.token;
+        self.lexer.consume();
+        self.token();
+    }
+
+    fn token(&mut self) {
+        while self.at_runtime_line() {
+            self.lexer.skip_line();
+        }
+
+        self.lexer.comments.clear();
+
+        if self.lexer.token == BraceClose && self.braces.pop() == Some(Brace::Template) {
+            self.lexer.read_template_kind();
+        }
+
+        match self.lexer.token {
+            BraceOpen => self.braces.push(Brace::Block),
+            TemplateOpen => self.braces.push(Brace::Template),
+            OperatorDivision | OperatorDivideAssign if !ends_operand(self.previous) => {
+                self.lexer.read_regular_expression();
+                self.lexer.token = LiteralRegEx;
+            },
+            _ => {},
+        }
+    }
+
+    /// Check whether the current token follows a webpack runtime marker
+    /// starting its line.
+    fn at_runtime_line(&mut self) -> bool {
+        let comment = match self.lexer.comments.last() {
+            Some(comment) => *comment,
+            None => return false,
+        };
+
+        self.lexer.comments.clear();
+
+        let source = self.source.as_bytes();
+        let line_start = comment.start == 0 || source[comment.start as usize - 1] == b'\n';
+        let same_line = !source[comment.end as usize..self.lexer.start() as usize].contains(&b'\n');
+
+        line_start && same_line && (comment.item == "/******/" || comment.item == "/***/")
+    }
+
+    /// Record the specifier of a call with a string literal as its only
+    /// argument, the current token being the `(`.
+    fn call(&mut self, kind: DependencyKind) {
+        if self.lexer.token != ParenOpen {
+            return;
+        }
+
+        self.next();
+
+        if self.lexer.token != LiteralString {
+            return;
+        }
+
+        let (start, end) = self.lexer.loc();
+
+        self.next();
+
+        if let ParenClose | Comma = self.lexer.token {
+            self.push(kind, start, end);
+        }
+    }
+
+    /// Skip the bindings of an `import` or `export` declaration up to the
+    /// specifier following `from`, if there is one.
+    fn specifier_after_from(&mut self, kind: DependencyKind) {
+        loop {
+            match self.lexer.token {
+                Identifier if self.lexer.token_as_str() == "from" => {
+                    self.next();
+
+                    if self.lexer.token == LiteralString {
+                        return self.string(kind);
+                    }
+                },
+                BraceOpen | BraceClose | Comma | OperatorMultiplication | LiteralString => self.next(),
+                token if token.is_word() => self.next(),
+                _ => return,
+            }
+        }
+    }
+
+    /// Record the specifier of the current string literal token.
+    fn string(&mut self, kind: DependencyKind) {
+        let (start, end) = self.lexer.loc();
+
+        self.push(kind, start, end);
+        self.next();
+    }
+
+    fn push(&mut self, kind: DependencyKind, start: u32, end: u32) {
+        self.dependencies.push(Dependency {
+            kind,
+            specifier: &self.source[start as usize + 1..end as usize - 1],
+            start,
+            end,
+        });
+    }
+}
+
+/// Check whether a `/` following the `token` is a division.
+fn ends_operand(token: Token) -> bool {
+    matches!(token,
+        Identifier | This | Super | Accessor | PrivateIdentifier |
+        LiteralTrue | LiteralFalse | LiteralNull | LiteralString |
+        LiteralNumber | LiteralBinary | LiteralRegEx | TemplateClosed |
+        ParenClose | BracketClose | BraceClose |
+        OperatorIncrement | OperatorDecrement
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scan(source: &str) -> Vec<(DependencyKind, &str)> {
+        scan_dependencies(source).iter().map(|dependency| (dependency.kind, dependency.specifier)).collect()
+    }
+
+    #[test]
+    fn requires() {
+        assert_eq!(scan_dependencies("const a = require('./a');"), vec![
+            Dependency { kind: DependencyKind::Require, specifier: "./a", start: 18, end: 23 },
+        ]);
+        assert_eq!(scan("require(\"a\"); foo.require('b'); require(c); require('d' + e); myrequire('f');"), vec![
+            (DependencyKind::Require, "a"),
+        ]);
+    }
+
+    #[test]
+    fn imports_and_exports() {
+        let source = "import 'a'; import b, { c as d } from 'e'; import * as f from 'g'; import { from } from 'h'; \
+                      export * from 'i'; export { j, k as default } from 'l'; export { m }; export default 'n';";
+
+        assert_eq!(scan(source), vec![
+            (DependencyKind::Import, "a"),
+            (DependencyKind::Import, "e"),
+            (DependencyKind::Import, "g"),
+            (DependencyKind::Import, "h"),
+            (DependencyKind::Export, "i"),
+            (DependencyKind::Export, "l"),
+        ]);
+    }
+
+    #[test]
+    fn dynamic_imports() {
+        assert_eq!(scan("import('a').then(b => import(b)); import.meta.url;"), vec![
+            (DependencyKind::DynamicImport, "a"),
+        ]);
+    }
+
+    #[test]
+    fn unicode_accessors() {
+        assert_eq!(scan("a.é; a.été = require('b');"), vec![
+            (DependencyKind::Require, "b"),
+        ]);
+    }
+
+    #[test]
+    fn regular_expressions_and_templates() {
+        let source = "let a = /'/g.test(b) / 2; `${ require('c') } ${ { d: 1 } } require('e')`; require('f');";
+
+        assert_eq!(scan(source), vec![
+            (DependencyKind::Require, "c"),
+            (DependencyKind::Require, "f"),
+        ]);
+    }
+
+    #[test]
+    fn webpack_runtime() {
+        let source = concat!(
+            "/******/ (() => { // webpackBootstrap\n",
+            "/******/ \tvar __webpack_modules__ = ({\n",
+            "/***/ \"./src/a.js\":\n",
+            "/***/ ((module, __unused_webpack_exports, __webpack_require__) => {\n",
+            "const b = __webpack_require__(/*! ./b */ \"./src/b.js\");\n",
+            "/***/ }),\n",
+            "/******/ \t});\n",
+            "/******/ \tfunction __webpack_require__(moduleId) { return require('runtime'); }\n",
+            "/******/ \tvar __webpack_exports__ = __webpack_require__(\"./src/a.js\");\n",
+            "/******/ })()\n",
+        );
+
+        assert_eq!(scan(source), vec![
+            (DependencyKind::Require, "./src/b.js"),
+        ]);
+    }
+}
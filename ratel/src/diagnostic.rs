@@ -0,0 +1,397 @@
+//! Diagnostics shared by the parser, the validator and the lints: a message
+//! with a severity and a code, pointing at spans of the source labeled with
+//! what they are, such as the previous declaration of a duplicate. They can
+//! be rendered as snippets of the source, or serialized to JSON.
+//!
+//! Hosts analyzing many modules, possibly on several threads, can have
+//! the diagnostics streamed to a `DiagnosticsSink` as they're produced,
+//! rather than collecting them all at the end.
+
+use std::fmt::{self, Display, Write};
+use std::sync::Arc;
+#[cfg(any(test, feature = "std"))]
+use std::sync::Mutex;
+use allocated::*;
+use error::log10;
+
+/// How serious a `Diagnostic` is.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        })
+    }
+}
+
+/// Span of the source a `Diagnostic` points at.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Label {
+    pub start: usize,
+    pub end: usize,
+
+    /// Whether the diagnostic is about this span, rather than it being
+    /// related code
+    pub primary: bool,
+
+    /// Text shown next to the span, may be empty
+    pub message: String,
+}
+
+/// Edit of the source fixing what a `Diagnostic` is about, replacing the
+/// span with the `replacement`, or inserting it where the span is empty.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Suggestion {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+
+    /// What the edit does, such as declaring a variable
+    pub message: String,
+}
+
+/// Message about the source, with the spans it points at.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// Identifier of the kind of diagnostic, such as `E0004` for errors
+    /// of the parser, or the name of a lint
+    pub code: &'static str,
+    pub message: String,
+
+    /// Labels in the order they were added
+    pub labels: Vec<Label>,
+
+    /// Fixes in the order they were added, left out of the JSON if there
+    /// are none
+    #[cfg_attr(feature = "serialize", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without labels.
+    #[inline]
+    pub fn new<M: Into<String>>(severity: Severity, code: &'static str, message: M) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message: message.into(),
+            labels: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Add a label for the span the diagnostic is about.
+    #[inline]
+    pub fn with_primary<M: Into<String>>(self, start: usize, end: usize, message: M) -> Self {
+        self.with_label(start, end, true, message.into())
+    }
+
+    /// Add a label for related code, such as a previous declaration.
+    #[inline]
+    pub fn with_related<M: Into<String>>(self, start: usize, end: usize, message: M) -> Self {
+        self.with_label(start, end, false, message.into())
+    }
+
+    /// Add a fix replacing `start..end` of the source with `replacement`.
+    #[inline]
+    pub fn with_suggestion<R, M>(mut self, start: usize, end: usize, replacement: R, message: M) -> Self where
+        R: Into<String>,
+        M: Into<String>,
+    {
+        self.suggestions.push(Suggestion { start, end, replacement: replacement.into(), message: message.into() });
+        self
+    }
+
+    fn with_label(mut self, start: usize, end: usize, primary: bool, message: String) -> Self {
+        self.labels.push(Label { start, end, primary, message });
+        self
+    }
+
+    /// Get the first primary label.
+    #[inline]
+    pub fn primary(&self) -> Option<&Label> {
+        self.labels.iter().find(|label| label.primary)
+    }
+
+    /// Display the diagnostic with the lines of `source` its labels point
+    /// at, primary spans underlined with `^` and related ones with `-`.
+    #[inline]
+    pub fn display<'a>(&'a self, source: &'a str) -> Rendered<'a> {
+        Rendered {
+            diagnostic: self,
+            source,
+        }
+    }
+}
+
+/// Receiver of diagnostics, shared by the phases and the threads reporting
+/// into it. Each phase reports the diagnostics it produced together, such
+/// as those of `parse_with_sink`, of a pass run by
+/// `PassManager::diagnostics_sink`, of the lints of `ratel-transformer`
+/// taking a sink, or of an `Output`, see `Output::report_to`.
+pub trait DiagnosticsSink: Send + Sync {
+    /// Receive a batch of diagnostics, in the order they were reported.
+    /// Batches are never empty.
+    fn report(&self, batch: &[Diagnostic]);
+}
+
+/// Collects the batches in the order they were received.
+#[cfg(any(test, feature = "std"))]
+impl DiagnosticsSink for Mutex<Vec<Diagnostic>> {
+    #[inline]
+    fn report(&self, batch: &[Diagnostic]) {
+        // A thread panicking while holding the lock can't leave the vector
+        // in an invalid state, diagnostics are only ever appended
+        let mut diagnostics = self.lock().unwrap_or_else(|err| err.into_inner());
+
+        diagnostics.extend_from_slice(batch);
+    }
+}
+
+impl<S: DiagnosticsSink + ?Sized> DiagnosticsSink for &S {
+    #[inline]
+    fn report(&self, batch: &[Diagnostic]) {
+        (**self).report(batch)
+    }
+}
+
+impl<S: DiagnosticsSink + ?Sized> DiagnosticsSink for Arc<S> {
+    #[inline]
+    fn report(&self, batch: &[Diagnostic]) {
+        (**self).report(batch)
+    }
+}
+
+/// `Diagnostic` displayed with the source, see `Diagnostic::display`.
+pub struct Rendered<'a> {
+    diagnostic: &'a Diagnostic,
+    source: &'a str,
+}
+
+/// Line and column, both starting at 0 and with columns counted in
+/// characters, of an offset in the source, along with the byte offsets
+/// of the start and the end of its line.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+    line_start: usize,
+    line_end: usize,
+}
+
+impl Position {
+    fn new(source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source.as_bytes()[..offset];
+        let line = before.iter().filter(|&&byte| byte == b'\n').count();
+        let line_start = before.iter().rposition(|&byte| byte == b'\n').map_or(0, |index| index + 1);
+        let line_end = source[line_start..].find('\n').map_or(source.len(), |index| line_start + index);
+
+        Position {
+            line,
+            column: chars_between(source, line_start, offset),
+            line_start,
+            line_end,
+        }
+    }
+}
+
+/// Number of characters starting in `start..end` of the source.
+#[inline]
+fn chars_between(source: &str, start: usize, end: usize) -> usize {
+    source[start..].char_indices().take_while(|&(index, _)| start + index < end).count()
+}
+
+impl<'a> Display for Rendered<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Rendered { diagnostic, source } = *self;
+
+        writeln!(f, "{}[{}]: {}", diagnostic.severity, diagnostic.code, diagnostic.message)?;
+
+        let mut labels: Vec<_> = diagnostic.labels.iter().map(|label| (Position::new(source, label.start), label)).collect();
+
+        let last_line = match labels.iter().map(|&(position, _)| position.line).max() {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+
+        let width = log10(last_line + 1);
+
+        if let Some(&(position, _)) = labels.iter().find(|&&(_, label)| label.primary) {
+            writeln!(f, "{:1$}--> {2}:{3}", "", width, position.line + 1, position.column + 1)?;
+        }
+
+        writeln!(f, "{:1$} |", "", width)?;
+
+        // Labels of a line follow it in the order of their columns, primary
+        // labels first where they start at the same column
+        labels.sort_by_key(|&(position, label)| (position.line, position.column, !label.primary));
+
+        let mut previous: Option<usize> = None;
+
+        for (position, label) in labels {
+            if previous != Some(position.line) {
+                if previous.is_some_and(|line| position.line > line + 1) {
+                    writeln!(f, "{:1$} ...", "", width)?;
+                }
+
+                let text = source[position.line_start..position.line_end].trim_end_matches('\r');
+
+                writeln!(f, "{:>2$} | {}", position.line + 1, text, width)?;
+
+                previous = Some(position.line);
+            }
+
+            // Spans going past the line are underlined to its end, empty
+            // spans get a single marker
+            let end = label.end.clamp(label.start.min(position.line_end), position.line_end);
+            let length = chars_between(source, label.start.min(position.line_end), end).max(1);
+            let marker = if label.primary { '^' } else { '-' };
+
+            write!(f, "{:1$} | {2:3$}", "", width, "", position.column)?;
+
+            for _ in 0..length {
+                f.write_char(marker)?;
+            }
+
+            if !label.message.is_empty() {
+                write!(f, " {}", label.message)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        for suggestion in &diagnostic.suggestions {
+            writeln!(f, "{:1$} = help: {2}", "", width, suggestion.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn renders_labels() {
+        let diagnostic = Diagnostic::new(Severity::Error, "E0004", "Duplicate parameter `a`")
+            .with_primary(14, 15, "bound again here")
+            .with_related(11, 12, "first bound here");
+
+        let expected = "\
+error[E0004]: Duplicate parameter `a`
+ --> 1:15
+  |
+1 | function f(a, a) {}
+  |            - first bound here
+  |               ^ bound again here
+";
+
+        assert_eq!(diagnostic.display("function f(a, a) {}").to_string(), expected);
+    }
+
+    #[test]
+    fn renders_labels_on_several_lines() {
+        let source = "let a;\n\n\nlet ą = 'foo\nbar';";
+        let diagnostic = Diagnostic::new(Severity::Warning, "lint", "Something")
+            .with_related(4, 5, "")
+            .with_primary(18, 40, "here");
+
+        let expected = "\
+warning[lint]: Something
+ --> 4:9
+  |
+1 | let a;
+  |     -
+  ...
+4 | let ą = 'foo
+  |         ^^^^ here
+";
+
+        assert_eq!(diagnostic.display(source).to_string(), expected);
+        assert_eq!(diagnostic.primary().map(|label| label.start), Some(18));
+    }
+
+    #[test]
+    fn renders_suggestions() {
+        let diagnostic = Diagnostic::new(Severity::Warning, "lint", "Something")
+            .with_primary(0, 1, "here")
+            .with_suggestion(0, 0, "let ", "declare `a`");
+
+        let expected = "\
+warning[lint]: Something
+ --> 1:1
+  |
+1 | a = 1;
+  | ^ here
+  = help: declare `a`
+";
+
+        assert_eq!(diagnostic.display("a = 1;").to_string(), expected);
+    }
+
+    #[test]
+    fn collects_batches_from_threads() {
+        use std::thread;
+
+        let sink = Arc::new(Mutex::new(Vec::new()));
+
+        let threads: Vec<_> = (0..4).map(|_| {
+            let sink = sink.clone();
+
+            thread::spawn(move || {
+                sink.report(&[
+                    Diagnostic::new(Severity::Warning, "first", "Something"),
+                    Diagnostic::new(Severity::Warning, "second", "Something"),
+                ]);
+            })
+        }).collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let diagnostics = sink.lock().unwrap();
+        let codes: Vec<_> = diagnostics.iter().map(|diagnostic| diagnostic.code).collect();
+
+        // Batches aren't interleaved
+        assert_eq!(codes, ["first", "second"].repeat(4));
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn serializes() {
+        let diagnostic = Diagnostic::new(Severity::Warning, "lint", "Something").with_primary(1, 2, "here");
+
+        assert_eq!(::serde_json::to_value(&diagnostic).unwrap(), json!({
+            "severity": "warning",
+            "code": "lint",
+            "message": "Something",
+            "labels": [{ "start": 1, "end": 2, "primary": true, "message": "here" }],
+        }));
+
+        let diagnostic = diagnostic.with_suggestion(1, 2, "b", "rename");
+
+        assert_eq!(::serde_json::to_value(&diagnostic).unwrap()["suggestions"], json!([
+            { "start": 1, "end": 2, "replacement": "b", "message": "rename" },
+        ]));
+    }
+}
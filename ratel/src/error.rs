@@ -1,22 +1,370 @@
-use std::fmt::{self, Debug, Display};
-use lexer::Token;
+use std::fmt::{self, Debug, Display, Write};
+use allocated::*;
+pub use lexer::Token;
+use diagnostic::{Diagnostic, Severity};
+use quota::{Resource, QuotaExceeded};
+
+/// Kind of the `Error`, most errors are simply unexpected tokens. New
+/// kinds may be added in minor releases.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    UnexpectedToken,
+
+    /// String or template literal exceeded `ParserOptions::max_literal_length`,
+    /// `end` of the `Error` points where the lexer stopped reading it, the
+    /// rest of the source is left unparsed
+    LiteralTooLong {
+        limit: usize,
+    },
+
+    /// String literal not closed before the end of the line, `end` of
+    /// the `Error` points at the line terminator
+    UnterminatedString,
+
+    /// Parameter name bound twice where duplicates are not allowed,
+    /// `start` and `end` of the `Error` point at the second occurrence
+    DuplicateParameter {
+        first_start: usize,
+        first_end: usize,
+    },
+
+    /// Function declaration as the body of a loop, or as the body of an
+    /// `if` or labeled statement in strict mode
+    FunctionInStatementPosition,
+
+    /// `yield` used as a parameter name in a generator or in strict mode,
+    /// or `await` in an async function
+    ReservedParameter,
+
+    /// Number literal with a leading zero such as `017` in strict mode
+    /// code, see `Module::warnings` for sloppy mode code
+    LegacyOctalLiteral,
+
+    /// Octal escape such as `\07`, or `\8` or `\9`, in a string literal
+    /// of strict mode code, see `Module::warnings` for sloppy mode code,
+    /// `start` and `end` of the `Error` point at the escape
+    LegacyOctalEscape,
+
+    /// AST went over `ParserOptions::quota`, `start` and `end` of the
+    /// `Error` point at the node allocated past the limit, the rest of
+    /// the source is left unparsed
+    QuotaExceeded {
+        resource: Resource,
+        limit: usize,
+    },
+
+    /// `const` or destructuring declarator without an initializer in the
+    /// head of a `for` statement, `raw` of the `Error` being the keyword
+    /// of the declaration
+    MissingInitializer,
+
+    /// Declaration in the head of a `for`-`in` or `for`-`of` statement
+    /// with more than one declarator, or with an initializer other than
+    /// that of `var` allowed by Annex B.3.5, `raw` of the `Error` being
+    /// `in` or `of`
+    InvalidForInOfDeclaration,
+
+    /// Shorthand property with an initializer, `{ a = 1 }`, in an object
+    /// literal that isn't an assignment target, `raw` of the `Error` being
+    /// the name of the property
+    InvalidShorthandInitializer,
+
+    /// Property of an object pattern in a binding, such as a parameter,
+    /// with a value that doesn't bind names, `{ a: b.c }`, `start` and
+    /// `end` of the `Error` pointing at the property
+    InvalidBindingProperty,
+
+    /// Element of an array or object literal destructured by an assignment
+    /// that can't be assigned to, `[a + 1] = b`, `start` and `end` of the
+    /// `Error` pointing at the element
+    InvalidAssignmentTarget,
+
+    /// `yield` or `await` expression in the parameters of a generator,
+    /// an async function or an arrow function, which are evaluated before
+    /// the body can be suspended, `raw` of the `Error` being the keyword
+    SuspensionInParameters,
+
+    /// Unary operator, other than `++` and `--`, or `await` right before
+    /// `**`, as in `-a ** b`, which has to be parenthesized one way or the
+    /// other, `raw` of the `Error` being the operator and `start` and `end`
+    /// pointing at the unary expression
+    UnparenthesizedUnaryOperand,
+
+    /// Optional chain as the operand of `new`, the tag of a template or
+    /// the target of a `for`-`in` or `for`-`of` loop, `raw` of the `Error`
+    /// being `new`, the backtick, or `in` or `of`, and `start` and `end`
+    /// pointing at the chain, or at the whole `new` expression
+    InvalidOptionalChain,
+
+    /// Private name such as `#a` used outside of a class body, `raw` of
+    /// the `Error` being the name
+    PrivateNameOutsideClass,
+
+    /// Source given to `Module::wrap` without a function whose body starts
+    /// at the given offset, `start` and `end` of the `Error` pointing at it
+    MissingFunctionBody,
+
+    /// `await` bound by a declaration or a `catch` clause in an async
+    /// function, `raw` of the `Error` being the name
+    ReservedBinding,
+
+    /// `super` called outside of the constructor of a class with `extends`,
+    /// `raw` of the `Error` being `super`
+    SuperCallOutsideConstructor,
+
+    /// Property of `super` accessed outside of a method, a class field or
+    /// a static block, `raw` of the `Error` being `super`
+    SuperPropertyOutsideMethod,
+}
 
 /// Error type used by the tokenizer and the parser internally.
+///
+/// Fields may be added in minor releases, errors are built with
+/// `Error::new` outside of the crate. Struct literals written before
+/// `kind` was added no longer compile, `ErrorKind::UnexpectedToken` is
+/// the kind all errors had until then.
 #[derive(PartialEq, Clone)]
+#[non_exhaustive]
 pub struct Error {
+    pub kind: ErrorKind,
     pub token: Token,
     pub raw: Box<str>,
     pub start: usize,
     pub end: usize,
 }
 
+impl Error {
+    #[inline]
+    pub fn new<R: Into<Box<str>>>(kind: ErrorKind, token: Token, raw: R, start: usize, end: usize) -> Self {
+        Error {
+            kind,
+            token,
+            raw: raw.into(),
+            start,
+            end,
+        }
+    }
+}
+
 impl Debug for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Unexpected {:?}({}) at {}:{}", &self.token, &*self.raw, self.start, self.end)
+        match self.kind {
+            ErrorKind::UnexpectedToken => {
+                write!(f, "Unexpected {:?}({}) at {}:{}", &self.token, &*self.raw, self.start, self.end)
+            },
+            ErrorKind::LiteralTooLong { limit } => {
+                write!(f, "Literal longer than {} bytes at {}:{}", limit, self.start, self.end)
+            },
+            ErrorKind::UnterminatedString => {
+                write!(f, "Unterminated string literal {} at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::DuplicateParameter { first_start, first_end } => {
+                write!(f, "Duplicate parameter {} at {}:{}, first bound at {}:{}", &*self.raw, self.start, self.end, first_start, first_end)
+            },
+            ErrorKind::FunctionInStatementPosition => {
+                write!(f, "Function declaration not allowed in statement position at {}:{}", self.start, self.end)
+            },
+            ErrorKind::ReservedParameter => {
+                write!(f, "Reserved word {} used as a parameter at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::LegacyOctalLiteral => {
+                write!(f, "Legacy octal literal {} at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::LegacyOctalEscape => {
+                write!(f, "Legacy octal escape {} at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::QuotaExceeded { resource, limit } => {
+                write!(f, "{} at {}:{}", QuotaExceeded { resource, limit }, self.start, self.end)
+            },
+            ErrorKind::MissingInitializer => {
+                write!(f, "Missing initializer in {} declaration at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::InvalidForInOfDeclaration => {
+                write!(f, "Invalid declaration in the head of a for-{} loop at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::InvalidShorthandInitializer => {
+                write!(f, "Invalid initializer of the shorthand property {} at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::InvalidBindingProperty => {
+                write!(f, "Invalid property of a binding pattern at {}:{}", self.start, self.end)
+            },
+            ErrorKind::InvalidAssignmentTarget => {
+                write!(f, "Invalid target of a destructuring assignment at {}:{}", self.start, self.end)
+            },
+            ErrorKind::SuspensionInParameters => {
+                write!(f, "{} expression in parameters at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::UnparenthesizedUnaryOperand => {
+                write!(f, "Unparenthesized unary {} before ** at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::InvalidOptionalChain => {
+                write!(f, "Invalid use of an optional chain with {} at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::PrivateNameOutsideClass => {
+                write!(f, "Private name {} outside of a class body at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::MissingFunctionBody => {
+                write!(f, "No function body at {}:{}", self.start, self.end)
+            },
+            ErrorKind::ReservedBinding => {
+                write!(f, "Reserved word {} used as a binding at {}:{}", &*self.raw, self.start, self.end)
+            },
+            ErrorKind::SuperCallOutsideConstructor => {
+                write!(f, "Super call outside of a derived constructor at {}:{}", self.start, self.end)
+            },
+            ErrorKind::SuperPropertyOutsideMethod => {
+                write!(f, "Super property outside of a method at {}:{}", self.start, self.end)
+            },
+        }
+    }
+}
+
+impl ErrorKind {
+    /// Code of the `Diagnostic` of errors of this kind.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ErrorKind::UnexpectedToken => "E0001",
+            ErrorKind::LiteralTooLong { .. } => "E0002",
+            ErrorKind::UnterminatedString => "E0003",
+            ErrorKind::DuplicateParameter { .. } => "E0004",
+            ErrorKind::FunctionInStatementPosition => "E0005",
+            ErrorKind::ReservedParameter => "E0006",
+            ErrorKind::LegacyOctalLiteral => "E0007",
+            ErrorKind::LegacyOctalEscape => "E0008",
+            ErrorKind::QuotaExceeded { .. } => "E0009",
+            ErrorKind::MissingInitializer => "E0010",
+            ErrorKind::InvalidForInOfDeclaration => "E0011",
+            ErrorKind::InvalidShorthandInitializer => "E0012",
+            ErrorKind::InvalidBindingProperty => "E0013",
+            ErrorKind::InvalidAssignmentTarget => "E0014",
+            ErrorKind::SuspensionInParameters => "E0015",
+            ErrorKind::UnparenthesizedUnaryOperand => "E0016",
+            ErrorKind::InvalidOptionalChain => "E0017",
+            ErrorKind::PrivateNameOutsideClass => "E0018",
+            ErrorKind::MissingFunctionBody => "E0019",
+            ErrorKind::ReservedBinding => "E0020",
+            ErrorKind::SuperCallOutsideConstructor => "E0021",
+            ErrorKind::SuperPropertyOutsideMethod => "E0022",
+        }
     }
 }
 
+impl Error {
+    /// Describe the error as a `Diagnostic`, with the error's span as the
+    /// primary label and any related span, such as the first binding of
+    /// a duplicate parameter, as further labels.
+    pub fn diagnostic(&self) -> Diagnostic {
+        let (message, label) = match self.kind {
+            ErrorKind::UnexpectedToken if self.token == Token::EndOfProgram => {
+                ("Unexpected end of program".to_owned(), "expected more code")
+            },
+            ErrorKind::UnexpectedToken => {
+                (concat("Unexpected token `", &self.raw, "`"), "unexpected token")
+            },
+            ErrorKind::LiteralTooLong { limit } => {
+                let mut message = String::new();
+                let _ = write!(message, "Literal longer than {} bytes", limit);
+
+                (message, "literal too long")
+            },
+            ErrorKind::UnterminatedString => {
+                ("Unterminated string literal".to_owned(), "string not closed before the end of the line")
+            },
+            ErrorKind::DuplicateParameter { .. } => {
+                (concat("Duplicate parameter `", &self.raw, "`"), "bound again here")
+            },
+            ErrorKind::FunctionInStatementPosition => {
+                ("Function declaration not allowed in statement position".to_owned(), "not allowed here")
+            },
+            ErrorKind::ReservedParameter => {
+                (concat("Reserved word `", &self.raw, "` used as a parameter"), "reserved word")
+            },
+            ErrorKind::LegacyOctalLiteral if self.raw.bytes().all(|byte| byte < b'8') => {
+                (concat("Legacy octal literal `", &self.raw, "`"), "use the `0o` prefix instead")
+            },
+            ErrorKind::LegacyOctalLiteral => {
+                (concat("Decimal literal `", &self.raw, "` with a leading zero"), "remove the leading zero")
+            },
+            ErrorKind::LegacyOctalEscape => {
+                (concat("Legacy octal escape `", &self.raw, "`"), "use a `\\x` or `\\u` escape instead")
+            },
+            ErrorKind::QuotaExceeded { resource, limit } => {
+                let mut message = String::new();
+                let _ = write!(message, "{}", QuotaExceeded { resource, limit });
+
+                (message, "parsing stopped here")
+            },
+            ErrorKind::MissingInitializer => {
+                (concat("Missing initializer in `", &self.raw, "` declaration"), "initializer required")
+            },
+            ErrorKind::InvalidForInOfDeclaration => {
+                (concat("Invalid declaration in the head of a `for`-`", &self.raw, "` loop"), "expected a single binding without an initializer")
+            },
+            ErrorKind::InvalidShorthandInitializer => {
+                (concat("Invalid initializer of the shorthand property `", &self.raw, "`"), "only allowed in destructuring assignments")
+            },
+            ErrorKind::InvalidBindingProperty => {
+                ("Invalid property of a binding pattern".to_owned(), "expected a name or a nested pattern")
+            },
+            ErrorKind::InvalidAssignmentTarget => {
+                ("Invalid target of a destructuring assignment".to_owned(), "expected a name, a member or a nested pattern")
+            },
+            ErrorKind::SuspensionInParameters => {
+                (concat("`", &self.raw, "` expression in parameters"), "only allowed in the body of the function")
+            },
+            ErrorKind::UnparenthesizedUnaryOperand => {
+                (concat("Unparenthesized unary `", &self.raw, "` before `**`"), "parenthesize either operand")
+            },
+            ErrorKind::InvalidOptionalChain => {
+                let label = match &*self.raw {
+                    "new" => "can't be constructed",
+                    "`"   => "can't tag a template",
+                    _     => "can't be assigned to",
+                };
+
+                (concat("Invalid use of an optional chain with `", &self.raw, "`"), label)
+            },
+            ErrorKind::PrivateNameOutsideClass => {
+                (concat("Private name `", &self.raw, "` outside of a class body"), "only allowed within a class")
+            },
+            ErrorKind::MissingFunctionBody => {
+                ("No function body at the offset to wrap in".to_owned(), "expected the body of a function")
+            },
+            ErrorKind::ReservedBinding => {
+                (concat("Reserved word `", &self.raw, "` used as a binding"), "reserved word")
+            },
+            ErrorKind::SuperCallOutsideConstructor => {
+                ("`super` called outside of a derived constructor".to_owned(), "only allowed in the constructor of a class with `extends`")
+            },
+            ErrorKind::SuperPropertyOutsideMethod => {
+                ("`super` property outside of a method".to_owned(), "only allowed in methods, class fields and static blocks")
+            },
+        };
+
+        let diagnostic = Diagnostic::new(Severity::Error, self.kind.code(), message)
+            .with_primary(self.start, self.end, label);
+
+        match self.kind {
+            ErrorKind::DuplicateParameter { first_start, first_end } => {
+                diagnostic.with_related(first_start, first_end, "first bound here")
+            },
+            _ => diagnostic,
+        }
+    }
+}
+
+#[inline]
+fn concat(before: &str, raw: &str, after: &str) -> String {
+    let mut message = String::with_capacity(before.len() + raw.len() + after.len());
+
+    message.push_str(before);
+    message.push_str(raw);
+    message.push_str(after);
+    message
+}
+
 /// Error type returned by `parser::parse`. This error will include
 /// owned `String` of the source code where the error occurred, so
 /// that a meaningful error can be printed out.
@@ -99,7 +447,7 @@ impl Display for ParseError {
     }
 }
 
-fn log10(mut num: usize) -> usize {
+pub(crate) fn log10(mut num: usize) -> usize {
     let mut log = 0;
 
     while num > 0 {
@@ -131,4 +479,22 @@ mod test {
         assert_eq!(format!("{}", err), expected);
     }
 
+    #[test]
+    fn duplicate_parameter_diagnostic() {
+        let err = Error {
+            kind: ErrorKind::DuplicateParameter { first_start: 11, first_end: 12 },
+            token: Token::Identifier,
+            raw: "a".into(),
+            start: 14,
+            end: 15,
+        };
+
+        let diagnostic = err.diagnostic();
+
+        assert_eq!(diagnostic.code, "E0004");
+        assert_eq!(diagnostic.message, "Duplicate parameter `a`");
+        assert_eq!(diagnostic.labels.len(), 2);
+        assert_eq!((diagnostic.labels[1].start, diagnostic.labels[1].primary), (11, false));
+    }
+
 }
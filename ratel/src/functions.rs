@@ -0,0 +1,527 @@
+use std::vec;
+use allocated::*;
+
+use ast::{Statement, StatementNode, StatementList, BlockNode, ExpressionNode, ExpressionList, Pattern, PatternList};
+use ast::{Property, PropertyKey, Function, Name, EmptyName, Class, ClassMember, MethodKind, Node};
+use ast::expression::ArrowBody;
+use ast::statement::{ForInit, ExportStatement};
+
+/// Kind of a function-like node, see `Module::functions`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum FunctionKind {
+    /// `function foo() {}` in statement position
+    Declaration,
+
+    /// `function () {}` in expression position
+    Expression,
+
+    /// `() => {}`
+    Arrow,
+
+    /// Object or class method
+    Method,
+
+    /// Class `get` accessor
+    Getter,
+
+    /// Class `set` accessor
+    Setter,
+
+    /// Class `constructor`
+    Constructor,
+}
+
+/// Summary of a function-like node, see `Module::functions`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FunctionInfo<'ast> {
+    pub kind: FunctionKind,
+
+    /// Name of the function, or the key of a method when it's not computed
+    pub name: Option<&'ast str>,
+
+    /// `start` of the node, including the key for methods
+    pub start: u32,
+
+    /// `end` of the node
+    pub end: u32,
+
+    /// Number of parameters, including a rest parameter
+    pub params: usize,
+
+    pub generator: bool,
+
+    /// Whether it's an `async` function, arrow function or method
+    pub is_async: bool,
+}
+
+/// Iterator over all function-like nodes of a `Module`, in source order.
+pub struct Functions<'ast> {
+    iter: vec::IntoIter<FunctionInfo<'ast>>,
+}
+
+impl<'ast> Functions<'ast> {
+    pub(crate) fn new(body: StatementList<'ast>) -> Self {
+        let mut collector = Collector { functions: Vec::new(), bodies: None };
+
+        for statement in body {
+            collector.statement(*statement);
+        }
+
+        Functions {
+            iter: collector.functions.into_iter(),
+        }
+    }
+}
+
+impl<'ast> Iterator for Functions<'ast> {
+    type Item = FunctionInfo<'ast>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Find the body of the function whose body starts at `offset`.
+pub(crate) fn function_body_at<'ast>(body: StatementList<'ast>, offset: u32) -> Option<&'ast BlockNode<'ast, Statement<'ast>>> {
+    let mut collector = Collector { functions: Vec::new(), bodies: Some(Vec::new()) };
+
+    for statement in body {
+        collector.statement(*statement);
+    }
+
+    collector.bodies?.into_iter().find(|block| block.start == offset)
+}
+
+struct Collector<'ast> {
+    functions: Vec<FunctionInfo<'ast>>,
+
+    /// Bodies of all functions, only collected for `function_body_at`
+    bodies: Option<Vec<&'ast BlockNode<'ast, Statement<'ast>>>>,
+}
+
+impl<'ast> Collector<'ast> {
+    fn push<N: Name<'ast>>(&mut self, kind: FunctionKind, name: Option<&'ast str>, start: u32, end: u32, function: &Function<'ast, N>) {
+        self.functions.push(FunctionInfo {
+            kind,
+            name,
+            start,
+            end,
+            params: function.params.iter().count(),
+            generator: function.generator,
+            is_async: function.is_async,
+        });
+    }
+
+    fn function<N: Name<'ast>>(&mut self, function: &'ast Function<'ast, N>) {
+        if let Some(ref mut bodies) = self.bodies {
+            bodies.push(&function.body);
+        }
+
+        self.params(function.params);
+
+        for statement in function.body.body {
+            self.statement(*statement);
+        }
+    }
+
+    fn method(&mut self, kind: FunctionKind, key: Node<'ast, PropertyKey<'ast>>, value: Node<'ast, Function<'ast, EmptyName>>, start: u32, end: u32) {
+        let name = match key.item {
+            PropertyKey::Computed(expression) => {
+                self.expression(expression);
+                None
+            },
+            PropertyKey::Literal(raw) | PropertyKey::Binary(raw) => Some(unquote(raw)),
+            PropertyKey::Private(name) => Some(name),
+        };
+
+        self.push(kind, name, start, end, &value.item);
+        self.function(&value.get().item);
+    }
+
+    fn class<N: Name<'ast>>(&mut self, class: &'ast Class<'ast, N>) {
+        if let Some(extends) = class.extends {
+            self.expression(extends);
+        }
+
+        for member in class.body.body {
+            match member.item {
+                ClassMember::Error => {},
+                ClassMember::Method { key, kind, value, .. } => {
+                    let kind = match kind {
+                        MethodKind::Constructor => FunctionKind::Constructor,
+                        MethodKind::Method      => FunctionKind::Method,
+                        MethodKind::Get         => FunctionKind::Getter,
+                        MethodKind::Set         => FunctionKind::Setter,
+                    };
+
+                    self.method(kind, key, value, member.start, member.end);
+                },
+                ClassMember::Property { key, value, .. } => {
+                    if let PropertyKey::Computed(expression) = key.item {
+                        self.expression(expression);
+                    }
+
+                    if let Some(value) = value {
+                        self.expression(value);
+                    }
+                },
+                ClassMember::StaticBlock { body } => {
+                    for statement in body {
+                        self.statement(*statement);
+                    }
+                },
+            }
+        }
+    }
+
+    fn params(&mut self, params: PatternList<'ast>) {
+        for param in params {
+            self.pattern(*param);
+        }
+    }
+
+    fn pattern(&mut self, pattern: Node<'ast, Pattern<'ast>>) {
+        match pattern.item {
+            Pattern::Void | Pattern::Identifier(_) | Pattern::RestElement { .. } => {},
+            Pattern::ObjectPattern { properties } => {
+                for property in properties {
+                    self.property(*property);
+                }
+            },
+            Pattern::ArrayPattern { elements } => self.params(elements),
+            Pattern::AssignmentPattern { left, right } => {
+                self.pattern(left);
+                self.expression(right);
+            },
+        }
+    }
+
+    fn property(&mut self, property: Node<'ast, Property<'ast>>) {
+        match property.item {
+            Property::Shorthand(_) => {},
+            Property::ShorthandDefault { value, .. } => self.expression(value),
+            Property::Literal { key, value } => {
+                if let PropertyKey::Computed(expression) = key.item {
+                    self.expression(expression);
+                }
+
+                self.expression(value);
+            },
+            Property::Method { key, value } => {
+                self.method(FunctionKind::Method, key, value, property.start, property.end);
+            },
+            Property::Spread { argument } => self.expression(argument),
+        }
+    }
+
+    fn for_init(&mut self, init: Node<'ast, ForInit<'ast>>) {
+        match init.item {
+            ForInit::Declaration(declaration) => {
+                for declarator in declaration.declarators {
+                    self.pattern(declarator.id);
+
+                    if let Some(init) = declarator.init {
+                        self.expression(init);
+                    }
+                }
+            },
+            ForInit::Expression(expression) => self.expression(expression),
+        }
+    }
+
+    fn statement(&mut self, statement: StatementNode<'ast>) {
+        use ast::Statement::*;
+
+        match statement.get().item {
+            Empty | Break(_) | Continue(_) | Import(_) => {},
+            Expression(expression) => self.expression(expression),
+            Declaration(declaration) => {
+                for declarator in declaration.declarators {
+                    self.pattern(declarator.id);
+
+                    if let Some(init) = declarator.init {
+                        self.expression(init);
+                    }
+                }
+            },
+            Return(ref ret) => {
+                if let Some(value) = ret.value {
+                    self.expression(value);
+                }
+            },
+            Throw(ref throw) => self.expression(throw.value),
+            If(ref stmt) => {
+                self.expression(stmt.test);
+                self.statement(stmt.consequent);
+
+                if let Some(alternate) = stmt.alternate {
+                    self.statement(alternate);
+                }
+            },
+            While(ref stmt) => {
+                self.expression(stmt.test);
+                self.statement(stmt.body);
+            },
+            Do(ref stmt) => {
+                self.statement(stmt.body);
+                self.expression(stmt.test);
+            },
+            For(ref stmt) => {
+                if let Some(init) = stmt.init {
+                    self.for_init(init);
+                }
+                if let Some(test) = stmt.test {
+                    self.expression(test);
+                }
+                if let Some(update) = stmt.update {
+                    self.expression(update);
+                }
+                self.statement(stmt.body);
+            },
+            ForIn(ref stmt) => {
+                self.for_init(stmt.left);
+                self.expression(stmt.right);
+                self.statement(stmt.body);
+            },
+            ForOf(ref stmt) => {
+                self.for_init(stmt.left);
+                self.expression(stmt.right);
+                self.statement(stmt.body);
+            },
+            Try(ref stmt) => {
+                for statement in stmt.block.body {
+                    self.statement(*statement);
+                }
+
+                if let Some(handler) = stmt.handler {
+                    self.pattern(handler.param);
+
+                    for statement in handler.body.body {
+                        self.statement(*statement);
+                    }
+                }
+
+                if let Some(finalizer) = stmt.finalizer {
+                    for statement in finalizer.body {
+                        self.statement(*statement);
+                    }
+                }
+            },
+            Block(ref block) => {
+                for statement in block.body {
+                    self.statement(*statement);
+                }
+            },
+            Labeled(ref stmt) => self.statement(stmt.body),
+            Function(ref function) => {
+                let name = Some(function.name.0.item);
+
+                self.push(FunctionKind::Declaration, name, statement.start, statement.end, function);
+                self.function(function);
+            },
+            Class(ref class) => self.class(class),
+            Export(ref export) => match *export {
+                ExportStatement::Declaration(declaration) => self.statement(declaration),
+                ExportStatement::DefaultDeclaration(expression) |
+                ExportStatement::Default(expression) => self.expression(expression),
+                ExportStatement::Named { .. } | ExportStatement::All { .. } => {},
+            },
+            Switch(ref stmt) => {
+                self.expression(stmt.discriminant);
+
+                for case in stmt.cases.body {
+                    if let Some(test) = case.test {
+                        self.expression(test);
+                    }
+
+                    for statement in case.consequent {
+                        self.statement(*statement);
+                    }
+                }
+            },
+        }
+    }
+
+    fn expressions(&mut self, expressions: ExpressionList<'ast>) {
+        for expression in expressions {
+            self.expression(*expression);
+        }
+    }
+
+    fn expression(&mut self, expression: ExpressionNode<'ast>) {
+        use ast::Expression::*;
+
+        match expression.get().item {
+            Void | This(_) | Super(_) | Identifier(_) | Literal(_) | MetaProperty(_) => {},
+            Sequence(ref expr) => self.expressions(expr.body),
+            Array(ref expr) => self.expressions(expr.body),
+            Member(ref expr) => self.expression(expr.object),
+            PrivateMember(ref expr) => self.expression(expr.object),
+            Chain(ref expr) => self.expression(expr.expression),
+            ComputedMember(ref expr) => {
+                self.expression(expr.object);
+                self.expression(expr.property);
+            },
+            Call(ref expr) => {
+                self.expression(expr.callee);
+                self.expressions(expr.arguments);
+            },
+            Binary(ref expr) => {
+                self.expression(expr.left);
+                self.expression(expr.right);
+            },
+            Prefix(ref expr) => self.expression(expr.operand),
+            Postfix(ref expr) => self.expression(expr.operand),
+            Conditional(ref expr) => {
+                self.expression(expr.test);
+                self.expression(expr.consequent);
+                self.expression(expr.alternate);
+            },
+            Template(ref expr) => self.expressions(expr.expressions),
+            TaggedTemplate(ref expr) => {
+                self.expression(expr.tag);
+                self.expressions(expr.quasi.expressions);
+            },
+            Spread(ref expr) => self.expression(expr.argument),
+            Await(ref expr) => self.expression(expr.argument),
+            Yield(ref expr) => {
+                if let Some(argument) = expr.argument {
+                    self.expression(argument);
+                }
+            },
+            Arrow(ref arrow) => {
+                self.functions.push(FunctionInfo {
+                    kind: FunctionKind::Arrow,
+                    name: None,
+                    start: expression.start,
+                    end: expression.end,
+                    params: arrow.params.iter().count(),
+                    generator: false,
+                    is_async: arrow.is_async,
+                });
+                self.params(arrow.params);
+
+                match arrow.body {
+                    ArrowBody::Expression(body) => self.expression(body),
+                    ArrowBody::Block(block) => {
+                        for statement in block.body {
+                            self.statement(*statement);
+                        }
+                    },
+                }
+            },
+            Object(ref object) => {
+                for property in object.body {
+                    self.property(*property);
+                }
+            },
+            Function(ref function) => {
+                let name = function.name.0.map(|name| name.item);
+
+                self.push(FunctionKind::Expression, name, expression.start, expression.end, function);
+                self.function(function);
+            },
+            Class(ref class) => self.class(class),
+        }
+    }
+}
+
+/// Strip the quotes of a string literal used as a key, escapes are kept as is.
+fn unquote(raw: &str) -> &str {
+    match raw.as_bytes().first() {
+        Some(b'"') | Some(b'\'') => &raw[1..raw.len() - 1],
+        _ => raw,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+
+    type Summary = (FunctionKind, Option<String>, u32, u32, usize, bool);
+
+    fn functions(src: &str) -> Vec<Summary> {
+        let module = parse(src).unwrap();
+
+        module.functions().map(|f| {
+            (f.kind, f.name.map(String::from), f.start, f.end, f.params, f.generator)
+        }).collect()
+    }
+
+    #[test]
+    fn declarations_and_expressions() {
+        assert_eq!(functions("function foo(a, b) { return function () {} }"), vec![
+            (FunctionKind::Declaration, Some("foo".into()), 0, 44, 2, false),
+            (FunctionKind::Expression, None, 28, 42, 0, false),
+        ]);
+        assert_eq!(functions("(function* bar(...rest) {})"), vec![
+            (FunctionKind::Expression, Some("bar".into()), 1, 26, 1, true),
+        ]);
+    }
+
+    #[test]
+    fn arrows() {
+        let module = parse("let f = (a = () => 1) => b => c;").unwrap();
+        let arrows: Vec<_> = module.functions().map(|f| (f.kind, f.name.is_some(), f.params)).collect();
+
+        assert_eq!(arrows, vec![
+            (FunctionKind::Arrow, false, 1),
+            (FunctionKind::Arrow, false, 0),
+            (FunctionKind::Arrow, false, 1),
+        ]);
+    }
+
+    #[test]
+    fn methods() {
+        assert_eq!(functions("({ foo() {}, 'bar'(a) {}, [baz]() {} })"), vec![
+            (FunctionKind::Method, Some("foo".into()), 3, 11, 0, false),
+            (FunctionKind::Method, Some("bar".into()), 13, 24, 1, false),
+            (FunctionKind::Method, None, 26, 36, 0, false),
+        ]);
+    }
+
+    #[test]
+    fn class_members() {
+        let src = "class A { constructor() {} get a() {} set a(v) {} static b() {} }";
+
+        assert_eq!(functions(src), vec![
+            (FunctionKind::Constructor, Some("constructor".into()), 10, 26, 0, false),
+            (FunctionKind::Getter, Some("a".into()), 27, 37, 0, false),
+            (FunctionKind::Setter, Some("a".into()), 38, 49, 1, false),
+            (FunctionKind::Method, Some("b".into()), 50, 63, 0, false),
+        ]);
+    }
+
+    #[test]
+    fn async_functions() {
+        let src = "async function a() {} (async function () {}); async () => {}; x => x; ({ async b() {} }); class C { async c() {} d() {} }";
+        let module = parse(src).unwrap();
+        let functions: Vec<_> = module.functions().map(|f| (f.kind, f.is_async, f.generator)).collect();
+
+        assert_eq!(functions, vec![
+            (FunctionKind::Declaration, true, false),
+            (FunctionKind::Expression, true, false),
+            (FunctionKind::Arrow, true, false),
+            (FunctionKind::Arrow, false, false),
+            (FunctionKind::Method, true, false),
+            (FunctionKind::Method, true, false),
+            (FunctionKind::Method, false, false),
+        ]);
+    }
+
+    #[test]
+    fn nested_in_statements() {
+        let module = parse("if (a) { for (;;) { try {} catch (e) { x(function () {}) } } }").unwrap();
+        let kinds: Vec<_> = module.functions().map(|f| f.kind).collect();
+
+        assert_eq!(kinds, vec![FunctionKind::Expression]);
+    }
+}
@@ -0,0 +1,533 @@
+//! Internal consistency checks of the AST, compiled with the
+//! `debug-invariants` feature. With it the parser, the editing methods of
+//! `Module` and the passes of `ratel-transformer` check the AST they leave
+//! behind, and panic on the first violation, so that a transform
+//! corrupting the AST is caught where it happens rather than in codegen.
+//!
+//! Locations are only checked on their own: statements inserted into a
+//! module, or nodes created by transforms, have locations relative to
+//! another source, or none at all, so nodes needn't be ordered or nested
+//! by their locations.
+
+use std::fmt::{self, Display};
+use std::collections::BTreeSet;
+
+use ast::{Loc, Node, NodeList, Statement, StatementNode, ExpressionNode, ExpressionList, Pattern, PatternList};
+use ast::{Property, PropertyKey, Function, Name, EmptyName, Class, ClassMember};
+use ast::expression::ArrowBody;
+use ast::statement::{ForInit, DeclarationStatement, ExportStatement};
+use module::Module;
+
+/// Kind of a `Violation`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ViolationKind {
+    /// `start` of the node is after its `end`
+    InvertedSpan,
+
+    /// Item of a list reached a second time, either because the list is
+    /// cyclic or because it shares items with another list, so that
+    /// changing one changes the other
+    SharedListItem,
+
+    /// Declaration without any declarators, which can't be printed as a
+    /// complete statement
+    EmptyDeclaration,
+
+    /// `Void` expression or pattern anywhere but as an element of an array
+    MisplacedHole,
+}
+
+/// Broken invariant, `start` and `end` are those of the offending node.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self.kind {
+            ViolationKind::InvertedSpan => "node starts after its end",
+            ViolationKind::SharedListItem => "list item reached twice",
+            ViolationKind::EmptyDeclaration => "declaration without declarators",
+            ViolationKind::MisplacedHole => "hole outside of an array",
+        };
+
+        write!(f, "{} at {}:{}", message, self.start, self.end)
+    }
+}
+
+/// Check the invariants of the AST of the module, returning the first
+/// violation found.
+pub fn check(module: &Module) -> Result {
+    let mut checker = Checker { items: BTreeSet::new() };
+
+    checker.statements(module.body())
+}
+
+/// Panic with the first violation of the AST of the module, `after` names
+/// what last changed it.
+#[inline]
+pub fn assert_valid(module: &Module, after: &str) {
+    if let Err(violation) = check(module) {
+        panic!("AST invariant violated after {}: {}", after, violation);
+    }
+}
+
+type Result<T = ()> = ::std::result::Result<T, Violation>;
+
+struct Checker {
+    /// Addresses of the list items seen so far
+    items: BTreeSet<usize>,
+}
+
+#[inline]
+fn violation<T>(kind: ViolationKind, loc: &Loc<T>) -> Result {
+    Err(Violation { kind, start: loc.start, end: loc.end })
+}
+
+impl Checker {
+    fn span<T>(&mut self, loc: &Loc<T>) -> Result {
+        match loc.start > loc.end {
+            true => violation(ViolationKind::InvertedSpan, loc),
+            false => Ok(()),
+        }
+    }
+
+    /// Check the items of a list are reached once, calling `each` on them.
+    fn list<'ast, T: Copy, F>(&mut self, list: NodeList<'ast, T>, mut each: F) -> Result where
+        F: FnMut(&mut Self, Node<'ast, T>) -> Result,
+    {
+        for item in list {
+            if !self.items.insert(item as *const Node<'ast, T> as usize) {
+                return violation(ViolationKind::SharedListItem, item);
+            }
+
+            self.span(item)?;
+            each(self, *item)?;
+        }
+
+        Ok(())
+    }
+
+    fn statements<'ast>(&mut self, statements: NodeList<'ast, Statement<'ast>>) -> Result {
+        self.list(statements, Checker::statement)
+    }
+
+    fn expressions<'ast>(&mut self, expressions: ExpressionList<'ast>) -> Result {
+        self.list(expressions, Checker::expression)
+    }
+
+    fn params<'ast>(&mut self, params: PatternList<'ast>) -> Result {
+        self.list(params, Checker::pattern)
+    }
+
+    fn function<'ast, N: Name<'ast>>(&mut self, function: &Function<'ast, N>) -> Result {
+        self.params(function.params)?;
+        self.span(&function.body)?;
+        self.statements(function.body.body)
+    }
+
+    fn method<'ast>(&mut self, key: Node<'ast, PropertyKey<'ast>>, value: Node<'ast, Function<'ast, EmptyName>>) -> Result {
+        self.key(key)?;
+        self.span(&value)?;
+        self.function(&value.item)
+    }
+
+    fn key<'ast>(&mut self, key: Node<'ast, PropertyKey<'ast>>) -> Result {
+        self.span(&key)?;
+
+        match key.item {
+            PropertyKey::Computed(expression) => self.expression(expression),
+            PropertyKey::Literal(_) | PropertyKey::Binary(_) | PropertyKey::Private(_) => Ok(()),
+        }
+    }
+
+    fn class<'ast, N: Name<'ast>>(&mut self, class: &Class<'ast, N>) -> Result {
+        if let Some(extends) = class.extends {
+            self.expression(extends)?;
+        }
+
+        self.span(&class.body)?;
+        self.list(class.body.body, |checker, member| match member.item {
+            ClassMember::Error => Ok(()),
+            ClassMember::Method { key, value, .. } => checker.method(key, value),
+            ClassMember::Property { key, value, .. } => {
+                checker.key(key)?;
+
+                match value {
+                    Some(value) => checker.expression(value),
+                    None => Ok(()),
+                }
+            },
+            ClassMember::StaticBlock { body } => checker.statements(body),
+        })
+    }
+
+    fn pattern<'ast>(&mut self, pattern: Node<'ast, Pattern<'ast>>) -> Result {
+        self.span(&pattern)?;
+        self.pattern_in(pattern, false)
+    }
+
+    /// Check a pattern, which may only be a hole if `in_array`.
+    fn pattern_in<'ast>(&mut self, pattern: Node<'ast, Pattern<'ast>>, in_array: bool) -> Result {
+        match pattern.item {
+            Pattern::Void if in_array => Ok(()),
+            Pattern::Void => violation(ViolationKind::MisplacedHole, &pattern),
+            Pattern::Identifier(_) => Ok(()),
+            Pattern::RestElement { argument } => self.span(&argument),
+            Pattern::ObjectPattern { properties } => self.list(properties, Checker::property),
+            Pattern::ArrayPattern { elements } => self.list(elements, |checker, element| checker.pattern_in(element, true)),
+            Pattern::AssignmentPattern { left, right } => {
+                self.pattern(left)?;
+                self.expression(right)
+            },
+        }
+    }
+
+    fn property<'ast>(&mut self, property: Node<'ast, Property<'ast>>) -> Result {
+        match property.item {
+            Property::Shorthand(_) => Ok(()),
+            Property::ShorthandDefault { name, value } => {
+                self.span(&name)?;
+                self.expression(value)
+            },
+            Property::Literal { key, value } => {
+                self.key(key)?;
+                self.expression(value)
+            },
+            Property::Method { key, value } => self.method(key, value),
+            Property::Spread { argument } => self.expression(argument),
+        }
+    }
+
+    fn declaration<'ast, T>(&mut self, declaration: &DeclarationStatement<'ast>, loc: &Loc<T>) -> Result {
+        if declaration.declarators.is_empty() {
+            return violation(ViolationKind::EmptyDeclaration, loc);
+        }
+
+        self.list(declaration.declarators, |checker, declarator| {
+            checker.pattern(declarator.id)?;
+
+            match declarator.init {
+                Some(init) => checker.expression(init),
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn for_init<'ast>(&mut self, init: Node<'ast, ForInit<'ast>>) -> Result {
+        self.span(&init)?;
+
+        match init.item {
+            ForInit::Declaration(ref declaration) => self.declaration(declaration, &init),
+            ForInit::Expression(expression) => self.expression(expression),
+        }
+    }
+
+    fn statement<'ast>(&mut self, statement: StatementNode<'ast>) -> Result {
+        use ast::Statement::*;
+
+        self.span(&statement)?;
+
+        match statement.get().item {
+            Empty => Ok(()),
+            Expression(expression) => self.expression(expression),
+            Declaration(ref declaration) => self.declaration(declaration, &statement),
+            Return(ref ret) => match ret.value {
+                Some(value) => self.expression(value),
+                None => Ok(()),
+            },
+            Break(ref stmt) => match stmt.label {
+                Some(label) => self.span(&label),
+                None => Ok(()),
+            },
+            Continue(ref stmt) => match stmt.label {
+                Some(label) => self.span(&label),
+                None => Ok(()),
+            },
+            Throw(ref throw) => self.expression(throw.value),
+            If(ref stmt) => {
+                self.expression(stmt.test)?;
+                self.statement(stmt.consequent)?;
+
+                match stmt.alternate {
+                    Some(alternate) => self.statement(alternate),
+                    None => Ok(()),
+                }
+            },
+            While(ref stmt) => {
+                self.expression(stmt.test)?;
+                self.statement(stmt.body)
+            },
+            Do(ref stmt) => {
+                self.statement(stmt.body)?;
+                self.expression(stmt.test)
+            },
+            For(ref stmt) => {
+                if let Some(init) = stmt.init {
+                    self.for_init(init)?;
+                }
+                if let Some(test) = stmt.test {
+                    self.expression(test)?;
+                }
+                if let Some(update) = stmt.update {
+                    self.expression(update)?;
+                }
+                self.statement(stmt.body)
+            },
+            ForIn(ref stmt) => {
+                self.for_init(stmt.left)?;
+                self.expression(stmt.right)?;
+                self.statement(stmt.body)
+            },
+            ForOf(ref stmt) => {
+                self.for_init(stmt.left)?;
+                self.expression(stmt.right)?;
+                self.statement(stmt.body)
+            },
+            Try(ref stmt) => {
+                self.span(&stmt.block)?;
+                self.statements(stmt.block.body)?;
+
+                if let Some(handler) = stmt.handler {
+                    self.span(&handler)?;
+                    self.pattern(handler.param)?;
+                    self.span(&handler.body)?;
+                    self.statements(handler.body.body)?;
+                }
+
+                match stmt.finalizer {
+                    Some(finalizer) => {
+                        self.span(&finalizer)?;
+                        self.statements(finalizer.body)
+                    },
+                    None => Ok(()),
+                }
+            },
+            Block(ref block) => self.statements(block.body),
+            Labeled(ref stmt) => {
+                self.span(&stmt.label)?;
+                self.statement(stmt.body)
+            },
+            Function(ref function) => {
+                self.span(&function.name.0)?;
+                self.function(function)
+            },
+            Class(ref class) => {
+                self.span(&class.name.0)?;
+                self.class(class)
+            },
+            Switch(ref stmt) => {
+                self.expression(stmt.discriminant)?;
+                self.span(&stmt.cases)?;
+                self.list(stmt.cases.body, |checker, case| {
+                    if let Some(test) = case.test {
+                        checker.expression(test)?;
+                    }
+
+                    checker.statements(case.consequent)
+                })
+            },
+            Import(ref stmt) => {
+                self.list(stmt.specifiers, |checker, specifier| {
+                    checker.span(&specifier.local())
+                })?;
+                self.span(&stmt.source)
+            },
+            Export(ref export) => match *export {
+                ExportStatement::Declaration(declaration) => self.statement(declaration),
+                ExportStatement::DefaultDeclaration(expression) |
+                ExportStatement::Default(expression) => self.expression(expression),
+                ExportStatement::Named { specifiers, source } => {
+                    self.list(specifiers, |checker, specifier| {
+                        checker.span(&specifier.local)?;
+                        checker.span(&specifier.exported)
+                    })?;
+
+                    match source {
+                        Some(source) => self.span(&source),
+                        None => Ok(()),
+                    }
+                },
+                ExportStatement::All { exported, source } => {
+                    if let Some(exported) = exported {
+                        self.span(&exported)?;
+                    }
+
+                    self.span(&source)
+                },
+            },
+        }
+    }
+
+    fn expression<'ast>(&mut self, expression: ExpressionNode<'ast>) -> Result {
+        self.span(&expression)?;
+        self.expression_in(expression)
+    }
+
+    /// Check an expression whose span is already checked, such as an item
+    /// of a list.
+    fn expression_in<'ast>(&mut self, expression: ExpressionNode<'ast>) -> Result {
+        use ast::Expression::*;
+
+        match expression.get().item {
+            Void => violation(ViolationKind::MisplacedHole, &expression),
+            This(_) | Super(_) | Identifier(_) | Literal(_) => Ok(()),
+            MetaProperty(ref expr) => {
+                self.span(&expr.meta)?;
+                self.span(&expr.property)
+            },
+            Sequence(ref expr) => self.expressions(expr.body),
+            Array(ref expr) => self.list(expr.body, |checker, element| match element.item {
+                Void => Ok(()),
+                _ => checker.expression_in(element),
+            }),
+            Member(ref expr) => {
+                self.expression(expr.object)?;
+                self.span(&expr.property)
+            },
+            ComputedMember(ref expr) => {
+                self.expression(expr.object)?;
+                self.expression(expr.property)
+            },
+            PrivateMember(ref expr) => {
+                self.expression(expr.object)?;
+                self.span(&expr.property)
+            },
+            Chain(ref expr) => self.expression(expr.expression),
+            Call(ref expr) => {
+                self.expression(expr.callee)?;
+                self.expressions(expr.arguments)
+            },
+            Binary(ref expr) => {
+                self.expression(expr.left)?;
+                self.expression(expr.right)
+            },
+            Prefix(ref expr) => self.expression(expr.operand),
+            Postfix(ref expr) => self.expression(expr.operand),
+            Conditional(ref expr) => {
+                self.expression(expr.test)?;
+                self.expression(expr.consequent)?;
+                self.expression(expr.alternate)
+            },
+            Template(ref expr) => {
+                self.list(expr.quasis, |_, _| Ok(()))?;
+                self.expressions(expr.expressions)
+            },
+            TaggedTemplate(ref expr) => {
+                self.expression(expr.tag)?;
+                self.span(&expr.quasi)?;
+                self.list(expr.quasi.quasis, |_, _| Ok(()))?;
+                self.expressions(expr.quasi.expressions)
+            },
+            Spread(ref expr) => self.expression(expr.argument),
+            Await(ref expr) => self.expression(expr.argument),
+            Yield(ref expr) => match expr.argument {
+                Some(argument) => self.expression(argument),
+                None => Ok(()),
+            },
+            Arrow(ref arrow) => {
+                self.params(arrow.params)?;
+
+                match arrow.body {
+                    ArrowBody::Expression(body) => self.expression(body),
+                    ArrowBody::Block(block) => {
+                        self.span(&block)?;
+                        self.statements(block.body)
+                    },
+                }
+            },
+            Object(ref object) => self.list(object.body, Checker::property),
+            Function(ref function) => {
+                if let Some(name) = function.name.0 {
+                    self.span(&name)?;
+                }
+
+                self.function(function)
+            },
+            Class(ref class) => {
+                if let Some(name) = class.name.0 {
+                    self.span(&name)?;
+                }
+
+                self.class(class)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ast::{Expression, DeclarationKind};
+    use ast::expression::{ThisExpression, ArrayExpression};
+    use parser::parse;
+
+    fn first<'ast>(module: &Module<'ast>) -> &'ast StatementNode<'ast> {
+        module.body().first_element().unwrap()
+    }
+
+    #[test]
+    fn accepts_parsed_modules() {
+        let module = parse("a: for (const [, b = 1] of d) { `${e}` + f`g`; [, h, , ]; function c(...i) {} }").unwrap();
+
+        assert_eq!(check(&module), Ok(()));
+    }
+
+    #[test]
+    fn rejects_inverted_spans() {
+        let module = parse("a;").unwrap();
+        let this = Loc::new(5, 2, Expression::This(ThisExpression));
+
+        let statement = Loc::new(0, 2, Statement::Expression(Node::new(&this)));
+
+        first(&module).set(&statement);
+
+        assert_eq!(check(&module), Err(Violation { kind: ViolationKind::InvertedSpan, start: 5, end: 2 }));
+    }
+
+    #[test]
+    fn rejects_shared_list_items() {
+        let module = parse("[a, b]; [c];").unwrap();
+
+        let body = match first(&module).item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Array(array) => array.body,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        let array = Loc::new(8, 11, Expression::Array(ArrayExpression { body }));
+        let second = module.body().iter().nth(1).unwrap();
+
+        let statement = Loc::new(8, 12, Statement::Expression(Node::new(&array)));
+
+        second.set(&statement);
+
+        assert_eq!(check(&module).map_err(|violation| (violation.kind, violation.start)), Err((ViolationKind::SharedListItem, 1)));
+    }
+
+    #[test]
+    fn rejects_incomplete_statements() {
+        let module = parse("a;").unwrap();
+        let declaration = Loc::new(0, 4, Statement::Declaration(DeclarationStatement {
+            kind: DeclarationKind::Var,
+            declarators: NodeList::empty(),
+        }));
+
+        first(&module).set(&declaration);
+
+        assert_eq!(check(&module).map_err(|violation| violation.kind), Err(ViolationKind::EmptyDeclaration));
+
+        let hole = Loc::new(0, 0, Expression::Void);
+
+        let statement = Loc::new(0, 1, Statement::Expression(Node::new(&hole)));
+
+        first(&module).set(&statement);
+
+        assert_eq!(check(&module).map_err(|violation| violation.kind), Err(ViolationKind::MisplacedHole));
+    }
+}
@@ -201,14 +201,6 @@ pub const L_T: ByteHandler = Some(|lex| {
     lex.token = Identifier;
 });
 
-// Identifier or keyword starting with a letter `u`
-pub const L_U: ByteHandler = Some(|lex| {
-    match_label!(lex [b'n' b'd' b'e' b'f' b'i' b'n' b'e' b'd' => LiteralUndefined]);
-
-    lex.read_label();
-    lex.token = Identifier;
-});
-
 // Identifier or keyword starting with a letter `v`
 pub const L_V: ByteHandler = Some(|lex| {
     match_label!(lex {
@@ -8,8 +8,10 @@ use lexer::labels::*;
 use lexer::token::Token::*;
 
 use std::str;
-use error::Error;
+use allocated::*;
+use error::{Error, ErrorKind};
 use toolshed::Arena;
+use ast::Loc;
 
 macro_rules! expect_byte {
     ($lex:ident) => ({
@@ -61,12 +63,12 @@ static BYTE_HANDLERS: [ByteHandler; 256] = [
 //   0    1    2    3    4    5    6    7    8    9    A    B    C    D    E    F   //
     EOF, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 0
     ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, ___, // 1
-    ___, EXL, QOT, ERR, IDT, PRC, AMP, QOT, PNO, PNC, ATR, PLS, COM, MIN, PRD, SLH, // 2
+    ___, EXL, QOT, HSH, IDT, PRC, AMP, QOT, PNO, PNC, ATR, PLS, COM, MIN, PRD, SLH, // 2
     ZER, DIG, DIG, DIG, DIG, DIG, DIG, DIG, DIG, DIG, COL, SEM, LSS, EQL, MOR, QST, // 3
     ERR, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, // 4
     IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, IDT, BTO, IDT, BTC, CRT, IDT, // 5
     TPL, IDT, L_B, L_C, L_D, L_E, L_F, IDT, IDT, L_I, IDT, IDT, L_L, IDT, L_N, IDT, // 6
-    L_P, IDT, L_R, L_S, L_T, L_U, L_V, L_W, IDT, L_Y, IDT, BEO, PIP, BEC, TLD, ERR, // 7
+    L_P, IDT, L_R, L_S, L_T, IDT, L_V, L_W, IDT, L_Y, IDT, BEO, PIP, BEC, TLD, ERR, // 7
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 8
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // 9
     UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, UNI, // A
@@ -268,9 +270,19 @@ const MOR: ByteHandler = Some(|lex| {
 
 // ?
 const QST: ByteHandler = Some(|lex| {
-    lex.bump();
+    lex.token = match lex.next_byte() {
+        // `?.5` is a conditional followed by a number
+        b'.' => match lex.peek_byte() {
+            b'0'..=b'9' => OperatorConditional,
+            _ => {
+                lex.bump();
+
+                OperatorOptionalChain
+            }
+        },
 
-    lex.token = OperatorConditional;
+        _ => OperatorConditional
+    };
 });
 
 // ~
@@ -403,6 +415,7 @@ const SLH: ByteHandler = Some(|lex| {
             unwind_loop!({
                 match lex.next_byte() {
                     0 | b'\n' => {
+                        lex.read_comment();
                         return lex.consume();
                     }
                     _ => {}
@@ -420,6 +433,7 @@ const SLH: ByteHandler = Some(|lex| {
                         match lex.next_byte() {
                             b'/' => {
                                 lex.bump();
+                                lex.read_comment();
                                 return lex.consume();
                             },
                             0 => return lex.token = UnexpectedEndOfProgram,
@@ -455,24 +469,26 @@ const PRC: ByteHandler = Some(|lex| {
     };
 });
 
-// Unicode character
-const UNI: ByteHandler = Some(|lex| {
-    let start = lex.index;
+// #
+const HSH: ByteHandler = Some(|lex| {
+    lex.bump();
 
-    // TODO: unicodes with different lengths
-    let first = lex.slice_source(start, start + 4).chars().next().expect("Has to have one");
+    match lex.read_byte() {
+        b'a'..=b'z' | b'A'..=b'Z' | b'$' | b'_' => {
+            lex.read_label();
 
-    if !first.is_alphanumeric() {
-        return lex.token = UnexpectedToken;
+            lex.token = PrivateIdentifier;
+        },
+        _ => lex.token = UnexpectedToken,
     }
+});
 
-    // `read_label` bumps one at the beginning,
-    // so we subtract it here.
-    lex.index += first.len_utf8() - 1;
-
-    lex.read_label();
-
-    lex.token = Identifier;
+// Unicode character
+const UNI: ByteHandler = Some(|lex| {
+    lex.token = match lex.read_unicode_label() {
+        true  => Identifier,
+        false => UnexpectedToken,
+    };
 });
 
 // 0
@@ -496,20 +512,34 @@ const ZER: ByteHandler = Some(|lex| {
             return lex.read_hexadec();
         },
 
+        // Legacy octal literal such as `017`, or decimal literal with
+        // a leading zero such as `019`
+        b'0'..=b'9' => {
+            lex.legacy_octal = Some(lex.token_start as u32);
+        },
+
         _ => {}
     }
 
+    // Legacy octal literals have no fraction or exponent, `017.x` is an
+    // access of a property of `017`, while `019.5` is a decimal literal
+    let mut octal = lex.legacy_octal.is_some();
+
     loop {
         match lex.read_byte() {
-            b'0'...b'9' => {
+            b'0'..=b'7' => {
                 lex.bump();
             },
-            b'.' => {
+            b'8' | b'9' => {
+                octal = false;
+                lex.bump();
+            },
+            b'.' if !octal => {
                 lex.bump();
 
                 return lex.read_float();
             },
-            b'e' | b'E' => {
+            b'e' | b'E' if !octal => {
                 lex.bump();
 
                 return lex.read_scientific();
@@ -518,7 +548,7 @@ const ZER: ByteHandler = Some(|lex| {
         }
     }
 
-    lex.token = LiteralNumber;
+    lex.end_number(LiteralNumber);
 });
 
 // 1 to 9
@@ -537,7 +567,7 @@ const DIG: ByteHandler = Some(|lex| {
                 return lex.read_scientific();
             },
             _ => {
-                return lex.token = LiteralNumber;
+                return lex.end_number(LiteralNumber);
             },
         }
     });
@@ -578,16 +608,60 @@ const QOT: ByteHandler = Some(|lex| {
         match lex.read_byte() {
             ch if ch == style => {
                 lex.bump();
+
+                if lex.over_limit(lex.token_start) {
+                    return lex.literal_too_long();
+                }
+
                 return lex.token = LiteralString;
             },
             b'\\' => {
+                let escape = lex.index;
+
                 lex.bump();
-                expect_byte!(lex);
+
+                // Legacy octal escape such as `\07`, or `\8` and `\9`, but
+                // not `\0` on its own
+                let legacy = match lex.read_byte() {
+                    b'1'..=b'9' => true,
+                    b'0' => lex.peek_byte().is_ascii_digit(),
+                    _ => false,
+                };
+
+                if legacy && lex.legacy_octal.is_none() {
+                    lex.legacy_octal = Some(escape as u32);
+                }
+
+                // Line continuation, `\r\n` counts as a single line terminator
+                if lex.read_byte() == b'\r' {
+                    lex.bump();
+
+                    if lex.read_byte() == b'\n' {
+                        lex.bump();
+                    }
+                } else {
+                    expect_byte!(lex);
+                }
+
+                if lex.over_limit(lex.token_start) {
+                    return lex.literal_too_long();
+                }
+            },
+            // Unescaped line terminators end the string in error, without
+            // consuming them so that lexing resumes on the next line
+            b'\n' | b'\r' => {
+                return lex.token = UnexpectedToken;
             },
             0 => {
                 return lex.token = UnexpectedEndOfProgram;
             },
-            _ => lex.bump()
+            _ => {
+                lex.bump();
+
+                if lex.over_limit(lex.token_start) {
+                    return lex.literal_too_long();
+                }
+            }
         }
     });
 });
@@ -617,6 +691,25 @@ pub struct Lexer<'arena> {
     accessor_start: usize,
 
     pub quasi: &'arena str,
+
+    /// Start of the legacy octal number literal, or of the first legacy
+    /// octal escape of the string literal, that the current token is.
+    /// Annex B.1 allows them outside of strict mode code
+    pub legacy_octal: Option<u32>,
+
+    /// Comments read so far, with their delimiters
+    pub comments: Vec<Loc<&'arena str>>,
+
+    /// Number of tokens read so far, comments included
+    tokens: usize,
+
+    /// Maximum length in bytes of string literals, quotes included, and
+    /// of template strings, see `ParserOptions::max_literal_length`
+    max_literal_length: Option<usize>,
+
+    /// Whether the current `UnexpectedToken` is a literal over the
+    /// `max_literal_length`, which is read no further
+    too_long: bool,
 }
 
 
@@ -624,7 +717,15 @@ impl<'arena> Lexer<'arena> {
     /// Create a new `Lexer` from source using an existing arena.
     #[inline]
     pub fn new(arena: &'arena Arena, source: &str) -> Self {
-        unsafe { Lexer::from_ptr(arena.alloc_str_with_nul(source)) }
+        Lexer::with_max_literal_length(arena, source, None)
+    }
+
+    /// Same as `new`, reading literals up to `max_literal_length` bytes.
+    /// Longer ones are left as an `UnexpectedToken` where the limit was
+    /// reached, and lexing stops at the error, see `invalid_token`.
+    #[inline]
+    pub fn with_max_literal_length(arena: &'arena Arena, source: &str, max_literal_length: Option<usize>) -> Self {
+        unsafe { Lexer::from_ptr_with_limit(arena.alloc_str_with_nul(source), max_literal_length) }
     }
 
     /// Create a new `Lexer` from a raw pointer to byte string.
@@ -635,8 +736,16 @@ impl<'arena> Lexer<'arena> {
     /// **The source must be valid UTF8!**
     /// Passing a pointer to data that is not valid UTF8 will lead
     /// to bugs or undefined behavior.
+    ///
+    /// A byte order mark at the start of the source is skipped, offsets
+    /// are still counted from the start of the source.
     #[inline]
     pub unsafe fn from_ptr(ptr: *const u8) -> Self {
+        Lexer::from_ptr_with_limit(ptr, None)
+    }
+
+    #[inline]
+    unsafe fn from_ptr_with_limit(ptr: *const u8, max_literal_length: Option<usize>) -> Self {
         let mut lexer = Lexer {
             token: UnexpectedToken,
             asi: Asi::NoSemicolon,
@@ -645,8 +754,19 @@ impl<'arena> Lexer<'arena> {
             token_start: 0,
             accessor_start: 0,
             quasi: "",
+            legacy_octal: None,
+            comments: Vec::new(),
+            tokens: 0,
+            max_literal_length,
+            too_long: false,
         };
 
+        // Bytes past the nul terminator aren't read, the comparison stops
+        // at the first byte that differs
+        if *ptr == 0xEF && *ptr.add(1) == 0xBB && *ptr.add(2) == 0xBF {
+            lexer.index = 3;
+        }
+
         lexer.consume();
 
         lexer
@@ -656,6 +776,8 @@ impl<'arena> Lexer<'arena> {
     #[inline]
     pub fn consume(&mut self) {
         self.asi = Asi::NoSemicolon;
+        self.legacy_octal = None;
+        self.too_long = false;
 
         let mut ch;
 
@@ -664,6 +786,7 @@ impl<'arena> Lexer<'arena> {
 
             if let Some(handler) = self.handler_from_byte(ch) {
                 self.token_start = self.index;
+                self.tokens += 1;
                 return handler(self);
             }
 
@@ -675,6 +798,38 @@ impl<'arena> Lexer<'arena> {
         })
     }
 
+    /// Skip the rest of the source, leaving `EndOfProgram` as the current
+    /// token.
+    pub fn halt(&mut self) {
+        while self.read_byte() != 0 {
+            self.bump();
+        }
+
+        self.token_start = self.index;
+        self.asi = Asi::ImplicitSemicolon;
+        self.token = EndOfProgram;
+    }
+
+    /// Skip the rest of the line of the current token, then advance the
+    /// lexer to the first token after it.
+    pub fn skip_line(&mut self) {
+        loop {
+            match self.read_byte() {
+                0 | b'\n' => break,
+                _ => self.bump(),
+            }
+        }
+
+        self.consume();
+    }
+
+    /// Get the number of tokens read so far, including the current one
+    /// and comments.
+    #[inline]
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
     /// Create an `&str` slice from source spanning current token.
     #[inline]
     pub fn token_as_str(&self) -> &'arena str {
@@ -690,6 +845,15 @@ impl<'arena> Lexer<'arena> {
         self.slice_from(start)
     }
 
+    /// Record the comment spanning the current token.
+    #[inline]
+    fn read_comment(&mut self) {
+        let comment = self.token_as_str();
+        let loc = Loc::new(self.token_start as u32, self.index as u32, comment);
+
+        self.comments.push(loc);
+    }
+
     #[inline]
     fn handler_from_byte(&mut self, byte: u8) -> ByteHandler {
         unsafe { *(&BYTE_HANDLERS as *const ByteHandler).offset(byte as isize) }
@@ -740,6 +904,10 @@ impl<'arena> Lexer<'arena> {
         let start = self.index;
 
         loop {
+            if self.over_limit(start) {
+                return self.literal_too_long();
+            }
+
             match self.read_byte() {
                 b'`' => {
                     let end = self.index;
@@ -780,6 +948,22 @@ impl<'arena> Lexer<'arena> {
         }
     }
 
+    /// Check whether the literal read from `start` up to the current byte
+    /// is over the `max_literal_length`.
+    #[inline]
+    fn over_limit(&self, start: usize) -> bool {
+        match self.max_literal_length {
+            Some(limit) => self.index - start > limit,
+            None => false,
+        }
+    }
+
+    #[cold]
+    fn literal_too_long(&mut self) {
+        self.token = UnexpectedToken;
+        self.too_long = true;
+    }
+
     /// Get a definition of which ASI rules can be applied.
     #[inline]
     pub fn asi(&self) -> Asi {
@@ -791,11 +975,29 @@ impl<'arena> Lexer<'arena> {
         let end = self.index;
         let token = self.token;
 
+        if self.too_long {
+            let limit = self.max_literal_length.unwrap_or(0);
+            let raw = self.slice_source(start, end).to_owned().into_boxed_str();
+
+            self.halt();
+
+            return Error::new(ErrorKind::LiteralTooLong { limit }, token, raw, start, end);
+        }
+
+        let kind = match token {
+            UnexpectedToken | UnexpectedEndOfProgram => match self.byte_at(start) {
+                b'"' | b'\'' => ErrorKind::UnterminatedString,
+                _             => ErrorKind::UnexpectedToken,
+            },
+            _ => ErrorKind::UnexpectedToken,
+        };
+
         if token != EndOfProgram {
             self.consume();
         }
 
         Error {
+            kind,
             token,
             start,
             end,
@@ -814,6 +1016,21 @@ impl<'arena> Lexer<'arena> {
         unsafe { *self.ptr.offset(self.index as isize) }
     }
 
+    /// Read the byte following the current one without moving the index.
+    /// The source is null terminated, so this is in bounds as long as the
+    /// current byte isn't the terminator.
+    #[inline]
+    fn peek_byte(&self) -> u8 {
+        unsafe { *self.ptr.add(self.index + 1) }
+    }
+
+    /// Read a byte at an arbitrary position of the source preceding
+    /// the current index.
+    #[inline]
+    fn byte_at(&self, index: usize) -> u8 {
+        unsafe { *self.ptr.add(index) }
+    }
+
     /// Manually increment the index. Calling `read_byte` and then `bump`
     /// is equivalent to consuming a byte on an iterator.
     #[inline]
@@ -829,6 +1046,8 @@ impl<'arena> Lexer<'arena> {
 
     #[inline]
     fn read_binary(&mut self) {
+        let start = self.index;
+
         loop {
             match self.read_byte() {
                 b'0' => {
@@ -841,7 +1060,7 @@ impl<'arena> Lexer<'arena> {
             }
         }
 
-        self.token = LiteralBinary;
+        self.end_digits(start, LiteralBinary);
     }
 
     /// This is a specialized method that expects the next token to be an identifier,
@@ -882,11 +1101,24 @@ impl<'arena> Lexer<'arena> {
                 self.accessor_start = self.index;
 
                 if ch > 127 {
-                    unimplemented!();
-                    // return unicode(self)
+                    return self.token = match self.read_unicode_label() {
+                        true  => Accessor,
+                        false => UnexpectedToken,
+                    };
                 } else if TABLE[ch as usize] {
                     self.read_label();
                     return self.token = Accessor;
+                } else if ch == b'#' {
+                    // Private name, `.#a`, keeping the `#` in the accessor
+                    self.bump();
+
+                    return match self.read_byte() {
+                        b'a'..=b'z' | b'A'..=b'Z' | b'$' | b'_' => {
+                            self.read_label();
+                            self.token = Accessor
+                        },
+                        _ => self.token = UnexpectedToken,
+                    };
                 } else {
                     return self.token = UnexpectedToken;
                 }
@@ -896,6 +1128,28 @@ impl<'arena> Lexer<'arena> {
         })
     }
 
+    /// Read a label starting with the non-ASCII character at the current
+    /// index, returning whether that character can start one.
+    #[inline]
+    fn read_unicode_label(&mut self) -> bool {
+        let start = self.index;
+
+        // TODO: unicodes with different lengths
+        let first = self.slice_source(start, start + 4).chars().next().expect("Has to have one");
+
+        if !first.is_alphanumeric() {
+            return false;
+        }
+
+        // `read_label` bumps one at the beginning,
+        // so we subtract it here.
+        self.index += first.len_utf8() - 1;
+
+        self.read_label();
+
+        true
+    }
+
     #[inline]
     fn read_label(&mut self) {
         while util::legal_in_label(self.read_byte()) {
@@ -903,6 +1157,15 @@ impl<'arena> Lexer<'arena> {
         }
     }
 
+    /// Get the source between `start` and `end`, which can't be past
+    /// the current token.
+    #[inline]
+    pub fn slice(&self, start: u32, end: u32) -> &'arena str {
+        debug_assert!(start <= end && end as usize <= self.index);
+
+        self.slice_source(start as usize, end as usize)
+    }
+
     #[inline]
     fn slice_from(&self, start: usize) -> &'arena str {
         let end = self.index;
@@ -923,6 +1186,8 @@ impl<'arena> Lexer<'arena> {
 
     #[inline]
     fn read_octal(&mut self) {
+        let start = self.index;
+
         loop {
             match self.read_byte() {
                 b'0'...b'7' => self.bump(),
@@ -930,11 +1195,13 @@ impl<'arena> Lexer<'arena> {
             };
         }
 
-        self.token = LiteralNumber;
+        self.end_digits(start, LiteralNumber);
     }
 
     #[inline]
     fn read_hexadec(&mut self) {
+        let start = self.index;
+
         loop {
             match self.read_byte() {
                 b'0'...b'9' |
@@ -944,7 +1211,7 @@ impl<'arena> Lexer<'arena> {
             };
         }
 
-        self.token = LiteralNumber;
+        self.end_digits(start, LiteralNumber);
     }
 
     #[inline]
@@ -960,7 +1227,7 @@ impl<'arena> Lexer<'arena> {
             }
         }
 
-        self.token = LiteralNumber;
+        self.end_number(LiteralNumber);
     }
 
     #[inline]
@@ -970,6 +1237,8 @@ impl<'arena> Lexer<'arena> {
             _           => {}
         }
 
+        let start = self.index;
+
         loop {
             match self.read_byte() {
                 b'0'...b'9' => self.bump(),
@@ -977,7 +1246,46 @@ impl<'arena> Lexer<'arena> {
             }
         }
 
-        self.token = LiteralNumber;
+        self.end_digits(start, LiteralNumber);
+    }
+
+    /// End a number the digits of which start at `start`, such as those
+    /// of an exponent or following `0x`, there being at least one.
+    #[inline]
+    fn end_digits(&mut self, start: usize, token: Token) {
+        match self.index == start {
+            true  => self.token = UnexpectedToken,
+            false => self.end_number(token),
+        }
+    }
+
+    /// End a number, which can't be directly followed by a digit or the
+    /// start of an identifier, as in `3in x`, `1.toString` or `0b12`.
+    #[inline]
+    fn end_number(&mut self, token: Token) {
+        let byte = self.read_byte();
+
+        let adjacent = match byte {
+            b'0'..=b'9' |
+            b'a'..=b'z' |
+            b'A'..=b'Z' |
+            b'$' | b'_' | b'\\' => true,
+            0x80..=0xFF => {
+                let len = match byte {
+                    0xF0..=0xFF => 4,
+                    0xE0..=0xEF => 3,
+                    _           => 2,
+                };
+
+                self.slice_source(self.index, self.index + len).chars().next().is_some_and(char::is_alphanumeric)
+            },
+            _ => false,
+        };
+
+        self.token = match adjacent {
+            true  => UnexpectedToken,
+            false => token,
+        };
     }
 
     #[inline]
@@ -1068,6 +1376,22 @@ mod test {
         assert_lex(" /*abc foo **/ bar", [(Identifier, "bar")]);
     }
 
+    #[test]
+    fn comments() {
+        let arena = Arena::new();
+        let mut lex = Lexer::new(&arena, "/* foo */ bar // baz\n/**/");
+
+        while lex.token != EndOfProgram {
+            lex.consume();
+        }
+
+        assert_eq!(lex.comments, vec![
+            Loc::new(0, 9, "/* foo */"),
+            Loc::new(14, 20, "// baz"),
+            Loc::new(21, 25, "/**/"),
+        ]);
+    }
+
     #[test]
     fn method_call() {
         assert_lex(
@@ -1096,6 +1420,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn method_call_with_unicode() {
+        assert_lex(
+            "foo.été a.é",
+            [
+                (Identifier, "foo"),
+                (Accessor, ".été"),
+                (Identifier, "a"),
+                (Accessor, ".é"),
+            ]
+        );
+    }
+
     #[test]
     fn simple_math() {
         assert_lex(
@@ -1158,6 +1495,56 @@ mod test {
         assert_lex("'foo", [(UnexpectedEndOfProgram, "'foo")]);
     }
 
+    #[test]
+    fn line_continuation() {
+        assert_lex("'foo\\\nbar'", [(LiteralString, "'foo\\\nbar'")]);
+        assert_lex("\"foo\\\r\nbar\"", [(LiteralString, "\"foo\\\r\nbar\"")]);
+        assert_lex("'foo\\\rbar'", [(LiteralString, "'foo\\\rbar'")]);
+    }
+
+    #[test]
+    fn unterminated_string() {
+        assert_lex("'foo\nbar", [(UnexpectedToken, "'foo"), (Identifier, "bar")]);
+        assert_lex("x = \"foo\r\n1", [
+            (Identifier, "x"),
+            (OperatorAssign, "="),
+            (UnexpectedToken, "\"foo"),
+            (LiteralNumber, "1"),
+        ]);
+    }
+
+    #[test]
+    fn unterminated_string_error() {
+        let arena = Arena::new();
+        let mut lex = Lexer::new(&arena, "a = 'foo\nbar");
+
+        lex.consume();
+        lex.consume();
+
+        let err = lex.invalid_token();
+
+        assert_eq!(err.kind, ErrorKind::UnterminatedString);
+        assert_eq!((err.start, err.end), (4, 8));
+        assert_eq!(lex.token, Identifier);
+    }
+
+    #[test]
+    fn literal_too_long_error() {
+        let arena = Arena::new();
+        let mut lex = Lexer::with_max_literal_length(&arena, "a = 'foobar'; b", Some(4));
+
+        lex.consume();
+        lex.consume();
+
+        assert_eq!(lex.token, UnexpectedToken);
+
+        let err = lex.invalid_token();
+
+        assert_eq!(err.kind, ErrorKind::LiteralTooLong { limit: 4 });
+        assert_eq!((err.start, err.end), (4, 9));
+        assert_eq!(lex.token, EndOfProgram);
+    }
+
     #[test]
     fn keywords() {
         assert_lex(
@@ -1204,7 +1591,7 @@ mod test {
                 (Throw, "throw"),
                 (LiteralTrue, "true"),
                 (Try, "try"),
-                (LiteralUndefined, "undefined"),
+                (Identifier, "undefined"),
                 (OperatorTypeof, "typeof"),
                 (DeclarationVar, "var"),
                 (OperatorVoid, "void"),
@@ -1221,7 +1608,7 @@ mod test {
             "
                 => new ++ -- ! ~ typeof void delete * / % ** + - << >>
                 >>> < <= > >= instanceof in === !== == != & ^ | && ||
-                ? = += -= **= *= /= %= <<= >>= >>>= &= ^= |= ...
+                ? = += -= **= *= /= %= <<= >>= >>>= &= ^= |= ... ?.
             ",
              &[
                 (OperatorFatArrow, "=>"),
@@ -1272,7 +1659,102 @@ mod test {
                 (OperatorBitXorAssign, "^="),
                 (OperatorBitOrAssign, "|="),
                 (OperatorSpread, "..."),
+                (OperatorOptionalChain, "?."),
             ][..]
         );
     }
+
+    #[test]
+    fn conditional_with_fraction() {
+        assert_lex("a?.5:b", [
+            (Identifier, "a"),
+            (OperatorConditional, "?"),
+            (LiteralNumber, ".5"),
+            (Colon, ":"),
+            (Identifier, "b"),
+        ]);
+    }
+
+    #[test]
+    fn legacy_octal() {
+        let arena = Arena::new();
+        let mut lex = Lexer::new(&arena, r#"0755 0 0.5 09 'a\07' '\0' '\08' "\9" 1"#);
+        let mut found = Vec::new();
+
+        while lex.token != EndOfProgram {
+            found.push(lex.legacy_octal);
+            lex.consume();
+        }
+
+        assert_eq!(found, vec![Some(0), None, None, Some(11), Some(16), None, Some(27), Some(33), None]);
+    }
+
+    #[test]
+    fn number_boundaries() {
+        assert_lex("1..toString", [(LiteralNumber, "1."), (Accessor, ".toString")]);
+        assert_lex("0.1.toString", [(LiteralNumber, "0.1"), (Accessor, ".toString")]);
+        assert_lex("1 .toString", [(LiteralNumber, "1"), (Accessor, ".toString")]);
+        assert_lex("1.5e3.x", [(LiteralNumber, "1.5e3"), (Accessor, ".x")]);
+        assert_lex("0x10.x 0b1.x .5.x", [
+            (LiteralNumber, "0x10"),
+            (Accessor, ".x"),
+            (LiteralBinary, "0b1"),
+            (Accessor, ".x"),
+            (LiteralNumber, ".5"),
+            (Accessor, ".x"),
+        ]);
+        assert_lex("017.x 019.5", [(LiteralNumber, "017"), (Accessor, ".x"), (LiteralNumber, "019.5")]);
+        assert_lex("a.1", [(Identifier, "a"), (LiteralNumber, ".1")]);
+        assert_lex("'a''b'", [(LiteralString, "'a'"), (LiteralString, "'b'")]);
+    }
+
+    #[test]
+    fn private_names() {
+        assert_lex("#foo = 1", [(PrivateIdentifier, "#foo"), (OperatorAssign, "="), (LiteralNumber, "1")]);
+        assert_lex("this.#foo", [(This, "this"), (Accessor, ".#foo")]);
+        assert_lex("a?.#b", [(Identifier, "a"), (OperatorOptionalChain, "?."), (PrivateIdentifier, "#b")]);
+        assert_lex("# foo", [(UnexpectedToken, "#"), (Identifier, "foo")]);
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        assert_lex("\u{feff}a", [(Identifier, "a")]);
+
+        let arena = Arena::new();
+        let lex = Lexer::new(&arena, "\u{feff}a");
+
+        assert_eq!(lex.loc(), (3, 4));
+    }
+
+    #[test]
+    fn number_followed_by_identifier() {
+        assert_lex("1.toString", [(UnexpectedToken, "1."), (Identifier, "toString")]);
+        assert_lex("3in x", [(UnexpectedToken, "3"), (OperatorIn, "in"), (Identifier, "x")]);
+        assert_lex("1_0", [(UnexpectedToken, "1"), (Identifier, "_0")]);
+        assert_lex("0b12", [(UnexpectedToken, "0b1"), (LiteralNumber, "2")]);
+        assert_lex("0xg", [(UnexpectedToken, "0x"), (Identifier, "g")]);
+        assert_lex("017e1", [(UnexpectedToken, "017"), (Identifier, "e1")]);
+        assert_lex("1\u{e9}", [(UnexpectedToken, "1"), (Identifier, "\u{e9}")]);
+    }
+
+    #[test]
+    fn incomplete_numbers() {
+        assert_lex("1e", [(UnexpectedToken, "1e")]);
+        assert_lex("1e+;", [(UnexpectedToken, "1e+"), (Semicolon, ";")]);
+        assert_lex("0.5e", [(UnexpectedToken, "0.5e")]);
+        assert_lex("0x 0b 0o", [(UnexpectedToken, "0x"), (UnexpectedToken, "0b"), (UnexpectedToken, "0o")]);
+        assert_lex("1.e5", [(LiteralNumber, "1.e5")]);
+    }
+
+    #[test]
+    fn operator_boundaries() {
+        assert_lex("a+++b", [(Identifier, "a"), (OperatorIncrement, "++"), (OperatorAddition, "+"), (Identifier, "b")]);
+        assert_lex("a++ +b", [(Identifier, "a"), (OperatorIncrement, "++"), (OperatorAddition, "+"), (Identifier, "b")]);
+        assert_lex("a+ ++b", [(Identifier, "a"), (OperatorAddition, "+"), (OperatorIncrement, "++"), (Identifier, "b")]);
+        assert_lex("a---b", [(Identifier, "a"), (OperatorDecrement, "--"), (OperatorSubtraction, "-"), (Identifier, "b")]);
+        assert_lex("a=>>b", [(Identifier, "a"), (OperatorFatArrow, "=>"), (OperatorGreater, ">"), (Identifier, "b")]);
+        assert_lex("a>>>=b", [(Identifier, "a"), (OperatorUBSRAssign, ">>>="), (Identifier, "b")]);
+        assert_lex("x\ninstanceof y", [(Identifier, "x"), (OperatorInstanceof, "instanceof"), (Identifier, "y")]);
+        assert_lex("x instanceofy", [(Identifier, "x"), (Identifier, "instanceofy")]);
+    }
 }
@@ -10,6 +10,7 @@
 // SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 // IMPRT TRY   STATI TRUE  FALSE NULL  UNDEF STR   NUM   BIN   REGEX ENUM
 // IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+// ?.    #PRIV
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Token {
@@ -103,7 +104,6 @@ pub enum Token {
     LiteralTrue,
     LiteralFalse,
     LiteralNull,
-    LiteralUndefined,
     LiteralString,
     LiteralNumber,
     LiteralBinary,
@@ -121,6 +121,8 @@ pub enum Token {
     TemplateClosed,
     UnexpectedToken,
     UnexpectedEndOfProgram,
+    OperatorOptionalChain,    //   …  ?. …
+    PrivateIdentifier,        //    #name
 }
 
 impl Token {
@@ -164,9 +166,41 @@ impl Token {
             LiteralTrue        |
             LiteralFalse       |
             LiteralNull        |
-            LiteralUndefined   => true,
+            DeclarationVar     |
+            DeclarationLet     |
+            DeclarationConst   |
+            OperatorIn         |
+            ReservedEnum       |
+            ReservedImplements |
+            ReservedPackage    |
+            ReservedProtected  |
+            ReservedInterface  |
+            ReservedPrivate    |
+            ReservedPublic     => true,
 
             _                  => false,
         }
     }
+
+    /// Whether the word can't be used as an identifier reference, and
+    /// is thus only allowed as a property name. Assumes `is_word`.
+    #[inline]
+    pub fn is_reserved_word(&self) -> bool {
+        use self::Token::*;
+
+        match *self {
+            Identifier         |
+            Yield              |
+            Static             |
+            DeclarationLet     |
+            ReservedImplements |
+            ReservedPackage    |
+            ReservedProtected  |
+            ReservedInterface  |
+            ReservedPrivate    |
+            ReservedPublic     => false,
+
+            _                  => true,
+        }
+    }
 }
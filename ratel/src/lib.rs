@@ -1,13 +1,19 @@
 #![recursion_limit="128"]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
+#[cfg(not(any(test, feature = "std")))]
+extern crate alloc;
+
+#[cfg(feature = "serialize")]
 extern crate serde;
 extern crate toolshed;
 
+#[cfg(feature = "serialize")]
 #[macro_use]
 extern crate serde_derive;
 
-#[cfg(test)]
-#[macro_use]
+#[cfg(any(test, feature = "json"))]
+#[cfg_attr(test, macro_use)]
 extern crate serde_json;
 
 #[cfg(test)]
@@ -15,12 +21,68 @@ extern crate serde_json;
 extern crate pretty_assertions;
 
 pub mod ast;
+pub mod diagnostic;
 pub mod error;
+#[cfg(feature = "unstable")]
 pub mod lexer;
+#[cfg(not(feature = "unstable"))]
+mod lexer;
+pub mod prelude;
 
 mod module;
+mod functions;
+mod dependencies;
 mod parser;
+#[cfg(feature = "serialize")]
 mod astgen;
 
-pub use parser::parse;
-pub use module::Module;
+pub mod cancel;
+#[cfg(feature = "debug-invariants")]
+pub mod invariants;
+pub mod observer;
+pub mod output;
+pub mod quota;
+pub mod sourcemap;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+#[cfg(feature = "binast")]
+pub mod binast;
+
+/// Parts of `std` used by the lexer, the parser and the AST, taken from
+/// `core` and `alloc` without the `std` feature.
+#[cfg(not(any(test, feature = "std")))]
+mod std {
+    pub use core::{cell, char, fmt, iter, marker, mem, ops, result, slice, str, time};
+    pub use alloc::{borrow, collections, vec};
+
+    pub mod sync {
+        pub use alloc::sync::Arc;
+        pub use core::sync::atomic;
+    }
+}
+
+/// Allocated types of the `std` prelude, which has to be imported from
+/// `alloc` without the `std` feature.
+mod allocated {
+    #[cfg(any(test, feature = "std"))]
+    pub use std::prelude::v1::{Box, String, ToOwned, Vec};
+
+    #[cfg(not(any(test, feature = "std")))]
+    pub use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
+}
+
+pub use parser::{parse, parse_with_options, parse_with_observer, parse_with_sink, parse_relaxed, ParserOptions};
+#[cfg(feature = "json")]
+pub use parser::parse_relaxed_json;
+pub use parser::plugin;
+#[cfg(feature = "trace")]
+pub use parser::trace::{trace, Trace, TraceEvent, Production};
+#[cfg(feature = "serialize")]
+pub use astgen::dialect::{Dialect, Shape, Wrapper, Position, Estree, Shift, Babel, StableIds, InDialect};
+#[cfg(feature = "json")]
+pub use astgen::json::write_json;
+pub use module::{Module, Features, LineColumn};
+pub use output::{Output, Timing, parse_output};
+pub use functions::{Functions, FunctionInfo, FunctionKind};
+pub use dependencies::{scan_dependencies, Dependency, DependencyKind};
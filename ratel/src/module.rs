@@ -1,12 +1,159 @@
 use toolshed::list::UnsafeList;
 use toolshed::Arena;
-use ast::StatementList;
+use ast::{Loc, Block, NodeList, Statement, StatementList, StatementNode, Terminator};
+use error::{Error, ErrorKind, Token};
+use diagnostic::Diagnostic;
+use quota::Budget;
+use parser::parse_statements;
+use functions::{Functions, function_body_at};
 use std::marker::PhantomData;
+use std::ops::{BitOr, Range};
+use allocated::*;
+
+/// Set of syntax features used by a `Module`, recorded while parsing.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Features(u32);
+
+impl Features {
+    pub const CLASSES: Features              = Features(1 << 0);
+    pub const GENERATORS: Features           = Features(1 << 1);
+    pub const ARROW_FUNCTIONS: Features      = Features(1 << 2);
+    pub const TEMPLATE_LITERALS: Features    = Features(1 << 3);
+    /// Spread in arrays, calls and objects
+    pub const SPREAD: Features               = Features(1 << 4);
+    pub const REST_PARAMETERS: Features      = Features(1 << 5);
+    /// Array or object patterns in declarations and parameters
+    pub const DESTRUCTURING: Features        = Features(1 << 6);
+    /// `let` and `const` declarations
+    pub const LEXICAL_DECLARATIONS: Features = Features(1 << 7);
+    pub const FOR_OF: Features               = Features(1 << 8);
+    pub const NEW_TARGET: Features           = Features(1 << 9);
+    /// `async` functions and methods
+    pub const ASYNC_FUNCTIONS: Features      = Features(1 << 10);
+    /// `import` and `export` declarations
+    pub const MODULES: Features              = Features(1 << 11);
+    /// `?.` member accesses and calls
+    pub const OPTIONAL_CHAINING: Features    = Features(1 << 12);
+    /// `**` and `**=`
+    pub const EXPONENTIATION: Features       = Features(1 << 13);
+    pub const CLASS_FIELDS: Features         = Features(1 << 14);
+    /// Private fields, methods and accessors, and accesses to them
+    pub const PRIVATE_MEMBERS: Features      = Features(1 << 15);
+    pub const STATIC_BLOCKS: Features        = Features(1 << 16);
+
+    /// Set with no features.
+    #[inline]
+    pub fn empty() -> Self {
+        Features(0)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check whether all of the `other` features are in the set.
+    #[inline]
+    pub fn contains(&self, other: Features) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Check whether any of the `other` features are in the set.
+    #[inline]
+    pub fn intersects(&self, other: Features) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, other: Features) {
+        self.0 |= other.0;
+    }
+
+    /// Raw bits of the set, stable between versions.
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+impl BitOr for Features {
+    type Output = Features;
+
+    #[inline]
+    fn bitor(self, other: Features) -> Features {
+        Features(self.0 | other.0)
+    }
+}
+
+/// Position in the source of a `Module`, see `Module::position`. Lines
+/// start at 1, columns at 0 and are counted in UTF-16 code units, same as
+/// the `loc` of ESTree.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct LineColumn {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Default for LineColumn {
+    #[inline]
+    fn default() -> Self {
+        LineColumn {
+            line: 1,
+            column: 0,
+        }
+    }
+}
+
+impl LineColumn {
+    /// Get the position following the `text` from this one.
+    pub(crate) fn advance(self, text: &str) -> Self {
+        let mut line = self.line;
+        let mut line_start = None;
+        let mut bytes = text.bytes().enumerate().peekable();
+
+        while let Some((index, byte)) = bytes.next() {
+            match byte {
+                b'\r' if bytes.peek().map(|&(_, next)| next) == Some(b'\n') => continue,
+                b'\r' | b'\n' => {
+                    line += 1;
+                    line_start = Some(index + 1);
+                },
+                _ => {},
+            }
+        }
+
+        let column = match line_start {
+            Some(start) => text[start..].encode_utf16().count() as u32,
+            None        => self.column + text.encode_utf16().count() as u32,
+        };
+
+        LineColumn { line, column }
+    }
+}
 
 /// A JavaScript module parsed to an AST.
 pub struct Module<'ast> {
     body: UnsafeList,
     arena: Arena,
+
+    /// `start`, `end` of statements and how they were terminated, sorted
+    terminators: Vec<(u32, u32, Terminator)>,
+
+    /// `start` of statements and their line and column, sorted
+    positions: Vec<(u32, LineColumn)>,
+
+    features: Features,
+
+    /// `start`, `end` of function declarations in sloppy mode blocks, sorted
+    block_functions: Vec<(u32, u32)>,
+
+    comments: UnsafeList,
+
+    warnings: Vec<Diagnostic>,
+
+    /// Quota of the module and the allocations charged to it so far
+    budget: Budget,
+
     _phantom: PhantomData<&'ast StatementList<'ast>>
 }
 
@@ -16,19 +163,383 @@ impl<'ast> Module<'ast> {
         Module {
             body,
             arena,
+            terminators: Vec::new(),
+            positions: Vec::new(),
+            features: Features::empty(),
+            block_functions: Vec::new(),
+            comments: NodeList::<&str>::empty().into_unsafe(),
+            warnings: Vec::new(),
+            budget: Budget::default(),
             _phantom: PhantomData,
         }
     }
 
+    #[inline]
+    pub(crate) fn with_terminators(mut self, mut terminators: Vec<(u32, u32, Terminator)>) -> Self {
+        terminators.sort_unstable_by_key(|&(start, end, _)| (start, end));
+        self.terminators = terminators;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn with_positions(mut self, mut positions: Vec<(u32, LineColumn)>) -> Self {
+        positions.sort_unstable_by_key(|&(start, _)| start);
+        positions.dedup_by_key(|&mut (start, _)| start);
+        self.positions = positions;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn with_block_functions(mut self, mut block_functions: Vec<(u32, u32)>) -> Self {
+        block_functions.sort_unstable();
+        self.block_functions = block_functions;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn with_comments(mut self, comments: UnsafeList) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Get the set of syntax features used anywhere in the module,
+    /// useful to tell which transforms need to run without traversing
+    /// the AST.
+    #[inline]
+    pub fn features(&self) -> Features {
+        self.features
+    }
+
+    /// Get how the `statement` was terminated. Only available when parsed
+    /// with `ParserOptions::terminators`, and only for statements that
+    /// require a semicolon (expressions, declarations, `return`, `break`,
+    /// `continue` and `throw`). Statements inserted later have none, nor
+    /// do the statements at the offsets their source spans.
+    #[inline]
+    pub fn terminator(&self, statement: &Loc<Statement<'ast>>) -> Option<Terminator> {
+        self.terminators
+            .binary_search_by_key(&(statement.start, statement.end), |&(start, end, _)| (start, end))
+            .ok()
+            .map(|index| self.terminators[index].2)
+    }
+
+    /// Get the line and column at which the `statement` starts. Only
+    /// available when parsed with `ParserOptions::positions`, statements
+    /// inserted later have none, nor do the statements at the offsets
+    /// their source spans.
+    #[inline]
+    pub fn position(&self, statement: &Loc<Statement<'ast>>) -> Option<LineColumn> {
+        self.positions
+            .binary_search_by_key(&statement.start, |&(start, _)| start)
+            .ok()
+            .map(|index| self.positions[index].1)
+    }
+
+    /// Get the `start` of every statement with its line and column, sorted,
+    /// as a table to look up the positions of other nodes from, see
+    /// `position`.
+    #[inline]
+    pub fn positions(&self) -> &[(u32, LineColumn)] {
+        &self.positions
+    }
+
+    #[inline]
+    pub(crate) fn with_warnings(mut self, warnings: Vec<Diagnostic>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Get the warnings about code accepted in sloppy mode only, in the
+    /// order they were found. These are the legacy octal literals and
+    /// escapes of Annex B.1, such as `017` and `'\07'`, which are
+    /// `ErrorKind::LegacyOctalLiteral` and `ErrorKind::LegacyOctalEscape`
+    /// errors in strict mode code. Statements inserted later have none.
+    #[inline]
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    #[inline]
+    pub(crate) fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Get the `Budget` tracking allocations of the module against the
+    /// `ParserOptions::quota` it was parsed with. Passes of
+    /// `ratel-transformer` charge the nodes they allocate to it, and stop
+    /// changing the module once it's spent. Statements inserted later are
+    /// not charged.
+    #[inline]
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Get the `start`, `end` of all function declarations nested in blocks
+    /// of sloppy mode code, sorted. Per Annex B.3.3 their names are also
+    /// var-declared in the enclosing function, which strict mode code and
+    /// older engines don't do, so the behavior of such code differs across
+    /// environments.
+    #[inline]
+    pub fn block_functions(&self) -> &[(u32, u32)] {
+        &self.block_functions
+    }
+
+    /// Get all comments in the source, with their delimiters, in source
+    /// order. Comments of statements inserted later are not included.
+    #[inline]
+    pub fn comments(&self) -> NodeList<'ast, &'ast str> {
+        unsafe { self.comments.into_list() }
+    }
+
+    /// Iterate over all function-like nodes in the module, in source order,
+    /// including arrow functions, methods and accessors.
+    #[inline]
+    pub fn functions(&self) -> Functions<'ast> {
+        Functions::new(self.body())
+    }
+
     /// Get the body of the module as a list of statements.
     #[inline]
     pub fn body(&self) -> StatementList<'ast> {
         unsafe { self.body.into_list() }
     }
 
+    /// Parse the `source` and insert its statements so that the first one
+    /// ends up at `index` in the body, shifting the following statements.
+    /// Locations of the inserted statements are relative to the `source`,
+    /// and their syntax features are added to `features`. Terminators and
+    /// positions are not recorded for inserted statements, and those of
+    /// the statements at the offsets the `source` spans are dropped, as
+    /// they would be mistaken for them.
+    ///
+    /// Panics if `index` is greater than the number of statements.
+    pub fn insert(&mut self, index: usize, source: &str) -> Result<(), Vec<Error>> {
+        let (statements, features) = parse_statements(source, &self.arena)?;
+        let mut body = self.statements();
+
+        assert!(index <= body.len(), "insertion index (is {}) should be <= len (is {})", index, body.len());
+
+        let tail = body.split_off(index);
+
+        body.extend(statements.iter().cloned());
+        body.extend(tail);
+
+        self.body = NodeList::from_iter(&self.arena, body).into_unsafe();
+        self.features.insert(features);
+        self.forget(source);
+
+        #[cfg(feature = "debug-invariants")]
+        ::invariants::assert_valid(self, "Module::insert");
+
+        Ok(())
+    }
+
+    /// Parse the `source` and append its statements to the body,
+    /// same as `insert` at the end.
+    pub fn append(&mut self, source: &str) -> Result<(), Vec<Error>> {
+        let index = self.body().iter().count();
+
+        self.insert(index, source)
+    }
+
+    /// Parse the `source` as the new body, moving the current statements
+    /// to the start of the body of the function in `source` whose body
+    /// starts at byte `offset`. Locations of the new statements are
+    /// relative to the `source`, and their syntax features are added to
+    /// `features`, with terminators and positions dropped as by `insert`.
+    /// Fails with `ErrorKind::MissingFunctionBody` if the
+    /// `source` has no such function, leaving the module untouched.
+    pub fn wrap(&mut self, source: &str, offset: u32) -> Result<(), Vec<Error>> {
+        let (statements, features) = parse_statements(source, &self.arena)?;
+        let block = match function_body_at(statements, offset) {
+            Some(block) => block,
+            None => {
+                let error = Error {
+                    kind: ErrorKind::MissingFunctionBody,
+                    token: Token::BraceOpen,
+                    raw: "".into(),
+                    start: offset as usize,
+                    end: offset as usize,
+                };
+
+                return Err(Vec::from([error]));
+            },
+        };
+        let mut body = self.statements();
+
+        body.extend(block.body.iter().cloned());
+
+        let body = NodeList::from_iter(&self.arena, body);
+
+        block.set(self.arena.alloc(Loc::new(block.start, block.end, Block { body })));
+
+        self.body = statements.into_unsafe();
+        self.features.insert(features);
+        self.forget(source);
+
+        #[cfg(feature = "debug-invariants")]
+        ::invariants::assert_valid(self, "Module::wrap");
+
+        Ok(())
+    }
+
+    /// Drop the terminators and positions of the statements starting
+    /// within the length of the inserted `source`, as the statements
+    /// parsed from it have locations relative to it and would find those
+    /// of the original statements at the same offsets.
+    fn forget(&mut self, source: &str) {
+        let len = source.len() as u32;
+
+        self.terminators.retain(|&(start, _, _)| start >= len);
+        self.positions.retain(|&(start, _)| start >= len);
+    }
+
+    /// Remove the statements in the `range` of indexes from the body.
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn remove(&mut self, range: Range<usize>) {
+        let mut body = self.statements();
+
+        body.drain(range);
+
+        self.body = NodeList::from_iter(&self.arena, body).into_unsafe();
+
+        #[cfg(feature = "debug-invariants")]
+        ::invariants::assert_valid(self, "Module::remove");
+    }
+
+    /// Copy the body to a `Vec`, with statements living as long as the
+    /// borrow of the module, so that they can be mixed with new ones
+    /// allocated on the `Arena`.
+    #[inline]
+    fn statements<'a>(&'a self) -> Vec<StatementNode<'a>> {
+        let body: StatementList<'a> = unsafe { self.body.into_list() };
+
+        body.iter().cloned().collect()
+    }
+
     /// Get a reference to the `Arena` on which the AST is allocated.
+    /// Requires the `unstable` feature, as the allocator is an
+    /// implementation detail which may change in any release.
+    #[cfg(feature = "unstable")]
     #[inline]
     pub fn arena(&'ast self) -> &'ast Arena {
         &self.arena
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::{parse, parse_with_options, ParserOptions};
+
+    fn assert_body(module: &Module, expected: &str) {
+        assert_eq!(module.body(), parse(expected).unwrap().body());
+    }
+
+    #[test]
+    fn comments() {
+        let mut module = parse("// a\nb; /* c */").unwrap();
+
+        module.append("/* d */ e;").unwrap();
+
+        let comments = module.comments().iter().map(|comment| (comment.start, comment.end, comment.item)).collect::<Vec<_>>();
+
+        assert_eq!(comments, vec![(0, 4, "// a"), (8, 15, "/* c */")]);
+    }
+
+    #[test]
+    fn insert_statements() {
+        let mut module = parse("b; c;").unwrap();
+
+        module.insert(0, "'use strict'; a;").unwrap();
+        assert_body(&module, "'use strict'; a; b; c;");
+
+        module.insert(3, "class B {}").unwrap();
+        assert_body(&module, "'use strict'; a; b; class B {} c;");
+        assert!(module.features().contains(Features::CLASSES));
+    }
+
+    #[test]
+    fn insert_forgets_colliding_locations() {
+        let options = ParserOptions {
+            terminators: true,
+            positions: true,
+            ..ParserOptions::default()
+        };
+        let mut module = parse_with_options("a\nb;\nc;", options).unwrap();
+
+        module.insert(0, "x;\ny").unwrap();
+
+        let terminators: Vec<_> = module.body().iter().map(|statement| module.terminator(statement)).collect();
+        let positions: Vec<_> = module.body().iter().map(|statement| module.position(statement)).collect();
+
+        assert_eq!(terminators, vec![None, None, None, None, Some(Terminator::Semicolon)]);
+        assert_eq!(positions, vec![None, None, None, None, Some(LineColumn { line: 3, column: 0 })]);
+
+        module.wrap("(function () {})();", 13).unwrap();
+
+        assert!(module.positions().is_empty());
+    }
+
+    #[test]
+    fn append_statements() {
+        let mut module = parse("").unwrap();
+
+        module.append("a;").unwrap();
+        module.append("b; c;").unwrap();
+        assert_body(&module, "a; b; c;");
+    }
+
+    #[test]
+    fn insert_invalid_source() {
+        let mut module = parse("a;").unwrap();
+
+        assert!(module.append("b +;").is_err());
+        assert_body(&module, "a;");
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut module = parse("a;").unwrap();
+
+        let _ = module.insert(2, "b;");
+    }
+
+    #[test]
+    fn remove_statements() {
+        let mut module = parse("a; b; c; d;").unwrap();
+
+        module.remove(1..3);
+        assert_body(&module, "a; d;");
+
+        module.remove(0..2);
+        assert_body(&module, "");
+    }
+
+    #[test]
+    fn wrap_statements() {
+        let mut module = parse("'use strict'; a; b;").unwrap();
+
+        module.wrap("x(function () {}, function () { return c; });", 30).unwrap();
+        assert_body(&module, "x(function () {}, function () { 'use strict'; a; b; return c; });");
+    }
+
+    #[test]
+    fn wrap_without_function() {
+        let mut module = parse("a;").unwrap();
+        let errors = module.wrap("x(function () {});", 0).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].kind, errors[0].start, errors[0].end), (ErrorKind::MissingFunctionBody, 0, 0));
+        assert_body(&module, "a;");
+    }
+}
@@ -0,0 +1,149 @@
+//! Hooks for build tools collecting metrics of parsing, see
+//! `parse_with_observer`. The parser calls a `ParserObserver` at the
+//! boundaries of its phases, with counters of what it has read and built
+//! so far, leaving the clock and the metrics stack to the observer.
+//! `Timings` records the duration of each phase.
+
+#[cfg(any(test, feature = "std"))]
+use std::time::{Duration, Instant};
+#[cfg(any(test, feature = "std"))]
+use allocated::*;
+
+/// Phase of parsing a module, in the order they run.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Phase {
+    /// Copying the source onto the arena of the module, and reading the
+    /// first token
+    Setup,
+
+    /// Parsing and validating the statements
+    Parse,
+
+    /// Building the `Module` from the results of the parser
+    Finish,
+}
+
+/// Counters of a parse at the end of a phase.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct ParseStats {
+    /// Length of the source in bytes, which is copied to the arena
+    pub source_bytes: usize,
+
+    /// Tokens read so far, comments included
+    pub tokens: usize,
+
+    /// Statements of the module body parsed so far
+    pub statements: usize,
+
+    pub comments: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// Callbacks invoked by `parse_with_observer` at the start and end of
+/// every `Phase`.
+pub trait ParserObserver {
+    #[inline]
+    fn phase_start(&mut self, _phase: Phase) {}
+
+    #[inline]
+    fn phase_end(&mut self, _phase: Phase, _stats: &ParseStats) {}
+}
+
+/// Observer doing nothing, used by `parse_with_options`.
+impl ParserObserver for () {}
+
+/// Observer recording the duration of every phase, with the counters at
+/// its end.
+#[cfg(any(test, feature = "std"))]
+#[derive(Debug, Default)]
+pub struct Timings {
+    pub phases: Vec<(Phase, Duration, ParseStats)>,
+    started: Option<Instant>,
+}
+
+#[cfg(any(test, feature = "std"))]
+impl Timings {
+    #[inline]
+    pub fn new() -> Self {
+        Timings::default()
+    }
+
+    /// Total duration of the phases recorded so far.
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|&(_, duration, _)| duration).sum()
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl ParserObserver for Timings {
+    #[inline]
+    fn phase_start(&mut self, _phase: Phase) {
+        self.started = Some(Instant::now());
+    }
+
+    #[inline]
+    fn phase_end(&mut self, phase: Phase, stats: &ParseStats) {
+        let elapsed = self.started.take().map_or(Duration::default(), |started| started.elapsed());
+
+        self.phases.push((phase, elapsed, *stats));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::{parse_with_observer, ParserOptions};
+
+    #[derive(Default)]
+    struct Events(Vec<(bool, Phase, Option<ParseStats>)>);
+
+    impl ParserObserver for Events {
+        fn phase_start(&mut self, phase: Phase) {
+            self.0.push((true, phase, None));
+        }
+
+        fn phase_end(&mut self, phase: Phase, stats: &ParseStats) {
+            self.0.push((false, phase, Some(*stats)));
+        }
+    }
+
+    #[test]
+    fn observes_phases() {
+        let mut events = Events::default();
+        let module = parse_with_observer("a; /* b */ 017;", ParserOptions::default(), &mut events);
+
+        assert!(module.is_ok());
+
+        let stats = ParseStats {
+            source_bytes: 15,
+            tokens: 6,
+            statements: 2,
+            comments: 1,
+            errors: 0,
+            warnings: 1,
+        };
+
+        assert_eq!(events.0, vec![
+            (true, Phase::Setup, None),
+            (false, Phase::Setup, Some(ParseStats { source_bytes: 15, tokens: 1, ..ParseStats::default() })),
+            (true, Phase::Parse, None),
+            (false, Phase::Parse, Some(stats)),
+            (true, Phase::Finish, None),
+            (false, Phase::Finish, Some(stats)),
+        ]);
+    }
+
+    #[test]
+    fn times_phases() {
+        let mut timings = Timings::new();
+        let module = parse_with_observer("a(", ParserOptions::default(), &mut timings);
+
+        assert!(module.is_err());
+
+        let phases: Vec<_> = timings.phases.iter().map(|&(phase, _, stats)| (phase, stats.errors > 0)).collect();
+
+        assert_eq!(phases, vec![(Phase::Setup, false), (Phase::Parse, true), (Phase::Finish, true)]);
+        assert!(timings.total() >= timings.phases[0].1);
+    }
+}
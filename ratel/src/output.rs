@@ -0,0 +1,234 @@
+//! Common result of the phases of the crate and of `ratel-transformer` and
+//! `ratel-codegen`, so that they can be chained with `Output::and_then`.
+//! Each phase gives the value it produced along with its diagnostics, the
+//! time it took and the syntax features of the module:
+//!
+//! - `parse_output` gives the `Module`, which is owned by the `Output`.
+//! - `PassManager::run_output` gives a `&Module`, borrowed from the owner
+//!   of the module it transformed, see `Output::as_borrowed`.
+//! - `codegen_output` gives the code as a `String`.
+//!
+//! The functions returning a `Result` are still there, `Output` collects
+//! everything the phases report in one place without losing warnings.
+
+#[cfg(any(test, feature = "std"))]
+use std::time::Instant;
+use std::marker::PhantomData;
+use std::time::Duration;
+use allocated::*;
+use toolshed::Arena;
+use ast::{NodeList, Statement};
+use diagnostic::{Diagnostic, DiagnosticsSink, Severity};
+use module::{Module, Features};
+use parser::{parse_with_options, ParserOptions};
+
+/// Time it took to run a phase, or one of the passes of a pipeline.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Timing {
+    /// Name of the phase, such as `"parse"`, or of the pass
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// Value produced by a phase, a `Module` by default, with what the phases
+/// that led to it reported.
+pub struct Output<'ast, T = Module<'ast>> {
+    pub value: T,
+
+    /// Errors and warnings of all the phases, in the order they ran
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Timings of all the phases, in the order they ran. Phases are only
+    /// timed with the `std` feature.
+    pub timings: Vec<Timing>,
+
+    /// Syntax features used by the module, see `Module::features`
+    pub features: Features,
+
+    _phantom: PhantomData<&'ast ()>,
+}
+
+impl<'ast, T> Output<'ast, T> {
+    #[inline]
+    pub fn new(value: T, features: Features) -> Self {
+        Output {
+            value,
+            diagnostics: Vec::new(),
+            timings: Vec::new(),
+            features,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Add the time it took to run the phase `name`.
+    #[inline]
+    pub fn with_timing<N: Into<String>>(mut self, name: N, duration: Duration) -> Self {
+        self.timings.push(Timing { name: name.into(), duration });
+        self
+    }
+
+    /// Check whether any of the phases reported an error.
+    #[inline]
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// Report the diagnostics of the phases so far to the `sink`, as a
+    /// single batch. Nothing is reported if there are none.
+    #[inline]
+    pub fn report_to<S: DiagnosticsSink + ?Sized>(&self, sink: &S) {
+        if !self.diagnostics.is_empty() {
+            sink.report(&self.diagnostics);
+        }
+    }
+
+    /// Get the value, or the diagnostics if any of them is an error.
+    #[inline]
+    pub fn into_result(self) -> Result<T, Vec<Diagnostic>> {
+        match self.has_errors() {
+            true => Err(self.diagnostics),
+            false => Ok(self.value),
+        }
+    }
+
+    /// Replace the value, keeping what the phases reported.
+    #[inline]
+    pub fn map<U, F>(self, f: F) -> Output<'ast, U> where
+        F: FnOnce(T) -> U,
+    {
+        Output {
+            value: f(self.value),
+            diagnostics: self.diagnostics,
+            timings: self.timings,
+            features: self.features,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Run the next phase on the value, adding its diagnostics and timings
+    /// after those of the phases so far. The next phase runs even if there
+    /// are errors, check `has_errors` first to stop at them.
+    #[inline]
+    pub fn and_then<U, F>(mut self, f: F) -> Output<'ast, U> where
+        F: FnOnce(T) -> Output<'ast, U>,
+    {
+        let mut next = f(self.value);
+
+        self.diagnostics.append(&mut next.diagnostics);
+        self.timings.append(&mut next.timings);
+
+        Output {
+            value: next.value,
+            diagnostics: self.diagnostics,
+            timings: self.timings,
+            features: self.features | next.features,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'ast> Output<'ast> {
+    /// Borrow the module, for the phases working on a `&Module`, with a
+    /// copy of what was reported so far.
+    #[inline]
+    pub fn as_borrowed(&'ast self) -> Output<'ast, &'ast Module<'ast>> {
+        Output {
+            value: &self.value,
+            diagnostics: self.diagnostics.clone(),
+            timings: self.timings.clone(),
+            features: self.features,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Parse the source to an `Output`, with the errors as diagnostics followed
+/// by the warnings of the module. When parsing fails the module is empty.
+pub fn parse_output<'ast>(source: &str, options: ParserOptions) -> Output<'ast> {
+    #[cfg(any(test, feature = "std"))]
+    let start = Instant::now();
+
+    let result = parse_with_options(source, options);
+
+    let output = match result {
+        Ok(module) => {
+            let features = module.features();
+            let warnings = module.warnings().to_vec();
+            let mut output = Output::new(module, features);
+
+            output.diagnostics = warnings;
+            output
+        },
+        Err(errors) => {
+            let empty = Module::new(NodeList::<Statement>::empty().into_unsafe(), Arena::new());
+            let mut output = Output::new(empty, Features::empty());
+
+            output.diagnostics = errors.iter().map(|error| error.diagnostic()).collect();
+            output
+        },
+    };
+
+    #[cfg(any(test, feature = "std"))]
+    let output = output.with_timing("parse", start.elapsed());
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_with_warnings() {
+        let output = parse_output("017; () => {}", ParserOptions::default());
+
+        assert!(!output.has_errors());
+        assert_eq!(output.diagnostics.len(), 1);
+        assert_eq!(output.diagnostics[0].severity, Severity::Warning);
+        assert_eq!(output.features, Features::ARROW_FUNCTIONS);
+        assert_eq!(output.timings.iter().map(|timing| timing.name.as_str()).collect::<Vec<_>>(), vec!["parse"]);
+        assert_eq!(output.value.body().iter().count(), 2);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let output = parse_output("a(", ParserOptions::default());
+
+        assert!(output.has_errors());
+        assert_eq!(output.value.body().iter().count(), 0);
+        assert_eq!(output.into_result().err().unwrap()[0].code, "E0001");
+    }
+
+    #[test]
+    fn reports_to_a_sink() {
+        use std::sync::Mutex;
+
+        let sink = Mutex::new(Vec::new());
+
+        parse_output("017; 017;", ParserOptions::default()).report_to(&sink);
+        parse_output("a;", ParserOptions::default()).report_to(&sink);
+
+        let diagnostics = sink.into_inner().unwrap();
+
+        assert_eq!(diagnostics.iter().map(|diagnostic| diagnostic.code).collect::<Vec<_>>(), vec!["E0007", "E0007"]);
+    }
+
+    #[test]
+    fn chains_phases() {
+        let parsed = parse_output("017;", ParserOptions::default());
+
+        let output = parsed.as_borrowed().and_then(|module| {
+            let mut output = Output::new(module.body().iter().count(), Features::CLASSES)
+                .with_timing("count", Duration::from_secs(1));
+
+            output.diagnostics.push(Diagnostic::new(Severity::Note, "count", "counted"));
+            output
+        });
+
+        assert_eq!(output.value, 1);
+        assert_eq!(output.features, Features::CLASSES);
+        assert_eq!(output.diagnostics.iter().map(|diagnostic| diagnostic.code).collect::<Vec<_>>(), vec!["E0007", "count"]);
+        assert_eq!(output.timings.iter().map(|timing| timing.name.as_str()).collect::<Vec<_>>(), vec!["parse", "count"]);
+        assert_eq!(output.map(|count| count * 2).into_result(), Ok(2));
+    }
+}
@@ -1,7 +1,7 @@
 use error::Error;
 
 use ast::{Node, Loc, NodeList, Pattern};
-use ast::{Name, ClassMember, Property, PropertyKey, MandatoryName, Block};
+use ast::{Name, ClassMember, Property, PropertyKey, MandatoryName, Block, StringLiteral};
 use parser::Parser;
 
 pub trait Handle<'ast> {
@@ -100,6 +100,17 @@ impl<'ast> ToError for Node<'ast, PropertyKey<'ast>> {
     }
 }
 
+impl<'ast> ToError for Node<'ast, StringLiteral<'ast>> {
+    #[inline]
+    fn to_error() -> Self {
+        Node::new(&Loc {
+            start: 0,
+            end: 0,
+            item: StringLiteral { value: "", raw: "" }
+        })
+    }
+}
+
 impl ToError for () {
     #[inline]
     fn to_error() -> Self {
@@ -1,14 +1,22 @@
+use std::mem;
 use toolshed::list::ListBuilder;
 use parser::{Parser, Parse, BindingPower, ANY, B0, B15};
+use parser::validator::Params;
+use error::ErrorKind;
 use lexer::Token::*;
+use lexer::Asi;
 use ast::{Node, NodeList, Expression, ExpressionNode, IdentifierNode, ExpressionList};
-use ast::{Property, PropertyKey, OperatorKind, Literal, Function, Class, StatementNode};
+use ast::{Property, PropertyKey, OperatorKind, Literal, NumberLiteral, StringLiteral};
+use ast::{Function, Class, StatementNode};
 use ast::expression::*;
+use module::Features;
+#[cfg(feature = "trace")]
+use parser::trace::Production;
 
 
 type ExpressionHandler = for<'ast> fn(&mut Parser<'ast>) -> ExpressionNode<'ast>;
 
-pub type Context = &'static [ExpressionHandler; 108];
+pub type Context = &'static [ExpressionHandler; 109];
 
 static DEF_CONTEXT: Context = &[
     ____, ____, ____, ____, PRN,  ____, ARR,  ____, OBJ,  ____, ____, NEW,
@@ -29,14 +37,17 @@ static DEF_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, YILD, ____, FUNC, THIS, ____, ____, ____,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
-    ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
-//  IMPRT TRY   STATI TRUE  FALSE NULL  UNDEF STR   NUM   BIN   REGEX ENUM
+    ____, ____, ____, TRUE, FALS, NULL, STR,  NUM,  BIN,  ____, ____, ____,
+//  IMPRT TRY   STATI TRUE  FALSE NULL  STR   NUM   BIN   REGEX ENUM  IMPL
 
-    ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
-//  IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+    ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____, ____,
+//  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E ?.
+
+    ____,
+//  #PRIV
 ];
 
 // Adds handlers for VoidExpression and SpreadExpression
@@ -47,9 +58,10 @@ pub static ARRAY_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, SPRD, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
-    ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
-    ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
+    SUPR, ____, ____, ____, ____, YILD, ____, FUNC, THIS, ____, ____, ____,
+    ____, ____, ____, TRUE, FALS, NULL, STR,  NUM,  BIN,  ____, ____, ____,
+    ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____, ____,
+    ____,
 ];
 
 // Adds handler for SpreadExpression
@@ -60,9 +72,10 @@ pub static CALL_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, SPRD, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
-    ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
-    ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
+    SUPR, ____, ____, ____, ____, YILD, ____, FUNC, THIS, ____, ____, ____,
+    ____, ____, ____, TRUE, FALS, NULL, STR,  NUM,  BIN,  ____, ____, ____,
+    ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____, ____,
+    ____,
 ];
 
 macro_rules! create_handlers {
@@ -112,19 +125,32 @@ create_handlers! {
 
     const IDEN = |par| {
         let ident = par.lexer.token_as_str();
-        let expr = par.alloc_in_loc(ident);
+        let (start, end) = par.lexer.loc();
 
         par.lexer.consume();
-        expr
+
+        if ident == "async" && par.asi() == Asi::NoSemicolon {
+            return par.async_expression(start, end);
+        }
+
+        if ident == "await" && (par.is_async || par.async_arguments && par.await_operand()) {
+            return par.await_expression(start);
+        }
+
+        par.alloc_at_loc(start, end, ident)
     };
 
     const SPRD = |par| {
+        par.features.insert(Features::SPREAD);
+
         let start = par.lexer.start_then_consume();
         let argument = par.expression::<B0>();
 
         par.alloc_at_loc(start, argument.end, SpreadExpression { argument })
     };
 
+    pub const YILD = |par| par.yield_expression();
+
     pub const THIS = |par| {
         let expr = par.alloc_in_loc(ThisExpression);
         par.lexer.consume();
@@ -132,12 +158,36 @@ create_handlers! {
         expr
     };
 
+    pub const SUPR = |par| {
+        let expr = par.alloc_in_loc(SuperExpression);
+        par.lexer.consume();
+
+        // `super` can only be called or have its members accessed
+        match par.lexer.token {
+            ParenOpen if !par.super_call => {
+                par.error_at(ErrorKind::SuperCallOutsideConstructor, Super, "super", expr.start, expr.end);
+            },
+            BracketOpen | Accessor if !par.super_property => {
+                par.error_at(ErrorKind::SuperPropertyOutsideMethod, Super, "super", expr.start, expr.end);
+            },
+            ParenOpen | BracketOpen | Accessor => {},
+            _ => par.error::<()>(),
+        }
+
+        expr
+    };
+
     pub const OP = |par| {
         let start = par.lexer.start();
         let op = OperatorKind::from_token(par.lexer.token).expect("Must be a prefix operator");
         par.lexer.consume();
         let expression = par.prefix_expression(op);
         let end = par.lexer.end();
+
+        if !matches!(op, OperatorKind::Increment | OperatorKind::Decrement) {
+            par.unary_operand(op.as_str(), start, expression.operand.end);
+        }
+
         par.alloc_at_loc(start, end, expression)
     };
 
@@ -155,6 +205,11 @@ create_handlers! {
         } else {
             let expression = par.prefix_expression(OperatorKind::New);
             let end = par.lexer.end();
+
+            if expression.operand.is_optional_chain() {
+                par.error_at(ErrorKind::InvalidOptionalChain, OperatorNew, "new", start, expression.operand.end);
+            }
+
             par.alloc_at_loc(start, end, expression)
         }
     };
@@ -168,14 +223,14 @@ create_handlers! {
     pub const REG = |par| par.regular_expression();
 
     pub const TRUE = |par| {
-        let expr = par.alloc_in_loc(Literal::True);
+        let expr = par.alloc_in_loc(Literal::Boolean(true));
         par.lexer.consume();
 
         expr
     };
 
     pub const FALS = |par| {
-        let expr = par.alloc_in_loc(Literal::False);
+        let expr = par.alloc_in_loc(Literal::Boolean(false));
 
         par.lexer.consume();
         expr
@@ -188,39 +243,41 @@ create_handlers! {
         expr
     };
 
-    pub const UNDE = |par| {
-        let expr = par.alloc_in_loc(Literal::Undefined);
-
-        par.lexer.consume();
-        expr
-    };
-
     pub const STR = |par| {
-        let value = par.lexer.token_as_str();
-        let expr = par.alloc_in_loc(Literal::String(value));
+        par.validate_octal();
+
+        let raw = par.lexer.token_as_str();
+        let raw = par.literal(raw);
+        let value = par.string_value(raw);
+        let expr = par.alloc_in_loc(Literal::String(StringLiteral { value, raw }));
 
         par.lexer.consume();
         expr
     };
 
     pub const NUM = |par| {
-        let value = par.lexer.token_as_str();
-        let expr = par.alloc_in_loc(Literal::Number(value));
+        par.validate_octal();
+
+        let raw = par.lexer.token_as_str();
+        let expr = par.alloc_in_loc(Literal::Number(NumberLiteral::new(raw)));
 
         par.lexer.consume();
         expr
     };
 
     pub const BIN = |par| {
-        let value = par.lexer.token_as_str();
-        let expr = par.alloc_in_loc(Literal::Binary(value));
+        let raw = par.lexer.token_as_str();
+        let expr = par.alloc_in_loc(Literal::Number(NumberLiteral::new(raw)));
 
         par.lexer.consume();
         expr
     };
 
     pub const TPLS = |par| {
+        par.features.insert(Features::TEMPLATE_LITERALS);
+
         let quasi = par.lexer.quasi;
+        let quasi = par.literal(quasi);
         let quasi = par.alloc_in_loc(quasi);
 
         par.lexer.consume();
@@ -237,12 +294,16 @@ create_handlers! {
 impl<'ast> Parser<'ast> {
     #[inline]
     fn bound_expression(&mut self) -> ExpressionNode<'ast> {
-        unsafe { (*(DEF_CONTEXT as *const ExpressionHandler).offset(self.lexer.token as isize))(self) }
+        self.context_bound_expression(DEF_CONTEXT)
     }
 
     #[inline]
     fn context_bound_expression(&mut self, context: Context) -> ExpressionNode<'ast> {
-        unsafe { (*(context as *const ExpressionHandler).offset(self.lexer.token as isize))(self) }
+        trace!(self, trace_enter, Production::Expression);
+        let expression = unsafe { (*(context as *const ExpressionHandler).offset(self.lexer.token as isize))(self) };
+        trace!(self, trace_leave);
+
+        expression
     }
 
     #[inline]
@@ -265,16 +326,48 @@ impl<'ast> Parser<'ast> {
         self.nested_expression::<B>(left)
     }
 
+    /// Parse an arrow function after the `=>`, from the expressions before
+    /// it, `async` being read by the caller.
     #[inline]
-    pub fn arrow_function_expression(&mut self, params: ExpressionList<'ast>) -> ArrowExpression<'ast> {
+    pub fn arrow_function_expression(&mut self, params: ExpressionList<'ast>, is_async: bool) -> ArrowExpression<'ast> {
+        self.features.insert(Features::ARROW_FUNCTIONS);
+
+        if is_async {
+            self.features.insert(Features::ASYNC_FUNCTIONS);
+        }
+
+        // Suspensions since the first parameter are in the parameters
+        if let Some(first) = params.first_element() {
+            self.validate_suspensions(first.start);
+        }
+
         let params = self.params_from_expressions(params);
+        let strict = self.strict;
+        let generator = self.generator;
+        let outer_async = self.is_async;
+        let outer_suspensions = mem::take(&mut self.suspensions);
+        let async_arguments = mem::replace(&mut self.async_arguments, false);
+        let blocks = self.blocks;
+
+        self.generator = false;
+        self.is_async = is_async;
+        self.blocks = 0;
 
         let body = match self.lexer.token {
-            BraceOpen => ArrowBody::Block(self.unchecked_block()),
+            BraceOpen => ArrowBody::Block(self.function_body()),
             _         => ArrowBody::Expression(self.expression::<B0>()),
         };
 
+        self.validate_params(params, Params::Arrow, false, is_async);
+        self.strict = strict;
+        self.generator = generator;
+        self.is_async = outer_async;
+        self.suspensions = outer_suspensions;
+        self.async_arguments = async_arguments;
+        self.blocks = blocks;
+
         ArrowExpression {
+            is_async,
             params,
             body,
         }
@@ -320,7 +413,7 @@ impl<'ast> Parser<'ast> {
             ParenClose => {
                 self.lexer.consume();
                 expect!(self, OperatorFatArrow);
-                let expression = self.arrow_function_expression(NodeList::empty());
+                let expression = self.arrow_function_expression(NodeList::empty(), false);
                 let end = self.lexer.end();
                 self.alloc_at_loc(start, end, expression)
             },
@@ -329,7 +422,14 @@ impl<'ast> Parser<'ast> {
 
                 expect!(self, ParenClose);
 
-                expression
+                match self.lexer.token {
+                    Accessor | BracketOpen | ParenOpen | TemplateOpen | TemplateClosed
+                        if expression.is_optional_chain() =>
+                    {
+                        self.alloc_at_loc(expression.start, expression.end, ChainExpression { expression })
+                    },
+                    _ => expression,
+                }
             }
         }
     }
@@ -344,6 +444,85 @@ impl<'ast> Parser<'ast> {
         }
     }
 
+    /// Unary expressions can't be the left operand of `**` unless
+    /// parenthesized, as in `(-a) ** b`
+    #[inline]
+    fn unary_operand(&mut self, operator: &str, start: u32, end: u32) {
+        if self.lexer.token == OperatorExponent {
+            self.error_at(ErrorKind::UnparenthesizedUnaryOperand, OperatorExponent, operator, start, end);
+        }
+    }
+
+    /// Parse `await` after the keyword, within async functions only, as
+    /// it's an identifier everywhere else.
+    #[inline]
+    pub fn await_expression(&mut self, start: u32) -> ExpressionNode<'ast> {
+        let argument = self.expression::<B15>();
+
+        self.unary_operand("await", start, argument.end);
+        self.suspensions.push(("await", start, argument.end));
+        self.alloc_at_loc(start, argument.end, AwaitExpression { argument })
+    }
+
+    /// Check whether `await` in the arguments of an `async` call is followed
+    /// by what can only be its operand, in which case it's taken for an
+    /// expression, to be reported once the arguments are known to be the
+    /// parameters of an arrow function or not.
+    #[inline]
+    fn await_operand(&self) -> bool {
+        matches!(self.lexer.token,
+            Identifier | This | Super | Function | Class | OperatorNew |
+            LiteralTrue | LiteralFalse | LiteralNull | LiteralString |
+            LiteralNumber | LiteralBinary | BraceOpen | OperatorLogicalNot |
+            OperatorBitwiseNot | OperatorTypeof | OperatorVoid | OperatorDelete
+        )
+    }
+
+    /// Parse `yield`, which is an identifier outside of generators in
+    /// sloppy mode code. The argument is optional and must start on the
+    /// same line.
+    #[inline]
+    pub fn yield_expression(&mut self) -> ExpressionNode<'ast> {
+        let (start, end) = self.lexer.loc();
+
+        if !self.generator {
+            if self.strict {
+                self.error::<()>();
+            }
+
+            let expr = self.alloc_at_loc(start, end, "yield");
+
+            self.lexer.consume();
+
+            return expr;
+        }
+
+        self.lexer.consume();
+
+        let delegate = self.asi() == Asi::NoSemicolon && self.lexer.token == OperatorMultiplication;
+
+        if delegate {
+            self.lexer.consume();
+        }
+
+        let argument = match (self.asi(), self.lexer.token) {
+            _ if delegate                       => Some(self.expression::<B0>()),
+            (Asi::NoSemicolon, BracketClose)    |
+            (Asi::NoSemicolon, Comma)           |
+            (Asi::NoSemicolon, Colon)           => None,
+            (Asi::NoSemicolon, _)               => Some(self.expression::<B0>()),
+            _                                   => None,
+        };
+
+        let end = argument.map_or(end, |argument| argument.end);
+
+        self.suspensions.push(("yield", start, end));
+        self.alloc_at_loc(start, end, YieldExpression {
+            argument,
+            delegate,
+        })
+    }
+
     #[inline]
     pub fn object_expression(&mut self) -> ExpressionNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -359,6 +538,8 @@ impl<'ast> Parser<'ast> {
     pub fn meta_property_expression(&mut self, meta: IdentifierNode<'ast>) -> MetaPropertyExpression<'ast> {
         let property = self.lexer.accessor_as_str();
 
+        self.features.insert(Features::NEW_TARGET);
+
         // Only `NewTarget` is a valid MetaProperty.
         if property != "target" {
             self.error::<()>();
@@ -401,21 +582,63 @@ impl<'ast> Parser<'ast> {
     #[inline]
     pub fn property(&mut self) -> Node<'ast, Property<'ast>> {
         let start = self.lexer.start();
+        let mut is_async = false;
 
         let key = match self.lexer.token {
             _ if self.lexer.token.is_word() => {
                 let (start, end) = self.lexer.loc();
                 let label = self.lexer.token_as_str();
+                let token = self.lexer.token;
 
                 self.lexer.consume();
 
                 match self.lexer.token {
                     Colon | ParenOpen => self.alloc_at_loc(start, end, PropertyKey::Literal(label)),
 
+                    BracketOpen if label == "async" && self.lexer.asi() == Asi::NoSemicolon => {
+                        is_async = true;
+
+                        self.computed_key()
+                    },
+
+                    _ if label == "async" && self.lexer.token.is_word() && self.lexer.asi() == Asi::NoSemicolon => {
+                        let key = PropertyKey::Literal(self.lexer.token_as_str());
+
+                        is_async = true;
+
+                        let key = self.alloc_in_loc(key);
+
+                        self.lexer.consume();
+
+                        key
+                    },
+
+                    _ if token.is_reserved_word() => {
+                        self.error_at(ErrorKind::UnexpectedToken, token, label, start, end);
+
+                        return self.alloc_at_loc(start, end, Property::Shorthand(label));
+                    },
+
+                    OperatorAssign => {
+                        self.lexer.consume();
+
+                        let name = self.alloc_at_loc(start, end, label);
+                        let value = self.expression::<B0>();
+
+                        self.cover_initializers.push((label, start, value.end));
+
+                        return self.alloc_at_loc(start, value.end, Property::ShorthandDefault {
+                            name,
+                            value,
+                        });
+                    },
+
                     _ => return self.alloc_at_loc(start, end, Property::Shorthand(label)),
                 }
             },
             OperatorSpread => {
+                self.features.insert(Features::SPREAD);
+
                 let start = self.lexer.start_then_consume();
                 let argument = self.expression::<B0>();
                 let end = self.lexer.end();
@@ -423,6 +646,8 @@ impl<'ast> Parser<'ast> {
             },
             LiteralString |
             LiteralNumber => {
+                self.validate_octal();
+
                 let num = self.lexer.token_as_str();
                 let key = self.alloc_in_loc(PropertyKey::Literal(num));
 
@@ -438,20 +663,12 @@ impl<'ast> Parser<'ast> {
 
                 key
             },
-            BracketOpen => {
-                let start = self.lexer.start_then_consume();
-                let expression = self.expression::<ANY>();
-                let end = self.lexer.end();
-
-                expect!(self, BracketClose);
-
-                self.alloc_at_loc(start, end, PropertyKey::Computed(expression))
-            },
+            BracketOpen => self.computed_key(),
             _ => return self.error(),
         };
 
         match self.lexer.token {
-            Colon => {
+            Colon if !is_async => {
                 self.lexer.consume();
 
                 let value = self.expression::<B0>();
@@ -462,7 +679,7 @@ impl<'ast> Parser<'ast> {
                 })
             },
             ParenOpen => {
-                let value = Node::parse(self);
+                let value = self.method(is_async, false);
 
                 self.alloc_at_loc(start, value.end, Property::Method {
                     key,
@@ -473,6 +690,18 @@ impl<'ast> Parser<'ast> {
         }
     }
 
+    /// Parse the computed key, `[a]`, of a property or a class member
+    #[inline]
+    pub fn computed_key(&mut self) -> Node<'ast, PropertyKey<'ast>> {
+        let start = self.lexer.start_then_consume();
+        let expression = self.expression::<ANY>();
+        let end = self.lexer.end();
+
+        expect!(self, BracketClose);
+
+        self.alloc_at_loc(start, end, PropertyKey::Computed(expression))
+    }
+
     #[inline]
     pub fn array_expression(&mut self) -> ExpressionNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -533,7 +762,10 @@ impl<'ast> Parser<'ast> {
     where
         T: Copy + From<TemplateLiteral<'ast>>,
     {
+        self.features.insert(Features::TEMPLATE_LITERALS);
+
         let quasi = self.lexer.quasi;
+        let quasi = self.literal(quasi);
         let quasi = self.alloc_in_loc(quasi);
 
         self.lexer.consume();
@@ -549,7 +781,10 @@ impl<'ast> Parser<'ast> {
     where
         T: Copy + From<TemplateLiteral<'ast>>,
     {
+        self.features.insert(Features::TEMPLATE_LITERALS);
+
         let quasi = self.lexer.quasi;
+        let quasi = self.literal(quasi);
         let quasi = self.alloc_in_loc(quasi);
 
         let start = self.lexer.start_then_consume();
@@ -569,6 +804,7 @@ impl<'ast> Parser<'ast> {
             match self.lexer.token {
                 TemplateOpen => {
                     let quasi = self.lexer.quasi;
+                    let quasi = self.literal(quasi);
                     quasis.push(self.arena, self.alloc_in_loc(quasi));
                     self.lexer.consume();
                     expressions.push(self.arena, self.expression::<ANY>());
@@ -584,6 +820,7 @@ impl<'ast> Parser<'ast> {
                 },
                 TemplateClosed => {
                     let quasi = self.lexer.quasi;
+                    let quasi = self.literal(quasi);
                     quasis.push(self.arena, self.alloc_in_loc(quasi));
                     end = self.lexer.end_then_consume();
                     break;
@@ -609,6 +846,8 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     pub fn tagged_template_expression(&mut self, tag: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.template_tag(tag);
+
         let quasi = self.template_literal();
 
         self.alloc_at_loc(tag.start, quasi.end, TaggedTemplateExpression {
@@ -617,6 +856,15 @@ impl<'ast> Parser<'ast> {
         })
     }
 
+    /// Optional chains can't tag templates unless parenthesized, as in
+    /// `(a?.b)`x``
+    #[inline]
+    pub fn template_tag(&mut self, tag: ExpressionNode<'ast>) {
+        if tag.is_optional_chain() {
+            self.error_at(ErrorKind::InvalidOptionalChain, TemplateOpen, "`", tag.start, tag.end);
+        }
+    }
+
     #[inline]
     pub fn function_expression(&mut self) -> ExpressionNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -625,6 +873,77 @@ impl<'ast> Parser<'ast> {
         self.alloc_at_loc(start, function.body.end, function)
     }
 
+    /// Parse what follows `async` at `start` and `end` on the same line,
+    /// an async function or arrow function, or else the `async` identifier
+    /// along with the arguments if it's called.
+    pub fn async_expression(&mut self, start: u32, end: u32) -> ExpressionNode<'ast> {
+        match self.lexer.token {
+            Function => self.async_function_expression(start),
+            ParenOpen => {
+                self.lexer.consume();
+
+                // Outside of async functions `await` is an identifier, yet
+                // the arguments may be the parameters of an arrow function
+                let async_arguments = mem::replace(&mut self.async_arguments, !self.is_async);
+                let suspensions = self.suspensions.len();
+                let arguments = self.call_arguments();
+                let call_end = self.lexer.end_then_consume();
+
+                self.async_arguments = async_arguments;
+
+                if self.lexer.token == OperatorFatArrow && self.asi() == Asi::NoSemicolon {
+                    self.lexer.consume();
+
+                    let expression = self.arrow_function_expression(arguments, true);
+                    let end = self.lexer.end();
+
+                    return self.alloc_at_loc(start, end, expression);
+                }
+
+                if !self.is_async {
+                    self.validate_await_arguments(suspensions);
+                }
+
+                let callee = self.alloc_at_loc(start, end, "async");
+
+                self.alloc_at_loc(start, call_end, CallExpression {
+                    callee,
+                    arguments,
+                    optional: false,
+                })
+            },
+            Identifier => {
+                let param = self.lexer.token_as_str();
+                let (param_start, param_end) = self.lexer.loc();
+
+                self.lexer.consume();
+
+                match self.lexer.token {
+                    OperatorFatArrow if self.lexer.asi() == Asi::NoSemicolon => self.lexer.consume(),
+                    _ => self.error::<()>(),
+                }
+
+                let param = self.alloc_at_loc(param_start, param_end, param);
+                let expression = self.arrow_function_expression(NodeList::from(self.arena, param), true);
+                let end = self.lexer.end();
+
+                self.alloc_at_loc(start, end, expression)
+            },
+            _ => self.alloc_at_loc(start, end, "async"),
+        }
+    }
+
+    /// Parse an `async` function expression, at the `function` keyword
+    /// following `async` at `start`.
+    #[inline]
+    pub fn async_function_expression(&mut self, start: u32) -> ExpressionNode<'ast> {
+        self.lexer.consume();
+
+        let function: FunctionExpression = self.function(Params::Function, true);
+
+        self.alloc_at_loc(start, function.body.end, function)
+    }
+
     #[inline]
     pub fn class_expression(&mut self) -> ExpressionNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -637,11 +956,11 @@ impl<'ast> Parser<'ast> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use ast::{OperatorKind, Literal, Statement, Function, Pattern, Class};
+    use ast::{OperatorKind, Literal, Statement, Function, EmptyName, Pattern, Class, ClassMember};
     use ast::expression::*;
     use ast::statement::*;
     use parser::parse;
-    use parser::mock::Mock;
+    use testing::Mock;
 
     #[test]
     fn ident_expression() {
@@ -650,11 +969,31 @@ mod test {
         assert_expr!("foobar;", expected);
     }
 
+    #[test]
+    fn undefined_expression() {
+        let expected = Expression::Identifier("undefined");
+
+        assert_expr!("undefined;", expected);
+    }
+
+    #[test]
+    fn void_expression() {
+        let src = "void 0;";
+        let mock = Mock::new();
+
+        let expected = PrefixExpression {
+            operator: OperatorKind::Void,
+            operand: mock.number("0"),
+        };
+
+        assert_expr!(src, expected);
+    }
+
     #[test]
     fn value_expression() {
-        let expected_a = Literal::String(r#""foobar""#);
-        let expected_b = Literal::Number("100");
-        let expected_c = Literal::True;
+        let expected_a = Literal::String(StringLiteral { value: "foobar", raw: r#""foobar""# });
+        let expected_b = Literal::Number(NumberLiteral::new("100"));
+        let expected_c = Literal::Boolean(true);
 
         assert_expr!(r#""foobar";"#, expected_a);
         assert_expr!("100;", expected_b);
@@ -697,8 +1036,8 @@ mod test {
 
         let expected = TemplateLiteral {
             expressions: mock.list([
-                Literal::Number("10"),
-                Literal::Number("20"),
+                Literal::Number(NumberLiteral::new("10")),
+                Literal::Number(NumberLiteral::new("20")),
             ]),
             quasis: mock.list(["foo", "bar", "baz" ]),
         };
@@ -715,7 +1054,7 @@ mod test {
             tag: mock.ptr("foo"),
             quasi: mock.ptr(TemplateLiteral {
                 expressions: mock.list([
-                    Literal::Number("42"),
+                    Literal::Number(NumberLiteral::new("42")),
                 ]),
                 quasis: mock.list(["bar", "baz"]),
             })
@@ -764,6 +1103,44 @@ mod test {
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn exponentiation_expression() {
+        let src = "a * b ** c ** d;";
+        let mock = Mock::new();
+
+        let expected = BinaryExpression {
+            operator: OperatorKind::Multiplication,
+            left: mock.ptr("a"),
+            right: mock.ptr(BinaryExpression {
+                operator: OperatorKind::Exponent,
+                left: mock.ptr("b"),
+                right: mock.ptr(BinaryExpression {
+                    operator: OperatorKind::Exponent,
+                    left: mock.ptr("c"),
+                    right: mock.ptr("d"),
+                }),
+            }),
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn exponentiation_of_unary_operands() {
+        assert!(parse("(-a) ** b; -(a ** b); a ** -b; ++a ** b; a-- ** b; new A ** b; a **= -b;").is_ok());
+        assert!(parse("async function f() { (await a) ** b; await (a ** b); }").is_ok());
+
+        let error = |src| match parse(src) {
+            Ok(_)       => panic!("{:?} should not parse", src),
+            Err(errors) => errors.iter().map(|err| (err.kind, err.raw.to_string(), err.start, err.end)).collect::<Vec<_>>(),
+        };
+
+        assert_eq!(error("-a ** b;"), vec![(ErrorKind::UnparenthesizedUnaryOperand, "-".to_owned(), 0, 2)]);
+        assert_eq!(error("x = typeof a ** b;"), vec![(ErrorKind::UnparenthesizedUnaryOperand, "typeof".to_owned(), 4, 12)]);
+        assert_eq!(error("a ** !b ** c;"), vec![(ErrorKind::UnparenthesizedUnaryOperand, "!".to_owned(), 5, 7)]);
+        assert_eq!(error("async function f() { await a ** b; }"), vec![(ErrorKind::UnparenthesizedUnaryOperand, "await".to_owned(), 21, 28)]);
+    }
+
     #[test]
     fn conditional_expression() {
         let src = "true ? foo : bar";
@@ -771,7 +1148,7 @@ mod test {
         let mock = Mock::new();
 
         let expected = ConditionalExpression {
-            test: mock.ptr(Expression::Literal(Literal::True)),
+            test: mock.ptr(Expression::Literal(Literal::Boolean(true))),
             consequent: mock.ptr("foo"),
             alternate: mock.ptr("bar"),
         };
@@ -779,6 +1156,34 @@ mod test {
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn nested_conditional_expression() {
+        // Right associative, with assignments in either branch
+        let src = "a ? b = c : d ? e : f = g";
+
+        let mock = Mock::new();
+
+        let expected = ConditionalExpression {
+            test: mock.ptr("a"),
+            consequent: mock.ptr(BinaryExpression {
+                operator: OperatorKind::Assign,
+                left: mock.ptr("b"),
+                right: mock.ptr("c"),
+            }),
+            alternate: mock.ptr(ConditionalExpression {
+                test: mock.ptr("d"),
+                consequent: mock.ptr("e"),
+                alternate: mock.ptr(BinaryExpression {
+                    operator: OperatorKind::Assign,
+                    left: mock.ptr("f"),
+                    right: mock.ptr("g"),
+                }),
+            }),
+        };
+
+        assert_expr!(src, expected);
+    }
+
     #[test]
     fn postfix_expression() {
         let src = "baz++;";
@@ -801,6 +1206,7 @@ mod test {
             let expected = CallExpression {
                 callee: mock.ptr("foo"),
                 arguments: NodeList::empty(),
+                optional: false,
             };
 
             assert_expr!(src, expected);
@@ -813,8 +1219,9 @@ mod test {
             let expected = CallExpression {
                 callee: mock.ptr("foo"),
                 arguments: mock.list([
-                    Literal::Number("1"),
+                    Literal::Number(NumberLiteral::new("1")),
                 ]),
+                optional: false,
             };
 
             assert_expr!(src, expected);
@@ -827,9 +1234,10 @@ mod test {
             let expected = CallExpression {
                 callee: mock.ptr("foo"),
                 arguments: mock.list([
-                    Literal::Number("1"),
-                    Literal::Number("2"),
+                    Literal::Number(NumberLiteral::new("1")),
+                    Literal::Number(NumberLiteral::new("2")),
                 ]),
+                optional: false,
             };
 
             assert_expr!(src, expected);
@@ -842,8 +1250,9 @@ mod test {
             let expected = CallExpression {
                 callee: mock.ptr("foo"),
                 arguments: mock.list([
-                    Literal::Number("1"),
+                    Literal::Number(NumberLiteral::new("1")),
                 ]),
+                optional: false,
             };
 
             assert_expr!(src, expected);
@@ -856,9 +1265,10 @@ mod test {
             let expected = CallExpression {
                 callee: mock.ptr("foo"),
                 arguments: mock.list([
-                    Literal::Number("1"),
-                    Literal::Number("2"),
+                    Literal::Number(NumberLiteral::new("1")),
+                    Literal::Number(NumberLiteral::new("2")),
                 ]),
+                optional: false,
             };
 
             assert_expr!(src, expected);
@@ -873,11 +1283,211 @@ mod test {
         let expected = MemberExpression {
             object: mock.ptr("foo"),
             property: mock.ptr("bar"),
+            optional: false,
         };
 
         assert_expr!(src, expected);
     }
 
+    /// Parse `src` as the only statement of the constructor of a class with
+    /// `extends`, where `super` can be called and have its members accessed.
+    fn assert_constructor_expr<'ast, E: Into<Expression<'ast>>>(src: &str, expected: E) {
+        let src = format!("class A extends B {{ constructor() {{ {}; }} }}", src);
+        let module = parse(&src).unwrap();
+
+        let class = match module.body().iter().next().map(|statement| statement.item) {
+            Some(Statement::Class(class)) => class,
+            _ => panic!("Statement isn't a class!"),
+        };
+
+        match class.body.body.iter().next().map(|member| member.item) {
+            Some(ClassMember::Method { value, .. }) => match value.body.body.iter().next().map(|statement| statement.item) {
+                Some(Statement::Expression(expression)) => assert_eq!(expression.item, expected.into()),
+                _ => panic!("Statement isn't an expression!"),
+            },
+            _ => panic!("Member isn't a method!"),
+        }
+    }
+
+    #[test]
+    fn super_expression() {
+        let mock = Mock::new();
+
+        assert_constructor_expr("super.foo", MemberExpression {
+            object: mock.ptr(SuperExpression),
+            property: mock.ptr("foo"),
+            optional: false,
+        });
+
+        assert_constructor_expr("super[foo]", ComputedMemberExpression {
+            object: mock.ptr(SuperExpression),
+            property: mock.ptr("foo"),
+            optional: false,
+        });
+
+        assert_constructor_expr("super(foo)", CallExpression {
+            callee: mock.ptr(SuperExpression),
+            arguments: mock.list(["foo"]),
+            optional: false,
+        });
+
+        assert!(parse("super;").is_err());
+        assert!(parse("super + 1;").is_err());
+        assert!(parse("super?.foo;").is_err());
+        assert!(parse("super`foo`;").is_err());
+    }
+
+    #[test]
+    fn optional_member_expression() {
+        let src = "foo?.bar";
+        let mock = Mock::new();
+
+        let expected = MemberExpression {
+            object: mock.ptr("foo"),
+            property: mock.ptr("bar"),
+            optional: true,
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn optional_computed_member_expression() {
+        let src = "foo?.[10]";
+        let mock = Mock::new();
+
+        let expected = ComputedMemberExpression {
+            object: mock.ptr("foo"),
+            property: mock.number("10"),
+            optional: true,
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    /// Parse `src` as the only statement of a static block of a class,
+    /// where private names can be used.
+    fn assert_class_expr(src: &str, expected: Expression) {
+        let src = format!("class A {{ static {{ {}; }} }}", src);
+        let module = parse(&src).unwrap();
+
+        let class = match module.body().iter().next().map(|statement| statement.item) {
+            Some(Statement::Class(class)) => class,
+            _ => panic!("Statement isn't a class!"),
+        };
+
+        match class.body.body.iter().next().map(|member| member.item) {
+            Some(ClassMember::StaticBlock { body }) => match body.iter().next().map(|statement| statement.item) {
+                Some(Statement::Expression(expression)) => assert_eq!(expression.item, expected),
+                _ => panic!("Statement isn't an expression!"),
+            },
+            _ => panic!("Member isn't a static block!"),
+        }
+    }
+
+    #[test]
+    fn private_member_expression() {
+        let mock = Mock::new();
+
+        let expected = PrivateMemberExpression {
+            object: mock.ptr(Expression::This(ThisExpression)),
+            property: mock.ptr("foo"),
+            optional: false,
+        };
+
+        assert_class_expr("this.#foo", Expression::from(expected));
+    }
+
+    #[test]
+    fn optional_private_member_expression() {
+        let mock = Mock::new();
+
+        let expected = PrivateMemberExpression {
+            object: mock.ptr("foo"),
+            property: mock.ptr("bar"),
+            optional: true,
+        };
+
+        assert_class_expr("foo?.#bar", Expression::from(expected));
+        assert!(parse("class A { m() { this.# foo; } }").is_err());
+        assert!(parse("class A { m() { this.#1; } }").is_err());
+    }
+
+    #[test]
+    fn private_name_outside_class() {
+        assert!(parse("class A { #x; m() { (function () { this.#x; }); } static { a?.#x; } } class B extends (this.#x) {}").is_err());
+        assert!(parse("class A { #x; m() { (function () { this.#x; }); } static { a?.#x; } }").is_ok());
+
+        let error = |src| match parse(src) {
+            Ok(_)       => panic!("{:?} should not parse", src),
+            Err(errors) => errors.iter().map(|err| (err.kind, err.raw.to_string(), err.start, err.end)).collect::<Vec<_>>(),
+        };
+
+        assert_eq!(error("(function () {}).#x;"), vec![(ErrorKind::PrivateNameOutsideClass, "#x".to_owned(), 17, 19)]);
+        assert_eq!(error("a?.#x;"), vec![(ErrorKind::PrivateNameOutsideClass, "#x".to_owned(), 3, 5)]);
+        assert_eq!(error("class A { #x; } this.#x;"), vec![(ErrorKind::PrivateNameOutsideClass, "#x".to_owned(), 21, 23)]);
+    }
+
+    #[test]
+    fn optional_call_expression() {
+        let src = "foo?.(1)";
+        let mock = Mock::new();
+
+        let expected = CallExpression {
+            callee: mock.ptr("foo"),
+            arguments: mock.list([
+                Literal::Number(NumberLiteral::new("1")),
+            ]),
+            optional: true,
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn optional_chain_is_not_assignable() {
+        assert!(parse("foo?.bar = 1;").is_err());
+        assert!(parse("foo?.bar.baz = 1;").is_err());
+        assert!(parse("foo?.[bar] = 1;").is_err());
+        assert!(parse("foo[bar] = 1; foo.bar[baz] += 1;").is_ok());
+        assert!(parse("foo ? .5 : 1;").is_ok());
+    }
+
+    #[test]
+    fn parenthesized_optional_chain() {
+        let src = "(foo?.bar).baz";
+        let mock = Mock::new();
+
+        let expected = MemberExpression {
+            object: mock.ptr(ChainExpression {
+                expression: mock.ptr(MemberExpression {
+                    object: mock.ptr("foo"),
+                    property: mock.ptr("bar"),
+                    optional: true,
+                }),
+            }),
+            property: mock.ptr("baz"),
+            optional: false,
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn optional_chain_early_errors() {
+        assert!(parse("new (a?.b)(); (a?.b)`c`; (a?.b)?.c; x = (a?.b); new a.b?.c;").is_err());
+        assert!(parse("new (a?.b)(); (a?.b)`c`; (a?.b)?.c; x = (a?.b);").is_ok());
+
+        let error = |src| match parse(src) {
+            Ok(_)       => panic!("{:?} should not parse", src),
+            Err(errors) => errors.iter().map(|err| (err.kind, err.raw.to_string(), err.start, err.end)).collect::<Vec<_>>(),
+        };
+
+        assert_eq!(error("new a?.b();"), vec![(ErrorKind::InvalidOptionalChain, "new".to_owned(), 0, 10)]);
+        assert_eq!(error("a?.b`c`;"), vec![(ErrorKind::InvalidOptionalChain, "`".to_owned(), 0, 4)]);
+        assert_eq!(error("a?.b`c${d}`;"), vec![(ErrorKind::InvalidOptionalChain, "`".to_owned(), 0, 4)]);
+    }
+
     #[test]
     fn keyword_member_expression() {
         let src = "foo.function";
@@ -886,11 +1496,112 @@ mod test {
         let expected = MemberExpression {
             object: mock.ptr("foo"),
             property: mock.ptr("function"),
+            optional: false,
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn keyword_object_keys() {
+        let src = "({ default: 1, var: 2, in: 3, static: 4, yield })";
+        let mock = Mock::new();
+
+        let expected = ObjectExpression {
+            body: mock.list([
+                Property::Literal {
+                    key: mock.ptr(PropertyKey::Literal("default")),
+                    value: mock.number("1"),
+                },
+                Property::Literal {
+                    key: mock.ptr(PropertyKey::Literal("var")),
+                    value: mock.number("2"),
+                },
+                Property::Literal {
+                    key: mock.ptr(PropertyKey::Literal("in")),
+                    value: mock.number("3"),
+                },
+                Property::Literal {
+                    key: mock.ptr(PropertyKey::Literal("static")),
+                    value: mock.number("4"),
+                },
+                Property::Shorthand("yield"),
+            ])
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn computed_object_keys() {
+        let src = "({ [a]: 1, [b + c]() {}, async [d]() {} })";
+        let mock = Mock::new();
+
+        let method = |is_async| mock.ptr(Function {
+            name: EmptyName,
+            generator: false,
+            is_async,
+            params: NodeList::empty(),
+            body: mock.empty_block()
+        });
+
+        let expected = ObjectExpression {
+            body: mock.list([
+                Property::Literal {
+                    key: mock.ptr(PropertyKey::Computed(mock.ptr("a"))),
+                    value: mock.number("1"),
+                },
+                Property::Method {
+                    key: mock.ptr(PropertyKey::Computed(mock.ptr(BinaryExpression {
+                        operator: OperatorKind::Addition,
+                        left: mock.ptr("b"),
+                        right: mock.ptr("c"),
+                    }))),
+                    value: method(false),
+                },
+                Property::Method {
+                    key: mock.ptr(PropertyKey::Computed(mock.ptr("d"))),
+                    value: method(true),
+                },
+            ])
+        };
+
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn reserved_word_shorthand_throws() {
+        assert!(parse("({ class });").is_err());
+        assert!(parse("({ default, a });").is_err());
+    }
+
+    #[test]
+    fn shorthand_default_in_assignment() {
+        let src = "({ a = 1 } = b)";
+        let mock = Mock::new();
+
+        let expected = BinaryExpression {
+            operator: OperatorKind::Assign,
+            left: mock.ptr(ObjectExpression {
+                body: mock.list([
+                    Property::ShorthandDefault {
+                        name: mock.ptr("a"),
+                        value: mock.number("1"),
+                    },
+                ])
+            }),
+            right: mock.ptr("b"),
         };
 
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn shorthand_default_in_literal_throws() {
+        assert!(parse("({ a = 1 });").is_err());
+        assert!(parse("[{ a = 1 }];").is_err());
+    }
+
     #[test]
     fn computed_member_expression() {
         let src = "foo[10]";
@@ -899,6 +1610,7 @@ mod test {
         let expected = ComputedMemberExpression {
             object: mock.ptr("foo"),
             property: mock.number("10"),
+            optional: false,
         };
 
         assert_expr!(src, expected);
@@ -929,6 +1641,21 @@ mod test {
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn array_elements() {
+        let module = parse("[,,1,,]").unwrap();
+
+        let elements: Vec<_> = match module.body().first_element().unwrap().item {
+            Statement::Expression(ref expression) => match expression.item {
+                Expression::Array(ref array) => array.elements().map(|element| element.is_some()).collect(),
+                _ => panic!("Expected an array"),
+            },
+            _ => panic!("Expected an expression statement"),
+        };
+
+        assert_eq!(elements, vec![false, false, true, false]);
+    }
+
     #[test]
     fn array_expression() {
         let src = "[0, 1, 2]";
@@ -936,9 +1663,9 @@ mod test {
 
         let expected = ArrayExpression {
             body: mock.list([
-                Literal::Number("0"),
-                Literal::Number("1"),
-                Literal::Number("2"),
+                Literal::Number(NumberLiteral::new("0")),
+                Literal::Number(NumberLiteral::new("1")),
+                Literal::Number(NumberLiteral::new("2")),
             ])
         };
 
@@ -995,7 +1722,8 @@ mod test {
                 Expression::Spread(SpreadExpression {
                     argument: mock.ptr("c")
                 })
-            ])
+            ]),
+            optional: false,
         };
 
         assert_expr!(src, expected);
@@ -1006,6 +1734,77 @@ mod test {
         assert!(parse("let foo = ...c;").is_err());
     }
 
+    #[test]
+    fn yield_expression() {
+        let src = "function* f() { yield; yield a; yield* b; yield\nc; [yield] }";
+        let mock = Mock::new();
+
+        let yield_expression = |argument, delegate| -> ExpressionNode {
+            mock.ptr(YieldExpression { argument, delegate })
+        };
+
+        let expected = mock.list([
+            Function {
+                name: mock.name("f"),
+                generator: true,
+                is_async: false,
+                params: NodeList::empty(),
+                body: mock.block([
+                    yield_expression(None, false),
+                    yield_expression(Some(mock.ptr("a")), false),
+                    yield_expression(Some(mock.ptr("b")), true),
+                    yield_expression(None, false),
+                    mock.ptr("c"),
+                    mock.ptr(ArrayExpression {
+                        body: mock.list([YieldExpression { argument: None, delegate: false }])
+                    }),
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn await_expression() {
+        let src = "async function f() { await a; await b + c; }";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Function {
+                name: mock.name("f"),
+                generator: false,
+                is_async: true,
+                params: NodeList::empty(),
+                body: mock.block([
+                    mock.ptr(AwaitExpression { argument: mock.ptr("a") }),
+                    mock.ptr(BinaryExpression {
+                        operator: OperatorKind::Addition,
+                        left: mock.ptr(AwaitExpression { argument: mock.ptr("b") }),
+                        right: mock.ptr("c"),
+                    }),
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn await_outside_of_async_functions() {
+        assert_expr!("await", "await");
+        assert!(parse("function f() { await(a); var await; }").is_ok());
+        assert!(parse("async function f() { function g() { await; } }").is_ok());
+        assert!(parse("function f() { await a; }").is_err());
+    }
+
+    #[test]
+    fn yield_outside_of_generators() {
+        assert_expr!("yield", "yield");
+        assert!(parse("function* f() { () => yield }").is_ok());
+        assert!(parse("'use strict'; yield").is_err());
+    }
+
     #[test]
     fn function_expression() {
         let src = "(function () {})";
@@ -1014,6 +1813,7 @@ mod test {
         let expected = Function {
             name: None.into(),
             generator: false,
+            is_async: false,
             params: NodeList::empty(),
             body: mock.empty_block()
         };
@@ -1029,6 +1829,7 @@ mod test {
         let expected = Function {
             name: mock.name("foo"),
             generator: false,
+            is_async: false,
             params: NodeList::empty(),
             body: mock.empty_block()
         };
@@ -1042,18 +1843,68 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            is_async: false,
             params: NodeList::empty(),
             body: ArrowBody::Expression(mock.ptr("bar")),
         };
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn async_arrow_function_expression() {
+        let mock = Mock::new();
+
+        let expected = ArrowExpression {
+            is_async: true,
+            params: mock.list([
+                Pattern::Identifier("a"),
+                Pattern::Identifier("b"),
+            ]),
+            body: ArrowBody::Expression(mock.ptr(AwaitExpression { argument: mock.ptr("a") })),
+        };
+        assert_expr!("async (a, b) => await a", expected);
+
+        let expected = ArrowExpression {
+            is_async: true,
+            params: mock.list([
+                Pattern::Identifier("x")
+            ]),
+            body: ArrowBody::Expression(mock.ptr("x")),
+        };
+        assert_expr!("async x => x", expected);
+    }
+
+    #[test]
+    fn async_calls() {
+        let mock = Mock::new();
+
+        let expected = CallExpression {
+            callee: mock.ptr("async"),
+            arguments: mock.list(["a", "b"]),
+            optional: false,
+        };
+        assert_expr!("async(a, b)", expected);
+
+        let expected = ArrowExpression {
+            is_async: false,
+            params: mock.list([
+                Pattern::Identifier("async")
+            ]),
+            body: ArrowBody::Expression(mock.ptr("async")),
+        };
+        assert_expr!("async => async", expected);
+
+        assert!(parse("async\n(x) => x").is_err());
+        assert!(parse("async x\n=> x").is_err());
+    }
+
     #[test]
     fn arrow_function_shorthand() {
         let src = "n => n * n";
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            is_async: false,
             params: mock.list([
                 Pattern::Identifier("n")
             ]),
@@ -1074,6 +1925,7 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            is_async: false,
             params: mock.list([
                 Pattern::Identifier("a"),
                 Pattern::Identifier("b"),
@@ -1095,6 +1947,7 @@ mod test {
         let mock = Mock::new();
 
         let expected = ArrowExpression {
+            is_async: false,
             params: mock.list([
                 Pattern::Identifier("a"),
                 Pattern::Identifier("b"),
@@ -1108,6 +1961,47 @@ mod test {
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn arrow_function_with_destructuring_params() {
+        let src = "({a, b: c}, [d, , ...e]) => a";
+        let mock = Mock::new();
+
+        let expected = ArrowExpression {
+            is_async: false,
+            params: mock.list([
+                Pattern::ObjectPattern {
+                    properties: mock.list([
+                        Property::Shorthand("a"),
+                        Property::Literal {
+                            key: mock.ptr(PropertyKey::Literal("b")),
+                            value: mock.ptr("c"),
+                        }
+                    ])
+                },
+                Pattern::ArrayPattern {
+                    elements: mock.list([
+                        Pattern::Identifier("d"),
+                        Pattern::Void,
+                        Pattern::RestElement {
+                            argument: mock.ptr("e"),
+                        }
+                    ])
+                }
+            ]),
+            body: ArrowBody::Expression(mock.ptr("a"))
+        };
+        assert_expr!(src, expected);
+    }
+
+    #[test]
+    fn arrow_function_invalid_patterns_throws() {
+        assert!(parse("({a: b.c}) => a").is_err());
+        assert!(parse("([a.b]) => a").is_err());
+        assert!(parse("([...a, b]) => a").is_err());
+        assert!(parse("([...a.b]) => a").is_err());
+        assert!(parse("({a() {}}) => a").is_err());
+    }
+
     #[test]
     fn class_expression() {
         let src = "(class {})";
@@ -1159,13 +2053,13 @@ mod test {
             operator: OperatorKind::LogicalAnd,
             left: mock.ptr(BinaryExpression {
                 operator: OperatorKind::StrictEquality,
-                left: mock.ptr(Literal::True),
-                right: mock.ptr(Literal::True),
+                left: mock.ptr(Literal::Boolean(true)),
+                right: mock.ptr(Literal::Boolean(true)),
             }),
             right: mock.ptr(BinaryExpression {
                 operator: OperatorKind::StrictEquality,
-                left: mock.ptr(Literal::False),
-                right: mock.ptr(Literal::False),
+                left: mock.ptr(Literal::Boolean(false)),
+                right: mock.ptr(Literal::Boolean(false)),
             }),
         };
 
@@ -1180,6 +2074,7 @@ mod test {
         let expected = SequenceExpression {
             body: mock.list([
                 Expression::Arrow(ArrowExpression {
+                    is_async: false,
                     params: NodeList::empty(),
                     body: ArrowBody::Block(mock.ptr(BlockStatement {
                         body: NodeList::empty()
@@ -1281,6 +2176,7 @@ mod test {
         let expected = MemberExpression {
             object: mock.ptr("foo"),
             property: mock.ptr("bar"),
+            optional: false,
         };
 
         assert_expr!(src, expected);
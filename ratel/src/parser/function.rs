@@ -1,8 +1,14 @@
+use std::mem;
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, ANY, B0};
+use parser::{Parser, Parse, B0, B17};
+use parser::validator::Params;
 use lexer::Token::*;
-use ast::{Node, NodeList, EmptyName, OptionalName, MandatoryName, Name};
-use ast::{MethodKind, Pattern, Function, Class, ClassMember, PropertyKey};
+use lexer::Asi;
+use ast::{Node, NodeList, Expression, ExpressionNode, OperatorKind, EmptyName, OptionalName, MandatoryName, Name};
+use ast::{MethodKind, Pattern, Function, Class, ClassMember, PropertyKey, Statement};
+use ast::expression::PrefixExpression;
+use module::Features;
+use error::ErrorKind;
 
 impl<'ast> Parse<'ast> for EmptyName {
     type Output = Self;
@@ -66,21 +72,7 @@ impl<'ast, N> Parse<'ast> for Function<'ast, N> where
 
     #[inline]
     fn parse(par: &mut Parser<'ast>) -> Self::Output {
-        let generator: bool = if par.lexer.token == OperatorMultiplication {
-            par.lexer.consume();
-            true
-        } else {
-            false
-        };
-
-        let name = N::parse(par);
-
-        Function {
-            name,
-            generator,
-            params: par.params(),
-            body: par.block(),
-        }
+        par.function(Params::Function, false)
     }
 }
 
@@ -105,87 +97,130 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
     fn parse(par: &mut Parser<'ast>) -> Self::Output {
         let start = par.lexer.start();
 
-        let is_static = match par.lexer.token {
-            Static => {
-                par.lexer.consume();
-                true
+        let mut is_static = false;
+        let mut static_end = 0;
+
+        if par.lexer.token == Static {
+            static_end = par.lexer.end_then_consume();
+            is_static = true;
+
+            if par.lexer.token == BraceOpen {
+                return par.static_block(start);
             }
-            _ => false
-        };
+        }
 
         let mut kind = MethodKind::Method;
-
-        let token_start = par.lexer.start();
-        let token_end;
+        let mut is_async = false;
 
         let key = match par.lexer.token {
-            _ if par.lexer.token.is_word() => {
-                let mut label = par.lexer.token_as_str();
-                token_end = par.lexer.end_then_consume();
-
-                if par.lexer.token.is_word() {
-                    kind = match label {
-                        "get" => MethodKind::Get,
-                        "set" => MethodKind::Set,
-                        _     => return par.error()
-                    };
-                    label = par.lexer.token_as_str();
-                    par.lexer.consume();
-                } else if !is_static && label == "constructor" {
-                    kind = MethodKind::Constructor;
-                }
+            // Not a modifier, but a member named `static`
+            ParenOpen | OperatorAssign | Semicolon | BraceClose if is_static => {
+                is_static = false;
 
-                PropertyKey::Literal(label)
-            },
-            LiteralNumber => {
-                let num = par.lexer.token_as_str();
-                token_end = par.lexer.end_then_consume();
-                PropertyKey::Literal(num)
-            },
-            LiteralBinary => {
-                let num = par.lexer.token_as_str();
-                token_end = par.lexer.end_then_consume();
-                PropertyKey::Binary(num)
+                par.alloc_at_loc(start, static_end, PropertyKey::Literal("static"))
             },
-            BracketOpen => {
+            _ if par.lexer.token.is_word() => {
+                let (token_start, token_end) = par.lexer.loc();
+                let label = par.lexer.token_as_str();
+
                 par.lexer.consume();
 
-                let expression = par.expression::<ANY>();
-                token_end = par.lexer.end();
+                let is_key = match par.lexer.token {
+                    BracketOpen       |
+                    LiteralString     |
+                    LiteralNumber     |
+                    LiteralBinary     |
+                    PrivateIdentifier => true,
+                    _ => par.lexer.token.is_word(),
+                };
+
+                let modifier = match label {
+                    "get" if is_key => Some((MethodKind::Get, false)),
+                    "set" if is_key => Some((MethodKind::Set, false)),
+                    // Otherwise a field named `async` followed by another member
+                    "async" if is_key && par.asi() == Asi::NoSemicolon => Some((MethodKind::Method, true)),
+                    _ => None,
+                };
+
+                if let Some((modifier_kind, modifier_async)) = modifier {
+                    kind = modifier_kind;
+                    is_async = modifier_async;
+
+                    let key = par.class_member_key();
+
+                    // The constructor can be neither an accessor nor `async`
+                    if !is_static && is_constructor_key(key.item) {
+                        return par.error();
+                    }
 
-                expect!(par, BracketClose);
+                    key
+                } else {
+                    if !is_static && label == "constructor" {
+                        kind = MethodKind::Constructor;
+                    }
 
-                PropertyKey::Computed(expression)
+                    par.alloc_at_loc(token_start, token_end, PropertyKey::Literal(label))
+                }
             },
-            _ => return par.error()
+            _ => {
+                let key = par.class_member_key();
+
+                if !is_static && is_constructor_key(key.item) {
+                    kind = MethodKind::Constructor;
+                }
+
+                key
+            }
         };
 
-        let key = par.alloc_at_loc(token_start, token_end, key);
         let end;
         let member = match par.lexer.token {
             ParenOpen => {
-                let value = Node::parse(par);
+                let super_call = kind == MethodKind::Constructor && par.derived;
+                let value = par.method(is_async, super_call);
 
                 end = value.end;
 
                 ClassMember::Method {
-                    is_static: is_static,
+                    is_static,
                     key,
                     kind,
                     value,
                 }
             },
-            OperatorAssign => {
-                par.lexer.consume();
+            // Accessors, `async` methods and the constructor can't be fields
+            _ if kind == MethodKind::Method && !is_async => {
+                let value = match par.lexer.token {
+                    OperatorAssign => {
+                        par.lexer.consume();
+
+                        let super_property = mem::replace(&mut par.super_property, true);
+                        let super_call = mem::replace(&mut par.super_call, false);
+                        let value = par.expression::<B0>();
+
+                        par.super_property = super_property;
+                        par.super_call = super_call;
+
+                        Some(value)
+                    },
+                    _ => None,
+                };
+
+                end = match value {
+                    Some(value) => value.end,
+                    None        => key.end,
+                };
 
-                let expression = par.expression::<B0>();
+                if par.asi() == Asi::NoSemicolon {
+                    return par.error();
+                }
 
-                end = expression.end;
+                par.features.insert(Features::CLASS_FIELDS);
 
-                ClassMember::Literal {
+                ClassMember::Property {
                     is_static,
                     key,
-                    value: expression,
+                    value,
                 }
             },
             _ => return par.error(),
@@ -199,6 +234,95 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
     }
 }
 
+/// Whether the `key` of a non-static method makes it the `constructor`,
+/// which can be spelled as a string literal as well.
+#[inline]
+fn is_constructor_key(key: PropertyKey) -> bool {
+    match key {
+        PropertyKey::Literal(name) => {
+            name == "constructor" || name == "'constructor'" || name == "\"constructor\""
+        },
+        _ => false,
+    }
+}
+
+impl<'ast> Parser<'ast> {
+    /// Parse a static initialization block starting at `start`, after the
+    /// `static` keyword.
+    #[inline]
+    fn static_block(&mut self, start: u32) -> Node<'ast, ClassMember<'ast>> {
+        // Nor `yield` or `await` of the enclosing function apply in the block
+        let generator = mem::replace(&mut self.generator, false);
+        let is_async = mem::replace(&mut self.is_async, false);
+        let async_arguments = mem::replace(&mut self.async_arguments, false);
+        let super_property = mem::replace(&mut self.super_property, true);
+        let super_call = mem::replace(&mut self.super_call, false);
+
+        self.features.insert(Features::STATIC_BLOCKS);
+        self.lexer.consume();
+
+        let block = self.raw_block::<Statement>();
+        let end = self.lexer.end_then_consume();
+
+        self.generator = generator;
+        self.is_async = is_async;
+        self.async_arguments = async_arguments;
+        self.super_property = super_property;
+        self.super_call = super_call;
+
+        self.alloc_at_loc(start, end, ClassMember::StaticBlock { body: block.body })
+    }
+
+    /// Parse the key of a class member after its modifiers.
+    #[inline]
+    fn class_member_key(&mut self) -> Node<'ast, PropertyKey<'ast>> {
+        match self.lexer.token {
+            _ if self.lexer.token.is_word() => {
+                let key = self.alloc_in_loc(PropertyKey::Literal(self.lexer.token_as_str()));
+
+                self.lexer.consume();
+
+                key
+            },
+            LiteralString |
+            LiteralNumber => {
+                self.validate_octal();
+
+                let key = self.alloc_in_loc(PropertyKey::Literal(self.lexer.token_as_str()));
+
+                self.lexer.consume();
+
+                key
+            },
+            LiteralBinary => {
+                let key = self.alloc_in_loc(PropertyKey::Binary(self.lexer.token_as_str()));
+
+                self.lexer.consume();
+
+                key
+            },
+            PrivateIdentifier => {
+                let name = &self.lexer.token_as_str()[1..];
+
+                // `#constructor` is reserved
+                if name == "constructor" {
+                    return self.error();
+                }
+
+                self.features.insert(Features::PRIVATE_MEMBERS);
+
+                let key = self.alloc_in_loc(PropertyKey::Private(name));
+
+                self.lexer.consume();
+
+                key
+            },
+            BracketOpen => self.computed_key(),
+            _ => self.error()
+        }
+    }
+}
+
 impl<'ast, N> Parse<'ast> for Class<'ast, N> where
     N: Name<'ast> + Parse<'ast, Output = N>,
 {
@@ -206,26 +330,142 @@ impl<'ast, N> Parse<'ast> for Class<'ast, N> where
 
     #[inline]
     fn parse(par: &mut Parser<'ast>) -> Self::Output {
+        par.features.insert(Features::CLASSES);
+
+        // All parts of a class are strict mode code
+        let strict = par.strict;
+        par.strict = true;
+
         let name = N::parse(par);
 
         let super_class = match par.lexer.token {
             Extends => {
                 par.lexer.consume();
 
-                Some(par.expression::<B0>())
+                Some(par.class_heritage())
             },
             _ => None
         };
 
+        par.classes += 1;
+
+        let derived = mem::replace(&mut par.derived, super_class.is_some());
+        let body = par.block();
+
+        par.classes -= 1;
+        par.derived = derived;
+        par.strict = strict;
+
         Class {
             name: name.into(),
             extends: super_class,
-            body: par.block(),
+            body,
         }
     }
 }
 
 impl<'ast> Parser<'ast> {
+    /// Parse the expression after `extends`, which has to be a left-hand
+    /// side expression unless it's parenthesized, as in `(a ? B : C)`.
+    #[inline]
+    fn class_heritage(&mut self) -> ExpressionNode<'ast> {
+        let token = self.lexer.token;
+        let raw = self.lexer.token_as_str();
+        let (start, end) = self.lexer.loc();
+        let heritage = self.expression::<B17>();
+
+        let is_new = matches!(heritage.item, Expression::Prefix(PrefixExpression { operator: OperatorKind::New, .. }));
+
+        if token != ParenOpen && heritage.binding_power() < 17 && !is_new {
+            self.error_at(ErrorKind::UnexpectedToken, token, raw, start, end);
+        }
+
+        heritage
+    }
+
+    /// Parse a function after the `function` keyword, or a method after
+    /// its key, `async` being read by the caller.
+    #[inline]
+    pub fn function<N>(&mut self, kind: Params, is_async: bool) -> Function<'ast, N> where
+        N: Name<'ast> + Parse<'ast, Output = N>,
+    {
+        if is_async {
+            self.features.insert(Features::ASYNC_FUNCTIONS);
+        }
+
+        let generator: bool = if self.lexer.token == OperatorMultiplication {
+            self.lexer.consume();
+            self.features.insert(Features::GENERATORS);
+            true
+        } else {
+            false
+        };
+
+        let name = N::parse(self);
+        let strict = self.strict;
+        let outer_generator = self.generator;
+        let outer_async = self.is_async;
+        let outer_suspensions = mem::take(&mut self.suspensions);
+        let outer_async_arguments = mem::replace(&mut self.async_arguments, false);
+        let outer_super_property = self.super_property;
+        let outer_super_call = self.super_call;
+        let blocks = self.blocks;
+
+        // Functions have no `super`, `method` has set whether it can be called
+        match kind {
+            Params::Function => {
+                self.super_property = false;
+                self.super_call = false;
+            },
+            Params::Method => self.super_property = true,
+            Params::Arrow => {},
+        }
+
+        // Parameters are parsed in the context of the function, so that
+        // `yield` in those of a generator is reported rather than being
+        // taken for an identifier
+        self.blocks = 0;
+        self.generator = generator;
+        self.is_async = is_async;
+
+        let params = self.params();
+
+        self.validate_suspensions(0);
+
+        let body = self.function_body();
+
+        self.validate_params(params, kind, generator, is_async);
+        self.strict = strict;
+        self.generator = outer_generator;
+        self.is_async = outer_async;
+        self.suspensions = outer_suspensions;
+        self.async_arguments = outer_async_arguments;
+        self.super_property = outer_super_property;
+        self.super_call = outer_super_call;
+        self.blocks = blocks;
+
+        Function {
+            name,
+            generator,
+            is_async,
+            params,
+            body,
+        }
+    }
+
+    /// Parse a method after its key, `super_call` telling whether it's the
+    /// constructor of a class with `extends`.
+    #[inline]
+    pub fn method(&mut self, is_async: bool, super_call: bool) -> Node<'ast, Function<'ast, EmptyName>> {
+        let start = self.lexer.start();
+        let outer_super_call = mem::replace(&mut self.super_call, super_call);
+        let function = self.function(Params::Method, is_async);
+
+        self.super_call = outer_super_call;
+
+        self.alloc_at_loc(start, function.body.end, function)
+    }
+
     #[inline]
     fn pattern_void(&mut self) -> Node<'ast, Pattern<'ast>> {
         let loc = self.lexer.start();
@@ -244,6 +484,8 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn pattern_array(&mut self) -> Node<'ast, Pattern<'ast>> {
+        self.features.insert(Features::DESTRUCTURING);
+
         let start = self.lexer.start_then_consume();
         let elements = self.array_elements(Parser::pattern_array_element);
         let end = self.lexer.end_then_consume();
@@ -255,10 +497,15 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn pattern_object(&mut self) -> Node<'ast, Pattern<'ast>> {
+        self.features.insert(Features::DESTRUCTURING);
+
         let start = self.lexer.start_then_consume();
         let properties = self.property_list();
         let end = self.lexer.end_then_consume();
 
+        self.validate_binding_properties(properties);
+        self.resolve_properties(properties);
+
         self.alloc_at_loc(start, end, Pattern::ObjectPattern {
             properties,
         })
@@ -288,16 +535,35 @@ impl<'ast> Parser<'ast> {
             BracketOpen          => self.pattern_array(),
             BraceOpen            => self.pattern_object(),
             Comma | BracketClose => return self.pattern_void(),
+            OperatorSpread       => return self.pattern_rest_element(),
             _                    => self.error()
         };
 
         self.pattern_assign(left)
     }
 
+    /// Rest element of an array pattern, which must be its last element.
+    #[inline]
+    fn pattern_rest_element(&mut self) -> Node<'ast, Pattern<'ast>> {
+        self.features.insert(Features::REST_PARAMETERS);
+
+        let start = self.lexer.start_then_consume();
+        let argument = self.identifier();
+
+        if self.lexer.token != BracketClose {
+            self.error::<()>();
+        }
+
+        self.alloc_at_loc(start, argument.end, Pattern::RestElement {
+            argument
+        })
+    }
+
     #[inline]
     fn pattern_param(&mut self) -> Node<'ast, Pattern<'ast>> {
+        // `yield` is checked by `validate_params` once the body is parsed
         let left = match self.lexer.token {
-            Identifier           => self.pattern_identifier(),
+            Identifier | Yield   => self.pattern_identifier(),
             BracketOpen          => self.pattern_array(),
             BraceOpen            => self.pattern_object(),
             _                    => self.error()
@@ -308,9 +574,11 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn rest_element(&mut self) -> Node<'ast, Pattern<'ast>> {
+        self.features.insert(Features::REST_PARAMETERS);
+
         let start = self.lexer.start_then_consume();
         let argument = match self.lexer.token {
-            Identifier => {
+            Identifier | Yield => {
                 let ident = self.lexer.token_as_str();
                 let ident = self.alloc_in_loc(ident);
 
@@ -386,10 +654,11 @@ impl<'ast> Parser<'ast> {
 mod test {
     use super::*;
     use parser::parse;
-    use parser::mock::Mock;
+    use testing::Mock;
     use ast::{NodeList, Literal, Expression, Function, Class};
-    use ast::{ClassMember, Pattern};
+    use ast::{ClassMember, Pattern, Property};
     use ast::statement::*;
+    use ast::expression::{CallExpression, MemberExpression, ConditionalExpression};
 
     #[test]
     fn function_empty() {
@@ -400,6 +669,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: NodeList::empty(),
                 body: mock.empty_block(),
             }
@@ -418,6 +688,7 @@ mod test {
                 Function {
                     name: mock.name("foo"),
                     generator: true,
+                    is_async: false,
                     params: NodeList::empty(),
                     body: mock.empty_block(),
                 }
@@ -434,6 +705,7 @@ mod test {
                 Function {
                     name: mock.name("foo"),
                     generator: true,
+                    is_async: false,
                     params: NodeList::empty(),
                     body: mock.empty_block(),
                 }
@@ -450,6 +722,7 @@ mod test {
                 Function {
                     name: mock.name("foo"),
                     generator: true,
+                    is_async: false,
                     params: NodeList::empty(),
                     body: mock.empty_block(),
                 }
@@ -459,6 +732,62 @@ mod test {
         }
     }
 
+    #[test]
+    fn async_function() {
+        let src = "async function foo() {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Function {
+                name: mock.name("foo"),
+                generator: false,
+                is_async: true,
+                params: NodeList::empty(),
+                body: mock.empty_block(),
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn async_function_expression() {
+        let src = "(async function () {})";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(Expression::Function(Function {
+                name: None.into(),
+                generator: false,
+                is_async: true,
+                params: NodeList::empty(),
+                body: mock.empty_block(),
+            })))
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn async_as_identifier() {
+        let src = "async\nfunction foo() {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Expression(mock.ptr(Expression::Identifier("async"))),
+            Statement::Function(Function {
+                name: mock.name("foo"),
+                generator: false,
+                is_async: false,
+                params: NodeList::empty(),
+                body: mock.empty_block(),
+            }),
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+        assert!(parse("async(); async = 1; ({ async: 1, async() {} })").is_ok());
+    }
+
     #[test]
     fn function_params() {
         let src = "function foo(bar, baz) {}";
@@ -468,6 +797,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: mock.list([
                     Pattern::Identifier("bar"),
                     Pattern::Identifier("baz"),
@@ -488,6 +818,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: NodeList::empty(),
                 body: mock.block([
                     mock.ptr("bar"),
@@ -508,6 +839,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: mock.list([
                     Pattern::AssignmentPattern {
                         left: mock.ptr(Pattern::Identifier("a")),
@@ -541,6 +873,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: mock.list([
                     Pattern::Identifier("a"),
                     Pattern::Identifier("b"),
@@ -569,6 +902,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: mock.list([
                     Pattern::RestElement {
                         argument: mock.ptr("rest"),
@@ -589,6 +923,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: mock.list([
                     Pattern::Identifier("a"),
                     Pattern::AssignmentPattern {
@@ -610,6 +945,49 @@ mod test {
         assert!(parse("function foo(...rest, a) {}").is_err());
     }
 
+    #[test]
+    fn function_with_destructuring_params() {
+        let src = "function foo({a, b = 1}, [c, ...d]) {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Function {
+                name: mock.name("foo"),
+                generator: false,
+                is_async: false,
+                params: mock.list([
+                    Pattern::ObjectPattern {
+                        properties: mock.list([
+                            Property::Shorthand("a"),
+                            Property::ShorthandDefault {
+                                name: mock.ptr("b"),
+                                value: mock.number("1"),
+                            }
+                        ])
+                    },
+                    Pattern::ArrayPattern {
+                        elements: mock.list([
+                            Pattern::Identifier("c"),
+                            Pattern::RestElement {
+                                argument: mock.ptr("d"),
+                            }
+                        ])
+                    }
+                ]),
+                body: mock.empty_block()
+            }
+        ]);
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn function_with_invalid_patterns() {
+        assert!(parse("function foo({a: 1}) {}").is_err());
+        assert!(parse("function foo({a: {b: c.d}}) {}").is_err());
+        assert!(parse("function foo([...a, b]) {}").is_err());
+        assert!(parse("function foo([...a,]) {}").is_err());
+    }
+
     #[test]
     fn class_empty() {
         let src = "class Foo {}";
@@ -667,6 +1045,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: mock.list([
                                 Pattern::Identifier("bar"),
                                 Pattern::Identifier("baz")
@@ -716,6 +1095,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: mock.list([
                                 Pattern::Identifier("bar"),
                                 Pattern::Identifier("baz")
@@ -732,6 +1112,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: mock.list([
                                 Pattern::Identifier("moon")
                             ]),
@@ -747,6 +1128,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: NodeList::empty(),
                             body: mock.empty_block()
                         })
@@ -758,6 +1140,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: NodeList::empty(),
                             body: mock.empty_block()
                         })
@@ -769,6 +1152,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: NodeList::empty(),
                             body: mock.empty_block()
                         })
@@ -799,25 +1183,25 @@ mod test {
                 name: mock.name("Foo"),
                 extends: None,
                 body: mock.block([
-                    ClassMember::Literal {
+                    ClassMember::Property {
                         is_static: false,
                         key: mock.ptr(PropertyKey::Literal("doge")),
-                        value: mock.number("10")
+                        value: Some(mock.number("10"))
                     },
-                    ClassMember::Literal {
+                    ClassMember::Property {
                         is_static: false,
                         key: mock.ptr(PropertyKey::Literal("to")),
-                        value: mock.number("20")
+                        value: Some(mock.number("20"))
                     },
-                    ClassMember::Literal {
+                    ClassMember::Property {
                         is_static: false,
                         key: mock.ptr(PropertyKey::Literal("the")),
-                        value: mock.number("30")
+                        value: Some(mock.number("30"))
                     },
-                    ClassMember::Literal {
+                    ClassMember::Property {
                         is_static: true,
                         key: mock.ptr(PropertyKey::Literal("moon")),
-                        value: mock.number("42")
+                        value: Some(mock.number("42"))
                     },
                 ])
             }
@@ -826,6 +1210,81 @@ mod test {
         assert_eq!(parse(src).unwrap().body(), expected);
     }
 
+    #[test]
+    fn class_fields() {
+        let src = "class Foo { a; static b\n c = 1\n async\n get; 'd' }";
+        let mock = Mock::new();
+
+        let field = |is_static, key, value| ClassMember::Property {
+            is_static,
+            key: mock.ptr(PropertyKey::Literal(key)),
+            value,
+        };
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([
+                    field(false, "a", None),
+                    field(true, "b", None),
+                    field(false, "c", Some(mock.number("1"))),
+                    field(false, "async", None),
+                    field(false, "get", None),
+                    field(false, "'d'", None),
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn class_fields_invalid() {
+        assert!(parse("class Foo { a b }").is_err());
+        assert!(parse("class Foo { a = 1 b = 2 }").is_err());
+        assert!(parse("class Foo { get a; }").is_err());
+        assert!(parse("class Foo { async a; }").is_err());
+        assert!(parse("class Foo { constructor = 1; }").is_err());
+    }
+
+    #[test]
+    fn class_keyword_members() {
+        let src = "class Foo { static delete() {} static() {} static = 1; var() {} }";
+        let mock = Mock::new();
+
+        let method = |is_static, key| ClassMember::Method {
+            is_static,
+            key: mock.ptr(PropertyKey::Literal(key)),
+            kind: MethodKind::Method,
+            value: mock.ptr(Function {
+                name: EmptyName,
+                generator: false,
+                is_async: false,
+                params: NodeList::empty(),
+                body: mock.empty_block()
+            })
+        };
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([
+                    method(true, "delete"),
+                    method(false, "static"),
+                    ClassMember::Property {
+                        is_static: false,
+                        key: mock.ptr(PropertyKey::Literal("static")),
+                        value: Some(mock.number("1"))
+                    },
+                    method(false, "var"),
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
 
     #[test]
     fn class_extends_null() {
@@ -843,6 +1302,61 @@ mod test {
         assert_eq!(parse(src).unwrap().body(), expected);
     }
 
+    #[test]
+    fn class_extends_left_hand_side() {
+        let src = "class Foo extends bar.baz(qux) {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: Some(mock.ptr(CallExpression {
+                    callee: mock.ptr(MemberExpression {
+                        object: mock.ptr("bar"),
+                        property: mock.ptr("baz"),
+                        optional: false,
+                    }),
+                    arguments: mock.list(["qux"]),
+                    optional: false,
+                })),
+                body: mock.empty_block()
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+        assert!(parse("class Foo extends new Bar() {}").is_ok());
+        assert!(parse("class Foo extends bar`baz`[0] {}").is_ok());
+    }
+
+    #[test]
+    fn class_extends_parenthesized() {
+        let src = "class Foo extends (a ? B : C) {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: Some(mock.ptr(ConditionalExpression {
+                    test: mock.ptr("a"),
+                    consequent: mock.ptr("B"),
+                    alternate: mock.ptr("C"),
+                })),
+                body: mock.empty_block()
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn class_extends_invalid() {
+        assert!(parse("class Foo extends a ? B : C {}").is_err());
+        assert!(parse("class Foo extends a = B {}").is_err());
+        assert!(parse("class Foo extends a + B {}").is_err());
+        assert!(parse("class Foo extends !B {}").is_err());
+        assert!(parse("class Foo extends B++ {}").is_err());
+    }
+
     #[test]
     fn class_methods() {
         let src = r#"
@@ -867,6 +1381,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: mock.list([
                                 Pattern::Identifier("foo")
                             ]),
@@ -880,6 +1395,7 @@ mod test {
                         value: mock.ptr(Function {
                             name: EmptyName,
                             generator: false,
+                            is_async: false,
                             params: mock.list([
                                 Pattern::Identifier("bar")
                             ]),
@@ -892,4 +1408,193 @@ mod test {
 
         assert_eq!(parse(src).unwrap().body(), expected);
     }
+
+    #[test]
+    fn async_methods() {
+        let src = "class Foo { async foo() {} static async bar() {} }";
+        let mock = Mock::new();
+
+        let method = |is_static, key| ClassMember::Method {
+            is_static,
+            key: mock.ptr(PropertyKey::Literal(key)),
+            kind: MethodKind::Method,
+            value: mock.ptr(Function {
+                name: EmptyName,
+                generator: false,
+                is_async: true,
+                params: NodeList::empty(),
+                body: mock.empty_block()
+            })
+        };
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([method(false, "foo"), method(true, "bar")])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn computed_methods() {
+        let src = "class Foo { [a]() {} static [b]() {} get [c]() {} set [d](v) {} async [e]() {} }";
+        let mock = Mock::new();
+
+        let method = |is_static, key, kind, is_async, params| ClassMember::Method {
+            is_static,
+            key: mock.ptr(PropertyKey::Computed(mock.ptr(key))),
+            kind,
+            value: mock.ptr(Function {
+                name: EmptyName,
+                generator: false,
+                is_async,
+                params,
+                body: mock.empty_block()
+            })
+        };
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([
+                    method(false, "a", MethodKind::Method, false, NodeList::empty()),
+                    method(true, "b", MethodKind::Method, false, NodeList::empty()),
+                    method(false, "c", MethodKind::Get, false, NodeList::empty()),
+                    method(false, "d", MethodKind::Set, false, mock.list([Pattern::Identifier("v")])),
+                    method(false, "e", MethodKind::Method, true, NodeList::empty()),
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn class_method_kinds() {
+        let src = "class Foo { constructor() {} 'constructor'() {} static constructor() {} static get a() {} static set b(v) {} get 'c'() {} set 1(v) {} }";
+        let mock = Mock::new();
+
+        let method = |is_static, key, kind, params| ClassMember::Method {
+            is_static,
+            key: mock.ptr(PropertyKey::Literal(key)),
+            kind,
+            value: mock.ptr(Function {
+                name: EmptyName,
+                generator: false,
+                is_async: false,
+                params,
+                body: mock.empty_block()
+            })
+        };
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([
+                    method(false, "constructor", MethodKind::Constructor, NodeList::empty()),
+                    method(false, "'constructor'", MethodKind::Constructor, NodeList::empty()),
+                    method(true, "constructor", MethodKind::Method, NodeList::empty()),
+                    method(true, "a", MethodKind::Get, NodeList::empty()),
+                    method(true, "b", MethodKind::Set, mock.list([Pattern::Identifier("v")])),
+                    method(false, "'c'", MethodKind::Get, NodeList::empty()),
+                    method(false, "1", MethodKind::Set, mock.list([Pattern::Identifier("v")])),
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn class_constructor_invalid() {
+        assert!(parse("class Foo { get constructor() {} }").is_err());
+        assert!(parse("class Foo { set 'constructor'(v) {} }").is_err());
+        assert!(parse("class Foo { async constructor() {} }").is_err());
+        assert!(parse("class Foo { static get constructor() {} }").is_ok());
+    }
+
+    #[test]
+    fn class_private_members() {
+        let src = "class Foo { #a = 1; static #b; get #c() {} }";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([
+                    ClassMember::Property {
+                        is_static: false,
+                        key: mock.ptr(PropertyKey::Private("a")),
+                        value: Some(mock.number("1")),
+                    },
+                    ClassMember::Property {
+                        is_static: true,
+                        key: mock.ptr(PropertyKey::Private("b")),
+                        value: None,
+                    },
+                    ClassMember::Method {
+                        is_static: false,
+                        key: mock.ptr(PropertyKey::Private("c")),
+                        kind: MethodKind::Get,
+                        value: mock.ptr(Function {
+                            name: EmptyName,
+                            generator: false,
+                            is_async: false,
+                            params: NodeList::empty(),
+                            body: mock.empty_block()
+                        })
+                    },
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn class_static_blocks() {
+        let src = "class Foo { static {} static { a; b; } static = 1; }";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: None,
+                body: mock.block([
+                    ClassMember::StaticBlock {
+                        body: NodeList::empty(),
+                    },
+                    ClassMember::StaticBlock {
+                        body: mock.list([mock.ptr::<Expression, _>("a"), mock.ptr("b")]),
+                    },
+                    ClassMember::Property {
+                        is_static: false,
+                        key: mock.ptr(PropertyKey::Literal("static")),
+                        value: Some(mock.number("1")),
+                    },
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn class_static_blocks_invalid() {
+        assert!(parse("class Foo { static static {} }").is_err());
+        assert!(parse("function* f() { class Foo { static { yield 1; } } }").is_err());
+    }
+
+    #[test]
+    fn class_private_members_invalid() {
+        assert!(parse("class Foo { #constructor() {} }").is_err());
+        assert!(parse("class Foo { # a; }").is_err());
+        assert!(parse("class Foo { #1; }").is_err());
+    }
 }
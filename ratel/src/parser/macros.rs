@@ -23,6 +23,15 @@ macro_rules! expect {
     }
 }
 
+/// Record a parser decision, compiles to nothing without the `trace` feature.
+#[macro_export]
+macro_rules! trace {
+    ($parser:ident, $method:ident $( , $arg:expr )*) => {
+        #[cfg(feature = "trace")]
+        $parser.$method($( $arg ),*);
+    }
+}
+
 #[macro_export]
 macro_rules! parameter_key {
     ($parser:ident) => {
@@ -45,7 +54,8 @@ macro_rules! parameter_key {
 #[macro_export]
 macro_rules! assert_expr {
     ($src:expr, $expr:expr) => ({
-        let mut body = parse($src).unwrap().body().iter();
+        let module = parse($src).unwrap();
+        let mut body = module.body().iter();
 
         match body.next().map(|s| s.item).unwrap() {
             Statement::Expression(ref expression) => assert_eq!(expression.item, Expression::from($expr)),
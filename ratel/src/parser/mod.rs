@@ -5,20 +5,41 @@ mod expression;
 mod statement;
 mod function;
 mod nested;
+mod validator;
+mod relaxed;
+pub mod plugin;
+#[cfg(feature = "trace")]
+pub mod trace;
+
+pub use self::relaxed::parse_relaxed;
+#[cfg(feature = "json")]
+pub use self::relaxed::parse_relaxed_json;
+
+use allocated::*;
+use std::borrow::Cow;
+#[cfg(any(test, feature = "std"))]
+use std::collections::HashMap as LiteralMap;
+#[cfg(not(any(test, feature = "std")))]
+use std::collections::BTreeMap as LiteralMap;
 
 use toolshed::list::ListBuilder;
 use toolshed::Arena;
-use error::Error;
-use module::Module;
+use error::{Error, ErrorKind};
+use diagnostic::{Diagnostic, DiagnosticsSink};
+use observer::{ParserObserver, Phase, ParseStats};
+use module::{Module, Features, LineColumn};
+use quota::{Budget, Quota, QuotaExceeded};
 
 use self::error::ToError;
 use self::nested::*;
+use self::plugin::Plugins;
 
 use ast::{Loc, Node, Statement, NodeList, Block, BlockNode};
 use ast::{Expression, ExpressionNode, ExpressionList, IdentifierNode};
-use ast::{OperatorKind, Pattern};
-use ast::expression::BinaryExpression;
-use lexer::{Lexer, Asi};
+use ast::{OperatorKind, Pattern, Terminator};
+use ast::{Property, decode_string};
+use ast::expression::{BinaryExpression, ObjectExpression, ArrayExpression, SpreadExpression};
+use lexer::{Lexer, Token, Asi};
 use lexer::Token::*;
 
 pub trait Parse<'ast> {
@@ -27,37 +48,325 @@ pub trait Parse<'ast> {
     fn parse(&mut Parser<'ast>) -> Self::Output;
 }
 
+/// Settings for `parse_with_options`, `Default` matches plain `parse`.
+/// New options may be added in minor releases, so outside of ratel the
+/// options are made with `ParserOptions::default()` and the `with_`
+/// methods rather than a struct expression.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub struct ParserOptions {
+    /// Maximum length in bytes of a string literal, quotes included, or of
+    /// a template string part. Lexing stops with an
+    /// `ErrorKind::LiteralTooLong` error as soon as a literal is longer,
+    /// the rest of the source is left unparsed.
+    pub max_literal_length: Option<usize>,
+
+    /// Make identical string literals and template strings share a single
+    /// slice, so that they can be compared by pointer.
+    pub intern_literals: bool,
+
+    /// Record how statements were terminated, see `Module::terminator`.
+    pub terminators: bool,
+
+    /// Record the line and column at which statements start, see
+    /// `Module::position`.
+    pub positions: bool,
+
+    /// Treat the whole source as strict mode code, as if it started
+    /// with a `"use strict"` directive.
+    pub strict: bool,
+
+    /// Limits on the size of the AST, see `ratel::quota`. Parsing stops
+    /// with an `ErrorKind::QuotaExceeded` error at the first node over
+    /// the limits, which also apply to transforms of the `Module`.
+    pub quota: Quota,
+
+    /// Extensions of the grammar, see `ratel::plugin`
+    pub plugins: Plugins,
+}
+
+impl ParserOptions {
+    /// Set `max_literal_length`.
+    #[inline]
+    pub fn with_max_literal_length(mut self, max_literal_length: Option<usize>) -> Self {
+        self.max_literal_length = max_literal_length;
+        self
+    }
+
+    /// Set `intern_literals`.
+    #[inline]
+    pub fn with_intern_literals(mut self, intern_literals: bool) -> Self {
+        self.intern_literals = intern_literals;
+        self
+    }
+
+    /// Set `terminators`.
+    #[inline]
+    pub fn with_terminators(mut self, terminators: bool) -> Self {
+        self.terminators = terminators;
+        self
+    }
+
+    /// Set `positions`.
+    #[inline]
+    pub fn with_positions(mut self, positions: bool) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    /// Set `strict`.
+    #[inline]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set `quota`.
+    #[inline]
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Set `plugins`.
+    #[inline]
+    pub fn with_plugins(mut self, plugins: Plugins) -> Self {
+        self.plugins = plugins;
+        self
+    }
+}
+
 pub struct Parser<'ast> {
     arena: &'ast Arena,
 
+    options: ParserOptions,
+
+    /// Slices of literals seen so far, if interning
+    literals: LiteralMap<&'ast str, &'ast str>,
+
+    /// Terminators of statements, if recording
+    terminators: Vec<(u32, u32, Terminator)>,
+
+    /// Starts of statements and their line and column, if recording
+    positions: Vec<(u32, LineColumn)>,
+
+    /// Offset and line and column of the last position recorded, from
+    /// which the next ones are counted
+    position_cursor: (u32, LineColumn),
+
+    /// Syntax features encountered so far
+    features: Features,
+
+    /// Whether the code being parsed is strict mode code
+    strict: bool,
+
+    /// Whether the code being parsed is the body of a generator, where
+    /// `yield` is an expression
+    generator: bool,
+
+    /// Whether the code being parsed is the body of an async function,
+    /// where `await` is an expression
+    is_async: bool,
+
+    /// Whether the code being parsed is in the arguments of an `async` call
+    /// outside of async functions, which may turn out to be the parameters
+    /// of an async arrow function, see `await_operand`
+    async_arguments: bool,
+
+    /// Nesting level of blocks within the current function
+    blocks: u32,
+
+    /// Function declarations in blocks of sloppy mode code
+    block_functions: Vec<(u32, u32)>,
+
+    /// Nesting level of class bodies, outside of which private names
+    /// such as `#a` can't be used
+    classes: u32,
+
+    /// Whether the class body being parsed has `extends`
+    derived: bool,
+
+    /// Whether the code being parsed is in a method, a field initializer
+    /// or a static block, where properties of `super` can be used
+    super_property: bool,
+
+    /// Whether the code being parsed is in the constructor of a class with
+    /// `extends`, where `super` can be called
+    super_call: bool,
+
+    /// Shorthand properties with an initializer in object literals not
+    /// yet known to be assignment targets, see `Property::ShorthandDefault`
+    cover_initializers: Vec<(&'ast str, u32, u32)>,
+
+    /// `yield` and `await` expressions of the current function, checked
+    /// once known to be in parameters, see `validate_suspensions`
+    suspensions: Vec<(&'static str, u32, u32)>,
+
     /// Lexer will produce tokens from the source
     lexer: Lexer<'ast>,
 
     /// Errors occurred during parsing
     errors: Vec<Error>,
 
+    /// Code accepted in sloppy mode only, see `Module::warnings`
+    warnings: Vec<Diagnostic>,
+
     /// AST under construction
     body: NodeList<'ast, Statement<'ast>>,
+
+    /// Allocations so far against `ParserOptions::quota`
+    budget: Budget,
+
+    /// Whether the quota or the `max_literal_length` was exceeded, once it
+    /// is the rest of the source is skipped and no more errors are reported
+    exhausted: bool,
+
+    /// Decisions made so far
+    #[cfg(feature = "trace")]
+    tracer: self::trace::Tracer,
 }
 
 impl<'ast> Parser<'ast> {
-    pub fn new(source: &str, arena: &'ast Arena) -> Self {
-        Parser {
+    pub fn new(source: &str, arena: &'ast Arena, options: ParserOptions) -> Self {
+        let mut parser = Parser {
             arena,
-            lexer: Lexer::new(arena, source),
+            options,
+            literals: LiteralMap::new(),
+            terminators: Vec::new(),
+            positions: Vec::new(),
+            position_cursor: (0, LineColumn::default()),
+            features: Features::empty(),
+            strict: options.strict,
+            generator: false,
+            is_async: false,
+            async_arguments: false,
+            blocks: 0,
+            block_functions: Vec::new(),
+            classes: 0,
+            derived: false,
+            super_property: false,
+            super_call: false,
+            cover_initializers: Vec::new(),
+            suspensions: Vec::new(),
+            lexer: Lexer::with_max_literal_length(arena, source, options.max_literal_length),
             errors: Vec::new(),
+            warnings: Vec::new(),
             body: NodeList::empty(),
+            budget: Budget::new(options.quota),
+            exhausted: false,
+            #[cfg(feature = "trace")]
+            tracer: self::trace::Tracer::default(),
+        };
+
+        // The lexer copies the source with a terminating zero onto the arena
+        if let Err(err) = parser.budget.charge_bytes(source.len() + 1) {
+            parser.exhaust(err, 0, 0);
         }
+
+        parser
     }
 
     fn error<T: ToError>(&mut self) -> T {
+        if self.exhausted {
+            return T::to_error();
+        }
+
         let err = self.lexer.invalid_token();
 
+        // The lexer stops at literals over the limit, as a quota
+        if let ErrorKind::LiteralTooLong { .. } = err.kind {
+            self.exhausted = true;
+        }
+
         self.errors.push(err);
 
         T::to_error()
     }
 
+    /// Private names such as `#a` are only allowed within a class body.
+    #[inline]
+    fn private_name(&mut self, raw: &str, start: u32, end: u32) {
+        self.features.insert(Features::PRIVATE_MEMBERS);
+
+        if self.classes == 0 {
+            self.error_at(ErrorKind::PrivateNameOutsideClass, PrivateIdentifier, raw, start, end);
+        }
+    }
+
+    /// Report an error at a location other than the current token
+    fn error_at(&mut self, kind: ErrorKind, token: Token, raw: &str, start: u32, end: u32) {
+        if self.exhausted {
+            return;
+        }
+
+        self.errors.push(Error {
+            kind,
+            token,
+            raw: raw.into(),
+            start: start as usize,
+            end: end as usize,
+        });
+    }
+
+    /// Intern the current literal token if the `ParserOptions` say so,
+    /// its length was checked by the lexer
+    #[inline]
+    fn literal(&mut self, value: &'ast str) -> &'ast str {
+        if self.options.intern_literals {
+            return self.literals.entry(value).or_insert(value);
+        }
+
+        value
+    }
+
+    /// Decode the value of a string literal, allocating it only if it has
+    /// escapes, in which case it's charged to the quota if new.
+    #[inline]
+    fn string_value(&mut self, raw: &'ast str) -> &'ast str {
+        let cooked = match decode_string(raw) {
+            Cow::Borrowed(value) => return value,
+            Cow::Owned(cooked) => cooked,
+        };
+
+        if self.options.intern_literals {
+            if let Some(&value) = self.literals.get(cooked.as_str()) {
+                return value;
+            }
+        }
+
+        if let Err(err) = self.budget.charge_bytes(cooked.len()) {
+            let (start, end) = self.lexer.loc();
+
+            self.exhaust(err, start, end);
+        }
+
+        let value = self.arena.alloc_string(cooked);
+
+        if self.options.intern_literals {
+            self.literals.insert(value, value);
+        }
+
+        value
+    }
+
+    /// Allocate the comments read by the lexer so far as a list
+    fn stats(&self, source: &str) -> ParseStats {
+        ParseStats {
+            source_bytes: source.len(),
+            tokens: self.lexer.tokens(),
+            statements: self.body.iter().count(),
+            comments: self.lexer.comments.len(),
+            errors: self.errors.len(),
+            warnings: self.warnings.len(),
+        }
+    }
+
+    fn comments(&self) -> NodeList<'ast, &'ast str> {
+        let arena = self.arena;
+
+        NodeList::from_iter(arena, self.lexer.comments.iter().map(|&comment| Node::new(arena.alloc(comment))))
+    }
+
     #[inline]
     fn asi(&mut self) -> Asi {
         self.lexer.asi()
@@ -79,9 +388,28 @@ impl<'ast> Parser<'ast> {
     fn alloc<T>(&mut self, val: Loc<T>) -> Node<'ast, T> where
         T: Copy,
     {
+        if let Err(err) = self.budget.charge_node::<T>() {
+            self.exhaust(err, val.start, val.end);
+        }
+
         Node::new(self.arena.alloc(val.into()))
     }
 
+    /// Report the quota exceeded at the node at `start`, `end` and stop
+    /// parsing.
+    #[cold]
+    fn exhaust(&mut self, err: QuotaExceeded, start: u32, end: u32) {
+        if self.exhausted {
+            return;
+        }
+
+        let token = self.lexer.token;
+
+        self.error_at(ErrorKind::QuotaExceeded { resource: err.resource, limit: err.limit }, token, "", start, end);
+        self.exhausted = true;
+        self.lexer.halt();
+    }
+
     #[inline]
     fn alloc_in_loc<T, I>(&mut self, item: I) -> Node<'ast, T> where
         T: Copy,
@@ -105,11 +433,22 @@ impl<'ast> Parser<'ast> {
             return;
         }
 
-        let statement = self.statement();
+        let statement = self.module_item();
         let builder = ListBuilder::new(self.arena, statement);
+        let mut prologue = self.directive(statement);
+
+        self.validate_cover_initializers();
 
         while self.lexer.token != EndOfProgram {
-            builder.push(self.arena, self.statement());
+            let statement = self.module_item();
+
+            self.validate_cover_initializers();
+
+            if prologue {
+                prologue = self.directive(statement);
+            }
+
+            builder.push(self.arena, statement);
         }
 
         self.body = builder.as_list()
@@ -132,16 +471,39 @@ impl<'ast> Parser<'ast> {
         self.alloc_at_loc(start, end, block)
     }
 
-    /// Same as above, but assumes that the opening brace has already been checked
+    /// Same as `block`, but directives at the start of the body apply.
     #[inline]
-    fn unchecked_block<I>(&mut self) -> BlockNode<'ast, I> where
-        I: Parse<'ast, Output = Node<'ast, I>> + Copy
-    {
-        let start = self.lexer.start_then_consume();
-        let block = self.raw_block();
-        let end   = self.lexer.end_then_consume();
+    fn function_body(&mut self) -> BlockNode<'ast, Statement<'ast>> {
+        let start = self.lexer.start();
 
-        self.alloc_at_loc(start, end, block)
+        match self.lexer.token {
+            BraceOpen => self.lexer.consume(),
+            _         => self.error::<()>(),
+        }
+
+        if self.lexer.token == BraceClose {
+            let end = self.lexer.end_then_consume();
+
+            return self.alloc_at_loc(start, end, Block { body: NodeList::empty() });
+        }
+
+        let statement = self.statement();
+        let builder = ListBuilder::new(self.arena, statement);
+        let mut prologue = self.directive(statement);
+
+        while self.lexer.token != BraceClose && self.lexer.token != EndOfProgram {
+            let statement = self.statement();
+
+            if prologue {
+                prologue = self.directive(statement);
+            }
+
+            builder.push(self.arena, statement);
+        }
+
+        let end = self.lexer.end_then_consume();
+
+        self.alloc_at_loc(start, end, Block { body: builder.as_list() })
     }
 
     #[inline]
@@ -152,6 +514,8 @@ impl<'ast> Parser<'ast> {
             return Block { body: NodeList::empty() };
         }
 
+        self.blocks += 1;
+
         let statement = I::parse(self);
         let builder = ListBuilder::new(self.arena, statement);
 
@@ -159,9 +523,22 @@ impl<'ast> Parser<'ast> {
             builder.push(self.arena, I::parse(self));
         }
 
+        self.blocks -= 1;
+
         Block { body: builder.as_list() }
     }
 
+    /// Push `item` onto a list that may still be empty, starting it with a
+    /// `ListBuilder` when it is, as `GrowableList` loses its items once
+    /// optimized.
+    #[inline]
+    fn push_item<T: Copy>(&self, list: &mut Option<ListBuilder<'ast, T>>, item: T) {
+        match *list {
+            Some(ref builder) => builder.push(self.arena, item),
+            None => *list = Some(ListBuilder::new(self.arena, item)),
+        }
+    }
+
     #[inline]
     fn identifier(&mut self) -> IdentifierNode<'ast> {
         match self.lexer.token {
@@ -191,12 +568,84 @@ impl<'ast> Parser<'ast> {
             Expression::Identifier(ident) => {
                 Pattern::Identifier(ident)
             },
+            Expression::Object(ObjectExpression { body }) => {
+                self.features.insert(Features::DESTRUCTURING);
+                self.validate_binding_properties(body);
+                self.resolve_properties(body);
+
+                Pattern::ObjectPattern {
+                    properties: body,
+                }
+            },
+            Expression::Array(ArrayExpression { body }) => {
+                self.features.insert(Features::DESTRUCTURING);
+
+                let mut elements = body.iter().peekable();
+                let mut patterns = None;
+
+                while let Some(&element) = elements.next() {
+                    let element = match element.item {
+                        Expression::Void => self.alloc_at_loc(element.start, element.end, Pattern::Void),
+                        Expression::Spread(SpreadExpression { argument }) if elements.peek().is_none() => {
+                            self.features.insert(Features::REST_PARAMETERS);
+
+                            let argument = match argument.item {
+                                Expression::Identifier(ident) => self.alloc_at_loc(argument.start, argument.end, ident),
+                                _ => self.error(),
+                            };
+
+                            self.alloc_at_loc(element.start, element.end, Pattern::RestElement { argument })
+                        },
+                        _ => self.pattern_from_expression(element),
+                    };
+
+                    self.push_item(&mut patterns, element);
+                }
+
+                Pattern::ArrayPattern {
+                    elements: patterns.map_or(NodeList::empty(), |builder| builder.as_list()),
+                }
+            },
             _ => self.error()
         };
 
         self.alloc_at_loc(expression.start, expression.end, pattern)
     }
 
+    /// Accept the shorthand properties with an initializer of an object
+    /// literal, and of the literals nested in it, the literal being the
+    /// target of an assignment.
+    fn resolve_target(&mut self, expression: ExpressionNode<'ast>) {
+        if self.cover_initializers.is_empty() {
+            return;
+        }
+
+        match expression.item {
+            Expression::Object(ObjectExpression { body }) => self.resolve_properties(body),
+            Expression::Array(ArrayExpression { body }) => {
+                for &element in body {
+                    self.resolve_target(element);
+                }
+            },
+            Expression::Spread(SpreadExpression { argument }) => self.resolve_target(argument),
+            _ => {},
+        }
+    }
+
+    /// Same as `resolve_target`, for the properties of an object pattern.
+    fn resolve_properties(&mut self, properties: NodeList<'ast, Property<'ast>>) {
+        for property in properties {
+            match property.item {
+                Property::ShorthandDefault { .. } => {
+                    self.cover_initializers.retain(|&(_, start, _)| start != property.start);
+                },
+                Property::Literal { value, .. } => self.resolve_target(value),
+                Property::Spread { argument } => self.resolve_target(argument),
+                _ => {},
+            }
+        }
+    }
+
     #[inline]
     fn params_from_expressions(&mut self, expressions: ExpressionList<'ast>) -> NodeList<'ast, Pattern<'ast>> {
         let mut expressions = expressions.iter();
@@ -220,83 +669,103 @@ impl<'ast> Parser<'ast> {
 
 /// Parse the JavaScript source `&str` and produce an Abstract Syntax Tree `Module`.
 pub fn parse<'src, 'ast>(source: &'src str) -> Result<Module<'ast>, Vec<Error>> {
+    parse_with_options(source, ParserOptions::default())
+}
+
+/// Same as `parse`, with custom `ParserOptions`.
+pub fn parse_with_options<'ast>(source: &str, options: ParserOptions) -> Result<Module<'ast>, Vec<Error>> {
+    parse_with_observer(source, options, &mut ())
+}
+
+/// Same as `parse_with_options`, reporting the errors to the `sink` as a
+/// single batch, or the warnings of the module if there are no errors.
+pub fn parse_with_sink<'ast, S>(source: &str, options: ParserOptions, sink: &S) -> Result<Module<'ast>, Vec<Error>> where
+    S: DiagnosticsSink + ?Sized,
+{
+    let result = parse_with_options(source, options);
+
+    match result {
+        Ok(ref module) => if !module.warnings().is_empty() {
+            sink.report(module.warnings());
+        },
+        Err(ref errors) => {
+            let batch: Vec<Diagnostic> = errors.iter().map(Error::diagnostic).collect();
+
+            sink.report(&batch);
+        },
+    }
+
+    result
+}
+
+/// Same as `parse_with_options`, calling the `observer` at the start and
+/// end of every phase of parsing, see `ratel::observer`.
+pub fn parse_with_observer<'ast, O>(source: &str, options: ParserOptions, observer: &mut O) -> Result<Module<'ast>, Vec<Error>> where
+    O: ParserObserver + ?Sized,
+{
     let arena = Arena::new();
 
-    let (body, errors) = {
-        let mut parser = Parser::new(source, &arena);
+    let (body, errors, warnings, terminators, positions, features, block_functions, comments, budget, stats) = {
+        observer.phase_start(Phase::Setup);
 
-        parser.parse();
+        let mut parser = Parser::new(source, &arena, options);
 
-        (parser.body.into_unsafe(), parser.errors)
-    };
+        observer.phase_end(Phase::Setup, &parser.stats(source));
+        observer.phase_start(Phase::Parse);
 
-    match errors.len() {
-        0 => Ok(Module::new(body, arena)),
-        _ => Err(errors)
-    }
-}
+        parser.parse();
 
-#[cfg(test)]
-mod mock {
-    use super::*;
-    use ast::{Literal, ExpressionNode, Block, BlockNode, Name};
+        let stats = parser.stats(source);
 
-    pub struct Mock {
-        arena: Arena
-    }
+        observer.phase_end(Phase::Parse, &stats);
+        observer.phase_start(Phase::Finish);
 
-    impl Mock {
-        pub fn new() -> Self {
-            Mock {
-                arena: Arena::new()
-            }
-        }
+        let comments = parser.comments().into_unsafe();
 
-        pub fn ptr<'a, T, I>(&'a self, val: I) -> Node<'a, T> where
-            T: 'a + Copy,
-            I: Into<T>,
-        {
-            Node::new(self.arena.alloc(Loc::new(0, 0, val.into())))
-        }
+        (parser.body.into_unsafe(), parser.errors, parser.warnings, parser.terminators, parser.positions, parser.features, parser.block_functions, comments, parser.budget, stats)
+    };
 
-        pub fn name<'a, N>(&'a self, val: &'a str) -> N where
-            N: Name<'a> + From<Node<'a, &'a str>>,
-        {
-            N::from(Node::new(self.arena.alloc(Loc::new(0, 0, val))))
-        }
+    let result = match errors.len() {
+        0 => {
+            let module = Module::new(body, arena)
+                .with_terminators(terminators)
+                .with_positions(positions)
+                .with_features(features)
+                .with_block_functions(block_functions)
+                .with_comments(comments)
+                .with_warnings(warnings)
+                .with_budget(budget);
+
+            #[cfg(feature = "debug-invariants")]
+            ::invariants::assert_valid(&module, "parsing");
+
+            Ok(module)
+        },
+        _ => Err(errors)
+    };
 
-        pub fn number<'a>(&'a self, number: &'static str) -> ExpressionNode<'a> {
-            self.ptr(Literal::Number(number))
-        }
+    observer.phase_end(Phase::Finish, &stats);
 
-        pub fn block<'a, I, T, L>(&'a self, list: L) -> BlockNode<'a, I> where
-            I: Copy,
-            T: Into<I> + Copy,
-            L: AsRef<[T]>
-        {
-            self.ptr(Block { body: self.list(list) })
-        }
+    result
+}
 
-        pub fn empty_block<'a, I: Copy>(&'a self) -> BlockNode<'a, I> {
-            self.ptr(Block { body: NodeList::empty() })
-        }
+/// Parse the source to a list of statements allocated on an existing
+/// `Arena`, along with the syntax features used.
+pub(crate) fn parse_statements<'ast>(source: &str, arena: &'ast Arena) -> Result<(NodeList<'ast, Statement<'ast>>, Features), Vec<Error>> {
+    let mut parser = Parser::new(source, arena, ParserOptions::default());
 
-        pub fn list<'a, T, I, L>(&'a self, list: L) -> NodeList<'a, T> where
-            T: 'a + Copy,
-            L: AsRef<[I]>,
-            I: Into<T> + Copy,
-        {
-            NodeList::from_iter(&self.arena, list.as_ref().iter().cloned().map(|i| {
-                Node::new(self.arena.alloc(Loc::new(0, 0, i.into())))
-            }))
-        }
+    parser.parse();
+
+    match parser.errors.len() {
+        0 => Ok((parser.body, parser.features)),
+        _ => Err(parser.errors)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use parser::mock::Mock;
+    use testing::Mock;
 
     #[test]
     fn empty_parse() {
@@ -315,4 +784,237 @@ mod test {
 
         assert_eq!(parse(";;;").unwrap().body(), expected);
     }
+
+    #[test]
+    fn quota() {
+        use quota::Resource;
+
+        let source = "a; function b() { c(d, e); }";
+        let usage = parse(source).unwrap().budget().usage();
+
+        let options = ParserOptions {
+            quota: Quota { max_nodes: Some(usage.nodes), max_arena_bytes: Some(usage.arena_bytes) },
+            ..ParserOptions::default()
+        };
+
+        assert_eq!(parse_with_options(source, options).unwrap().budget().usage(), usage);
+
+        let options = ParserOptions {
+            quota: Quota { max_nodes: Some(4), max_arena_bytes: None },
+            ..ParserOptions::default()
+        };
+
+        let errors = parse_with_options(source, options).err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::QuotaExceeded { resource: Resource::Nodes, limit: 4 });
+        assert_eq!((errors[0].start, errors[0].end), (20, 21));
+
+        let options = ParserOptions {
+            quota: Quota { max_nodes: None, max_arena_bytes: Some(source.len()) },
+            ..ParserOptions::default()
+        };
+
+        let errors = parse_with_options(source, options).err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::QuotaExceeded { resource: Resource::ArenaBytes, limit: source.len() });
+    }
+
+    #[test]
+    fn max_literal_length() {
+        let options = ParserOptions {
+            max_literal_length: Some(5),
+            ..ParserOptions::default()
+        };
+
+        assert!(parse_with_options("'foo'; `foo${bar}baz`;", options).is_ok());
+
+        assert!(parse_with_options("'foo'; `fooba${bar}bazqu`;", options).is_ok());
+
+        // The rest of the source isn't read once the limit is reached
+        let errors = parse_with_options("'foo'; 'foobar'; a(", options).err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::LiteralTooLong { limit: 5 });
+        assert_eq!((errors[0].start, errors[0].end), (7, 13));
+
+        let errors = parse_with_options("`foo${bar}bazqux`;", options).err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::LiteralTooLong { limit: 5 });
+        assert_eq!((errors[0].start, errors[0].end), (9, 16));
+
+        let errors = parse_with_options("'a\\nbc'", options).err().unwrap();
+
+        assert_eq!(errors[0].kind, ErrorKind::LiteralTooLong { limit: 5 });
+    }
+
+    #[test]
+    fn reports_to_sink() {
+        use std::sync::Mutex;
+        use diagnostic::Severity;
+
+        let sink = Mutex::new(Vec::new());
+
+        assert!(parse_with_sink("a;", ParserOptions::default(), &sink).is_ok());
+        assert!(parse_with_sink("017;", ParserOptions::default(), &sink).is_ok());
+        assert!(parse_with_sink("a(", ParserOptions::default(), &sink).is_err());
+
+        let codes: Vec<_> = sink.into_inner().unwrap().iter().map(|diagnostic| (diagnostic.severity, diagnostic.code)).collect();
+
+        assert_eq!(codes, vec![(Severity::Warning, "E0007"), (Severity::Error, "E0001"), (Severity::Error, "E0001")]);
+    }
+
+    #[test]
+    fn features() {
+        let features = |src| parse(src).unwrap().features();
+
+        assert!(features("var a = 1; function f(b) { return a + b }").is_empty());
+        assert_eq!(features("class A {}"), Features::CLASSES);
+        assert_eq!(features("function* f() {}"), Features::GENERATORS);
+        assert_eq!(features("({ async f() {} })"), Features::ASYNC_FUNCTIONS);
+        assert_eq!(features("x => x"), Features::ARROW_FUNCTIONS);
+        assert_eq!(features("tag`foo${bar}`"), Features::TEMPLATE_LITERALS);
+        assert_eq!(features("f(...a)"), Features::SPREAD);
+        assert_eq!(features("function f(...a) {}"), Features::REST_PARAMETERS);
+        assert_eq!(features("var [a, { b }] = c;"), Features::DESTRUCTURING);
+        assert_eq!(features("for (const a of b) {}"), Features::LEXICAL_DECLARATIONS | Features::FOR_OF);
+        assert_eq!(features("function f() { new.target }"), Features::NEW_TARGET);
+        assert_eq!(features("import a from 'b';"), Features::MODULES);
+        assert_eq!(features("export default 1;"), Features::MODULES);
+        assert_eq!(features("a?.b; c?.[d]; e?.();"), Features::OPTIONAL_CHAINING);
+        assert_eq!(features("a ** b; c **= d;"), Features::EXPONENTIATION);
+        assert_eq!(features("class A { a; static b = 1; }"), Features::CLASSES | Features::CLASS_FIELDS);
+        assert_eq!(features("class A { #a() {} }"), Features::CLASSES | Features::PRIVATE_MEMBERS);
+        assert_eq!(features("class A { #a; m() { this.#a; } }"), Features::CLASSES | Features::CLASS_FIELDS | Features::PRIVATE_MEMBERS);
+        assert_eq!(features("class A { static {} }"), Features::CLASSES | Features::STATIC_BLOCKS);
+        assert!(!features("a.b; a * b; class A { static() {} }").intersects(Features::OPTIONAL_CHAINING | Features::EXPONENTIATION | Features::CLASS_FIELDS | Features::STATIC_BLOCKS));
+
+        let all = features("let a = ({ ...b }); class A { c() { return function* () { `d` } } }");
+
+        assert!(all.contains(Features::LEXICAL_DECLARATIONS | Features::SPREAD | Features::CLASSES));
+        assert!(all.contains(Features::GENERATORS | Features::TEMPLATE_LITERALS));
+        assert!(!all.intersects(Features::ARROW_FUNCTIONS | Features::FOR_OF));
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let errors = parse("foo('bar\n);\nbaz();").err().unwrap();
+
+        assert_eq!(errors[0].kind, ErrorKind::UnterminatedString);
+        assert_eq!((errors[0].start, errors[0].end), (4, 8));
+    }
+
+    #[test]
+    fn intern_literals() {
+        use ast::{Literal, Expression};
+
+        let options = ParserOptions {
+            intern_literals: true,
+            ..ParserOptions::default()
+        };
+
+        let module = parse_with_options("'foo'; 'foo';", options).unwrap();
+        let values: Vec<_> = module.body().iter().map(|statement| match statement.item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Literal(Literal::String(string)) => string.raw.as_ptr(),
+                _ => panic!("Expected a string literal"),
+            },
+            _ => panic!("Expected an expression statement"),
+        }).collect();
+
+        assert_eq!(values[0], values[1]);
+    }
+
+    #[test]
+    fn terminators() {
+        let options = ParserOptions {
+            terminators: true,
+            ..ParserOptions::default()
+        };
+
+        let module = parse_with_options("a;\nb\nfunction c() { return d }\nthrow e;\nlet f = 1\nbreak", options).unwrap();
+        let body: Vec<_> = module.body().iter().map(|statement| module.terminator(statement)).collect();
+
+        assert_eq!(body, vec![
+            Some(Terminator::Semicolon),
+            Some(Terminator::Asi),
+            None,
+            Some(Terminator::Semicolon),
+            Some(Terminator::Asi),
+            Some(Terminator::Asi),
+        ]);
+
+        let function = match module.body().iter().nth(2).unwrap().item {
+            Statement::Function(ref function) => function.body.body.first_element().unwrap(),
+            _ => panic!("Expected a function"),
+        };
+
+        assert_eq!(module.terminator(function), Some(Terminator::BraceClose));
+
+        let module = parse("a;").unwrap();
+
+        assert_eq!(module.terminator(module.body().first_element().unwrap()), None);
+    }
+
+    #[test]
+    fn declaration_spans() {
+        let module = parse("var a = 1\nfunction b() {}\nlet c;\nconst d = 2").unwrap();
+        let spans: Vec<_> = module.body().iter().map(|statement| (statement.start, statement.end)).collect();
+
+        assert_eq!(spans, vec![(0, 9), (10, 25), (26, 32), (33, 44)]);
+    }
+
+    #[test]
+    fn positions() {
+        let options = ParserOptions {
+            positions: true,
+            ..ParserOptions::default()
+        };
+
+        let source = "a;\r\n  if (b) {\n    c; d;\n  }\n'\u{e9}\u{1f600}'; e;\rexport const f = 1;";
+        let module = parse_with_options(source, options).unwrap();
+        let position = |line, column| Some(LineColumn { line, column });
+
+        let body: Vec<_> = module.body().iter().map(|statement| module.position(statement)).collect();
+
+        assert_eq!(body, vec![position(1, 0), position(2, 2), position(5, 0), position(5, 7), position(6, 0)]);
+
+        let block = match module.body().iter().nth(1).unwrap().item {
+            Statement::If(ref if_statement) => if_statement.consequent,
+            _ => panic!("Expected an if statement"),
+        };
+        let nested: Vec<_> = match block.item {
+            Statement::Block(ref block) => block.body.iter().map(|statement| module.position(statement)).collect(),
+            _ => panic!("Expected a block"),
+        };
+
+        assert_eq!(block.start, 13);
+        assert_eq!(module.position(&block), position(2, 9));
+        assert_eq!(nested, vec![position(3, 4), position(3, 7)]);
+        assert_eq!(module.positions().len(), 9);
+
+        let module = parse("a;").unwrap();
+
+        assert_eq!(module.position(module.body().first_element().unwrap()), None);
+        assert!(module.positions().is_empty());
+    }
+
+    #[test]
+    fn statement_and_expression_kinds() {
+        use ast::{StatementKind, ExpressionKind};
+
+        let module = parse("foo; if (bar) {} function baz() {}").unwrap();
+        let kinds: Vec<_> = module.body().iter().map(|s| s.kind()).collect();
+
+        assert_eq!(kinds, vec![StatementKind::Expression, StatementKind::If, StatementKind::Function]);
+
+        match module.body().first_element().unwrap().item {
+            Statement::Expression(ref expression) => {
+                assert_eq!(expression.kind(), ExpressionKind::Identifier);
+            },
+            _ => panic!("Expected an expression statement"),
+        }
+    }
 }
@@ -6,8 +6,11 @@ use ast::{NodeList, Expression, ExpressionNode};
 use ast::expression::*;
 use ast::OperatorKind::*;
 use lexer::Asi;
+use module::Features;
+#[cfg(feature = "trace")]
+use parser::trace::Production;
 
-const TOTAL_TOKENS: usize = 108;
+const TOTAL_TOKENS: usize = 109;
 
 type NestedHandler = Option<for<'ast> fn(&mut Parser<'ast>, ExpressionNode<'ast>) -> ExpressionNode<'ast>>;
 
@@ -62,10 +65,13 @@ bp!(ANY, [
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-//  IMPRT TRY   STATI TRUE  FALSE NULL  UNDEF STR   NUM   BIN   REGEX ENUM
+//  IMPRT TRY   STATI TRUE  FALSE NULL  STR   NUM   BIN   REGEX ENUM  IMPL
 
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
-//  IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+//  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E ?.
+
+    ____,
+//  #PRIV
 ]);
 
 bp!(B0, [
@@ -77,7 +83,8 @@ bp!(B0, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B1, [
@@ -89,19 +96,8 @@ bp!(B1, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
-]);
-
-bp!(B4, [
-    ____, ____, ____, ____, CALL, ____, CMEM, ____, ____, ____, ARRW, ____,
-    INC,  DEC,  ____, ____, ____, ____, ____, MUL,  DIV,  REM,  EXPN, ADD,
-    SUB,  BSL,  BSR,  UBSR, LESS, LSEQ, GRTR, GREQ, INOF, IN,   STEQ, SIEQ,
-    EQ,   INEQ, BWAN, BWXO, BWOR, AND,  OR,   ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B5, [
@@ -113,7 +109,8 @@ bp!(B5, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B6, [
@@ -125,7 +122,8 @@ bp!(B6, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B7, [
@@ -137,7 +135,8 @@ bp!(B7, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B8, [
@@ -149,7 +148,8 @@ bp!(B8, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B9, [
@@ -161,7 +161,8 @@ bp!(B9, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B10, [
@@ -173,7 +174,8 @@ bp!(B10, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B11, [
@@ -185,7 +187,8 @@ bp!(B11, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B12, [
@@ -197,7 +200,8 @@ bp!(B12, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B13, [
@@ -209,7 +213,8 @@ bp!(B13, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B14, [
@@ -221,7 +226,8 @@ bp!(B14, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 bp!(B15, [
@@ -233,7 +239,22 @@ bp!(B15, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
+]);
+
+// Left-hand side expressions, such as the heritage of a class
+bp!(B17, [
+    ____, ____, ____, ____, CALL, ____, CMEM, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____, OPTL,
+    ____,
 ]);
 
 const ____: NestedHandler = None;
@@ -258,9 +279,9 @@ const SEQ: NestedHandler = Some(|par, left| {
 const COND: NestedHandler = Some(|par, left| {
     par.lexer.consume();
 
-    let consequent = par.expression::<B4>();
+    let consequent = par.expression::<B0>();
     expect!(par, Colon);
-    let alternate = par.expression::<B4>();
+    let alternate = par.expression::<B0>();
 
     par.alloc_at_loc(left.start, alternate.end, ConditionalExpression {
         test: left,
@@ -277,7 +298,7 @@ const ARRW: NestedHandler = Some(|par, left| {
         _ => NodeList::from(par.arena, left)
     };
 
-    let expression = par.arrow_function_expression(params);
+    let expression = par.arrow_function_expression(params, false);
     let start = left.start;
     let end = par.lexer.end();
     return par.alloc_at_loc(start, end, expression)
@@ -285,6 +306,20 @@ const ARRW: NestedHandler = Some(|par, left| {
 
 const ACCS: NestedHandler = Some(|par, left| {
     let member = par.lexer.accessor_as_str();
+
+    if let Some(name) = member.strip_prefix('#') {
+        let end = par.lexer.end_then_consume();
+        let right = par.alloc_at_loc(end - member.len() as u32, end, name);
+
+        par.private_name(member, right.start, right.end);
+
+        return par.alloc_at_loc(left.start, right.end, PrivateMemberExpression {
+            object: left,
+            property: right,
+            optional: false,
+        });
+    }
+
     par.lexer.consume();
 
     let right = par.alloc_in_loc(member);
@@ -292,6 +327,7 @@ const ACCS: NestedHandler = Some(|par, left| {
     par.alloc_at_loc(left.start, right.end, MemberExpression {
         object: left,
         property: right,
+        optional: false,
     })
 });
 
@@ -303,6 +339,7 @@ const CALL: NestedHandler = Some(|par, left| {
     par.alloc_at_loc(start, end, CallExpression {
         callee: left,
         arguments,
+        optional: false,
     })
 });
 
@@ -316,10 +353,74 @@ const CMEM: NestedHandler = Some(|par, left| {
     par.alloc_at_loc(left.start, end, ComputedMemberExpression {
         object: left,
         property: property,
+        optional: false,
     })
 });
 
+const OPTL: NestedHandler = Some(|par, left| {
+    par.features.insert(Features::OPTIONAL_CHAINING);
+    par.lexer.consume();
+
+    match par.lexer.token {
+        ParenOpen => {
+            par.lexer.consume();
+            let arguments = par.call_arguments();
+            let end = par.lexer.end_then_consume();
+
+            par.alloc_at_loc(left.start, end, CallExpression {
+                callee: left,
+                arguments,
+                optional: true,
+            })
+        },
+        BracketOpen => {
+            par.lexer.consume();
+            let property = par.expression::<ANY>();
+
+            expect!(par, BracketClose);
+            let end = par.lexer.end();
+
+            par.alloc_at_loc(left.start, end, ComputedMemberExpression {
+                object: left,
+                property,
+                optional: true,
+            })
+        },
+        _ if par.lexer.token.is_word() => {
+            let member = par.lexer.token_as_str();
+            let right = par.alloc_in_loc(member);
+
+            par.lexer.consume();
+
+            par.alloc_at_loc(left.start, right.end, MemberExpression {
+                object: left,
+                property: right,
+                optional: true,
+            })
+        },
+        PrivateIdentifier => {
+            let member = par.lexer.token_as_str();
+            let right = par.alloc_in_loc(&member[1..]);
+
+            par.private_name(member, right.start, right.end);
+            par.lexer.consume();
+
+            par.alloc_at_loc(left.start, right.end, PrivateMemberExpression {
+                object: left,
+                property: right,
+                optional: true,
+            })
+        },
+        _ => {
+            par.error::<()>();
+            left
+        }
+    }
+});
+
 const TPLS: NestedHandler = Some(|par, left| {
+    par.template_tag(left);
+
     let quasi = par.template_string();
 
     par.alloc_at_loc(left.start, quasi.end, TaggedTemplateExpression {
@@ -364,6 +465,15 @@ macro_rules! assign {
                     par.error::<()>();
                 }
 
+                if $op == Assign {
+                    par.resolve_target(left);
+                    par.validate_assignment_target(left);
+                }
+
+                if $op == ExponentAssign {
+                    par.features.insert(Features::EXPONENTIATION);
+                }
+
                 let right = par.expression::<B1>();
 
                 par.alloc_at_loc(left.start, right.end, BinaryExpression {
@@ -384,6 +494,10 @@ macro_rules! binary {
             fn handler<'ast>(par: &mut Parser<'ast>, left: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
                 par.lexer.consume();
 
+                if $op == Exponent {
+                    par.features.insert(Features::EXPONENTIATION);
+                }
+
                 let right = par.expression::<$bp>();
 
                 par.alloc_at_loc(left.start, right.end, BinaryExpression {
@@ -438,7 +552,8 @@ binary!(SUB  , B13 => Subtraction);
 binary!(MUL  , B14 => Multiplication);
 binary!(DIV  , B14 => Division);
 binary!(REM  , B14 => Remainder);
-binary!(EXPN , B15 => Exponent);
+// Right-associative, `a ** b ** c` being `a ** (b ** c)`
+binary!(EXPN , B14 => Exponent);
 
 
 impl<'ast> Parser<'ast> {
@@ -448,7 +563,9 @@ impl<'ast> Parser<'ast> {
         B: BindingPower
     {
         while let Some(handler) = B::handler(self.asi(), self.lexer.token) {
+            trace!(self, trace_enter, Production::Nested);
             left = handler(self, left);
+            trace!(self, trace_leave);
         }
 
         left
@@ -0,0 +1,324 @@
+//! Extension points of the grammar, for forks accepting nonstandard forms
+//! without patching the parser. A `Plugin` is registered with
+//! `ParserOptions::plugins` and consulted where the grammar meets a token
+//! it has no rule for; the first plugin handling the token wins, and the
+//! parser reports the usual error if none does.
+//!
+//! Plugins parse through the `PluginParser` they are given and build
+//! standard nodes, so that codegen and transforms keep working on the
+//! module. The `try` statement is the only extension point so far:
+//!
+//! - `Plugin::catch_param` is called when the parameter of a `catch`
+//!   clause isn't followed by `)`, such as `catch (e if e instanceof A)`.
+//! - `Plugin::catch_handler` is called when a `catch` clause is followed
+//!   by another one.
+
+use std::fmt;
+use ast::{Node, NodeList, Pattern, Statement, StatementNode, ExpressionNode, BlockNode};
+use ast::statement::CatchClause;
+use parser::{Parser, Parse, ANY};
+
+/// Extension of the grammar, see the module documentation. A plugin
+/// returning `None` must leave the tokens to the plugins after it, it
+/// can't consume any.
+pub trait Plugin: Sync {
+    /// Parse the rest of a `catch` clause starting at `start`, the current
+    /// token following its `param`.
+    #[inline]
+    fn catch_param<'p, 'ast>(
+        &self,
+        _par: &mut PluginParser<'p, 'ast>,
+        _start: u32,
+        _param: Node<'ast, Pattern<'ast>>,
+    ) -> Option<Node<'ast, CatchClause<'ast>>> {
+        None
+    }
+
+    /// Parse the `catch` clause following the `handler`, the current token
+    /// being `catch`, into a single clause doing the work of both.
+    #[inline]
+    fn catch_handler<'p, 'ast>(
+        &self,
+        _par: &mut PluginParser<'p, 'ast>,
+        _handler: Node<'ast, CatchClause<'ast>>,
+    ) -> Option<Node<'ast, CatchClause<'ast>>> {
+        None
+    }
+}
+
+/// Plugins of `ParserOptions::plugins`, consulted in order. Two sets are
+/// equal if they hold the same plugins.
+#[derive(Clone, Copy, Default)]
+pub struct Plugins(pub &'static [&'static dyn Plugin]);
+
+impl fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Plugins({})", self.0.len())
+    }
+}
+
+impl PartialEq for Plugins {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(other.0).all(|(a, b)| {
+            *a as *const dyn Plugin as *const u8 == *b as *const dyn Plugin as *const u8
+        })
+    }
+}
+
+/// Parser as seen by a `Plugin`. Tokens are told apart by their source,
+/// the lexer isn't part of the API.
+pub struct PluginParser<'p, 'ast: 'p> {
+    parser: &'p mut Parser<'ast>,
+}
+
+impl<'p, 'ast> PluginParser<'p, 'ast> {
+    /// Source of the current token.
+    #[inline]
+    pub fn token_as_str(&self) -> &'ast str {
+        self.parser.lexer.token_as_str()
+    }
+
+    /// Start and end of the current token.
+    #[inline]
+    pub fn loc(&self) -> (u32, u32) {
+        self.parser.lexer.loc()
+    }
+
+    #[inline]
+    pub fn consume(&mut self) {
+        self.parser.lexer.consume();
+    }
+
+    /// Consume the current token if its source is `text`.
+    #[inline]
+    pub fn eat(&mut self, text: &str) -> bool {
+        let matches = self.token_as_str() == text;
+
+        if matches {
+            self.consume();
+        }
+
+        matches
+    }
+
+    /// Consume the current token if its source is `text`, report it as
+    /// unexpected otherwise.
+    #[inline]
+    pub fn expect(&mut self, text: &str) {
+        if !self.eat(text) {
+            self.error();
+        }
+    }
+
+    /// Report the current token as unexpected.
+    #[inline]
+    pub fn error(&mut self) {
+        self.parser.error::<()>();
+    }
+
+    #[inline]
+    pub fn expression(&mut self) -> ExpressionNode<'ast> {
+        self.parser.expression::<ANY>()
+    }
+
+    #[inline]
+    pub fn pattern(&mut self) -> Node<'ast, Pattern<'ast>> {
+        Pattern::parse(self.parser)
+    }
+
+    #[inline]
+    pub fn block(&mut self) -> BlockNode<'ast, Statement<'ast>> {
+        self.parser.block()
+    }
+
+    #[inline]
+    pub fn statement(&mut self) -> StatementNode<'ast> {
+        self.parser.statement()
+    }
+
+    /// Parse a `catch` clause, consulting the plugins as the `try`
+    /// statement does.
+    #[inline]
+    pub fn catch_clause(&mut self) -> Node<'ast, CatchClause<'ast>> {
+        self.parser.catch_clause()
+    }
+
+    /// Allocate a node, charged to `ParserOptions::quota`.
+    #[inline]
+    pub fn alloc_at_loc<T, I>(&mut self, start: u32, end: u32, item: I) -> Node<'ast, T> where
+        T: Copy,
+        I: Into<T>,
+    {
+        self.parser.alloc_at_loc(start, end, item)
+    }
+
+    #[inline]
+    pub fn list<T, I>(&mut self, nodes: I) -> NodeList<'ast, T> where
+        T: 'ast + Copy,
+        I: IntoIterator<Item = Node<'ast, T>>,
+    {
+        NodeList::from_iter(self.parser.arena, nodes)
+    }
+}
+
+impl<'ast> Parser<'ast> {
+    /// Consult the plugins in order, until one of them handles the
+    /// current token.
+    #[inline]
+    pub(super) fn plugin<T, F>(&mut self, mut f: F) -> Option<T> where
+        F: FnMut(&dyn Plugin, &mut PluginParser<'_, 'ast>) -> Option<T>,
+    {
+        for plugin in self.options.plugins.0 {
+            let mut par = PluginParser { parser: self };
+
+            if let Some(result) = f(*plugin, &mut par) {
+                return Some(result);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::{parse, parse_with_options, ParserOptions};
+    use ast::Expression;
+    use ast::statement::{IfStatement, ThrowStatement, BlockStatement};
+
+    /// Mozilla's `catch (e if cond)`, as an `if` statement in the body of
+    /// a standard clause, rethrowing when no condition holds.
+    struct ConditionalCatch;
+
+    impl Plugin for ConditionalCatch {
+        fn catch_param<'p, 'ast>(
+            &self,
+            par: &mut PluginParser<'p, 'ast>,
+            start: u32,
+            param: Node<'ast, Pattern<'ast>>,
+        ) -> Option<Node<'ast, CatchClause<'ast>>> {
+            let name = match param.item {
+                Pattern::Identifier(name) => name,
+                _ => return None,
+            };
+
+            if !par.eat("if") {
+                return None;
+            }
+
+            let test = par.expression();
+            par.expect(")");
+            let block = par.block();
+
+            let consequent = par.alloc_at_loc(block.start, block.end, BlockStatement { body: block.body });
+            let value = par.alloc_at_loc(param.start, param.end, Expression::Identifier(name));
+            let rethrow = par.alloc_at_loc(block.end, block.end, ThrowStatement { value });
+            let statement = par.alloc_at_loc(test.start, block.end, IfStatement {
+                test,
+                consequent,
+                alternate: Some(rethrow),
+            });
+            let body = par.list(Some(statement));
+            let body = par.alloc_at_loc(block.start, block.end, ::ast::Block { body });
+
+            Some(par.alloc_at_loc(start, block.end, CatchClause { param, body }))
+        }
+
+        fn catch_handler<'p, 'ast>(
+            &self,
+            par: &mut PluginParser<'p, 'ast>,
+            handler: Node<'ast, CatchClause<'ast>>,
+        ) -> Option<Node<'ast, CatchClause<'ast>>> {
+            let &statement = handler.body.body.only_element()?;
+
+            match statement.item {
+                Statement::If(_) => {},
+                _ => return None,
+            }
+
+            let next = par.catch_clause();
+
+            if next.param != handler.param {
+                par.error();
+            }
+
+            let alternate = match next.body.body.only_element() {
+                Some(&statement) => statement,
+                None => par.alloc_at_loc(next.body.start, next.body.end, BlockStatement { body: next.body.body }),
+            };
+            let statement = chain(par, statement, alternate);
+            let body = par.list(Some(statement));
+            let body = par.alloc_at_loc(handler.body.start, next.end, ::ast::Block { body });
+
+            Some(par.alloc_at_loc(handler.start, next.end, CatchClause { param: handler.param, body }))
+        }
+    }
+
+    /// Replace the rethrow at the end of a chain of conditions.
+    fn chain<'p, 'ast>(
+        par: &mut PluginParser<'p, 'ast>,
+        statement: StatementNode<'ast>,
+        alternate: StatementNode<'ast>,
+    ) -> StatementNode<'ast> {
+        match statement.item {
+            Statement::If(conditional) => {
+                let alternate = match conditional.alternate {
+                    Some(next) => chain(par, next, alternate),
+                    None => alternate,
+                };
+
+                par.alloc_at_loc(statement.start, alternate.end, IfStatement {
+                    alternate: Some(alternate),
+                    ..conditional
+                })
+            },
+            Statement::Throw(_) => alternate,
+            _ => {
+                par.error();
+                statement
+            }
+        }
+    }
+
+    static PLUGINS: &[&dyn Plugin] = &[&ConditionalCatch];
+
+    fn options() -> ParserOptions {
+        ParserOptions {
+            plugins: Plugins(PLUGINS),
+            ..ParserOptions::default()
+        }
+    }
+
+    #[test]
+    fn conditional_catch() {
+        let module = parse_with_options("try {} catch (e if e instanceof A) { a; }", options()).unwrap();
+        let expected = parse("try {} catch (e) { if (e instanceof A) { a; } else throw e; }").unwrap();
+
+        assert_eq!(module.body(), expected.body());
+    }
+
+    #[test]
+    fn multiple_handlers() {
+        let src = "try {} catch (e if a) { a; } catch (e if b) { b; } catch (e) { c; } finally { d; }";
+        let module = parse_with_options(src, options()).unwrap();
+        let expected = parse("try {} catch (e) { if (a) { a; } else if (b) { b; } else c; } finally { d; }").unwrap();
+
+        assert_eq!(module.body(), expected.body());
+    }
+
+    #[test]
+    fn plugins_decline() {
+        assert!(parse_with_options("try {} catch ({ e } if a) {}", options()).is_err());
+        assert!(parse_with_options("try {} catch (e) {} catch (e) {}", options()).is_err());
+        assert!(parse_with_options("try {} catch (e if a) {} catch (f) {}", options()).is_err());
+    }
+
+    #[test]
+    fn no_plugins() {
+        assert!(parse("try {} catch (e if a) {}").is_err());
+        assert!(parse("try {} catch (e) {} catch (e) {}").is_err());
+        assert_eq!(options(), options());
+        assert_ne!(options(), ParserOptions::default());
+    }
+}
@@ -0,0 +1,342 @@
+//! Relaxed JSON, as found in configuration files: the values of JSON5,
+//! that is JSON with comments, trailing commas, unquoted keys, single
+//! quoted strings, and hexadecimal, signed or non-finite numbers.
+
+use toolshed::list::ListBuilder;
+use toolshed::Arena;
+use parser::{Parser, ParserOptions};
+use lexer::Token::*;
+use ast::{Node, NodeList, Statement, Expression, ExpressionNode};
+use ast::{Property, PropertyKey, OperatorKind, Literal, NumberLiteral, StringLiteral};
+use ast::expression::{ObjectExpression, ArrayExpression, PrefixExpression};
+use error::Error;
+use module::Module;
+use allocated::*;
+
+impl<'ast> Parser<'ast> {
+    #[inline]
+    fn relaxed_value(&mut self) -> ExpressionNode<'ast> {
+        match self.lexer.token {
+            BraceOpen    => self.relaxed_object(),
+            BracketOpen  => self.relaxed_array(),
+            LiteralTrue  => self.relaxed_literal(Literal::Boolean(true)),
+            LiteralFalse => self.relaxed_literal(Literal::Boolean(false)),
+            LiteralNull  => self.relaxed_literal(Literal::Null),
+            LiteralString => {
+                self.validate_octal();
+
+                let raw = self.lexer.token_as_str();
+                let raw = self.literal(raw);
+                let value = self.string_value(raw);
+
+                self.relaxed_literal(Literal::String(StringLiteral { value, raw }))
+            },
+            OperatorAddition | OperatorSubtraction => {
+                let start = self.lexer.start();
+                let operator = match self.lexer.token {
+                    OperatorAddition => OperatorKind::Addition,
+                    _                => OperatorKind::Subtraction,
+                };
+
+                self.lexer.consume();
+
+                let operand = self.relaxed_number();
+
+                self.alloc_at_loc(start, operand.end, PrefixExpression { operator, operand })
+            },
+            _ => self.relaxed_number(),
+        }
+    }
+
+    /// Number literal, `Infinity` or `NaN`
+    #[inline]
+    fn relaxed_number(&mut self) -> ExpressionNode<'ast> {
+        match self.lexer.token {
+            LiteralNumber => self.validate_octal(),
+            LiteralBinary => {},
+            Identifier if matches!(self.lexer.token_as_str(), "Infinity" | "NaN") => {
+                let name = self.lexer.token_as_str();
+                let value = self.alloc_in_loc(name);
+
+                self.lexer.consume();
+
+                return value;
+            },
+            _ => {
+                let loc = self.lexer.start();
+
+                self.error::<()>();
+
+                return self.alloc_at_loc(loc, loc, Expression::Void);
+            },
+        }
+
+        let raw = self.lexer.token_as_str();
+
+        self.relaxed_literal(Literal::Number(NumberLiteral::new(raw)))
+    }
+
+    #[inline]
+    fn relaxed_literal(&mut self, literal: Literal<'ast>) -> ExpressionNode<'ast> {
+        let value = self.alloc_in_loc(literal);
+
+        self.lexer.consume();
+
+        value
+    }
+
+    fn relaxed_object(&mut self) -> ExpressionNode<'ast> {
+        let start = self.lexer.start_then_consume();
+        let builder = match self.lexer.token {
+            BraceClose => None,
+            _          => Some(ListBuilder::new(self.arena, self.relaxed_property())),
+        };
+
+        if let Some(ref builder) = builder {
+            loop {
+                match self.lexer.token {
+                    Comma      => self.lexer.consume(),
+                    BraceClose => break,
+                    _          => {
+                        self.error::<()>();
+                        break;
+                    },
+                }
+
+                match self.lexer.token {
+                    BraceClose => break,
+                    _          => builder.push(self.arena, self.relaxed_property()),
+                }
+            }
+        }
+
+        let end = self.lexer.end();
+
+        expect!(self, BraceClose);
+
+        self.alloc_at_loc(start, end, ObjectExpression {
+            body: builder.map_or(NodeList::empty(), |builder| builder.as_list()),
+        })
+    }
+
+    /// Property with a name or a string as its key
+    #[inline]
+    fn relaxed_property(&mut self) -> Node<'ast, Property<'ast>> {
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            LiteralString                   => self.validate_octal(),
+            _ if self.lexer.token.is_word() => {},
+            _                               => return self.error(),
+        }
+
+        let label = self.lexer.token_as_str();
+        let key = self.alloc_in_loc(PropertyKey::Literal(label));
+
+        self.lexer.consume();
+
+        expect!(self, Colon);
+
+        let value = self.relaxed_value();
+
+        self.alloc_at_loc(start, value.end, Property::Literal { key, value })
+    }
+
+    /// Array with no holes, a trailing comma is stored as one extra `Void`
+    /// same as in `ArrayExpression`
+    fn relaxed_array(&mut self) -> ExpressionNode<'ast> {
+        let start = self.lexer.start_then_consume();
+        let body = self.array_elements(|par| match par.lexer.token {
+            BracketClose => par.void_expression(),
+            _            => par.relaxed_value(),
+        });
+        let end = self.lexer.end();
+
+        expect!(self, BracketClose);
+
+        self.alloc_at_loc(start, end, ArrayExpression { body })
+    }
+}
+
+/// Parse the configuration-like `source` as relaxed JSON, see the module
+/// docs. Comments are allowed anywhere, as in JavaScript. The `Module` has
+/// a single expression statement with the value: an object with literal
+/// keys, an array, a string, a number, possibly signed, `Infinity`,
+/// `NaN`, `true`, `false` or `null`. Code other than that is an error, as
+/// are legacy octal literals and escapes.
+pub fn parse_relaxed<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+    let arena = Arena::new();
+
+    let (body, errors) = {
+        let options = ParserOptions {
+            strict: true,
+            ..ParserOptions::default()
+        };
+
+        let mut parser = Parser::new(source, &arena, options);
+        let value = parser.relaxed_value();
+
+        if parser.lexer.token != EndOfProgram {
+            parser.error::<()>();
+        }
+
+        let statement: Node<Statement> = parser.alloc_at_loc(value.start, value.end, Statement::Expression(value));
+
+        (NodeList::from(&arena, statement).into_unsafe(), parser.errors)
+    };
+
+    match errors.len() {
+        0 => Ok(Module::new(body, arena)),
+        _ => Err(errors),
+    }
+}
+
+/// Same as `parse_relaxed`, converting the value to JSON. Numbers that
+/// aren't finite, which JSON can't hold, are `null`.
+#[cfg(feature = "json")]
+pub fn parse_relaxed_json(source: &str) -> Result<::serde_json::Value, Vec<Error>> {
+    let module = parse_relaxed(source)?;
+
+    match module.body().first_element().map(|statement| statement.item) {
+        Some(Statement::Expression(value)) => Ok(json_value(&value.item)),
+        _ => unreachable!("Relaxed JSON is a single expression statement"),
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_value(value: &Expression) -> ::serde_json::Value {
+    use serde_json::{Map, Value};
+    use ast::decode_string;
+
+    match *value {
+        Expression::Literal(Literal::Null)            => Value::Null,
+        Expression::Literal(Literal::Boolean(value))  => Value::Bool(value),
+        Expression::Literal(Literal::String(string))  => Value::String(string.value.to_owned()),
+        Expression::Object(object)                    => {
+            let mut map = Map::new();
+
+            for property in &object.body {
+                if let Property::Literal { key, value } = property.item {
+                    let key = match key.item {
+                        PropertyKey::Literal(key) if key.starts_with(['"', '\'']) => decode_string(key).into_owned(),
+                        PropertyKey::Literal(key) => key.to_owned(),
+                        _ => continue,
+                    };
+
+                    map.insert(key, json_value(&value.item));
+                }
+            }
+
+            Value::Object(map)
+        },
+        Expression::Array(array) => {
+            let elements = array.elements().map(|element| element.map_or(Value::Null, |element| json_value(&element.item)));
+
+            Value::Array(elements.collect())
+        },
+        _ => json_number(value).map_or(Value::Null, |number| {
+            match number.fract() == 0.0 && number.abs() < (1u64 << 53) as f64 {
+                true  => Value::from(number as i64),
+                false => Value::from(number),
+            }
+        }),
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_number(value: &Expression) -> Option<f64> {
+    match *value {
+        Expression::Literal(Literal::Number(number)) => Some(number.value),
+        Expression::Identifier("Infinity")           => Some(f64::INFINITY),
+        Expression::Identifier("NaN")                => Some(f64::NAN),
+        Expression::Prefix(PrefixExpression { operator: OperatorKind::Subtraction, operand }) => json_number(&operand.item).map(|number| -number),
+        Expression::Prefix(PrefixExpression { operand, .. }) => json_number(&operand.item),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ast::StatementKind;
+
+    fn value_of<'ast>(module: &'ast Module<'ast>) -> &'ast Expression<'ast> {
+        match module.body().first_element().unwrap().item {
+            Statement::Expression(ref value) => &value.item,
+            _ => panic!("Expected an expression statement"),
+        }
+    }
+
+    #[test]
+    fn relaxed_values() {
+        let module = parse_relaxed("// config\n{ a: 1, 'b': [true, null,], \"c\": -0x10, d: { /* nested */ }, }").unwrap();
+
+        assert_eq!(module.body().iter().map(|statement| statement.kind()).collect::<Vec<_>>(), vec![StatementKind::Expression]);
+
+        let object = match *value_of(&module) {
+            Expression::Object(object) => object,
+            _ => panic!("Expected an object"),
+        };
+        let keys: Vec<_> = object.body.iter().map(|property| match property.item {
+            Property::Literal { key, .. } => key.item,
+            _ => panic!("Expected a literal property"),
+        }).collect();
+
+        assert_eq!(keys, vec![
+            PropertyKey::Literal("a"),
+            PropertyKey::Literal("'b'"),
+            PropertyKey::Literal("\"c\""),
+            PropertyKey::Literal("d"),
+        ]);
+
+        assert!(parse_relaxed("'single'").is_ok());
+        assert!(parse_relaxed("[+Infinity, -NaN, .5, 5., 1e3]").is_ok());
+        assert!(parse_relaxed("{ if: 1, true: 2 }").is_ok());
+    }
+
+    #[test]
+    fn not_relaxed_values() {
+        let sources = [
+            "",
+            "{ a: b }",
+            "{ a: 1 } + 1",
+            "[1,,2]",
+            "[1 2]",
+            "{ [a]: 1 }",
+            "{ 1: 2 }",
+            "{ a }",
+            "{ a() {} }",
+            "{ a: 1,, }",
+            "`template`",
+            "-'a'",
+            "f()",
+            "017",
+            "'\\07'",
+            "1; 2",
+        ];
+
+        for source in sources.iter() {
+            assert!(parse_relaxed(source).is_err(), "{:?} should not parse", source);
+        }
+    }
+
+    #[test]
+    fn relaxed_errors() {
+        let errors = parse_relaxed("{ a: 1, b: c }").err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].token, errors[0].start, errors[0].end), (Identifier, 11, 12));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn relaxed_json() {
+        let value = parse_relaxed_json("{ a: 1, 'b\\n': [true, null, 'x',], c: { d: -0x10, e: 1.5, f: +Infinity, g: NaN } }").unwrap();
+
+        assert_eq!(value, json!({
+            "a": 1,
+            "b\n": [true, null, "x"],
+            "c": { "d": -16, "e": 1.5, "f": null, "g": null },
+        }));
+    }
+}
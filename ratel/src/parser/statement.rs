@@ -1,20 +1,27 @@
-use toolshed::list::{ListBuilder, GrowableList};
+use toolshed::list::ListBuilder;
 use parser::{Parser, Parse, ANY, B0};
+use parser::validator::{Params, ForHead};
 use lexer::Token::*;
 use lexer::Asi;
-use ast::{Node, NodeList, Declarator, DeclarationKind};
-use ast::{Statement, StatementNode, Expression, ExpressionNode, Class, Function, Pattern};
+use ast::{Node, NodeList, Declarator, DeclarationKind, Terminator};
+use ast::{Statement, StatementNode, Expression, ExpressionNode, Class, Function, MandatoryName, Pattern};
 use ast::expression::BinaryExpression;
 use ast::statement::{ThrowStatement, ContinueStatement, BreakStatement, ReturnStatement};
 use ast::statement::{TryStatement, CatchClause, IfStatement, WhileStatement, DoStatement};
 use ast::statement::{DeclarationStatement, ForStatement, ForInStatement, ForOfStatement};
 use ast::statement::{SwitchStatement, SwitchCase, LabeledStatement, ForInit};
+use ast::statement::{ImportStatement, ImportSpecifier, ExportStatement, ExportSpecifier};
+use ast::{StringLiteral, IdentifierNode};
 use ast::OperatorKind::*;
+use error::ErrorKind;
+use module::{Features, LineColumn};
+#[cfg(feature = "trace")]
+use parser::trace::Production;
 
 
 type StatementHandler = for<'ast> fn(&mut Parser<'ast>) -> StatementNode<'ast>;
 
-static STMT_HANDLERS: [StatementHandler; 108] = [
+static STMT_HANDLERS: [StatementHandler; 109] = [
     ____, EMPT, ____, ____, PRN,  ____, ARR,  ____, BLCK, ____, ____, NEW,
 //  EOF   ;     :     ,     (     )     [     ]     {     }     =>    NEW
 
@@ -33,14 +40,17 @@ static STMT_HANDLERS: [StatementHandler; 108] = [
     CONS, BRK,  DO,   ____, ____, ____, ____, CLAS, ____, RET,  WHL,  ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, CONT, FOR,  SWCH, ____, ____, FUNC, THIS, ____, IF,   THRW,
+    SUPR, ____, CONT, FOR,  SWCH, YILD, ____, FUNC, THIS, ____, IF,   THRW,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
-    ____, TRY,  ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
-//  IMPRT TRY   STATI TRUE  FALSE NULL  UNDEF STR   NUM   BIN   REGEX ENUM
+    ____, TRY,  ____, TRUE, FALS, NULL, STR,  NUM,  BIN,  ____, ____, ____,
+//  IMPRT TRY   STATI TRUE  FALSE NULL  STR   NUM   BIN   REGEX ENUM  IMPL
 
-    ____, ____, ____, ____, ____, ____, LABL, ____, TPLE, TPLS, ____, ____,
-//  IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+    ____, ____, ____, ____, ____, LABL, ____, TPLE, TPLS, ____, ____, ____,
+//  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E ?.
+
+    ____,
+//  #PRIV
 ];
 
 
@@ -57,7 +67,7 @@ macro_rules! create_handlers {
 
 /// Shared expression handlers that produce StatementNode<'ast>
 use parser::expression::handlers::{
-    PRN, ARR, OP, NEW, REG, THIS, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, TPLS, TPLE
+    PRN, ARR, OP, NEW, REG, YILD, THIS, SUPR, TRUE, FALS, NULL, STR, NUM, BIN, TPLS, TPLE
 };
 
 create_handlers! {
@@ -127,7 +137,7 @@ impl<'ast> Parse<'ast> for SwitchCase<'ast> {
         let mut end = par.lexer.end();
         expect!(par, Colon);
 
-        let builder = GrowableList::new();
+        let mut consequent = None;
 
         loop {
             match par.lexer.token {
@@ -135,14 +145,14 @@ impl<'ast> Parse<'ast> for SwitchCase<'ast> {
                 _ => {
                     let statement = par.statement();
                     end = statement.end;
-                    builder.push(par.arena, statement);
+                    par.push_item(&mut consequent, statement);
                 }
             }
         }
 
         par.alloc_at_loc(start, end, SwitchCase {
             test,
-            consequent: builder.as_list()
+            consequent: consequent.map_or(NodeList::empty(), |builder| builder.as_list()),
         })
     }
 }
@@ -150,20 +160,84 @@ impl<'ast> Parse<'ast> for SwitchCase<'ast> {
 impl<'ast> Parser<'ast> {
     #[inline]
     pub fn statement(&mut self) -> StatementNode<'ast> {
-        unsafe { (*(&STMT_HANDLERS as *const StatementHandler).offset(self.lexer.token as isize))(self) }
+        self.positioned();
+
+        trace!(self, trace_enter, Production::Statement);
+        let statement = unsafe { (*(&STMT_HANDLERS as *const StatementHandler).offset(self.lexer.token as isize))(self) };
+        trace!(self, trace_leave);
+
+        statement
+    }
+
+    /// Statement at the top level of a module, where `import` declarations
+    /// are also allowed.
+    #[inline]
+    pub fn module_item(&mut self) -> StatementNode<'ast> {
+        match self.lexer.token {
+            Import => {
+                self.positioned();
+                self.import_statement()
+            },
+            Export => {
+                self.positioned();
+                self.export_statement()
+            },
+            _      => self.statement(),
+        }
+    }
+
+    /// Record the line and column of the statement starting at the current
+    /// token, if requested by `ParserOptions`. Statements start in order,
+    /// so the source is only counted through once.
+    #[inline]
+    fn positioned(&mut self) {
+        if !self.options.positions {
+            return;
+        }
+
+        let start = self.lexer.start();
+        let (offset, position) = match self.position_cursor {
+            (offset, _) if offset > start => (0, LineColumn::default()),
+            cursor => cursor,
+        };
+        let position = position.advance(self.lexer.slice(offset, start));
+
+        self.position_cursor = (start, position);
+        self.positions.push((start, position));
     }
 
     /// Expect a semicolon to terminate a statement. Will assume a semicolon
     /// following the ASI rules.
     #[inline]
-    fn expect_semicolon(&mut self) {
+    fn expect_semicolon(&mut self) -> Terminator {
         match self.asi() {
-            Asi::ExplicitSemicolon => self.lexer.consume(),
-            Asi::ImplicitSemicolon => {},
-            Asi::NoSemicolon       => self.error(),
+            Asi::ExplicitSemicolon => {
+                self.lexer.consume();
+
+                Terminator::Semicolon
+            },
+            Asi::ImplicitSemicolon => match self.lexer.token {
+                BraceClose => Terminator::BraceClose,
+                _          => Terminator::Asi,
+            },
+            Asi::NoSemicolon => {
+                self.error::<()>();
+
+                Terminator::Asi
+            },
         }
     }
 
+    /// Record the `Terminator` of a statement, if requested by `ParserOptions`
+    #[inline]
+    fn terminated(&mut self, statement: StatementNode<'ast>, terminator: Terminator) -> StatementNode<'ast> {
+        if self.options.terminators {
+            self.terminators.push((statement.start, statement.end, terminator));
+        }
+
+        statement
+    }
+
     #[inline]
     pub fn block_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -182,8 +256,10 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     pub fn wrap_expression(&mut self, expression: ExpressionNode<'ast>) -> StatementNode<'ast> {
-        self.expect_semicolon();
-        self.alloc_at_loc(expression.start, expression.end, expression)
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(expression.start, expression.end, expression);
+
+        self.terminated(statement, terminator)
     }
 
     #[inline]
@@ -193,33 +269,104 @@ impl<'ast> Parser<'ast> {
 
         self.lexer.consume();
 
+        if label == "async" && self.lexer.token == Function && self.asi() == Asi::NoSemicolon {
+            self.lexer.consume();
+
+            return self.function_declaration(start, true);
+        }
+
+        if label == "await" && self.is_async {
+            let expression = self.await_expression(start);
+
+            return self.expression_statement(expression);
+        }
+
         if self.lexer.token == Colon {
             self.lexer.consume();
 
+            let label = self.alloc_at_loc(start, end, label);
             let body = self.statement();
 
+            // Labeled function declarations are allowed by Annex B.3.2
+            if let Statement::Function(_) = body.item {
+                if self.strict {
+                    self.function_in_statement_position(body);
+                }
+            }
+
             return self.alloc_at_loc(start, body.end, LabeledStatement {
                 label,
                 body,
             });
         }
 
-        let expression = self.alloc_at_loc(start, end, label);
+        let expression = match label {
+            "async" if self.asi() == Asi::NoSemicolon => self.async_expression(start, end),
+            _ => self.alloc_at_loc(start, end, label),
+        };
         let expression = self.nested_expression::<ANY>(expression);
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, expression.end, expression);
 
-        self.expect_semicolon();
-
-        self.alloc_at_loc(start, expression.end, expression)
+        self.terminated(statement, terminator)
     }
 
     #[inline]
     pub fn function_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
-        let function = Function::parse(self);
+
+        self.function_declaration(start, false)
+    }
+
+    /// Parse a function declaration after the `function` keyword, starting
+    /// at `start`, which is that of `async` for `async` functions.
+    #[inline]
+    fn function_declaration(&mut self, start: u32, is_async: bool) -> StatementNode<'ast> {
+        let sloppy = !self.strict;
+        let function: Function<MandatoryName> = self.function(Params::Function, is_async);
+
+        if sloppy && self.blocks > 0 && !function.generator && !function.is_async {
+            self.block_functions.push((start, function.body.end));
+        }
 
         self.alloc_at_loc(start, function.body.end, function)
     }
 
+    /// Parse the body of a loop, where function declarations are never allowed.
+    #[inline]
+    fn loop_body(&mut self) -> StatementNode<'ast> {
+        let body = self.statement();
+
+        if let Statement::Function(_) = body.item {
+            self.function_in_statement_position(body);
+        }
+
+        body
+    }
+
+    /// Parse the body of an `if` statement. Function declarations are allowed
+    /// there by Annex B.3.4 in sloppy mode, as if they were wrapped in a block.
+    #[inline]
+    fn if_body(&mut self) -> StatementNode<'ast> {
+        self.blocks += 1;
+
+        let body = self.statement();
+
+        self.blocks -= 1;
+
+        if let Statement::Function(function) = body.item {
+            if self.strict || function.generator || function.is_async {
+                self.function_in_statement_position(body);
+            }
+        }
+
+        body
+    }
+
+    fn function_in_statement_position(&mut self, body: StatementNode<'ast>) {
+        self.error_at(ErrorKind::FunctionInStatementPosition, Function, "function", body.start, body.end);
+    }
+
     #[inline]
     fn class_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -230,23 +377,34 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     pub fn variable_declaration_statement(&mut self, kind: DeclarationKind) -> StatementNode<'ast> {
+        if kind != DeclarationKind::Var {
+            self.features.insert(Features::LEXICAL_DECLARATIONS);
+        }
+
         let start = self.lexer.start_then_consume();
         let declarators = self.variable_declarators();
-        let end = self.lexer.end();
+
+        // Without a semicolon the declaration ends with its last declarator,
+        // not with the token following it
+        let end = match self.asi() {
+            Asi::ExplicitSemicolon => self.lexer.end(),
+            _ => declarators.iter().last().map_or(start, |declarator| declarator.end),
+        };
         let declaration = self.alloc_at_loc(start, end, DeclarationStatement {
             kind: kind,
             declarators
         });
+        let terminator = self.expect_semicolon();
 
-        self.expect_semicolon();
-
-        declaration
+        self.terminated(declaration, terminator)
     }
 
     #[inline]
     pub fn variable_declarator(&mut self) -> Node<'ast, Declarator<'ast>> {
         let id = Pattern::parse(self);
 
+        self.validate_binding(id);
+
         let (init, end) = match self.lexer.token {
             OperatorAssign => {
                 self.lexer.consume();
@@ -292,20 +450,17 @@ impl<'ast> Parser<'ast> {
                 let expression = self.expression::<ANY>();
                 end = expression.end;
 
-                self.expect_semicolon();
-
                 Some(expression)
             }
 
-            Asi::ImplicitSemicolon => None,
-            Asi::ExplicitSemicolon => {
-                self.lexer.consume();
-
-                None
-            }
+            Asi::ImplicitSemicolon |
+            Asi::ExplicitSemicolon => None,
         };
 
-        self.alloc_at_loc(start, end, ReturnStatement { value })
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, end, ReturnStatement { value });
+
+        self.terminated(statement, terminator)
     }
 
     #[inline]
@@ -314,22 +469,20 @@ impl<'ast> Parser<'ast> {
         self.lexer.consume();
 
         let label = match self.asi() {
-            Asi::ExplicitSemicolon => {
-                self.lexer.consume();
-                None
-            },
+            Asi::ExplicitSemicolon |
             Asi::ImplicitSemicolon => None,
             Asi::NoSemicolon => {
                 let label = self.identifier();
                 end = label.end;
 
-                self.expect_semicolon();
-
                 Some(label)
             }
         };
 
-        self.alloc_at_loc(start, end, BreakStatement { label })
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, end, BreakStatement { label });
+
+        self.terminated(statement, terminator)
     }
 
     #[inline]
@@ -338,32 +491,30 @@ impl<'ast> Parser<'ast> {
         self.lexer.consume();
 
         let label = match self.asi() {
-            Asi::ExplicitSemicolon => {
-                self.lexer.consume();
-                None
-            },
+            Asi::ExplicitSemicolon |
             Asi::ImplicitSemicolon => None,
             Asi::NoSemicolon => {
                 let label = self.identifier();
                 end = label.end;
 
-                self.expect_semicolon();
-
                 Some(label)
             }
         };
 
-        self.alloc_at_loc(start, end, ContinueStatement { label })
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, end, ContinueStatement { label });
+
+        self.terminated(statement, terminator)
     }
 
     #[inline]
     pub fn throw_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
         let value = self.expression::<ANY>();
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, value.end, ThrowStatement { value });
 
-        self.expect_semicolon();
-
-        self.alloc_at_loc(start, value.end, ThrowStatement { value })
+        self.terminated(statement, terminator)
     }
 
     #[inline]
@@ -373,16 +524,14 @@ impl<'ast> Parser<'ast> {
 
         let (handler, finalizer, end) = match self.lexer.token {
             Catch => {
-                let start = self.lexer.start_then_consume();
-                expect!(self, ParenOpen);
-                let param = Pattern::parse(self);
-                expect!(self, ParenClose);
-                let body = self.block();
+                let mut handler = self.catch_clause();
 
-                let handler = self.alloc_at_loc(start, body.end, CatchClause {
-                    param,
-                    body,
-                });
+                while self.lexer.token == Catch {
+                    match self.plugin(|plugin, par| plugin.catch_handler(par, handler)) {
+                        Some(merged) => handler = merged,
+                        None         => break,
+                    }
+                }
 
                 match self.lexer.token {
                     Finally => {
@@ -414,6 +563,30 @@ impl<'ast> Parser<'ast> {
         })
     }
 
+    /// Parse a `catch` clause, leaving the tokens after its parameter other
+    /// than `)` to the plugins.
+    pub fn catch_clause(&mut self) -> Node<'ast, CatchClause<'ast>> {
+        let start = self.lexer.start_then_consume();
+        expect!(self, ParenOpen);
+        let param = Pattern::parse(self);
+
+        self.validate_binding(param);
+
+        if self.lexer.token != ParenClose {
+            if let Some(handler) = self.plugin(|plugin, par| plugin.catch_param(par, start, param)) {
+                return handler;
+            }
+        }
+
+        expect!(self, ParenClose);
+        let body = self.block();
+
+        self.alloc_at_loc(start, body.end, CatchClause {
+            param,
+            body,
+        })
+    }
+
     #[inline]
     pub fn if_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -421,12 +594,12 @@ impl<'ast> Parser<'ast> {
         let test = self.expression::<ANY>();
         expect!(self, ParenClose);
 
-        let consequent = self.statement();
+        let consequent = self.if_body();
 
         let (alternate, end) = match self.lexer.token {
             Else => {
                 self.lexer.consume();
-                let alternate = self.statement();
+                let alternate = self.if_body();
                 (Some(alternate), alternate.end)
             },
             _ => (None, consequent.end)
@@ -446,7 +619,7 @@ impl<'ast> Parser<'ast> {
         let test = self.expression::<ANY>();
         expect!(self, ParenClose);
 
-        let body = self.statement();
+        let body = self.loop_body();
 
         self.alloc_at_loc(start, body.end, WhileStatement {
             test,
@@ -457,7 +630,7 @@ impl<'ast> Parser<'ast> {
     #[inline]
     pub fn do_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
-        let body = self.statement();
+        let body = self.loop_body();
         expect!(self, While);
         expect!(self, ParenOpen);
         let test = self.expression::<ANY>();
@@ -472,6 +645,10 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn for_init(&mut self, kind: DeclarationKind) -> Node<'ast, ForInit<'ast>> {
+        if kind != DeclarationKind::Var {
+            self.features.insert(Features::LEXICAL_DECLARATIONS);
+        }
+
         let start = self.lexer.start_then_consume();
         let declarators = self.variable_declarators();
         let end = self.lexer.end();
@@ -507,6 +684,8 @@ impl<'ast> Parser<'ast> {
                 }) = init.item {
                     let left = self.alloc_at_loc(left.start, left.end, left);
 
+                    self.validate_for_head(left, ForHead::In);
+
                     return self.for_in_statement_from_parts(start, left, right);
                 }
 
@@ -514,18 +693,30 @@ impl<'ast> Parser<'ast> {
             },
         };
 
-        if let Some(ref init) = init {
+        if let Some(init) = init {
             match self.lexer.token {
                 OperatorIn => {
                     self.lexer.consume();
-                    return self.for_in_statement(start, *init);
+                    self.validate_for_head(init, ForHead::In);
+
+                    return self.for_in_statement(start, init);
                 },
                 Identifier if self.lexer.token_as_str() == "of" => {
                     self.lexer.consume();
-                    return self.for_of_statement(start, *init);
+                    self.validate_for_head(init, ForHead::Of);
+
+                    return self.for_of_statement(start, init);
+                },
+                ParenClose => if let Some((left, right)) = self.initialized_for_in(init) {
+                    self.validate_for_head(left, ForHead::In);
+
+                    return self.for_in_statement_from_parts(start, left, right);
                 },
-                _ => expect!(self, Semicolon)
+                _ => {}
             }
+
+            expect!(self, Semicolon);
+            self.validate_for_head(init, ForHead::Init);
         }
 
         let test = match self.lexer.token {
@@ -554,7 +745,7 @@ impl<'ast> Parser<'ast> {
             }
         };
 
-        let body = self.statement();
+        let body = self.loop_body();
 
         self.alloc_at_loc(start, body.end, ForStatement {
             init,
@@ -564,10 +755,48 @@ impl<'ast> Parser<'ast> {
         })
     }
 
+    /// Split the declaration of `for (var a = b in c)`, the initializer of
+    /// the last declarator having been parsed along with `in c`, into the
+    /// declaration with `b` as the initializer, and `c`.
+    fn initialized_for_in(&mut self, init: Node<'ast, ForInit<'ast>>) -> Option<(Node<'ast, ForInit<'ast>>, ExpressionNode<'ast>)> {
+        let declaration = match init.item {
+            ForInit::Declaration(declaration) => declaration,
+            ForInit::Expression(_) => return None,
+        };
+
+        let count = declaration.declarators.iter().count();
+        let last = declaration.declarators.iter().last()?;
+
+        let (left, right) = match last.init?.item {
+            Expression::Binary(BinaryExpression { operator: In, left, right, .. }) => (left, right),
+            _ => return None,
+        };
+
+        let mut declarators = None;
+
+        for declarator in declaration.declarators.iter().take(count - 1) {
+            self.push_item(&mut declarators, *declarator);
+        }
+
+        let declarator = self.alloc_at_loc(last.start, left.end, Declarator {
+            id: last.id,
+            init: Some(left),
+        });
+
+        self.push_item(&mut declarators, declarator);
+
+        let left = self.alloc_at_loc(init.start, left.end, DeclarationStatement {
+            kind: declaration.kind,
+            declarators: declarators.map_or(NodeList::empty(), |builder| builder.as_list()),
+        });
+
+        Some((left, right))
+    }
+
     fn for_in_statement_from_parts(&mut self, start: u32, left: Node<'ast, ForInit<'ast>>, right: ExpressionNode<'ast>) -> StatementNode<'ast> {
         expect!(self, ParenClose);
 
-        let body = self.statement();
+        let body = self.loop_body();
 
         self.alloc_at_loc(start, body.end, ForInStatement {
             left,
@@ -581,7 +810,7 @@ impl<'ast> Parser<'ast> {
 
         expect!(self, ParenClose);
 
-        let body = self.statement();
+        let body = self.loop_body();
 
         self.alloc_at_loc(start, body.end, ForInStatement {
             left,
@@ -591,11 +820,13 @@ impl<'ast> Parser<'ast> {
     }
 
     fn for_of_statement(&mut self, start: u32, left: Node<'ast, ForInit<'ast>>) -> StatementNode<'ast> {
+        self.features.insert(Features::FOR_OF);
+
         let right = self.expression::<ANY>();
 
         expect!(self, ParenClose);
 
-        let body = self.statement();
+        let body = self.loop_body();
 
         self.alloc_at_loc(start, body.end, ForOfStatement {
             left,
@@ -619,14 +850,324 @@ impl<'ast> Parser<'ast> {
             cases
         })
     }
+
+    /// Expect a contextual keyword such as `from` or `as`, which are lexed
+    /// as identifiers.
+    #[inline]
+    fn expect_word(&mut self, word: &str) {
+        match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == word => self.lexer.consume(),
+            _ => self.error(),
+        }
+    }
+
+    fn import_statement(&mut self) -> StatementNode<'ast> {
+        let start = self.lexer.start_then_consume();
+        let mut specifiers = None;
+
+        self.features.insert(Features::MODULES);
+
+        if self.lexer.token != LiteralString {
+            if self.lexer.token == Identifier {
+                let local = self.identifier();
+
+                let specifier = self.alloc_at_loc(local.start, local.end, ImportSpecifier::Default(local));
+
+                self.push_item(&mut specifiers, specifier);
+
+                if self.lexer.token == Comma {
+                    self.lexer.consume();
+                } else {
+                    self.expect_word("from");
+                }
+            }
+
+            match self.lexer.token {
+                OperatorMultiplication => {
+                    let start = self.lexer.start_then_consume();
+                    self.expect_word("as");
+
+                    let local = self.identifier();
+
+                    let specifier = self.alloc_at_loc(start, local.end, ImportSpecifier::Namespace(local));
+
+                    self.push_item(&mut specifiers, specifier);
+                    self.expect_word("from");
+                },
+                BraceOpen => {
+                    self.lexer.consume();
+
+                    while self.lexer.token != BraceClose {
+                        let specifier = self.import_specifier();
+
+                        self.push_item(&mut specifiers, specifier);
+
+                        match self.lexer.token {
+                            Comma      => self.lexer.consume(),
+                            BraceClose => break,
+                            _          => {
+                                self.error::<()>();
+                                break;
+                            }
+                        }
+                    }
+
+                    expect!(self, BraceClose);
+                    self.expect_word("from");
+                },
+                _ if specifiers.is_none() => self.error(),
+                _ => {},
+            }
+        }
+
+        let source = self.module_source();
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, source.end, ImportStatement {
+            specifiers: specifiers.map_or(NodeList::empty(), |builder| builder.as_list()),
+            source,
+        });
+
+        self.terminated(statement, terminator)
+    }
+
+    /// `a` or `a as b` within the braces of an `import`, the imported name
+    /// can be any word if it's renamed.
+    fn import_specifier(&mut self) -> Node<'ast, ImportSpecifier<'ast>> {
+        let imported = match self.lexer.token {
+            Identifier => self.identifier(),
+            _ => {
+                let word = self.word();
+
+                // Reserved words can't be bound, so must be renamed
+                if self.lexer.token != Identifier || self.lexer.token_as_str() != "as" {
+                    self.error::<()>();
+                }
+
+                word
+            },
+        };
+
+        let local = match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == "as" => {
+                self.lexer.consume();
+                self.identifier()
+            },
+            _ => imported,
+        };
+
+        self.alloc_at_loc(imported.start, local.end, ImportSpecifier::Named {
+            imported,
+            local,
+        })
+    }
+
+    fn export_statement(&mut self) -> StatementNode<'ast> {
+        let start = self.lexer.start_then_consume();
+
+        self.features.insert(Features::MODULES);
+
+        match self.lexer.token {
+            DeclarationVar | DeclarationLet | DeclarationConst | Function | Class => {
+                let declaration = self.statement();
+
+                self.alloc_at_loc(start, declaration.end, ExportStatement::Declaration(declaration))
+            },
+            Identifier if self.lexer.token_as_str() == "async" => {
+                let async_start = self.lexer.start_then_consume();
+
+                if self.lexer.token == Function && self.asi() == Asi::NoSemicolon {
+                    self.lexer.consume();
+                } else {
+                    self.error::<()>();
+                }
+
+                let declaration = self.function_declaration(async_start, true);
+
+                self.alloc_at_loc(start, declaration.end, ExportStatement::Declaration(declaration))
+            },
+            Default => {
+                self.lexer.consume();
+                self.export_default(start)
+            },
+            BraceOpen => self.export_named(start),
+            OperatorMultiplication => {
+                self.lexer.consume();
+
+                let exported = match self.lexer.token {
+                    Identifier if self.lexer.token_as_str() == "as" => {
+                        self.lexer.consume();
+
+                        Some(self.word())
+                    },
+                    _ => None,
+                };
+
+                self.expect_word("from");
+
+                let source = self.module_source();
+                let terminator = self.expect_semicolon();
+                let statement = self.alloc_at_loc(start, source.end, ExportStatement::All {
+                    exported,
+                    source,
+                });
+
+                self.terminated(statement, terminator)
+            },
+            _ => {
+                self.error::<()>();
+                self.alloc_at_loc(start, start, Statement::Empty)
+            },
+        }
+    }
+
+    /// What follows `export default` at `start`, a function or class that
+    /// may be anonymous, or else an expression.
+    fn export_default(&mut self, start: u32) -> StatementNode<'ast> {
+        let expression = match self.lexer.token {
+            Function | Class => {
+                let declaration = match self.lexer.token {
+                    Function => self.function_expression(),
+                    _        => self.class_expression(),
+                };
+
+                return self.alloc_at_loc(start, declaration.end, ExportStatement::DefaultDeclaration(declaration));
+            },
+            Identifier if self.lexer.token_as_str() == "async" => {
+                let (async_start, async_end) = self.lexer.loc();
+
+                self.lexer.consume();
+
+                if self.lexer.token == Function && self.asi() == Asi::NoSemicolon {
+                    let declaration = self.async_function_expression(async_start);
+
+                    return self.alloc_at_loc(start, declaration.end, ExportStatement::DefaultDeclaration(declaration));
+                }
+
+                let expression = match self.asi() {
+                    Asi::NoSemicolon => self.async_expression(async_start, async_end),
+                    _                => self.alloc_at_loc(async_start, async_end, "async"),
+                };
+
+                self.nested_expression::<B0>(expression)
+            },
+            _ => self.expression::<B0>(),
+        };
+
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, expression.end, ExportStatement::Default(expression));
+
+        self.terminated(statement, terminator)
+    }
+
+    /// `export { a, b as c }` at `start`, the local names can be any word if
+    /// they are exported from another module.
+    fn export_named(&mut self, start: u32) -> StatementNode<'ast> {
+        let mut specifiers = None;
+        let mut reserved = None;
+
+        self.lexer.consume();
+
+        while self.lexer.token != BraceClose {
+            if self.lexer.token != Identifier && reserved.is_none() {
+                let (start, end) = self.lexer.loc();
+
+                reserved = Some((self.lexer.token, self.lexer.token_as_str(), start, end));
+            }
+
+            let local = self.word();
+            let exported = match self.lexer.token {
+                Identifier if self.lexer.token_as_str() == "as" => {
+                    self.lexer.consume();
+                    self.word()
+                },
+                _ => local,
+            };
+
+            let specifier = self.alloc_at_loc(local.start, exported.end, ExportSpecifier {
+                local,
+                exported,
+            });
+
+            self.push_item(&mut specifiers, specifier);
+
+            match self.lexer.token {
+                Comma      => self.lexer.consume(),
+                BraceClose => break,
+                _          => {
+                    self.error::<()>();
+                    break;
+                }
+            }
+        }
+
+        let mut end = self.lexer.end();
+        expect!(self, BraceClose);
+
+        let source = match self.lexer.token {
+            Identifier if self.lexer.token_as_str() == "from" => {
+                self.lexer.consume();
+
+                let source = self.module_source();
+                end = source.end;
+
+                Some(source)
+            },
+            _ => {
+                // Only bindings of the module can be exported without `from`
+                if let Some((token, raw, start, end)) = reserved {
+                    self.error_at(ErrorKind::UnexpectedToken, token, raw, start, end);
+                }
+
+                None
+            },
+        };
+
+        let terminator = self.expect_semicolon();
+        let statement = self.alloc_at_loc(start, end, ExportStatement::Named {
+            specifiers: specifiers.map_or(NodeList::empty(), |builder| builder.as_list()),
+            source,
+        });
+
+        self.terminated(statement, terminator)
+    }
+
+    /// Any word, reserved words included, as the name of an import or export.
+    fn word(&mut self) -> IdentifierNode<'ast> {
+        if !self.lexer.token.is_word() {
+            return self.error();
+        }
+
+        let word = self.lexer.token_as_str();
+        let word = self.alloc_in_loc(word);
+
+        self.lexer.consume();
+        word
+    }
+
+    /// String literal naming the module an `import` or `export` refers to.
+    fn module_source(&mut self) -> Node<'ast, StringLiteral<'ast>> {
+        match self.lexer.token {
+            LiteralString => {
+                let raw = self.lexer.token_as_str();
+                let raw = self.literal(raw);
+                let value = self.string_value(raw);
+                let source = self.alloc_in_loc(StringLiteral { value, raw });
+
+                self.lexer.consume();
+                source
+            },
+            _ => self.error(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use parser::parse;
-    use parser::mock::Mock;
-    use ast::{NodeList, Literal, Function, Class, OperatorKind, BlockStatement};
+    use testing::Mock;
+    use error::ErrorKind;
+    use ast::{NodeList, Literal, NumberLiteral, StringLiteral, Function, Class, OperatorKind, BlockStatement};
     use ast::expression::*;
 
     #[test]
@@ -637,7 +1178,7 @@ mod test {
         let expected = mock.list([
             BlockStatement {
                 body: mock.list([
-                    mock.ptr(Literal::True)
+                    mock.ptr(Literal::Boolean(true))
                 ])
             }
         ]);
@@ -652,10 +1193,10 @@ mod test {
 
         let expected = mock.list([
             LabeledStatement {
-                label: "foobar",
+                label: mock.ptr("foobar"),
                 body: mock.ptr(BlockStatement {
                     body: mock.list([
-                        mock.ptr(Literal::True)
+                        mock.ptr(Literal::Boolean(true))
                     ])
                 })
             }
@@ -671,7 +1212,7 @@ mod test {
 
         let expected = mock.list([
             IfStatement {
-                test: mock.ptr(Literal::True),
+                test: mock.ptr(Literal::Boolean(true)),
                 consequent: mock.ptr(mock.ptr("foo")),
                 alternate: None
             }
@@ -687,7 +1228,7 @@ mod test {
 
         let expected = mock.list([
             IfStatement {
-                test: mock.ptr(Literal::True),
+                test: mock.ptr(Literal::Boolean(true)),
                 consequent: mock.ptr(mock.ptr("foo")),
                 alternate: Some(mock.ptr(BlockStatement {
                     body: mock.list([
@@ -707,7 +1248,7 @@ mod test {
 
         let expected = mock.list([
             WhileStatement {
-                test: mock.ptr(Literal::True),
+                test: mock.ptr(Literal::Boolean(true)),
                 body: mock.ptr(mock.ptr("foo"))
             }
         ]);
@@ -722,7 +1263,7 @@ mod test {
 
         let expected = mock.list([
             WhileStatement {
-                test: mock.ptr(Literal::True),
+                test: mock.ptr(Literal::Boolean(true)),
                 body: mock.ptr(BlockStatement {
                     body: mock.list([
                         mock.ptr("foo")
@@ -742,7 +1283,7 @@ mod test {
         let expected = mock.list([
             DoStatement {
                 body: mock.ptr(mock.ptr("foo")),
-                test: mock.ptr(Literal::True)
+                test: mock.ptr(Literal::Boolean(true))
             }
         ]);
 
@@ -784,7 +1325,7 @@ mod test {
 
         let expected = mock.list([
             ThrowStatement {
-                value: mock.ptr(Literal::String("'3'")),
+                value: mock.ptr(Literal::String(StringLiteral { value: "3", raw: "'3'" })),
             }
         ]);
 
@@ -888,6 +1429,18 @@ mod test {
         assert_eq!(parse(src).unwrap().body(), expected);
     }
 
+    #[test]
+    fn undefined_bindings() {
+        // `undefined` is a plain identifier, which can be shadowed
+        assert!(parse("var undefined = 1;").is_ok());
+        assert!(parse("function f(undefined) {}").is_ok());
+        assert!(parse("function undefined() {}").is_ok());
+        assert!(parse("undefined => undefined;").is_ok());
+        assert!(parse("try {} catch (undefined) {}").is_ok());
+        assert!(parse("undefined: for (;;) { break undefined; }").is_ok());
+        assert!(parse("undefined = 1;").is_ok());
+    }
+
     #[test]
     fn variable_declaration_statement_spread() {
         let src = "const a = {...foo}";
@@ -932,8 +1485,8 @@ mod test {
                         }),
                         init: Some(mock.ptr(ArrayExpression {
                             body: mock.list([
-                                Expression::Literal(Literal::Number("1")),
-                                Expression::Literal(Literal::Number("2")),
+                                Expression::Literal(Literal::Number(NumberLiteral::new("1"))),
+                                Expression::Literal(Literal::Number(NumberLiteral::new("2"))),
                             ])
                         })),
                     },
@@ -1150,6 +1703,32 @@ mod test {
         assert_eq!(parse(src).unwrap().body(), expected);
     }
 
+    #[test]
+    fn for_in_statement_with_initializer() {
+        let src = "for (var b = 1 in c) {}";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            ForInStatement {
+                left: mock.ptr(DeclarationStatement {
+                    kind: DeclarationKind::Var,
+                    declarators: mock.list([
+                        Declarator {
+                            id: mock.ptr(Pattern::Identifier("b")),
+                            init: Some(mock.number("1")),
+                        }
+                    ]),
+                }),
+                right: mock.ptr("c"),
+                body: mock.ptr(BlockStatement {
+                    body: NodeList::empty()
+                })
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
     #[test]
     fn function_statement() {
         let src = "function foo() {}";
@@ -1159,6 +1738,7 @@ mod test {
             Function {
                 name: mock.name("foo"),
                 generator: false,
+                is_async: false,
                 params: NodeList::empty(),
                 body: mock.empty_block(),
             }
@@ -1226,13 +1806,13 @@ mod test {
                         ])
                     },
                     SwitchCase {
-                        test: Some(mock.ptr(Expression::Literal(Literal::String("\"1\"")))),
+                        test: Some(mock.ptr(Expression::Literal(Literal::String(StringLiteral { value: "1", raw: "\"1\"" })))),
                         consequent: NodeList::empty()
                     },
                     SwitchCase {
                         test: None,
                         consequent: mock.list([
-                            ReturnStatement { value: Some(mock.ptr(Expression::Literal(Literal::False))) }
+                            ReturnStatement { value: Some(mock.ptr(Expression::Literal(Literal::Boolean(false)))) }
                         ])
                     },
                 ])
@@ -1240,4 +1820,230 @@ mod test {
         ]);
         assert_eq!(parse(src).unwrap().body(), expected);
     }
+
+    #[test]
+    fn import_statement() {
+        let src = r#"
+        import "a";
+        import b from "c";
+        import * as d from "e";
+        import f, { g, default as h, i as j } from "k";
+        "#;
+        let mock = Mock::new();
+        let g = mock.ptr("g");
+        let source = |raw: &'static str| mock.ptr(StringLiteral { value: &raw[1..raw.len() - 1], raw });
+
+        let expected = mock.list([
+            ImportStatement {
+                specifiers: NodeList::empty(),
+                source: source(r#""a""#),
+            },
+            ImportStatement {
+                specifiers: mock.list([ImportSpecifier::Default(mock.ptr("b"))]),
+                source: source(r#""c""#),
+            },
+            ImportStatement {
+                specifiers: mock.list([ImportSpecifier::Namespace(mock.ptr("d"))]),
+                source: source(r#""e""#),
+            },
+            ImportStatement {
+                specifiers: mock.list([
+                    ImportSpecifier::Default(mock.ptr("f")),
+                    ImportSpecifier::Named { imported: g, local: g },
+                    ImportSpecifier::Named { imported: mock.ptr("default"), local: mock.ptr("h") },
+                    ImportSpecifier::Named { imported: mock.ptr("i"), local: mock.ptr("j") },
+                ]),
+                source: source(r#""k""#),
+            },
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn import_statement_default_and_namespace() {
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            ImportStatement {
+                specifiers: mock.list([
+                    ImportSpecifier::Default(mock.ptr("a")),
+                    ImportSpecifier::Namespace(mock.ptr("b")),
+                ]),
+                source: mock.ptr(StringLiteral { value: "c", raw: "'c'" }),
+            },
+        ]);
+
+        assert_eq!(parse("import a, * as b from 'c'").unwrap().body(), expected);
+    }
+
+    #[test]
+    fn import_statement_errors() {
+        assert!(parse("import { default } from 'a';").is_err());
+        assert!(parse("import { a as default } from 'a';").is_err());
+        assert!(parse("import a 'b';").is_err());
+        assert!(parse("import * from 'a';").is_err());
+        assert!(parse("import a from b;").is_err());
+        assert!(parse("import a, from 'b';").is_err());
+        assert!(parse("import {} from 'a'; import { a, } from 'b';").is_ok());
+    }
+
+    #[test]
+    fn import_statement_only_at_top_level() {
+        assert!(parse("{ import a from 'b'; }").is_err());
+        assert!(parse("function f() { import a from 'b'; }").is_err());
+    }
+
+    #[test]
+    fn export_declaration_statement() {
+        let module = parse("export var a = 1; export function b() {} export async function c() {} export class D {}").unwrap();
+        let declarations = parse("var a = 1; function b() {} async function c() {} class D {}").unwrap();
+
+        let exported = module.body().iter().map(|statement| match statement.item {
+            Statement::Export(ExportStatement::Declaration(declaration)) => declaration.item,
+            _ => panic!("Expected an exported declaration"),
+        }).collect::<Vec<_>>();
+
+        assert_eq!(exported, declarations.body().iter().map(|statement| statement.item).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn export_named_statement() {
+        let src = r#"
+        export { a, b as default };
+        export { default as c } from 'd';
+        export * from 'e';
+        export * as f from 'g';
+        "#;
+        let mock = Mock::new();
+        let a = mock.ptr("a");
+
+        let expected = mock.list([
+            ExportStatement::Named {
+                specifiers: mock.list([
+                    ExportSpecifier { local: a, exported: a },
+                    ExportSpecifier { local: mock.ptr("b"), exported: mock.ptr("default") },
+                ]),
+                source: None,
+            },
+            ExportStatement::Named {
+                specifiers: mock.list([
+                    ExportSpecifier { local: mock.ptr("default"), exported: mock.ptr("c") },
+                ]),
+                source: Some(mock.ptr(StringLiteral { value: "d", raw: "'d'" })),
+            },
+            ExportStatement::All {
+                exported: None,
+                source: mock.ptr(StringLiteral { value: "e", raw: "'e'" }),
+            },
+            ExportStatement::All {
+                exported: Some(mock.ptr("f")),
+                source: mock.ptr(StringLiteral { value: "g", raw: "'g'" }),
+            },
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn export_default_statement() {
+        let src = "export default function () {} export default class A {} export default a + 1;";
+        let mock = Mock::new();
+        let function: FunctionExpression = Function {
+            name: None.into(),
+            generator: false,
+            is_async: false,
+            params: NodeList::empty(),
+            body: mock.empty_block(),
+        };
+        let class: ClassExpression = Class {
+            name: mock.name("A"),
+            extends: None,
+            body: mock.empty_block(),
+        };
+
+        let expected = mock.list([
+            ExportStatement::DefaultDeclaration(mock.ptr(function)),
+            ExportStatement::DefaultDeclaration(mock.ptr(class)),
+            ExportStatement::Default(mock.ptr(BinaryExpression {
+                operator: OperatorKind::Addition,
+                left: mock.ptr("a"),
+                right: mock.number("1"),
+            })),
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn export_statement_errors() {
+        assert!(parse("export { default };").is_err());
+        assert!(parse("export { a as b } from c;").is_err());
+        assert!(parse("export default;").is_err());
+        assert!(parse("export a;").is_err());
+        assert!(parse("export async () => {};").is_err());
+        assert!(parse("export async\nfunction a() {}").is_err());
+        assert!(parse("export * as 'a' from 'b';").is_err());
+        assert!(parse("{ export var a; }").is_err());
+        assert!(parse("export { default, a as if } from 'b'; export default async;").is_ok());
+    }
+
+    fn errors(src: &str) -> Vec<(ErrorKind, usize, usize)> {
+        match parse(src) {
+            Ok(_)       => Vec::new(),
+            Err(errors) => errors.iter().map(|err| (err.kind, err.start, err.end)).collect(),
+        }
+    }
+
+    #[test]
+    fn optional_chain_in_for_head() {
+        assert_eq!(errors("for ((a?.b).c of d);"), vec![]);
+        assert_eq!(errors("for (a?.b of c);"), vec![(ErrorKind::InvalidOptionalChain, 5, 9)]);
+        assert_eq!(errors("for (a?.b in c);"), vec![(ErrorKind::InvalidOptionalChain, 5, 9)]);
+    }
+
+    #[test]
+    fn function_in_if_statement() {
+        assert_eq!(errors("if (a) function f() {}"), vec![]);
+        assert_eq!(errors("if (a) {} else function f() {}"), vec![]);
+        assert_eq!(
+            errors("'use strict'; if (a) function f() {}"),
+            vec![(ErrorKind::FunctionInStatementPosition, 21, 36)]
+        );
+        assert_eq!(
+            errors("if (a) function* f() {}"),
+            vec![(ErrorKind::FunctionInStatementPosition, 7, 23)]
+        );
+    }
+
+    #[test]
+    fn function_in_loop_body() {
+        assert_eq!(errors("while (a) function f() {}"), vec![(ErrorKind::FunctionInStatementPosition, 10, 25)]);
+        assert_eq!(errors("for (;;) function f() {}"), vec![(ErrorKind::FunctionInStatementPosition, 9, 24)]);
+        assert_eq!(errors("while (a) { function f() {} }"), vec![]);
+    }
+
+    #[test]
+    fn labeled_function() {
+        assert_eq!(errors("foo: function f() {}"), vec![]);
+        assert_eq!(
+            errors("'use strict'; foo: function f() {}"),
+            vec![(ErrorKind::FunctionInStatementPosition, 19, 34)]
+        );
+    }
+
+    #[test]
+    fn block_functions() {
+        let module = parse("function a() {} { function b() {} } if (c) function d() {}").unwrap();
+
+        assert_eq!(module.block_functions(), &[(18, 33), (43, 58)]);
+
+        let module = parse("{ function a() { function b() {} } function* c() {} }").unwrap();
+
+        assert_eq!(module.block_functions(), &[(2, 34)]);
+
+        let module = parse("function a() { 'use strict'; { function b() {} } } { () => { function c() {} } }").unwrap();
+
+        assert_eq!(module.block_functions(), &[]);
+    }
 }
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use toolshed::Arena;
+use error::Error;
+use lexer::Token;
+use parser::{Parser, ParserOptions};
+
+/// Kind of the decision the parser made on a given token.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Production {
+    /// Dispatched a statement handler
+    Statement,
+
+    /// Dispatched a prefix expression handler
+    Expression,
+
+    /// Dispatched an infix / postfix handler, continuing an expression
+    Nested,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TraceEvent {
+    pub production: Production,
+    pub token: Token,
+    pub start: u32,
+    pub end: u32,
+    /// Nesting level of statements and expressions at the time of the decision
+    pub depth: u32,
+}
+
+/// Recorded sequence of decisions made by the parser for a given source,
+/// in the order they were made. Use `Display` to pretty-print the trace.
+#[derive(Debug)]
+pub struct Trace<'src> {
+    pub source: &'src str,
+    pub events: Vec<TraceEvent>,
+    pub errors: Vec<Error>,
+}
+
+#[derive(Debug, Default)]
+pub struct Tracer {
+    events: Vec<TraceEvent>,
+    depth: u32,
+}
+
+impl<'ast> Parser<'ast> {
+    #[inline]
+    pub fn trace_enter(&mut self, production: Production) {
+        self.trace_event(production);
+        self.tracer.depth += 1;
+    }
+
+    #[inline]
+    pub fn trace_leave(&mut self) {
+        self.tracer.depth -= 1;
+    }
+
+    #[inline]
+    pub fn trace_event(&mut self, production: Production) {
+        let (start, end) = self.lexer.loc();
+
+        self.tracer.events.push(TraceEvent {
+            production,
+            token: self.lexer.token,
+            start,
+            end,
+            depth: self.tracer.depth,
+        });
+    }
+}
+
+/// Parse the source, recording every decision made by the parser. Unlike
+/// `parse`, this never fails: errors are collected in the returned `Trace`.
+pub fn trace<'src>(source: &'src str) -> Trace<'src> {
+    let arena = Arena::new();
+    let mut parser = Parser::new(source, &arena, ParserOptions::default());
+
+    parser.parse();
+
+    Trace {
+        source,
+        events: parser.tracer.events,
+        errors: parser.errors,
+    }
+}
+
+impl<'src> fmt::Display for Trace<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for event in &self.events {
+            let slice = &self.source[event.start as usize..event.end as usize];
+
+            for _ in 0..event.depth {
+                f.write_str("  ")?;
+            }
+
+            writeln!(f, "{:?} {:?} `{}` at {}..{}", event.production, event.token, slice, event.start, event.end)?;
+        }
+
+        for error in &self.errors {
+            writeln!(f, "Error {:?} at {}..{}", error.token, error.start, error.end)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn traces_decisions() {
+        let trace = trace("foo = 1 + 2;");
+        let events: Vec<_> = trace.events.iter().map(|e| (e.production, e.token, e.depth)).collect();
+
+        assert_eq!(events, vec![
+            (Production::Statement, Token::Identifier, 0),
+            (Production::Nested, Token::OperatorAssign, 1),
+            (Production::Expression, Token::LiteralNumber, 2),
+            (Production::Nested, Token::OperatorAddition, 2),
+            (Production::Expression, Token::LiteralNumber, 3),
+        ]);
+        assert_eq!(trace.errors.len(), 0);
+    }
+
+    #[test]
+    fn pretty_prints() {
+        let trace = trace("a(1)");
+
+        assert_eq!(trace.to_string(), "\
+Statement Identifier `a` at 0..1
+  Nested ParenOpen `(` at 1..2
+    Expression LiteralNumber `1` at 2..3
+");
+    }
+
+    #[test]
+    fn records_errors() {
+        let trace = trace("a +;");
+
+        assert_eq!(trace.errors.len(), 1);
+        assert_eq!(trace.to_string().lines().last(), Some("Error Semicolon at 3..4"));
+    }
+}
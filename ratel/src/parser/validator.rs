@@ -0,0 +1,660 @@
+use std::mem;
+
+use allocated::*;
+use error::{Error, ErrorKind};
+use diagnostic::Severity;
+use lexer::Token;
+use parser::Parser;
+
+use ast::{Expression, ExpressionNode, Literal, StringLiteral, OperatorKind, Pattern, PatternList};
+use ast::{Property, Statement, StatementNode, Node, NodeList, DeclarationKind};
+use ast::expression::{BinaryExpression, ObjectExpression, ArrayExpression, SpreadExpression};
+use ast::statement::ForInit;
+
+/// Rules that apply to the parameter list of a function.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Params {
+    /// Plain function, duplicate names allowed in sloppy mode
+    Function,
+
+    /// Object or class method, duplicate names never allowed
+    Method,
+
+    /// Arrow function, duplicate names never allowed
+    Arrow,
+}
+
+/// Head of a `for` statement the declaration of which is validated.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ForHead {
+    /// `for (init; test; update)`, where `const` and destructuring
+    /// declarators need an initializer
+    Init,
+
+    /// `for (left in right)`
+    In,
+
+    /// `for (left of right)`
+    Of,
+}
+
+/// Name bound by a parameter, along with its location.
+type Binding<'ast> = (&'ast str, u32, u32);
+
+impl<'ast> Parser<'ast> {
+    /// Check whether the statement belongs to a directive prologue,
+    /// switching to strict mode on `"use strict"`.
+    pub fn directive(&mut self, statement: StatementNode<'ast>) -> bool {
+        let raw = match statement.item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Literal(Literal::String(StringLiteral { raw, .. })) => raw,
+                _ => return false,
+            },
+            _ => return false,
+        };
+
+        // Escaped or line-continued forms don't count, only exact raw source
+        if &raw[1..raw.len() - 1] == "use strict" {
+            self.strict = true;
+        }
+
+        true
+    }
+
+    /// Check the current number or string literal token for the legacy
+    /// octal forms of Annex B.1, errors in strict mode code and warnings
+    /// otherwise.
+    #[inline]
+    pub fn validate_octal(&mut self) {
+        let start = match self.lexer.legacy_octal {
+            Some(start) => start,
+            None => return,
+        };
+
+        let token = self.lexer.token;
+        let rest = &self.lexer.token_as_str()[(start - self.lexer.start()) as usize..];
+
+        let (kind, raw) = match token {
+            Token::LiteralNumber => (ErrorKind::LegacyOctalLiteral, rest),
+            _ => {
+                // Up to three octal digits starting with `0` to `3`, up to
+                // two otherwise, `\8` and `\9` have none
+                let limit = match rest.as_bytes()[1] {
+                    b'0'..=b'3' => 3,
+                    _ => 2,
+                };
+                let digits = rest[1..].bytes().take(limit).take_while(|byte| (b'0'..=b'7').contains(byte)).count();
+
+                (ErrorKind::LegacyOctalEscape, &rest[..1 + digits.max(1)])
+            },
+        };
+
+        let end = start + raw.len() as u32;
+
+        if self.strict {
+            return self.error_at(kind, token, raw, start, end);
+        }
+
+        let mut warning = Error {
+            kind,
+            token,
+            raw: raw.into(),
+            start: start as usize,
+            end: end as usize,
+        }.diagnostic();
+
+        warning.severity = Severity::Warning;
+
+        self.warnings.push(warning);
+    }
+
+    /// Validate the declaration in the head of a `for` statement, anything
+    /// else being left to the grammar.
+    pub fn validate_for_head(&mut self, init: Node<'ast, ForInit<'ast>>, head: ForHead) {
+        let declaration = match init.item {
+            ForInit::Declaration(declaration) => declaration,
+            ForInit::Expression(expression) => {
+                if head != ForHead::Init {
+                    self.resolve_target(expression);
+                    self.validate_assignment_target(expression);
+
+                    if expression.is_optional_chain() {
+                        let (token, raw) = match head {
+                            ForHead::Of => (Token::Identifier, "of"),
+                            _           => (Token::OperatorIn, "in"),
+                        };
+
+                        self.error_at(ErrorKind::InvalidOptionalChain, token, raw, expression.start, expression.end);
+                    }
+                }
+
+                return;
+            },
+        };
+
+        let (token, raw) = match declaration.kind {
+            DeclarationKind::Var   => (Token::DeclarationVar, "var"),
+            DeclarationKind::Let   => (Token::DeclarationLet, "let"),
+            DeclarationKind::Const => (Token::DeclarationConst, "const"),
+        };
+
+        if head == ForHead::Init {
+            for declarator in declaration.declarators {
+                let pattern = !matches!(declarator.id.item, Pattern::Identifier(_));
+
+                if declarator.init.is_none() && (pattern || declaration.kind == DeclarationKind::Const) {
+                    self.error_at(ErrorKind::MissingInitializer, token, raw, declarator.start, declarator.end);
+                }
+            }
+
+            return;
+        }
+
+        let (token, raw) = match head {
+            ForHead::Of => (Token::Identifier, "of"),
+            _           => (Token::OperatorIn, "in"),
+        };
+
+        for (index, declarator) in declaration.declarators.iter().enumerate() {
+            let annex_b = head == ForHead::In
+                && declaration.kind == DeclarationKind::Var
+                && !self.strict
+                && matches!(declarator.id.item, Pattern::Identifier(_));
+
+            if index > 0 || (declarator.init.is_some() && !annex_b) {
+                self.error_at(ErrorKind::InvalidForInOfDeclaration, token, raw, declarator.start, declarator.end);
+            }
+        }
+    }
+
+    /// Report the shorthand properties with an initializer left in object
+    /// literals that weren't assignment targets, once the top level
+    /// statement they are in has been parsed.
+    pub fn validate_cover_initializers(&mut self) {
+        for (name, start, end) in mem::take(&mut self.cover_initializers) {
+            self.error_at(ErrorKind::InvalidShorthandInitializer, Token::OperatorAssign, name, start, end);
+        }
+    }
+
+    /// Report the `yield` and `await` expressions starting at or after
+    /// `start`, those of the parameters of the function being parsed.
+    pub fn validate_suspensions(&mut self, start: u32) {
+        let (params, before): (Vec<_>, Vec<_>) = mem::take(&mut self.suspensions)
+            .into_iter()
+            .partition(|&(_, suspension, _)| suspension >= start);
+
+        self.suspensions = before;
+
+        for (keyword, start, end) in params {
+            let token = if keyword == "yield" { Token::Yield } else { Token::Identifier };
+
+            self.error_at(ErrorKind::SuspensionInParameters, token, keyword, start, end);
+        }
+    }
+
+    /// Report the `await` expressions recorded since the `first` suspension
+    /// in the arguments of an `async` call that isn't an arrow function
+    /// after all, outside of async functions where they can't appear.
+    pub fn validate_await_arguments(&mut self, first: usize) {
+        let arguments = self.suspensions.split_off(first);
+
+        for (keyword, start, end) in arguments {
+            if keyword == "await" {
+                self.error_at(ErrorKind::UnexpectedToken, Token::Identifier, keyword, start, end);
+            } else {
+                self.suspensions.push((keyword, start, end));
+            }
+        }
+    }
+
+    /// Validate the parameters of a function once its body has been parsed,
+    /// so that a `"use strict"` directive in the body is taken into account.
+    pub fn validate_params(&mut self, params: PatternList<'ast>, kind: Params, generator: bool, is_async: bool) {
+        let mut bindings = Vec::new();
+        let mut simple = true;
+
+        for param in params {
+            if let Pattern::Identifier(_) = param.item {} else {
+                simple = false;
+            }
+
+            collect_pattern(param.item, param.start, param.end, &mut bindings);
+        }
+
+        let duplicates = kind != Params::Function || self.strict || !simple;
+
+        for (index, &(name, start, end)) in bindings.iter().enumerate() {
+            if (name == "yield" && (generator || self.strict)) || (name == "await" && is_async) {
+                self.error_at(ErrorKind::ReservedParameter, Token::Identifier, name, start, end);
+            }
+
+            if !duplicates {
+                continue;
+            }
+
+            let first = bindings[..index].iter().find(|&&(other, _, _)| other == name);
+
+            if let Some(&(_, first_start, first_end)) = first {
+                self.error_at(ErrorKind::DuplicateParameter {
+                    first_start: first_start as usize,
+                    first_end: first_end as usize,
+                }, Token::Identifier, name, start, end);
+            }
+        }
+    }
+
+    /// Check that a declaration or a `catch` clause doesn't bind `await`
+    /// in an async function.
+    pub fn validate_binding(&mut self, pattern: Node<'ast, Pattern<'ast>>) {
+        if !self.is_async {
+            return;
+        }
+
+        let mut bindings = Vec::new();
+
+        collect_pattern(pattern.item, pattern.start, pattern.end, &mut bindings);
+
+        for (name, start, end) in bindings {
+            if name == "await" {
+                self.error_at(ErrorKind::ReservedBinding, Token::Identifier, name, start, end);
+            }
+        }
+    }
+
+    /// Check that the values of the properties of an object pattern bind
+    /// names, destructuring to `{ a: b.c }` is only allowed in assignments.
+    pub fn validate_binding_properties(&mut self, properties: NodeList<'ast, Property<'ast>>) {
+        for property in properties {
+            if !is_binding_property(property.item) {
+                self.error_at(ErrorKind::InvalidBindingProperty, Token::BraceOpen, "", property.start, property.end);
+            }
+        }
+    }
+
+    /// Check that an object or array literal destructured by an assignment,
+    /// `[a.b, c = 1] = d`, only holds what can be assigned to.
+    pub fn validate_assignment_target(&mut self, expression: ExpressionNode<'ast>) {
+        match expression.item {
+            Expression::Object(ObjectExpression { body }) => {
+                for property in body {
+                    match property.item {
+                        Property::Shorthand(_) | Property::ShorthandDefault { .. } => {},
+                        Property::Literal { value, .. } => self.validate_assignment_element(value),
+                        Property::Spread { argument } => self.validate_assignment_element(argument),
+                        Property::Method { .. } => {
+                            self.error_at(ErrorKind::InvalidAssignmentTarget, Token::BraceOpen, "", property.start, property.end);
+                        },
+                    }
+                }
+            },
+            Expression::Array(ArrayExpression { body }) => {
+                let mut elements = body.iter().peekable();
+
+                while let Some(&element) = elements.next() {
+                    match element.item {
+                        Expression::Void => {},
+                        Expression::Spread(SpreadExpression { argument }) if elements.peek().is_none() => {
+                            self.validate_assignment_element(argument);
+                        },
+                        Expression::Spread(_) => {
+                            self.error_at(ErrorKind::InvalidAssignmentTarget, Token::OperatorSpread, "...", element.start, element.end);
+                        },
+                        _ => self.validate_assignment_element(element),
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Element of a destructuring assignment, with or without a default.
+    fn validate_assignment_element(&mut self, expression: ExpressionNode<'ast>) {
+        let target = match expression.item {
+            Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, left, .. }) => left,
+            _ => expression,
+        };
+
+        match target.item {
+            Expression::Object(_) | Expression::Array(_) => self.validate_assignment_target(target),
+            _ if target.is_lvalue() => {},
+            _ => self.error_at(ErrorKind::InvalidAssignmentTarget, Token::BracketOpen, "", target.start, target.end),
+        }
+    }
+}
+
+fn is_binding_property(property: Property) -> bool {
+    match property {
+        Property::Shorthand(_) | Property::ShorthandDefault { .. } => true,
+        Property::Literal { value, .. } => is_binding(value),
+        Property::Spread { argument } => matches!(argument.item, Expression::Identifier(_)),
+        Property::Method { .. } => false,
+    }
+}
+
+/// Check whether an expression in a pattern binds names, as an identifier
+/// or a nested pattern, with or without a default.
+fn is_binding(expression: ExpressionNode) -> bool {
+    match expression.item {
+        Expression::Identifier(_) => true,
+        Expression::Binary(BinaryExpression {
+            operator: OperatorKind::Assign,
+            left,
+            ..
+        }) => is_binding(left),
+        Expression::Object(ObjectExpression { body }) => {
+            body.iter().all(|property| is_binding_property(property.item))
+        },
+        Expression::Array(ArrayExpression { body }) => {
+            let mut elements = body.iter().peekable();
+
+            while let Some(element) = elements.next() {
+                let valid = match element.item {
+                    Expression::Void => true,
+                    Expression::Spread(SpreadExpression { argument }) => {
+                        elements.peek().is_none() && matches!(argument.item, Expression::Identifier(_))
+                    },
+                    _ => is_binding(*element),
+                };
+
+                if !valid {
+                    return false;
+                }
+            }
+
+            true
+        },
+        _ => false,
+    }
+}
+
+fn collect_pattern<'ast>(pattern: Pattern<'ast>, start: u32, end: u32, bindings: &mut Vec<Binding<'ast>>) {
+    match pattern {
+        Pattern::Void => {},
+        Pattern::Identifier(name) => bindings.push((name, start, end)),
+        Pattern::RestElement { argument } => {
+            bindings.push((argument.item, argument.start, argument.end));
+        },
+        Pattern::AssignmentPattern { left, .. } => {
+            collect_pattern(left.item, left.start, left.end, bindings);
+        },
+        Pattern::ArrayPattern { elements } => {
+            for element in elements {
+                collect_pattern(element.item, element.start, element.end, bindings);
+            }
+        },
+        Pattern::ObjectPattern { properties } => collect_properties(properties, bindings),
+    }
+}
+
+fn collect_properties<'ast>(properties: NodeList<'ast, Property<'ast>>, bindings: &mut Vec<Binding<'ast>>) {
+    for property in properties {
+        match property.item {
+            Property::Shorthand(name) => bindings.push((name, property.start, property.end)),
+            Property::ShorthandDefault { name, .. } => bindings.push((name.item, name.start, name.end)),
+            Property::Literal { value, .. } => collect_expression(value, bindings),
+            Property::Spread { argument } => collect_expression(argument, bindings),
+            _ => {},
+        }
+    }
+}
+
+fn collect_expression<'ast>(expression: ExpressionNode<'ast>, bindings: &mut Vec<Binding<'ast>>) {
+    match expression.item {
+        Expression::Identifier(name) => bindings.push((name, expression.start, expression.end)),
+        Expression::Binary(BinaryExpression {
+            operator: OperatorKind::Assign,
+            left,
+            ..
+        }) => collect_expression(left, bindings),
+        Expression::Object(ObjectExpression { body }) => collect_properties(body, bindings),
+        Expression::Array(ArrayExpression { body }) => {
+            for &element in body {
+                collect_expression(element, bindings);
+            }
+        },
+        Expression::Spread(SpreadExpression { argument }) => collect_expression(argument, bindings),
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use parser::{parse, parse_with_options, ParserOptions};
+    use error::ErrorKind;
+    use diagnostic::Severity;
+
+    fn kinds(src: &str) -> Vec<(ErrorKind, usize, usize)> {
+        match parse(src) {
+            Ok(_)       => Vec::new(),
+            Err(errors) => errors.iter().map(|err| (err.kind, err.start, err.end)).collect(),
+        }
+    }
+
+    fn duplicate(first_start: usize, first_end: usize, start: usize, end: usize) -> Vec<(ErrorKind, usize, usize)> {
+        vec![(ErrorKind::DuplicateParameter { first_start, first_end }, start, end)]
+    }
+
+    #[test]
+    fn sloppy_function_duplicates() {
+        assert_eq!(kinds("function f(a, a) {}"), vec![]);
+        assert_eq!(kinds("(function (a, b, a) {})"), vec![]);
+    }
+
+    #[test]
+    fn strict_function_duplicates() {
+        assert_eq!(kinds("function f(a, a) { 'use strict' }"), duplicate(11, 12, 14, 15));
+        assert_eq!(kinds("'use strict'; function f(a, a) {}"), duplicate(25, 26, 28, 29));
+        assert_eq!(kinds("function f() { 'use strict'; return function (a, a) {} }"), duplicate(46, 47, 49, 50));
+    }
+
+    #[test]
+    fn strict_from_options() {
+        let options = ParserOptions { strict: true, ..ParserOptions::default() };
+
+        assert!(parse_with_options("function f(a, a) {}", options).is_err());
+    }
+
+    #[test]
+    fn strict_does_not_leak() {
+        assert_eq!(kinds("function f() { 'use strict' } function g(a, a) {}"), vec![]);
+        assert_eq!(kinds("function f() { foo(); 'use strict' } function g(a, a) {}"), vec![]);
+        assert_eq!(kinds("class A {} function g(a, a) {}"), vec![]);
+    }
+
+    #[test]
+    fn non_simple_duplicates() {
+        assert_eq!(kinds("function f(a, [a]) {}"), duplicate(11, 12, 15, 16));
+        assert_eq!(kinds("function f(a, b = 1, ...a) {}"), duplicate(11, 12, 24, 25));
+    }
+
+    #[test]
+    fn arrow_duplicates() {
+        assert_eq!(kinds("(a, a) => a"), duplicate(1, 2, 4, 5));
+        assert_eq!(kinds("(a, b = 1, a) => {}"), duplicate(1, 2, 11, 12));
+    }
+
+    #[test]
+    fn pattern_duplicates() {
+        assert_eq!(kinds("({ a: { b } }, b) => {}"), duplicate(8, 9, 15, 16));
+        assert_eq!(kinds("([a, [b]], b) => {}"), duplicate(6, 7, 11, 12));
+        assert_eq!(kinds("function f({ a: [b = 1] }, { ...b }) { 'use strict' }"), duplicate(17, 18, 32, 33));
+        assert_eq!(kinds("function f([a, ...b], { c: b }) { 'use strict' }"), duplicate(18, 19, 27, 28));
+    }
+
+    #[test]
+    fn binding_properties() {
+        assert_eq!(kinds("function f({ a: 1 }) {}"), vec![(ErrorKind::InvalidBindingProperty, 13, 17)]);
+        assert_eq!(kinds("({ a, b: 1, c: [d()] }) => {}"), vec![
+            (ErrorKind::InvalidBindingProperty, 6, 10),
+            (ErrorKind::InvalidBindingProperty, 12, 20),
+        ]);
+        assert_eq!(kinds("let { a: b() } = c"), vec![(ErrorKind::InvalidBindingProperty, 6, 12)]);
+        assert_eq!(kinds("({ a: b.c } = d)"), vec![]);
+    }
+
+    #[test]
+    fn assignment_targets() {
+        assert_eq!(kinds("[a = 1, b.c, d[0] = 2, ...e.f] = g"), vec![]);
+        assert_eq!(kinds("({ a = 1, b: [c = 2], d: e.f = 3, ...g } = h)"), vec![]);
+        assert_eq!(kinds("[a + 1, ...b] = c"), vec![(ErrorKind::InvalidAssignmentTarget, 1, 6)]);
+        assert_eq!(kinds("[...a, b] = c"), vec![(ErrorKind::InvalidAssignmentTarget, 1, 5)]);
+        assert_eq!(kinds("({ a: [b + 1] } = c)"), vec![(ErrorKind::InvalidAssignmentTarget, 7, 12)]);
+        assert_eq!(kinds("for ([a = 1, 2] of b) {}"), vec![(ErrorKind::InvalidAssignmentTarget, 13, 14)]);
+    }
+
+    #[test]
+    fn method_duplicates() {
+        assert_eq!(kinds("({ m(a, a) {} })"), duplicate(5, 6, 8, 9));
+        assert_eq!(kinds("class A { m(a, a) {} }"), duplicate(12, 13, 15, 16));
+    }
+
+    #[test]
+    fn yield_parameter() {
+        assert_eq!(kinds("function f(yield) {}"), vec![]);
+        assert_eq!(kinds("function f(...yield) {}"), vec![]);
+        assert_eq!(kinds("function* f(yield) {}"), vec![(ErrorKind::ReservedParameter, 12, 17)]);
+        assert_eq!(kinds("function f(yield) { 'use strict' }"), vec![(ErrorKind::ReservedParameter, 11, 16)]);
+        assert_eq!(kinds("class A { m(yield) {} }"), vec![(ErrorKind::ReservedParameter, 12, 17)]);
+    }
+
+    #[test]
+    fn await_bindings() {
+        assert_eq!(kinds("function f(await) { var await; try {} catch (await) {} }"), vec![]);
+        assert_eq!(kinds("async function f(await) {}"), vec![(ErrorKind::ReservedParameter, 17, 22)]);
+        assert_eq!(kinds("async function f([a, await]) {}"), vec![(ErrorKind::ReservedParameter, 21, 26)]);
+        assert_eq!(kinds("({ async m(await) {} })"), vec![(ErrorKind::ReservedParameter, 11, 16)]);
+        assert_eq!(kinds("async (await) => 1"), vec![(ErrorKind::ReservedParameter, 7, 12)]);
+        assert_eq!(kinds("async function f() { var await; }"), vec![(ErrorKind::ReservedBinding, 25, 30)]);
+        assert_eq!(kinds("async function f() { let [a, await] = b; }"), vec![(ErrorKind::ReservedBinding, 29, 34)]);
+        assert_eq!(kinds("async function f() { try {} catch (await) {} }"), vec![(ErrorKind::ReservedBinding, 35, 40)]);
+        assert_eq!(kinds("async function f() { function g() { var await; } }"), vec![]);
+    }
+
+    #[test]
+    fn suspensions_in_parameters() {
+        assert_eq!(kinds("function* g(a = yield) {}"), vec![(ErrorKind::SuspensionInParameters, 16, 21)]);
+        assert_eq!(kinds("function* g(a = yield b) {}"), vec![(ErrorKind::SuspensionInParameters, 16, 23)]);
+        assert_eq!(kinds("async function f(a = await b) {}"), vec![(ErrorKind::SuspensionInParameters, 21, 28)]);
+        assert_eq!(kinds("({ async m([a] = [await b]) {} })"), vec![(ErrorKind::SuspensionInParameters, 18, 25)]);
+        assert_eq!(kinds("class A { async m(a = await b) {} }"), vec![(ErrorKind::SuspensionInParameters, 22, 29)]);
+    }
+
+    #[test]
+    fn suspensions_in_arrow_parameters() {
+        assert_eq!(kinds("function* g() { (a = yield) => {}; }"), vec![(ErrorKind::SuspensionInParameters, 21, 26)]);
+        assert_eq!(kinds("async function f() { async (a = await b) => {}; }"), vec![(ErrorKind::SuspensionInParameters, 32, 39)]);
+        assert_eq!(kinds("async function f() { (a, b = await c) => {}; }"), vec![(ErrorKind::SuspensionInParameters, 29, 36)]);
+        assert_eq!(kinds("async (a = await 1) => a"), vec![(ErrorKind::SuspensionInParameters, 11, 18)]);
+        assert_eq!(kinds("function f() { async ([a = await b]) => {}; }"), vec![(ErrorKind::SuspensionInParameters, 27, 34)]);
+    }
+
+    #[test]
+    fn await_in_async_call_arguments() {
+        assert_eq!(kinds("async(await 1);"), vec![(ErrorKind::UnexpectedToken, 6, 13)]);
+        assert_eq!(kinds("async(await, await(a), await + 1, function () { await; }, () => await);"), vec![]);
+        assert_eq!(kinds("async(async function () { await a; });"), vec![]);
+    }
+
+    #[test]
+    fn suspensions_outside_of_parameters() {
+        // Parameters are parsed in the context of their own function
+        assert_eq!(kinds("function f(a = yield) {} function* g() { function h(a = yield) {} }"), vec![]);
+        assert_eq!(kinds("function* g(a = function* () { yield; }) {}"), vec![]);
+        assert_eq!(kinds("async function f(a = async () => await b) {}"), vec![]);
+        assert_eq!(kinds("function* g() { yield; (a) => {}; yield (b) => {}; (c = function* () { yield; }) => {}; }"), vec![]);
+        assert_eq!(kinds("async function f() { await a; (b = await c); async (d) => await d; }"), vec![]);
+    }
+
+    #[test]
+    fn super_in_methods() {
+        assert_eq!(kinds("({ a() { super.b; return () => super[c]; } })"), vec![]);
+        assert_eq!(kinds("class A { b() { super.b(); } static c = super.c; static { super.d; } }"), vec![]);
+        assert_eq!(kinds("class A extends B { constructor() { super(); (() => super())(); } }"), vec![]);
+        assert_eq!(kinds("class A extends B { constructor() { class C { d() { super.d; } } } }"), vec![]);
+    }
+
+    #[test]
+    fn super_outside_of_methods() {
+        assert_eq!(kinds("super.x"), vec![(ErrorKind::SuperPropertyOutsideMethod, 0, 5)]);
+        assert_eq!(kinds("function f() { return super.x }"), vec![(ErrorKind::SuperPropertyOutsideMethod, 22, 27)]);
+        assert_eq!(kinds("({ a: function () { super.x } })"), vec![(ErrorKind::SuperPropertyOutsideMethod, 20, 25)]);
+        assert_eq!(kinds("({ a() { function b() { super[x]; } } })"), vec![(ErrorKind::SuperPropertyOutsideMethod, 24, 29)]);
+        assert_eq!(kinds("class A { constructor() { super(); } }"), vec![(ErrorKind::SuperCallOutsideConstructor, 26, 31)]);
+        assert_eq!(kinds("class A extends B { b() { super(); } }"), vec![(ErrorKind::SuperCallOutsideConstructor, 26, 31)]);
+        assert_eq!(kinds("class A extends B { c = super(); }"), vec![(ErrorKind::SuperCallOutsideConstructor, 24, 29)]);
+        assert_eq!(kinds("class A extends B { constructor() { ({ c() { super(); } }); } }"), vec![(ErrorKind::SuperCallOutsideConstructor, 45, 50)]);
+        assert_eq!(kinds("class A extends B { constructor() { class C { constructor() { super(); } } } }"), vec![
+            (ErrorKind::SuperCallOutsideConstructor, 62, 67),
+        ]);
+    }
+
+    #[test]
+    fn for_init_declarations() {
+        assert_eq!(kinds("for (var a;;) {} for (let b, c = 1;;) {} for (const d = 1, [e] = f;;) {}"), vec![]);
+        assert_eq!(kinds("for (const a;;) {}"), vec![(ErrorKind::MissingInitializer, 11, 12)]);
+        assert_eq!(kinds("for (var [a];;) {}"), vec![(ErrorKind::MissingInitializer, 9, 12)]);
+        assert_eq!(kinds("for (let a, { b };;) {}"), vec![(ErrorKind::MissingInitializer, 12, 17)]);
+    }
+
+    #[test]
+    fn for_in_of_declarations() {
+        assert_eq!(kinds("for (const a of b) {} for (let [c] in d) {} for (var e in f) {}"), vec![]);
+        assert_eq!(kinds("for (var a, b in c) {}"), vec![(ErrorKind::InvalidForInOfDeclaration, 12, 13)]);
+        assert_eq!(kinds("for (let a = 1 of b) {}"), vec![(ErrorKind::InvalidForInOfDeclaration, 9, 14)]);
+        assert_eq!(kinds("for (var a = 1 of b) {}"), vec![(ErrorKind::InvalidForInOfDeclaration, 9, 14)]);
+        assert_eq!(kinds("for (let a = 1 in b) {}"), vec![(ErrorKind::InvalidForInOfDeclaration, 9, 14)]);
+        assert_eq!(kinds("for (var [a] = 1 in b) {}"), vec![(ErrorKind::InvalidForInOfDeclaration, 9, 16)]);
+    }
+
+    #[test]
+    fn annex_b_for_in_initializer() {
+        assert_eq!(kinds("for (var a = 1 in b) {}"), vec![]);
+        assert_eq!(kinds("'use strict'; for (var a = 1 in b) {}"), vec![(ErrorKind::InvalidForInOfDeclaration, 23, 28)]);
+    }
+
+    #[test]
+    fn shorthand_initializers_in_targets() {
+        assert_eq!(kinds("({ a = 1 } = b); [{ c = 2 }] = d; ({ e: { f = 3 } } = g);"), vec![]);
+        assert_eq!(kinds("for ({ a = 1 } of b) {} for ({ c = 2 } in d) {}"), vec![]);
+        assert_eq!(kinds("var { a = 1 } = b; function f({ c = 2 }) {}"), vec![]);
+        assert_eq!(kinds("[{ a = 1 }, b[function () { c; }]] = d"), vec![]);
+    }
+
+    #[test]
+    fn shorthand_initializers_in_literals() {
+        assert_eq!(kinds("({ a = 1 })"), vec![(ErrorKind::InvalidShorthandInitializer, 3, 8)]);
+        assert_eq!(kinds("f({ a = 1 })"), vec![(ErrorKind::InvalidShorthandInitializer, 4, 9)]);
+        assert_eq!(kinds("({ a = 1 }).b = c"), vec![(ErrorKind::InvalidShorthandInitializer, 3, 8)]);
+        assert_eq!(kinds("({ a = 1 } += b)"), vec![(ErrorKind::InvalidShorthandInitializer, 3, 8)]);
+        assert_eq!(kinds("var { a = { b = 1 } } = c"), vec![(ErrorKind::InvalidShorthandInitializer, 12, 17)]);
+    }
+
+        fn warnings(src: &str) -> Vec<(&'static str, usize, usize)> {
+        let module = parse(src).unwrap();
+
+        module.warnings().iter().map(|warning| {
+            let label = warning.primary().unwrap();
+
+            assert_eq!(warning.severity, Severity::Warning);
+
+            (warning.code, label.start, label.end)
+        }).collect()
+    }
+
+    #[test]
+    fn sloppy_legacy_octal() {
+        assert_eq!(warnings("0755; 0; 0.5; 0o755; '\\0'; '\\x07';"), vec![("E0007", 0, 4)]);
+        assert_eq!(warnings("09; ({ 017: a });"), vec![("E0007", 0, 2), ("E0007", 7, 10)]);
+        assert_eq!(warnings(r"'a\07b\1'; '\08'; '\9';"), vec![("E0008", 2, 5), ("E0008", 12, 14), ("E0008", 19, 21)]);
+        assert_eq!(warnings(r"'\377' + '\400'"), vec![("E0008", 1, 5), ("E0008", 10, 13)]);
+        assert_eq!(warnings("function f() { 'use strict' } 017;"), vec![("E0007", 30, 33)]);
+    }
+
+    #[test]
+    fn strict_legacy_octal() {
+        assert_eq!(kinds("'use strict'; 0755;"), vec![(ErrorKind::LegacyOctalLiteral, 14, 18)]);
+        assert_eq!(kinds(r"function f() { 'use strict'; return '\07'; }"), vec![(ErrorKind::LegacyOctalEscape, 37, 40)]);
+        assert_eq!(kinds("class A { 017() {} }"), vec![(ErrorKind::LegacyOctalLiteral, 10, 13)]);
+        assert_eq!(kinds("class A { m() { return { a: 08 }; } }"), vec![(ErrorKind::LegacyOctalLiteral, 28, 30)]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Types and functions covered by semver guarantees, to be imported with
+//! `use ratel::prelude::v1::*;`. Items are only ever added to a version of
+//! the prelude, changes that would break code using it go into a new one
+//! while the old one is kept around.
+//!
+//! The AST in `ratel::ast` is not part of it yet, as it still changes along
+//! with the language. Implementation details, the lexer and the `Arena`
+//! a `Module` is allocated on, are only available with the `unstable`
+//! feature.
+
+/// First version of the prelude.
+pub mod v1 {
+    pub use parser::{parse, parse_with_options, ParserOptions};
+    pub use module::{Module, Features};
+    pub use error::{Error, ErrorKind};
+    pub use diagnostic::{Diagnostic, Label, Severity};
+    pub use quota::{Quota, Resource};
+    pub use output::{Output, parse_output};
+}
+
+#[cfg(test)]
+mod test {
+    use super::v1::*;
+
+    #[test]
+    fn parses_with_the_prelude() {
+        let module: Module = parse("017;").unwrap();
+
+        assert_eq!(module.warnings()[0].severity, Severity::Warning);
+
+        let errors: Vec<Error> = parse_with_options("017;", ParserOptions::default().with_strict(true)).err().unwrap();
+
+        assert_eq!(errors[0].kind, ErrorKind::LegacyOctalLiteral);
+    }
+}
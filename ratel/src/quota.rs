@@ -0,0 +1,151 @@
+//! Limits on the size of the AST, so that services parsing untrusted code
+//! can bound the memory used by each module. The `Quota` is set with
+//! `ParserOptions::quota` and stays with the `Module`, allocations of the
+//! parser and of transforms are charged to its `Budget`.
+//!
+//! Usage is counted as nodes and the bytes of the arena they take, along
+//! with the copy of the source. Links of lists and interned strings are
+//! not counted, every node is linked in at most one list, so a limit on
+//! nodes also bounds them.
+
+use std::cell::Cell;
+use std::fmt::{self, Display};
+use std::mem::size_of;
+
+use ast::Loc;
+
+/// Limits of a `Budget`, `None` being unlimited.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Quota {
+    /// Maximum number of nodes
+    pub max_nodes: Option<usize>,
+
+    /// Maximum number of bytes allocated on the arena
+    pub max_arena_bytes: Option<usize>,
+}
+
+/// Resource of which a `Quota` was exceeded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Resource {
+    Nodes,
+    ArenaBytes,
+}
+
+/// Allocations counted so far by a `Budget`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Usage {
+    pub nodes: usize,
+    pub arena_bytes: usize,
+}
+
+/// Error of an allocation going over the `limit` of the `Quota`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct QuotaExceeded {
+    pub resource: Resource,
+    pub limit: usize,
+}
+
+impl Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.resource {
+            Resource::Nodes => write!(f, "AST larger than {} nodes", self.limit),
+            Resource::ArenaBytes => write!(f, "AST larger than {} bytes", self.limit),
+        }
+    }
+}
+
+/// `Quota` along with the `Usage` charged against it so far.
+#[derive(Debug, Default)]
+pub struct Budget {
+    quota: Quota,
+    usage: Cell<Usage>,
+}
+
+impl Budget {
+    #[inline]
+    pub fn new(quota: Quota) -> Self {
+        Budget {
+            quota,
+            usage: Cell::new(Usage::default()),
+        }
+    }
+
+    #[inline]
+    pub fn quota(&self) -> Quota {
+        self.quota
+    }
+
+    #[inline]
+    pub fn usage(&self) -> Usage {
+        self.usage.get()
+    }
+
+    /// Charge the allocation of a node of type `T`.
+    #[inline]
+    pub fn charge_node<T>(&self) -> Result<(), QuotaExceeded> {
+        let mut usage = self.usage.get();
+
+        usage.nodes += 1;
+        usage.arena_bytes += size_of::<Loc<T>>();
+
+        self.usage.set(usage);
+        self.check()
+    }
+
+    /// Charge the allocation of `bytes` not taken by nodes, such as the
+    /// copy of the source.
+    #[inline]
+    pub fn charge_bytes(&self, bytes: usize) -> Result<(), QuotaExceeded> {
+        let mut usage = self.usage.get();
+
+        usage.arena_bytes += bytes;
+
+        self.usage.set(usage);
+        self.check()
+    }
+
+    /// Get `Err(QuotaExceeded)` if the usage is over the quota.
+    #[inline]
+    pub fn check(&self) -> Result<(), QuotaExceeded> {
+        let usage = self.usage.get();
+
+        if let Some(limit) = self.quota.max_nodes {
+            if usage.nodes > limit {
+                return Err(QuotaExceeded { resource: Resource::Nodes, limit });
+            }
+        }
+
+        if let Some(limit) = self.quota.max_arena_bytes {
+            if usage.arena_bytes > limit {
+                return Err(QuotaExceeded { resource: Resource::ArenaBytes, limit });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ast::Expression;
+
+    #[test]
+    fn charges_allocations() {
+        let budget = Budget::new(Quota { max_nodes: Some(2), max_arena_bytes: None });
+
+        assert_eq!(budget.charge_bytes(100), Ok(()));
+        assert_eq!(budget.charge_node::<Expression>(), Ok(()));
+        assert_eq!(budget.charge_node::<Expression>(), Ok(()));
+        assert_eq!(budget.charge_node::<Expression>(), Err(QuotaExceeded { resource: Resource::Nodes, limit: 2 }));
+        assert_eq!(budget.usage(), Usage { nodes: 3, arena_bytes: 100 + 3 * size_of::<Loc<Expression>>() });
+    }
+
+    #[test]
+    fn limits_arena_bytes() {
+        let budget = Budget::new(Quota { max_nodes: None, max_arena_bytes: Some(10) });
+
+        assert_eq!(budget.charge_bytes(10), Ok(()));
+        assert_eq!(budget.charge_bytes(1), Err(QuotaExceeded { resource: Resource::ArenaBytes, limit: 10 }));
+    }
+}
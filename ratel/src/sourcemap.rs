@@ -0,0 +1,320 @@
+//! Consumer of version 3 source maps, mapping positions in generated code
+//! back to the original sources, so that errors found in compiled output
+//! can be reported against the code the user wrote.
+
+use std::fmt;
+use allocated::*;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Deserializer};
+#[cfg(feature = "serialize")]
+use serde::de::Error as DeError;
+
+/// Error decoding a source map.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SourceMapError {
+    /// Only version 3 source maps are supported
+    UnsupportedVersion(u32),
+
+    /// Character outside of the base64 alphabet in `mappings`
+    InvalidBase64(char),
+
+    /// Segment of `mappings` with a number of fields other than 1, 4 or 5,
+    /// or a value ending in the middle
+    InvalidSegment,
+
+    /// Segment referring to a source or name not in the map
+    IndexOutOfRange,
+}
+
+impl fmt::Display for SourceMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SourceMapError::UnsupportedVersion(version) => write!(f, "Unsupported source map version {}", version),
+            SourceMapError::InvalidBase64(ch) => write!(f, "Invalid base64 character {:?} in mappings", ch),
+            SourceMapError::InvalidSegment => write!(f, "Invalid segment in mappings"),
+            SourceMapError::IndexOutOfRange => write!(f, "Source or name index out of range in mappings"),
+        }
+    }
+}
+
+/// Position in an original source, lines and columns start at 0.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OriginalPosition<'map> {
+    pub source: &'map str,
+    pub line: u32,
+    pub column: u32,
+
+    /// Original name of the identifier at the position, if any
+    pub name: Option<&'map str>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Segment {
+    column: u32,
+
+    /// `source`, `line`, `column`, `name` of the original position,
+    /// segments of generated code without one have none
+    original: Option<(u32, u32, u32, Option<u32>)>,
+}
+
+/// Decoded source map.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SourceMap {
+    /// Sources with `sourceRoot` prepended
+    sources: Vec<String>,
+    names: Vec<String>,
+
+    /// Segments of every generated line, sorted by column
+    lines: Vec<Vec<Segment>>,
+}
+
+#[cfg(feature = "serialize")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    names: Vec<String>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Decode the `mappings` of a source map referring to `sources` and
+    /// `names` by index.
+    pub fn new(mappings: &str, sources: Vec<String>, names: Vec<String>) -> Result<Self, SourceMapError> {
+        let mut lines = Vec::new();
+
+        // Every field but the generated column is relative to the same
+        // field of the previous segment of any line
+        let mut previous = [0i64; 4];
+
+        for line in mappings.split(';') {
+            let mut segments = Vec::new();
+            let mut column = 0i64;
+
+            for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+                let fields = decode_segment(segment)?;
+
+                column += fields[0];
+
+                let original = match fields.len() {
+                    1 => None,
+                    4 | 5 => {
+                        for (field, value) in previous.iter_mut().zip(&fields[1..]) {
+                            *field += value;
+                        }
+
+                        let name = match fields.len() {
+                            5 => Some(index(previous[3], names.len())?),
+                            _ => None,
+                        };
+
+                        Some((index(previous[0], sources.len())?, position(previous[1])?, position(previous[2])?, name))
+                    },
+                    _ => return Err(SourceMapError::InvalidSegment),
+                };
+
+                segments.push(Segment {
+                    column: position(column)?,
+                    original,
+                });
+            }
+
+            segments.sort_by_key(|segment| segment.column);
+            lines.push(segments);
+        }
+
+        Ok(SourceMap { sources, names, lines })
+    }
+
+    /// Find the original position of the generated code at `line` and
+    /// `column`, both starting at 0, with columns counted in UTF-16 code
+    /// units. That's the position of the closest segment at or before it
+    /// on the same line.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<OriginalPosition<'_>> {
+        let segments = self.lines.get(line as usize)?;
+        let segment = segments.iter().rev().find(|segment| segment.column <= column)?;
+        let (source, line, column, name) = segment.original?;
+
+        Some(OriginalPosition {
+            source: &self.sources[source as usize],
+            line,
+            column,
+            name: name.map(|name| &*self.names[name as usize]),
+        })
+    }
+
+    /// Find the original position of the byte at `offset` of the generated
+    /// `source`.
+    pub fn lookup_offset(&self, source: &str, offset: usize) -> Option<OriginalPosition<'_>> {
+        let before = source.get(..offset)?;
+        let (line, start) = before.bytes().enumerate().fold((0, 0), |(line, start), (index, byte)| {
+            match byte {
+                b'\r' if before.as_bytes().get(index + 1) == Some(&b'\n') => (line, start),
+                b'\r' | b'\n' => (line + 1, index + 1),
+                _ => (line, start),
+            }
+        });
+        let column = before[start..].encode_utf16().count();
+
+        self.lookup(line, column as u32)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<'de> Deserialize<'de> for SourceMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawSourceMap::deserialize(deserializer)?;
+
+        if raw.version != 3 {
+            return Err(D::Error::custom(SourceMapError::UnsupportedVersion(raw.version)));
+        }
+
+        let root = match raw.source_root {
+            Some(ref root) if !root.is_empty() && !root.ends_with('/') => format!("{}/", root),
+            Some(root) => root,
+            None => String::new(),
+        };
+
+        let sources = raw.sources.into_iter().map(|source| format!("{}{}", root, source.unwrap_or_default())).collect();
+
+        SourceMap::new(&raw.mappings, sources, raw.names).map_err(D::Error::custom)
+    }
+}
+
+/// Decode the base64 VLQ fields of a segment.
+fn decode_segment(segment: &str) -> Result<Vec<i64>, SourceMapError> {
+    let mut fields = Vec::with_capacity(5);
+    let mut value = 0i64;
+    let mut shift = 0;
+
+    for ch in segment.chars() {
+        let digit = match ch {
+            'A'..='Z' => ch as i64 - 'A' as i64,
+            'a'..='z' => ch as i64 - 'a' as i64 + 26,
+            '0'..='9' => ch as i64 - '0' as i64 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => return Err(SourceMapError::InvalidBase64(ch)),
+        };
+
+        if shift > 32 {
+            return Err(SourceMapError::InvalidSegment);
+        }
+
+        value += (digit & 31) << shift;
+
+        // Continuation bit
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+
+        // Sign is held by the lowest bit
+        fields.push(match value & 1 {
+            1 => -(value >> 1),
+            _ => value >> 1,
+        });
+
+        value = 0;
+        shift = 0;
+    }
+
+    match shift {
+        0 => Ok(fields),
+        _ => Err(SourceMapError::InvalidSegment),
+    }
+}
+
+#[inline]
+fn position(value: i64) -> Result<u32, SourceMapError> {
+    match value {
+        0..=0xFFFF_FFFF => Ok(value as u32),
+        _ => Err(SourceMapError::InvalidSegment),
+    }
+}
+
+#[inline]
+fn index(value: i64, len: usize) -> Result<u32, SourceMapError> {
+    match value {
+        0..=0xFFFF_FFFF if (value as usize) < len => Ok(value as u32),
+        _ => Err(SourceMapError::IndexOutOfRange),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn source_map(mappings: &str) -> SourceMap {
+        SourceMap::new(mappings, vec!["a.js".into(), "b.js".into()], vec!["foo".into()]).unwrap()
+    }
+
+    #[test]
+    fn decodes_vlq() {
+        assert_eq!(decode_segment("AAAA"), Ok(vec![0, 0, 0, 0]));
+        assert_eq!(decode_segment("CADF"), Ok(vec![1, 0, -1, -2]));
+        assert_eq!(decode_segment("gBAAkBA"), Ok(vec![16, 0, 0, 18, 0]));
+        assert_eq!(decode_segment("A*"), Err(SourceMapError::InvalidBase64('*')));
+        assert_eq!(decode_segment("g"), Err(SourceMapError::InvalidSegment));
+    }
+
+    #[test]
+    fn looks_up_positions() {
+        // `foo` of a.js at line 2, column 4, then b.js at line 0, column 0,
+        // then unmapped code
+        let map = source_map(";AAEIA,KCFJ,G");
+
+        assert_eq!(map.lookup(0, 0), None);
+        assert_eq!(map.lookup(1, 0), Some(OriginalPosition { source: "a.js", line: 2, column: 4, name: Some("foo") }));
+        assert_eq!(map.lookup(1, 4), Some(OriginalPosition { source: "a.js", line: 2, column: 4, name: Some("foo") }));
+        assert_eq!(map.lookup(1, 5), Some(OriginalPosition { source: "b.js", line: 0, column: 0, name: None }));
+        assert_eq!(map.lookup(1, 8), None);
+        assert_eq!(map.lookup(2, 0), None);
+    }
+
+    #[test]
+    fn looks_up_offsets() {
+        let map = source_map("AAAA,KAAK;AACL,EAAE");
+
+        assert_eq!(map.lookup_offset("a;\r\nb + c;", 4).map(|pos| (pos.line, pos.column)), Some((1, 0)));
+        assert_eq!(map.lookup_offset("a;\r\nb + c;", 8).map(|pos| (pos.line, pos.column)), Some((1, 2)));
+
+        // Columns are counted in UTF-16 code units, `b` is at 4
+        assert_eq!(map.lookup_offset("'ąą'b", 6).map(|pos| (pos.line, pos.column)), Some((0, 0)));
+        assert_eq!(map.lookup_offset("'ąą'bc", 7).map(|pos| (pos.line, pos.column)), Some((0, 5)));
+        assert_eq!(map.lookup_offset("a", 2), None);
+    }
+
+    #[test]
+    fn rejects_invalid_mappings() {
+        let invalid = SourceMap::new("AAA", vec!["a.js".into()], vec![]);
+
+        assert_eq!(invalid, Err(SourceMapError::InvalidSegment));
+
+        let invalid = SourceMap::new("ACAA", vec!["a.js".into()], vec![]);
+
+        assert_eq!(invalid, Err(SourceMapError::IndexOutOfRange));
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn deserializes() {
+        let map: SourceMap = ::serde_json::from_str(r#"{
+            "version": 3,
+            "sourceRoot": "src",
+            "sources": ["a.js"],
+            "names": [],
+            "mappings": "AAAA"
+        }"#).unwrap();
+
+        assert_eq!(map.lookup(0, 0).map(|pos| pos.source), Some("src/a.js"));
+
+        let invalid = ::serde_json::from_str::<SourceMap>(r#"{"version":2,"sources":[],"mappings":""}"#);
+
+        assert!(invalid.is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Builders of AST nodes for tests, available to other crates with the
+//! `testing` feature. Nodes built by `Mock` are placed at `0..0` unless
+//! made with one of the `_at` methods, which is fine for comparing trees,
+//! as `Loc` equality ignores positions, while serializer and sourcemap
+//! tests can set the real `start` and `end` of the nodes they check.
+
+use toolshed::Arena;
+use ast::{Loc, Node, NodeList, Literal, NumberLiteral, ExpressionNode, Block, BlockNode, Name};
+
+/// Arena of the nodes built, which live as long as the `Mock`.
+pub struct Mock {
+    arena: Arena
+}
+
+impl Default for Mock {
+    fn default() -> Self {
+        Mock::new()
+    }
+}
+
+impl Mock {
+    pub fn new() -> Self {
+        Mock {
+            arena: Arena::new()
+        }
+    }
+
+    pub fn ptr<'a, T, I>(&'a self, val: I) -> Node<'a, T> where
+        T: 'a + Copy,
+        I: Into<T>,
+    {
+        self.ptr_at(0, 0, val)
+    }
+
+    /// Same as `ptr`, with the node spanning `start..end`.
+    pub fn ptr_at<'a, T, I>(&'a self, start: u32, end: u32, val: I) -> Node<'a, T> where
+        T: 'a + Copy,
+        I: Into<T>,
+    {
+        Node::new(self.arena.alloc(Loc::new(start, end, val.into())))
+    }
+
+    pub fn name<'a, N>(&'a self, val: &'a str) -> N where
+        N: Name<'a> + From<Node<'a, &'a str>>,
+    {
+        self.name_at(0, 0, val)
+    }
+
+    /// Same as `name`, with the identifier spanning `start..end`.
+    pub fn name_at<'a, N>(&'a self, start: u32, end: u32, val: &'a str) -> N where
+        N: Name<'a> + From<Node<'a, &'a str>>,
+    {
+        N::from(self.ptr_at(start, end, val))
+    }
+
+    pub fn number<'a>(&'a self, number: &'static str) -> ExpressionNode<'a> {
+        self.ptr(Literal::Number(NumberLiteral::new(number)))
+    }
+
+    pub fn block<'a, I, T, L>(&'a self, list: L) -> BlockNode<'a, I> where
+        I: Copy,
+        T: Into<I> + Copy,
+        L: AsRef<[T]>
+    {
+        self.ptr(Block { body: self.list(list) })
+    }
+
+    pub fn empty_block<'a, I: Copy>(&'a self) -> BlockNode<'a, I> {
+        self.ptr(Block { body: NodeList::empty() })
+    }
+
+    pub fn list<'a, T, I, L>(&'a self, list: L) -> NodeList<'a, T> where
+        T: 'a + Copy,
+        L: AsRef<[I]>,
+        I: Into<T> + Copy,
+    {
+        NodeList::from_iter(&self.arena, list.as_ref().iter().cloned().map(|i| {
+            self.ptr(i.into())
+        }))
+    }
+
+    /// List of nodes already built, keeping their spans, such as those
+    /// made with `ptr_at`.
+    pub fn nodes<'a, T, L>(&'a self, nodes: L) -> NodeList<'a, T> where
+        T: 'a + Copy,
+        L: AsRef<[Node<'a, T>]>,
+    {
+        NodeList::from_iter(&self.arena, nodes.as_ref().iter().cloned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ast::{Expression, Statement};
+    use parser::parse;
+
+    #[test]
+    fn spans() {
+        let mock = Mock::new();
+        let foo: ExpressionNode = mock.ptr_at(0, 3, "foo");
+        let bar: ExpressionNode = mock.ptr_at(5, 8, "bar");
+        let list = mock.nodes([foo, bar]);
+
+        assert_eq!(list.iter().map(|node| (node.start, node.end)).collect::<Vec<_>>(), vec![(0, 3), (5, 8)]);
+        assert_eq!(mock.ptr::<Expression, _>("foo").start, 0);
+    }
+
+    #[test]
+    fn spans_match_the_parser() {
+        let module = parse("foo;\nbar;").unwrap();
+        let mock = Mock::new();
+        let expected = mock.nodes([
+            mock.ptr_at::<Statement, _>(0, 3, mock.ptr_at::<Expression, _>(0, 3, "foo")),
+            mock.ptr_at::<Statement, _>(5, 8, mock.ptr_at::<Expression, _>(5, 8, "bar")),
+        ]);
+
+        let spans = |list: NodeList<Statement>| list.iter().map(|node| (node.start, node.end)).collect::<Vec<_>>();
+
+        assert_eq!(module.body(), expected);
+        assert_eq!(spans(module.body()), spans(expected));
+    }
+}
@@ -0,0 +1,3 @@
+f(async (a, b) => await a + b);
+async x => x;
+async(a);
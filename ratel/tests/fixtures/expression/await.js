@@ -0,0 +1,5 @@
+async function f() {
+  await a;
+  return (await b) + c;
+}
+await;
@@ -0,0 +1,8 @@
+({ [a]: 1, [b]() {}, async [c]() {} });
+
+class A {
+    [d]() {}
+    static [e]() {}
+    get [f]() {}
+    set [f](value) {}
+}
@@ -0,0 +1,7 @@
+const point = {
+  x: 1,
+  length() {
+    return this.x;
+  },
+  [key](a) {},
+};
@@ -0,0 +1 @@
+({ a, b = 1, c: { d = 2 } } = e);
@@ -0,0 +1,4 @@
+f(...args);
+[a, ...rest];
+new F(...a, b);
+a?.(...b);
@@ -0,0 +1,6 @@
+class A extends B {
+    constructor() {
+        super();
+        super.foo(super[bar]);
+    }
+}
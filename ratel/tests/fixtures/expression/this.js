@@ -0,0 +1 @@
+this;
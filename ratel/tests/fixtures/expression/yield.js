@@ -0,0 +1,5 @@
+function* gen() {
+    yield;
+    yield a, b;
+    yield* inner();
+}
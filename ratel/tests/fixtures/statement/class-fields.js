@@ -0,0 +1,5 @@
+class Foo {
+    a = 1;
+    static b;
+    [c] = d
+}
@@ -0,0 +1,7 @@
+class Foo {
+    constructor(a) {}
+    static create() {}
+    get size() {}
+    set size(v) {}
+    static get default() {}
+}
@@ -0,0 +1,7 @@
+class Foo {
+    #a = 1;
+    static #b() {}
+    m() {
+        return this.#a + this?.#a;
+    }
+}
@@ -0,0 +1,6 @@
+class Foo {
+    static a = 1;
+    static {
+        init(this.a);
+    }
+}
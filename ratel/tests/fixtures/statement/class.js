@@ -0,0 +1,5 @@
+class Foo extends Bar {
+    bar(baz) {
+        return baz;
+    }
+}
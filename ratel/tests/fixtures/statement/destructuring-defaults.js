@@ -0,0 +1,5 @@
+const {a = 1, b: {c = 2}} = obj;
+let [x = 5, [y = 6] = [], {z = 7} = {}] = arr;
+[x = 5, {a: b.c = 1}] = arr;
+for ([x = 1] of xs) {}
+function f({a = 1, b: [c = 2]}, [d = 3]) {}
@@ -0,0 +1 @@
+function f({a, b: {c}, d = 1, ...e}, [x, , y = 2, ...z], w = 3) {}
@@ -0,0 +1,8 @@
+export var a = 1;
+export default function () {}
+export default class B {}
+export default a;
+export { a, a as b };
+export { default as c } from "d";
+export * from "e";
+export * as f from "g";
@@ -0,0 +1,3 @@
+import "a";
+import b, * as c from "d";
+import e, { f, default as g } from "h";
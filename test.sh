@@ -6,6 +6,24 @@ echo ""
 
 cargo test || exit
 
+echo ""
+echo ""
+echo "TESTING: without default features"
+echo "#################################"
+echo ""
+
+cargo build -p ratel --no-default-features || exit
+cargo test -p ratel --no-default-features || exit
+cargo test -p ratel-transformer --no-default-features || exit
+
+echo ""
+echo ""
+echo "TESTING: with AST invariant checks"
+echo "##################################"
+echo ""
+
+cargo test -p ratel -p ratel-transformer --features debug-invariants || exit
+
 echo ""
 echo ""
 echo "TESTING: ffi"